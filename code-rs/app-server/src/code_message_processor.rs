@@ -1,14 +1,36 @@
 use std::collections::HashMap;
+use std::path::Path;
+use std::ops::Range;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 
+use code_core::ARCHIVED_SESSIONS_SUBDIR;
 use code_core::AuthManager;
 use code_core::CodexConversation;
 use code_core::ConversationManager;
 use code_core::NewConversation;
+use code_core::SessionCatalog;
+use code_core::SessionQuery;
+use code_core::auth::login_with_api_key;
 use code_core::config::Config;
 use code_core::config::ConfigOverrides;
+use code_core::config::ConfigToml;
+use code_core::config::load_config_as_toml_with_cli_overrides;
+use code_core::config_edit::CONFIG_KEY_EFFORT;
+use code_core::config_edit::CONFIG_KEY_MODEL;
+use code_core::config_edit::persist_overrides_and_clear_if_none;
+use code_core::config_types::ReasoningEffort as CoreReasoningEffort;
+use code_core::config_types::ReasoningSummary as CoreReasoningSummary;
+use code_core::config_types::TextVerbosity;
+use code_core::default_client::get_code_user_agent;
+use code_core::error::CodexErr;
+use code_core::error::SandboxErr;
+use code_core::exec::ExecParams;
+use code_core::exec::SandboxType;
+use code_core::exec::process_exec_tool_call;
+use code_core::exec_env::create_env;
+use code_core::get_platform_sandbox;
 use code_core::git_info::git_diff_to_remote;
 use code_core::protocol::ApplyPatchApprovalRequestEvent;
 use code_core::protocol::Event;
@@ -16,7 +38,14 @@ use code_core::protocol::EventMsg;
 use code_core::protocol::ExecApprovalRequestEvent;
 use code_protocol::mcp_protocol::FuzzyFileSearchParams;
 use code_protocol::mcp_protocol::FuzzyFileSearchResponse;
+use code_protocol::config_types::ReasoningEffort;
+use code_protocol::config_types::ReasoningSummary;
+use code_protocol::config_types::Verbosity;
+use code_protocol::protocol::EventMsg as WireEventMsg;
 use code_protocol::protocol::ReviewDecision;
+use code_protocol::protocol::RolloutItem;
+use code_protocol::protocol::RolloutLine;
+use code_protocol::protocol::SessionSource;
 use mcp_types::JSONRPCErrorError;
 use mcp_types::RequestId;
 use tokio::sync::Mutex;
@@ -39,38 +68,69 @@ use code_protocol::mcp_protocol::AddConversationListenerParams;
 use code_protocol::mcp_protocol::AddConversationSubscriptionResponse;
 use code_protocol::mcp_protocol::ApplyPatchApprovalParams;
 use code_protocol::mcp_protocol::ApplyPatchApprovalResponse;
+use code_protocol::mcp_protocol::ArchiveConversationParams;
+use code_protocol::mcp_protocol::ArchiveConversationResponse;
 use code_protocol::mcp_protocol::ClientRequest;
 use code_protocol::mcp_protocol::ConversationId;
+use code_protocol::mcp_protocol::ConversationSummary;
 use code_protocol::mcp_protocol::EXEC_COMMAND_APPROVAL_METHOD;
+use code_protocol::mcp_protocol::ExecArbitraryCommandResponse;
 use code_protocol::mcp_protocol::ExecCommandApprovalParams;
 use code_protocol::mcp_protocol::ExecCommandApprovalResponse;
+use code_protocol::mcp_protocol::ExecOneOffCommandParams;
+use code_protocol::mcp_protocol::GetUserAgentResponse;
+use code_protocol::mcp_protocol::GetUserSavedConfigResponse;
 use code_protocol::mcp_protocol::InputItem as WireInputItem;
 use code_protocol::mcp_protocol::InterruptConversationParams;
 use code_protocol::mcp_protocol::InterruptConversationResponse;
+use code_protocol::mcp_protocol::ListConversationsParams;
+use code_protocol::mcp_protocol::ListConversationsResponse;
+use code_protocol::mcp_protocol::LoginApiKeyParams;
+use code_protocol::mcp_protocol::LoginApiKeyResponse;
 // Unused login-related and diff param imports removed
 use code_protocol::mcp_protocol::GitDiffToRemoteResponse;
 use code_protocol::mcp_protocol::NewConversationParams;
 use code_protocol::mcp_protocol::NewConversationResponse;
+use code_protocol::mcp_protocol::Profile;
 use code_protocol::mcp_protocol::RemoveConversationListenerParams;
 use code_protocol::mcp_protocol::RemoveConversationSubscriptionResponse;
+use code_protocol::mcp_protocol::ResumeConversationParams;
+use code_protocol::mcp_protocol::ResumeConversationResponse;
+use code_protocol::mcp_protocol::SandboxSettings;
 use code_protocol::mcp_protocol::SendUserMessageParams;
 use code_protocol::mcp_protocol::SendUserMessageResponse;
 use code_protocol::mcp_protocol::SendUserTurnParams;
 use code_protocol::mcp_protocol::SendUserTurnResponse;
+use code_protocol::mcp_protocol::SetDefaultModelParams;
+use code_protocol::mcp_protocol::SetDefaultModelResponse;
+use code_protocol::mcp_protocol::Tools;
+use code_protocol::mcp_protocol::UserInfoResponse;
+use code_protocol::mcp_protocol::UserSavedConfig;
 
 // Removed deprecated ChatGPT login support scaffolding
 
+const DEFAULT_CONVERSATION_PAGE_SIZE: usize = 25;
+const MAX_CONVERSATION_PAGE_SIZE: usize = 200;
+const DEFAULT_ONE_OFF_EXEC_TIMEOUT_MS: u64 = 10_000;
+
+/// Sessions offered by `listConversations`: interactive ones plus those
+/// started through this server.
+const LISTED_SESSION_SOURCES: &[SessionSource] = &[
+    SessionSource::Cli,
+    SessionSource::VSCode,
+    SessionSource::Mcp,
+];
+
 /// Handles JSON-RPC messages for Codex conversations.
 pub struct CodexMessageProcessor {
-    _auth_manager: Arc<AuthManager>,
+    auth_manager: Arc<AuthManager>,
     conversation_manager: Arc<ConversationManager>,
     outgoing: Arc<OutgoingMessageSender>,
     code_linux_sandbox_exe: Option<PathBuf>,
-    _config: Arc<Config>,
+    config: Arc<Config>,
     conversation_listeners: HashMap<Uuid, oneshot::Sender<()>>,
     // Queue of pending interrupt requests per conversation. We reply when TurnAborted arrives.
     pending_interrupts: Arc<Mutex<HashMap<Uuid, Vec<RequestId>>>>,
-    #[allow(dead_code)]
     pending_fuzzy_searches: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
 }
 
@@ -83,11 +143,11 @@ impl CodexMessageProcessor {
         config: Arc<Config>,
     ) -> Self {
         Self {
-            _auth_manager: auth_manager,
+            auth_manager,
             conversation_manager,
             outgoing,
             code_linux_sandbox_exe,
-            _config: config,
+            config,
             conversation_listeners: HashMap::new(),
             pending_interrupts: Arc::new(Mutex::new(HashMap::new())),
             pending_fuzzy_searches: Arc::new(Mutex::new(HashMap::new())),
@@ -157,12 +217,43 @@ impl CodexMessageProcessor {
             ClientRequest::GitDiffToRemote { request_id, params } => {
                 self.git_diff_to_origin(request_id, params.cwd).await;
             }
-            _ => { /* ignore unsupported methods */ }
+            ClientRequest::ListConversations { request_id, params } => {
+                self.list_conversations(request_id, params).await;
+            }
+            ClientRequest::ResumeConversation { request_id, params } => {
+                self.resume_conversation(request_id, params).await;
+            }
+            ClientRequest::ArchiveConversation { request_id, params } => {
+                self.archive_conversation(request_id, params).await;
+            }
+            ClientRequest::LoginApiKey { request_id, params } => {
+                self.login_api_key(request_id, params).await;
+            }
+            ClientRequest::GetUserSavedConfig { request_id, .. } => {
+                self.get_user_saved_config(request_id).await;
+            }
+            ClientRequest::SetDefaultModel { request_id, params } => {
+                self.set_default_model(request_id, params).await;
+            }
+            ClientRequest::GetUserAgent { request_id, .. } => {
+                let user_agent =
+                    get_code_user_agent(Some(&self.config.responses_originator_header));
+                self.outgoing
+                    .send_response(request_id, GetUserAgentResponse { user_agent })
+                    .await;
+            }
+            ClientRequest::UserInfo { request_id, .. } => {
+                self.get_user_info(request_id).await;
+            }
+            ClientRequest::FuzzyFileSearch { request_id, params } => {
+                self.fuzzy_file_search(request_id, params).await;
+            }
+            ClientRequest::ExecOneOffCommand { request_id, params } => {
+                self.exec_one_off_command(request_id, params).await;
+            }
         }
     }
 
-    // Upstream added utility endpoints (user info, set default model, one-off exec).
-    // Our fork does not expose them via this server; omit to preserve behavior.
     async fn process_new_conversation(&self, request_id: RequestId, params: NewConversationParams) {
         let config = match derive_config_from_params(params, self.code_linux_sandbox_exe.clone()) {
             Ok(config) => config,
@@ -189,7 +280,7 @@ impl CodexMessageProcessor {
                     model: session_configured.model,
                     reasoning_effort: None,
                     // We do not expose the underlying rollout file path in this fork; provide the sessions root.
-                    rollout_path: self._config.code_home.join("sessions"),
+                    rollout_path: self.config.code_home.join("sessions"),
                 };
                 self.outgoing.send_response(request_id, response).await;
             }
@@ -442,7 +533,390 @@ impl CodexMessageProcessor {
         }
     }
 
-    #[allow(dead_code)]
+    async fn list_conversations(&self, request_id: RequestId, params: ListConversationsParams) {
+        let ListConversationsParams { page_size, cursor } = params;
+        let page_size = page_size
+            .unwrap_or(DEFAULT_CONVERSATION_PAGE_SIZE)
+            .clamp(1, MAX_CONVERSATION_PAGE_SIZE);
+
+        let catalog = SessionCatalog::new(self.config.code_home.clone());
+        let query = SessionQuery {
+            sources: LISTED_SESSION_SOURCES.to_vec(),
+            min_user_messages: 1,
+            ..SessionQuery::default()
+        };
+        let entries = match catalog.query(&query).await {
+            Ok(entries) => entries,
+            Err(err) => {
+                self.send_internal_error(
+                    request_id,
+                    format!("failed to list conversations: {err:#}"),
+                )
+                .await;
+                return;
+            }
+        };
+
+        let ids: Vec<String> = entries
+            .iter()
+            .map(|entry| entry.session_id.to_string())
+            .collect();
+        let Some((range, next_cursor)) = page_bounds(&ids, cursor.as_deref(), page_size) else {
+            self.send_invalid_request_error(
+                request_id,
+                format!("invalid cursor: {}", cursor.unwrap_or_default()),
+            )
+            .await;
+            return;
+        };
+        let items = entries[range]
+            .iter()
+            .map(|entry| ConversationSummary {
+                conversation_id: ConversationId::from(entry.session_id),
+                path: catalog.entry_rollout_path(entry),
+                preview: entry.last_user_snippet.clone().unwrap_or_default(),
+                timestamp: Some(entry.created_at.clone()),
+            })
+            .collect();
+
+        self.outgoing
+            .send_response(request_id, ListConversationsResponse { items, next_cursor })
+            .await;
+    }
+
+    async fn resume_conversation(&self, request_id: RequestId, params: ResumeConversationParams) {
+        let ResumeConversationParams { path, overrides } = params;
+        if !path.is_absolute() || !path.is_file() {
+            self.send_invalid_request_error(
+                request_id,
+                format!("rollout file not found: {}", path.display()),
+            )
+            .await;
+            return;
+        }
+
+        let config = match derive_config_from_params(
+            overrides.unwrap_or_default(),
+            self.code_linux_sandbox_exe.clone(),
+        ) {
+            Ok(config) => config,
+            Err(err) => {
+                self.send_invalid_request_error(
+                    request_id,
+                    format!("error deriving config: {err}"),
+                )
+                .await;
+                return;
+            }
+        };
+
+        // Read the history before resuming, which appends to the same file.
+        let initial_messages = match tokio::fs::read_to_string(&path).await {
+            Ok(text) => Some(initial_messages_from_rollout(&text)),
+            Err(err) => {
+                error!("failed to read rollout {}: {err}", path.display());
+                None
+            }
+        };
+
+        match self
+            .conversation_manager
+            .resume_conversation_from_rollout(config, path, self.auth_manager.clone())
+            .await
+        {
+            Ok(NewConversation {
+                conversation_id,
+                session_configured,
+                ..
+            }) => {
+                let response = ResumeConversationResponse {
+                    conversation_id,
+                    model: session_configured.model,
+                    initial_messages,
+                };
+                self.outgoing.send_response(request_id, response).await;
+            }
+            Err(err) => {
+                self.send_internal_error(
+                    request_id,
+                    format!("error resuming conversation: {err}"),
+                )
+                .await;
+            }
+        }
+    }
+
+    /// Moves the rollout into `archived_sessions/`, which drops it from the
+    /// catalog on its next reconcile. A live conversation is shut down first.
+    async fn archive_conversation(&self, request_id: RequestId, params: ArchiveConversationParams) {
+        let ArchiveConversationParams {
+            conversation_id,
+            rollout_path,
+        } = params;
+
+        let catalog = SessionCatalog::new(self.config.code_home.clone());
+        let entry = match catalog.find_by_id(&conversation_id.to_string()).await {
+            Ok(Some(entry)) => entry,
+            Ok(None) => {
+                self.send_invalid_request_error(
+                    request_id,
+                    format!("conversation not found: {conversation_id}"),
+                )
+                .await;
+                return;
+            }
+            Err(err) => {
+                self.send_internal_error(
+                    request_id,
+                    format!("failed to load sessions: {err:#}"),
+                )
+                .await;
+                return;
+            }
+        };
+
+        let recorded_path = catalog.entry_rollout_path(&entry);
+        if !same_path(&recorded_path, &rollout_path) {
+            self.send_invalid_request_error(
+                request_id,
+                format!(
+                    "rollout {} does not belong to conversation {conversation_id}",
+                    rollout_path.display()
+                ),
+            )
+            .await;
+            return;
+        }
+        let Some(file_name) = recorded_path.file_name() else {
+            self.send_invalid_request_error(
+                request_id,
+                format!("invalid rollout path: {}", recorded_path.display()),
+            )
+            .await;
+            return;
+        };
+
+        if let Some(conversation) = self
+            .conversation_manager
+            .remove_conversation(&conversation_id)
+            .await
+        {
+            let _ = conversation.submit(Op::Shutdown).await;
+        }
+
+        let archive_dir = self.config.code_home.join(ARCHIVED_SESSIONS_SUBDIR);
+        let archived = std::fs::create_dir_all(&archive_dir)
+            .and_then(|()| std::fs::rename(&recorded_path, archive_dir.join(file_name)));
+        match archived {
+            Ok(()) => {
+                self.outgoing
+                    .send_response(request_id, ArchiveConversationResponse {})
+                    .await;
+            }
+            Err(err) => {
+                self.send_internal_error(
+                    request_id,
+                    format!("failed to archive conversation {conversation_id}: {err}"),
+                )
+                .await;
+            }
+        }
+    }
+
+    async fn login_api_key(&self, request_id: RequestId, params: LoginApiKeyParams) {
+        let api_key = params.api_key.trim();
+        if api_key.is_empty() {
+            self.send_invalid_request_error(request_id, "api key must not be empty".to_string())
+                .await;
+            return;
+        }
+
+        match login_with_api_key(&self.config.code_home, api_key) {
+            Ok(()) => {
+                self.auth_manager.reload();
+                self.outgoing
+                    .send_response(request_id, LoginApiKeyResponse {})
+                    .await;
+            }
+            Err(err) => {
+                self.send_internal_error(
+                    request_id,
+                    format!("failed to save api key: {err}"),
+                )
+                .await;
+            }
+        }
+    }
+
+    async fn get_user_saved_config(&self, request_id: RequestId) {
+        match load_config_as_toml_with_cli_overrides(&self.config.code_home, Vec::new()) {
+            Ok(config_toml) => {
+                let response = GetUserSavedConfigResponse {
+                    config: user_saved_config_from_toml(config_toml),
+                };
+                self.outgoing.send_response(request_id, response).await;
+            }
+            Err(err) => {
+                self.send_internal_error(
+                    request_id,
+                    format!("failed to load config.toml: {err}"),
+                )
+                .await;
+            }
+        }
+    }
+
+    async fn set_default_model(&self, request_id: RequestId, params: SetDefaultModelParams) {
+        let SetDefaultModelParams {
+            model,
+            reasoning_effort,
+        } = params;
+        let model = model.map(|model| model.trim().to_string());
+        if model.as_deref().is_some_and(str::is_empty) {
+            self.send_invalid_request_error(request_id, "model must not be empty".to_string())
+                .await;
+            return;
+        }
+        let effort = reasoning_effort.map(|effort| effort.to_string());
+
+        let overrides: [(&[&str], Option<&str>); 2] = [
+            (&[CONFIG_KEY_MODEL], model.as_deref()),
+            (&[CONFIG_KEY_EFFORT], effort.as_deref()),
+        ];
+        match persist_overrides_and_clear_if_none(
+            &self.config.code_home,
+            self.config.active_profile.as_deref(),
+            &overrides,
+        )
+        .await
+        {
+            Ok(()) => {
+                self.outgoing
+                    .send_response(request_id, SetDefaultModelResponse {})
+                    .await;
+            }
+            Err(err) => {
+                self.send_internal_error(
+                    request_id,
+                    format!("failed to save default model: {err}"),
+                )
+                .await;
+            }
+        }
+    }
+
+    async fn get_user_info(&self, request_id: RequestId) {
+        let alleged_user_email = match self.auth_manager.auth() {
+            Some(auth) => auth
+                .get_token_data()
+                .await
+                .ok()
+                .and_then(|tokens| tokens.id_token.email),
+            None => None,
+        };
+        self.outgoing
+            .send_response(request_id, UserInfoResponse { alleged_user_email })
+            .await;
+    }
+
+    async fn exec_one_off_command(&self, request_id: RequestId, params: ExecOneOffCommandParams) {
+        let ExecOneOffCommandParams {
+            command,
+            timeout_ms,
+            cwd,
+            sandbox_policy,
+        } = params;
+        if command.is_empty() {
+            self.send_invalid_request_error(request_id, "command must not be empty".to_string())
+                .await;
+            return;
+        }
+
+        let sandbox_policy = match sandbox_policy.map(map_sandbox_policy_from_wire) {
+            Some(Ok(policy)) => policy,
+            Some(Err(err)) => {
+                self.send_invalid_request_error(
+                    request_id,
+                    format!("invalid sandbox policy: {err}"),
+                )
+                .await;
+                return;
+            }
+            None => self.config.sandbox_policy.clone(),
+        };
+        let sandbox_type = match sandbox_policy {
+            core_protocol::SandboxPolicy::DangerFullAccess => SandboxType::None,
+            _ => get_platform_sandbox().unwrap_or(SandboxType::None),
+        };
+        let cwd = match cwd {
+            Some(cwd) => self.config.cwd.join(cwd),
+            None => self.config.cwd.clone(),
+        };
+        let params = ExecParams {
+            command,
+            cwd: cwd.clone(),
+            timeout_ms: Some(timeout_ms.unwrap_or(DEFAULT_ONE_OFF_EXEC_TIMEOUT_MS)),
+            env: create_env(&self.config.shell_environment_policy),
+            with_escalated_permissions: None,
+            justification: None,
+            exec_limits: self.config.exec_limits.clone(),
+        };
+
+        // Run off the request loop so a slow command does not block other requests.
+        let code_linux_sandbox_exe = self.code_linux_sandbox_exe.clone();
+        let outgoing = self.outgoing.clone();
+        tokio::spawn(async move {
+            let result = process_exec_tool_call(
+                params,
+                sandbox_type,
+                &sandbox_policy,
+                &cwd,
+                &code_linux_sandbox_exe,
+                None,
+            )
+            .await;
+            let output = match result {
+                Ok(output) => output,
+                Err(CodexErr::Sandbox(
+                    SandboxErr::Timeout { output } | SandboxErr::Denied { output },
+                )) => *output,
+                Err(err) => {
+                    let error = JSONRPCErrorError {
+                        code: INTERNAL_ERROR_CODE,
+                        message: format!("exec failed: {err}"),
+                        data: None,
+                    };
+                    outgoing.send_error(request_id, error).await;
+                    return;
+                }
+            };
+            let response = ExecArbitraryCommandResponse {
+                exit_code: output.exit_code,
+                stdout: output.stdout.text,
+                stderr: output.stderr.text,
+            };
+            outgoing.send_response(request_id, response).await;
+        });
+    }
+
+    async fn send_invalid_request_error(&self, request_id: RequestId, message: String) {
+        let error = JSONRPCErrorError {
+            code: INVALID_REQUEST_ERROR_CODE,
+            message,
+            data: None,
+        };
+        self.outgoing.send_error(request_id, error).await;
+    }
+
+    async fn send_internal_error(&self, request_id: RequestId, message: String) {
+        let error = JSONRPCErrorError {
+            code: INTERNAL_ERROR_CODE,
+            message,
+            data: None,
+        };
+        self.outgoing.send_error(request_id, error).await;
+    }
+
     async fn fuzzy_file_search(&mut self, request_id: RequestId, params: FuzzyFileSearchParams) {
         let FuzzyFileSearchParams {
             query,
@@ -604,6 +1078,7 @@ async fn apply_bespoke_event_handling(
             command,
             cwd,
             reason,
+            grant_root,
         }) => {
             let params = ExecCommandApprovalParams {
                 conversation_id,
@@ -611,6 +1086,7 @@ async fn apply_bespoke_event_handling(
                 command,
                 cwd,
                 reason,
+                grant_root,
             };
             let value = serde_json::to_value(&params).unwrap_or_default();
             let rx = outgoing
@@ -665,6 +1141,7 @@ fn derive_config_from_params(
         experimental_client_tools: None,
         compact_prompt_override: None,
         compact_prompt_override_file: None,
+        model_cassette: None,
     };
 
     let cli_overrides = cli_overrides
@@ -673,7 +1150,109 @@ fn derive_config_from_params(
         .map(|(k, v)| (k, json_to_toml(v)))
         .collect();
 
-    Config::load_with_cli_overrides(cli_overrides, overrides)
+    let mut config = Config::load_with_cli_overrides(cli_overrides, overrides)?;
+    // App-server clients have no request for MCP elicitations, so answer them
+    // without asking.
+    config.mcp_elicitation = config.mcp_elicitation.non_interactive();
+    Ok(config)
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Returns the slice of `ids` on the page after `cursor` and the cursor for
+/// the page after that. The cursor is the id of the last conversation on the
+/// previous page; `None` means the cursor matches no conversation.
+fn page_bounds(
+    ids: &[String],
+    cursor: Option<&str>,
+    page_size: usize,
+) -> Option<(Range<usize>, Option<String>)> {
+    let start = match cursor {
+        None => 0,
+        Some(cursor) => ids.iter().position(|id| id == cursor)? + 1,
+    };
+    let end = start.saturating_add(page_size).min(ids.len());
+    let next_cursor = if end < ids.len() && end > start {
+        Some(ids[end - 1].clone())
+    } else {
+        None
+    };
+    Some((start..end, next_cursor))
+}
+
+/// Events recorded in a rollout, in order, for clients to render the history
+/// of a resumed conversation. Lines that fail to parse are skipped.
+fn initial_messages_from_rollout(text: &str) -> Vec<WireEventMsg> {
+    text.lines()
+        .filter_map(|line| serde_json::from_str::<RolloutLine>(line).ok())
+        .filter_map(|line| match line.item {
+            RolloutItem::Event(event) => Some(event.msg),
+            _ => None,
+        })
+        .collect()
+}
+
+fn map_sandbox_policy_from_wire(
+    policy: code_protocol::protocol::SandboxPolicy,
+) -> serde_json::Result<core_protocol::SandboxPolicy> {
+    serde_json::to_value(policy).and_then(serde_json::from_value)
+}
+
+fn user_saved_config_from_toml(config: ConfigToml) -> UserSavedConfig {
+    let sandbox_settings = config
+        .sandbox_workspace_write
+        .map(|settings| SandboxSettings {
+            writable_roots: settings.writable_roots,
+            network_access: Some(settings.network_access),
+            exclude_tmpdir_env_var: Some(settings.exclude_tmpdir_env_var),
+            exclude_slash_tmp: Some(settings.exclude_slash_tmp),
+        });
+    let tools = config.tools.map(|tools| Tools {
+        web_search: tools.web_search,
+        view_image: tools.view_image,
+    });
+    let profiles = config
+        .profiles
+        .into_iter()
+        .map(|(name, profile)| {
+            let profile = Profile {
+                model: profile.model,
+                model_provider: profile.model_provider,
+                approval_policy: profile.approval_policy.map(map_ask_for_approval_to_wire),
+                model_reasoning_effort: profile
+                    .model_reasoning_effort
+                    .and_then(map_reasoning_effort_to_wire),
+                model_reasoning_summary: profile
+                    .model_reasoning_summary
+                    .map(map_reasoning_summary_to_wire),
+                model_verbosity: profile.model_text_verbosity.map(map_verbosity_to_wire),
+                chatgpt_base_url: profile.chatgpt_base_url,
+            };
+            (name, profile)
+        })
+        .collect();
+
+    UserSavedConfig {
+        approval_policy: config.approval_policy.map(map_ask_for_approval_to_wire),
+        sandbox_mode: config.sandbox_mode,
+        sandbox_settings,
+        model: config.model,
+        model_reasoning_effort: config
+            .model_reasoning_effort
+            .and_then(map_reasoning_effort_to_wire),
+        model_reasoning_summary: config
+            .model_reasoning_summary
+            .map(map_reasoning_summary_to_wire),
+        model_verbosity: config.model_text_verbosity.map(map_verbosity_to_wire),
+        tools,
+        profile: config.profile,
+        profiles,
+    }
 }
 
 async fn on_patch_approval_response(
@@ -772,4 +1351,192 @@ fn map_ask_for_approval_from_wire(a: code_protocol::protocol::AskForApproval) ->
     }
 }
 
-// Unused legacy mappers removed to avoid warnings.
+fn map_ask_for_approval_to_wire(a: core_protocol::AskForApproval) -> code_protocol::protocol::AskForApproval {
+    match a {
+        core_protocol::AskForApproval::UnlessTrusted => code_protocol::protocol::AskForApproval::UnlessTrusted,
+        core_protocol::AskForApproval::OnFailure => code_protocol::protocol::AskForApproval::OnFailure,
+        core_protocol::AskForApproval::OnRequest => code_protocol::protocol::AskForApproval::OnRequest,
+        core_protocol::AskForApproval::Never => code_protocol::protocol::AskForApproval::Never,
+    }
+}
+
+fn map_reasoning_effort_to_wire(effort: CoreReasoningEffort) -> Option<ReasoningEffort> {
+    match effort {
+        CoreReasoningEffort::Minimal => Some(ReasoningEffort::Minimal),
+        CoreReasoningEffort::Low => Some(ReasoningEffort::Low),
+        CoreReasoningEffort::Medium => Some(ReasoningEffort::Medium),
+        CoreReasoningEffort::High => Some(ReasoningEffort::High),
+        CoreReasoningEffort::XHigh => Some(ReasoningEffort::XHigh),
+        CoreReasoningEffort::None => None,
+    }
+}
+
+fn map_reasoning_summary_to_wire(summary: CoreReasoningSummary) -> ReasoningSummary {
+    match summary {
+        CoreReasoningSummary::Auto => ReasoningSummary::Auto,
+        CoreReasoningSummary::Concise => ReasoningSummary::Concise,
+        CoreReasoningSummary::Detailed => ReasoningSummary::Detailed,
+        CoreReasoningSummary::None => ReasoningSummary::None,
+    }
+}
+
+fn map_verbosity_to_wire(verbosity: TextVerbosity) -> Verbosity {
+    match verbosity {
+        TextVerbosity::Low => Verbosity::Low,
+        TextVerbosity::Medium => Verbosity::Medium,
+        TextVerbosity::High => Verbosity::High,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use code_core::config_profile::ConfigProfile;
+    use code_core::config_types::SandboxWorkspaceWrite;
+
+    fn ids(count: usize) -> Vec<String> {
+        (0..count).map(|i| format!("id-{i}")).collect()
+    }
+
+    #[test]
+    fn page_bounds_walks_pages_with_cursors() {
+        let ids = ids(5);
+
+        let (range, next) = page_bounds(&ids, None, 2).unwrap_or_else(|| panic!("first page"));
+        assert_eq!(range, 0..2);
+        assert_eq!(next.as_deref(), Some("id-1"));
+
+        let (range, next) =
+            page_bounds(&ids, next.as_deref(), 2).unwrap_or_else(|| panic!("second page"));
+        assert_eq!(range, 2..4);
+        assert_eq!(next.as_deref(), Some("id-3"));
+
+        let (range, next) =
+            page_bounds(&ids, next.as_deref(), 2).unwrap_or_else(|| panic!("last page"));
+        assert_eq!(range, 4..5);
+        assert_eq!(next, None);
+    }
+
+    #[test]
+    fn page_bounds_ends_without_cursor_on_exact_fit() {
+        let ids = ids(4);
+        let (range, next) = page_bounds(&ids, Some("id-1"), 2).unwrap_or_else(|| panic!("page"));
+        assert_eq!(range, 2..4);
+        assert_eq!(next, None);
+
+        let (range, next) = page_bounds(&ids, Some("id-3"), 2).unwrap_or_else(|| panic!("page"));
+        assert_eq!(range, 4..4);
+        assert_eq!(next, None);
+    }
+
+    #[test]
+    fn page_bounds_rejects_unknown_cursor() {
+        assert!(page_bounds(&ids(3), Some("missing"), 2).is_none());
+    }
+
+    #[test]
+    fn initial_messages_keep_recorded_events_in_order() {
+        let rollout = [
+            r#"{"timestamp":"t1","type":"event","payload":{"id":"1","event_seq":0,"msg":{"type":"user_message","message":"hello"}}}"#,
+            r#"{"timestamp":"t2","type":"response_item","payload":{"type":"message","role":"assistant","content":[{"type":"output_text","text":"hi"}]}}"#,
+            "not json",
+            r#"{"timestamp":"t3","type":"event","payload":{"id":"1","event_seq":1,"msg":{"type":"agent_message","message":"hi there"}}}"#,
+        ]
+        .join("\n");
+
+        let messages = initial_messages_from_rollout(&rollout);
+        assert_eq!(messages.len(), 2);
+        assert!(
+            matches!(&messages[0], WireEventMsg::UserMessage(event) if event.message == "hello")
+        );
+        assert!(
+            matches!(&messages[1], WireEventMsg::AgentMessage(event) if event.message == "hi there")
+        );
+    }
+
+    #[test]
+    fn sandbox_policy_from_wire_keeps_workspace_write_settings() {
+        let wire = code_protocol::protocol::SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![PathBuf::from("/work/extra")],
+            network_access: true,
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: false,
+            allow_git_writes: true,
+            read_denied_paths: Some(vec![PathBuf::from("/work/.env")]),
+        };
+
+        let policy = map_sandbox_policy_from_wire(wire).unwrap_or_else(|e| panic!("{e}"));
+        let core_protocol::SandboxPolicy::WorkspaceWrite {
+            writable_roots,
+            network_access,
+            exclude_tmpdir_env_var,
+            exclude_slash_tmp,
+            read_denied_paths,
+            ..
+        } = policy
+        else {
+            panic!("expected workspace-write, got {policy:?}");
+        };
+        assert_eq!(writable_roots, vec![PathBuf::from("/work/extra")]);
+        assert!(network_access);
+        assert!(exclude_tmpdir_env_var);
+        assert!(!exclude_slash_tmp);
+        assert_eq!(read_denied_paths, vec![PathBuf::from("/work/.env")]);
+
+        let read_only =
+            map_sandbox_policy_from_wire(code_protocol::protocol::SandboxPolicy::ReadOnly)
+                .unwrap_or_else(|e| panic!("{e}"));
+        assert_eq!(read_only, core_protocol::SandboxPolicy::ReadOnly);
+    }
+
+    #[test]
+    fn user_saved_config_maps_settings_and_profiles() {
+        let config = ConfigToml {
+            model: Some("gpt-5".to_string()),
+            approval_policy: Some(core_protocol::AskForApproval::OnRequest),
+            model_reasoning_effort: Some(CoreReasoningEffort::None),
+            sandbox_workspace_write: Some(SandboxWorkspaceWrite {
+                writable_roots: vec![PathBuf::from("/work")],
+                network_access: true,
+                ..SandboxWorkspaceWrite::default()
+            }),
+            profile: Some("fast".to_string()),
+            profiles: HashMap::from([(
+                "fast".to_string(),
+                ConfigProfile {
+                    model: Some("gpt-5-mini".to_string()),
+                    model_reasoning_effort: Some(CoreReasoningEffort::Low),
+                    ..ConfigProfile::default()
+                },
+            )]),
+            ..ConfigToml::default()
+        };
+
+        let saved = user_saved_config_from_toml(config);
+        assert_eq!(saved.model.as_deref(), Some("gpt-5"));
+        assert_eq!(
+            saved.approval_policy,
+            Some(code_protocol::protocol::AskForApproval::OnRequest)
+        );
+        // `none` has no wire equivalent and is left unset.
+        assert_eq!(saved.model_reasoning_effort, None);
+        assert_eq!(
+            saved.sandbox_settings,
+            Some(SandboxSettings {
+                writable_roots: vec![PathBuf::from("/work")],
+                network_access: Some(true),
+                exclude_tmpdir_env_var: Some(false),
+                exclude_slash_tmp: Some(false),
+            })
+        );
+        assert_eq!(saved.tools, None);
+        assert_eq!(saved.profile.as_deref(), Some("fast"));
+        let fast = saved
+            .profiles
+            .get("fast")
+            .unwrap_or_else(|| panic!("missing profile"));
+        assert_eq!(fast.model.as_deref(), Some("gpt-5-mini"));
+        assert_eq!(fast.model_reasoning_effort, Some(ReasoningEffort::Low));
+        assert_eq!(fast.approval_policy, None);
+    }
+}
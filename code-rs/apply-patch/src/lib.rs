@@ -1,6 +1,7 @@
 mod parser;
 mod seek_sequence;
 mod standalone_executable;
+mod syntax_anchor;
 
 use std::collections::HashMap;
 use std::path::Path;
@@ -25,6 +26,7 @@ use tree_sitter::StreamingIterator;
 use tree_sitter_bash::LANGUAGE as BASH;
 
 pub use standalone_executable::main;
+pub use syntax_anchor::FuzzyMatch;
 
 // Back-compat shim for codex-core callers
 // The core crate expects a simple async FileSystem abstraction and a default
@@ -218,6 +220,9 @@ pub enum ApplyPatchFileChange {
         move_path: Option<PathBuf>,
         /// new_content that will result after the unified_diff is applied.
         new_content: String,
+        /// Chunks that were located by anchoring on a syntax node because
+        /// their context did not match the file.
+        fuzzy_matches: Vec<FuzzyMatch>,
     },
 }
 
@@ -355,6 +360,7 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
                         let ApplyPatchFileUpdate {
                             unified_diff,
                             content: contents,
+                            fuzzy_matches,
                         } = match unified_diff_from_chunks(&path, &chunks) {
                             Ok(diff) => diff,
                             Err(e) => {
@@ -367,6 +373,7 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
                                 unified_diff,
                                 move_path: move_path.map(|p| effective_cwd.join(p)),
                                 new_content: contents,
+                                fuzzy_matches,
                             },
                         );
                     }
@@ -638,6 +645,8 @@ pub struct AffectedPaths {
     pub added: Vec<PathBuf>,
     pub modified: Vec<PathBuf>,
     pub deleted: Vec<PathBuf>,
+    /// Chunks that only applied after anchoring on a syntax node.
+    pub fuzzy_matches: Vec<FuzzyMatch>,
 }
 
 /// Apply the hunks to the filesystem, returning which files were added, modified, or deleted.
@@ -650,6 +659,7 @@ fn apply_hunks_to_files(hunks: &[Hunk]) -> anyhow::Result<AffectedPaths> {
     let mut added: Vec<PathBuf> = Vec::new();
     let mut modified: Vec<PathBuf> = Vec::new();
     let mut deleted: Vec<PathBuf> = Vec::new();
+    let mut fuzzy_matches: Vec<FuzzyMatch> = Vec::new();
     for hunk in hunks {
         match hunk {
             Hunk::AddFile { path, contents } => {
@@ -674,8 +684,12 @@ fn apply_hunks_to_files(hunks: &[Hunk]) -> anyhow::Result<AffectedPaths> {
                 move_path,
                 chunks,
             } => {
-                let AppliedPatch { new_contents, .. } =
-                    derive_new_contents_from_chunks(path, chunks)?;
+                let AppliedPatch {
+                    new_contents,
+                    fuzzy_matches: chunk_fuzzy_matches,
+                    ..
+                } = derive_new_contents_from_chunks(path, chunks)?;
+                fuzzy_matches.extend(chunk_fuzzy_matches);
                 if let Some(dest) = move_path {
                     if let Some(parent) = dest.parent()
                         && !parent.as_os_str().is_empty()
@@ -701,12 +715,14 @@ fn apply_hunks_to_files(hunks: &[Hunk]) -> anyhow::Result<AffectedPaths> {
         added,
         modified,
         deleted,
+        fuzzy_matches,
     })
 }
 
 struct AppliedPatch {
     original_contents: String,
     new_contents: String,
+    fuzzy_matches: Vec<FuzzyMatch>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -764,7 +780,7 @@ fn derive_new_contents_from_chunks(
         original_lines.pop();
     }
 
-    let replacements = compute_replacements(&original_lines, path, chunks)?;
+    let (replacements, fuzzy_matches) = compute_replacements(&original_lines, path, chunks)?;
     let new_lines = apply_replacements(original_lines, &replacements);
     let mut new_lines = new_lines;
     if !new_lines.last().is_some_and(String::is_empty) {
@@ -774,21 +790,33 @@ fn derive_new_contents_from_chunks(
     Ok(AppliedPatch {
         original_contents,
         new_contents,
+        fuzzy_matches,
     })
 }
 
+type Replacement = (usize, usize, Vec<String>);
+
 /// Compute a list of replacements needed to transform `original_lines` into the
 /// new lines, given the patch `chunks`. Each replacement is returned as
-/// `(start_index, old_len, new_lines)`.
+/// `(start_index, old_len, new_lines)`, alongside the chunks that needed the
+/// syntax-anchored fallback to be located.
 fn compute_replacements(
     original_lines: &[String],
     path: &Path,
     chunks: &[UpdateFileChunk],
-) -> std::result::Result<Vec<(usize, usize, Vec<String>)>, ApplyPatchError> {
+) -> std::result::Result<(Vec<Replacement>, Vec<FuzzyMatch>), ApplyPatchError> {
     let mut replacements: Vec<(usize, usize, Vec<String>)> = Vec::new();
+    let mut fuzzy_matches: Vec<FuzzyMatch> = Vec::new();
     let mut line_index: usize = 0;
 
     for chunk in chunks {
+        // End of the previous replacement: chunks must be applied in order,
+        // so nothing before this line may be replaced again.
+        let chunk_start = line_index;
+        // Set when the `change_context` line itself could only be found as a
+        // syntax node; the chunk is then located inside one of those nodes.
+        let mut anchors = Vec::new();
+
         // If a chunk has a `change_context`, we use seek_sequence to find it, then
         // adjust our `line_index` to continue from there.
         if let Some(ctx_line) = &chunk.change_context {
@@ -800,11 +828,16 @@ fn compute_replacements(
             ) {
                 line_index = idx + 1;
             } else {
-                return Err(ApplyPatchError::ComputeReplacements(format!(
-                    "Failed to find context '{}' in {}",
-                    ctx_line,
-                    path.display()
-                )));
+                anchors = syntax_anchor::find_anchors(path, original_lines, ctx_line, line_index);
+                anchors.retain(|anchor| anchor.end > chunk_start);
+                let Some(first) = anchors.first() else {
+                    return Err(ApplyPatchError::ComputeReplacements(format!(
+                        "Failed to find context '{}' in {}",
+                        ctx_line,
+                        path.display()
+                    )));
+                };
+                line_index = first.start.max(chunk_start);
             }
         }
 
@@ -848,42 +881,79 @@ fn compute_replacements(
             .collect();
 
         let mut pattern: &[String] = &normalized_old_lines;
-        let mut found =
-            seek_sequence::seek_sequence(original_lines, pattern, line_index, chunk.is_end_of_file);
-
         let mut new_slice: &[String] = &normalized_new_lines;
+        let mut found = None;
 
-        if found.is_none() && pattern.last().is_some_and(String::is_empty) {
-            // Retry without the trailing empty line which represents the final
-            // newline in the file.
-            pattern = &pattern[..pattern.len() - 1];
-            if new_slice.last().is_some_and(String::is_empty) {
-                new_slice = &new_slice[..new_slice.len() - 1];
-            }
-
+        if anchors.is_empty() {
             found = seek_sequence::seek_sequence(
                 original_lines,
                 pattern,
                 line_index,
                 chunk.is_end_of_file,
             );
+
+            if found.is_none() && pattern.last().is_some_and(String::is_empty) {
+                // Retry without the trailing empty line which represents the final
+                // newline in the file.
+                pattern = &pattern[..pattern.len() - 1];
+                if new_slice.last().is_some_and(String::is_empty) {
+                    new_slice = &new_slice[..new_slice.len() - 1];
+                }
+
+                found = seek_sequence::seek_sequence(
+                    original_lines,
+                    pattern,
+                    line_index,
+                    chunk.is_end_of_file,
+                );
+            }
         }
 
         if let Some(start_idx) = found {
             replacements.push((start_idx, pattern.len(), new_slice.to_vec()));
             line_index = start_idx + pattern.len();
-        } else {
-            return Err(ApplyPatchError::ComputeReplacements(format!(
-                "Failed to find expected lines in {}:\n{}",
-                path.display(),
-                normalized_old_lines.join("\n"),
-            )));
+            continue;
         }
+
+        // The context has drifted from the file: anchor on the definition
+        // named by the `@@` header and look for the chunk inside it.
+        if let Some(ctx_line) = &chunk.change_context
+            && anchors.is_empty()
+        {
+            anchors = syntax_anchor::find_anchors(path, original_lines, ctx_line, chunk_start);
+            anchors.retain(|anchor| anchor.end > chunk_start);
+        }
+        if let Some((anchor, relocated)) = anchors.into_iter().find_map(|anchor| {
+            syntax_anchor::relocate_in_anchor(
+                original_lines,
+                &anchor,
+                chunk_start,
+                &normalized_old_lines,
+                &normalized_new_lines,
+            )
+            .map(|relocated| (anchor, relocated))
+        }) {
+            fuzzy_matches.push(FuzzyMatch {
+                path: path.to_path_buf(),
+                anchor: anchor.label,
+                line: anchor.start + 1,
+                fuzz: relocated.fuzz,
+            });
+            line_index = relocated.start + relocated.old_len;
+            replacements.push((relocated.start, relocated.old_len, relocated.new_lines));
+            continue;
+        }
+
+        return Err(ApplyPatchError::ComputeReplacements(format!(
+            "Failed to find expected lines in {}:\n{}",
+            path.display(),
+            normalized_old_lines.join("\n"),
+        )));
     }
 
     replacements.sort_by(|(lhs_idx, _, _), (rhs_idx, _, _)| lhs_idx.cmp(rhs_idx));
 
-    Ok(replacements)
+    Ok((replacements, fuzzy_matches))
 }
 
 /// Apply the `(start_index, old_len, new_lines)` replacements to `original_lines`,
@@ -919,6 +989,7 @@ fn apply_replacements(
 pub struct ApplyPatchFileUpdate {
     unified_diff: String,
     content: String,
+    fuzzy_matches: Vec<FuzzyMatch>,
 }
 
 pub fn unified_diff_from_chunks(
@@ -936,12 +1007,14 @@ pub fn unified_diff_from_chunks_with_context(
     let AppliedPatch {
         original_contents,
         new_contents,
+        fuzzy_matches,
     } = derive_new_contents_from_chunks(path, chunks)?;
     let text_diff = TextDiff::from_lines(&original_contents, &new_contents);
     let unified_diff = text_diff.unified_diff().context_radius(context).to_string();
     Ok(ApplyPatchFileUpdate {
         unified_diff,
         content: new_contents,
+        fuzzy_matches,
     })
 }
 
@@ -961,6 +1034,9 @@ pub fn print_summary(
     for path in &affected.deleted {
         writeln!(out, "D {}", path.display())?;
     }
+    for fuzzy_match in &affected.fuzzy_matches {
+        writeln!(out, "Note: {fuzzy_match}")?;
+    }
     Ok(())
 }
 
@@ -1292,6 +1368,79 @@ PATCH"#,
         assert_eq!(contents, "foo\nbaz\n");
     }
 
+    #[test]
+    fn test_update_file_hunk_anchors_stale_context_on_syntax_node() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        fs::write(
+            &path,
+            "fn setup() {}\n\nfn run(verbose: bool) {\n    let x = 1;\n    println!(\"{x}\");\n}\n",
+        )
+        .unwrap();
+        // Both the header and the first context line are out of date: `run`
+        // has since gained a parameter.
+        let patch = wrap_patch(&format!(
+            r#"*** Update File: {}
+@@ fn run
+ fn run() {{
+-    let x = 1;
++    let x = 2;
+     println!("{{x}}");"#,
+            path.display()
+        ));
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+        let stdout_str = String::from_utf8(stdout).unwrap();
+        let expected_out = format!(
+            "Success. Updated the following files:\nM {0}\nNote: {0}: chunk anchored on `fn run(verbose: bool)` (line 3), fuzz 1\n",
+            path.display()
+        );
+        assert_eq!(stdout_str, expected_out);
+        let contents = fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            contents,
+            "fn setup() {}\n\nfn run(verbose: bool) {\n    let x = 2;\n    println!(\"{x}\");\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_update_file_anchored_chunk_does_not_overlap_previous_chunk() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        fs::write(
+            &path,
+            "fn run(verbose: bool) {\n    let x = 1;\n    println!(\"{x}\");\n}\n\nmod legacy {\n    fn run(verbose: bool, dry_run: bool) {\n        let x = 1;\n        println!(\"{x}\");\n    }\n}\n",
+        )
+        .unwrap();
+        // The second header matches the first `run` exactly, but its lines
+        // must come after the first chunk's replacement, i.e. in `legacy`.
+        let patch = wrap_patch(&format!(
+            r#"*** Update File: {}
+@@ fn run(verbose: bool) {{
+-    let x = 1;
++    let x = 2;
+@@ fn run(verbose: bool) {{
+-        let x = 1;
++        let x = 3;"#,
+            path.display()
+        ));
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+        let stdout_str = String::from_utf8(stdout).unwrap();
+        let expected_out = format!(
+            "Success. Updated the following files:\nM {0}\nNote: {0}: chunk anchored on `fn run(verbose: bool, dry_run: bool)` (line 7), fuzz 0\n",
+            path.display()
+        );
+        assert_eq!(stdout_str, expected_out);
+        let contents = fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            contents,
+            "fn run(verbose: bool) {\n    let x = 2;\n    println!(\"{x}\");\n}\n\nmod legacy {\n    fn run(verbose: bool, dry_run: bool) {\n        let x = 3;\n        println!(\"{x}\");\n    }\n}\n"
+        );
+    }
+
     #[test]
     fn test_update_file_hunk_preserves_crlf_line_endings() {
         let dir = tempdir().unwrap();
@@ -1552,6 +1701,7 @@ PATCH"#,
         let expected = ApplyPatchFileUpdate {
             unified_diff: expected_diff.to_string(),
             content: "foo\nBAR\nbaz\nQUX\n".to_string(),
+            fuzzy_matches: Vec::new(),
         };
         assert_eq!(expected, diff);
     }
@@ -1588,6 +1738,7 @@ PATCH"#,
         let expected = ApplyPatchFileUpdate {
             unified_diff: expected_diff.to_string(),
             content: "FOO\nbar\nbaz\n".to_string(),
+            fuzzy_matches: Vec::new(),
         };
         assert_eq!(expected, diff);
     }
@@ -1625,6 +1776,7 @@ PATCH"#,
         let expected = ApplyPatchFileUpdate {
             unified_diff: expected_diff.to_string(),
             content: "foo\nbar\nBAZ\n".to_string(),
+            fuzzy_matches: Vec::new(),
         };
         assert_eq!(expected, diff);
     }
@@ -1659,6 +1811,7 @@ PATCH"#,
         let expected = ApplyPatchFileUpdate {
             unified_diff: expected_diff.to_string(),
             content: "foo\nbar\nbaz\nquux\n".to_string(),
+            fuzzy_matches: Vec::new(),
         };
         assert_eq!(expected, diff);
     }
@@ -1714,6 +1867,7 @@ PATCH"#,
         let expected = ApplyPatchFileUpdate {
             unified_diff: expected_diff.to_string(),
             content: "a\nB\nc\nd\nE\nf\ng\n".to_string(),
+            fuzzy_matches: Vec::new(),
         };
 
         assert_eq!(expected, diff);
@@ -1773,6 +1927,7 @@ g
                         .to_string(),
                         move_path: None,
                         new_content: "updated session directory content\n".to_string(),
+                        fuzzy_matches: Vec::new(),
                     },
                 )]),
                 patch: argv[1].clone(),
@@ -0,0 +1,350 @@
+//! Fallback for locating update chunks whose context has drifted from the
+//! file on disk.
+//!
+//! When `seek_sequence` cannot find a chunk, we parse the file with
+//! tree-sitter, find the definition (function, impl, class, ...) named by the
+//! chunk's `@@` header, and look for the chunk inside that definition only,
+//! dropping up to [`MAX_FUZZ`] context lines from each end of the chunk.
+
+use std::fmt;
+use std::path::Path;
+use std::path::PathBuf;
+
+use tree_sitter::Language;
+use tree_sitter::Node;
+use tree_sitter::Parser;
+
+use crate::seek_sequence::seek_sequence;
+
+/// Maximum number of context lines that may be ignored at each end of a
+/// chunk.
+pub(crate) const MAX_FUZZ: usize = 3;
+
+/// A chunk that could only be applied by anchoring it on a syntax node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    pub path: PathBuf,
+    /// First line of the definition the chunk was anchored on, e.g.
+    /// `pub fn render(&self)`.
+    pub anchor: String,
+    /// 1-based line at which the anchor starts.
+    pub line: usize,
+    /// Number of context lines ignored at each end of the chunk.
+    pub fuzz: usize,
+}
+
+impl fmt::Display for FuzzyMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: chunk anchored on `{}` (line {}), fuzz {}",
+            self.path.display(),
+            self.anchor,
+            self.line,
+            self.fuzz
+        )
+    }
+}
+
+/// A definition whose name matched a chunk header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SyntaxAnchor {
+    pub(crate) label: String,
+    /// Line range of the definition, end exclusive.
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+}
+
+/// A chunk relocated inside an anchor: `(start_index, old_len, new_lines)`
+/// like the other replacements, plus the fuzz that was needed.
+pub(crate) struct Relocated {
+    pub(crate) start: usize,
+    pub(crate) old_len: usize,
+    pub(crate) new_lines: Vec<String>,
+    pub(crate) fuzz: usize,
+}
+
+/// Definition node kinds per language, with the keyword that must appear in
+/// a header for it to refer to such a node.
+type Definitions = &'static [(&'static str, Option<&'static str>)];
+
+const RUST_DEFINITIONS: Definitions = &[
+    ("function_item", Some("fn")),
+    ("function_signature_item", Some("fn")),
+    ("impl_item", Some("impl")),
+    ("struct_item", Some("struct")),
+    ("enum_item", Some("enum")),
+    ("trait_item", Some("trait")),
+    ("mod_item", Some("mod")),
+    ("macro_definition", Some("macro_rules")),
+];
+
+const PYTHON_DEFINITIONS: Definitions = &[
+    ("function_definition", Some("def")),
+    ("class_definition", Some("class")),
+];
+
+const JAVASCRIPT_DEFINITIONS: Definitions = &[
+    ("function_declaration", Some("function")),
+    ("generator_function_declaration", Some("function")),
+    ("class_declaration", Some("class")),
+    ("abstract_class_declaration", Some("class")),
+    ("interface_declaration", Some("interface")),
+    ("method_definition", None),
+];
+
+const GO_DEFINITIONS: Definitions = &[
+    ("function_declaration", Some("func")),
+    ("method_declaration", Some("func")),
+    ("type_declaration", Some("type")),
+];
+
+fn language_for_path(path: &Path) -> Option<(Language, Definitions)> {
+    let extension = path.extension()?.to_str()?;
+    let language = match extension {
+        "rs" => (tree_sitter_rust::LANGUAGE.into(), RUST_DEFINITIONS),
+        "py" | "pyi" => (tree_sitter_python::LANGUAGE.into(), PYTHON_DEFINITIONS),
+        "js" | "jsx" | "mjs" | "cjs" => (
+            tree_sitter_javascript::LANGUAGE.into(),
+            JAVASCRIPT_DEFINITIONS,
+        ),
+        "ts" | "mts" | "cts" => (
+            tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            JAVASCRIPT_DEFINITIONS,
+        ),
+        "tsx" => (
+            tree_sitter_typescript::LANGUAGE_TSX.into(),
+            JAVASCRIPT_DEFINITIONS,
+        ),
+        "go" => (tree_sitter_go::LANGUAGE.into(), GO_DEFINITIONS),
+        _ => return None,
+    };
+    Some(language)
+}
+
+/// Finds the definitions in `lines` that `header` (a chunk's `@@` context
+/// line) may refer to, best candidate first: definitions whose first line
+/// equals the header, then the ones starting at or after `from`, each in
+/// source order.
+pub(crate) fn find_anchors(
+    path: &Path,
+    lines: &[String],
+    header: &str,
+    from: usize,
+) -> Vec<SyntaxAnchor> {
+    let Some((language, definitions)) = language_for_path(path) else {
+        return Vec::new();
+    };
+    let mut parser = Parser::new();
+    if parser.set_language(&language).is_err() {
+        return Vec::new();
+    }
+    let source = lines.join("\n");
+    let Some(tree) = parser.parse(&source, None) else {
+        return Vec::new();
+    };
+
+    let header_tokens: Vec<&str> = identifiers(header).collect();
+    let header = header.trim();
+    let mut candidates = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        let mut cursor = node.walk();
+        stack.extend(node.named_children(&mut cursor));
+
+        let Some((_, keyword)) = definitions.iter().find(|(kind, _)| *kind == node.kind()) else {
+            continue;
+        };
+        let Some(name) = definition_name(node, &source) else {
+            continue;
+        };
+        if !header_tokens.contains(&name)
+            || keyword.is_some_and(|keyword| !header_tokens.contains(&keyword))
+        {
+            continue;
+        }
+
+        let start = node.start_position().row;
+        let end = node.end_position().row + 1;
+        let label = lines
+            .get(start)
+            .map(|line| line.trim().trim_end_matches(['{', ':']).trim_end())
+            .unwrap_or_default()
+            .to_string();
+        let rank = (
+            label != header.trim_end_matches(['{', ':']).trim_end(),
+            start < from,
+            start,
+            end - start,
+        );
+        candidates.push((rank, SyntaxAnchor { label, start, end }));
+    }
+    candidates.sort_by_key(|(rank, _)| *rank);
+    candidates.into_iter().map(|(_, anchor)| anchor).collect()
+}
+
+/// The name a header would use for `node`: its `name` field, the
+/// implemented type for a Rust `impl`, or the name of a Go type spec.
+fn definition_name<'a>(node: Node, source: &'a str) -> Option<&'a str> {
+    let name_node = node
+        .child_by_field_name("name")
+        .or_else(|| node.child_by_field_name("type"))
+        .or_else(|| {
+            let mut cursor = node.walk();
+            node.named_children(&mut cursor)
+                .find_map(|child| child.child_by_field_name("name"))
+        })?;
+    let text = name_node.utf8_text(source.as_bytes()).ok()?;
+    // `Foo<T>` and `crate::Foo` are referred to as `Foo`.
+    let text = text.split('<').next().unwrap_or(text);
+    text.rsplit("::")
+        .next()
+        .and_then(|name| identifiers(name).last())
+}
+
+fn identifiers(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+        .filter(|token| !token.is_empty())
+}
+
+/// Looks for `old_lines` inside `anchor`, at or after line `from`, ignoring
+/// progressively more of the context lines shared by `old_lines` and
+/// `new_lines` at either end.
+pub(crate) fn relocate_in_anchor(
+    lines: &[String],
+    anchor: &SyntaxAnchor,
+    from: usize,
+    old_lines: &[String],
+    new_lines: &[String],
+) -> Option<Relocated> {
+    let scope_start = anchor.start.max(from);
+    let scope = lines.get(scope_start..anchor.end.min(lines.len()))?;
+    let leading = old_lines
+        .iter()
+        .zip(new_lines)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let trailing = old_lines[leading..]
+        .iter()
+        .rev()
+        .zip(new_lines[leading.min(new_lines.len())..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+
+    for fuzz in 0..=MAX_FUZZ.min(leading.max(trailing)) {
+        let front = fuzz.min(leading);
+        let back = fuzz.min(trailing);
+        let pattern = &old_lines[front..old_lines.len() - back];
+        if pattern.is_empty() {
+            break;
+        }
+        if let Some(offset) = seek_sequence(scope, pattern, 0, false) {
+            return Some(Relocated {
+                start: scope_start + offset,
+                old_len: pattern.len(),
+                new_lines: new_lines[front..new_lines.len() - back].to_vec(),
+                fuzz,
+            });
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn to_vec(text: &str) -> Vec<String> {
+        text.lines().map(str::to_string).collect()
+    }
+
+    const RUST_SOURCE: &str = "\
+struct Greeter;
+
+impl Greeter {
+    pub fn new() -> Self {
+        Greeter
+    }
+
+    pub fn greet(&self, name: &str) -> String {
+        let greeting = format!(\"Hello, {name}!\");
+        greeting
+    }
+}
+
+fn greet() {}
+";
+
+    #[test]
+    fn finds_method_from_abbreviated_header() {
+        let lines = to_vec(RUST_SOURCE);
+        let anchors = find_anchors(Path::new("lib.rs"), &lines, "fn greet", 0);
+        assert_eq!(
+            Some(&SyntaxAnchor {
+                label: "pub fn greet(&self, name: &str) -> String".to_string(),
+                start: 7,
+                end: 11,
+            }),
+            anchors.first()
+        );
+
+        // Past the method, the free function comes first.
+        let anchors = find_anchors(Path::new("lib.rs"), &lines, "fn greet", 12);
+        assert_eq!(
+            vec![13, 7],
+            anchors
+                .iter()
+                .map(|anchor| anchor.start)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn matches_impl_by_type_name() {
+        let lines = to_vec(RUST_SOURCE);
+        let anchors = find_anchors(Path::new("lib.rs"), &lines, "impl Greeter", 0);
+        assert_eq!(
+            vec![(2, 12)],
+            anchors
+                .iter()
+                .map(|anchor| (anchor.start, anchor.end))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Vec::<SyntaxAnchor>::new(),
+            find_anchors(Path::new("lib.rs"), &lines, "impl Other", 0)
+        );
+        assert_eq!(
+            Vec::<SyntaxAnchor>::new(),
+            find_anchors(Path::new("notes.txt"), &lines, "impl Greeter", 0)
+        );
+    }
+
+    #[test]
+    fn relocates_with_stale_context() {
+        let lines = to_vec(RUST_SOURCE);
+        let anchor = SyntaxAnchor {
+            label: String::new(),
+            start: 7,
+            end: 11,
+        };
+        // The leading context line is stale: the model remembers a different
+        // signature.
+        let old_lines = to_vec(
+            "    pub fn greet(&self, name: String) -> String {\n        let greeting = format!(\"Hello, {name}!\");\n        greeting",
+        );
+        let new_lines = to_vec(
+            "    pub fn greet(&self, name: String) -> String {\n        let greeting = format!(\"Hi, {name}!\");\n        greeting",
+        );
+        let relocated = relocate_in_anchor(&lines, &anchor, 0, &old_lines, &new_lines)
+            .expect("chunk should be relocated");
+        assert_eq!(1, relocated.fuzz);
+        assert_eq!(8, relocated.start);
+        assert_eq!(1, relocated.old_len);
+        assert_eq!(
+            vec!["        let greeting = format!(\"Hi, {name}!\");".to_string()],
+            relocated.new_lines
+        );
+    }
+}
@@ -0,0 +1,12 @@
+class Circle:
+    def __init__(self, radius):
+        self.radius = radius
+
+    def area(self, precision=2):
+        value = math.pi * self.radius ** 2
+        return round(value, precision)
+
+
+class Square:
+    def area(self):
+        return self.side ** 2
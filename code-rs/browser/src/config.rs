@@ -71,6 +71,18 @@ pub struct BrowserConfig {
     /// Number of WS connect attempts before giving up
     #[serde(default = "default_connect_attempts")]
     pub connect_attempts: u32,
+
+    /// Let pages open popups and `target=_blank` links in new tabs instead of
+    /// redirecting them into the current tab. New tabs are reachable through
+    /// the browser tab actions.
+    #[serde(default)]
+    pub allow_new_tabs: bool,
+
+    /// Append completed requests to a journal under
+    /// `$CODE_HOME/browser/network/` for `code browser har`. Credential
+    /// headers are redacted before they are written.
+    #[serde(default)]
+    pub record_network: bool,
 }
 
 impl Default for BrowserConfig {
@@ -95,6 +107,8 @@ impl Default for BrowserConfig {
             user_agent: None,
             connect_attempt_timeout_ms: default_connect_attempt_timeout_ms(),
             connect_attempts: default_connect_attempts(),
+            allow_new_tabs: false,
+            record_network: false,
         }
     }
 }
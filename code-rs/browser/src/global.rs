@@ -12,6 +12,17 @@ static GLOBAL_BROWSER_MANAGER: Lazy<Arc<RwLock<Option<Arc<BrowserManager>>>>> =
 static LAST_CONNECTION: Lazy<Arc<RwLock<(Option<u16>, Option<String>)>>> =
     Lazy::new(|| Arc::new(RwLock::new((None, None))));
 
+/// Config for the global manager, seeded from the user's `[browser]` settings.
+static DEFAULT_CONFIG: Lazy<std::sync::RwLock<BrowserConfig>> =
+    Lazy::new(|| std::sync::RwLock::new(BrowserConfig::default()));
+
+/// Adjust the config used when the global browser manager is created.
+pub fn configure_default_browser(update: impl FnOnce(&mut BrowserConfig)) {
+    if let Ok(mut config) = DEFAULT_CONFIG.write() {
+        update(&mut config);
+    }
+}
+
 /// Get or create the global browser manager
 pub async fn get_or_create_browser_manager() -> Arc<BrowserManager> {
     // Fast path: try read lock to avoid contending on writer when already initialized
@@ -24,7 +35,10 @@ pub async fn get_or_create_browser_manager() -> Arc<BrowserManager> {
     if let Some(existing) = w.as_ref() {
         return existing.clone();
     }
-    let config = BrowserConfig::default();
+    let config = DEFAULT_CONFIG
+        .read()
+        .map(|config| config.clone())
+        .unwrap_or_default();
     let manager = Arc::new(BrowserManager::new(config));
     *w = Some(manager.clone());
     manager
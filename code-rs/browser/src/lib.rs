@@ -3,16 +3,24 @@ pub mod config;
 pub mod global;
 pub mod hooks;
 pub mod manager;
+pub mod network;
 pub mod page;
+pub mod recording;
+pub mod snapshot;
+mod storage;
 pub mod tools;
 
 pub use config::BrowserConfig;
 pub use config::ViewportConfig;
 pub use config::WaitStrategy;
 pub use manager::BrowserManager;
+pub use manager::TabInfo;
+pub use network::NetworkEntry;
+pub use network::NetworkFilter;
 pub use page::Page;
 pub use page::ScreenshotMode;
 pub use page::ScreenshotRegion;
+pub use snapshot::PageSnapshot;
 
 use thiserror::Error;
 
@@ -38,6 +46,9 @@ pub enum BrowserError {
 
     #[error("Asset storage error: {0}")]
     AssetError(String),
+
+    #[error("Element ref error: {0}")]
+    ElementRefError(String),
 }
 
 impl From<chromiumoxide::error::CdpError> for BrowserError {
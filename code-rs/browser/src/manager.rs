@@ -2,7 +2,16 @@ use crate::BrowserError;
 use crate::Result;
 use crate::config::BrowserConfig;
 use crate::config::WaitStrategy;
+use crate::network::NetworkEntry;
+use crate::network::NetworkFilter;
 use crate::page::Page;
+use crate::recording::ActionRecorder;
+use crate::recording::BrowserAction;
+use crate::recording::ElementTarget;
+use crate::recording::RecordedStep;
+use crate::snapshot::ElementRefTable;
+use crate::snapshot::PageSnapshot;
+use crate::snapshot::RefLookupError;
 use chromiumoxide::Browser;
 use chromiumoxide::BrowserConfig as CdpConfig;
 use chromiumoxide::browser::HeadlessMode;
@@ -14,8 +23,11 @@ use once_cell::sync::Lazy;
 use reqwest::Client;
 use serde::Deserialize;
 use serde_json::Value;
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 use tokio::sync::Mutex;
 use tokio::sync::RwLock;
 use tokio::time::Duration;
@@ -305,6 +317,14 @@ pub struct BrowserManager {
     auto_viewport_correction_enabled: Arc<tokio::sync::RwLock<bool>>,
     /// Track last applied device metrics to avoid redundant overrides
     last_metrics_applied: Arc<Mutex<Option<(i64, i64, f64, bool, std::time::Instant)>>>,
+    /// Element refs minted by the most recent accessibility snapshot
+    element_refs: Arc<Mutex<Option<ElementRefTable>>>,
+    /// Pages we have wrapped, keyed by CDP target id, so switching back to a
+    /// tab reuses its console buffer and cursor state.
+    tab_pages: Arc<Mutex<HashMap<String, Arc<Page>>>>,
+    /// Replayable log of the actions issued in this browser session
+    recorder: Arc<Mutex<Option<ActionRecorder>>>,
+    recording_enabled: Arc<AtomicBool>,
 }
 
 #[derive(Debug)]
@@ -343,6 +363,10 @@ impl BrowserManager {
             viewport_monitor_handle: Arc::new(Mutex::new(None)),
             auto_viewport_correction_enabled: Arc::new(tokio::sync::RwLock::new(true)),
             last_metrics_applied: Arc::new(Mutex::new(None)),
+            element_refs: Arc::new(Mutex::new(None)),
+            tab_pages: Arc::new(Mutex::new(HashMap::new())),
+            recorder: Arc::new(Mutex::new(None)),
+            recording_enabled: Arc::new(AtomicBool::new(true)),
         }
     }
 
@@ -970,9 +994,14 @@ impl BrowserManager {
         }
 
         self.stop_navigation_monitor().await;
+        self.stop_viewport_monitor().await;
 
         let mut page_guard = self.page.lock().await;
         *page_guard = None;
+        *self.element_refs.lock().await = None;
+        self.tab_pages.lock().await.clear();
+        // The next session starts a fresh recording file.
+        *self.recorder.lock().await = None;
 
         // Also cleanup the background page
         let mut background_page_guard = self.background_page.lock().await;
@@ -1193,13 +1222,33 @@ impl BrowserManager {
             browser.new_page("about:blank").await?
         };
 
+        let page = self.prepare_page(cdp_page, &config).await?;
+        *page_guard = Some(Arc::clone(&page));
+        info!(
+            "[bm] get_or_create_page: complete in {:?}",
+            overall_start.elapsed()
+        );
+
+        Ok(page)
+    }
+
+    /// Wrap a CDP page: apply overrides, install the cursor and console capture,
+    /// make sure the page monitors run, and register it as a known tab.
+    async fn prepare_page(
+        &self,
+        cdp_page: chromiumoxide::page::Page,
+        config: &BrowserConfig,
+    ) -> Result<Arc<Page>> {
         // Apply page overrides (UA, locale, timezone, viewport, etc.)
         let overrides_start = Instant::now();
         self.apply_page_overrides(&cdp_page).await?;
-        info!("[bm] get_or_create_page: overrides in {:?}", overrides_start.elapsed());
+        info!("[bm] prepare_page: overrides in {:?}", overrides_start.elapsed());
 
         let page = Arc::new(Page::new(cdp_page, config.clone()));
-        *page_guard = Some(Arc::clone(&page));
+        self.tab_pages
+            .lock()
+            .await
+            .insert(page.target_id(), Arc::clone(&page));
 
         // Inject the virtual cursor when page is created
         debug!("Injecting virtual cursor for new page");
@@ -1264,22 +1313,38 @@ impl BrowserManager {
             warn!("Failed to install console capture on page creation: {}", e);
         }
 
-        // Start navigation monitoring for this page
-        self.start_navigation_monitor(Arc::clone(&page)).await;
+        self.ensure_page_monitors().await;
+        Ok(page)
+    }
+
+    /// Start the navigation and viewport monitors unless they are already
+    /// running. Both follow whichever tab is active, so switching tabs does
+    /// not restart them.
+    async fn ensure_page_monitors(&self) {
+        if Self::monitor_running(&self.navigation_monitor_handle).await
+            && Self::monitor_running(&self.viewport_monitor_handle).await
+        {
+            return;
+        }
+        // Start navigation monitoring for the active page
+        self.start_navigation_monitor().await;
         // Start viewport monitor (low-frequency, non-invasive)
-        self.start_viewport_monitor(Arc::clone(&page)).await;
+        self.start_viewport_monitor().await;
         // TEMP: disable auto-corrections post-initial set to validate no unintended resizes
         // This affects both external and internal; explicit browser.setViewport still works
         self.set_auto_viewport_correction(false).await;
-        info!(
-            "[bm] get_or_create_page: complete in {:?}",
-            overall_start.elapsed()
-        );
+    }
 
-        Ok(page)
+    async fn monitor_running(handle: &Mutex<Option<tokio::task::JoinHandle<()>>>) -> bool {
+        handle
+            .lock()
+            .await
+            .as_ref()
+            .is_some_and(|handle| !handle.is_finished())
     }
 
     pub async fn close_page(&self) -> Result<()> {
+        *self.element_refs.lock().await = None;
         let mut page_guard = self.page.lock().await;
         if let Some(page) = page_guard.take() {
             page.close().await?;
@@ -1287,6 +1352,181 @@ impl BrowserManager {
         Ok(())
     }
 
+    /// CDP target id of the tab that browser actions currently target.
+    pub async fn active_tab_id(&self) -> Option<String> {
+        self.page.lock().await.as_ref().map(|page| page.target_id())
+    }
+
+    /// List open tabs (page targets), excluding the hidden screenshot page.
+    pub async fn list_tabs(&self) -> Result<Vec<TabInfo>> {
+        self.ensure_browser().await?;
+        let active_id = self.active_tab_id().await;
+        let background_id = self
+            .background_page
+            .lock()
+            .await
+            .as_ref()
+            .map(|page| page.target_id());
+        let pages = {
+            let browser_guard = self.browser.lock().await;
+            let browser = browser_guard.as_ref().ok_or(BrowserError::NotInitialized)?;
+            browser.pages().await?
+        };
+
+        let mut tabs = Vec::with_capacity(pages.len());
+        for cdp_page in pages {
+            let target_id = cdp_page.target_id().inner().clone();
+            if background_id.as_deref() == Some(target_id.as_str()) {
+                continue;
+            }
+            let url = match tokio::time::timeout(Duration::from_millis(300), cdp_page.url()).await {
+                Ok(Ok(Some(url))) => url,
+                _ => "about:blank".to_string(),
+            };
+            let title = tokio::time::timeout(Duration::from_millis(300), cdp_page.get_title())
+                .await
+                .ok()
+                .and_then(std::result::Result::ok)
+                .flatten();
+            tabs.push(TabInfo {
+                active: active_id.as_deref() == Some(target_id.as_str()),
+                opener_id: cdp_page
+                    .opener_id()
+                    .as_ref()
+                    .map(|opener| opener.inner().clone()),
+                target_id,
+                url,
+                title,
+            });
+        }
+        Ok(tabs)
+    }
+
+    /// Open a new tab, make it the active tab and optionally navigate it.
+    pub async fn open_tab(&self, url: Option<&str>) -> Result<TabInfo> {
+        self.ensure_browser().await?;
+        let cdp_page = {
+            let browser_guard = self.browser.lock().await;
+            let browser = browser_guard.as_ref().ok_or(BrowserError::NotInitialized)?;
+            browser.new_page("about:blank").await?
+        };
+        let config = self.config.read().await.clone();
+        let page = self.prepare_page(cdp_page, &config).await?;
+        self.set_active_page(Arc::clone(&page)).await;
+        if let Some(url) = url {
+            self.goto(url).await?;
+        }
+        Ok(Self::tab_info(&page, true).await)
+    }
+
+    /// Make the tab with the given target id (or unique prefix) the active tab.
+    pub async fn switch_tab(&self, target_id: &str) -> Result<TabInfo> {
+        self.ensure_browser().await?;
+        let cdp_page = self.find_tab(target_id).await?;
+        let resolved_id = cdp_page.target_id().inner().clone();
+        let cached = self.tab_pages.lock().await.get(&resolved_id).cloned();
+        let page = match cached {
+            Some(page) => page,
+            None => {
+                let config = self.config.read().await.clone();
+                self.prepare_page(cdp_page, &config).await?
+            }
+        };
+        if let Err(e) = page.bring_to_front().await {
+            debug!("Failed to bring tab {} to front: {}", resolved_id, e);
+        }
+        self.set_active_page(Arc::clone(&page)).await;
+        Ok(Self::tab_info(&page, true).await)
+    }
+
+    /// Close a tab (the active one when `target_id` is None). When the active
+    /// tab closes, its opener (or the most recent remaining tab) becomes active.
+    /// Returns the tab that is active afterwards, if any.
+    pub async fn close_tab(&self, target_id: Option<&str>) -> Result<Option<TabInfo>> {
+        self.ensure_browser().await?;
+        let active_id = self.active_tab_id().await;
+        let resolved_id = match target_id {
+            Some(id) => self.find_tab(id).await?.target_id().inner().clone(),
+            None => active_id.clone().ok_or(BrowserError::PageNotLoaded)?,
+        };
+        let opener_id = self
+            .tab_pages
+            .lock()
+            .await
+            .get(&resolved_id)
+            .and_then(|page| page.opener_id());
+
+        self.execute_cdp_browser(
+            "Target.closeTarget",
+            serde_json::json!({ "targetId": resolved_id }),
+        )
+        .await?;
+        self.tab_pages.lock().await.remove(&resolved_id);
+
+        if active_id.as_deref() != Some(resolved_id.as_str()) {
+            let page = self.page.lock().await.clone();
+            return Ok(match page {
+                Some(page) => Some(Self::tab_info(&page, true).await),
+                None => None,
+            });
+        }
+
+        *self.page.lock().await = None;
+        *self.element_refs.lock().await = None;
+
+        let remaining = self.list_tabs().await?;
+        let next = opener_id
+            .filter(|opener| remaining.iter().any(|tab| &tab.target_id == opener))
+            .or_else(|| remaining.last().map(|tab| tab.target_id.clone()));
+        match next {
+            Some(next_id) => Ok(Some(self.switch_tab(&next_id).await?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_tab(&self, target_id: &str) -> Result<chromiumoxide::page::Page> {
+        let pages = {
+            let browser_guard = self.browser.lock().await;
+            let browser = browser_guard.as_ref().ok_or(BrowserError::NotInitialized)?;
+            browser.pages().await?
+        };
+        let mut matches: Vec<chromiumoxide::page::Page> = pages
+            .into_iter()
+            .filter(|page| {
+                page.target_id()
+                    .inner()
+                    .to_ascii_lowercase()
+                    .starts_with(&target_id.to_ascii_lowercase())
+            })
+            .collect();
+        match matches.len() {
+            1 => Ok(matches.remove(0)),
+            0 => Err(BrowserError::CdpError(format!("No tab with id '{target_id}'"))),
+            n => Err(BrowserError::CdpError(format!(
+                "Tab id '{target_id}' is ambiguous ({n} matches); use a longer prefix"
+            ))),
+        }
+    }
+
+    async fn set_active_page(&self, page: Arc<Page>) {
+        *self.page.lock().await = Some(page);
+        *self.element_refs.lock().await = None;
+        self.update_activity().await;
+    }
+
+    async fn tab_info(page: &Page, active: bool) -> TabInfo {
+        TabInfo {
+            target_id: page.target_id(),
+            opener_id: page.opener_id(),
+            url: page
+                .get_current_url()
+                .await
+                .unwrap_or_else(|_| "about:blank".to_string()),
+            title: page.get_title().await,
+            active,
+        }
+    }
+
     pub async fn is_enabled(&self) -> bool {
         self.config.read().await.enabled
     }
@@ -1641,6 +1881,10 @@ impl BrowserManager {
                             recovery_attempts
                         );
                     }
+                    self.record_action(BrowserAction::Goto {
+                        url: url.to_string(),
+                    })
+                    .await;
                     return Ok(result);
                 }
                 Err(err) => {
@@ -1991,7 +2235,10 @@ impl BrowserManager {
     /// Click at the specified coordinates
     pub async fn click(&self, x: f64, y: f64) -> Result<()> {
         let page = self.get_or_create_page().await?;
-        page.click(x, y).await
+        page.click(x, y).await?;
+        self.record_action(BrowserAction::Click { x, y, target: None })
+            .await;
+        Ok(())
     }
 
     /// Click at the current mouse position
@@ -2015,37 +2262,392 @@ impl BrowserManager {
     /// Type text into the currently focused element
     pub async fn type_text(&self, text: &str) -> Result<()> {
         let page = self.get_or_create_page().await?;
-        page.type_text(text).await
+        page.type_text(text).await?;
+        self.record_typing(&page, text, None).await;
+        Ok(())
     }
 
     /// Press a key (e.g., "Enter", "Tab", "Escape", "ArrowDown")
     pub async fn press_key(&self, key: &str) -> Result<()> {
         let page = self.get_or_create_page().await?;
-        page.press_key(key).await
+        page.press_key(key).await?;
+        self.record_action(BrowserAction::Key {
+            key: key.to_string(),
+        })
+        .await;
+        Ok(())
     }
 
     /// Execute JavaScript code with enhanced return value handling
     pub async fn execute_javascript(&self, code: &str) -> Result<serde_json::Value> {
         let page = self.get_or_create_page().await?;
-        page.execute_javascript(code).await
+        let result = page.execute_javascript(code).await?;
+        self.record_action(BrowserAction::Javascript {
+            code: code.to_string(),
+        })
+        .await;
+        Ok(result)
     }
 
     /// Scroll the page by the given delta in pixels
     pub async fn scroll_by(&self, dx: f64, dy: f64) -> Result<()> {
         let page = self.get_or_create_page().await?;
-        page.scroll_by(dx, dy).await
+        page.scroll_by(dx, dy).await?;
+        self.record_action(BrowserAction::Scroll { dx, dy }).await;
+        Ok(())
     }
 
     /// Navigate browser history backward one entry
     pub async fn history_back(&self) -> Result<()> {
         let page = self.get_or_create_page().await?;
-        page.go_back().await
+        page.go_back().await?;
+        self.record_action(BrowserAction::HistoryBack).await;
+        Ok(())
     }
 
     /// Navigate browser history forward one entry
     pub async fn history_forward(&self) -> Result<()> {
         let page = self.get_or_create_page().await?;
-        page.go_forward().await
+        page.go_forward().await?;
+        self.record_action(BrowserAction::HistoryForward).await;
+        Ok(())
+    }
+
+    /// Enable or disable action recording (replay runs disable it).
+    pub fn set_action_recording(&self, enabled: bool) {
+        self.recording_enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Path of the JSON action script for the current session, once an
+    /// action has been recorded.
+    pub async fn recording_path(&self) -> Option<std::path::PathBuf> {
+        self.recorder
+            .lock()
+            .await
+            .as_ref()
+            .and_then(|r| r.path().map(std::path::Path::to_path_buf))
+    }
+
+    async fn record_action(&self, action: BrowserAction) {
+        self.record_step(action, false).await;
+    }
+
+    /// Record text typed into the focused element, masking it when the
+    /// element is a sensitive field or cannot be inspected.
+    async fn record_typing(&self, page: &Page, text: &str, target: Option<ElementTarget>) {
+        if !self.recording_enabled.load(Ordering::Relaxed) {
+            return;
+        }
+        let sensitive = Self::focused_input_is_sensitive(page).await.unwrap_or(true);
+        let action = BrowserAction::Type {
+            text: text.to_string(),
+            target,
+        };
+        self.record_step(action, sensitive).await;
+    }
+
+    async fn record_step(&self, action: BrowserAction, sensitive_input: bool) {
+        if !self.recording_enabled.load(Ordering::Relaxed) {
+            return;
+        }
+        let page_url = self.get_current_url().await;
+        let viewport = self.config.read().await.viewport.clone();
+        let mut recorder = self.recorder.lock().await;
+        recorder
+            .get_or_insert_with(|| {
+                ActionRecorder::new(crate::recording::new_recording_path(), Some(viewport))
+            })
+            .record(RecordedStep { action, page_url }, sensitive_input);
+    }
+
+    /// Whether the focused element is a password, payment-card or one-time
+    /// code field; `None` when it cannot be inspected.
+    async fn focused_input_is_sensitive(page: &Page) -> Option<bool> {
+        let result = page
+            .execute_cdp_raw(
+                "Runtime.evaluate",
+                serde_json::json!({
+                    "expression": "(() => { const el = document.activeElement; return el ? [el.getAttribute('type') || '', el.getAttribute('autocomplete') || ''] : null; })()",
+                    "returnByValue": true,
+                }),
+            )
+            .await
+            .ok()?;
+        let attrs = result.get("result")?.get("value")?.as_array()?;
+        let input_type = attrs.first()?.as_str()?;
+        let autocomplete = attrs.get(1)?.as_str()?;
+        Some(crate::recording::is_sensitive_input(
+            input_type,
+            autocomplete,
+        ))
+    }
+
+    /// Identity of the element behind a snapshot ref, for recording.
+    async fn element_target(&self, element_ref: &str) -> Option<ElementTarget> {
+        let refs = self.element_refs.lock().await;
+        refs.as_ref()?.get(element_ref).map(|entry| ElementTarget {
+            role: entry.role.clone(),
+            name: entry.name.clone(),
+            occurrence: entry.occurrence,
+        })
+    }
+
+    /// Take a fresh snapshot and return the ref currently pointing at `target`.
+    pub async fn find_element_ref(&self, target: &ElementTarget) -> Result<Option<String>> {
+        self.snapshot(None).await?;
+        let refs = self.element_refs.lock().await;
+        Ok(refs
+            .as_ref()
+            .and_then(|table| table.find(&target.role, &target.name, target.occurrence))
+            .map(str::to_string))
+    }
+
+    /// Capture an accessibility outline of the current page and mint element refs
+    /// that `click_ref`/`type_ref`/`select_ref` can target.
+    pub async fn snapshot(&self, max_nodes: Option<usize>) -> Result<PageSnapshot> {
+        let page = self.get_or_create_page().await?;
+        let (snapshot, table) = self
+            .capture_ref_table(
+                &page,
+                max_nodes.unwrap_or(crate::snapshot::DEFAULT_SNAPSHOT_MAX_NODES),
+            )
+            .await?;
+        *self.element_refs.lock().await = Some(table);
+        self.update_activity().await;
+        Ok(snapshot)
+    }
+
+    async fn capture_ref_table(
+        &self,
+        page: &Page,
+        max_nodes: usize,
+    ) -> Result<(PageSnapshot, ElementRefTable)> {
+        let _ = page
+            .execute_cdp_raw("Accessibility.enable", serde_json::json!({}))
+            .await;
+        let tree = page
+            .execute_cdp_raw("Accessibility.getFullAXTree", serde_json::json!({}))
+            .await?;
+        let url = page.get_current_url().await.unwrap_or_default();
+        let title = page.get_title().await;
+        let (snapshot, mut table) = crate::snapshot::build_snapshot(&tree, &url, title, max_nodes);
+        table.document_id = Self::document_id(page).await;
+        Ok((snapshot, table))
+    }
+
+    /// Backend node id of the root of the page's current document. It changes
+    /// whenever the page navigates or reloads.
+    async fn document_id(page: &Page) -> Option<i64> {
+        page.execute_cdp_raw("DOM.getDocument", serde_json::json!({ "depth": 0 }))
+            .await
+            .ok()?
+            .get("root")?
+            .get("backendNodeId")?
+            .as_i64()
+    }
+
+    /// Resolve an element ref to a live backend node id. Refs never follow the
+    /// page: after a navigation, reload or re-render that detached the node,
+    /// the whole table is dropped and the caller must take a new snapshot.
+    async fn resolve_element_ref(&self, page: &Page, element_ref: &str) -> Result<i64> {
+        let current_url = page.get_current_url().await.unwrap_or_default();
+        let document_id = Self::document_id(page).await;
+        let mut refs_guard = self.element_refs.lock().await;
+        let table = refs_guard.as_ref().ok_or_else(|| {
+            BrowserError::ElementRefError(
+                "No snapshot has been taken yet; call the snapshot action first".to_string(),
+            )
+        })?;
+        let entry = match table.lookup(element_ref, &current_url, document_id) {
+            Ok(entry) => entry.clone(),
+            Err(RefLookupError::Unknown) => {
+                return Err(BrowserError::ElementRefError(format!(
+                    "Unknown element ref '{element_ref}'; take a new snapshot"
+                )));
+            }
+            Err(RefLookupError::Stale) => {
+                debug!(
+                    element_ref,
+                    from = %table.url,
+                    to = %current_url,
+                    "Page changed since the snapshot; dropping element refs"
+                );
+                *refs_guard = None;
+                return Err(BrowserError::ElementRefError(format!(
+                    "Element ref '{element_ref}' is stale: the page navigated or reloaded since the snapshot; take a new snapshot"
+                )));
+            }
+        };
+
+        let described = page
+            .execute_cdp_raw(
+                "DOM.describeNode",
+                serde_json::json!({ "backendNodeId": entry.backend_node_id }),
+            )
+            .await;
+        if described.is_err() {
+            *refs_guard = None;
+            return Err(BrowserError::ElementRefError(format!(
+                "Element ref '{element_ref}' ({} \"{}\") is stale: the page changed since the snapshot; take a new snapshot",
+                entry.role, entry.name
+            )));
+        }
+        Ok(entry.backend_node_id)
+    }
+
+    /// Click the center of the element identified by a snapshot ref.
+    /// Returns the viewport coordinates that were clicked.
+    pub async fn click_ref(&self, element_ref: &str) -> Result<(f64, f64)> {
+        let page = self.get_or_create_page().await?;
+        let backend_node_id = self.resolve_element_ref(&page, element_ref).await?;
+        let _ = page
+            .execute_cdp_raw(
+                "DOM.scrollIntoViewIfNeeded",
+                serde_json::json!({ "backendNodeId": backend_node_id }),
+            )
+            .await;
+        let model = page
+            .execute_cdp_raw(
+                "DOM.getBoxModel",
+                serde_json::json!({ "backendNodeId": backend_node_id }),
+            )
+            .await
+            .map_err(|e| {
+                BrowserError::ElementRefError(format!(
+                    "Element ref '{element_ref}' has no layout box (hidden or detached): {e}"
+                ))
+            })?;
+        let quad: Vec<f64> = model
+            .get("model")
+            .and_then(|m| m.get("content"))
+            .and_then(Value::as_array)
+            .map(|points| points.iter().filter_map(Value::as_f64).collect())
+            .unwrap_or_default();
+        if quad.len() < 8 {
+            return Err(BrowserError::ElementRefError(format!(
+                "Element ref '{element_ref}' returned an invalid box model"
+            )));
+        }
+        let x = (quad[0] + quad[2] + quad[4] + quad[6]) / 4.0;
+        let y = (quad[1] + quad[3] + quad[5] + quad[7]) / 4.0;
+        page.click(x, y).await?;
+        self.update_activity().await;
+        let target = self.element_target(element_ref).await;
+        self.record_action(BrowserAction::Click { x, y, target }).await;
+        Ok((x, y))
+    }
+
+    /// Focus the element identified by a snapshot ref and type text into it.
+    pub async fn type_ref(&self, element_ref: &str, text: &str) -> Result<()> {
+        let page = self.get_or_create_page().await?;
+        let backend_node_id = self.resolve_element_ref(&page, element_ref).await?;
+        let _ = page
+            .execute_cdp_raw(
+                "DOM.scrollIntoViewIfNeeded",
+                serde_json::json!({ "backendNodeId": backend_node_id }),
+            )
+            .await;
+        page.execute_cdp_raw(
+            "DOM.focus",
+            serde_json::json!({ "backendNodeId": backend_node_id }),
+        )
+        .await
+        .map_err(|e| {
+            BrowserError::ElementRefError(format!(
+                "Element ref '{element_ref}' cannot receive focus: {e}"
+            ))
+        })?;
+        page.type_text(text).await?;
+        self.update_activity().await;
+        let target = self.element_target(element_ref).await;
+        self.record_typing(&page, text, target).await;
+        Ok(())
+    }
+
+    /// Choose options in the `<select>` identified by a snapshot ref, matching by
+    /// option value first and visible label second. Returns the selected values.
+    pub async fn select_ref(&self, element_ref: &str, values: &[String]) -> Result<Vec<String>> {
+        let page = self.get_or_create_page().await?;
+        let backend_node_id = self.resolve_element_ref(&page, element_ref).await?;
+        let resolved = page
+            .execute_cdp_raw(
+                "DOM.resolveNode",
+                serde_json::json!({ "backendNodeId": backend_node_id }),
+            )
+            .await?;
+        let object_id = resolved
+            .get("object")
+            .and_then(|o| o.get("objectId"))
+            .and_then(Value::as_str)
+            .ok_or_else(|| {
+                BrowserError::ElementRefError(format!(
+                    "Element ref '{element_ref}' could not be resolved to a JS object"
+                ))
+            })?
+            .to_string();
+        let result = page
+            .execute_cdp_raw(
+                "Runtime.callFunctionOn",
+                serde_json::json!({
+                    "objectId": object_id,
+                    "functionDeclaration": crate::snapshot::SELECT_OPTIONS_JS,
+                    "arguments": [{ "value": values }],
+                    "returnByValue": true,
+                    "awaitPromise": false,
+                }),
+            )
+            .await?;
+        if let Some(details) = result.get("exceptionDetails") {
+            let message = details
+                .get("exception")
+                .and_then(|e| e.get("description"))
+                .and_then(Value::as_str)
+                .or_else(|| details.get("text").and_then(Value::as_str))
+                .unwrap_or("select failed");
+            return Err(BrowserError::ElementRefError(message.to_string()));
+        }
+        let selected = result
+            .get("result")
+            .and_then(|r| r.get("value"))
+            .and_then(Value::as_array)
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|v| v.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.update_activity().await;
+        if let Some(target) = self.element_target(element_ref).await {
+            self.record_action(BrowserAction::Select {
+                values: values.to_vec(),
+                target,
+            })
+            .await;
+        }
+        Ok(selected)
+    }
+
+    /// Requests captured on the active tab, oldest first.
+    pub async fn network_requests(&self, filter: &NetworkFilter) -> Result<Vec<NetworkEntry>> {
+        let page = self.get_or_create_page().await?;
+        Ok(page.network_entries(filter).await)
+    }
+
+    /// Drop captured requests on the active tab.
+    pub async fn clear_network_requests(&self) -> Result<()> {
+        let page = self.get_or_create_page().await?;
+        page.clear_network_log().await;
+        Ok(())
+    }
+
+    /// Fetch the response body for a request captured on the active tab.
+    /// Returns the body and whether it is base64-encoded.
+    pub async fn network_response_body(&self, request_id: &str) -> Result<(String, bool)> {
+        let page = self.get_or_create_page().await?;
+        let body = page.get_response_body(request_id).await?;
+        self.update_activity().await;
+        Ok(body)
     }
 
     /// Capture console logs from the browser, including errors and unhandled rejections
@@ -2217,41 +2819,41 @@ impl BrowserManager {
     }
 
     /// Start monitoring for page navigation changes
-    async fn start_navigation_monitor(&self, page: Arc<Page>) {
+    async fn start_navigation_monitor(&self) {
         // Stop any existing monitor
         self.stop_navigation_monitor().await;
 
         let navigation_callback = Arc::clone(&self.navigation_callback);
-        let page_target_id = page.target_id_debug();
-        let page_session_id = page.session_id_debug();
-        let page_weak = Arc::downgrade(&page);
+        let active_page = Arc::clone(&self.page);
 
         let assets_arc = Arc::clone(&self.assets);
         let config_arc = Arc::clone(&self.config);
         let handle = tokio::spawn(async move {
             let mut last_url = String::new();
+            let mut last_target_id = String::new();
             let mut last_seq: u64 = 0;
             let mut _check_count = 0; // reserved for future periodic checks
 
-            debug!(
-                target_id = %page_target_id,
-                session_id = %page_session_id,
-                "Starting navigation monitor"
-            );
+            debug!("Starting navigation monitor");
 
             loop {
-                // Check if page is still alive
-                let page = match page_weak.upgrade() {
-                    Some(p) => p,
-                    None => {
-                        debug!(
-                            target_id = %page_target_id,
-                            session_id = %page_session_id,
-                            "Page dropped, stopping navigation monitor"
-                        );
-                        break;
-                    }
+                // Follow the active tab; there is none while a tab is being
+                // opened or after the last one closed.
+                let Some(page) = active_page.lock().await.clone() else {
+                    tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+                    continue;
                 };
+                let target_id = page.target_id();
+                if target_id != last_target_id {
+                    debug!(
+                        target_id = %page.target_id_debug(),
+                        session_id = %page.session_id_debug(),
+                        "Navigation monitor following tab"
+                    );
+                    last_target_id = target_id;
+                    // The SPA listener counter belongs to the other tab's document.
+                    last_seq = 0;
+                }
 
                 // Get current URL
                 if let Ok(current_url) = page.get_current_url().await {
@@ -2368,6 +2970,7 @@ impl BrowserManager {
                 }
 
                 // periodic counter disabled; listener-based SPA detection in place
+                drop(page);
 
                 // Check every 500ms
                 tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
@@ -2389,10 +2992,11 @@ impl BrowserManager {
     /// Start a low-frequency viewport monitor that checks for drift without forcing resyncs.
     /// Applies the same logic to internal and external: only correct after two consecutive
     /// mismatches and at most once per minute to avoid jank. Logs when throttled.
-    async fn start_viewport_monitor(&self, page: Arc<Page>) {
+    async fn start_viewport_monitor(&self) {
         // Stop any existing monitor first
         self.stop_viewport_monitor().await;
 
+        let active_page = Arc::clone(&self.page);
         let config_arc = Arc::clone(&self.config);
         let correction_enabled = Arc::clone(&self.auto_viewport_correction_enabled);
         let handle = tokio::spawn(async move {
@@ -2405,6 +3009,9 @@ impl BrowserManager {
 
             loop {
                 tokio::time::sleep(check_interval).await;
+                let Some(page) = active_page.lock().await.clone() else {
+                    continue;
+                };
 
                 // Snapshot expected config
                 let cfg = config_arc.read().await.clone();
@@ -2491,6 +3098,28 @@ impl BrowserManager {
     }
 }
 
+/// An open browser tab, identified by its CDP target id.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TabInfo {
+    pub target_id: String,
+    pub opener_id: Option<String>,
+    pub url: String,
+    pub title: Option<String>,
+    pub active: bool,
+}
+
+impl TabInfo {
+    /// Leading characters of the target id; enough to address a tab in practice.
+    pub fn short_id(&self) -> &str {
+        short_tab_id(&self.target_id)
+    }
+}
+
+/// Shorten a CDP target id for display. Tab actions accept any unique prefix.
+pub fn short_tab_id(target_id: &str) -> &str {
+    target_id.get(..8).unwrap_or(target_id)
+}
+
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct BrowserStatus {
     pub enabled: bool,
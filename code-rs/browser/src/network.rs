@@ -0,0 +1,650 @@
+//! Network request capture for browser pages.
+//!
+//! Every [`crate::Page`] owns a [`NetworkLog`] that is fed from CDP
+//! `Network.*` events, the same way console output is collected. When
+//! `record_network` is enabled, completed requests are also appended to a
+//! per-process JSONL journal under `$CODE_HOME/browser/network/` so
+//! `code browser har` can export the session as a HAR 1.2 file after the fact.
+//! Credential headers are redacted before anything reaches the journal.
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::sync::mpsc;
+use tracing::debug;
+
+/// Maximum number of requests kept in memory per page.
+pub const NETWORK_BUFFER_LIMIT: usize = 1000;
+
+/// Number of session journals retained in the capture directory.
+const JOURNAL_RETENTION: usize = 20;
+
+/// Headers whose values are replaced before an entry is journaled.
+const CREDENTIAL_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "x-api-key",
+    "x-auth-token",
+];
+
+const REDACTED: &str = "[redacted]";
+
+/// Response half of a captured request.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetworkResponse {
+    pub status: i64,
+    pub status_text: String,
+    pub mime_type: String,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_ip: Option<String>,
+    #[serde(default)]
+    pub from_cache: bool,
+    /// Phase breakdown in milliseconds, when Chrome reported resource timing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timings: Option<NetworkTimings>,
+}
+
+/// HAR-style timing phases in milliseconds; `-1` marks a phase that did not apply.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct NetworkTimings {
+    pub dns: f64,
+    pub connect: f64,
+    pub ssl: f64,
+    pub send: f64,
+    pub wait: f64,
+    pub receive: f64,
+}
+
+impl NetworkTimings {
+    /// Derive phases from CDP `ResourceTiming` offsets (all relative to `requestTime`).
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_offsets(
+        dns_start: f64,
+        dns_end: f64,
+        connect_start: f64,
+        connect_end: f64,
+        ssl_start: f64,
+        ssl_end: f64,
+        send_start: f64,
+        send_end: f64,
+        receive_headers_end: f64,
+    ) -> Self {
+        let span = |start: f64, end: f64| {
+            if start >= 0.0 && end >= start {
+                end - start
+            } else {
+                -1.0
+            }
+        };
+        Self {
+            dns: span(dns_start, dns_end),
+            connect: span(connect_start, connect_end),
+            ssl: span(ssl_start, ssl_end),
+            send: span(send_start, send_end).max(0.0),
+            wait: span(send_end, receive_headers_end).max(0.0),
+            receive: 0.0,
+        }
+    }
+}
+
+/// A single captured request/response exchange.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkEntry {
+    pub request_id: String,
+    pub tab_id: String,
+    pub url: String,
+    pub method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource_type: Option<String>,
+    #[serde(default)]
+    pub request_headers: BTreeMap<String, String>,
+    /// Wall-clock start time (Unix epoch milliseconds).
+    pub started_unix_ms: f64,
+    /// CDP monotonic timestamp of the request, used to compute durations.
+    #[serde(default, skip_serializing)]
+    pub started_ts: f64,
+    /// Offset of the response headers from the request start, in milliseconds.
+    #[serde(default, skip_serializing)]
+    pub response_ms: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<NetworkResponse>,
+    /// Total time until the body finished loading or the request failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<f64>,
+    /// Bytes transferred over the wire, including headers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transfer_size: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Location the server redirected this request to, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redirect_url: Option<String>,
+}
+
+impl NetworkEntry {
+    pub fn new(
+        request_id: impl Into<String>,
+        tab_id: impl Into<String>,
+        method: impl Into<String>,
+        url: impl Into<String>,
+        started_unix_ms: f64,
+        started_ts: f64,
+    ) -> Self {
+        Self {
+            request_id: request_id.into(),
+            tab_id: tab_id.into(),
+            url: url.into(),
+            method: method.into(),
+            resource_type: None,
+            request_headers: BTreeMap::new(),
+            started_unix_ms,
+            started_ts,
+            response_ms: None,
+            response: None,
+            duration_ms: None,
+            transfer_size: None,
+            error: None,
+            redirect_url: None,
+        }
+    }
+
+    /// True once the request finished loading, failed, or was redirected.
+    pub fn is_complete(&self) -> bool {
+        self.duration_ms.is_some()
+    }
+
+    /// True for transport failures and HTTP error statuses.
+    pub fn is_error(&self) -> bool {
+        self.error.is_some() || self.response.as_ref().is_some_and(|r| r.status >= 400)
+    }
+
+    fn complete(&mut self, timestamp: f64) {
+        let duration = ((timestamp - self.started_ts) * 1000.0).max(0.0);
+        self.duration_ms = Some(duration);
+        let response_ms = self.response_ms;
+        if let Some(timings) = self.response.as_mut().and_then(|r| r.timings.as_mut())
+            && let Some(response_ms) = response_ms
+        {
+            timings.receive = (duration - response_ms).max(0.0);
+        }
+    }
+}
+
+/// Selection criteria for [`NetworkLog::entries`].
+#[derive(Debug, Clone, Default)]
+pub struct NetworkFilter {
+    /// Substring of the URL, or a glob when it contains `*`.
+    pub url_pattern: Option<String>,
+    /// Only include failed requests and HTTP error statuses.
+    pub errors_only: bool,
+    /// Return at most this many of the most recent matches.
+    pub limit: Option<usize>,
+}
+
+impl NetworkFilter {
+    pub fn matches(&self, entry: &NetworkEntry) -> bool {
+        if self.errors_only && !entry.is_error() {
+            return false;
+        }
+        match self.url_pattern.as_deref() {
+            Some(pattern) if !pattern.is_empty() => url_matches(pattern, &entry.url),
+            _ => true,
+        }
+    }
+}
+
+/// Match a URL against a substring, or a `*` glob anchored at both ends.
+pub fn url_matches(pattern: &str, url: &str) -> bool {
+    if !pattern.contains('*') {
+        return url.contains(pattern);
+    }
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = url.strip_prefix(first) else {
+        return false;
+    };
+    let remaining: Vec<&str> = parts.collect();
+    let Some((last, middle)) = remaining.split_last() else {
+        return true;
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// Bounded per-page request log.
+#[derive(Debug, Default)]
+pub struct NetworkLog {
+    entries: VecDeque<NetworkEntry>,
+    journal: Option<NetworkJournal>,
+}
+
+impl NetworkLog {
+    pub fn new(journal: Option<NetworkJournal>) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            journal,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Record `Network.requestWillBeSent`. When `redirect` carries the
+    /// response that caused a redirect, the previous hop is closed out first.
+    pub fn request_started(&mut self, entry: NetworkEntry, redirect: Option<NetworkResponse>) {
+        if let Some(redirect) = redirect {
+            let timestamp = entry.started_ts;
+            let next_url = entry.url.clone();
+            if let Some(prev) = self.pending_mut(&entry.request_id) {
+                prev.response = Some(redirect);
+                prev.redirect_url = Some(next_url);
+                prev.complete(timestamp);
+                let finished = prev.clone();
+                self.append_journal(&finished);
+            }
+        }
+        self.entries.push_back(entry);
+        while self.entries.len() > NETWORK_BUFFER_LIMIT {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Record `Network.responseReceived`.
+    pub fn response_received(&mut self, request_id: &str, timestamp: f64, response: NetworkResponse) {
+        if let Some(entry) = self.pending_mut(request_id) {
+            entry.response_ms = Some(((timestamp - entry.started_ts) * 1000.0).max(0.0));
+            entry.response = Some(response);
+        }
+    }
+
+    /// Record `Network.loadingFinished`.
+    pub fn loading_finished(&mut self, request_id: &str, timestamp: f64, encoded_data_length: f64) {
+        let finished = self.pending_mut(request_id).map(|entry| {
+            entry.transfer_size = Some(encoded_data_length);
+            entry.complete(timestamp);
+            entry.clone()
+        });
+        if let Some(entry) = finished {
+            self.append_journal(&entry);
+        }
+    }
+
+    /// Record `Network.loadingFailed`.
+    pub fn loading_failed(&mut self, request_id: &str, timestamp: f64, error_text: String) {
+        let failed = self.pending_mut(request_id).map(|entry| {
+            entry.error = Some(error_text);
+            entry.complete(timestamp);
+            entry.clone()
+        });
+        if let Some(entry) = failed {
+            self.append_journal(&entry);
+        }
+    }
+
+    /// Matching entries, oldest first, keeping only the newest `filter.limit`.
+    pub fn entries(&self, filter: &NetworkFilter) -> Vec<NetworkEntry> {
+        let mut matched: Vec<NetworkEntry> = self
+            .entries
+            .iter()
+            .filter(|entry| filter.matches(entry))
+            .cloned()
+            .collect();
+        if let Some(limit) = filter.limit
+            && matched.len() > limit
+        {
+            matched.drain(..matched.len() - limit);
+        }
+        matched
+    }
+
+    /// Latest entry for `request_id` (redirect chains share one id).
+    pub fn get(&self, request_id: &str) -> Option<&NetworkEntry> {
+        self.entries.iter().rev().find(|entry| entry.request_id == request_id)
+    }
+
+    fn pending_mut(&mut self, request_id: &str) -> Option<&mut NetworkEntry> {
+        self.entries
+            .iter_mut()
+            .rev()
+            .find(|entry| entry.request_id == request_id && !entry.is_complete())
+    }
+
+    fn append_journal(&self, entry: &NetworkEntry) {
+        if let Some(journal) = self.journal.as_ref() {
+            journal.append(entry);
+        }
+    }
+}
+
+/// Handle to a JSONL journal. Lines are written by a background thread, so
+/// callers (which hold the page's log lock) never wait on disk I/O.
+#[derive(Debug, Clone)]
+pub struct NetworkJournal {
+    tx: mpsc::Sender<String>,
+}
+
+impl NetworkJournal {
+    /// Start a writer appending to `path`. The file is created on the first
+    /// write, readable only by the current user.
+    pub fn open(path: PathBuf) -> Option<Self> {
+        let (tx, rx) = mpsc::channel::<String>();
+        std::thread::Builder::new()
+            .name("browser-network-journal".to_string())
+            .spawn(move || {
+                let mut file = None;
+                for line in rx {
+                    if file.is_none() {
+                        match crate::storage::open_private_append(&path) {
+                            Ok(opened) => file = Some(opened),
+                            Err(err) => {
+                                debug!("Failed to open network journal {}: {}", path.display(), err);
+                                continue;
+                            }
+                        }
+                    }
+                    if let Some(file) = file.as_mut()
+                        && let Err(err) = file.write_all(line.as_bytes())
+                    {
+                        debug!("Failed to append network journal {}: {}", path.display(), err);
+                    }
+                }
+            })
+            .map_err(|err| debug!("Failed to start network journal writer: {}", err))
+            .ok()?;
+        Some(Self { tx })
+    }
+
+    fn append(&self, entry: &NetworkEntry) {
+        let mut entry = entry.clone();
+        redact_credentials(&mut entry);
+        let Ok(mut line) = serde_json::to_string(&entry) else {
+            return;
+        };
+        line.push('\n');
+        let _ = self.tx.send(line);
+    }
+}
+
+/// Replace the values of authorization and cookie headers.
+fn redact_credentials(entry: &mut NetworkEntry) {
+    let response_headers = entry.response.as_mut().map(|response| &mut response.headers);
+    for headers in std::iter::once(&mut entry.request_headers).chain(response_headers) {
+        for (name, value) in headers.iter_mut() {
+            if CREDENTIAL_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+                *value = REDACTED.to_string();
+            }
+        }
+    }
+}
+
+/// Convert CDP `Network.Headers` JSON into a sorted name/value map.
+pub fn headers_from_json(headers: &Value) -> BTreeMap<String, String> {
+    let Some(map) = headers.as_object() else {
+        return BTreeMap::new();
+    };
+    map.iter()
+        .map(|(name, value)| {
+            let value = match value {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            (name.clone(), value)
+        })
+        .collect()
+}
+
+/// Directory holding network journals for `code_home`.
+pub fn capture_dir(code_home: &Path) -> PathBuf {
+    code_home.join("browser").join("network")
+}
+
+/// Journal shared by every page in this process, started on first use.
+/// Returns `None` when no writable home directory is available.
+pub fn session_journal() -> Option<NetworkJournal> {
+    static JOURNAL: OnceLock<Option<NetworkJournal>> = OnceLock::new();
+    JOURNAL
+        .get_or_init(|| {
+            let dir = capture_dir(&crate::storage::default_code_home()?);
+            let path = crate::storage::new_session_file(&dir, "jsonl", JOURNAL_RETENTION)?;
+            NetworkJournal::open(path)
+        })
+        .clone()
+}
+
+/// Journals in `dir`, newest first.
+pub fn list_journals(dir: &Path) -> Vec<PathBuf> {
+    crate::storage::files_newest_first(dir, "jsonl")
+}
+
+/// Read a JSONL journal written by [`NetworkLog`], skipping malformed lines.
+pub fn read_journal(path: &Path) -> std::io::Result<Vec<NetworkEntry>> {
+    let contents = std::fs::read_to_string(path)?;
+    Ok(contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Build a HAR 1.2 document from captured entries.
+pub fn to_har(entries: &[NetworkEntry]) -> Value {
+    let har_entries: Vec<Value> = entries.iter().map(har_entry).collect();
+    json!({
+        "log": {
+            "version": "1.2",
+            "creator": { "name": "code", "version": env!("CARGO_PKG_VERSION") },
+            "pages": [],
+            "entries": har_entries,
+        }
+    })
+}
+
+fn har_headers(headers: &BTreeMap<String, String>) -> Vec<Value> {
+    headers
+        .iter()
+        .flat_map(|(name, value)| {
+            // CDP folds repeated headers into one newline-separated value.
+            value
+                .split('\n')
+                .map(move |v| json!({ "name": name, "value": v }))
+        })
+        .collect()
+}
+
+fn har_query_string(url: &str) -> Vec<Value> {
+    url::Url::parse(url)
+        .map(|parsed| {
+            parsed
+                .query_pairs()
+                .map(|(name, value)| json!({ "name": name, "value": value }))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn har_entry(entry: &NetworkEntry) -> Value {
+    let started = chrono::DateTime::from_timestamp_millis(entry.started_unix_ms as i64)
+        .unwrap_or_default()
+        .to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+    let response = entry.response.clone().unwrap_or_default();
+    let http_version = response.protocol.clone().unwrap_or_else(|| "HTTP/1.1".to_string());
+    let transfer = entry.transfer_size.unwrap_or(-1.0);
+    let total = entry.duration_ms.unwrap_or(0.0);
+    let timings = response.timings.unwrap_or(NetworkTimings {
+        dns: -1.0,
+        connect: -1.0,
+        ssl: -1.0,
+        send: 0.0,
+        wait: total,
+        receive: 0.0,
+    });
+
+    let mut value = json!({
+        "startedDateTime": started,
+        "time": total,
+        "request": {
+            "method": entry.method,
+            "url": entry.url,
+            "httpVersion": http_version,
+            "cookies": [],
+            "headers": har_headers(&entry.request_headers),
+            "queryString": har_query_string(&entry.url),
+            "headersSize": -1,
+            "bodySize": -1,
+        },
+        "response": {
+            "status": response.status,
+            "statusText": response.status_text,
+            "httpVersion": http_version,
+            "cookies": [],
+            "headers": har_headers(&response.headers),
+            "content": { "size": transfer.max(0.0), "mimeType": response.mime_type },
+            "redirectURL": entry.redirect_url.clone().unwrap_or_default(),
+            "headersSize": -1,
+            "bodySize": transfer,
+        },
+        "cache": {},
+        "timings": {
+            "blocked": -1,
+            "dns": timings.dns,
+            "connect": timings.connect,
+            "ssl": timings.ssl,
+            "send": timings.send,
+            "wait": timings.wait,
+            "receive": timings.receive,
+        },
+    });
+    if let Some(obj) = value.as_object_mut() {
+        if let Some(ip) = response.remote_ip {
+            obj.insert("serverIPAddress".to_string(), Value::String(ip));
+        }
+        if let Some(resource_type) = entry.resource_type.clone() {
+            obj.insert("_resourceType".to_string(), Value::String(resource_type));
+        }
+        if let Some(error) = entry.error.clone() {
+            obj.insert("_error".to_string(), Value::String(error));
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: i64) -> NetworkResponse {
+        NetworkResponse {
+            status,
+            status_text: "OK".to_string(),
+            mime_type: "application/json".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn tracks_request_lifecycle_and_redirects() {
+        let mut log = NetworkLog::new(None);
+        log.request_started(NetworkEntry::new("1", "tab", "GET", "http://a.test/old", 1_000.0, 10.0), None);
+        log.request_started(
+            NetworkEntry::new("1", "tab", "GET", "http://a.test/new", 1_050.0, 10.05),
+            Some(response(302)),
+        );
+        log.response_received("1", 10.1, response(200));
+        log.loading_finished("1", 10.2, 512.0);
+        log.request_started(NetworkEntry::new("2", "tab", "POST", "http://a.test/api", 1_100.0, 10.1), None);
+        log.loading_failed("2", 10.3, "net::ERR_CONNECTION_REFUSED".to_string());
+
+        let all = log.entries(&NetworkFilter::default());
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].redirect_url.as_deref(), Some("http://a.test/new"));
+        assert_eq!(all[1].response.as_ref().map(|r| r.status), Some(200));
+        assert_eq!(all[1].transfer_size, Some(512.0));
+        assert!(all[1].duration_ms.is_some_and(|d| (d - 150.0).abs() < 1e-6));
+
+        let errors = log.entries(&NetworkFilter {
+            errors_only: true,
+            ..Default::default()
+        });
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].request_id, "2");
+
+        let har = to_har(&all);
+        assert_eq!(har["log"]["version"], "1.2");
+        assert_eq!(har["log"]["entries"][2]["_error"], "net::ERR_CONNECTION_REFUSED");
+    }
+
+    #[test]
+    fn journal_redacts_credentials_and_is_private() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.jsonl");
+        let mut log = NetworkLog::new(NetworkJournal::open(path.clone()));
+
+        let mut entry = NetworkEntry::new("1", "tab", "GET", "http://a.test/me", 1_000.0, 10.0);
+        entry.request_headers.insert("Authorization".to_string(), "Bearer secret".to_string());
+        entry.request_headers.insert("Accept".to_string(), "*/*".to_string());
+        log.request_started(entry, None);
+        let mut reply = response(200);
+        reply.headers.insert("set-cookie".to_string(), "session=secret".to_string());
+        log.response_received("1", 10.1, reply);
+        log.loading_finished("1", 10.2, 64.0);
+        drop(log);
+
+        let mut journaled = Vec::new();
+        for _ in 0..100 {
+            journaled = read_journal(&path).unwrap_or_default();
+            if !journaled.is_empty() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(journaled.len(), 1);
+        let entry = &journaled[0];
+        assert_eq!(entry.request_headers["Authorization"], REDACTED);
+        assert_eq!(entry.request_headers["Accept"], "*/*");
+        assert_eq!(entry.response.as_ref().unwrap().headers["set-cookie"], REDACTED);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn url_patterns_support_substrings_and_globs() {
+        let url = "https://example.com/api/v1/users?id=3";
+        assert!(url_matches("/api/", url));
+        assert!(url_matches("https://example.com/api/*", url));
+        assert!(url_matches("*users*", url));
+        assert!(!url_matches("*.png", url));
+        assert!(!url_matches("http://*", url));
+    }
+}
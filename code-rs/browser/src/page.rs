@@ -19,6 +19,12 @@ use chromiumoxide::cdp::browser_protocol::page::CaptureScreenshotParams;
 use chromiumoxide::page::Page as CdpPage;
 use chromiumoxide::cdp::js_protocol::runtime as cdp_runtime;
 use chromiumoxide::cdp::browser_protocol::log as cdp_log;
+use chromiumoxide::cdp::browser_protocol::network as cdp_network;
+use crate::network::NetworkEntry;
+use crate::network::NetworkFilter;
+use crate::network::NetworkLog;
+use crate::network::NetworkResponse;
+use crate::network::NetworkTimings;
 use futures::StreamExt;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -51,6 +57,8 @@ pub struct Page {
     cursor_state: Arc<Mutex<CursorState>>,
     // Buffer for CDP-captured console logs
     console_logs: Arc<Mutex<Vec<serde_json::Value>>>,
+    // Requests captured from CDP Network.* events
+    network_log: Arc<Mutex<NetworkLog>>,
     // Screenshot path preflight cache:
     // - We strongly prefer compositor captures via from_surface(false) to avoid visible flashes in the
     //   user's real Chrome window. However, that path can be flaky or unavailable when the window is not
@@ -72,6 +80,11 @@ impl Page {
             is_mouse_down: false,
         };
 
+        let journal = if config.record_network {
+            crate::network::session_journal()
+        } else {
+            None
+        };
         let page = Self {
             cdp_page: Arc::new(cdp_page),
             config,
@@ -79,15 +92,17 @@ impl Page {
             cursor_state: Arc::new(Mutex::new(initial_cursor)),
             preflight_cache: Arc::new(Mutex::new(None)),
             console_logs: Arc::new(Mutex::new(Vec::new())),
+            network_log: Arc::new(Mutex::new(NetworkLog::new(journal))),
         };
 
         // Register a unified bootstrap (runs on every new document):
-        //  - Blocks _blank/tab opens
+        //  - Blocks _blank/tab opens (unless `allow_new_tabs` is set)
         //  - Installs minimal virtual cursor early
         //  - Hooks SPA history to signal route changes
         let cdp_page_boot = page.cdp_page.clone();
+        let allow_new_tabs = page.config.allow_new_tabs;
         tokio::spawn(async move {
-            if let Err(e) = Self::inject_bootstrap_script(&cdp_page_boot).await {
+            if let Err(e) = Self::inject_bootstrap_script(&cdp_page_boot, allow_new_tabs).await {
                 warn!("Failed to inject unified bootstrap script: {}", e);
             } else {
                 debug!("Unified bootstrap script registered for new documents");
@@ -156,9 +171,93 @@ impl Page {
             }
         });
 
+        // Enable the Network domain and record request/response lifecycles.
+        let cdp_page_net = page.cdp_page.clone();
+        let network_buf = page.network_log.clone();
+        let tab_id = page.target_id();
+        tokio::spawn(async move {
+            Self::capture_network_events(cdp_page_net, network_buf, tab_id).await;
+        });
+
         page
     }
 
+    async fn capture_network_events(
+        cdp_page: Arc<CdpPage>,
+        log: Arc<Mutex<NetworkLog>>,
+        tab_id: String,
+    ) {
+        enum NetEvent {
+            Request(Arc<cdp_network::EventRequestWillBeSent>),
+            Response(Arc<cdp_network::EventResponseReceived>),
+            Finished(Arc<cdp_network::EventLoadingFinished>),
+            Failed(Arc<cdp_network::EventLoadingFailed>),
+        }
+
+        if let Err(e) = cdp_page.execute(cdp_network::EnableParams::default()).await {
+            debug!("Network capture unavailable: {}", e);
+            return;
+        }
+        let (Ok(requests), Ok(responses), Ok(finished), Ok(failed)) = (
+            cdp_page.event_listener::<cdp_network::EventRequestWillBeSent>().await,
+            cdp_page.event_listener::<cdp_network::EventResponseReceived>().await,
+            cdp_page.event_listener::<cdp_network::EventLoadingFinished>().await,
+            cdp_page.event_listener::<cdp_network::EventLoadingFailed>().await,
+        ) else {
+            debug!("Failed to subscribe to network events");
+            return;
+        };
+
+        let mut events = futures::stream::select_all(vec![
+            requests.map(NetEvent::Request).boxed(),
+            responses.map(NetEvent::Response).boxed(),
+            finished.map(NetEvent::Finished).boxed(),
+            failed.map(NetEvent::Failed).boxed(),
+        ]);
+        while let Some(event) = events.next().await {
+            let mut log = log.lock().await;
+            match event {
+                NetEvent::Request(evt) => {
+                    let mut entry = NetworkEntry::new(
+                        evt.request_id.inner().clone(),
+                        tab_id.clone(),
+                        evt.request.method.clone(),
+                        evt.request.url.clone(),
+                        *evt.wall_time.inner() * 1000.0,
+                        *evt.timestamp.inner(),
+                    );
+                    entry.resource_type = evt.r#type.as_ref().map(|t| t.as_ref().to_string());
+                    entry.request_headers =
+                        crate::network::headers_from_json(evt.request.headers.inner());
+                    let redirect = evt.redirect_response.as_ref().map(network_response);
+                    log.request_started(entry, redirect);
+                }
+                NetEvent::Response(evt) => {
+                    log.response_received(
+                        evt.request_id.inner(),
+                        *evt.timestamp.inner(),
+                        network_response(&evt.response),
+                    );
+                }
+                NetEvent::Finished(evt) => {
+                    log.loading_finished(
+                        evt.request_id.inner(),
+                        *evt.timestamp.inner(),
+                        evt.encoded_data_length,
+                    );
+                }
+                NetEvent::Failed(evt) => {
+                    let error = if evt.canceled.unwrap_or(false) {
+                        format!("{} (canceled)", evt.error_text)
+                    } else {
+                        evt.error_text.clone()
+                    };
+                    log.loading_failed(evt.request_id.inner(), *evt.timestamp.inner(), error);
+                }
+            }
+        }
+    }
+
     /// Ensure the virtual cursor is present; inject if missing, then update to current position.
     async fn ensure_virtual_cursor(&self) -> Result<bool> {
         // Desired runtime version of the virtual cursor script
@@ -298,14 +397,14 @@ impl Page {
 
     /// Injects a unified bootstrap for each new document: tab blocking + cursor bootstrap + SPA hooks
     /// and early console capture so tools like `browser_console` can read logs reliably.
-    async fn inject_bootstrap_script(cdp_page: &Arc<CdpPage>) -> Result<()> {
+    async fn inject_bootstrap_script(cdp_page: &Arc<CdpPage>, allow_new_tabs: bool) -> Result<()> {
         // This script installs the full virtual cursor on DOM ready for each new document.
-        // It also prevents _blank tabs, hooks SPA history changes, and installs
-        // console/error capture early so logs accumulate from the start of the page.
+        // It also prevents _blank tabs (unless `allow_new_tabs` is set), hooks SPA history
+        // changes, and installs console/error capture early so logs accumulate from the start.
         let script = r#"
 (function(){
   // 1) Tab blocking: override window.open + intercept target="_blank"
-  try {
+  if (!window.__code_allow_new_tabs) try {
     const originalOpen = window.open;
     const openProxy = new Proxy(originalOpen, {
       apply(_t, _this, args) {
@@ -483,6 +582,11 @@ impl Page {
 })();
 "#;
 
+        let script = if allow_new_tabs {
+            format!("window.__code_allow_new_tabs = true;\n{script}")
+        } else {
+            script.to_string()
+        };
         let params = AddScriptToEvaluateOnNewDocumentParams::new(script);
         cdp_page.execute(params).await?;
         Ok(())
@@ -1443,6 +1547,31 @@ impl Page {
         serde_json::Value::Array(slice)
     }
 
+    /// Captured network requests matching `filter`, oldest first.
+    pub async fn network_entries(&self, filter: &NetworkFilter) -> Vec<NetworkEntry> {
+        self.network_log.lock().await.entries(filter)
+    }
+
+    /// Drop all captured network requests for this page.
+    pub async fn clear_network_log(&self) {
+        self.network_log.lock().await.clear();
+    }
+
+    /// Fetch a response body via `Network.getResponseBody`. Returns the body
+    /// and whether it is base64-encoded.
+    pub async fn get_response_body(&self, request_id: &str) -> Result<(String, bool)> {
+        if self.network_log.lock().await.get(request_id).is_none() {
+            return Err(BrowserError::CdpError(format!(
+                "Unknown network request id: {request_id}"
+            )));
+        }
+        let returns = self
+            .cdp_page
+            .execute(cdp_network::GetResponseBodyParams::new(request_id.to_string()))
+            .await?;
+        Ok((returns.result.body.clone(), returns.result.base64_encoded))
+    }
+
     pub async fn get_url(&self) -> Result<String> {
         let url_guard = self.current_url.read().await;
         url_guard.clone().ok_or(BrowserError::PageNotLoaded)
@@ -1473,6 +1602,25 @@ impl Page {
             .map(|opener_id| format!("{opener_id:?}"))
     }
 
+    /// CDP target id of the tab backing this page.
+    pub fn target_id(&self) -> String {
+        self.cdp_page.target_id().inner().clone()
+    }
+
+    /// CDP target id of the tab that opened this one (popups, `target=_blank`).
+    pub fn opener_id(&self) -> Option<String> {
+        self.cdp_page
+            .opener_id()
+            .as_ref()
+            .map(|opener_id| opener_id.inner().clone())
+    }
+
+    /// Activate this tab in the browser window.
+    pub async fn bring_to_front(&self) -> Result<()> {
+        self.cdp_page.bring_to_front().await?;
+        Ok(())
+    }
+
     pub async fn update_viewport(&self, _viewport: ViewportConfig) -> Result<()> {
         Ok(())
     }
@@ -2280,3 +2428,31 @@ pub struct ViewportResult {
     pub height: u32,
     pub dpr: f64,
 }
+
+fn network_response(response: &cdp_network::Response) -> NetworkResponse {
+    let timings = response.timing.as_ref().map(|t| {
+        NetworkTimings::from_offsets(
+            t.dns_start,
+            t.dns_end,
+            t.connect_start,
+            t.connect_end,
+            t.ssl_start,
+            t.ssl_end,
+            t.send_start,
+            t.send_end,
+            t.receive_headers_end,
+        )
+    });
+    NetworkResponse {
+        status: response.status,
+        status_text: response.status_text.clone(),
+        mime_type: response.mime_type.clone(),
+        headers: crate::network::headers_from_json(response.headers.inner()),
+        protocol: response.protocol.clone(),
+        remote_ip: response.remote_ip_address.clone(),
+        from_cache: response.from_disk_cache.unwrap_or(false)
+            || response.from_service_worker.unwrap_or(false)
+            || response.from_prefetch_cache.unwrap_or(false),
+        timings,
+    }
+}
@@ -0,0 +1,594 @@
+//! Replayable browser action scripts.
+//!
+//! `BrowserManager` appends every user-visible action (navigation, clicks,
+//! typing, keys, scrolling, scripts) to an [`ActionScript`] saved as JSON lines
+//! under `$CODE_HOME/browser/recordings/`: a header line followed by one line
+//! per step. Text typed into password, payment-card and one-time-code fields
+//! is masked before it is written; see [`is_sensitive_input`]. `code browser replay
+//! <file>` re-runs a script in a headless browser via [`replay_script`],
+//! screenshotting each step and reporting where the page diverged from the
+//! recording. Hand-written scripts may also use a single JSON document.
+
+use crate::BrowserError;
+use crate::Result;
+use crate::config::ViewportConfig;
+use crate::manager::BrowserManager;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::Instant;
+use tracing::debug;
+
+/// Current on-disk script format.
+pub const SCRIPT_VERSION: u32 = 1;
+
+/// Number of recordings retained in the recordings directory.
+const RECORDING_RETENTION: usize = 20;
+
+/// Extension of recordings written by [`ActionRecorder`].
+const RECORDING_EXTENSION: &str = "jsonl";
+
+/// Stands in for text typed into a sensitive field. Replace it in the script
+/// to replay the step.
+pub const MASKED_TEXT: &str = "<masked>";
+
+/// Whether text typed into a field with these `type` and `autocomplete`
+/// attributes must be kept out of recordings: passwords (`type=password`,
+/// `autocomplete=*-password`), payment cards (`cc-*`) and one-time codes.
+pub fn is_sensitive_input(input_type: &str, autocomplete: &str) -> bool {
+    input_type.eq_ignore_ascii_case("password")
+        || autocomplete.split_whitespace().any(|token| {
+            let token = token.to_ascii_lowercase();
+            token.ends_with("-password") || token.starts_with("cc-") || token == "one-time-code"
+        })
+}
+
+/// How long replay waits for the URL to settle on the recorded value.
+const URL_SETTLE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Stable description of an element, taken from the accessibility snapshot
+/// ref that was acted on. Replay re-snapshots and looks the element up again.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ElementTarget {
+    pub role: String,
+    pub name: String,
+    #[serde(default)]
+    pub occurrence: usize,
+}
+
+/// A single browser action.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum BrowserAction {
+    Goto {
+        url: String,
+    },
+    Click {
+        x: f64,
+        y: f64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        target: Option<ElementTarget>,
+    },
+    Type {
+        text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        target: Option<ElementTarget>,
+    },
+    Select {
+        values: Vec<String>,
+        target: ElementTarget,
+    },
+    Key {
+        key: String,
+    },
+    Scroll {
+        dx: f64,
+        dy: f64,
+    },
+    HistoryBack,
+    HistoryForward,
+    Javascript {
+        code: String,
+    },
+    /// Hand-written check: `script` must evaluate to a truthy value.
+    Assert {
+        script: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+    Wait {
+        ms: u64,
+    },
+}
+
+impl BrowserAction {
+    /// One-line, human-readable description used in replay reports.
+    pub fn describe(&self) -> String {
+        let target = |t: &Option<ElementTarget>| match t {
+            Some(t) => format!(" {} \"{}\"", t.role, t.name),
+            None => String::new(),
+        };
+        match self {
+            BrowserAction::Goto { url } => format!("goto {url}"),
+            BrowserAction::Click { x, y, target: t } => {
+                format!("click ({x:.0}, {y:.0}){}", target(t))
+            }
+            BrowserAction::Type { text, target: t } => format!("type {text:?}{}", target(t)),
+            BrowserAction::Select { values, target: t } => {
+                format!("select {} in {} \"{}\"", values.join(", "), t.role, t.name)
+            }
+            BrowserAction::Key { key } => format!("key {key}"),
+            BrowserAction::Scroll { dx, dy } => format!("scroll ({dx:.0}, {dy:.0})"),
+            BrowserAction::HistoryBack => "history back".to_string(),
+            BrowserAction::HistoryForward => "history forward".to_string(),
+            BrowserAction::Javascript { code } => {
+                let first = code.lines().next().unwrap_or_default();
+                format!("javascript {}", truncate(first, 60))
+            }
+            BrowserAction::Assert { script, message } => match message {
+                Some(message) => format!("assert {message}"),
+                None => format!("assert {}", truncate(script, 60)),
+            },
+            BrowserAction::Wait { ms } => format!("wait {ms} ms"),
+        }
+    }
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        text.to_string()
+    } else {
+        let head: String = text.chars().take(max).collect();
+        format!("{head}…")
+    }
+}
+
+/// An action plus the page URL observed right after it ran.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedStep {
+    #[serde(flatten)]
+    pub action: BrowserAction,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_url: Option<String>,
+}
+
+/// A recorded browser session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionScript {
+    pub version: u32,
+    #[serde(default)]
+    pub recorded_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub viewport: Option<ViewportConfig>,
+    #[serde(default)]
+    pub steps: Vec<RecordedStep>,
+}
+
+impl ActionScript {
+    pub fn new(viewport: Option<ViewportConfig>) -> Self {
+        Self {
+            version: SCRIPT_VERSION,
+            recorded_at: chrono::Local::now().to_rfc3339(),
+            viewport,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Load a script written either as a single JSON document or as JSON
+    /// lines (a header line, then one line per step).
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        let script = match serde_json::from_str::<Self>(&contents) {
+            Ok(script) => script,
+            Err(_) => Self::parse_lines(&contents).map_err(|e| {
+                BrowserError::ConfigError(format!("invalid action script {}: {e}", path.display()))
+            })?,
+        };
+        if script.version > SCRIPT_VERSION {
+            return Err(BrowserError::ConfigError(format!(
+                "action script version {} is newer than supported version {SCRIPT_VERSION}",
+                script.version
+            )));
+        }
+        Ok(script)
+    }
+
+    fn parse_lines(contents: &str) -> serde_json::Result<Self> {
+        let mut lines = contents.lines().filter(|line| !line.trim().is_empty());
+        let mut script: Self = serde_json::from_str(lines.next().unwrap_or_default())?;
+        for line in lines {
+            script.steps.push(serde_json::from_str(line)?);
+        }
+        Ok(script)
+    }
+}
+
+/// Accumulates steps for the current browser session and appends each one to
+/// the script file on disk.
+#[derive(Debug)]
+pub struct ActionRecorder {
+    path: Option<PathBuf>,
+    script: ActionScript,
+    header_written: bool,
+}
+
+impl ActionRecorder {
+    pub fn new(path: Option<PathBuf>, viewport: Option<ViewportConfig>) -> Self {
+        Self {
+            path,
+            script: ActionScript::new(viewport),
+            header_written: false,
+        }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn script(&self) -> &ActionScript {
+        &self.script
+    }
+
+    /// Append `step`. When `sensitive_input` is set, typed text is replaced
+    /// with [`MASKED_TEXT`].
+    pub fn record(&mut self, mut step: RecordedStep, sensitive_input: bool) {
+        if sensitive_input && let BrowserAction::Type { text, .. } = &mut step.action {
+            *text = MASKED_TEXT.to_string();
+        }
+        if let Some(path) = self.path.as_ref() {
+            let mut lines = String::new();
+            if !self.header_written {
+                let header = ActionScript {
+                    steps: Vec::new(),
+                    ..self.script.clone()
+                };
+                push_json_line(&mut lines, &header);
+            }
+            push_json_line(&mut lines, &step);
+            let written = crate::storage::open_private_append(path)
+                .and_then(|mut file| file.write_all(lines.as_bytes()));
+            match written {
+                Ok(()) => self.header_written = true,
+                Err(err) => {
+                    debug!(
+                        "Failed to append action recording {}: {}",
+                        path.display(),
+                        err
+                    );
+                }
+            }
+        }
+        self.script.steps.push(step);
+    }
+}
+
+fn push_json_line<T: Serialize>(out: &mut String, value: &T) {
+    match serde_json::to_string(value) {
+        Ok(line) => {
+            out.push_str(&line);
+            out.push('\n');
+        }
+        Err(err) => debug!("Failed to serialize action recording: {}", err),
+    }
+}
+
+/// Directory holding action recordings for `code_home`.
+pub fn recordings_dir(code_home: &Path) -> PathBuf {
+    code_home.join("browser").join("recordings")
+}
+
+/// Fresh recording path for a new browser session.
+pub(crate) fn new_recording_path() -> Option<PathBuf> {
+    let dir = recordings_dir(&crate::storage::default_code_home()?);
+    crate::storage::new_session_file(&dir, RECORDING_EXTENSION, RECORDING_RETENTION)
+}
+
+/// Recordings in `dir`, newest first.
+pub fn list_recordings(dir: &Path) -> Vec<PathBuf> {
+    crate::storage::files_newest_first(dir, RECORDING_EXTENSION)
+}
+
+/// Options for [`replay_script`].
+#[derive(Debug, Clone, Default)]
+pub struct ReplayOptions {
+    /// Directory to write per-step screenshots into.
+    pub screenshot_dir: Option<PathBuf>,
+    /// Stop at the first divergence instead of running the remaining steps.
+    pub stop_on_divergence: bool,
+}
+
+/// Outcome of one replayed step.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayStepReport {
+    pub index: usize,
+    pub action: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Why the step did not match the recording, if it did not.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub divergence: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub screenshot: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReplayReport {
+    pub steps: Vec<ReplayStepReport>,
+}
+
+impl ReplayReport {
+    pub fn divergences(&self) -> usize {
+        self.steps.iter().filter(|s| s.divergence.is_some()).count()
+    }
+}
+
+/// Compare URLs ignoring a trailing slash.
+fn urls_match(expected: &str, actual: &str) -> bool {
+    expected.trim_end_matches('/') == actual.trim_end_matches('/')
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(_) | Value::Object(_) => true,
+    }
+}
+
+async fn resolve_target(manager: &BrowserManager, target: &ElementTarget) -> Result<String> {
+    manager.find_element_ref(target).await?.ok_or_else(|| {
+        BrowserError::ElementRefError(format!(
+            "element {} \"{}\" (#{}) not found",
+            target.role, target.name, target.occurrence
+        ))
+    })
+}
+
+async fn run_action(manager: &BrowserManager, action: &BrowserAction) -> Result<Option<String>> {
+    match action {
+        BrowserAction::Goto { url } => {
+            manager.goto(url).await?;
+        }
+        BrowserAction::Click { x, y, target } => match target {
+            Some(target) => {
+                let element_ref = resolve_target(manager, target).await?;
+                manager.click_ref(&element_ref).await?;
+            }
+            None => manager.click(*x, *y).await?,
+        },
+        BrowserAction::Type { text, .. } if text == MASKED_TEXT => {
+            return Ok(Some(format!(
+                "typed text was masked when recorded; replace {MASKED_TEXT:?} in the script"
+            )));
+        }
+        BrowserAction::Type { text, target } => match target {
+            Some(target) => {
+                let element_ref = resolve_target(manager, target).await?;
+                manager.type_ref(&element_ref, text).await?;
+            }
+            None => manager.type_text(text).await?,
+        },
+        BrowserAction::Select { values, target } => {
+            let element_ref = resolve_target(manager, target).await?;
+            manager.select_ref(&element_ref, values).await?;
+        }
+        BrowserAction::Key { key } => manager.press_key(key).await?,
+        BrowserAction::Scroll { dx, dy } => manager.scroll_by(*dx, *dy).await?,
+        BrowserAction::HistoryBack => manager.history_back().await?,
+        BrowserAction::HistoryForward => manager.history_forward().await?,
+        BrowserAction::Javascript { code } => {
+            let result = manager.execute_javascript(code).await?;
+            if result.get("success").and_then(Value::as_bool) == Some(false) {
+                let error = result
+                    .get("error")
+                    .and_then(Value::as_str)
+                    .unwrap_or("script failed");
+                return Ok(Some(format!("javascript error: {error}")));
+            }
+        }
+        BrowserAction::Assert { script, message } => {
+            let result = manager.execute_javascript(script).await?;
+            let passed = result.get("success").and_then(Value::as_bool) == Some(true)
+                && result.get("value").is_some_and(is_truthy);
+            if !passed {
+                let detail = result
+                    .get("error")
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+                    .unwrap_or_else(|| {
+                        format!("got {}", result.get("value").cloned().unwrap_or(Value::Null))
+                    });
+                let label = message.as_deref().unwrap_or(script);
+                return Ok(Some(format!("assertion failed: {label} ({detail})")));
+            }
+        }
+        BrowserAction::Wait { ms } => tokio::time::sleep(Duration::from_millis(*ms)).await,
+    }
+    Ok(None)
+}
+
+/// Wait briefly for client-side navigation to land on `expected`.
+async fn settle_url(manager: &BrowserManager, expected: &str) -> Option<String> {
+    let deadline = Instant::now() + URL_SETTLE_TIMEOUT;
+    loop {
+        let current = manager.get_current_url().await;
+        let settled = current.as_deref().is_some_and(|url| urls_match(expected, url));
+        if settled || Instant::now() >= deadline {
+            return current;
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+}
+
+/// Re-run `script` against `manager`, which should be a dedicated (typically
+/// headless) instance. Action recording is disabled on `manager` for the run.
+pub async fn replay_script(
+    manager: &BrowserManager,
+    script: &ActionScript,
+    options: &ReplayOptions,
+) -> Result<ReplayReport> {
+    manager.set_action_recording(false);
+    if let Some(dir) = options.screenshot_dir.as_ref() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let mut report = ReplayReport::default();
+    for (index, step) in script.steps.iter().enumerate() {
+        let mut divergence = match run_action(manager, &step.action).await {
+            Ok(divergence) => divergence,
+            Err(err) => Some(format!("action failed: {err}")),
+        };
+
+        let url = match step.page_url.as_deref() {
+            Some(expected) => settle_url(manager, expected).await,
+            None => manager.get_current_url().await,
+        };
+        if divergence.is_none()
+            && let (Some(expected), Some(actual)) = (step.page_url.as_deref(), url.as_deref())
+            && !urls_match(expected, actual)
+        {
+            divergence = Some(format!("URL changed: expected {expected}, got {actual}"));
+        }
+
+        let mut screenshot = None;
+        if let Some(dir) = options.screenshot_dir.as_ref() {
+            match manager.capture_screenshot().await {
+                Ok(paths) => {
+                    if let Some(src) = paths.first() {
+                        let ext = src.extension().and_then(|e| e.to_str()).unwrap_or("png");
+                        let dest = dir.join(format!("step-{:03}.{ext}", index + 1));
+                        if std::fs::copy(src, &dest).is_ok() {
+                            screenshot = Some(dest);
+                        }
+                    }
+                }
+                Err(err) => debug!("Replay screenshot for step {} failed: {}", index + 1, err),
+            }
+        }
+
+        let diverged = divergence.is_some();
+        report.steps.push(ReplayStepReport {
+            index: index + 1,
+            action: step.action.describe(),
+            url,
+            divergence,
+            screenshot,
+        });
+        if diverged && options.stop_on_divergence {
+            break;
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripts_round_trip_through_json() {
+        let script = ActionScript {
+            version: SCRIPT_VERSION,
+            recorded_at: String::new(),
+            viewport: None,
+            steps: vec![
+                RecordedStep {
+                    action: BrowserAction::Goto {
+                        url: "https://example.com".to_string(),
+                    },
+                    page_url: Some("https://example.com/".to_string()),
+                },
+                RecordedStep {
+                    action: BrowserAction::Click {
+                        x: 10.0,
+                        y: 20.0,
+                        target: Some(ElementTarget {
+                            role: "button".to_string(),
+                            name: "Sign in".to_string(),
+                            occurrence: 0,
+                        }),
+                    },
+                    page_url: None,
+                },
+                RecordedStep {
+                    action: BrowserAction::HistoryBack,
+                    page_url: None,
+                },
+            ],
+        };
+        let json = serde_json::to_value(&script).unwrap_or_default();
+        assert_eq!(json["steps"][0]["action"], "goto");
+        assert_eq!(json["steps"][1]["target"]["name"], "Sign in");
+        assert_eq!(json["steps"][2]["action"], "history_back");
+
+        let parsed: ActionScript = serde_json::from_value(json).unwrap_or_else(|e| panic!("{e}"));
+        assert_eq!(parsed.steps, script.steps);
+    }
+
+    #[test]
+    fn recorder_masks_only_sensitive_fields() {
+        let dir = tempfile::tempdir().unwrap_or_else(|e| panic!("{e}"));
+        let path = dir.path().join("session.jsonl");
+        let mut recorder = ActionRecorder::new(Some(path.clone()), None);
+        let typed = |text: &str| RecordedStep {
+            action: BrowserAction::Type {
+                text: text.to_string(),
+                target: None,
+            },
+            page_url: None,
+        };
+        recorder.record(
+            RecordedStep {
+                action: BrowserAction::Goto {
+                    url: "https://example.com".to_string(),
+                },
+                page_url: None,
+            },
+            false,
+        );
+        recorder.record(
+            typed("ada@example.com"),
+            is_sensitive_input("email", "email"),
+        );
+        recorder.record(
+            typed("hunter2"),
+            is_sensitive_input("password", "current-password"),
+        );
+
+        let contents = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{e}"));
+        assert_eq!(contents.lines().count(), 4);
+        assert!(!contents.contains("hunter2"));
+
+        let loaded = ActionScript::load(&path).unwrap_or_else(|e| panic!("{e}"));
+        assert_eq!(loaded.steps, recorder.script().steps);
+        assert_eq!(loaded.steps[1].action, typed("ada@example.com").action);
+        assert_eq!(loaded.steps[2].action, typed(MASKED_TEXT).action);
+    }
+
+    #[test]
+    fn sensitive_inputs_are_recognized() {
+        assert!(is_sensitive_input("PASSWORD", ""));
+        assert!(is_sensitive_input("text", "new-password"));
+        assert!(is_sensitive_input("text", "shipping cc-number"));
+        assert!(is_sensitive_input("text", "one-time-code"));
+        assert!(!is_sensitive_input("text", "username"));
+        assert!(!is_sensitive_input("search", ""));
+    }
+
+    #[test]
+    fn hand_written_assert_steps_parse() {
+        let parsed: RecordedStep = serde_json::from_str(
+            r#"{"action":"assert","script":"document.title === 'Home'","message":"title is Home"}"#,
+        )
+        .unwrap_or_else(|e| panic!("{e}"));
+        assert_eq!(parsed.action.describe(), "assert title is Home");
+        assert!(urls_match("https://a.test/", "https://a.test"));
+        assert!(!is_truthy(&Value::String(String::new())));
+    }
+}
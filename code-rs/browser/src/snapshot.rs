@@ -0,0 +1,434 @@
+//! Accessibility-tree snapshots with stable element refs.
+//!
+//! A snapshot walks the CDP `Accessibility.getFullAXTree` result and renders a
+//! compact outline. Interactive nodes receive short refs (`e1`, `e2`, ...) that
+//! the agent can pass back to `click`/`type`/`select` instead of guessing pixel
+//! coordinates. Each ref remembers the backend DOM node id it was minted for
+//! and is only valid for the document it was minted against: once the page
+//! navigates or reloads, every ref is rejected until a new snapshot is taken.
+
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Default cap on rendered outline lines so dense pages stay readable.
+pub const DEFAULT_SNAPSHOT_MAX_NODES: usize = 500;
+
+/// Longest accessible name rendered in the outline before truncation.
+const MAX_NAME_CHARS: usize = 80;
+
+/// Roles that the agent can act on and therefore receive a ref.
+const INTERACTIVE_ROLES: &[&str] = &[
+    "button",
+    "checkbox",
+    "combobox",
+    "link",
+    "listbox",
+    "menuitem",
+    "menuitemcheckbox",
+    "menuitemradio",
+    "option",
+    "radio",
+    "searchbox",
+    "slider",
+    "spinbutton",
+    "switch",
+    "tab",
+    "textbox",
+    "treeitem",
+];
+
+/// Structural roles that only add noise unless they carry a name.
+const TRANSPARENT_ROLES: &[&str] = &[
+    "generic",
+    "none",
+    "presentation",
+    "GenericContainer",
+    "LineBreak",
+    "InlineTextBox",
+    "RootWebArea",
+    "WebArea",
+    "Section",
+    "paragraph",
+    "group",
+    "div",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElementRefEntry {
+    pub backend_node_id: i64,
+    pub role: String,
+    pub name: String,
+    /// Zero-based index among nodes sharing the same role and name, in document order.
+    pub occurrence: usize,
+}
+
+/// Refs minted by the most recent snapshot of a page.
+#[derive(Debug, Clone, Default)]
+pub struct ElementRefTable {
+    /// URL of the document the refs were minted against.
+    pub url: String,
+    /// Backend node id of the root of that document, when known.
+    pub document_id: Option<i64>,
+    entries: HashMap<String, ElementRefEntry>,
+}
+
+impl ElementRefTable {
+    pub fn get(&self, element_ref: &str) -> Option<&ElementRefEntry> {
+        self.entries.get(element_ref)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Ref of the node with the given role, name and occurrence, if any.
+    pub fn find(&self, role: &str, name: &str, occurrence: usize) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(_, e)| e.role == role && e.name == name && e.occurrence == occurrence)
+            .map(|(element_ref, _)| element_ref.as_str())
+    }
+
+    /// Entry for `element_ref`, provided the page still shows the document the
+    /// refs were minted against. `document_id` identifies the current document
+    /// (the backend node id of its root), so a reload of the same URL also
+    /// invalidates the refs.
+    pub fn lookup(
+        &self,
+        element_ref: &str,
+        url: &str,
+        document_id: Option<i64>,
+    ) -> Result<&ElementRefEntry, RefLookupError> {
+        if url != self.url || document_id != self.document_id {
+            return Err(RefLookupError::Stale);
+        }
+        self.get(element_ref).ok_or(RefLookupError::Unknown)
+    }
+}
+
+/// Why a ref could not be resolved against the current page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefLookupError {
+    /// The page navigated or reloaded since the snapshot.
+    Stale,
+    /// The snapshot minted no such ref.
+    Unknown,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PageSnapshot {
+    pub url: String,
+    pub title: Option<String>,
+    /// Indented outline, one node per line, with `[ref=eN]` on interactive nodes.
+    pub outline: String,
+    pub ref_count: usize,
+    pub truncated: bool,
+}
+
+struct AxNode<'a> {
+    raw: &'a Value,
+    role: String,
+    name: String,
+    ignored: bool,
+    children: Vec<&'a str>,
+}
+
+fn ax_string(node: &Value, key: &str) -> String {
+    node.get(key)
+        .and_then(|v| v.get("value"))
+        .map(|v| match v {
+            Value::String(s) => s.clone(),
+            Value::Null => String::new(),
+            other => other.to_string(),
+        })
+        .unwrap_or_default()
+}
+
+fn ax_property(node: &Value, name: &str) -> Option<Value> {
+    node.get("properties")?
+        .as_array()?
+        .iter()
+        .find(|p| p.get("name").and_then(Value::as_str) == Some(name))
+        .and_then(|p| p.get("value"))
+        .and_then(|v| v.get("value"))
+        .cloned()
+}
+
+fn truncate_name(name: &str) -> String {
+    let collapsed = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.chars().count() <= MAX_NAME_CHARS {
+        collapsed
+    } else {
+        let mut out: String = collapsed.chars().take(MAX_NAME_CHARS - 1).collect();
+        out.push('…');
+        out
+    }
+}
+
+/// Build an outline and ref table from a `Accessibility.getFullAXTree` response.
+pub fn build_snapshot(
+    ax_tree: &Value,
+    url: &str,
+    title: Option<String>,
+    max_nodes: usize,
+) -> (PageSnapshot, ElementRefTable) {
+    let empty = Vec::new();
+    let raw_nodes = ax_tree
+        .get("nodes")
+        .and_then(Value::as_array)
+        .unwrap_or(&empty);
+
+    let mut nodes: HashMap<&str, AxNode<'_>> = HashMap::new();
+    let mut root_id: Option<&str> = None;
+    for raw in raw_nodes {
+        let Some(id) = raw.get("nodeId").and_then(Value::as_str) else {
+            continue;
+        };
+        if root_id.is_none() && raw.get("parentId").is_none() {
+            root_id = Some(id);
+        }
+        let children = raw
+            .get("childIds")
+            .and_then(Value::as_array)
+            .map(|ids| ids.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        nodes.insert(
+            id,
+            AxNode {
+                raw,
+                role: ax_string(raw, "role"),
+                name: ax_string(raw, "name"),
+                ignored: raw.get("ignored").and_then(Value::as_bool).unwrap_or(false),
+                children,
+            },
+        );
+    }
+
+    let mut renderer = OutlineRenderer {
+        nodes: &nodes,
+        lines: Vec::new(),
+        table: ElementRefTable {
+            url: url.to_string(),
+            document_id: None,
+            entries: HashMap::new(),
+        },
+        occurrences: HashMap::new(),
+        max_nodes,
+        truncated: false,
+    };
+    if let Some(root) = root_id {
+        renderer.visit(root, 0, "");
+    }
+
+    let snapshot = PageSnapshot {
+        url: url.to_string(),
+        title,
+        outline: renderer.lines.join("\n"),
+        ref_count: renderer.table.len(),
+        truncated: renderer.truncated,
+    };
+    (snapshot, renderer.table)
+}
+
+struct OutlineRenderer<'a> {
+    nodes: &'a HashMap<&'a str, AxNode<'a>>,
+    lines: Vec<String>,
+    table: ElementRefTable,
+    occurrences: HashMap<(String, String), usize>,
+    max_nodes: usize,
+    truncated: bool,
+}
+
+impl OutlineRenderer<'_> {
+    fn visit(&mut self, id: &str, depth: usize, parent_name: &str) {
+        let nodes = self.nodes;
+        let Some(node) = nodes.get(id) else {
+            return;
+        };
+        if self.lines.len() >= self.max_nodes {
+            self.truncated = true;
+            return;
+        }
+
+        let name = truncate_name(&node.name);
+        let interactive = INTERACTIVE_ROLES.contains(&node.role.as_str());
+        let is_text = node.role == "StaticText";
+        let visible = !node.ignored
+            && if is_text {
+                !name.is_empty() && name != parent_name
+            } else {
+                interactive
+                    || !TRANSPARENT_ROLES.contains(&node.role.as_str())
+                    || (!name.is_empty() && node.role != "RootWebArea")
+            };
+
+        let child_depth = if visible {
+            self.emit(node, &name, depth, interactive);
+            depth + 1
+        } else {
+            depth
+        };
+
+        // Text children of named nodes usually repeat the name; skip them.
+        let name_for_children = if visible { name.as_str() } else { parent_name };
+        for child in &node.children {
+            self.visit(child, child_depth, name_for_children);
+        }
+    }
+
+    fn emit(&mut self, node: &AxNode<'_>, name: &str, depth: usize, interactive: bool) {
+        let indent = "  ".repeat(depth);
+        let role = if node.role == "StaticText" {
+            "text"
+        } else {
+            node.role.as_str()
+        };
+        let mut line = if name.is_empty() {
+            format!("{indent}- {role}")
+        } else {
+            format!("{indent}- {role} \"{name}\"")
+        };
+
+        let mut attrs: Vec<String> = Vec::new();
+        if let Some(level) = ax_property(node.raw, "level") {
+            attrs.push(format!("level={level}"));
+        }
+        for flag in ["checked", "selected", "expanded", "disabled"] {
+            match ax_property(node.raw, flag) {
+                Some(Value::Bool(true)) => attrs.push(flag.to_string()),
+                Some(Value::String(s)) if s == "true" || s == "mixed" => {
+                    attrs.push(format!("{flag}={s}"))
+                }
+                _ => {}
+            }
+        }
+        let value = ax_string(node.raw, "value");
+        if !value.is_empty() && value != name {
+            attrs.push(format!("value=\"{}\"", truncate_name(&value)));
+        }
+
+        if interactive
+            && let Some(backend_node_id) = node.raw.get("backendDOMNodeId").and_then(Value::as_i64)
+        {
+            let key = (node.role.clone(), name.to_string());
+            let occurrence = *self
+                .occurrences
+                .entry(key)
+                .and_modify(|n| *n += 1)
+                .or_insert(0);
+            let element_ref = format!("e{}", self.table.len() + 1);
+            attrs.push(format!("ref={element_ref}"));
+            self.table.entries.insert(
+                element_ref,
+                ElementRefEntry {
+                    backend_node_id,
+                    role: node.role.clone(),
+                    name: name.to_string(),
+                    occurrence,
+                },
+            );
+        }
+
+        if !attrs.is_empty() {
+            line.push_str(&format!(" [{}]", attrs.join(", ")));
+        }
+        self.lines.push(line);
+    }
+}
+
+/// JavaScript applied via `Runtime.callFunctionOn` to choose options in a `<select>`.
+/// Matches each requested value against option values first, then visible labels.
+pub(crate) const SELECT_OPTIONS_JS: &str = r#"function(values) {
+    if (!(this instanceof HTMLSelectElement)) {
+        throw new Error('Element is not a <select> (got <' + String(this.tagName || '?').toLowerCase() + '>)');
+    }
+    const wanted = Array.isArray(values) ? values.map(String) : [String(values)];
+    const options = Array.from(this.options);
+    const picked = [];
+    for (const want of wanted) {
+        const match = options.find(o => o.value === want) || options.find(o => o.label.trim() === want.trim());
+        if (!match) throw new Error('No option matching "' + want + '"');
+        picked.push(match);
+    }
+    if (!this.multiple && picked.length > 1) {
+        throw new Error('Cannot select multiple options in a single-select element');
+    }
+    for (const o of options) o.selected = picked.includes(o);
+    this.dispatchEvent(new Event('input', { bubbles: true }));
+    this.dispatchEvent(new Event('change', { bubbles: true }));
+    return picked.map(o => o.value);
+}"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample_tree() -> Value {
+        json!({
+            "nodes": [
+                { "nodeId": "1", "role": { "value": "RootWebArea" }, "name": { "value": "Login" }, "childIds": ["2", "3", "6"] },
+                { "nodeId": "2", "parentId": "1", "role": { "value": "heading" }, "name": { "value": "Sign in" },
+                  "properties": [{ "name": "level", "value": { "type": "integer", "value": 1 } }], "childIds": ["7"] },
+                { "nodeId": "3", "parentId": "1", "role": { "value": "generic" }, "name": { "value": "" }, "childIds": ["4", "5"] },
+                { "nodeId": "4", "parentId": "3", "role": { "value": "textbox" }, "name": { "value": "Email" }, "backendDOMNodeId": 40 },
+                { "nodeId": "5", "parentId": "3", "role": { "value": "button" }, "name": { "value": "Continue" }, "backendDOMNodeId": 50 },
+                { "nodeId": "6", "parentId": "1", "role": { "value": "button" }, "name": { "value": "Continue" }, "backendDOMNodeId": 60 },
+                { "nodeId": "7", "parentId": "2", "role": { "value": "StaticText" }, "name": { "value": "Sign in" } }
+            ]
+        })
+    }
+
+    #[test]
+    fn outline_flattens_generic_nodes_and_assigns_refs() {
+        let (snapshot, table) = build_snapshot(&sample_tree(), "https://x.test/login", None, 100);
+        assert_eq!(
+            snapshot.outline,
+            "- heading \"Sign in\" [level=1]\n- textbox \"Email\" [ref=e1]\n- button \"Continue\" [ref=e2]\n- button \"Continue\" [ref=e3]"
+        );
+        assert_eq!(snapshot.ref_count, 3);
+        assert!(!snapshot.truncated);
+        assert_eq!(table.get("e2").map(|e| e.backend_node_id), Some(50));
+        assert_eq!(table.get("e3").map(|e| e.occurrence), Some(1));
+    }
+
+    #[test]
+    fn outline_respects_node_cap() {
+        let (snapshot, _) = build_snapshot(&sample_tree(), "https://x.test/login", None, 2);
+        assert_eq!(snapshot.outline.lines().count(), 2);
+        assert!(snapshot.truncated);
+    }
+
+    #[test]
+    fn refs_are_rejected_after_navigation_or_reload() {
+        let (_, mut table) = build_snapshot(&sample_tree(), "https://x.test/login", None, 100);
+        table.document_id = Some(7);
+
+        assert_eq!(
+            table
+                .lookup("e2", "https://x.test/login", Some(7))
+                .map(|e| e.backend_node_id),
+            Ok(50)
+        );
+        assert_eq!(
+            table.lookup("e9", "https://x.test/login", Some(7)),
+            Err(RefLookupError::Unknown)
+        );
+
+        // Navigating away, even to a page with a matching "Continue" button,
+        // must not remap the old ref.
+        assert_eq!(
+            table.lookup("e2", "https://x.test/next", Some(8)),
+            Err(RefLookupError::Stale)
+        );
+        // A reload keeps the URL but replaces the document.
+        assert_eq!(
+            table.lookup("e2", "https://x.test/login", Some(8)),
+            Err(RefLookupError::Stale)
+        );
+    }
+}
@@ -0,0 +1,60 @@
+//! On-disk locations for browser session artifacts (network journals, action
+//! recordings) under `$CODE_HOME/browser/`.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Resolve the Code home directory the same way the rest of the CLI does:
+/// `$CODE_HOME`, then `$CODEX_HOME`, then `~/.code`.
+pub(crate) fn default_code_home() -> Option<PathBuf> {
+    std::env::var("CODE_HOME")
+        .or_else(|_| std::env::var("CODEX_HOME"))
+        .map(PathBuf::from)
+        .or_else(|_| std::env::var("HOME").map(|home| PathBuf::from(home).join(".code")))
+        .ok()
+}
+
+/// Create `dir`, prune it to the newest `keep - 1` files with `ext`, and
+/// return a fresh per-process file path inside it.
+pub(crate) fn new_session_file(dir: &Path, ext: &str, keep: usize) -> Option<PathBuf> {
+    std::fs::create_dir_all(dir).ok()?;
+    for stale in files_newest_first(dir, ext)
+        .into_iter()
+        .skip(keep.saturating_sub(1))
+    {
+        let _ = std::fs::remove_file(stale);
+    }
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let pid = std::process::id();
+    Some(dir.join(format!("{stamp}-{pid}.{ext}")))
+}
+
+/// Files in `dir` with extension `ext`, newest first.
+pub(crate) fn files_newest_first(dir: &Path, ext: &str) -> Vec<PathBuf> {
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<(std::time::SystemTime, PathBuf)> = read_dir
+        .filter_map(std::result::Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|e| e == ext))
+        .filter_map(|path| {
+            let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+            Some((modified, path))
+        })
+        .collect();
+    files.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    files.into_iter().map(|(_, path)| path).collect()
+}
+
+/// Open `path` for appending, creating it with mode 0600 on Unix.
+pub(crate) fn open_private_append(path: &Path) -> std::io::Result<std::fs::File> {
+    let mut options = std::fs::OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)
+}
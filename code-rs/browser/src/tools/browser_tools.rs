@@ -101,6 +101,89 @@ impl BrowserTools {
                 Ok(BrowserToolResult::SetViewport(result))
             }
 
+            BrowserToolCall::Snapshot { max_nodes } => {
+                let snapshot = self.manager.snapshot(max_nodes).await?;
+                Ok(BrowserToolResult::Snapshot(snapshot))
+            }
+
+            BrowserToolCall::Click { element_ref } => {
+                let (x, y) = self.manager.click_ref(&element_ref).await?;
+                Ok(BrowserToolResult::Click(ClickResult { element_ref, x, y }))
+            }
+
+            BrowserToolCall::Type { element_ref, text } => {
+                self.manager.type_ref(&element_ref, &text).await?;
+                Ok(BrowserToolResult::Type(TypeResult {
+                    element_ref,
+                    typed: text,
+                }))
+            }
+
+            BrowserToolCall::Select {
+                element_ref,
+                values,
+            } => {
+                let selected = self.manager.select_ref(&element_ref, &values).await?;
+                Ok(BrowserToolResult::Select(SelectResult {
+                    element_ref,
+                    selected,
+                }))
+            }
+
+            BrowserToolCall::Tabs { op, tab_id, url } => match op.as_deref() {
+                Some("list") | None => Ok(BrowserToolResult::Tabs(TabsResult {
+                    tabs: self.manager.list_tabs().await?,
+                })),
+                Some("open") => {
+                    let tab = self.manager.open_tab(url.as_deref()).await?;
+                    Ok(BrowserToolResult::Tabs(TabsResult { tabs: vec![tab] }))
+                }
+                Some("switch") => {
+                    let tab_id = tab_id.ok_or_else(|| {
+                        crate::BrowserError::ConfigError("switch requires tab_id".to_string())
+                    })?;
+                    let tab = self.manager.switch_tab(&tab_id).await?;
+                    Ok(BrowserToolResult::Tabs(TabsResult { tabs: vec![tab] }))
+                }
+                Some("close") => {
+                    let active = self.manager.close_tab(tab_id.as_deref()).await?;
+                    Ok(BrowserToolResult::Tabs(TabsResult {
+                        tabs: active.into_iter().collect(),
+                    }))
+                }
+                Some(other) => Err(crate::BrowserError::ConfigError(format!(
+                    "Unknown tabs operation: {other}"
+                ))),
+            },
+
+            BrowserToolCall::Network {
+                filter,
+                errors_only,
+                limit,
+                request_id,
+                clear,
+            } => {
+                if let Some(request_id) = request_id {
+                    let (body, base64_encoded) =
+                        self.manager.network_response_body(&request_id).await?;
+                    return Ok(BrowserToolResult::NetworkBody(NetworkBodyResult {
+                        request_id,
+                        body,
+                        base64_encoded,
+                    }));
+                }
+                let filter = crate::network::NetworkFilter {
+                    url_pattern: filter,
+                    errors_only: errors_only.unwrap_or(false),
+                    limit: Some(limit.unwrap_or(50)),
+                };
+                let requests = self.manager.network_requests(&filter).await?;
+                if clear.unwrap_or(false) {
+                    self.manager.clear_network_requests().await?;
+                }
+                Ok(BrowserToolResult::Network(NetworkResult { requests }))
+            }
+
             BrowserToolCall::Close { what } => match what.as_deref() {
                 Some("browser") => {
                     self.manager.stop().await?;
@@ -148,6 +231,45 @@ pub enum BrowserToolCall {
         mobile: Option<bool>,
     },
 
+    #[serde(rename = "browser.snapshot")]
+    Snapshot { max_nodes: Option<usize> },
+
+    #[serde(rename = "browser.click")]
+    Click {
+        #[serde(rename = "ref")]
+        element_ref: String,
+    },
+
+    #[serde(rename = "browser.type")]
+    Type {
+        #[serde(rename = "ref")]
+        element_ref: String,
+        text: String,
+    },
+
+    #[serde(rename = "browser.select")]
+    Select {
+        #[serde(rename = "ref")]
+        element_ref: String,
+        values: Vec<String>,
+    },
+
+    #[serde(rename = "browser.tabs")]
+    Tabs {
+        op: Option<String>,
+        tab_id: Option<String>,
+        url: Option<String>,
+    },
+
+    #[serde(rename = "browser.network")]
+    Network {
+        filter: Option<String>,
+        errors_only: Option<bool>,
+        limit: Option<usize>,
+        request_id: Option<String>,
+        clear: Option<bool>,
+    },
+
     #[serde(rename = "browser.close")]
     Close { what: Option<String> },
 }
@@ -166,6 +288,13 @@ pub enum BrowserToolResult {
     Goto(crate::page::GotoResult),
     Screenshot(ScreenshotResult),
     SetViewport(crate::page::ViewportResult),
+    Snapshot(crate::snapshot::PageSnapshot),
+    Click(ClickResult),
+    Type(TypeResult),
+    Select(SelectResult),
+    Tabs(TabsResult),
+    Network(NetworkResult),
+    NetworkBody(NetworkBodyResult),
     Close(CloseResult),
 }
 
@@ -174,6 +303,45 @@ pub struct ScreenshotResult {
     pub images: Vec<ImageRef>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct ClickResult {
+    #[serde(rename = "ref")]
+    pub element_ref: String,
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TypeResult {
+    #[serde(rename = "ref")]
+    pub element_ref: String,
+    pub typed: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SelectResult {
+    #[serde(rename = "ref")]
+    pub element_ref: String,
+    pub selected: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TabsResult {
+    pub tabs: Vec<crate::manager::TabInfo>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NetworkResult {
+    pub requests: Vec<crate::network::NetworkEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NetworkBodyResult {
+    pub request_id: String,
+    pub body: String,
+    pub base64_encoded: bool,
+}
+
 #[derive(Debug, Serialize)]
 pub struct CloseResult {
     pub closed: String,
@@ -109,6 +109,121 @@ pub fn get_browser_tools_schema() -> Vec<BrowserToolSchema> {
                 "required": ["width", "height"]
             }),
         },
+        BrowserToolSchema {
+            name: "browser.snapshot".to_string(),
+            description: "Capture an accessibility outline of the page with element refs for click/type/select".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "max_nodes": {
+                        "type": "number",
+                        "description": "Maximum number of outline lines (default: 500)"
+                    }
+                }
+            }),
+        },
+        BrowserToolSchema {
+            name: "browser.click".to_string(),
+            description: "Click the element identified by a snapshot ref".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "ref": {
+                        "type": "string",
+                        "description": "Element ref from browser.snapshot (e.g. e12)"
+                    }
+                },
+                "required": ["ref"]
+            }),
+        },
+        BrowserToolSchema {
+            name: "browser.type".to_string(),
+            description: "Focus the element identified by a snapshot ref and type text into it".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "ref": {
+                        "type": "string",
+                        "description": "Element ref from browser.snapshot (e.g. e12)"
+                    },
+                    "text": {
+                        "type": "string",
+                        "description": "Text to type"
+                    }
+                },
+                "required": ["ref", "text"]
+            }),
+        },
+        BrowserToolSchema {
+            name: "browser.select".to_string(),
+            description: "Choose options in the <select> identified by a snapshot ref".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "ref": {
+                        "type": "string",
+                        "description": "Element ref from browser.snapshot (e.g. e12)"
+                    },
+                    "values": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Option values or visible labels to select"
+                    }
+                },
+                "required": ["ref", "values"]
+            }),
+        },
+        BrowserToolSchema {
+            name: "browser.tabs".to_string(),
+            description: "List, open, switch or close browser tabs (popups and target=_blank pages included)".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "op": {
+                        "type": "string",
+                        "enum": ["list", "open", "switch", "close"],
+                        "description": "Tab operation (default: list)"
+                    },
+                    "tab_id": {
+                        "type": "string",
+                        "description": "CDP target id or unique prefix (switch/close; close defaults to the active tab)"
+                    },
+                    "url": {
+                        "type": "string",
+                        "description": "URL to load in the new tab (open)"
+                    }
+                }
+            }),
+        },
+        BrowserToolSchema {
+            name: "browser.network".to_string(),
+            description: "List network requests captured on the active tab, or fetch one response body".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "filter": {
+                        "type": "string",
+                        "description": "URL substring, or glob when it contains * (e.g. */api/*)"
+                    },
+                    "errors_only": {
+                        "type": "boolean",
+                        "description": "Only failed requests and HTTP status >= 400"
+                    },
+                    "limit": {
+                        "type": "number",
+                        "description": "Most recent requests to return (default: 50)"
+                    },
+                    "request_id": {
+                        "type": "string",
+                        "description": "Return the response body for this request instead of listing"
+                    },
+                    "clear": {
+                        "type": "boolean",
+                        "description": "Clear the captured requests after listing"
+                    }
+                }
+            }),
+        },
         BrowserToolSchema {
             name: "browser.close".to_string(),
             description: "Close the page or browser".to_string(),
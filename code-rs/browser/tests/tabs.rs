@@ -0,0 +1,199 @@
+//! Tab behaviour against a real headless Chrome. Skipped when no Chrome or
+//! Chromium executable can be found (set `CHROME` to point at one).
+
+use std::io::Read;
+use std::io::Write;
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chromiumoxide::detection::DetectionOptions;
+use chromiumoxide::detection::default_executable;
+use code_browser::BrowserConfig;
+use code_browser::BrowserManager;
+use code_browser::TabInfo;
+
+const LINK_PAGE: &str = r#"<body style="margin:0"><a href="/second" target="_blank" style="display:block;width:100vw;height:100vh">new tab</a></body>"#;
+const OPEN_PAGE: &str = r#"<body style="margin:0"><button onclick="window.open('/second')" style="width:100vw;height:100vh">open</button></body>"#;
+
+/// Serves `LINK_PAGE` at `/link`, `OPEN_PAGE` at `/open` and a page naming
+/// its own path anywhere else. Returns the base URL.
+fn serve_pages() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind test server");
+    let base = format!("http://{}", listener.local_addr().expect("server address"));
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                match stream.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => request.extend_from_slice(&buf[..n]),
+                }
+            }
+            let request = String::from_utf8_lossy(&request);
+            let path = request.split_whitespace().nth(1).unwrap_or("/").to_string();
+            let body = match path.as_str() {
+                "/link" => LINK_PAGE.to_string(),
+                "/open" => OPEN_PAGE.to_string(),
+                other => format!("<p>{other}</p>"),
+            };
+            let _ = write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+        }
+    });
+    base
+}
+
+fn launch(allow_new_tabs: bool) -> Option<BrowserManager> {
+    if default_executable(DetectionOptions::default()).is_err() {
+        eprintln!("Skipping test because no Chrome executable was found.");
+        return None;
+    }
+    Some(BrowserManager::new(BrowserConfig {
+        enabled: true,
+        headless: true,
+        allow_new_tabs,
+        ..BrowserConfig::default()
+    }))
+}
+
+/// Polls `list_tabs` until `done` holds, or gives up after ten seconds and
+/// returns the last listing.
+async fn wait_for_tabs(
+    browser: &BrowserManager,
+    done: impl Fn(&[TabInfo]) -> bool,
+) -> Vec<TabInfo> {
+    let mut tabs = Vec::new();
+    for _ in 0..50 {
+        tabs = browser.list_tabs().await.expect("list tabs");
+        if done(&tabs) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+    tabs
+}
+
+/// Clicks the full-page link or button of `page`. Returns the tabs before the
+/// click and once some tab has loaded `/second`.
+async fn click_through(
+    browser: &BrowserManager,
+    base: &str,
+    page: &str,
+) -> (Vec<TabInfo>, Vec<TabInfo>) {
+    browser.goto(&format!("{base}{page}")).await.expect("goto");
+    let before = browser.list_tabs().await.expect("list tabs");
+    browser.click(100.0, 100.0).await.expect("click");
+    let after = wait_for_tabs(browser, |tabs| {
+        tabs.iter().any(|tab| tab.url.ends_with("/second"))
+    })
+    .await;
+    (before, after)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn new_tabs_are_redirected_into_the_current_tab_unless_allowed() {
+    let Some(browser) = launch(false) else {
+        return;
+    };
+    let base = serve_pages();
+
+    for page in ["/link", "/open"] {
+        let (before, after) = click_through(&browser, &base, page).await;
+        assert_eq!(after.len(), before.len(), "{page} opened a tab: {after:?}");
+        let active = after.iter().find(|tab| tab.active).expect("active tab");
+        assert!(active.url.ends_with("/second"), "{page}: {after:?}");
+    }
+
+    browser.stop().await.expect("stop browser");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn new_tabs_open_when_allowed() {
+    let Some(browser) = launch(true) else {
+        return;
+    };
+    let base = serve_pages();
+
+    for page in ["/link", "/open"] {
+        let (before, after) = click_through(&browser, &base, page).await;
+        assert_eq!(after.len(), before.len() + 1, "{page}: {after:?}");
+        let active = after.iter().find(|tab| tab.active).expect("active tab");
+        assert!(active.url.ends_with(page), "{page}: {after:?}");
+        let opened = after
+            .iter()
+            .find(|tab| !before.iter().any(|b| b.target_id == tab.target_id))
+            .expect("opened tab");
+        assert!(opened.url.ends_with("/second"), "{page}: {after:?}");
+        assert_eq!(opened.opener_id, Some(active.target_id.clone()));
+
+        browser
+            .close_tab(Some(opened.target_id.as_str()))
+            .await
+            .expect("close opened tab");
+    }
+
+    browser.stop().await.expect("stop browser");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn navigation_is_reported_after_switching_and_closing_tabs() {
+    let Some(browser) = launch(false) else {
+        return;
+    };
+    let base = serve_pages();
+    let navigations = Arc::new(Mutex::new(Vec::<String>::new()));
+    let seen = Arc::clone(&navigations);
+    browser
+        .set_navigation_callback(move |url| seen.lock().expect("navigations").push(url))
+        .await;
+    let reported = |suffix: &'static str| {
+        let navigations = Arc::clone(&navigations);
+        async move {
+            for _ in 0..50 {
+                if navigations
+                    .lock()
+                    .expect("navigations")
+                    .iter()
+                    .any(|url| url.ends_with(suffix))
+                {
+                    return true;
+                }
+                tokio::time::sleep(Duration::from_millis(200)).await;
+            }
+            false
+        }
+    };
+
+    browser.goto(&format!("{base}/first")).await.expect("goto");
+    let first = browser.active_tab_id().await.expect("first tab");
+    let second_url = format!("{base}/second");
+    let second = browser
+        .open_tab(Some(second_url.as_str()))
+        .await
+        .expect("open tab");
+
+    browser.switch_tab(&first).await.expect("switch tab");
+    browser
+        .execute_javascript("location.href = '/after-switch'")
+        .await
+        .expect("navigate");
+    assert!(reported("/after-switch").await, "{navigations:?}");
+
+    browser
+        .close_tab(Some(second.target_id.as_str()))
+        .await
+        .expect("close tab");
+    browser
+        .execute_javascript("location.href = '/after-close'")
+        .await
+        .expect("navigate");
+    assert!(reported("/after-close").await, "{navigations:?}");
+
+    browser.stop().await.expect("stop browser");
+}
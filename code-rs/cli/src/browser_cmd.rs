@@ -0,0 +1,230 @@
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use code_browser::BrowserConfig;
+use code_browser::BrowserManager;
+use code_browser::network;
+use code_browser::recording;
+use code_browser::recording::ActionScript;
+use code_browser::recording::ReplayOptions;
+use code_core::config::find_code_home;
+
+/// Subcommands:
+/// - `har`    — export captured browser network traffic as a HAR 1.2 file
+/// - `replay` — re-run a recorded browser action script headless
+#[derive(Debug, clap::Parser)]
+pub struct BrowserCli {
+    #[command(subcommand)]
+    pub subcommand: BrowserSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum BrowserSubcommand {
+    /// Export a browser session's network requests as HAR 1.2.
+    Har(HarArgs),
+
+    /// Re-run a recorded browser action script and report divergences.
+    Replay(ReplayArgs),
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct HarArgs {
+    /// Network journal to export. Defaults to the most recent session.
+    #[arg(long, value_name = "FILE")]
+    pub session: Option<PathBuf>,
+
+    /// Write the HAR file here instead of stdout.
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Only include requests whose URL contains this text (or matches a `*` glob).
+    #[arg(long)]
+    pub filter: Option<String>,
+
+    /// List recorded sessions instead of exporting.
+    #[arg(long, default_value_t = false, conflicts_with_all = ["session", "output", "filter"])]
+    pub list: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct ReplayArgs {
+    /// Action script to replay. Defaults to the most recent recording.
+    #[arg(value_name = "FILE")]
+    pub file: Option<PathBuf>,
+
+    /// Directory for per-step screenshots (default: ./browser-replay-<script name>).
+    #[arg(long, value_name = "DIR")]
+    pub screenshots: Option<PathBuf>,
+
+    /// Stop at the first divergence.
+    #[arg(long, default_value_t = false)]
+    pub fail_fast: bool,
+
+    /// Show the browser window instead of running headless.
+    #[arg(long, default_value_t = false)]
+    pub headed: bool,
+
+    /// Print the replay report as JSON.
+    #[arg(long, default_value_t = false)]
+    pub json: bool,
+
+    /// List recorded scripts instead of replaying.
+    #[arg(long, default_value_t = false, conflicts_with_all = ["file", "screenshots", "fail_fast", "headed", "json"])]
+    pub list: bool,
+}
+
+impl BrowserCli {
+    pub async fn run(self) -> Result<()> {
+        match self.subcommand {
+            BrowserSubcommand::Har(args) => run_har(args),
+            BrowserSubcommand::Replay(args) => run_replay(args).await,
+        }
+    }
+}
+
+fn run_har(args: HarArgs) -> Result<()> {
+    let code_home = find_code_home().context("failed to resolve CODE_HOME")?;
+    let capture_dir = network::capture_dir(&code_home);
+
+    if args.list {
+        let journals = network::list_journals(&capture_dir);
+        if journals.is_empty() {
+            println!(
+                "No browser network sessions recorded in {}. Set `record_network = true` under `[browser]` to capture them.",
+                capture_dir.display()
+            );
+        }
+        for path in journals {
+            let count = network::read_journal(&path).map(|e| e.len()).unwrap_or(0);
+            println!("{}\t{count} request(s)", path.display());
+        }
+        return Ok(());
+    }
+
+    let journal = match args.session {
+        Some(path) => path,
+        None => match network::list_journals(&capture_dir).into_iter().next() {
+            Some(path) => path,
+            None => bail!(
+                "no browser network sessions recorded in {}",
+                capture_dir.display()
+            ),
+        },
+    };
+
+    let mut entries = network::read_journal(&journal)
+        .with_context(|| format!("failed to read {}", journal.display()))?;
+    if let Some(pattern) = args.filter.as_deref() {
+        entries.retain(|entry| network::url_matches(pattern, &entry.url));
+    }
+    entries.sort_by(|a, b| a.started_unix_ms.total_cmp(&b.started_unix_ms));
+
+    let har = serde_json::to_string_pretty(&network::to_har(&entries))?;
+    match args.output {
+        Some(path) => {
+            std::fs::write(&path, har)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            eprintln!(
+                "Wrote {} request(s) from {} to {}",
+                entries.len(),
+                journal.display(),
+                path.display()
+            );
+        }
+        None => {
+            let mut stdout = std::io::stdout().lock();
+            stdout.write_all(har.as_bytes())?;
+            stdout.write_all(b"\n")?;
+        }
+    }
+    Ok(())
+}
+
+async fn run_replay(args: ReplayArgs) -> Result<()> {
+    let code_home = find_code_home().context("failed to resolve CODE_HOME")?;
+    let recordings_dir = recording::recordings_dir(&code_home);
+
+    if args.list {
+        let recordings = recording::list_recordings(&recordings_dir);
+        if recordings.is_empty() {
+            println!("No browser recordings in {}.", recordings_dir.display());
+        }
+        for path in recordings {
+            let steps = ActionScript::load(&path).map(|s| s.steps.len()).unwrap_or(0);
+            println!("{}\t{steps} step(s)", path.display());
+        }
+        return Ok(());
+    }
+
+    let path = match args.file {
+        Some(path) => path,
+        None => match recording::list_recordings(&recordings_dir).into_iter().next() {
+            Some(path) => path,
+            None => bail!("no browser recordings in {}", recordings_dir.display()),
+        },
+    };
+    let script = ActionScript::load(&path)
+        .with_context(|| format!("failed to load {}", path.display()))?;
+    if script.steps.is_empty() {
+        bail!("{} has no steps to replay", path.display());
+    }
+
+    let screenshot_dir = args.screenshots.unwrap_or_else(|| {
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("script");
+        PathBuf::from(format!("browser-replay-{stem}"))
+    });
+
+    let mut config = BrowserConfig {
+        enabled: true,
+        headless: !args.headed,
+        ..BrowserConfig::default()
+    };
+    if let Some(viewport) = script.viewport.clone() {
+        config.viewport = viewport;
+    }
+    let manager = BrowserManager::new(config);
+    manager.set_action_recording(false);
+    manager.start().await.context("failed to launch browser")?;
+
+    let options = ReplayOptions {
+        screenshot_dir: Some(screenshot_dir.clone()),
+        stop_on_divergence: args.fail_fast,
+    };
+    let result = recording::replay_script(&manager, &script, &options).await;
+    if let Err(err) = manager.stop().await {
+        tracing::warn!("failed to stop replay browser: {err}");
+    }
+    let report = result.context("replay failed")?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("Replaying {} ({} steps)", path.display(), script.steps.len());
+        for step in &report.steps {
+            let status = if step.divergence.is_some() { "✗" } else { "✓" };
+            println!("{status} {:>3}. {}", step.index, step.action);
+            if let Some(url) = step.url.as_deref() {
+                println!("       url: {url}");
+            }
+            if let Some(divergence) = step.divergence.as_deref() {
+                println!("       {divergence}");
+            }
+        }
+        println!("Screenshots: {}", screenshot_dir.display());
+    }
+
+    let divergences = report.divergences();
+    if divergences > 0 {
+        bail!(
+            "{divergences} of {} replayed step(s) diverged from the recording",
+            report.steps.len()
+        );
+    }
+    Ok(())
+}
@@ -37,8 +37,12 @@ use std::process;
 use tokio::runtime::{Builder as TokioRuntimeBuilder, Handle as TokioHandle};
 
 mod mcp_cmd;
+mod browser_cmd;
+mod sessions_cmd;
 
 use crate::mcp_cmd::McpCli;
+use crate::browser_cmd::BrowserCli;
+use crate::sessions_cmd::SessionsCli;
 
 const CLI_COMMAND_NAME: &str = "code";
 pub(crate) const CODEX_SECURE_MODE_ENV_VAR: &str = "CODEX_SECURE_MODE";
@@ -161,6 +165,12 @@ enum Subcommand {
 
     /// Manage Code Bridge subscription for this workspace.
     Bridge(BridgeCommand),
+
+    /// Browser session utilities (HAR export, scripted replay).
+    Browser(BrowserCli),
+
+    /// List, inspect, archive and prune recorded sessions.
+    Sessions(SessionsCli),
 }
 
 #[derive(Debug, Parser)]
@@ -603,6 +613,12 @@ async fn cli_main(code_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()>
         Some(Subcommand::Bridge(bridge_cli)) => {
             run_bridge_command(bridge_cli).await?;
         }
+        Some(Subcommand::Browser(browser_cli)) => {
+            browser_cli.run().await?;
+        }
+        Some(Subcommand::Sessions(sessions_cli)) => {
+            sessions_cli.run().await?;
+        }
         Some(Subcommand::Llm(mut llm_cli)) => {
             prepend_config_flags(
                 &mut llm_cli.config_overrides,
@@ -1076,6 +1092,10 @@ fn resolve_resume_path(session_id: Option<&str>, last: bool) -> anyhow::Result<O
         let query = SessionQuery {
             cwd: None,
             git_root: None,
+            cwd_within: None,
+            git_branch: None,
+            updated_after: None,
+            updated_before: None,
             sources: vec![SessionSource::Cli, SessionSource::VSCode, SessionSource::Exec],
             min_user_messages: 1,
             include_archived: false,
@@ -0,0 +1,469 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use anyhow::Result;
+use chrono::DateTime;
+use chrono::Duration;
+use chrono::Local;
+use chrono::NaiveDate;
+use chrono::Utc;
+use code_core::SessionCatalog;
+use code_core::SessionIndexEntry;
+use code_core::SessionQuery;
+use code_core::config::find_code_home;
+use code_core::git_info::get_git_repo_root;
+use code_protocol::protocol::SessionSource;
+use serde::Serialize;
+
+/// Subcommands:
+/// - `list`      — list indexed sessions, newest first
+/// - `show`      — print one session's catalog entry
+/// - `archive`   — hide a session from the resume picker and `list`
+/// - `unarchive` — restore an archived session
+/// - `delete`    — mark a session deleted, or remove its files with `--purge`
+/// - `prune`     — delete every session older than a cutoff
+#[derive(Debug, clap::Parser)]
+pub struct SessionsCli {
+    #[command(subcommand)]
+    pub subcommand: SessionsSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum SessionsSubcommand {
+    /// List sessions, newest first.
+    #[clap(alias = "ls")]
+    List(ListArgs),
+
+    /// Show one session's catalog entry.
+    Show(ShowArgs),
+
+    /// Hide a session from the resume picker and `code sessions list`.
+    Archive(SessionIdArg),
+
+    /// Restore an archived session.
+    Unarchive(SessionIdArg),
+
+    /// Mark a session deleted, or remove it from disk with `--purge`.
+    Delete(DeleteArgs),
+
+    /// Delete sessions whose last activity is older than a cutoff.
+    Prune(PruneArgs),
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct FilterArgs {
+    /// Only sessions started in this repository (or directory) or below it.
+    #[arg(long, value_name = "PATH")]
+    pub repo: Option<PathBuf>,
+
+    /// Only sessions recorded on this git branch.
+    #[arg(long, value_name = "BRANCH")]
+    pub branch: Option<String>,
+
+    /// Only sessions started from these entry points (repeatable).
+    #[arg(long = "source", value_enum, value_name = "SOURCE")]
+    pub sources: Vec<SourceArg>,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct ListArgs {
+    #[clap(flatten)]
+    pub filter: FilterArgs,
+
+    /// Only sessions active at or after this time (`2026-01-31`, RFC 3339, or an age such as `7d`).
+    #[arg(long, value_name = "WHEN", value_parser = parse_time)]
+    pub since: Option<DateTime<Utc>>,
+
+    /// Only sessions last active before this time (same formats as `--since`).
+    #[arg(long, value_name = "WHEN", value_parser = parse_time)]
+    pub until: Option<DateTime<Utc>>,
+
+    /// Only sessions whose last message, directory or branch contains this text.
+    #[arg(long, value_name = "TEXT")]
+    pub search: Option<String>,
+
+    /// Include archived sessions.
+    #[arg(long, default_value_t = false)]
+    pub archived: bool,
+
+    /// Include sessions marked deleted.
+    #[arg(long, default_value_t = false)]
+    pub deleted: bool,
+
+    /// Maximum number of sessions to print.
+    #[arg(long, short = 'n', value_name = "N")]
+    pub limit: Option<usize>,
+
+    /// Print the entries as a JSON array.
+    #[arg(long, default_value_t = false)]
+    pub json: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct ShowArgs {
+    /// Session id (a unique prefix is enough).
+    #[arg(value_name = "SESSION_ID")]
+    pub session_id: String,
+
+    /// Print the entry as JSON.
+    #[arg(long, default_value_t = false)]
+    pub json: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct SessionIdArg {
+    /// Session id (a unique prefix is enough).
+    #[arg(value_name = "SESSION_ID")]
+    pub session_id: String,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct DeleteArgs {
+    /// Session id (a unique prefix is enough).
+    #[arg(value_name = "SESSION_ID")]
+    pub session_id: String,
+
+    /// Remove the rollout and snapshot files instead of only marking the session deleted.
+    #[arg(long, default_value_t = false)]
+    pub purge: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct PruneArgs {
+    /// Delete sessions with no activity for this long (e.g. `30d`, `12h`, `2w`).
+    #[arg(long, value_name = "AGE", value_parser = parse_age)]
+    pub older_than: Duration,
+
+    #[clap(flatten)]
+    pub filter: FilterArgs,
+
+    /// Remove the rollout and snapshot files instead of only marking sessions deleted.
+    #[arg(long, default_value_t = false)]
+    pub purge: bool,
+
+    /// Print what would be deleted without changing anything.
+    #[arg(long, default_value_t = false)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SourceArg {
+    Cli,
+    Vscode,
+    Exec,
+    Mcp,
+}
+
+impl From<SourceArg> for SessionSource {
+    fn from(source: SourceArg) -> Self {
+        match source {
+            SourceArg::Cli => SessionSource::Cli,
+            SourceArg::Vscode => SessionSource::VSCode,
+            SourceArg::Exec => SessionSource::Exec,
+            SourceArg::Mcp => SessionSource::Mcp,
+        }
+    }
+}
+
+/// Catalog entry plus the absolute rollout path, as printed by `--json`.
+#[derive(Serialize)]
+struct SessionJson<'a> {
+    #[serde(flatten)]
+    entry: &'a SessionIndexEntry,
+    rollout_file: PathBuf,
+}
+
+impl SessionsCli {
+    pub async fn run(self) -> Result<()> {
+        let code_home = find_code_home().context("failed to resolve CODE_HOME")?;
+        let catalog = SessionCatalog::new(code_home.clone());
+        match self.subcommand {
+            SessionsSubcommand::List(args) => run_list(&catalog, &code_home, args).await,
+            SessionsSubcommand::Show(args) => run_show(&catalog, &code_home, args).await,
+            SessionsSubcommand::Archive(args) => {
+                let entry = catalog.find_unique_by_prefix(&args.session_id).await?;
+                catalog.set_archived(entry.session_id, true).await?;
+                println!("Archived {}", entry.session_id);
+                Ok(())
+            }
+            SessionsSubcommand::Unarchive(args) => {
+                let entry = catalog.find_unique_by_prefix(&args.session_id).await?;
+                catalog.set_archived(entry.session_id, false).await?;
+                println!("Unarchived {}", entry.session_id);
+                Ok(())
+            }
+            SessionsSubcommand::Delete(args) => {
+                let entry = catalog.find_unique_by_prefix(&args.session_id).await?;
+                delete_entry(&catalog, &entry, args.purge).await?;
+                let verb = if args.purge { "Purged" } else { "Deleted" };
+                println!("{verb} {}", entry.session_id);
+                Ok(())
+            }
+            SessionsSubcommand::Prune(args) => run_prune(&catalog, args).await,
+        }
+    }
+}
+
+async fn run_list(catalog: &SessionCatalog, code_home: &Path, args: ListArgs) -> Result<()> {
+    let mut query = build_query(&args.filter)?;
+    query.updated_after = args.since;
+    query.updated_before = args.until;
+    query.include_archived = args.archived;
+    query.include_deleted = args.deleted;
+
+    let mut entries = catalog.query(&query).await?;
+    if let Some(text) = args.search.as_deref() {
+        let needle = text.to_lowercase();
+        entries.retain(|entry| {
+            [
+                entry.last_user_snippet.as_deref(),
+                Some(entry.cwd_display.as_str()),
+                entry.git_branch.as_deref(),
+            ]
+            .into_iter()
+            .flatten()
+            .any(|field| field.to_lowercase().contains(&needle))
+        });
+    }
+    if let Some(limit) = args.limit {
+        entries.truncate(limit);
+    }
+
+    if args.json {
+        let rows: Vec<SessionJson<'_>> = entries
+            .iter()
+            .map(|entry| SessionJson {
+                entry,
+                rollout_file: catalog.entry_rollout_path(entry),
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&rows)?);
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("No sessions found in {}.", code_home.display());
+        return Ok(());
+    }
+    for entry in &entries {
+        println!("{}", summary_line(entry));
+    }
+    Ok(())
+}
+
+async fn run_show(catalog: &SessionCatalog, code_home: &Path, args: ShowArgs) -> Result<()> {
+    let entry = catalog.find_unique_by_prefix(&args.session_id).await?;
+    let rollout_file = catalog.entry_rollout_path(&entry);
+    if args.json {
+        let row = SessionJson {
+            entry: &entry,
+            rollout_file,
+        };
+        println!("{}", serde_json::to_string_pretty(&row)?);
+        return Ok(());
+    }
+
+    let status = match (entry.archived, entry.deleted) {
+        (_, true) => "deleted",
+        (true, false) => "archived",
+        (false, false) => "active",
+    };
+    println!("Session   {}", entry.session_id);
+    println!("Status    {status}");
+    println!("Source    {}", source_label(entry.session_source));
+    println!("Created   {}", format_time(&entry.created_at));
+    println!("Updated   {}", format_time(&entry.last_event_at));
+    println!("Directory {}", entry.cwd_display);
+    if let Some(root) = &entry.git_project_root {
+        println!("Repo      {}", root.display());
+    }
+    if let Some(branch) = &entry.git_branch {
+        println!("Branch    {branch}");
+    }
+    if let Some(model) = &entry.model_provider {
+        println!("Model     {model}");
+    }
+    println!(
+        "Messages  {} ({} from the user)",
+        entry.message_count, entry.user_message_count
+    );
+    if let Some(snippet) = &entry.last_user_snippet {
+        println!("Last ask  {snippet}");
+    }
+    println!("Rollout   {}", rollout_file.display());
+    if let Some(snapshot) = &entry.snapshot_path {
+        println!("Snapshot  {}", code_home.join(snapshot).display());
+    }
+    Ok(())
+}
+
+async fn run_prune(catalog: &SessionCatalog, args: PruneArgs) -> Result<()> {
+    let mut query = build_query(&args.filter)?;
+    query.updated_before = Some(Utc::now() - args.older_than);
+    query.include_archived = true;
+    // Already-deleted sessions only need another pass when purging files.
+    query.include_deleted = args.purge;
+
+    let entries = catalog.query(&query).await?;
+    if entries.is_empty() {
+        println!("No sessions to prune.");
+        return Ok(());
+    }
+    for entry in &entries {
+        if args.dry_run {
+            println!("would delete {}", summary_line(entry));
+        } else {
+            delete_entry(catalog, entry, args.purge).await?;
+            println!("deleted {}", summary_line(entry));
+        }
+    }
+    let verb = match (args.dry_run, args.purge) {
+        (true, _) => "Would delete",
+        (false, true) => "Purged",
+        (false, false) => "Deleted",
+    };
+    println!("{verb} {} session(s).", entries.len());
+    Ok(())
+}
+
+fn build_query(filter: &FilterArgs) -> Result<SessionQuery> {
+    let cwd_within = match &filter.repo {
+        Some(path) => {
+            let path = path
+                .canonicalize()
+                .with_context(|| format!("failed to resolve {}", path.display()))?;
+            Some(get_git_repo_root(&path).unwrap_or(path))
+        }
+        None => None,
+    };
+    Ok(SessionQuery {
+        cwd_within,
+        git_branch: filter.branch.clone(),
+        sources: filter
+            .sources
+            .iter()
+            .copied()
+            .map(SessionSource::from)
+            .collect(),
+        ..SessionQuery::default()
+    })
+}
+
+async fn delete_entry(
+    catalog: &SessionCatalog,
+    entry: &SessionIndexEntry,
+    purge: bool,
+) -> Result<()> {
+    if purge {
+        catalog.purge(entry.session_id).await?;
+    } else {
+        catalog.set_deleted(entry.session_id, true).await?;
+    }
+    Ok(())
+}
+
+fn summary_line(entry: &SessionIndexEntry) -> String {
+    let mut line = format!(
+        "{}  {}  {:<6}  {:>3} msgs  {}",
+        entry.session_id,
+        format_time(&entry.last_event_at),
+        source_label(entry.session_source),
+        entry.user_message_count,
+        entry.cwd_display,
+    );
+    if let Some(branch) = &entry.git_branch {
+        line.push_str(&format!(" ({branch})"));
+    }
+    if entry.archived {
+        line.push_str(" [archived]");
+    }
+    if entry.deleted {
+        line.push_str(" [deleted]");
+    }
+    if let Some(snippet) = &entry.last_user_snippet {
+        line.push_str(&format!("  {snippet}"));
+    }
+    line
+}
+
+fn source_label(source: SessionSource) -> &'static str {
+    match source {
+        SessionSource::Cli => "cli",
+        SessionSource::VSCode => "vscode",
+        SessionSource::Exec => "exec",
+        SessionSource::Mcp => "mcp",
+        SessionSource::Unknown => "other",
+    }
+}
+
+/// Render a catalog timestamp in local time, or as recorded if it does not parse.
+fn format_time(timestamp: &str) -> String {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|time| {
+            time.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_else(|_| timestamp.to_string())
+}
+
+/// Parse `30d`, `12h`, `45m` or `2w`.
+fn parse_age(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount
+        .parse()
+        .map_err(|_| format!("`{value}` is not an age like `30d`"))?;
+    match unit {
+        "m" => Ok(Duration::minutes(amount)),
+        "h" => Ok(Duration::hours(amount)),
+        "d" => Ok(Duration::days(amount)),
+        "w" => Ok(Duration::weeks(amount)),
+        _ => Err(format!("`{value}` needs a unit of m, h, d or w")),
+    }
+}
+
+/// Parse an RFC 3339 time, a local `YYYY-MM-DD` date (midnight), or an age
+/// counted back from now.
+fn parse_time(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return date
+            .and_hms_opt(0, 0, 0)
+            .and_then(|midnight| midnight.and_local_timezone(Local).earliest())
+            .map(|time| time.with_timezone(&Utc))
+            .ok_or_else(|| format!("`{value}` is not a valid local date"));
+    }
+    parse_age(value)
+        .map(|age| Utc::now() - age)
+        .map_err(|_| format!("`{value}` is not a date, RFC 3339 time or age like `7d`"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ages_and_rejects_unknown_units() {
+        assert_eq!(parse_age("30d"), Ok(Duration::days(30)));
+        assert_eq!(parse_age("2w"), Ok(Duration::weeks(2)));
+        assert_eq!(parse_age("12h"), Ok(Duration::hours(12)));
+        assert!(parse_age("30").is_err());
+        assert!(parse_age("3y").is_err());
+    }
+
+    #[test]
+    fn parses_rfc3339_times() {
+        assert_eq!(
+            parse_time("2026-01-31T12:00:00Z").map(|time| time.to_rfc3339()),
+            Ok("2026-01-31T12:00:00+00:00".to_string())
+        );
+        assert!(parse_time("yesterday").is_err());
+    }
+}
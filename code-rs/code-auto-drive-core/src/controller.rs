@@ -148,6 +148,22 @@ impl AutoRunPhase {
         matches!(self, Self::AwaitingDiagnostics { .. })
     }
 
+    /// Stable name of the variant, without its payload; used when reporting
+    /// phase changes to `auto_drive.phase` project hooks.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Idle => "idle",
+            Self::AwaitingGoalEntry => "awaiting_goal_entry",
+            Self::Launching => "launching",
+            Self::Active => "active",
+            Self::PausedManual { .. } => "paused_manual",
+            Self::AwaitingCoordinator { .. } => "awaiting_coordinator",
+            Self::AwaitingDiagnostics { .. } => "awaiting_diagnostics",
+            Self::AwaitingReview { .. } => "awaiting_review",
+            Self::TransientRecovery { .. } => "transient_recovery",
+        }
+    }
+
     pub fn resume_after_submit(&self) -> Option<bool> {
         match self {
             Self::PausedManual {
@@ -165,6 +181,14 @@ impl Default for AutoRunPhase {
     }
 }
 
+/// A change from one [`AutoRunPhase`] variant to another. Changes that only
+/// touch a variant's payload are not reported.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AutoPhaseChange {
+    pub previous: &'static str,
+    pub phase: &'static str,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum PhaseTransition {
     BeginLaunch,
@@ -327,15 +351,28 @@ pub struct AutoDriveController {
     pub pending_stop_message: Option<String>,
     pub last_completion_explanation: Option<String>,
     pub phase: AutoRunPhase,
+    /// Phase changes not yet collected by `take_phase_changes`.
+    phase_changes: Vec<AutoPhaseChange>,
     // Non-cloneable guard is kept separately; controller stays Clone.
     pub review_lock: Option<std::sync::Arc<code_core::review_coord::ReviewGuard>>, 
 }
 
 impl AutoDriveController {
     fn apply_phase(&mut self, phase: AutoRunPhase) {
+        if phase.name() != self.phase.name() {
+            self.phase_changes.push(AutoPhaseChange {
+                previous: self.phase.name(),
+                phase: phase.name(),
+            });
+        }
         self.phase = phase;
     }
 
+    /// Drains the phase changes recorded since the last call, oldest first.
+    pub fn take_phase_changes(&mut self) -> Vec<AutoPhaseChange> {
+        std::mem::take(&mut self.phase_changes)
+    }
+
     pub fn set_waiting_for_response(&mut self, waiting: bool) {
         if waiting {
             match &mut self.phase {
@@ -740,6 +777,8 @@ impl AutoDriveController {
         let elapsed_override = self.elapsed_override;
         let pending_stop_message = self.pending_stop_message.clone();
         let last_completion_explanation = self.last_completion_explanation.clone();
+        let phase = self.phase;
+        let phase_changes = std::mem::take(&mut self.phase_changes);
 
         *self = Self::default();
 
@@ -756,11 +795,14 @@ impl AutoDriveController {
         self.pending_stop_message = pending_stop_message;
         self.last_completion_explanation = last_completion_explanation;
         self.review_lock = None;
-        self.phase = if self.phase.is_active() {
+        let next_phase = if self.phase.is_active() {
             AutoRunPhase::Active
         } else {
             AutoRunPhase::Idle
         };
+        self.phase = phase;
+        self.phase_changes = phase_changes;
+        self.apply_phase(next_phase);
     }
 
     pub fn reset_intro_timing(&mut self) {
@@ -809,7 +851,7 @@ impl AutoDriveController {
     }
 
     pub fn set_phase(&mut self, phase: AutoRunPhase) {
-        self.phase = phase;
+        self.apply_phase(phase);
     }
 
     pub fn phase(&self) -> &AutoRunPhase {
@@ -914,7 +956,7 @@ impl AutoDriveController {
 
 #[cfg(test)]
 mod tests {
-    use super::{AutoControllerEffect, AutoDriveController, AutoRunPhase};
+    use super::{AutoContinueMode, AutoControllerEffect, AutoDriveController, AutoRunPhase};
     use std::time::Instant;
 
     #[test]
@@ -984,6 +1026,39 @@ mod tests {
         assert!(controller.last_run_summary.is_none());
     }
 
+    #[test]
+    fn phase_changes_are_recorded_once_per_variant_change() {
+        let mut controller = AutoDriveController::default();
+        controller.prepare_launch(
+            "Ship it".to_string(),
+            false,
+            false,
+            false,
+            false,
+            AutoContinueMode::Immediate,
+            true,
+        );
+        controller.set_waiting_for_response(true);
+        // Only the payload changes here, so nothing new is recorded.
+        controller.set_coordinator_waiting(false);
+        controller.set_waiting_for_response(false);
+
+        let changes: Vec<_> = controller
+            .take_phase_changes()
+            .into_iter()
+            .map(|change| (change.previous, change.phase))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("idle", "launching"),
+                ("launching", "awaiting_diagnostics"),
+                ("awaiting_diagnostics", "active"),
+            ]
+        );
+        assert!(controller.take_phase_changes().is_empty());
+    }
+
     #[test]
     fn countdown_tick_respects_decision_seq() {
         let mut controller = AutoDriveController::default();
@@ -430,6 +430,22 @@ pub fn record_token_usage(
     })
 }
 
+/// Ledger id for usage served by a model provider that bills outside the
+/// active account, such as a `[model_failover]` fallback with its own key.
+pub fn provider_ledger_id(provider_id: &str) -> String {
+    let sanitized: String = provider_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("provider-{sanitized}")
+}
+
 pub fn record_rate_limit_snapshot(
     code_home: &Path,
     account_id: &str,
@@ -1154,4 +1170,20 @@ mod tests {
         assert_eq!(parsed.tokens_last_hour.total_tokens, 210);
         assert_eq!(parsed.hourly_entries.len(), 1);
     }
+
+    #[test]
+    fn provider_usage_gets_a_ledger_of_its_own() {
+        let home = TempDir::new().expect("tempdir");
+        let ledger = provider_ledger_id("azure/eu west");
+        assert_eq!(ledger, "provider-azure_eu_west");
+
+        record_token_usage(home.path(), &ledger, None, &sample_usage(), Utc::now())
+            .expect("record usage");
+
+        let summary = load_account_usage(home.path(), &ledger)
+            .expect("load summary")
+            .expect("summary present");
+        assert_eq!(summary.account_id, ledger);
+        assert_eq!(summary.totals.total_tokens, 210);
+    }
 }
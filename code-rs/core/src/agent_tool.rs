@@ -1469,6 +1469,7 @@ fn command_exists(cmd: &str) -> bool {
             &SandboxPolicy::DangerFullAccess,
             StdioPolicy::RedirectForShellTool,
             env.clone(),
+            None,
         )
         .await;
 
@@ -1875,6 +1876,7 @@ async fn execute_cloud_built_in_streaming(
         &SandboxPolicy::DangerFullAccess,
         StdioPolicy::RedirectForShellTool,
         env,
+        None,
     )
     .await
     .map_err(|e| format!("Failed to spawn cloud submit: {}", e))?;
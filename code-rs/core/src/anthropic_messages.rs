@@ -0,0 +1,1121 @@
+//! Client for the Anthropic Messages API (`wire_api = "anthropic"`).
+//!
+//! Conversation items are translated into Messages content blocks and the
+//! streamed reply is mapped back onto [`ResponseEvent`]s, so the rest of the
+//! pipeline does not need to know which wire format the provider speaks.
+//!
+//! - Function calls and their outputs become `tool_use` / `tool_result`
+//!   blocks keyed by the call id.
+//! - Freeform (`custom`) tools are declared with an `{input: string}` schema,
+//!   their grammar appended to the description. Their calls round-trip as
+//!   `tool_use` blocks with that input and come back as custom tool calls.
+//! - `thinking` blocks become reasoning items. The block signature is kept in
+//!   `encrypted_content` so the block can be sent back unchanged on the next
+//!   turn, which the API requires while a tool loop is in progress. Item ids
+//!   carry an `anthropic:` prefix so that reasoning from other providers (whose
+//!   `encrypted_content` is not a valid signature) is never replayed.
+//! - A reply cut off by `max_tokens` is reported with
+//!   [`ResponseEvent::OutputTruncated`].
+//! - Cache breakpoints are placed on the tool list, the system prompt and the
+//!   last two user turns.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use bytes::Bytes;
+use code_otel::otel_event_manager::OtelEventManager;
+use code_protocol::models::ContentItem;
+use code_protocol::models::ReasoningItemContent;
+use code_protocol::models::ReasoningItemReasoningSummary;
+use code_protocol::models::ResponseItem;
+use eventsource_stream::Eventsource;
+use futures::Stream;
+use futures::StreamExt;
+use futures::TryStreamExt;
+use reqwest::StatusCode;
+use serde_json::Value;
+use serde_json::json;
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+use tracing::debug;
+use tracing::trace;
+
+use crate::ModelProviderInfo;
+use crate::auth::AuthManager;
+use crate::cassette::Cassette;
+use crate::cassette::tee_response;
+use crate::client_common::Prompt;
+use crate::client_common::ResponseEvent;
+use crate::client_common::ResponseStream;
+use crate::config_types::ReasoningEffort as ReasoningEffortConfig;
+use crate::debug_logger::DebugLogger;
+use crate::error::CodexErr;
+use crate::error::Result;
+use crate::error::RetryLimitReachedError;
+use crate::error::UnexpectedResponseError;
+use crate::model_family::ModelFamily;
+use crate::openai_tools::create_tools_json_for_responses_api;
+use crate::protocol::TokenUsage;
+use crate::util::backoff;
+
+/// `max_tokens` is required by the Messages API. Used when neither the config
+/// nor the model family sets `model_max_output_tokens`.
+const DEFAULT_MAX_TOKENS: u64 = 16_384;
+
+/// Prefix of the ids of items produced by this API.
+const ITEM_ID_PREFIX: &str = "anthropic:";
+
+/// Smallest thinking budget the API accepts.
+const MIN_THINKING_BUDGET: u64 = 1_024;
+
+/// Implementation for the Anthropic Messages API.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn stream_anthropic_messages(
+    prompt: &Prompt,
+    model_family: &ModelFamily,
+    model_slug: &str,
+    effort: ReasoningEffortConfig,
+    max_output_tokens: Option<u64>,
+    client: &reqwest::Client,
+    provider: &ModelProviderInfo,
+    debug_logger: &Arc<Mutex<DebugLogger>>,
+    auth_manager: Option<Arc<AuthManager>>,
+    otel_event_manager: Option<OtelEventManager>,
+    cassette: Option<&Arc<Cassette>>,
+    log_tag: Option<&str>,
+) -> Result<ResponseStream> {
+    if prompt.output_schema.is_some() {
+        return Err(CodexErr::UnsupportedOperation(
+            "output_schema is not supported for the Anthropic Messages API".to_string(),
+        ));
+    }
+
+    let mut system = vec![json!({
+        "type": "text",
+        "text": prompt.get_full_instructions(model_family),
+    })];
+    let mut messages = build_messages(&prompt.get_formatted_input());
+    let (mut tools, custom_tools) =
+        build_tools(create_tools_json_for_responses_api(&prompt.tools)?);
+    add_cache_breakpoints(&mut system, &mut tools, &mut messages);
+
+    let max_tokens = max_output_tokens.unwrap_or(DEFAULT_MAX_TOKENS);
+    let mut payload = json!({
+        "model": model_slug,
+        "max_tokens": max_tokens,
+        "system": system,
+        "messages": messages,
+        "stream": true,
+    });
+    if let Some(obj) = payload.as_object_mut() {
+        if !tools.is_empty() {
+            obj.insert("tools".to_string(), Value::Array(tools));
+        }
+        if let Some(budget) = thinking_budget(effort, max_tokens) {
+            obj.insert(
+                "thinking".to_string(),
+                json!({"type": "enabled", "budget_tokens": budget}),
+            );
+        }
+    }
+
+    let endpoint = provider.get_full_url(&None);
+    debug!(
+        "POST to {}: {}",
+        endpoint,
+        serde_json::to_string_pretty(&payload).unwrap_or_default()
+    );
+
+    if let Some(cassette) = cassette.filter(|cassette| cassette.is_replay()) {
+        let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(1600);
+        tokio::spawn(process_anthropic_sse(
+            cassette.replay(&payload)?,
+            tx_event,
+            max_tokens,
+            custom_tools,
+            provider.stream_idle_timeout(),
+            Arc::clone(debug_logger),
+            String::new(),
+            otel_event_manager,
+        ));
+        return Ok(ResponseStream { rx_event });
+    }
+
+    let mut attempt = 0;
+    let max_retries = provider.request_max_retries();
+    let mut request_id = String::new();
+    loop {
+        attempt += 1;
+
+        let auth = auth_manager.as_ref().and_then(|m| m.auth());
+        let req_builder = provider
+            .create_request_builder(client, &auth)
+            .await?
+            .header(reqwest::header::ACCEPT, "text/event-stream")
+            .json(&payload);
+
+        if request_id.is_empty() {
+            let endpoint_for_log = provider.get_full_url(&auth);
+            if let Ok(logger) = debug_logger.lock() {
+                // Headers are left out so the API key never reaches the log.
+                request_id = logger
+                    .start_request_log(&endpoint_for_log, &payload, None, log_tag)
+                    .unwrap_or_default();
+            }
+        }
+
+        match req_builder.send().await {
+            Ok(resp) if resp.status().is_success() => {
+                if let Ok(logger) = debug_logger.lock() {
+                    let _ = logger.append_response_event(
+                        &request_id,
+                        "stream_initiated",
+                        &json!({
+                            "status": "success",
+                            "status_code": resp.status().as_u16()
+                        }),
+                    );
+                }
+                let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(1600);
+                let stream = tee_response(
+                    cassette,
+                    &payload,
+                    resp.bytes_stream().map_err(CodexErr::Reqwest),
+                )?;
+                tokio::spawn(process_anthropic_sse(
+                    stream,
+                    tx_event,
+                    max_tokens,
+                    custom_tools.clone(),
+                    provider.stream_idle_timeout(),
+                    Arc::clone(debug_logger),
+                    request_id.clone(),
+                    otel_event_manager.clone(),
+                ));
+                return Ok(ResponseStream { rx_event });
+            }
+            Ok(res) => {
+                let status = res.status();
+                if !(status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()) {
+                    let body = res.text().await.unwrap_or_default();
+                    if let Ok(logger) = debug_logger.lock() {
+                        let _ = logger.append_response_event(
+                            &request_id,
+                            "error",
+                            &json!({
+                                "status": status.as_u16(),
+                                "body": body
+                            }),
+                        );
+                        let _ = logger.end_request_log(&request_id);
+                    }
+                    return Err(CodexErr::UnexpectedStatus(UnexpectedResponseError {
+                        status,
+                        body,
+                        request_id: None,
+                    }));
+                }
+
+                if attempt > max_retries {
+                    return Err(CodexErr::RetryLimit(RetryLimitReachedError {
+                        status,
+                        request_id: None,
+                        retryable: true,
+                    }));
+                }
+
+                let retry_after_secs = res
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|s| s.parse::<u64>().ok());
+                let delay = retry_after_secs
+                    .map(Duration::from_secs)
+                    .unwrap_or_else(|| backoff(attempt));
+                tokio::time::sleep(delay).await;
+            }
+            Err(e) => {
+                if attempt > max_retries {
+                    if let Ok(logger) = debug_logger.lock() {
+                        let _ = logger.append_response_event(
+                            &request_id,
+                            "network_error",
+                            &json!({ "error": e.to_string() }),
+                        );
+                        let _ = logger.end_request_log(&request_id);
+                    }
+                    if e.is_connect() || e.is_timeout() || e.is_request() {
+                        let req_id = (!request_id.is_empty()).then(|| request_id.clone());
+                        return Err(CodexErr::Stream(
+                            format!("[transport] network unavailable: {e}"),
+                            None,
+                            req_id,
+                        ));
+                    }
+                    return Err(e.into());
+                }
+                tokio::time::sleep(backoff(attempt)).await;
+            }
+        }
+    }
+}
+
+/// Thinking budget for the configured effort, or `None` when thinking should
+/// stay off. The budget must stay below `max_tokens`.
+/// Translate Responses API tool definitions into Messages tools. Returns the
+/// tools together with the names of the freeform ones, whose calls are mapped
+/// back to custom tool calls. Built-in tools the API has no equivalent for are
+/// dropped.
+fn build_tools(tools: Vec<Value>) -> (Vec<Value>, HashSet<String>) {
+    let mut custom_tools = HashSet::new();
+    let tools = tools
+        .into_iter()
+        .filter_map(|tool| {
+            let name = tool.get("name").cloned().unwrap_or(Value::Null);
+            let description = tool
+                .get("description")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            match tool.get("type").and_then(Value::as_str) {
+                Some("function") => Some(json!({
+                    "name": name,
+                    "description": description,
+                    "input_schema": tool
+                        .get("parameters")
+                        .cloned()
+                        .unwrap_or_else(|| json!({"type": "object"})),
+                })),
+                Some("custom") => {
+                    if let Some(name) = name.as_str() {
+                        custom_tools.insert(name.to_string());
+                    }
+                    let format = tool.get("format");
+                    let field = |key: &str| {
+                        format
+                            .and_then(|f| f.get(key))
+                            .and_then(Value::as_str)
+                            .unwrap_or_default()
+                    };
+                    let description = if field("definition").is_empty() {
+                        description.to_string()
+                    } else {
+                        format!(
+                            "{description}\n\n`input` must follow this {} grammar:\n{}",
+                            field("syntax"),
+                            field("definition"),
+                        )
+                    };
+                    Some(json!({
+                        "name": name,
+                        "description": description,
+                        "input_schema": {
+                            "type": "object",
+                            "properties": {"input": {"type": "string"}},
+                            "required": ["input"],
+                        },
+                    }))
+                }
+                _ => None,
+            }
+        })
+        .collect();
+    (tools, custom_tools)
+}
+
+fn thinking_budget(effort: ReasoningEffortConfig, max_tokens: u64) -> Option<u64> {
+    let wanted = match effort {
+        ReasoningEffortConfig::Minimal | ReasoningEffortConfig::None => return None,
+        ReasoningEffortConfig::Low => 2_048,
+        ReasoningEffortConfig::Medium => 4_096,
+        ReasoningEffortConfig::High => 8_192,
+        ReasoningEffortConfig::XHigh => 16_384,
+    };
+    let budget = wanted.min(max_tokens / 2);
+    (budget >= MIN_THINKING_BUDGET).then_some(budget)
+}
+
+/// Translate conversation items into Messages API `messages`. Consecutive
+/// items with the same role are merged into one message, and developer or
+/// system messages are sent as user turns because the API only knows `user`
+/// and `assistant`.
+fn build_messages(input: &[ResponseItem]) -> Vec<Value> {
+    let mut messages = Vec::<Value>::new();
+    for item in input {
+        match item {
+            ResponseItem::Message { role, content, .. } => {
+                let role = if role == "assistant" {
+                    "assistant"
+                } else {
+                    "user"
+                };
+                for c in content {
+                    let block = match c {
+                        ContentItem::InputText { text } | ContentItem::OutputText { text } => {
+                            if text.is_empty() {
+                                continue;
+                            }
+                            json!({"type": "text", "text": text})
+                        }
+                        ContentItem::InputImage { image_url } => image_block(image_url),
+                    };
+                    push_block(&mut messages, role, block);
+                }
+            }
+            ResponseItem::Reasoning {
+                id,
+                summary,
+                content,
+                encrypted_content,
+            } => {
+                // Only blocks this API produced carry a valid signature.
+                // Reasoning from another provider earlier in the session is
+                // dropped, even when it has `encrypted_content` of its own.
+                if !id.starts_with(ITEM_ID_PREFIX) {
+                    continue;
+                }
+                let Some(signature) = encrypted_content else {
+                    continue;
+                };
+                let mut text = String::new();
+                for ReasoningItemReasoningSummary::SummaryText { text: t } in summary {
+                    text.push_str(t);
+                }
+                for c in content.iter().flatten() {
+                    match c {
+                        ReasoningItemContent::ReasoningText { text: t }
+                        | ReasoningItemContent::Text { text: t } => text.push_str(t),
+                    }
+                }
+                // Redacted blocks carry no text; their payload lives where the
+                // signature would be.
+                let block = if text.is_empty() {
+                    json!({"type": "redacted_thinking", "data": signature})
+                } else {
+                    json!({"type": "thinking", "thinking": text, "signature": signature})
+                };
+                push_block(&mut messages, "assistant", block);
+            }
+            ResponseItem::FunctionCall {
+                name,
+                arguments,
+                call_id,
+                ..
+            } => {
+                let block = json!({
+                    "type": "tool_use",
+                    "id": call_id,
+                    "name": name,
+                    "input": tool_input(arguments),
+                });
+                push_block(&mut messages, "assistant", block);
+            }
+            ResponseItem::LocalShellCall {
+                id,
+                call_id,
+                action,
+                ..
+            } => {
+                let block = json!({
+                    "type": "tool_use",
+                    "id": call_id.clone().or_else(|| id.clone()).unwrap_or_default(),
+                    "name": "local_shell",
+                    "input": serde_json::to_value(action).unwrap_or_else(|_| json!({})),
+                });
+                push_block(&mut messages, "assistant", block);
+            }
+            ResponseItem::CustomToolCall {
+                call_id,
+                name,
+                input,
+                ..
+            } => {
+                let block = json!({
+                    "type": "tool_use",
+                    "id": call_id,
+                    "name": name,
+                    "input": {"input": input},
+                });
+                push_block(&mut messages, "assistant", block);
+            }
+            ResponseItem::FunctionCallOutput { call_id, output } => {
+                let mut block = json!({
+                    "type": "tool_result",
+                    "tool_use_id": call_id,
+                    "content": output.content,
+                });
+                if output.success == Some(false)
+                    && let Some(obj) = block.as_object_mut()
+                {
+                    obj.insert("is_error".to_string(), Value::Bool(true));
+                }
+                push_block(&mut messages, "user", block);
+            }
+            ResponseItem::CustomToolCallOutput { call_id, output } => {
+                let block = json!({
+                    "type": "tool_result",
+                    "tool_use_id": call_id,
+                    "content": output,
+                });
+                push_block(&mut messages, "user", block);
+            }
+            ResponseItem::CompactionSummary { .. }
+            | ResponseItem::WebSearchCall { .. }
+            | ResponseItem::Other => {
+                // Omit these items from the conversation history.
+                continue;
+            }
+        }
+    }
+    messages
+}
+
+/// Append `block` to the last message when it has the same role, otherwise
+/// start a new message. `tool_result` blocks must lead a user message, so they
+/// are kept ahead of any other blocks already in it.
+fn push_block(messages: &mut Vec<Value>, role: &str, block: Value) {
+    if let Some(Value::Object(last)) = messages.last_mut()
+        && last.get("role").and_then(Value::as_str) == Some(role)
+        && let Some(content) = last.get_mut("content").and_then(Value::as_array_mut)
+    {
+        if is_tool_result(&block) {
+            let position = content
+                .iter()
+                .position(|existing| !is_tool_result(existing))
+                .unwrap_or(content.len());
+            content.insert(position, block);
+        } else {
+            content.push(block);
+        }
+        return;
+    }
+    messages.push(json!({"role": role, "content": [block]}));
+}
+
+fn is_tool_result(block: &Value) -> bool {
+    block.get("type").and_then(Value::as_str) == Some("tool_result")
+}
+
+fn image_block(image_url: &str) -> Value {
+    if let Some(rest) = image_url.strip_prefix("data:")
+        && let Some((meta, data)) = rest.split_once(',')
+        && let Some(media_type) = meta.strip_suffix(";base64")
+    {
+        return json!({
+            "type": "image",
+            "source": {"type": "base64", "media_type": media_type, "data": data},
+        });
+    }
+    json!({
+        "type": "image",
+        "source": {"type": "url", "url": image_url},
+    })
+}
+
+/// `tool_use.input` must be an object; arguments that do not parse as one are
+/// sent as `{}`.
+fn tool_input(arguments: &str) -> Value {
+    match serde_json::from_str::<Value>(arguments) {
+        Ok(value @ Value::Object(_)) => value,
+        _ => json!({}),
+    }
+}
+
+/// Mark the end of the tool list, the system prompt and the last two user
+/// turns as cache breakpoints. The API allows at most four.
+fn add_cache_breakpoints(system: &mut [Value], tools: &mut [Value], messages: &mut [Value]) {
+    let breakpoints = tools.last_mut().into_iter().chain(system.last_mut()).chain(
+        messages
+            .iter_mut()
+            .rev()
+            .filter(|message| message.get("role").and_then(Value::as_str) == Some("user"))
+            .take(2)
+            .filter_map(|message| {
+                message
+                    .get_mut("content")
+                    .and_then(Value::as_array_mut)
+                    .and_then(|content| content.last_mut())
+            }),
+    );
+    for block in breakpoints {
+        if let Some(obj) = block.as_object_mut() {
+            obj.insert("cache_control".to_string(), json!({"type": "ephemeral"}));
+        }
+    }
+}
+
+/// Token counts reported in `message_start` and updated by `message_delta`.
+/// Anthropic reports cached input separately from `input_tokens`.
+#[derive(Debug, Default)]
+struct MessageUsage {
+    input_tokens: u64,
+    cache_creation_input_tokens: u64,
+    cache_read_input_tokens: u64,
+    output_tokens: u64,
+}
+
+impl MessageUsage {
+    fn update(&mut self, usage: &Value) {
+        let fields = [
+            ("input_tokens", &mut self.input_tokens),
+            (
+                "cache_creation_input_tokens",
+                &mut self.cache_creation_input_tokens,
+            ),
+            ("cache_read_input_tokens", &mut self.cache_read_input_tokens),
+            ("output_tokens", &mut self.output_tokens),
+        ];
+        for (key, slot) in fields {
+            if let Some(value) = usage.get(key).and_then(Value::as_u64) {
+                *slot = value;
+            }
+        }
+    }
+
+    fn to_token_usage(&self) -> TokenUsage {
+        let input_tokens =
+            self.input_tokens + self.cache_creation_input_tokens + self.cache_read_input_tokens;
+        TokenUsage {
+            input_tokens,
+            cached_input_tokens: self.cache_read_input_tokens,
+            output_tokens: self.output_tokens,
+            reasoning_output_tokens: 0,
+            total_tokens: input_tokens + self.output_tokens,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlockKind {
+    Text,
+    Thinking,
+    RedactedThinking,
+    ToolUse,
+}
+
+/// A content block being streamed, keyed by its `index`.
+#[derive(Debug)]
+struct OpenBlock {
+    kind: BlockKind,
+    /// Text, thinking text or partial tool input JSON.
+    text: String,
+    /// Thinking signature, or the payload of a redacted block.
+    signature: String,
+    tool_id: String,
+    tool_name: String,
+}
+
+impl OpenBlock {
+    fn new(kind: BlockKind) -> Self {
+        Self {
+            kind,
+            text: String::new(),
+            signature: String::new(),
+            tool_id: String::new(),
+            tool_name: String::new(),
+        }
+    }
+
+    fn into_item(self, item_id: String, custom_tools: &HashSet<String>) -> Option<ResponseItem> {
+        match self.kind {
+            BlockKind::Text => (!self.text.is_empty()).then(|| ResponseItem::Message {
+                id: Some(item_id),
+                role: "assistant".to_string(),
+                content: vec![ContentItem::OutputText { text: self.text }],
+            }),
+            BlockKind::Thinking | BlockKind::RedactedThinking => {
+                let summary = if self.text.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![ReasoningItemReasoningSummary::SummaryText { text: self.text }]
+                };
+                Some(ResponseItem::Reasoning {
+                    id: item_id,
+                    summary,
+                    content: None,
+                    encrypted_content: (!self.signature.is_empty()).then_some(self.signature),
+                })
+            }
+            BlockKind::ToolUse if custom_tools.contains(&self.tool_name) => {
+                let input = serde_json::from_str::<Value>(&self.text)
+                    .ok()
+                    .and_then(|value| {
+                        value
+                            .get("input")
+                            .and_then(Value::as_str)
+                            .map(str::to_string)
+                    })
+                    .unwrap_or_default();
+                Some(ResponseItem::CustomToolCall {
+                    id: None,
+                    status: None,
+                    call_id: self.tool_id,
+                    name: self.tool_name,
+                    input,
+                })
+            }
+            BlockKind::ToolUse => Some(ResponseItem::FunctionCall {
+                id: None,
+                name: self.tool_name,
+                arguments: if self.text.trim().is_empty() {
+                    "{}".to_string()
+                } else {
+                    self.text
+                },
+                call_id: self.tool_id,
+            }),
+        }
+    }
+}
+
+/// SSE processor for the Messages streaming format.
+async fn process_anthropic_sse<S>(
+    stream: S,
+    tx_event: mpsc::Sender<Result<ResponseEvent>>,
+    max_tokens: u64,
+    custom_tools: HashSet<String>,
+    idle_timeout: Duration,
+    debug_logger: Arc<Mutex<DebugLogger>>,
+    request_id: String,
+    otel_event_manager: Option<OtelEventManager>,
+) where
+    S: Stream<Item = Result<Bytes>> + Unpin,
+{
+    let mut stream = stream.eventsource();
+    let mut message_id = String::new();
+    let mut usage = MessageUsage::default();
+    let mut blocks: HashMap<u64, OpenBlock> = HashMap::new();
+    let mut sequence_number: u64 = 0;
+
+    loop {
+        let next_event = if let Some(manager) = otel_event_manager.as_ref() {
+            manager
+                .log_sse_event(|| timeout(idle_timeout, stream.next()))
+                .await
+        } else {
+            timeout(idle_timeout, stream.next()).await
+        };
+
+        let sse = match next_event {
+            Ok(Some(Ok(ev))) => ev,
+            Ok(Some(Err(e))) => {
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(
+                        format!("[transport] {e}"),
+                        None,
+                        Some(request_id.clone()),
+                    )))
+                    .await;
+                return;
+            }
+            Ok(None) => {
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(
+                        "stream closed before message_stop".into(),
+                        None,
+                        Some(request_id.clone()),
+                    )))
+                    .await;
+                return;
+            }
+            Err(_) => {
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(
+                        "[idle] timeout waiting for SSE".into(),
+                        None,
+                        Some(request_id.clone()),
+                    )))
+                    .await;
+                return;
+            }
+        };
+
+        let data = sse.data.trim();
+        if data.is_empty() {
+            continue;
+        }
+        let chunk: Value = match serde_json::from_str(data) {
+            Ok(v) => v,
+            Err(e) => {
+                debug!("anthropic SSE parse error: {e} | event: {}", sse.event);
+                continue;
+            }
+        };
+        trace!("anthropic_messages received SSE chunk: {chunk:?}");
+        if let Ok(logger) = debug_logger.lock() {
+            let _ = logger.append_response_event(&request_id, "sse_event", &chunk);
+        }
+
+        let index = chunk.get("index").and_then(Value::as_u64).unwrap_or(0);
+        let item_id = format!("{ITEM_ID_PREFIX}{message_id}_{index}");
+        let output_index = u32::try_from(index).ok();
+
+        match chunk
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or(&sse.event)
+        {
+            "message_start" => {
+                if let Some(message) = chunk.get("message") {
+                    if let Some(id) = message.get("id").and_then(Value::as_str) {
+                        message_id = id.to_string();
+                    }
+                    if let Some(u) = message.get("usage") {
+                        usage.update(u);
+                    }
+                }
+                let _ = tx_event.send(Ok(ResponseEvent::Created)).await;
+            }
+            "content_block_start" => {
+                let Some(content_block) = chunk.get("content_block") else {
+                    continue;
+                };
+                let field = |key: &str| {
+                    content_block
+                        .get(key)
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string()
+                };
+                let block = match content_block.get("type").and_then(Value::as_str) {
+                    Some("text") => {
+                        let mut block = OpenBlock::new(BlockKind::Text);
+                        block.text = field("text");
+                        block
+                    }
+                    Some("thinking") => {
+                        let mut block = OpenBlock::new(BlockKind::Thinking);
+                        block.text = field("thinking");
+                        block.signature = field("signature");
+                        block
+                    }
+                    Some("redacted_thinking") => {
+                        let mut block = OpenBlock::new(BlockKind::RedactedThinking);
+                        block.signature = field("data");
+                        block
+                    }
+                    Some("tool_use") => {
+                        let mut block = OpenBlock::new(BlockKind::ToolUse);
+                        block.tool_id = field("id");
+                        block.tool_name = field("name");
+                        block
+                    }
+                    other => {
+                        debug!("ignoring unsupported anthropic content block: {other:?}");
+                        continue;
+                    }
+                };
+                blocks.insert(index, block);
+            }
+            "content_block_delta" => {
+                let Some(delta) = chunk.get("delta") else {
+                    continue;
+                };
+                let Some(block) = blocks.get_mut(&index) else {
+                    continue;
+                };
+                let text = |key: &str| {
+                    delta
+                        .get(key)
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string()
+                };
+                sequence_number += 1;
+                match delta.get("type").and_then(Value::as_str) {
+                    Some("text_delta") => {
+                        let delta = text("text");
+                        block.text.push_str(&delta);
+                        let _ = tx_event
+                            .send(Ok(ResponseEvent::OutputTextDelta {
+                                delta,
+                                item_id: Some(item_id),
+                                sequence_number: Some(sequence_number),
+                                output_index,
+                            }))
+                            .await;
+                    }
+                    Some("thinking_delta") => {
+                        let delta = text("thinking");
+                        block.text.push_str(&delta);
+                        let _ = tx_event
+                            .send(Ok(ResponseEvent::ReasoningSummaryDelta {
+                                delta,
+                                item_id: Some(item_id),
+                                sequence_number: Some(sequence_number),
+                                output_index,
+                                summary_index: Some(0),
+                            }))
+                            .await;
+                    }
+                    Some("signature_delta") => block.signature.push_str(&text("signature")),
+                    Some("input_json_delta") => block.text.push_str(&text("partial_json")),
+                    other => debug!("ignoring unsupported anthropic delta: {other:?}"),
+                }
+            }
+            "content_block_stop" => {
+                if let Some(item) = blocks
+                    .remove(&index)
+                    .and_then(|block| block.into_item(item_id, &custom_tools))
+                {
+                    sequence_number += 1;
+                    let _ = tx_event
+                        .send(Ok(ResponseEvent::OutputItemDone {
+                            item,
+                            sequence_number: Some(sequence_number),
+                            output_index,
+                        }))
+                        .await;
+                }
+            }
+            "message_delta" => {
+                if let Some(u) = chunk.get("usage") {
+                    usage.update(u);
+                }
+                if let Some(stop_reason) = chunk
+                    .get("delta")
+                    .and_then(|d| d.get("stop_reason"))
+                    .and_then(Value::as_str)
+                {
+                    debug!("anthropic message stopped: {stop_reason}");
+                    if stop_reason == "max_tokens" {
+                        let _ = tx_event
+                            .send(Ok(ResponseEvent::OutputTruncated {
+                                max_output_tokens: max_tokens,
+                            }))
+                            .await;
+                    }
+                }
+            }
+            "message_stop" => {
+                let _ = tx_event
+                    .send(Ok(ResponseEvent::Completed {
+                        response_id: message_id,
+                        token_usage: Some(usage.to_token_usage()),
+                    }))
+                    .await;
+                if let Ok(logger) = debug_logger.lock() {
+                    let _ = logger.end_request_log(&request_id);
+                }
+                return;
+            }
+            "error" => {
+                let error = chunk.get("error");
+                let kind = error
+                    .and_then(|e| e.get("type"))
+                    .and_then(Value::as_str)
+                    .unwrap_or("error");
+                let message = error
+                    .and_then(|e| e.get("message"))
+                    .and_then(Value::as_str)
+                    .unwrap_or("unknown error");
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(
+                        format!("{kind}: {message}"),
+                        None,
+                        Some(request_id.clone()),
+                    )))
+                    .await;
+                if let Ok(logger) = debug_logger.lock() {
+                    let _ = logger.end_request_log(&request_id);
+                }
+                return;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use code_protocol::models::FunctionCallOutputPayload;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn tool_calls_and_thinking_round_trip_into_messages() {
+        let input = vec![
+            ResponseItem::Message {
+                id: None,
+                role: "developer".to_string(),
+                content: vec![ContentItem::InputText {
+                    text: "be careful".to_string(),
+                }],
+            },
+            ResponseItem::Message {
+                id: None,
+                role: "user".to_string(),
+                content: vec![ContentItem::InputText {
+                    text: "list files".to_string(),
+                }],
+            },
+            ResponseItem::Reasoning {
+                id: "anthropic:msg_1_0".to_string(),
+                summary: vec![ReasoningItemReasoningSummary::SummaryText {
+                    text: "use ls".to_string(),
+                }],
+                content: None,
+                encrypted_content: Some("sig".to_string()),
+            },
+            // Reasoning from an OpenAI provider before a failover.
+            ResponseItem::Reasoning {
+                id: "rs_1".to_string(),
+                summary: Vec::new(),
+                content: None,
+                encrypted_content: Some("gAAAA".to_string()),
+            },
+            ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: "{\"command\":[\"ls\"]}".to_string(),
+                call_id: "toolu_1".to_string(),
+            },
+            ResponseItem::FunctionCallOutput {
+                call_id: "toolu_1".to_string(),
+                output: FunctionCallOutputPayload {
+                    content: "not found".to_string(),
+                    success: Some(false),
+                },
+            },
+        ];
+
+        assert_eq!(
+            build_messages(&input),
+            vec![
+                json!({"role": "user", "content": [
+                    {"type": "text", "text": "be careful"},
+                    {"type": "text", "text": "list files"},
+                ]}),
+                json!({"role": "assistant", "content": [
+                    {"type": "thinking", "thinking": "use ls", "signature": "sig"},
+                    {"type": "tool_use", "id": "toolu_1", "name": "shell", "input": {"command": ["ls"]}},
+                ]}),
+                json!({"role": "user", "content": [
+                    {"type": "tool_result", "tool_use_id": "toolu_1", "content": "not found", "is_error": true},
+                ]}),
+            ]
+        );
+    }
+
+    #[test]
+    fn custom_tools_are_declared_and_their_calls_map_back() {
+        let (tools, custom_tools) = build_tools(vec![
+            json!({"type": "function", "name": "shell", "description": "run", "parameters": {"type": "object"}}),
+            json!({"type": "custom", "name": "apply_patch", "description": "patch", "format": {
+                "type": "grammar", "syntax": "lark", "definition": "start: patch",
+            }}),
+            json!({"type": "web_search"}),
+        ]);
+        assert_eq!(
+            tools,
+            vec![
+                json!({"name": "shell", "description": "run", "input_schema": {"type": "object"}}),
+                json!({
+                    "name": "apply_patch",
+                    "description": "patch\n\n`input` must follow this lark grammar:\nstart: patch",
+                    "input_schema": {
+                        "type": "object",
+                        "properties": {"input": {"type": "string"}},
+                        "required": ["input"],
+                    },
+                }),
+            ]
+        );
+
+        let history = build_messages(&[ResponseItem::CustomToolCall {
+            id: None,
+            status: None,
+            call_id: "toolu_2".to_string(),
+            name: "apply_patch".to_string(),
+            input: "*** Begin Patch".to_string(),
+        }]);
+        let tool_use = &history[0]["content"][0];
+        assert_eq!(tool_use["name"], "apply_patch");
+
+        let mut block = OpenBlock::new(BlockKind::ToolUse);
+        block.tool_id = "toolu_2".to_string();
+        block.tool_name = "apply_patch".to_string();
+        block.text = tool_use["input"].to_string();
+        assert_eq!(
+            block.into_item("anthropic:msg_2_0".to_string(), &custom_tools),
+            Some(ResponseItem::CustomToolCall {
+                id: None,
+                status: None,
+                call_id: "toolu_2".to_string(),
+                name: "apply_patch".to_string(),
+                input: "*** Begin Patch".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn cache_breakpoints_cover_tools_system_and_last_two_user_turns() {
+        let mut system = vec![json!({"type": "text", "text": "sys"})];
+        let mut tools = vec![json!({"name": "a"}), json!({"name": "b"})];
+        let mut messages = vec![
+            json!({"role": "user", "content": [{"type": "text", "text": "1"}]}),
+            json!({"role": "assistant", "content": [{"type": "text", "text": "2"}]}),
+            json!({"role": "user", "content": [{"type": "text", "text": "3"}]}),
+            json!({"role": "assistant", "content": [{"type": "text", "text": "4"}]}),
+            json!({"role": "user", "content": [{"type": "text", "text": "5"}]}),
+        ];
+
+        add_cache_breakpoints(&mut system, &mut tools, &mut messages);
+
+        let marked = |block: &Value| block.get("cache_control").is_some();
+        assert!(marked(&system[0]));
+        assert_eq!(
+            tools.iter().map(marked).collect::<Vec<_>>(),
+            vec![false, true]
+        );
+        assert_eq!(
+            messages
+                .iter()
+                .map(|m| marked(&m["content"][0]))
+                .collect::<Vec<_>>(),
+            vec![false, false, true, false, true]
+        );
+    }
+
+    #[test]
+    fn usage_counts_cached_input() {
+        let mut usage = MessageUsage::default();
+        usage.update(&json!({
+            "input_tokens": 10,
+            "cache_creation_input_tokens": 20,
+            "cache_read_input_tokens": 300,
+            "output_tokens": 1,
+        }));
+        usage.update(&json!({"output_tokens": 42}));
+
+        assert_eq!(
+            usage.to_token_usage(),
+            TokenUsage {
+                input_tokens: 330,
+                cached_input_tokens: 300,
+                output_tokens: 42,
+                reasoning_output_tokens: 0,
+                total_tokens: 372,
+            }
+        );
+    }
+
+    #[test]
+    fn thinking_budget_follows_effort_and_max_tokens() {
+        assert_eq!(
+            thinking_budget(ReasoningEffortConfig::Minimal, 16_384),
+            None
+        );
+        assert_eq!(
+            thinking_budget(ReasoningEffortConfig::Medium, 16_384),
+            Some(4_096)
+        );
+        assert_eq!(
+            thinking_budget(ReasoningEffortConfig::XHigh, 16_384),
+            Some(8_192)
+        );
+        assert_eq!(thinking_budget(ReasoningEffortConfig::High, 1_500), None);
+    }
+}
@@ -11,6 +11,7 @@ use code_apply_patch::AffectedPaths;
 use code_apply_patch::ApplyPatchAction;
 use code_apply_patch::ApplyPatchFileChange;
 use code_apply_patch::FileSystem;
+use code_apply_patch::FuzzyMatch;
 use code_apply_patch::StdFileSystem;
 use code_apply_patch::print_summary;
 use code_protocol::models::FunctionCallOutputPayload;
@@ -141,7 +142,7 @@ pub(crate) async fn apply_patch(
     let auto_approved = match assess_patch_safety(
         &action,
         sess.get_approval_policy(),
-        sess.get_sandbox_policy(),
+        &sess.effective_sandbox_policy(),
         sess.get_cwd(),
     ) {
         SafetyCheck::AutoApprove { .. } => true,
@@ -209,6 +210,7 @@ pub(crate) fn convert_apply_patch_to_protocol(
                 unified_diff,
                 move_path,
                 new_content,
+                ..
             } => {
                 let original_content = std::fs::read_to_string(path).unwrap_or_default();
                 FileChange::Update {
@@ -265,6 +267,7 @@ async fn apply_changes_from_apply_patch(
     let mut added: Vec<PathBuf> = Vec::new();
     let mut modified: Vec<PathBuf> = Vec::new();
     let mut deleted: Vec<PathBuf> = Vec::new();
+    let mut fuzzy_matches: Vec<FuzzyMatch> = Vec::new();
 
     for (path, change) in action.changes() {
         match change {
@@ -289,8 +292,10 @@ async fn apply_changes_from_apply_patch(
             ApplyPatchFileChange::Update {
                 move_path,
                 new_content,
+                fuzzy_matches: change_fuzzy_matches,
                 ..
             } => {
+                fuzzy_matches.extend(change_fuzzy_matches.iter().cloned());
                 if let Some(move_path) = move_path {
                     if let Some(parent) = move_path.parent() {
                         if !parent.as_os_str().is_empty() {
@@ -317,5 +322,6 @@ async fn apply_changes_from_apply_patch(
         added,
         modified,
         deleted,
+        fuzzy_matches,
     })
 }
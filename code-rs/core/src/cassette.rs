@@ -0,0 +1,479 @@
+//! Record-and-replay cassettes for model traffic (`code exec
+//! --record-cassette` / `--replay-cassette`; no other front end sets a
+//! cassette).
+//!
+//! A cassette is a JSONL file with one entry per model request: the
+//! normalized request body, its hash and the raw SSE body the provider
+//! streamed back. Recording tees the live byte stream into the file; replay
+//! serves the recorded bytes to the same SSE parser without touching the
+//! network. Requests are matched on the hash of their normalized body, so
+//! paths, dates and timings that differ between runs do not break replay.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+
+use bytes::Bytes;
+use futures::Stream;
+use futures::StreamExt;
+use regex_lite::Regex;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+use sha1::Digest;
+use sha1::Sha1;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::warn;
+
+use crate::error::CodexErr;
+use crate::error::Result;
+
+/// Where model traffic is recorded to or replayed from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CassetteMode {
+    Record(PathBuf),
+    Replay(PathBuf),
+}
+
+impl CassetteMode {
+    pub fn path(&self) -> &Path {
+        match self {
+            CassetteMode::Record(path) | CassetteMode::Replay(path) => path,
+        }
+    }
+}
+
+/// Top-level request fields that change on every run and carry no meaning
+/// for the model.
+const VOLATILE_KEYS: &[&str] = &["prompt_cache_key"];
+
+static VOLATILE_PATTERNS: LazyLock<Vec<(Regex, &'static str)>> = LazyLock::new(|| {
+    [
+        (
+            r"<current_date>[^<]*</current_date>",
+            "<current_date>*</current_date>",
+        ),
+        (
+            r#""duration_seconds":[0-9.eE+-]+"#,
+            r#""duration_seconds":0"#,
+        ),
+        (r"Wall time: [0-9.]+ seconds", "Wall time: * seconds"),
+    ]
+    .into_iter()
+    .filter_map(|(pattern, replacement)| Some((Regex::new(pattern).ok()?, replacement)))
+    .collect()
+});
+
+/// Cassettes already opened by this process, so every `ModelClient` of a
+/// session (and its review/compaction clients) shares one recording.
+static OPEN_CASSETTES: LazyLock<Mutex<HashMap<CassetteMode, Arc<Cassette>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+pub(crate) type CassetteByteStream = Pin<Box<dyn Stream<Item = Result<Bytes>> + Send>>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CassetteEntry {
+    hash: String,
+    request: Value,
+    response: String,
+}
+
+#[derive(Debug, Default)]
+struct ReplayState {
+    entries: Vec<CassetteEntry>,
+    used: Vec<bool>,
+    requests_seen: usize,
+}
+
+#[derive(Debug)]
+pub(crate) struct Cassette {
+    path: PathBuf,
+    replay: bool,
+    /// Machine-specific strings replaced before hashing, longest first.
+    replacements: Vec<(String, &'static str)>,
+    /// Set when the file could not be created or read; surfaced on use.
+    open_error: Option<String>,
+    state: Mutex<ReplayState>,
+}
+
+impl Cassette {
+    /// Open the cassette for `mode`, reusing the instance when this process
+    /// already opened the same file. Recording truncates the file on first
+    /// open.
+    pub(crate) fn shared(mode: &CassetteMode, cwd: &Path, code_home: &Path) -> Arc<Self> {
+        let Ok(mut open) = OPEN_CASSETTES.lock() else {
+            return Arc::new(Self::open(mode, cwd, code_home));
+        };
+        open.entry(mode.clone())
+            .or_insert_with(|| Arc::new(Self::open(mode, cwd, code_home)))
+            .clone()
+    }
+
+    fn open(mode: &CassetteMode, cwd: &Path, code_home: &Path) -> Self {
+        let mut replacements: Vec<(String, &'static str)> =
+            [(cwd, "<cwd>"), (code_home, "<code_home>")]
+                .into_iter()
+                .map(|(path, placeholder)| (path.to_string_lossy().into_owned(), placeholder))
+                .filter(|(path, _)| path.len() > 1)
+                .collect();
+        replacements.sort_by(|a, b| b.0.len().cmp(&a.0.len()));
+
+        let path = mode.path().to_path_buf();
+        let (replay, loaded) = match mode {
+            CassetteMode::Record(_) => (false, create_cassette_file(&path).map(|()| Vec::new())),
+            CassetteMode::Replay(_) => (true, load_entries(&path)),
+        };
+        let (entries, open_error) = match loaded {
+            Ok(entries) => (entries, None),
+            Err(err) => (Vec::new(), Some(format!("{}: {err}", path.display()))),
+        };
+        let used = vec![false; entries.len()];
+
+        Self {
+            path,
+            replay,
+            replacements,
+            open_error,
+            state: Mutex::new(ReplayState {
+                entries,
+                used,
+                requests_seen: 0,
+            }),
+        }
+    }
+
+    pub(crate) fn is_replay(&self) -> bool {
+        self.replay
+    }
+
+    /// Serve the recorded response for `request`, or explain why none
+    /// matches.
+    pub(crate) fn replay(&self, request: &Value) -> Result<CassetteByteStream> {
+        self.check_open()?;
+        let normalized = self.normalize(request);
+        let hash = request_hash(&normalized);
+
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|_| CodexErr::Cassette("cassette state poisoned".to_string()))?;
+        state.requests_seen += 1;
+        let request_number = state.requests_seen;
+        let ReplayState { entries, used, .. } = &mut *state;
+
+        if let Some(index) = (0..entries.len()).find(|&i| !used[i] && entries[i].hash == hash) {
+            used[index] = true;
+            let body = Bytes::from(entries[index].response.clone());
+            return Ok(Box::pin(futures::stream::iter([Ok(body)])));
+        }
+
+        let Some(expected) = (0..entries.len()).find(|&i| !used[i]).map(|i| &entries[i]) else {
+            return Err(CodexErr::Cassette(format!(
+                "request #{request_number} has no match in {}: all {} recorded requests were already replayed",
+                self.path.display(),
+                entries.len()
+            )));
+        };
+        Err(CodexErr::Cassette(format!(
+            "request #{request_number} does not match {}; diff against the next recorded request:\n{}",
+            self.path.display(),
+            request_diff(&expected.request, &normalized)
+        )))
+    }
+
+    /// Pass `stream` through unchanged while saving its bytes; the entry is
+    /// written once the stream ends without a transport error and the
+    /// consumer has read all of it.
+    pub(crate) fn record<S>(
+        self: &Arc<Self>,
+        request: &Value,
+        mut stream: S,
+    ) -> Result<CassetteByteStream>
+    where
+        S: Stream<Item = Result<Bytes>> + Unpin + Send + 'static,
+    {
+        self.check_open()?;
+        let normalized = self.normalize(request);
+        let cassette = Arc::clone(self);
+        let (tx, rx) = mpsc::channel::<Result<Bytes>>(64);
+
+        tokio::spawn(async move {
+            let mut body = Vec::new();
+            let mut failed = false;
+            while let Some(chunk) = stream.next().await {
+                match &chunk {
+                    Ok(bytes) => body.extend_from_slice(bytes),
+                    Err(_) => failed = true,
+                }
+                // A consumer that stops reading early leaves the body truncated.
+                if tx.send(chunk).await.is_err() {
+                    failed = true;
+                }
+                if failed {
+                    break;
+                }
+            }
+            // A failed or abandoned attempt is retried and recorded then;
+            // keeping it would shadow the complete response on replay.
+            if failed {
+                return;
+            }
+            let entry = CassetteEntry {
+                hash: request_hash(&normalized),
+                request: normalized,
+                response: String::from_utf8_lossy(&body).into_owned(),
+            };
+            if let Err(err) = cassette.append(&entry) {
+                warn!(
+                    "failed to write cassette entry to {}: {err}",
+                    cassette.path.display()
+                );
+            }
+        });
+
+        Ok(Box::pin(ReceiverStream::new(rx)))
+    }
+
+    fn check_open(&self) -> Result<()> {
+        match &self.open_error {
+            Some(err) => Err(CodexErr::Cassette(err.clone())),
+            None => Ok(()),
+        }
+    }
+
+    fn append(&self, entry: &CassetteEntry) -> std::io::Result<()> {
+        let line = serde_json::to_string(entry)?;
+        // Hold the state lock so concurrent streams never interleave lines.
+        let _guard = self.state.lock();
+        let mut file = std::fs::OpenOptions::new().append(true).open(&self.path)?;
+        writeln!(file, "{line}")
+    }
+
+    fn normalize(&self, request: &Value) -> Value {
+        let mut value = self.normalize_value(request);
+        if let Value::Object(map) = &mut value {
+            for key in VOLATILE_KEYS {
+                map.remove(*key);
+            }
+        }
+        value
+    }
+
+    fn normalize_value(&self, value: &Value) -> Value {
+        match value {
+            Value::String(text) => Value::String(self.normalize_str(text)),
+            Value::Array(items) => Value::Array(
+                items
+                    .iter()
+                    .map(|item| self.normalize_value(item))
+                    .collect(),
+            ),
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(key, value)| (key.clone(), self.normalize_value(value)))
+                    .collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+
+    fn normalize_str(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for (path, placeholder) in &self.replacements {
+            if text.contains(path.as_str()) {
+                text = text.replace(path.as_str(), placeholder);
+            }
+        }
+        for (pattern, replacement) in VOLATILE_PATTERNS.iter() {
+            if pattern.is_match(&text) {
+                text = pattern.replace_all(&text, *replacement).into_owned();
+            }
+        }
+        text
+    }
+}
+
+/// Hand a live response body to the recording cassette, if there is one.
+pub(crate) fn tee_response<S>(
+    cassette: Option<&Arc<Cassette>>,
+    request: &Value,
+    stream: S,
+) -> Result<CassetteByteStream>
+where
+    S: Stream<Item = Result<Bytes>> + Unpin + Send + 'static,
+{
+    match cassette {
+        Some(cassette) if !cassette.is_replay() => cassette.record(request, stream),
+        _ => Ok(Box::pin(stream)),
+    }
+}
+
+fn create_cassette_file(path: &Path) -> std::io::Result<()> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::File::create(path).map(|_| ())
+}
+
+fn load_entries(path: &Path) -> std::io::Result<Vec<CassetteEntry>> {
+    let contents = std::fs::read_to_string(path)?;
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).map_err(|err| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("line {}: {err}", index + 1),
+                )
+            })
+        })
+        .collect()
+}
+
+fn request_hash(normalized: &Value) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(normalized.to_string().as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn request_diff(recorded: &Value, actual: &Value) -> String {
+    let pretty =
+        |value: &Value| serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string());
+    let recorded = pretty(recorded);
+    let actual = pretty(actual);
+    similar::TextDiff::from_lines(&recorded, &actual)
+        .unified_diff()
+        .context_radius(3)
+        .header("recorded", "actual")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    async fn collect(stream: CassetteByteStream) -> String {
+        stream
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .map(|chunk| String::from_utf8(chunk.unwrap().to_vec()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn normalization_masks_paths_dates_and_timings() {
+        let dir = TempDir::new().unwrap();
+        let cassette = Cassette::open(
+            &CassetteMode::Record(dir.path().join("c.jsonl")),
+            Path::new("/work/repo"),
+            Path::new("/home/me/.code"),
+        );
+
+        let normalized = cassette.normalize(&json!({
+            "prompt_cache_key": "0199-session",
+            "input": [
+                {"text": "cd /work/repo/src <current_date>2026-01-02</current_date>"},
+                {"output": "{\"output\":\"hi\",\"metadata\":{\"exit_code\":0,\"duration_seconds\":1.3}}"},
+                {"text": "see /home/me/.code/sessions"},
+            ],
+        }));
+
+        assert_eq!(
+            normalized,
+            json!({
+                "input": [
+                    {"text": "cd <cwd>/src <current_date>*</current_date>"},
+                    {"output": "{\"output\":\"hi\",\"metadata\":{\"exit_code\":0,\"duration_seconds\":0}}"},
+                    {"text": "see <code_home>/sessions"},
+                ],
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn recorded_streams_replay_by_request_and_report_mismatches() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("nested").join("c.jsonl");
+        let cwd = dir.path().join("repo");
+
+        let recorder = Arc::new(Cassette::open(
+            &CassetteMode::Record(path.clone()),
+            &cwd,
+            dir.path(),
+        ));
+        for (model, body) in [("a", "event: one\n\n"), ("b", "event: two\n\n")] {
+            let chunks = futures::stream::iter([Ok(Bytes::from(body))]);
+            let stream = recorder.record(&json!({"model": model}), chunks).unwrap();
+            assert_eq!(stream.collect::<Vec<_>>().await.len(), 1);
+        }
+        // Entries are written by the forwarding task after the stream ends.
+        for _ in 0..50 {
+            if load_entries(&path).unwrap().len() == 2 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+
+        let player = Cassette::open(&CassetteMode::Replay(path), &cwd, dir.path());
+        assert_eq!(
+            collect(player.replay(&json!({"model": "b"})).unwrap()).await,
+            "event: two\n\n"
+        );
+
+        let Err(CodexErr::Cassette(message)) = player.replay(&json!({"model": "c"})) else {
+            panic!("expected a cassette mismatch");
+        };
+        assert!(message.contains("request #2 does not match"));
+        assert!(message.contains("-  \"model\": \"a\""));
+        assert!(message.contains("+  \"model\": \"c\""));
+
+        assert_eq!(
+            collect(player.replay(&json!({"model": "a"})).unwrap()).await,
+            "event: one\n\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn streams_dropped_by_the_consumer_are_not_recorded() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("c.jsonl");
+        let recorder = Arc::new(Cassette::open(
+            &CassetteMode::Record(path.clone()),
+            dir.path(),
+            dir.path(),
+        ));
+
+        let (source_tx, source_rx) = mpsc::channel::<Result<Bytes>>(4);
+        let mut stream = recorder
+            .record(&json!({"model": "a"}), ReceiverStream::new(source_rx))
+            .unwrap();
+        source_tx
+            .send(Ok(Bytes::from("event: one\n\n")))
+            .await
+            .unwrap();
+        assert!(stream.next().await.is_some());
+        drop(stream);
+        source_tx
+            .send(Ok(Bytes::from("event: two\n\n")))
+            .await
+            .unwrap();
+
+        // The forwarding task drops the source once it has given up.
+        source_tx.closed().await;
+        assert!(load_entries(&path).unwrap().is_empty());
+    }
+}
@@ -1,5 +1,16 @@
 #[cfg(target_os = "linux")]
+use std::ffi::CStr;
+#[cfg(target_os = "linux")]
+use std::os::unix::ffi::OsStrExt;
+#[cfg(target_os = "linux")]
 use std::path::{Path, PathBuf};
+#[cfg(target_os = "linux")]
+use std::time::Duration;
+
+#[cfg(target_os = "linux")]
+use crate::config_types::ExecLimits;
+#[cfg(target_os = "linux")]
+use crate::protocol::ExecResourceUsage;
 
 #[cfg(target_os = "linux")]
 const CGROUP_MOUNT: &str = "/sys/fs/cgroup";
@@ -10,6 +21,16 @@ const EXEC_CGROUP_SUBDIR: &str = "code-exec";
 #[cfg(target_os = "linux")]
 const EXEC_CGROUP_OOM_SCORE_ADJ: &str = "500";
 
+#[cfg(target_os = "linux")]
+const EXEC_CGROUP_CONTROLLERS: [&str; 4] = ["memory", "cpu", "pids", "io"];
+
+#[cfg(target_os = "linux")]
+const CPU_MAX_PERIOD_USEC: u64 = 100_000;
+
+/// Capacity of the stack buffers `pre_exec` builds cgroup paths in.
+#[cfg(target_os = "linux")]
+const CGROUP_PATH_CAPACITY: usize = libc::PATH_MAX as usize;
+
 #[cfg(target_os = "linux")]
 pub(crate) fn default_exec_memory_max_bytes() -> Option<u64> {
     if let Ok(raw) = std::env::var("CODEX_EXEC_MEMORY_MAX_BYTES") {
@@ -87,50 +108,272 @@ pub(crate) fn exec_cgroup_abs_for_pid(pid: u32) -> Option<PathBuf> {
     exec_cgroup_parent_abs().map(|parent| parent.join(format!("pid-{pid}")))
 }
 
+/// Limits configured for a command's `code-exec/pid-N` cgroup; see
+/// [`PreparedExecCgroup`] for how they are applied.
 #[cfg(target_os = "linux")]
-fn best_effort_enable_memory_controller(parent: &Path) {
-    let controllers = std::fs::read_to_string(parent.join("cgroup.controllers")).ok();
-    if controllers.as_deref().unwrap_or_default().split_whitespace().all(|c| c != "memory") {
-        return;
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct ExecCgroupLimits {
+    pub(crate) memory_max_bytes: Option<u64>,
+    pub(crate) cpu_max: Option<String>,
+    pub(crate) pids_max: Option<u64>,
+    pub(crate) io_weight: Option<u16>,
+}
+
+#[cfg(target_os = "linux")]
+impl ExecCgroupLimits {
+    pub(crate) fn from_config(limits: &ExecLimits) -> Self {
+        let memory_max_bytes = limits
+            .memory_max_mb
+            .filter(|mb| *mb > 0)
+            .map(|mb| mb.saturating_mul(1024 * 1024))
+            .or_else(default_exec_memory_max_bytes);
+        Self {
+            memory_max_bytes,
+            cpu_max: limits.cpu_max_cores.and_then(cpu_max_value),
+            pids_max: limits.pids_max.filter(|max| *max > 0),
+            io_weight: limits.io_weight.map(|weight| weight.clamp(1, 10_000)),
+        }
     }
+}
+
+/// Renders `cpu.max` ("$QUOTA $PERIOD") for a budget of `cores` CPUs.
+#[cfg(target_os = "linux")]
+fn cpu_max_value(cores: f64) -> Option<String> {
+    if !cores.is_finite() || cores <= 0.0 {
+        return None;
+    }
+    // The kernel rejects quotas below 1ms.
+    let quota = ((cores * CPU_MAX_PERIOD_USEC as f64).round() as u64).max(1_000);
+    Some(format!("{quota} {CPU_MAX_PERIOD_USEC}"))
+}
+
+#[cfg(target_os = "linux")]
+fn best_effort_enable_controllers(parent: &Path) {
+    let controllers = std::fs::read_to_string(parent.join("cgroup.controllers")).ok();
+    let available: Vec<&str> = controllers
+        .as_deref()
+        .unwrap_or_default()
+        .split_whitespace()
+        .collect();
     let subtree = parent.join("cgroup.subtree_control");
-    let _ = std::fs::write(subtree, "+memory");
+    // One write per controller so a single refusal does not block the rest.
+    for controller in EXEC_CGROUP_CONTROLLERS {
+        if available.contains(&controller) {
+            let _ = std::fs::write(&subtree, format!("+{controller}"));
+        }
+    }
 }
 
 #[cfg(target_os = "linux")]
-pub(crate) fn best_effort_attach_self_to_exec_cgroup(pid: u32, memory_max_bytes: u64) {
-    let Some(parent) = exec_cgroup_parent_abs() else {
-        return;
-    };
+fn best_effort_write_limit(cgroup_dir: &Path, file: &str, value: &str) -> bool {
+    let path = cgroup_dir.join(file);
+    path.exists() && std::fs::write(path, value).is_ok()
+}
 
-    let _ = std::fs::create_dir_all(&parent);
-    best_effort_enable_memory_controller(&parent);
+/// Everything the `pre_exec` hook needs to move the child into its own
+/// `code-exec/pid-N` cgroup, rendered before `fork`. Only the child's pid is
+/// unknown until then, so the hook formats it and assembles the paths that
+/// contain it in fixed stack buffers; apart from that it only calls
+/// `mkdir`, `open`, `write` and `close`, and never allocates.
+#[cfg(target_os = "linux")]
+#[derive(Debug, PartialEq)]
+pub(crate) struct PreparedExecCgroup {
+    /// `<parent>/pid-`; the child appends its pid.
+    dir_prefix: Vec<u8>,
+    memory_max: Option<Vec<u8>>,
+    cpu_max: Option<Vec<u8>>,
+    pids_max: Option<Vec<u8>>,
+    io_weight: Option<Vec<u8>>,
+}
 
-    let cgroup_dir = parent.join(format!("pid-{pid}"));
-    if std::fs::create_dir_all(&cgroup_dir).is_err() {
-        return;
+#[cfg(target_os = "linux")]
+impl PreparedExecCgroup {
+    /// Creates the `code-exec` parent cgroup, enables its controllers and
+    /// renders `limits`. Returns `None` without cgroup v2.
+    pub(crate) fn prepare(limits: &ExecCgroupLimits) -> Option<Self> {
+        let parent = exec_cgroup_parent_abs()?;
+        let _ = std::fs::create_dir_all(&parent);
+        best_effort_enable_controllers(&parent);
+        Self::new(&parent, limits)
     }
 
-    let memory_max_path = cgroup_dir.join("memory.max");
-    if memory_max_path.exists() {
-        let _ = std::fs::write(&memory_max_path, memory_max_bytes.to_string());
-    } else {
-        // Memory controller not active for this subtree.
-        return;
+    /// Returns `None` when `parent` is too long for the child's path buffers.
+    fn new(parent: &Path, limits: &ExecCgroupLimits) -> Option<Self> {
+        let mut dir_prefix = parent.as_os_str().as_bytes().to_vec();
+        dir_prefix.extend_from_slice(b"/pid-");
+        // Room for the pid, the longest control file name and the NUL.
+        if dir_prefix.len() + 64 > CGROUP_PATH_CAPACITY {
+            return None;
+        }
+        Some(Self {
+            dir_prefix,
+            memory_max: limits
+                .memory_max_bytes
+                .map(|bytes| bytes.to_string().into_bytes()),
+            cpu_max: limits.cpu_max.clone().map(String::into_bytes),
+            pids_max: limits.pids_max.map(|max| max.to_string().into_bytes()),
+            io_weight: limits
+                .io_weight
+                .map(|weight| format!("default {weight}").into_bytes()),
+        })
+    }
+
+    /// Joins the calling process to a new `pid-N` cgroup with the prepared
+    /// limits. Safe to call between `fork` and `exec`.
+    pub(crate) fn attach_self(&self) {
+        // SAFETY: getpid cannot fail and is async-signal-safe.
+        let pid = unsafe { libc::getpid() } as u32;
+        let mut pid_buf = [0u8; 10];
+        let pid = render_decimal(pid, &mut pid_buf);
+
+        let mut dir = CgroupPath::new();
+        if !dir.push(&self.dir_prefix) || !dir.push(pid) {
+            return;
+        }
+        // SAFETY: `as_c_str` returns a NUL-terminated path.
+        if unsafe { libc::mkdir(dir.as_c_str().as_ptr(), 0o755) } != 0
+            && std::io::Error::last_os_error().raw_os_error() != Some(libc::EEXIST)
+        {
+            return;
+        }
+
+        let memory_limited = self
+            .memory_max
+            .as_deref()
+            .is_some_and(|value| dir.write_file(b"memory.max", value));
+        if memory_limited {
+            let _ = dir.write_file(b"memory.oom.group", b"1");
+            // Prefer killing the exec subtree first if the host does hit global OOM.
+            let _ = write_c_path(
+                c"/proc/self/oom_score_adj",
+                EXEC_CGROUP_OOM_SCORE_ADJ.as_bytes(),
+            );
+        }
+        if let Some(cpu_max) = self.cpu_max.as_deref() {
+            let _ = dir.write_file(b"cpu.max", cpu_max);
+        }
+        if let Some(pids_max) = self.pids_max.as_deref() {
+            let _ = dir.write_file(b"pids.max", pids_max);
+        }
+        if let Some(io_weight) = self.io_weight.as_deref() {
+            let _ = dir.write_file(b"io.weight", io_weight);
+        }
+
+        // Join even when no controller is delegated: `cpu.stat` is always
+        // present, so resource accounting still works.
+        let _ = dir.write_file(b"cgroup.procs", pid);
+    }
+}
+
+/// A NUL-terminated path assembled on the stack.
+#[cfg(target_os = "linux")]
+#[derive(Clone, Copy)]
+struct CgroupPath {
+    bytes: [u8; CGROUP_PATH_CAPACITY],
+    len: usize,
+}
+
+#[cfg(target_os = "linux")]
+impl CgroupPath {
+    fn new() -> Self {
+        Self {
+            bytes: [0; CGROUP_PATH_CAPACITY],
+            len: 0,
+        }
     }
 
-    let oom_group_path = cgroup_dir.join("memory.oom.group");
-    if oom_group_path.exists() {
-        let _ = std::fs::write(oom_group_path, "1");
+    /// Appends `part`, keeping room for the NUL. Returns false if it does
+    /// not fit.
+    fn push(&mut self, part: &[u8]) -> bool {
+        let end = self.len + part.len();
+        if end >= CGROUP_PATH_CAPACITY || part.contains(&0) {
+            return false;
+        }
+        self.bytes[self.len..end].copy_from_slice(part);
+        self.len = end;
+        true
     }
 
-    // Prefer killing the exec subtree first if the host does hit global OOM.
-    let _ = std::fs::write("/proc/self/oom_score_adj", EXEC_CGROUP_OOM_SCORE_ADJ);
+    fn as_c_str(&mut self) -> &CStr {
+        self.bytes[self.len] = 0;
+        // `push` keeps interior NULs out, so the first NUL is at `len`.
+        match CStr::from_bytes_until_nul(&self.bytes) {
+            Ok(path) => path,
+            Err(_) => c"",
+        }
+    }
+
+    /// Writes `value` to the file `name` in this directory.
+    fn write_file(&self, name: &[u8], value: &[u8]) -> bool {
+        let mut path = *self;
+        path.push(b"/") && path.push(name) && write_c_path(path.as_c_str(), value)
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn write_c_path(path: &CStr, value: &[u8]) -> bool {
+    // SAFETY: `path` is NUL-terminated and `value` outlives the write.
+    unsafe {
+        let fd = libc::open(path.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+        if fd < 0 {
+            return false;
+        }
+        let written = libc::write(fd, value.as_ptr().cast(), value.len());
+        libc::close(fd);
+        written == value.len() as isize
+    }
+}
 
-    let procs_path = cgroup_dir.join("cgroup.procs");
-    if procs_path.exists() {
-        let _ = std::fs::write(procs_path, pid.to_string());
+/// Formats `value` in decimal into the end of `buf` without allocating.
+#[cfg(target_os = "linux")]
+fn render_decimal(mut value: u32, buf: &mut [u8; 10]) -> &[u8] {
+    let mut start = buf.len();
+    loop {
+        start -= 1;
+        buf[start] = b'0' + (value % 10) as u8;
+        value /= 10;
+        if value == 0 {
+            break;
+        }
     }
+    &buf[start..]
+}
+
+/// Kills every process left in the command's cgroup, including descendants
+/// that escaped its process group.
+#[cfg(target_os = "linux")]
+pub(crate) fn best_effort_kill_exec_cgroup(pid: u32) {
+    let Some(dir) = exec_cgroup_abs_for_pid(pid) else {
+        return;
+    };
+    let _ = best_effort_write_limit(&dir, "cgroup.kill", "1");
+}
+
+/// Reads peak memory and CPU time for the command's cgroup. Call before
+/// `best_effort_cleanup_exec_cgroup`.
+#[cfg(target_os = "linux")]
+pub(crate) fn exec_cgroup_resource_usage(pid: u32) -> Option<ExecResourceUsage> {
+    let dir = exec_cgroup_abs_for_pid(pid)?;
+    let cpu_stat = std::fs::read_to_string(dir.join("cpu.stat")).ok()?;
+    let usage_usec = parse_cpu_stat_usage_usec(&cpu_stat)?;
+    let peak_memory_bytes = std::fs::read_to_string(dir.join("memory.peak"))
+        .ok()
+        .and_then(|raw| raw.trim().parse::<u64>().ok());
+    Some(ExecResourceUsage {
+        peak_memory_bytes,
+        cpu_time: Duration::from_micros(usage_usec),
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn parse_cpu_stat_usage_usec(contents: &str) -> Option<u64> {
+    contents.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        match (parts.next(), parts.next()) {
+            (Some("usage_usec"), Some(value)) => value.parse::<u64>().ok(),
+            _ => None,
+        }
+    })
 }
 
 #[cfg(target_os = "linux")]
@@ -173,3 +416,87 @@ pub(crate) fn best_effort_cleanup_exec_cgroup(pid: u32) {
     // Only remove the per-pid directory. The parent container stays.
     let _ = std::fs::remove_dir(&dir);
 }
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cpu_max_value_scales_quota_by_cores() {
+        assert_eq!(cpu_max_value(2.0).as_deref(), Some("200000 100000"));
+        assert_eq!(cpu_max_value(0.5).as_deref(), Some("50000 100000"));
+        assert_eq!(cpu_max_value(0.001).as_deref(), Some("1000 100000"));
+        assert_eq!(cpu_max_value(0.0), None);
+        assert_eq!(cpu_max_value(f64::NAN), None);
+    }
+
+    #[test]
+    fn parse_cpu_stat_reads_usage_usec() {
+        let contents = "usage_usec 3456789\nuser_usec 3000000\nsystem_usec 456789\n";
+        assert_eq!(parse_cpu_stat_usage_usec(contents), Some(3_456_789));
+        assert_eq!(parse_cpu_stat_usage_usec("nr_periods 0\n"), None);
+    }
+
+    #[test]
+    fn limits_from_config_prefer_configured_memory() {
+        let limits = ExecCgroupLimits::from_config(&ExecLimits {
+            memory_max_mb: Some(256),
+            cpu_max_cores: Some(1.5),
+            pids_max: Some(0),
+            io_weight: Some(20_000),
+            wall_time_max_secs: None,
+        });
+        assert_eq!(
+            limits,
+            ExecCgroupLimits {
+                memory_max_bytes: Some(256 * 1024 * 1024),
+                cpu_max: Some("150000 100000".to_string()),
+                pids_max: None,
+                io_weight: Some(10_000),
+            }
+        );
+    }
+
+    #[test]
+    fn prepared_cgroup_renders_limits_before_fork() {
+        let limits = ExecCgroupLimits {
+            memory_max_bytes: Some(1024),
+            cpu_max: Some("50000 100000".to_string()),
+            pids_max: None,
+            io_weight: Some(200),
+        };
+        let prepared = PreparedExecCgroup::new(Path::new("/sys/fs/cgroup/user/code-exec"), &limits);
+        assert_eq!(
+            prepared,
+            Some(PreparedExecCgroup {
+                dir_prefix: b"/sys/fs/cgroup/user/code-exec/pid-".to_vec(),
+                memory_max: Some(b"1024".to_vec()),
+                cpu_max: Some(b"50000 100000".to_vec()),
+                pids_max: None,
+                io_weight: Some(b"default 200".to_vec()),
+            })
+        );
+
+        let too_long = PathBuf::from("/".repeat(CGROUP_PATH_CAPACITY));
+        assert_eq!(PreparedExecCgroup::new(&too_long, &limits), None);
+    }
+
+    #[test]
+    fn render_decimal_formats_without_allocating() {
+        let mut buf = [0u8; 10];
+        assert_eq!(render_decimal(0, &mut buf), b"0");
+        assert_eq!(render_decimal(4_321, &mut buf), b"4321");
+        assert_eq!(render_decimal(u32::MAX, &mut buf), b"4294967295");
+    }
+
+    #[test]
+    fn cgroup_path_rejects_overflow_and_nul() {
+        let mut path = CgroupPath::new();
+        assert!(path.push(b"/sys/fs/cgroup/pid-"));
+        assert!(path.push(b"42"));
+        assert_eq!(path.as_c_str(), c"/sys/fs/cgroup/pid-42");
+        assert!(!path.push(b"a\0b"));
+        assert!(!path.push(&[b'a'; CGROUP_PATH_CAPACITY]));
+        assert_eq!(path.as_c_str(), c"/sys/fs/cgroup/pid-42");
+    }
+}
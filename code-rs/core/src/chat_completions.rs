@@ -21,6 +21,8 @@ use tracing::trace;
 
 use crate::auth::AuthManager;
 use crate::ModelProviderInfo;
+use crate::cassette::Cassette;
+use crate::cassette::tee_response;
 use crate::client_common::Prompt;
 use crate::client_common::ResponseEvent;
 use crate::client_common::ResponseStream;
@@ -39,6 +41,7 @@ use code_protocol::models::ReasoningItemContent;
 use code_protocol::models::ResponseItem;
 
 /// Implementation for the classic Chat Completions API.
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn stream_chat_completions(
     prompt: &Prompt,
     model_family: &ModelFamily,
@@ -48,6 +51,7 @@ pub(crate) async fn stream_chat_completions(
     debug_logger: &Arc<Mutex<DebugLogger>>,
     auth_manager: Option<Arc<AuthManager>>,
     otel_event_manager: Option<OtelEventManager>,
+    cassette: Option<&Arc<Cassette>>,
     log_tag: Option<&str>,
 ) -> Result<ResponseStream> {
     if prompt.output_schema.is_some() {
@@ -295,6 +299,11 @@ pub(crate) async fn stream_chat_completions(
         "stream": true,
         "tools": tools_json,
     });
+    if let Some(max_tokens) = prompt.max_output_tokens
+        && let Some(obj) = payload.as_object_mut()
+    {
+        obj.insert("max_tokens".to_string(), json!(max_tokens));
+    }
 
     if let Some(openrouter_cfg) = provider.openrouter_config() {
         if let Some(obj) = payload.as_object_mut() {
@@ -335,6 +344,19 @@ pub(crate) async fn stream_chat_completions(
         serde_json::to_string_pretty(&payload).unwrap_or_default()
     );
 
+    if let Some(cassette) = cassette.filter(|cassette| cassette.is_replay()) {
+        let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(1600);
+        tokio::spawn(process_chat_sse(
+            cassette.replay(&payload)?,
+            tx_event,
+            provider.stream_idle_timeout(),
+            Arc::clone(debug_logger),
+            String::new(),
+            otel_event_manager,
+        ));
+        return Ok(ResponseStream { rx_event });
+    }
+
     let mut attempt = 0;
     let max_retries = provider.request_max_retries();
     let mut request_id = String::new();
@@ -391,7 +413,11 @@ pub(crate) async fn stream_chat_completions(
                     );
                 }
                 let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(1600);
-                let stream = resp.bytes_stream().map_err(CodexErr::Reqwest);
+                let stream = tee_response(
+                    cassette,
+                    &payload,
+                    resp.bytes_stream().map_err(CodexErr::Reqwest),
+                )?;
                 let debug_logger_clone = Arc::clone(&debug_logger);
                 let request_id_clone = request_id.clone();
                 tokio::spawn(process_chat_sse(
@@ -1018,6 +1044,11 @@ where
                 Poll::Ready(Some(Ok(ResponseEvent::ModelsEtag(etag)))) => {
                     return Poll::Ready(Some(Ok(ResponseEvent::ModelsEtag(etag))));
                 }
+                Poll::Ready(Some(Ok(ResponseEvent::OutputTruncated { max_output_tokens }))) => {
+                    return Poll::Ready(Some(Ok(ResponseEvent::OutputTruncated {
+                        max_output_tokens,
+                    })));
+                }
                 Poll::Ready(Some(Ok(ResponseEvent::Completed {
                     response_id,
                     token_usage,
@@ -35,6 +35,7 @@ use chrono::{DateTime, Duration as ChronoDuration, Utc};
 const AUTH_REQUIRED_MESSAGE: &str = "Authentication required. Run `code login` to continue.";
 
 use crate::agent_defaults::{default_agent_configs, enabled_agent_model_specs};
+use crate::anthropic_messages::stream_anthropic_messages;
 use crate::chat_completions::AggregateStreamExt;
 use crate::chat_completions::stream_chat_completions;
 use crate::client_common::Prompt;
@@ -53,8 +54,11 @@ use crate::error::Result;
 use crate::error::RetryLimitReachedError;
 use crate::error::UnexpectedResponseError;
 use crate::error::UsageLimitReachedError;
+use crate::cassette::Cassette;
+use crate::cassette::tee_response;
 use crate::flags::CODEX_RS_SSE_FIXTURE;
-use crate::model_family::{find_family_for_model, ModelFamily};
+use crate::model_failover::ModelFailover;
+use crate::model_family::{derive_default_model_family, find_family_for_model, ModelFamily};
 use crate::model_provider_info::ModelProviderInfo;
 use crate::model_provider_info::WireApi;
 use crate::openai_tools::create_tools_json_for_responses_api;
@@ -211,6 +215,8 @@ pub struct ModelClient {
     reasoning_summary_disabled: AtomicBool,
     verbosity: TextVerbosityConfig,
     debug_logger: Arc<Mutex<DebugLogger>>,
+    failover: Arc<ModelFailover>,
+    cassette: Option<Arc<Cassette>>,
 }
 
 impl Clone for ModelClient {
@@ -229,6 +235,8 @@ impl Clone for ModelClient {
             ),
             verbosity: self.verbosity,
             debug_logger: Arc::clone(&self.debug_logger),
+            failover: Arc::clone(&self.failover),
+            cassette: self.cassette.clone(),
         }
     }
 }
@@ -248,6 +256,14 @@ impl ModelClient {
         let effective_verbosity = clamp_text_verbosity_for_model(config.model.as_str(), verbosity);
         let clamped_effort = clamp_reasoning_effort_for_model(config.model.as_str(), effort);
         let client = create_client(&config.responses_originator_header);
+        let failover = Arc::new(ModelFailover::new(
+            format!("{}:{}", config.model_provider_id, config.model),
+            config.model_failover.clone(),
+        ));
+        let cassette = config
+            .model_cassette
+            .as_ref()
+            .map(|mode| Cassette::shared(mode, &config.cwd, &config.code_home));
 
         Self {
             config,
@@ -261,6 +277,8 @@ impl ModelClient {
             reasoning_summary_disabled: AtomicBool::new(false),
             verbosity: effective_verbosity,
             debug_logger,
+            failover,
+            cassette,
         }
     }
 
@@ -422,10 +440,91 @@ impl ModelClient {
         &self.config.model_family
     }
 
-    /// Dispatches to either the Responses or Chat implementation depending on
-    /// the provider config.  Public callers always invoke `stream()` – the
-    /// specialised helpers are private to avoid accidental misuse.
+    /// Streams `prompt` from the provider currently serving the session: the
+    /// configured one, or the active `[model_failover]` entry after a
+    /// failover. Public callers always invoke `stream()` – the specialised
+    /// helpers are private to avoid accidental misuse.
     pub async fn stream(&self, prompt: &Prompt) -> Result<ResponseStream> {
+        match self.failover_client() {
+            Some(client) => client.dispatch_stream(prompt).await,
+            None => self.dispatch_stream(prompt).await,
+        }
+    }
+
+    /// Client for the active failover target, or `None` while the configured
+    /// provider serves requests.
+    fn failover_client(&self) -> Option<ModelClient> {
+        let target = self.failover.active_target()?;
+        let mut config = (*self.config).clone();
+        config.model = target.model.clone();
+        config.model_family = find_family_for_model(&target.model)
+            .unwrap_or_else(|| derive_default_model_family(&target.model));
+        config.model_provider_id = target.provider_id.clone();
+        config.model_provider = target.provider.clone();
+        // Only providers that take OpenAI credentials get the session's auth.
+        let auth_manager = if target.provider.requires_openai_auth {
+            self.auth_manager.clone()
+        } else {
+            None
+        };
+
+        Some(Self {
+            config: Arc::new(config),
+            auth_manager,
+            otel_event_manager: self.otel_event_manager.clone(),
+            client: self.client.clone(),
+            provider: target.provider.clone(),
+            session_id: self.session_id,
+            effort: clamp_reasoning_effort_for_model(&target.model, self.effort),
+            summary: self.summary,
+            reasoning_summary_disabled: AtomicBool::new(false),
+            verbosity: clamp_text_verbosity_for_model(&target.model, self.verbosity),
+            debug_logger: Arc::clone(&self.debug_logger),
+            failover: Arc::clone(&self.failover),
+            cassette: self.cassette.clone(),
+        })
+    }
+
+    /// Move to the next `[model_failover]` entry for the rest of the session.
+    /// Returns its `provider:model` label, or `None` when the chain is
+    /// exhausted.
+    pub fn fail_over(&self) -> Option<String> {
+        self.failover.advance().map(|target| target.label())
+    }
+
+    /// `provider:model` of whatever currently serves requests.
+    pub fn serving_label(&self) -> String {
+        self.failover.active_label()
+    }
+
+    /// Count `usage` against whatever currently serves requests.
+    pub(crate) fn record_served_usage(&self, usage: &TokenUsage) {
+        self.failover.record_usage(usage);
+    }
+
+    /// Tokens used through `label` (`provider:model`) this session.
+    pub(crate) fn tokens_used_by(&self, label: &str) -> u64 {
+        self.failover.tokens_used(label)
+    }
+
+    /// Usage per `provider:model` once the session has failed over.
+    pub(crate) fn usage_by_provider(&self) -> BTreeMap<String, TokenUsage> {
+        self.failover.usage_by_label()
+    }
+
+    /// Provider id of the active `[model_failover]` entry when it does not
+    /// take the session's OpenAI credentials, so its usage is billed outside
+    /// the active account.
+    pub(crate) fn separately_billed_provider(&self) -> Option<String> {
+        self.failover
+            .active_target()
+            .filter(|target| !target.provider.requires_openai_auth)
+            .map(|target| target.provider_id.clone())
+    }
+
+    /// Dispatches to the Responses, Chat or Anthropic Messages implementation
+    /// depending on the provider config.
+    async fn dispatch_stream(&self, prompt: &Prompt) -> Result<ResponseStream> {
         let env_log_tag = std::env::var("CODE_DEBUG_LOG_TAG").ok();
         let log_tag = env_log_tag
             .as_deref()
@@ -451,6 +550,7 @@ impl ModelClient {
                     &self.debug_logger,
                     self.auth_manager.clone(),
                     self.otel_event_manager.clone(),
+                    self.cassette.as_ref(),
                     log_tag,
                 )
                 .await?;
@@ -480,6 +580,36 @@ impl ModelClient {
 
                 Ok(ResponseStream { rx_event: rx })
             }
+            WireApi::Anthropic => {
+                let effective_family = prompt
+                    .model_family_override
+                    .as_ref()
+                    .unwrap_or(&self.config.model_family);
+                let model_slug = prompt
+                    .model_override
+                    .as_deref()
+                    .unwrap_or(self.config.model.as_str());
+                // Messages streams emit finished items themselves, so no
+                // aggregation adapter is needed here.
+                stream_anthropic_messages(
+                    prompt,
+                    effective_family,
+                    model_slug,
+                    self.effort,
+                    cap_output_tokens(
+                        prompt.max_output_tokens,
+                        self.config.model_max_output_tokens,
+                    ),
+                    &self.client,
+                    &self.provider,
+                    &self.debug_logger,
+                    self.auth_manager.clone(),
+                    self.otel_event_manager.clone(),
+                    self.cassette.as_ref(),
+                    log_tag,
+                )
+                .await
+            }
         }
     }
 
@@ -614,6 +744,7 @@ impl ModelClient {
                 include,
                 // Use a stable per-process cache key (session id). With store=false this is inert.
                 prompt_cache_key: Some(session_id_str.clone()),
+                max_output_tokens: prompt.max_output_tokens,
             };
 
             let mut payload_json = serde_json::to_value(&payload)?;
@@ -641,6 +772,20 @@ impl ModelClient {
             }
             let payload_body = serde_json::to_string(&payload_json)?;
 
+            if let Some(cassette) = self.cassette.as_ref().filter(|cassette| cassette.is_replay()) {
+                let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(1600);
+                tokio::spawn(process_sse(
+                    cassette.replay(&payload_json)?,
+                    tx_event,
+                    self.provider.stream_idle_timeout(),
+                    Arc::clone(&self.debug_logger),
+                    String::new(),
+                    self.otel_event_manager.clone(),
+                    Arc::new(RwLock::new(StreamCheckpoint::default())),
+                ));
+                return Ok(ResponseStream { rx_event });
+            }
+
             let mut auth_refresh_error: Option<RefreshTokenError> = None;
 
             // Always fetch the latest auth in case a prior attempt refreshed the token.
@@ -773,7 +918,11 @@ impl ModelClient {
                     }
 
                     // spawn task to process SSE
-                    let stream = resp.bytes_stream().map_err(CodexErr::Reqwest);
+                    let stream = tee_response(
+                        self.cassette.as_ref(),
+                        &payload_json,
+                        resp.bytes_stream().map_err(CodexErr::Reqwest),
+                    )?;
                     let debug_logger = Arc::clone(&self.debug_logger);
                     let request_id_clone = request_id.clone();
                     let otel_event_manager = self.otel_event_manager.clone();
@@ -1150,7 +1299,10 @@ impl ModelClient {
     }
 
     pub fn get_provider(&self) -> ModelProviderInfo {
-        self.provider.clone()
+        match self.failover.active_target() {
+            Some(target) => target.provider.clone(),
+            None => self.provider.clone(),
+        }
     }
 
     /// Returns the currently configured model slug.
@@ -1515,6 +1667,14 @@ struct ResponseCompletedOutputTokensDetails {
     reasoning_tokens: u64,
 }
 
+/// The smaller of a per-request output cap and the configured one.
+fn cap_output_tokens(requested: Option<u64>, configured: Option<u64>) -> Option<u64> {
+    match (requested, configured) {
+        (Some(requested), Some(configured)) => Some(requested.min(configured)),
+        (requested, configured) => requested.or(configured),
+    }
+}
+
 fn attach_item_ids(payload_json: &mut Value, original_items: &[ResponseItem]) {
     let Some(input_value) = payload_json.get_mut("input") else {
         return;
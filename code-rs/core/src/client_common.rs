@@ -85,6 +85,8 @@ pub struct Prompt {
     pub model_family_override: Option<ModelFamily>,
     /// Optional the output schema for the model's response.
     pub output_schema: Option<Value>,
+    /// Optional cap on the tokens the model may generate for this request.
+    pub max_output_tokens: Option<u64>,
     /// Optional tag used to route debug logs into helper-specific directories.
     pub log_tag: Option<String>,
     /// Optional override for session/conversation identifiers used for caching.
@@ -110,6 +112,7 @@ impl Default for Prompt {
             model_override: None,
             model_family_override: None,
             output_schema: None,
+            max_output_tokens: None,
             log_tag: None,
             session_id_override: None,
             model_descriptions: None,
@@ -308,6 +311,11 @@ pub enum ResponseEvent {
     },
     RateLimits(RateLimitSnapshotEvent),
     ModelsEtag(String),
+    /// The model stopped because it hit the output token limit, so the
+    /// response is incomplete.
+    OutputTruncated {
+        max_output_tokens: u64,
+    },
 }
 
 #[derive(Debug, Serialize)]
@@ -460,6 +468,8 @@ pub(crate) struct ResponsesApiRequest<'a> {
     pub(crate) include: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) prompt_cache_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) max_output_tokens: Option<u64>,
 }
 
 pub(crate) fn create_reasoning_param_for_request(
@@ -620,6 +630,7 @@ mod tests {
             stream: true,
             include: vec![],
             prompt_cache_key: None,
+            max_output_tokens: None,
             text: Some(Text { verbosity: OpenAiTextVerbosity::Low, format: None }),
         };
 
@@ -655,6 +666,7 @@ mod tests {
             stream: true,
             include: vec![],
             prompt_cache_key: None,
+            max_output_tokens: None,
             text: Some(Text {
                 verbosity: OpenAiTextVerbosity::Medium,
                 format: Some(TextFormat {
@@ -702,6 +714,7 @@ mod tests {
             stream: true,
             include: vec![],
             prompt_cache_key: None,
+            max_output_tokens: None,
             text: None,
         };
 
@@ -89,7 +89,7 @@ mod session;
 mod streaming;
 
 pub use session::ApprovedCommandPattern;
-pub(crate) use session::{Session, ToolCallCtx};
+pub(crate) use session::{AccountUsageContext, Session, ToolCallCtx};
 use self::compact::{build_compacted_history, collect_compaction_snippets};
 use self::compact_remote::run_inline_remote_auto_compact_task;
 use self::streaming::{add_pending_screenshot, capture_browser_screenshot, submission_loop};
@@ -193,12 +193,15 @@ fn to_proto_sandbox_policy(policy: SandboxPolicy) -> ProtoSandboxPolicy {
             exclude_tmpdir_env_var,
             exclude_slash_tmp,
             allow_git_writes,
+            network_allowlist: _,
+            read_denied_paths,
         } => ProtoSandboxPolicy::WorkspaceWrite {
             writable_roots,
             network_access,
             exclude_tmpdir_env_var,
             exclude_slash_tmp,
             allow_git_writes,
+            read_denied_paths: Some(read_denied_paths),
         },
     }
 }
@@ -533,11 +536,12 @@ async fn build_turn_status_items_legacy(sess: &Session) -> Vec<ResponseItem> {
                 };
 
                 // Try to capture screenshot and compare with last one
+                let tab_id = browser_manager.active_tab_id().await;
                 let screenshot_status = match capture_browser_screenshot(sess).await {
                     Ok((screenshot_path, _url)) => {
                         // Always update the UI with the latest screenshot, even if unchanged for LLM payload
                         // This ensures the user sees that a fresh capture occurred each turn.
-                        add_pending_screenshot(sess, screenshot_path.clone(), url.clone());
+                        add_pending_screenshot(sess, screenshot_path.clone(), url.clone(), tab_id);
                         // Check if screenshot has changed using image hashing
                         let mut last_screenshot_info = sess.last_screenshot_info.lock().unwrap();
 
@@ -614,6 +618,15 @@ async fn build_turn_status_items_legacy(sess: &Session) -> Vec<ResponseItem> {
                 };
                 current_status.push_str("\n");
                 current_status.push_str(&status_line);
+
+                if let Ok(tabs) = browser_manager.list_tabs().await {
+                    if tabs.len() > 1 {
+                        current_status.push_str(&format!(
+                            "\nBrowser tabs: {} open. Use browser action=tabs to list or switch.",
+                            tabs.len()
+                        ));
+                    }
+                }
             }
         }
     }
@@ -733,7 +746,8 @@ async fn build_turn_status_items_v2(sess: &Session) -> Vec<ResponseItem> {
 
             match capture_browser_screenshot(sess).await {
                 Ok((path, _)) => {
-                    add_pending_screenshot(sess, path.clone(), url.clone());
+                    let tab_id = browser_manager.active_tab_id().await;
+                    add_pending_screenshot(sess, path.clone(), url.clone(), tab_id);
                     let current_hash = crate::image_comparison::compute_image_hash(&path).ok();
                     let mut last_info = sess.last_screenshot_info.lock().unwrap();
                     let include_screenshot = should_include_browser_screenshot(
@@ -843,6 +857,27 @@ mod tests {
         assert!(should_include_browser_screenshot(&mut last, &path, Some(hash_two)));
     }
 
+    #[test]
+    fn proto_sandbox_policy_keeps_read_denied_paths() {
+        let policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: Vec::new(),
+            network_access: false,
+            network_allowlist: Vec::new(),
+            exclude_tmpdir_env_var: false,
+            exclude_slash_tmp: false,
+            allow_git_writes: true,
+            read_denied_paths: vec![PathBuf::from("/home/user/.ssh")],
+        };
+
+        let ProtoSandboxPolicy::WorkspaceWrite {
+            read_denied_paths, ..
+        } = to_proto_sandbox_policy(policy)
+        else {
+            panic!("expected workspace-write");
+        };
+        assert_eq!(read_denied_paths, Some(vec![PathBuf::from("/home/user/.ssh")]));
+    }
+
     fn make_snapshot(cwd: &str) -> EnvironmentContextSnapshot {
         EnvironmentContextSnapshot {
             version: EnvironmentContextSnapshot::VERSION,
@@ -957,8 +992,10 @@ use crate::timeboxed_exec_guidance::{
     AUTO_EXEC_TIMEBOXED_CLI_GUIDANCE,
     AUTO_EXEC_TIMEBOXED_REVIEW_GUIDANCE,
 };
+use crate::config_types::HookFailurePolicy;
 use crate::config_types::ProjectHookEvent;
 use crate::config_types::ShellEnvironmentPolicy;
+use crate::config_types::ExecLimits;
 use crate::conversation_history::ConversationHistory;
 use crate::error::{CodexErr, RetryAfter};
 use crate::error::Result as CodexResult;
@@ -974,6 +1011,8 @@ use crate::exec::process_exec_tool_call;
 use crate::review_format::format_review_findings_block;
 use crate::exec_env::create_env;
 use crate::mcp_connection_manager::McpConnectionManager;
+use crate::mcp_connection_manager::McpServerRequestHandlers;
+use crate::mcp_sampling::McpSampler;
 use crate::mcp_tool_call::handle_mcp_tool_call;
 use crate::model_family::{derive_default_model_family, find_family_for_model};
 use code_protocol::models::ContentItem;
@@ -993,7 +1032,7 @@ use crate::parse_command::parse_command;
 use crate::plan_tool::handle_update_plan;
 use crate::project_doc::get_user_instructions;
 use crate::skills::loader::load_skills;
-use crate::project_features::{ProjectCommand, ProjectHook, ProjectHooks};
+use crate::project_features::{HookDecision, ProjectCommand, ProjectHook, ProjectHooks};
 use crate::protocol::AgentMessageDeltaEvent;
 use crate::protocol::AgentMessageEvent;
 use crate::protocol::AgentReasoningDeltaEvent;
@@ -1013,6 +1052,7 @@ use crate::protocol::EventMsg;
 use crate::protocol::ExitedReviewModeEvent;
 use crate::protocol::ReviewSnapshotInfo;
 use crate::protocol::ListCustomPromptsResponseEvent;
+use crate::protocol::ListMcpResourcesResponseEvent;
 use crate::protocol::ListSkillsResponseEvent;
 use crate::protocol::{BrowserSnapshotEvent, EnvironmentContextDeltaEvent, EnvironmentContextFullEvent};
 use crate::protocol::ExecApprovalRequestEvent;
@@ -1031,6 +1071,8 @@ use crate::protocol::ReviewDecision;
 use crate::protocol::ValidationGroup;
 use crate::protocol::ReviewOutputEvent;
 use crate::protocol::ReviewRequest;
+use crate::protocol::SandboxDenial;
+use crate::protocol::SandboxDenialEvent;
 use crate::protocol::SandboxPolicy;
 use crate::protocol::SessionConfiguredEvent;
 use crate::protocol::Submission;
@@ -1039,9 +1081,11 @@ use std::sync::OnceLock;
 use tokio::sync::Notify;
 use crate::protocol::TurnDiffEvent;
 use crate::rollout::RolloutRecorder;
+use crate::command_safety::exec_policy::ExecPolicyOverlay;
 use crate::safety::SafetyCheck;
 use crate::safety::assess_command_safety;
 use crate::safety::assess_safety_for_untrusted_command;
+use crate::sandbox_denial::detect_sandbox_denials;
 use crate::shell;
 use crate::turn_diff_tracker::TurnDiffTracker;
 use crate::user_notification::UserNotification;
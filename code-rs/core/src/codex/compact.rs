@@ -8,6 +8,7 @@ use super::TurnContext;
 use super::streaming::get_last_assistant_message_from_turn;
 use crate::Prompt;
 use crate::client_common::ResponseEvent;
+use crate::config_types::ProjectHookEvent;
 use crate::environment_context::EnvironmentContext;
 use crate::error::CodexErr;
 use crate::error::RetryAfter;
@@ -31,6 +32,7 @@ use code_protocol::protocol::RolloutItem;
 use base64::Engine;
 use chrono::Utc;
 use futures::prelude::*;
+use serde_json::json;
 use code_app_server_protocol::AuthMode;
 use std::time::Duration;
 
@@ -175,7 +177,18 @@ pub(super) async fn run_inline_auto_compact_task(
     let sub_id = sess.next_internal_sub_id();
     let prompt_text = resolve_compact_prompt_text(turn_context.compact_prompt_override.as_deref());
     let input = vec![InputItem::Text { text: prompt_text.clone() }];
-    run_compact_task_inner_inline(sess, turn_context, sub_id, input).await
+    let token_usage = sess.state.lock().unwrap().token_usage_info.clone();
+    let history =
+        run_compact_task_inner_inline(Arc::clone(&sess), turn_context, sub_id.clone(), input).await;
+    sess.queue_lifecycle_hooks(ProjectHookEvent::ContextCompact, || {
+        json!({
+            "sub_id": sub_id,
+            "trigger": "auto",
+            "success": !history.is_empty(),
+            "token_usage": token_usage,
+        })
+    });
+    history
 }
 
 pub(super) async fn run_compact_task(
@@ -186,6 +199,7 @@ pub(super) async fn run_compact_task(
 ) {
     let start_event = sess.make_event(&sub_id, EventMsg::TaskStarted);
     sess.send_event(start_event).await;
+    let token_usage = sess.state.lock().unwrap().token_usage_info.clone();
     let compaction_result = if should_use_remote_compact_task(&sess).await {
         compact_remote::run_remote_compact_task(
             Arc::clone(&sess),
@@ -205,7 +219,14 @@ pub(super) async fn run_compact_task(
         .await
     };
 
-    let _ = compaction_result;
+    sess.queue_lifecycle_hooks(ProjectHookEvent::ContextCompact, || {
+        json!({
+            "sub_id": sub_id,
+            "trigger": "manual",
+            "success": compaction_result.is_ok(),
+            "token_usage": token_usage,
+        })
+    });
     let event = sess.make_event(
         &sub_id,
         EventMsg::TaskComplete(TaskCompleteEvent {
@@ -76,6 +76,7 @@ impl Drop for ExecDropGuard {
             stderr,
             exit_code,
             duration: Duration::ZERO,
+            resource_usage: None,
         });
 
         if let Some(session) = self.session.upgrade() {
@@ -132,7 +133,13 @@ fn sanitize_identifier(value: &str) -> String {
     slug
 }
 
-fn truncate_payload(text: &str, limit: usize) -> String {
+fn hook_label(hook: &ProjectHook) -> &str {
+    hook.name
+        .as_deref()
+        .unwrap_or_else(|| hook.command.first().map(String::as_str).unwrap_or("hook"))
+}
+
+pub(super) fn truncate_payload(text: &str, limit: usize) -> String {
     let mut iter = text.chars();
     let truncated: String = iter.by_ref().take(limit).collect();
     if iter.next().is_some() {
@@ -142,6 +149,30 @@ fn truncate_payload(text: &str, limit: usize) -> String {
     }
 }
 
+/// Payload for `turn.submit`: the text the user submitted and how many
+/// images came with it. `queued` is set when the input was queued behind a
+/// running turn instead of starting one.
+pub(super) fn turn_submit_hook_payload(sub_id: &str, items: &[InputItem], queued: bool) -> Value {
+    let text = items
+        .iter()
+        .filter_map(|item| match item {
+            InputItem::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let images = items
+        .iter()
+        .filter(|item| !matches!(item, InputItem::Text { .. }))
+        .count();
+    json!({
+        "sub_id": sub_id,
+        "text": truncate_payload(&text, HOOK_OUTPUT_LIMIT),
+        "images": images,
+        "queued": queued,
+    })
+}
+
 fn build_exec_hook_payload(
     event: ProjectHookEvent,
     ctx: &ExecCommandContext,
@@ -227,6 +258,24 @@ fn build_exec_hook_payload(
     }
 }
 
+/// Runs queued lifecycle hooks until the session is dropped. Lifecycle hooks
+/// do not take the session's `HookGuard`: they never run on behalf of a tool
+/// call, so there is nothing for them to block and no reason to queue them
+/// behind a slow `tool.*` hook.
+pub(super) fn spawn_lifecycle_hook_worker(
+    session: Weak<Session>,
+    mut rx: tokio::sync::mpsc::UnboundedReceiver<(ProjectHookEvent, Value)>,
+) {
+    tokio::spawn(async move {
+        while let Some((event, payload)) = rx.recv().await {
+            let Some(sess) = session.upgrade() else {
+                break;
+            };
+            sess.run_lifecycle_hooks(event, &payload).await;
+        }
+    });
+}
+
 pub struct ExecInvokeArgs<'a> {
     pub params: ExecParams,
     pub sandbox_type: SandboxType,
@@ -337,6 +386,7 @@ impl Session {
             duration,
             exit_code,
             timed_out: _,
+            resource_usage,
         } = output;
         // Because stdout and stderr could each be up to 100 KiB, we send
         // truncated versions.
@@ -359,6 +409,7 @@ impl Session {
                 stderr,
                 exit_code: *exit_code,
                 duration: *duration,
+                resource_usage: *resource_usage,
             })
         };
         let order = crate::protocol::OrderMeta { request_ordinal: attempt_req, output_index, sequence_number: seq_hint };
@@ -482,6 +533,7 @@ impl Session {
                     stderr,
                     exit_code,
                     duration: Duration::ZERO,
+                    resource_usage: None,
                 });
                 let event = self.make_event_with_order(sub_id, msg, order_meta.clone(), order_meta.sequence_number);
                 let _ = self.tx_event.send(event).await;
@@ -492,7 +544,7 @@ impl Session {
     async fn run_exec_with_events_inner<'a>(
         &self,
         turn_diff_tracker: &mut TurnDiffTracker,
-        begin_ctx: ExecCommandContext,
+        mut begin_ctx: ExecCommandContext,
         exec_args: ExecInvokeArgs<'a>,
         seq_hint: Option<u64>,
         output_index: Option<u32>,
@@ -526,21 +578,21 @@ impl Session {
         let ExecInvokeArgs { params, sandbox_type, sandbox_policy, sandbox_cwd, code_linux_sandbox_exe, stdout_stream } = exec_args;
         let tracking_command = params.command.clone();
         let dry_run_analysis = analyze_command(&tracking_command);
-        let params = maybe_run_with_user_profile(params, self);
-        let params_for_hooks = if enable_hooks {
+        let mut params = maybe_run_with_user_profile(params, self);
+        let mut params_for_hooks = if enable_hooks {
             Some(params.clone())
         } else {
             None
         };
 
         if enable_hooks {
-            if let Some(params_ref) = params_for_hooks.as_ref() {
+            if let Some(params_ref) = params_for_hooks.as_mut() {
                 let before_event = if is_apply_patch {
                     ProjectHookEvent::FileBeforeWrite
                 } else {
                     ProjectHookEvent::ToolBefore
                 };
-                self
+                let decision = self
                     .run_hooks_for_exec_event(
                         turn_diff_tracker,
                         before_event,
@@ -550,6 +602,27 @@ impl Session {
                         attempt_req,
                     )
                     .await;
+                match decision {
+                    HookDecision::Allow => {}
+                    HookDecision::Deny { reason } => {
+                        exec_guard.mark_completed();
+                        return Err(CodexErr::HookDenied(reason));
+                    }
+                    HookDecision::Modify { command, .. } => {
+                        if let Some(command) = command {
+                            if let Err(reason) = self
+                                .review_hook_rewrite(&begin_ctx, &command, &params, sandbox_type, sandbox_policy)
+                                .await
+                            {
+                                exec_guard.mark_completed();
+                                return Err(CodexErr::HookDenied(reason));
+                            }
+                            begin_ctx.command_for_display = command.clone();
+                            params.command = command.clone();
+                            params_ref.command = command;
+                        }
+                    }
+                }
             }
         }
 
@@ -571,6 +644,7 @@ impl Session {
                     aggregated_output: StreamOutput::new(get_error_message_ui(e)),
                     duration: Duration::default(),
                     timed_out: false,
+                    resource_usage: None,
                 };
                 &output_stderr
             }
@@ -655,6 +729,13 @@ impl Session {
         }
     }
 
+    /// Runs the hooks registered for `event`. `tool.before` and
+    /// `file.before_write` hooks are blocking: they run in order until one
+    /// denies the call, and a `modify` decision is shown to later hooks and
+    /// returned so the caller can run the rewritten command or patch. Other
+    /// events always return `HookDecision::Allow`. Hooks from concurrent tool
+    /// calls wait for each other rather than being skipped, so a gate hook is
+    /// never bypassed by a parallel call.
     pub(super) async fn run_hooks_for_exec_event(
         &self,
         turn_diff_tracker: &mut TurnDiffTracker,
@@ -663,22 +744,129 @@ impl Session {
         params: &ExecParams,
         output: Option<&ExecToolCallOutput>,
         attempt_req: u64,
-    ) {
+    ) -> HookDecision {
         if self.project_hooks.is_empty() {
-            return;
+            return HookDecision::Allow;
         }
         let hooks: Vec<ProjectHook> = self.project_hooks.hooks_for(event).cloned().collect();
         if hooks.is_empty() {
-            return;
+            return HookDecision::Allow;
         }
-        let Some(_guard) = HookGuard::try_acquire(&self.hook_guard) else {
-            return;
+        let Some(_guard) = HookGuard::acquire(&self.hook_lock).await else {
+            return HookDecision::Allow;
         };
-        let payload = build_exec_hook_payload(event, exec_ctx, params, output);
+        let blocking = matches!(
+            event,
+            ProjectHookEvent::ToolBefore | ProjectHookEvent::FileBeforeWrite
+        );
+        let mut params = params.clone();
+        let mut modified_command: Option<Vec<String>> = None;
+        let mut modified_patch: Option<String> = None;
+        let mut payload = build_exec_hook_payload(event, exec_ctx, &params, output);
         for (idx, hook) in hooks.into_iter().enumerate() {
-            self
+            let decision = self
                 .run_hook_command(turn_diff_tracker, &hook, event, &payload, Some(exec_ctx), attempt_req, idx)
                 .await;
+            if !blocking {
+                continue;
+            }
+            match decision {
+                HookDecision::Allow => {}
+                HookDecision::Deny { reason } => {
+                    return HookDecision::Deny {
+                        reason: format!("Blocked by hook `{}`: {reason}", hook_label(&hook)),
+                    };
+                }
+                HookDecision::Modify { command, patch } => {
+                    match event {
+                        ProjectHookEvent::ToolBefore => {
+                            if let Some(command) = command.filter(|command| !command.is_empty()) {
+                                params.command = command.clone();
+                                modified_command = Some(command);
+                            }
+                        }
+                        _ => {
+                            if let Some(patch) = patch {
+                                modified_patch = Some(patch);
+                            }
+                        }
+                    }
+                    payload = build_exec_hook_payload(event, exec_ctx, &params, output);
+                    if let (Some(patch), Some(map)) = (modified_patch.as_ref(), payload.as_object_mut()) {
+                        map.insert("patch".to_string(), Value::String(patch.clone()));
+                    }
+                }
+            }
+        }
+        if modified_command.is_some() || modified_patch.is_some() {
+            HookDecision::Modify {
+                command: modified_command,
+                patch: modified_patch,
+            }
+        } else {
+            HookDecision::Allow
+        }
+    }
+
+    /// The caller assessed and approved the command before a `tool.before`
+    /// hook rewrote it, so check the rewrite the same way. A command that was
+    /// going to run unsandboxed on the strength of an approval only keeps
+    /// that status if the new command is trusted on its own.
+    async fn review_hook_rewrite(
+        &self,
+        ctx: &ExecCommandContext,
+        command: &[String],
+        params: &ExecParams,
+        sandbox_type: SandboxType,
+        sandbox_policy: &SandboxPolicy,
+    ) -> Result<(), String> {
+        let safety = {
+            let state = self.state.lock().unwrap();
+            assess_command_safety(
+                command,
+                self.approval_policy,
+                sandbox_policy,
+                &state.approved_commands,
+                self.exec_policy.as_ref(),
+                params.with_escalated_permissions.unwrap_or(false),
+            )
+        };
+        let needs_approval = match safety {
+            SafetyCheck::AutoApprove { sandbox_type: assessed, .. } => {
+                sandbox_type == SandboxType::None && assessed != SandboxType::None
+            }
+            SafetyCheck::AskUser => true,
+            SafetyCheck::Reject { reason } => {
+                return Err(format!("hook-modified command rejected: {reason}"));
+            }
+        };
+        if !needs_approval {
+            return Ok(());
+        }
+
+        let rx_approve = self
+            .request_command_approval(
+                ctx.sub_id.clone(),
+                ctx.call_id.clone(),
+                command.to_vec(),
+                params.cwd.clone(),
+                Some("A tool.before hook rewrote this command.".to_string()),
+                None,
+            )
+            .await;
+        match rx_approve.await.unwrap_or_default() {
+            ReviewDecision::Approved => Ok(()),
+            ReviewDecision::ApprovedForSession => {
+                self.add_approved_command(ApprovedCommandPattern::new(
+                    command.to_vec(),
+                    ApprovedCommandMatchKind::Exact,
+                    None,
+                ));
+                Ok(())
+            }
+            ReviewDecision::Denied | ReviewDecision::Abort => {
+                Err("hook-modified command rejected by user".to_string())
+            }
         }
     }
 
@@ -690,19 +878,46 @@ impl Session {
         if hooks.is_empty() {
             return;
         }
-        let Some(_guard) = HookGuard::try_acquire(&self.hook_guard) else {
+        let Some(_guard) = HookGuard::acquire(&self.hook_lock).await else {
             return;
         };
         let payload = self.build_session_payload(event);
         let mut tracker = TurnDiffTracker::new();
         let attempt_req = self.current_request_ordinal();
         for (idx, hook) in hooks.into_iter().enumerate() {
-            self
+            let _ = self
                 .run_hook_command(&mut tracker, &hook, event, &payload, None, attempt_req, idx)
                 .await;
         }
     }
 
+    /// Queues the hooks registered for a lifecycle `event` (turns,
+    /// compaction, agents, approvals, Auto Drive). They run one at a time on
+    /// a background worker, so the caller is never delayed and hooks observe
+    /// events in the order they happened. `payload` is only built when a hook
+    /// is registered for `event`; its `event` key is filled in here.
+    pub(crate) fn queue_lifecycle_hooks(&self, event: ProjectHookEvent, payload: impl FnOnce() -> Value) {
+        if self.project_hooks.hooks_for(event).next().is_none() {
+            return;
+        }
+        let mut payload = payload();
+        if let Some(map) = payload.as_object_mut() {
+            map.insert("event".to_string(), Value::String(event.as_str().to_string()));
+        }
+        let _ = self.lifecycle_hook_tx.send((event, payload));
+    }
+
+    async fn run_lifecycle_hooks(&self, event: ProjectHookEvent, payload: &Value) {
+        let hooks: Vec<ProjectHook> = self.project_hooks.hooks_for(event).cloned().collect();
+        let mut tracker = TurnDiffTracker::new();
+        let attempt_req = self.current_request_ordinal();
+        for (idx, hook) in hooks.into_iter().enumerate() {
+            let _ = self
+                .run_hook_command(&mut tracker, &hook, event, payload, None, attempt_req, idx)
+                .await;
+        }
+    }
+
     fn build_session_payload(&self, event: ProjectHookEvent) -> Value {
         match event {
             ProjectHookEvent::SessionStart => json!({
@@ -730,7 +945,7 @@ impl Session {
         base_ctx: Option<&ExecCommandContext>,
         attempt_req: u64,
         index: usize,
-    ) {
+    ) -> HookDecision {
         let sub_id = base_ctx
             .map(|ctx| ctx.sub_id.clone())
             .unwrap_or_else(|| INITIAL_SUBMIT_ID.to_string());
@@ -766,6 +981,7 @@ impl Session {
             env,
             with_escalated_permissions: Some(false),
             justification: None,
+            exec_limits: self.exec_limits.clone(),
         };
 
         let exec_ctx = ExecCommandContext {
@@ -786,7 +1002,7 @@ impl Session {
             stdout_stream: None,
         };
 
-        if let Err(err) = Box::pin(self.run_exec_with_events_inner(
+        match Box::pin(HookGuard::scope(self.run_exec_with_events_inner(
             turn_diff_tracker,
             exec_ctx,
             exec_args,
@@ -794,21 +1010,32 @@ impl Session {
             None,
             attempt_req,
             false,
-        ))
+        )))
         .await
         {
-            let hook_label = hook
-                .name
-                .as_deref()
-                .unwrap_or_else(|| hook.command.first().map(String::as_str).unwrap_or("hook"));
-            let order = self.next_background_order(&sub_id, attempt_req, None);
-            self
-                .notify_background_event_with_order(
-                    &sub_id,
-                    order,
-                    format!("Hook `{}` failed: {}", hook_label, get_error_message_ui(&err)),
-                )
-                .await;
+            Ok(output) => HookDecision::from_hook_output(
+                output.exit_code,
+                &output.stdout.text,
+                &output.stderr.text,
+                hook.on_failure,
+            ),
+            Err(err) => {
+                let message = get_error_message_ui(&err);
+                let order = self.next_background_order(&sub_id, attempt_req, None);
+                self
+                    .notify_background_event_with_order(
+                        &sub_id,
+                        order,
+                        format!("Hook `{}` failed: {}", hook_label(hook), message),
+                    )
+                    .await;
+                match hook.on_failure {
+                    HookFailurePolicy::Deny => HookDecision::Deny {
+                        reason: format!("hook failed: {message}"),
+                    },
+                    HookFailurePolicy::Allow => HookDecision::Allow,
+                }
+            }
         }
     }
 
@@ -855,6 +1082,7 @@ impl Session {
             env,
             with_escalated_permissions: Some(false),
             justification: None,
+            exec_limits: self.exec_limits.clone(),
         };
 
         let call_id = format!("project_cmd_{}", sanitize_identifier(&command.name));
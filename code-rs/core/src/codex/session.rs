@@ -130,6 +130,9 @@ impl EnvironmentContextStreamRegistry {
 #[derive(Default)]
 pub(super) struct State {
     pub(super) approved_commands: HashSet<ApprovedCommandPattern>,
+    /// Writable roots the user granted after a sandbox denial; they apply to
+    /// every later sandboxed command in this session.
+    pub(super) granted_writable_roots: Vec<PathBuf>,
     pub(super) current_task: Option<AgentTask>,
     pub(super) pending_approvals: HashMap<String, oneshot::Sender<ReviewDecision>>,
     pub(super) pending_input: Vec<ResponseInputItem>,
@@ -231,29 +234,50 @@ pub(super) struct TurnScratchpad {
 }
 
 #[derive(Clone)]
-pub(super) struct AccountUsageContext {
-    pub(super) code_home: PathBuf,
-    pub(super) account_id: String,
-    pub(super) plan: Option<String>,
+pub(crate) struct AccountUsageContext {
+    pub(crate) code_home: PathBuf,
+    pub(crate) account_id: String,
+    pub(crate) plan: Option<String>,
+}
+
+impl AccountUsageContext {
+    /// Usage context for the active account under `code_home`, if any.
+    pub(crate) fn for_code_home(code_home: &Path) -> Option<Self> {
+        let code_home = code_home.to_path_buf();
+        let account_id = auth_accounts::get_active_account_id(&code_home).ok().flatten()?;
+        let plan = auth_accounts::find_account(&code_home, &account_id)
+            .ok()
+            .flatten()
+            .and_then(|account| {
+                account
+                    .tokens
+                    .as_ref()
+                    .and_then(|tokens| tokens.id_token.get_chatgpt_plan_type())
+            });
+        Some(Self {
+            code_home,
+            account_id,
+            plan,
+        })
+    }
 }
 
 pub(super) fn account_usage_context(sess: &Session) -> Option<AccountUsageContext> {
-    let code_home = sess.client.code_home().to_path_buf();
-    let account_id = auth_accounts::get_active_account_id(&code_home).ok().flatten()?;
-    let plan = auth_accounts::find_account(&code_home, &account_id)
-        .ok()
-        .flatten()
-        .and_then(|account| {
-            account
-                .tokens
-                .as_ref()
-                .and_then(|tokens| tokens.id_token.get_chatgpt_plan_type())
-        });
-    Some(AccountUsageContext {
-        code_home,
-        account_id,
-        plan,
-    })
+    AccountUsageContext::for_code_home(sess.client.code_home())
+}
+
+/// Ledger for usage served by whatever currently serves the session: the
+/// active account, or a ledger of its own for a `[model_failover]` provider
+/// that does not use the account's credentials.
+pub(super) fn served_usage_context(sess: &Session) -> Option<AccountUsageContext> {
+    match sess.client.separately_billed_provider() {
+        Some(provider_id) => Some(AccountUsageContext {
+            code_home: sess.client.code_home().to_path_buf(),
+            account_id: account_usage::provider_ledger_id(&provider_id),
+            plan: None,
+        }),
+        None => account_usage_context(sess),
+    }
 }
 
 pub(super) fn spawn_usage_task<F>(task: F)
@@ -322,7 +346,11 @@ pub(crate) struct Session {
     pub(super) compact_prompt_override: Option<String>,
     pub(super) approval_policy: AskForApproval,
     pub(super) sandbox_policy: SandboxPolicy,
+    /// Default execpolicy merged with the user and project overlays, when
+    /// any overlay files exist. Loaded once when the session is configured.
+    pub(super) exec_policy: Option<ExecPolicyOverlay>,
     pub(super) shell_environment_policy: ShellEnvironmentPolicy,
+    pub(super) exec_limits: ExecLimits,
     pub(super) _writable_roots: Vec<PathBuf>,
     pub(super) disable_response_storage: bool,
     pub(super) tools_config: ToolsConfig,
@@ -362,7 +390,10 @@ pub(crate) struct Session {
     pub(super) project_hooks: ProjectHooks,
     pub(super) project_commands: Vec<ProjectCommand>,
     pub(super) tool_output_max_bytes: usize,
-    pub(super) hook_guard: AtomicBool,
+    pub(super) hook_lock: tokio::sync::Mutex<()>,
+    /// Lifecycle hook invocations (`turn.*`, `agent.*`, ...) waiting to be
+    /// run by the worker started in `spawn_lifecycle_hook_worker`.
+    pub(super) lifecycle_hook_tx: tokio::sync::mpsc::UnboundedSender<(ProjectHookEvent, Value)>,
     pub(super) github: Arc<RwLock<crate::config_types::GithubConfig>>,
     pub(super) validation: Arc<RwLock<crate::config_types::ValidationConfig>>,
     pub(super) self_handle: Weak<Session>,
@@ -372,22 +403,32 @@ pub(crate) struct Session {
     pub(super) retention_config: crate::config_types::RetentionConfig,
     pub(super) model_descriptions: Option<String>,
 }
+tokio::task_local! {
+    /// Set while a project hook's own command runs.
+    static RUNNING_PROJECT_HOOK: ();
+}
+
+/// Serializes project hooks across a session's concurrent tool calls.
 pub(super) struct HookGuard<'a> {
-    flag: &'a AtomicBool,
+    _lock: tokio::sync::MutexGuard<'a, ()>,
 }
 
 impl<'a> HookGuard<'a> {
-    pub(super) fn try_acquire(flag: &'a AtomicBool) -> Option<Self> {
-        flag
-            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
-            .ok()
-            .map(|_| Self { flag })
+    /// Waits for any other hook in the session to finish. Returns `None` only
+    /// when called from inside a hook's own command, which must not trigger
+    /// hooks again.
+    pub(super) async fn acquire(lock: &'a tokio::sync::Mutex<()>) -> Option<Self> {
+        if RUNNING_PROJECT_HOOK.try_with(|_| ()).is_ok() {
+            return None;
+        }
+        Some(Self {
+            _lock: lock.lock().await,
+        })
     }
-}
 
-impl Drop for HookGuard<'_> {
-    fn drop(&mut self) {
-        self.flag.store(false, Ordering::SeqCst);
+    /// Runs `fut` (a hook's command) so that it cannot re-enter the hooks.
+    pub(super) async fn scope<F: std::future::Future>(fut: F) -> F::Output {
+        RUNNING_PROJECT_HOOK.scope((), fut).await
     }
 }
 
@@ -520,6 +561,27 @@ impl Session {
         &self.sandbox_policy
     }
 
+    /// The session's sandbox policy plus any writable roots granted since.
+    pub(crate) fn effective_sandbox_policy(&self) -> SandboxPolicy {
+        let mut policy = self.sandbox_policy.clone();
+        if let SandboxPolicy::WorkspaceWrite { writable_roots, .. } = &mut policy {
+            let state = self.state.lock().unwrap();
+            for root in &state.granted_writable_roots {
+                if !writable_roots.contains(root) {
+                    writable_roots.push(root.clone());
+                }
+            }
+        }
+        policy
+    }
+
+    pub(super) fn grant_writable_root(&self, root: PathBuf) {
+        let mut state = self.state.lock().unwrap();
+        if !state.granted_writable_roots.contains(&root) {
+            state.granted_writable_roots.push(root);
+        }
+    }
+
     pub(crate) fn session_uuid(&self) -> Uuid {
         self.id
     }
@@ -1060,8 +1122,20 @@ impl Session {
         command: Vec<String>,
         cwd: PathBuf,
         reason: Option<String>,
+        grant_root: Option<PathBuf>,
     ) -> oneshot::Receiver<ReviewDecision> {
         let (tx_approve, rx_approve) = oneshot::channel();
+        self.queue_lifecycle_hooks(ProjectHookEvent::ApprovalRequest, || {
+            json!({
+                "sub_id": sub_id,
+                "call_id": call_id,
+                "kind": "exec",
+                "command": command,
+                "cwd": cwd.to_string_lossy(),
+                "reason": reason,
+                "grant_root": grant_root.as_ref().map(|root| root.to_string_lossy()),
+            })
+        });
         let event = self.make_event(
             &sub_id,
             EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent {
@@ -1069,6 +1143,7 @@ impl Session {
                 command,
                 cwd,
                 reason,
+                grant_root,
             }),
         );
         let _ = self.tx_event.send(event).await;
@@ -1090,11 +1165,22 @@ impl Session {
         grant_root: Option<PathBuf>,
     ) -> oneshot::Receiver<ReviewDecision> {
         let (tx_approve, rx_approve) = oneshot::channel();
+        let changes = convert_apply_patch_to_protocol(action);
+        self.queue_lifecycle_hooks(ProjectHookEvent::ApprovalRequest, || {
+            json!({
+                "sub_id": sub_id,
+                "call_id": call_id,
+                "kind": "patch",
+                "changes": serde_json::to_value(&changes).unwrap_or(Value::Null),
+                "reason": reason,
+                "grant_root": grant_root.as_ref().map(|root| root.to_string_lossy()),
+            })
+        });
         let event = self.make_event(
             &sub_id,
             EventMsg::ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent {
                 call_id: call_id.clone(),
-                changes: convert_apply_patch_to_protocol(action),
+                changes,
                 reason,
                 grant_root,
             }),
@@ -2245,6 +2331,7 @@ impl State {
     pub fn partial_clone(&self) -> Self {
         Self {
             approved_commands: self.approved_commands.clone(),
+            granted_writable_roots: self.granted_writable_roots.clone(),
             history: self.history.clone(),
             // Preserve request_ordinal so reconfigurations (e.g., /reasoning)
             // do not reset provider ordering mid-session.
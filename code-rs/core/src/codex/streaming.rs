@@ -4,6 +4,9 @@ use super::exec::{
     ExecCommandContext,
     ExecInvokeArgs,
     maybe_run_with_user_profile,
+    spawn_lifecycle_hook_worker,
+    truncate_payload,
+    turn_submit_hook_payload,
 };
 use super::session::{
     BackgroundExecState,
@@ -13,11 +16,13 @@ use super::session::{
     account_usage_context,
     format_retry_eta,
     is_connectivity_error,
+    served_usage_context,
     spawn_usage_task,
 };
 use crate::auth;
 use crate::auth_accounts;
 use crate::account_switching::RateLimitSwitchState;
+use crate::model_failover::is_provider_failure;
 use code_app_server_protocol::AuthMode as AppAuthMode;
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -534,9 +539,15 @@ pub(super) async fn submission_loop(
                     drop(old_session_arc);
                 }
 
+                let server_requests = McpServerRequestHandlers {
+                    elicitation_policy: config.mcp_elicitation,
+                    tx_event: Some(tx_event.clone()),
+                    sampler: Some(McpSampler::new(client.clone(), config.mcp_sampling.clone())),
+                };
                 let (mcp_connection_manager, failed_clients) = match McpConnectionManager::new(
                     config.mcp_servers.clone(),
                     excluded_tools,
+                    server_requests,
                 )
                 .await
                 {
@@ -571,6 +582,10 @@ pub(super) async fn submission_loop(
                 );
                 tools_config.web_search_allowed_domains =
                     config.tools_web_search_allowed_domains.clone();
+                code_browser::global::configure_default_browser(|browser| {
+                    browser.record_network = config.browser.record_network;
+                    browser.allow_new_tabs = config.browser.allow_new_tabs;
+                });
 
                 let mut agent_models: Vec<String> = if config.agents.is_empty() {
                     default_agent_configs()
@@ -605,6 +620,25 @@ pub(super) async fn submission_loop(
                         remote.refresh_remote_models().await;
                     });
                 }
+                let mut exec_policy_notices = Vec::<String>::new();
+                let exec_policy = match ExecPolicyOverlay::load(&config.code_home, &cwd) {
+                    Ok(exec_policy) => exec_policy,
+                    Err(e) => {
+                        let message = format!("Ignoring execpolicy overlays: {e:#}");
+                        warn!("{message}");
+                        exec_policy_notices.push(message);
+                        None
+                    }
+                };
+                if let Some(exec_policy) = exec_policy.as_ref() {
+                    exec_policy_notices.extend(
+                        exec_policy
+                            .conflicts()
+                            .iter()
+                            .map(|conflict| format!("execpolicy overlay conflict: {conflict}")),
+                    );
+                }
+                let (lifecycle_hook_tx, lifecycle_hook_rx) = tokio::sync::mpsc::unbounded_channel();
                 let mut new_session = Arc::new(Session {
                     id: session_id,
                     client,
@@ -617,7 +651,9 @@ pub(super) async fn submission_loop(
                     compact_prompt_override: config.compact_prompt_override.clone(),
                     approval_policy,
                     sandbox_policy,
+                    exec_policy,
                     shell_environment_policy: config.shell_environment_policy.clone(),
+                    exec_limits: config.exec_limits.clone(),
                     cwd,
                     _writable_roots: writable_roots,
                     mcp_connection_manager,
@@ -646,7 +682,8 @@ pub(super) async fn submission_loop(
                     project_hooks: config.project_hooks.clone(),
                     project_commands: config.project_commands.clone(),
                     tool_output_max_bytes: config.tool_output_max_bytes,
-                    hook_guard: AtomicBool::new(false),
+                    hook_lock: tokio::sync::Mutex::new(()),
+                    lifecycle_hook_tx,
                     github: Arc::new(RwLock::new(config.github.clone())),
                     validation: Arc::new(RwLock::new(config.validation.clone())),
                     self_handle: Weak::new(),
@@ -658,8 +695,9 @@ pub(super) async fn submission_loop(
                 });
                 let weak_handle = Arc::downgrade(&new_session);
                 if let Some(inner) = Arc::get_mut(&mut new_session) {
-                    inner.self_handle = weak_handle;
+                    inner.self_handle = weak_handle.clone();
                 }
+                spawn_lifecycle_hook_worker(weak_handle, lifecycle_hook_rx);
                 sess = Some(new_session);
                 if let Some(sess_arc) = &sess {
                     if !config.always_allow_commands.is_empty() {
@@ -730,6 +768,16 @@ pub(super) async fn submission_loop(
                     }
                 }
 
+                for message in exec_policy_notices {
+                    let event = sess_arc.make_event(
+                        &sub.id,
+                        EventMsg::BackgroundEvent(BackgroundEventEvent { message }),
+                    );
+                    if let Err(e) = tx_event.send(event).await {
+                        warn!("failed to send execpolicy notice event: {e}");
+                    }
+                }
+
                 if let Some(notice) = resume_notice {
                     let event = sess_arc.make_event(
                         &sub.id,
@@ -757,7 +805,15 @@ pub(super) async fn submission_loop(
                     // Forward agent events to the main event channel
                     let tx_event_clone = tx_event.clone();
                     tokio::spawn(async move {
+                        let mut agent_statuses: HashMap<String, String> = HashMap::new();
                         while let Some(payload) = agent_rx.recv().await {
+                            for (event, agent) in
+                                agent_lifecycle_hook_events(&payload, &mut agent_statuses)
+                            {
+                                sess_for_agents.queue_lifecycle_hooks(event, || {
+                                    agent_hook_payload(agent, &payload)
+                                });
+                            }
                             let wake_messages = {
                                 let mut state = sess_for_agents.state.lock().unwrap();
                                 agent_completion_wake_messages(
@@ -804,6 +860,10 @@ pub(super) async fn submission_loop(
                 sess.notify_wait_interrupted(WaitInterruptReason::UserMessage);
                 sess.abort();
 
+                sess.queue_lifecycle_hooks(ProjectHookEvent::TurnSubmit, || {
+                    turn_submit_hook_payload(&sub.id, &items, false)
+                });
+
                 // Spawn a new agent for this user input.
                 let turn_context = sess.make_turn_context_with_schema(final_output_json_schema);
                 let agent = AgentTask::spawn(Arc::clone(&sess), turn_context, sub.id.clone(), items);
@@ -818,7 +878,12 @@ pub(super) async fn submission_loop(
                     }
                 };
 
-                if sess.has_running_task() {
+                let queued = sess.has_running_task();
+                sess.queue_lifecycle_hooks(ProjectHookEvent::TurnSubmit, || {
+                    turn_submit_hook_payload(&sub.id, &items, queued)
+                });
+
+                if queued {
                     let mut response_item = response_input_from_core_items(items.clone());
                     sess.enforce_user_message_limits(&sub.id, &mut response_item);
                     sess.notify_wait_interrupted(WaitInterruptReason::UserMessage);
@@ -844,6 +909,9 @@ pub(super) async fn submission_loop(
                         continue;
                     }
                 };
+                sess.queue_lifecycle_hooks(ProjectHookEvent::ApprovalDecision, || {
+                    json!({ "call_id": id, "kind": "exec", "decision": decision })
+                });
                 match decision {
                     ReviewDecision::Abort => {
                         sess.notify_wait_interrupted(WaitInterruptReason::SessionAborted);
@@ -878,6 +946,9 @@ pub(super) async fn submission_loop(
                         continue;
                     }
                 };
+                sess.queue_lifecycle_hooks(ProjectHookEvent::ApprovalDecision, || {
+                    json!({ "call_id": id, "kind": "patch", "decision": decision })
+                });
                 match decision {
                     ReviewDecision::Abort => {
                         sess.notify_wait_interrupted(WaitInterruptReason::SessionAborted);
@@ -886,6 +957,22 @@ pub(super) async fn submission_loop(
                     other => sess.notify_approval(&id, other),
                 }
             }
+            Op::ResolveElicitation { id, action, content } => {
+                let sess = match sess.as_ref() {
+                    Some(sess) => sess,
+                    None => {
+                        send_no_session_event(sub.id).await;
+                        continue;
+                    }
+                };
+                if let Err(err) = sess
+                    .mcp_connection_manager
+                    .resolve_elicitation(&id, action, content)
+                    .await
+                {
+                    warn!("failed to resolve MCP elicitation: {err:#}");
+                }
+            }
             Op::UpdateValidationTool { name, enable } => {
                 if let Some(sess) = sess.as_ref() {
                     sess.update_validation_tool(&name, enable);
@@ -994,6 +1081,31 @@ pub(super) async fn submission_loop(
                     event_seq: 0,
                     msg: EventMsg::ListCustomPromptsResponse(ListCustomPromptsResponseEvent {
                         custom_prompts,
+                        mcp_prompts: crate::mcp_resources::mcp_prompt_infos(
+                            &sess.mcp_connection_manager,
+                        ),
+                    }),
+                    order: None,
+                };
+
+                sess.send_event(event).await;
+            }
+            Op::ListMcpResources => {
+                let sess = match sess.as_ref() {
+                    Some(sess) => Arc::clone(sess),
+                    None => {
+                        send_no_session_event(sub.id).await;
+                        continue;
+                    }
+                };
+
+                let event = Event {
+                    id: sub.id.clone(),
+                    event_seq: 0,
+                    msg: EventMsg::ListMcpResourcesResponse(ListMcpResourcesResponseEvent {
+                        resources: crate::mcp_resources::mcp_resource_infos(
+                            &sess.mcp_connection_manager,
+                        ),
                     }),
                     order: None,
                 };
@@ -1041,6 +1153,23 @@ pub(super) async fn submission_loop(
 
                 sess.send_event(event).await;
             }
+            Op::AutoDrivePhase {
+                phase,
+                previous_phase,
+                goal,
+            } => {
+                let Some(sess) = sess.as_ref() else {
+                    send_no_session_event(sub.id).await;
+                    continue;
+                };
+                sess.queue_lifecycle_hooks(ProjectHookEvent::AutoDrivePhase, || {
+                    json!({
+                        "phase": phase,
+                        "previous_phase": previous_phase,
+                        "goal": goal,
+                    })
+                });
+            }
             Op::Compact => {
                 let sess = match sess.as_ref() {
                     Some(sess) => sess,
@@ -1403,6 +1532,21 @@ async fn run_agent(sess: Arc<Session>, turn_context: Arc<TurnContext>, sub_id: S
     if sess.tx_event.send(event).await.is_err() {
         return;
     }
+    // Expand `/server:prompt` invocations and attach `@server:resource` mentions.
+    let input = match crate::mcp_resources::expand_mcp_input(&sess.mcp_connection_manager, input).await {
+        Ok(input) => input,
+        Err(message) => {
+            let event = sess.make_event(&sub_id, EventMsg::Error(ErrorEvent { message }));
+            sess.tx_event.send(event).await.ok();
+            sess.remove_task(&sub_id);
+            let event = sess.make_event(
+                &sub_id,
+                EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message: None }),
+            );
+            sess.tx_event.send(event).await.ok();
+            return;
+        }
+    };
     // Continue with our fork's history and input handling.
 
     let is_review_mode = turn_context.is_review_mode;
@@ -1771,6 +1915,17 @@ async fn run_agent(sess: Arc<Session>, turn_context: Arc<TurnContext>, sub_id: S
     }
 
     sess.remove_task(&sub_id);
+    sess.queue_lifecycle_hooks(ProjectHookEvent::TurnComplete, || {
+        let token_usage = sess.state.lock().unwrap().token_usage_info.clone();
+        json!({
+            "sub_id": sub_id,
+            "last_agent_message": last_task_message
+                .as_deref()
+                .map(|message| truncate_payload(message, HOOK_OUTPUT_LIMIT)),
+            "token_usage": token_usage,
+            "review": is_review_mode,
+        })
+    });
     let event = sess.make_event(
         &sub_id,
         EventMsg::TaskComplete(TaskCompleteEvent {
@@ -1875,6 +2030,7 @@ async fn run_turn(
             model_override: None,
             model_family_override: None,
             output_schema: tc.final_output_json_schema.clone(),
+            max_output_tokens: None,
             log_tag: Some("codex/turn".to_string()),
             session_id_override: None,
             model_descriptions: sess.model_descriptions.clone(),
@@ -1896,6 +2052,9 @@ async fn run_turn(
             browser_enabled,
             agents_active,
         );
+        if sess.mcp_connection_manager.has_resources() {
+            prompt.tools.extend(crate::openai_tools::create_mcp_resource_tools());
+        }
 
         // Start a new scratchpad for this HTTP attempt
         sess.begin_attempt_scratchpad();
@@ -2028,6 +2187,7 @@ async fn run_turn(
             }
             Err(CodexErr::UsageNotIncluded) => return Err(CodexErr::UsageNotIncluded),
             Err(CodexErr::QuotaExceeded) => return Err(CodexErr::QuotaExceeded),
+            Err(CodexErr::Cassette(msg)) => return Err(CodexErr::Cassette(msg)),
             Err(e) => {
                 // Detect context-window overflow and auto-run a compact summarization once
                 if !did_auto_compact {
@@ -2188,6 +2348,11 @@ async fn run_turn(
                 };
 
                 if is_connectivity && retries >= max_retries {
+                    if fail_over_provider(sess, tc, &sub_id, attempt_req, &e).await {
+                        drain_scratchpad_into_attempt(&mut attempt_input);
+                        retries = 0;
+                        continue;
+                    }
                     let probe = tc.client.get_provider().base_url_for_probe();
                     let wait_message = format!(
                         "Network unavailable; waiting to reconnect to {probe} ({e})"
@@ -2231,6 +2396,11 @@ async fn run_turn(
 
                     tokio::time::sleep(delay).await;
                 } else {
+                    if fail_over_provider(sess, tc, &sub_id, attempt_req, &e).await {
+                        drain_scratchpad_into_attempt(&mut attempt_input);
+                        retries = 0;
+                        continue;
+                    }
                     error!(
                         retries,
                         max_retries,
@@ -2246,6 +2416,42 @@ async fn run_turn(
     }
 }
 
+/// Move the session to the next `[model_failover]` entry after `err` used up
+/// the serving provider's retries, and tell the user which provider takes
+/// over. Returns false when `err` is not a provider outage or the chain is
+/// exhausted.
+async fn fail_over_provider(
+    sess: &Session,
+    tc: &TurnContext,
+    sub_id: &str,
+    attempt_req: u64,
+    err: &CodexErr,
+) -> bool {
+    if !is_provider_failure(err) {
+        return false;
+    }
+    let previous = tc.client.serving_label();
+    let Some(next) = tc.client.fail_over() else {
+        return false;
+    };
+    warn!(
+        from = %previous,
+        to = %next,
+        error = %err,
+        "provider failed after retries; failing over"
+    );
+
+    let mut notice =
+        format!("Failover: {previous} failed after retries ({err}); now using {next}.");
+    let used = tc.client.tokens_used_by(&previous);
+    if used > 0 {
+        notice.push_str(&format!(" {previous} served {used} tokens this session."));
+    }
+    let order = sess.next_background_order(sub_id, attempt_req, None);
+    sess.notify_background_event_with_order(sub_id, order, notice).await;
+    true
+}
+
 const HTML_SANITIZER_GUARDRAILS_MESSAGE: &str =
     "TB2 HTML/XSS guardrails:\n- Do NOT use DOTALL/full-document regex (e.g. `<script.*?>.*?</script>`); catastrophic backtracking risk.\n- Prefer linear-time scanning with quote/state tracking; if using regex, only on bounded substrings (single tags).\n- Perf smoke test: write malformed `/tmp/stress.html` and run `timeout 5s python3 /app/filter.py /tmp/stress.html` (or equivalent). If it times out, rewrite for linear-time behavior.";
 
@@ -2563,6 +2769,9 @@ async fn try_run_turn(
                 response_id: _,
                 token_usage,
             } => {
+                if let Some(usage) = token_usage.as_ref() {
+                    sess.client.record_served_usage(usage);
+                }
                 let (new_info, rate_limits, should_emit);
                 {
                     let mut state = sess.state.lock().unwrap();
@@ -2583,6 +2792,7 @@ async fn try_run_turn(
                     let payload = TokenCountEvent {
                         info: new_info,
                         rate_limits,
+                        provider_usage: sess.client.usage_by_provider(),
                     };
                     sess.tx_event
                         .send(sess.make_event(&sub_id, EventMsg::TokenCount(payload)))
@@ -2591,7 +2801,7 @@ async fn try_run_turn(
                 }
 
                 if let Some(usage) = token_usage.as_ref() {
-                    if let Some(ctx) = account_usage_context(sess) {
+                    if let Some(ctx) = served_usage_context(sess) {
                         let usage_home = ctx.code_home.clone();
                         let usage_account = ctx.account_id.clone();
                         let usage_plan = ctx.plan.clone();
@@ -2664,6 +2874,16 @@ async fn try_run_turn(
                     sess.tx_event.send(stamped).await.ok();
                 }
             }
+            ResponseEvent::OutputTruncated { max_output_tokens } => {
+                let message = format!(
+                    "The response was cut off at the output limit of {max_output_tokens} tokens; raise `model_max_output_tokens` to allow longer responses."
+                );
+                let event = sess.make_event(
+                    &sub_id,
+                    EventMsg::BackgroundEvent(BackgroundEventEvent { message }),
+                );
+                sess.tx_event.send(event).await.ok();
+            }
             ResponseEvent::ModelsEtag(etag) => {
                 if let Some(remote) = sess.remote_models_manager.as_ref() {
                     remote.refresh_if_new_etag(etag).await;
@@ -2983,6 +3203,11 @@ async fn handle_function_call(
         "gh_run_wait" => handle_gh_run_wait(sess, &ctx, arguments).await,
         "kill" => handle_kill(sess, &ctx, arguments).await,
         "code_bridge" | "code_bridge_subscription" => handle_code_bridge(sess, &ctx, arguments).await,
+        crate::mcp_resources::LIST_MCP_RESOURCES_TOOL_NAME
+        | crate::mcp_resources::LIST_MCP_RESOURCE_TEMPLATES_TOOL_NAME
+        | crate::mcp_resources::READ_MCP_RESOURCE_TOOL_NAME => {
+            crate::mcp_resources::handle_mcp_resource_tool(sess, &ctx, &name, arguments).await
+        }
         _ => {
             match sess.mcp_connection_manager.parse_tool_name(&name) {
                 Some((server, tool_name)) => {
@@ -3292,6 +3517,9 @@ async fn handle_web_fetch(sess: &Session, ctx: &ToolCallCtx, arguments: String)
 
                 let manager = BrowserManager::new(config);
                 manager.set_enabled_sync(true);
+                // Internal fetches are not user actions; keep them out of the
+                // replayable recordings.
+                manager.set_action_recording(false);
 
                 const CHECK_JS: &str = r#"(function(){
   const discuss = document.querySelectorAll('[data-test-selector=\"issue-comment-body\"]');
@@ -5136,6 +5364,7 @@ async fn handle_kill(
                 aggregated_output: StreamOutput::new(cancel_message.clone()),
                 duration: std::time::Duration::ZERO,
                 timed_out: false,
+                resource_usage: None,
             };
 
             {
@@ -5154,6 +5383,7 @@ async fn handle_kill(
                 stderr: output.stderr.text.clone(),
                 exit_code: output.exit_code,
                 duration: output.duration,
+                resource_usage: output.resource_usage,
             });
             let event = Event {
                 id: sub_id_for_end.clone(),
@@ -5194,6 +5424,7 @@ fn to_exec_params(params: ShellToolCallParams, sess: &Session) -> ExecParams {
         env: create_env(&sess.shell_environment_policy),
         with_escalated_permissions,
         justification: params.justification,
+        exec_limits: sess.exec_limits.clone(),
     }
 }
 
@@ -7584,7 +7815,8 @@ async fn handle_container_exec_with_params(
                 }
             }
 
-            let changes = convert_apply_patch_to_protocol(&action);
+            let mut action = action;
+            let mut changes = convert_apply_patch_to_protocol(&action);
             turn_diff_tracker.on_patch_begin(&changes);
 
             let mut hook_ctx = ExecCommandContext {
@@ -7598,8 +7830,8 @@ async fn handle_container_exec_with_params(
                 }),
             };
 
-            // FileBeforeWrite hook for apply_patch
-            sess
+            // FileBeforeWrite hook for apply_patch; it may veto or rewrite the patch.
+            let hook_decision = sess
                 .run_hooks_for_exec_event(
                     turn_diff_tracker,
                     ProjectHookEvent::FileBeforeWrite,
@@ -7609,6 +7841,40 @@ async fn handle_container_exec_with_params(
                     attempt_req,
                 )
                 .await;
+            match hook_decision {
+                HookDecision::Allow => {}
+                HookDecision::Deny { reason } => {
+                    return ResponseInputItem::FunctionCallOutput {
+                        call_id,
+                        output: FunctionCallOutputPayload { content: reason, success: None },
+                    };
+                }
+                HookDecision::Modify { patch, .. } => {
+                    if let Some(patch) = patch {
+                        let argv = vec!["apply_patch".to_string(), patch];
+                        match sess.maybe_parse_apply_patch_verified(&argv, &action.cwd).await {
+                            MaybeApplyPatchVerified::Body(modified) => {
+                                changes = convert_apply_patch_to_protocol(&modified);
+                                turn_diff_tracker.on_patch_begin(&changes);
+                                if let Some(ctx) = hook_ctx.apply_patch.as_mut() {
+                                    ctx.changes = changes.clone();
+                                }
+                                action = modified;
+                            }
+                            _ => {
+                                return ResponseInputItem::FunctionCallOutput {
+                                    call_id,
+                                    output: FunctionCallOutputPayload {
+                                        content: "file.before_write hook returned a patch that could not be applied"
+                                            .to_string(),
+                                        success: None,
+                                    },
+                                };
+                            }
+                        }
+                    }
+                }
+            }
 
             let patch_start = std::time::Instant::now();
 
@@ -7675,6 +7941,7 @@ async fn handle_container_exec_with_params(
                         }),
                         duration: patch_start.elapsed(),
                         timed_out: false,
+                        resource_usage: None,
                     };
 
                     sess
@@ -7737,6 +8004,47 @@ async fn handle_container_exec_with_params(
         MaybeApplyPatchVerified::NotApplyPatch => {}
     }
 
+    // A rerun goes through `run_exec_with_events`, which applies the profile
+    // and hooks itself.
+    let params_for_rerun = params.clone();
+    let mut params = params;
+    let mut exec_command_context = ExecCommandContext {
+        sub_id: sub_id.clone(),
+        call_id: call_id.clone(),
+        command_for_display: params.command.clone(),
+        cwd: params.cwd.clone(),
+        apply_patch: None,
+    };
+
+    // ToolBefore hook for shell/container.exec commands; it may veto or
+    // rewrite the command. It runs before the safety check so a rewritten
+    // command is assessed and approved like one the model sent.
+    let hook_decision = sess
+        .run_hooks_for_exec_event(
+            turn_diff_tracker,
+            ProjectHookEvent::ToolBefore,
+            &exec_command_context,
+            &params,
+            None,
+            attempt_req,
+        )
+        .await;
+    match hook_decision {
+        HookDecision::Allow => {}
+        HookDecision::Deny { reason } => {
+            return ResponseInputItem::FunctionCallOutput {
+                call_id,
+                output: FunctionCallOutputPayload { content: reason, success: None },
+            };
+        }
+        HookDecision::Modify { command, .. } => {
+            if let Some(command) = command {
+                exec_command_context.command_for_display = command.clone();
+                params.command = command;
+            }
+        }
+    }
+
     let safety = {
         let state = sess.state.lock().unwrap();
         assess_command_safety(
@@ -7744,10 +8052,10 @@ async fn handle_container_exec_with_params(
             sess.approval_policy,
             &sess.sandbox_policy,
             &state.approved_commands,
+            sess.exec_policy.as_ref(),
             params.with_escalated_permissions.unwrap_or(false),
         )
     };
-    let command_for_display = params.command.clone();
     let harness_summary_json: Option<String> = None;
 
     let sandbox_type = match safety {
@@ -7781,6 +8089,7 @@ async fn handle_container_exec_with_params(
                     params.command.clone(),
                     params.cwd.clone(),
                     params.justification.clone(),
+                    None,
                 )
                 .await;
 
@@ -7830,29 +8139,9 @@ async fn handle_container_exec_with_params(
         }
     };
 
-    let exec_command_context = ExecCommandContext {
-        sub_id: sub_id.clone(),
-        call_id: call_id.clone(),
-        command_for_display: command_for_display.clone(),
-        cwd: params.cwd.clone(),
-        apply_patch: None,
-    };
-
     let display_label = crate::util::strip_bash_lc_and_escape(&exec_command_context.command_for_display);
     let params = maybe_run_with_user_profile(params, sess);
-
-    // ToolBefore hook for shell/container.exec commands
     let params_for_hooks = params.clone();
-    sess
-        .run_hooks_for_exec_event(
-            turn_diff_tracker,
-            ProjectHookEvent::ToolBefore,
-            &exec_command_context,
-            &params_for_hooks,
-            None,
-            attempt_req,
-        )
-        .await;
 
     // Prepare tail buffer and background registry entry
     let tail_buf = std::sync::Arc::new(std::sync::Mutex::new(Vec::<u8>::new()));
@@ -7908,7 +8197,8 @@ async fn handle_container_exec_with_params(
     let tx_event = sess.tx_event.clone();
     let sub_id_for_events = sub_id.clone();
     let call_id_for_events = call_id.clone();
-    let sandbox_policy = sess.sandbox_policy.clone();
+    let sandbox_policy = sess.effective_sandbox_policy();
+    let sandbox_policy_for_denials = sandbox_policy.clone();
     let sandbox_cwd = sess.get_cwd().to_path_buf();
     let code_linux_sandbox_exe = sess.code_linux_sandbox_exe.clone();
     let exec_spool_dir_for_task = if sess.client.debug_enabled() {
@@ -7970,6 +8260,7 @@ async fn handle_container_exec_with_params(
                         aggregated_output: StreamOutput::new(msg),
                         duration: start.elapsed(),
                         timed_out: false,
+                        resource_usage: None,
                     },
                     -1,
                 )
@@ -7983,6 +8274,7 @@ async fn handle_container_exec_with_params(
             stderr: out.stderr.text.clone(),
             exit_code,
             duration: out.duration,
+            resource_usage: out.resource_usage,
         });
         let ev = Event { id: sub_id_for_events.clone(), event_seq: 0, msg: end_msg, order: Some(order_meta_for_end) };
         let _ = tx_event.send(ev).await;
@@ -8079,6 +8371,31 @@ async fn handle_container_exec_with_params(
         };
         if let Some(done) = done_opt {
             let is_success = done.exit_code == 0;
+            let denials = if is_success || sandbox_type == SandboxType::None {
+                Vec::new()
+            } else {
+                detect_sandbox_denials(
+                    &done.aggregated_output.text,
+                    &sandbox_policy_for_denials,
+                    sess.get_cwd(),
+                    &exec_command_context.cwd,
+                )
+            };
+            if !denials.is_empty() {
+                if let Some(rerun) = handle_sandbox_denials(
+                    turn_diff_tracker,
+                    sess,
+                    &exec_command_context,
+                    params_for_rerun,
+                    sandbox_type,
+                    &denials,
+                    attempt_req,
+                )
+                .await
+                {
+                    return rerun;
+                }
+            }
             let mut content = format_exec_output_with_limit(
                 sess.get_cwd(),
                 &sub_id,
@@ -8086,6 +8403,9 @@ async fn handle_container_exec_with_params(
                 &done,
                 sess.tool_output_max_bytes,
             );
+            for denial in &denials {
+                content.push_str(&format!("\nsandbox: {denial}"));
+            }
             if let Some(harness) = harness_summary_json.as_ref() {
                 if !harness.is_empty() {
                     content.push('\n');
@@ -8128,6 +8448,144 @@ async fn handle_container_exec_with_params(
     ResponseInputItem::FunctionCallOutput { call_id: call_id.clone(), output: FunctionCallOutputPayload { content: msg, success: Some(true) } }
 }
 
+/// Reports the sandbox denials found in a failed command's output and, when
+/// granting a single writable root would lift one, asks the user whether to
+/// grant it for the rest of the session and rerun the command in the widened
+/// sandbox. Returns the rerun's output, or `None` if nothing was rerun.
+async fn handle_sandbox_denials(
+    turn_diff_tracker: &mut TurnDiffTracker,
+    sess: &Session,
+    exec_command_context: &ExecCommandContext,
+    params: ExecParams,
+    sandbox_type: SandboxType,
+    denials: &[SandboxDenial],
+    attempt_req: u64,
+) -> Option<ResponseInputItem> {
+    let sub_id = exec_command_context.sub_id.clone();
+    let call_id = exec_command_context.call_id.clone();
+    for denial in denials {
+        let order = sess.next_background_order(&sub_id, attempt_req, None);
+        let event = sess.make_event_with_order(
+            &sub_id,
+            EventMsg::SandboxDenial(SandboxDenialEvent {
+                call_id: call_id.clone(),
+                denial: denial.clone(),
+            }),
+            order,
+            None,
+        );
+        sess.send_event(event).await;
+    }
+
+    if matches!(sess.approval_policy, AskForApproval::Never) {
+        return None;
+    }
+    let (denial, grant_root) = denials.iter().find_map(|denial| {
+        denial
+            .suggested_writable_root
+            .clone()
+            .map(|root| (denial, root))
+    })?;
+
+    let rx_approve = sess
+        .request_command_approval(
+            sub_id.clone(),
+            call_id.clone(),
+            params.command.clone(),
+            exec_command_context.cwd.clone(),
+            Some(format!(
+                "{denial}; rerun with write access to {}?",
+                grant_root.display()
+            )),
+            Some(grant_root.clone()),
+        )
+        .await;
+    let decision = rx_approve.await.unwrap_or_default();
+    if let Some(manager) = sess.client.get_otel_event_manager().as_ref() {
+        manager.tool_decision(
+            "local_shell",
+            call_id.as_str(),
+            to_proto_review_decision(decision),
+            ToolDecisionSource::User,
+        );
+    }
+    match decision {
+        ReviewDecision::Approved | ReviewDecision::ApprovedForSession => {}
+        ReviewDecision::Denied | ReviewDecision::Abort => return None,
+    }
+
+    sess.grant_writable_root(grant_root.clone());
+    let retry_order = sess.next_background_order(&sub_id, attempt_req, None);
+    sess
+        .notify_background_event_with_order(
+            &sub_id,
+            retry_order,
+            format!(
+                "retrying command with write access to {}",
+                grant_root.display()
+            ),
+        )
+        .await;
+
+    let sandbox_policy = sess.effective_sandbox_policy();
+    let retry_output_result = sess
+        .run_exec_with_events(
+            turn_diff_tracker,
+            exec_command_context.clone(),
+            ExecInvokeArgs {
+                params,
+                sandbox_type,
+                sandbox_policy: &sandbox_policy,
+                sandbox_cwd: sess.get_cwd(),
+                code_linux_sandbox_exe: &sess.code_linux_sandbox_exe,
+                stdout_stream: Some(StdoutStream {
+                    sub_id: sub_id.clone(),
+                    call_id: call_id.clone(),
+                    tx_event: sess.tx_event.clone(),
+                    session: None,
+                    tail_buf: None,
+                    order: Some(crate::protocol::OrderMeta {
+                        request_ordinal: attempt_req,
+                        output_index: None,
+                        sequence_number: None,
+                    }),
+                    spool_dir: if sess.client.debug_enabled() {
+                        Some(sess.client.code_home().join("debug_logs").join("exec"))
+                    } else {
+                        None
+                    },
+                }),
+            },
+            None,
+            None,
+            attempt_req,
+        )
+        .await;
+
+    Some(match retry_output_result {
+        Ok(retry_output) => ResponseInputItem::FunctionCallOutput {
+            call_id: call_id.clone(),
+            output: FunctionCallOutputPayload {
+                content: format_exec_output_with_limit(
+                    sess.get_cwd(),
+                    &sub_id,
+                    &call_id,
+                    &retry_output,
+                    sess.tool_output_max_bytes,
+                ),
+                success: Some(retry_output.exit_code == 0),
+            },
+        },
+        Err(e) => ResponseInputItem::FunctionCallOutput {
+            call_id,
+            output: FunctionCallOutputPayload {
+                content: format!("retry failed: {e}"),
+                success: None,
+            },
+        },
+    })
+}
+
 #[allow(dead_code)]
 async fn handle_sandbox_error(
     turn_diff_tracker: &mut TurnDiffTracker,
@@ -8226,6 +8684,7 @@ async fn handle_sandbox_error(
             params.command.clone(),
             cwd.clone(),
             Some("command failed; retry without sandbox?".to_string()),
+            None,
         )
         .await;
 
@@ -8634,14 +9093,60 @@ fn agent_completion_wake_messages(
     messages
 }
 
-async fn enqueue_agent_completion_wake(
-    sess: &Arc<Session>,
-    messages: Vec<ResponseInputItem>,
-) {
-    if messages.is_empty() {
-        return;
-    }
-
+/// Hook events implied by an agent status update: `agent.spawn` the first
+/// time an agent is reported and `agent.finish` when it reaches a terminal
+/// status. `last_status` carries the statuses seen in earlier updates.
+fn agent_lifecycle_hook_events<'a>(
+    payload: &'a AgentStatusUpdatePayload,
+    last_status: &mut HashMap<String, String>,
+) -> Vec<(ProjectHookEvent, &'a crate::protocol::AgentInfo)> {
+    let mut events = Vec::new();
+    for agent in &payload.agents {
+        let previous = last_status.insert(agent.id.clone(), agent.status.clone());
+        if previous.is_none() {
+            events.push((ProjectHookEvent::AgentSpawn, agent));
+        }
+        let was_terminal = previous
+            .as_deref()
+            .is_some_and(is_terminal_agent_status);
+        if is_terminal_agent_status(&agent.status) && !was_terminal {
+            events.push((ProjectHookEvent::AgentFinish, agent));
+        }
+    }
+    events
+}
+
+fn agent_hook_payload(
+    agent: &crate::protocol::AgentInfo,
+    payload: &AgentStatusUpdatePayload,
+) -> serde_json::Value {
+    let truncate = |text: &Option<String>| {
+        text.as_deref()
+            .map(|text| truncate_payload(text, HOOK_OUTPUT_LIMIT))
+    };
+    json!({
+        "agent_id": agent.id,
+        "name": agent.name,
+        "status": agent.status,
+        "batch_id": agent.batch_id,
+        "model": agent.model,
+        "result": truncate(&agent.result),
+        "error": truncate(&agent.error),
+        "elapsed_ms": agent.elapsed_ms,
+        "token_count": agent.token_count,
+        "context": truncate(&payload.context),
+        "task": truncate(&payload.task),
+    })
+}
+
+async fn enqueue_agent_completion_wake(
+    sess: &Arc<Session>,
+    messages: Vec<ResponseInputItem>,
+) {
+    if messages.is_empty() {
+        return;
+    }
+
     let mut should_start_turn = false;
     for message in messages {
         if sess.enqueue_out_of_turn_item(message) {
@@ -8663,11 +9168,14 @@ async fn enqueue_agent_completion_wake(
 
 #[cfg(test)]
 mod agent_completion_wake_tests {
+    use std::collections::HashMap;
     use std::collections::HashSet;
 
     use super::agent_completion_wake_messages;
+    use super::agent_lifecycle_hook_events;
     use super::AgentSourceKind;
     use crate::agent_tool::AgentStatusUpdatePayload;
+    use crate::config_types::ProjectHookEvent;
     use crate::protocol::AgentInfo;
 
     fn agent_info(
@@ -8736,6 +9244,46 @@ mod agent_completion_wake_tests {
         };
         assert!(agent_completion_wake_messages(&auto_review, &mut seen).is_empty());
     }
+
+    #[test]
+    fn agent_lifecycle_hook_events_fire_once_per_transition() {
+        let mut statuses = HashMap::new();
+        let events_for = |payload: &AgentStatusUpdatePayload, statuses: &mut HashMap<String, String>| {
+            agent_lifecycle_hook_events(payload, statuses)
+                .into_iter()
+                .map(|(event, agent)| (event, agent.id.clone()))
+                .collect::<Vec<_>>()
+        };
+
+        let spawned = AgentStatusUpdatePayload {
+            agents: vec![agent_info("agent-1", "running", Some("batch-1"), None)],
+            context: None,
+            task: None,
+        };
+        assert_eq!(
+            events_for(&spawned, &mut statuses),
+            vec![(ProjectHookEvent::AgentSpawn, "agent-1".to_string())]
+        );
+        assert!(events_for(&spawned, &mut statuses).is_empty());
+
+        let finished = AgentStatusUpdatePayload {
+            agents: vec![
+                agent_info("agent-1", "completed", Some("batch-1"), None),
+                agent_info("agent-2", "failed", Some("batch-1"), None),
+            ],
+            context: None,
+            task: None,
+        };
+        assert_eq!(
+            events_for(&finished, &mut statuses),
+            vec![
+                (ProjectHookEvent::AgentFinish, "agent-1".to_string()),
+                (ProjectHookEvent::AgentSpawn, "agent-2".to_string()),
+                (ProjectHookEvent::AgentFinish, "agent-2".to_string()),
+            ]
+        );
+        assert!(events_for(&finished, &mut statuses).is_empty());
+    }
 }
 
 /// Send agent status update event to the TUI
@@ -8811,6 +9359,7 @@ pub(super) fn add_pending_screenshot(
     sess: &Session,
     screenshot_path: PathBuf,
     url: String,
+    tab_id: Option<String>,
 ) {
     // Do not queue screenshots for next turn anymore; we inject fresh per-turn.
     tracing::info!("Captured screenshot; updating UI and using per-turn injection");
@@ -8821,6 +9370,7 @@ pub(super) fn add_pending_screenshot(
         EventMsg::BrowserScreenshotUpdate(BrowserScreenshotUpdateEvent {
             screenshot_path,
             url,
+            tab_id,
         }),
     );
 
@@ -9000,6 +9550,11 @@ async fn handle_browser_tool(sess: &Session, ctx: &ToolCallCtx, arguments: Strin
         "history" => handle_browser_history(sess, ctx, payload_string.clone()).await,
         "inspect" => handle_browser_inspect(sess, ctx, payload_string.clone()).await,
         "console" => handle_browser_console(sess, ctx, payload_string.clone()).await,
+        "snapshot" => handle_browser_snapshot(sess, ctx, payload_string.clone()).await,
+        "select" => handle_browser_select(sess, ctx, payload_string.clone()).await,
+        "tabs" => handle_browser_tabs(sess, ctx, payload_string.clone()).await,
+        "network" => handle_browser_network(sess, ctx, payload_string.clone()).await,
+        "compare" => handle_browser_compare(sess, ctx, payload_string.clone()).await,
         "cdp" => handle_browser_cdp(sess, ctx, payload_string.clone()).await,
         "cleanup" => handle_browser_cleanup(sess, ctx).await,
         "fetch" => handle_web_fetch(sess, ctx, payload_string.clone()).await,
@@ -9271,6 +9826,31 @@ async fn handle_browser_click(sess: &Session, ctx: &ToolCallCtx, arguments: Stri
                 let _ = browser_manager
                     .execute_cdp("Overlay.hideHighlight", serde_json::json!({}))
                     .await;
+
+                // Element refs from a snapshot take precedence over coordinates
+                if let Some(element_ref) = params
+                    .as_ref()
+                    .and_then(|v| v.get("ref"))
+                    .and_then(|v| v.as_str())
+                {
+                    return match browser_manager.click_ref(element_ref).await {
+                        Ok((x, y)) => ResponseInputItem::FunctionCallOutput {
+                            call_id: call_id_clone.clone(),
+                            output: FunctionCallOutputPayload {
+                                content: format!("Clicked {} at ({:.0}, {:.0})", element_ref, x, y),
+                                success: Some(true),
+                            },
+                        },
+                        Err(e) => ResponseInputItem::FunctionCallOutput {
+                            call_id: call_id_clone.clone(),
+                            output: FunctionCallOutputPayload {
+                                content: format!("Failed to click {}: {}", element_ref, e),
+                                success: Some(false),
+                            },
+                        },
+                    };
+                }
+
                 // Determine click type: default 'click', or 'mousedown'/'mouseup'
                 let click_type = params
                     .as_ref()
@@ -9474,6 +10054,25 @@ async fn handle_browser_type(sess: &Session, ctx: &ToolCallCtx, arguments: Strin
                     Ok(json) => {
                         let text = json.get("text").and_then(|v| v.as_str()).unwrap_or("");
 
+                        if let Some(element_ref) = json.get("ref").and_then(|v| v.as_str()) {
+                            return match browser_manager.type_ref(element_ref, text).await {
+                                Ok(_) => ResponseInputItem::FunctionCallOutput {
+                                    call_id: call_id_clone.clone(),
+                                    output: FunctionCallOutputPayload {
+                                        content: format!("Typed into {}: {}", element_ref, text),
+                                        success: Some(true),
+                                    },
+                                },
+                                Err(e) => ResponseInputItem::FunctionCallOutput {
+                                    call_id: call_id_clone.clone(),
+                                    output: FunctionCallOutputPayload {
+                                        content: format!("Failed to type into {}: {}", element_ref, e),
+                                        success: Some(false),
+                                    },
+                                },
+                            };
+                        }
+
                         match browser_manager.type_text(text).await {
                             Ok(_) => {
                                 ResponseInputItem::FunctionCallOutput {
@@ -9855,6 +10454,606 @@ async fn handle_browser_console(sess: &Session, ctx: &ToolCallCtx, arguments: St
     .await
 }
 
+async fn handle_browser_snapshot(sess: &Session, ctx: &ToolCallCtx, arguments: String) -> ResponseInputItem {
+    let params = serde_json::from_str(&arguments).ok();
+    let sess_clone = sess;
+    let arguments_clone = arguments.clone();
+    let call_id_clone = ctx.call_id.clone();
+
+    execute_custom_tool(
+        sess,
+        ctx,
+        "browser_snapshot".to_string(),
+        params,
+        || async move {
+            let browser_manager = get_browser_manager_for_session(sess_clone).await;
+            if let Some(browser_manager) = browser_manager {
+                let max_nodes = serde_json::from_str::<Value>(&arguments_clone)
+                    .ok()
+                    .and_then(|json| json.get("max_nodes").and_then(|v| v.as_u64()))
+                    .map(|n| n as usize);
+
+                match browser_manager.snapshot(max_nodes).await {
+                    Ok(snapshot) => {
+                        let mut output = format!("Page: {}\n", snapshot.url);
+                        if let Some(title) = snapshot.title.as_deref().filter(|t| !t.is_empty()) {
+                            output.push_str(&format!("Title: {}\n", title));
+                        }
+                        output.push_str(&format!(
+                            "Refs: {} (use with action=click/type/select and 'ref')\n\n",
+                            snapshot.ref_count
+                        ));
+                        if snapshot.outline.is_empty() {
+                            output.push_str("(no accessible content)");
+                        } else {
+                            output.push_str(&snapshot.outline);
+                        }
+                        if snapshot.truncated {
+                            output.push_str("\n… outline truncated; raise max_nodes to see more");
+                        }
+                        ResponseInputItem::FunctionCallOutput {
+                            call_id: call_id_clone,
+                            output: FunctionCallOutputPayload {
+                                content: output,
+                                success: Some(true),
+                            },
+                        }
+                    }
+                    Err(e) => ResponseInputItem::FunctionCallOutput {
+                        call_id: call_id_clone,
+                        output: FunctionCallOutputPayload {
+                            content: format!("Failed to capture snapshot: {}", e),
+                            success: Some(false),
+                        },
+                    },
+                }
+            } else {
+                ResponseInputItem::FunctionCallOutput {
+                    call_id: call_id_clone,
+                    output: FunctionCallOutputPayload {
+                        content: "Browser is not enabled. Use browser_open to enable it first.".to_string(),
+                        success: Some(false),
+                    },
+                }
+            }
+        },
+    )
+    .await
+}
+
+async fn handle_browser_select(sess: &Session, ctx: &ToolCallCtx, arguments: String) -> ResponseInputItem {
+    let params = serde_json::from_str(&arguments).ok();
+    let sess_clone = sess;
+    let arguments_clone = arguments.clone();
+    let call_id_clone = ctx.call_id.clone();
+
+    execute_custom_tool(
+        sess,
+        ctx,
+        "browser_select".to_string(),
+        params,
+        || async move {
+            let browser_manager = get_browser_manager_for_session(sess_clone).await;
+            if let Some(browser_manager) = browser_manager {
+                let json = serde_json::from_str::<Value>(&arguments_clone).unwrap_or(Value::Null);
+                let Some(element_ref) = json.get("ref").and_then(|v| v.as_str()) else {
+                    return ResponseInputItem::FunctionCallOutput {
+                        call_id: call_id_clone,
+                        output: FunctionCallOutputPayload {
+                            content: "browser select requires 'ref' from a snapshot".to_string(),
+                            success: Some(false),
+                        },
+                    };
+                };
+                let values: Vec<String> = match json.get("values") {
+                    Some(Value::Array(items)) => items
+                        .iter()
+                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                        .collect(),
+                    Some(Value::String(single)) => vec![single.clone()],
+                    _ => Vec::new(),
+                };
+                if values.is_empty() {
+                    return ResponseInputItem::FunctionCallOutput {
+                        call_id: call_id_clone,
+                        output: FunctionCallOutputPayload {
+                            content: "browser select requires at least one entry in 'values'".to_string(),
+                            success: Some(false),
+                        },
+                    };
+                }
+
+                match browser_manager.select_ref(element_ref, &values).await {
+                    Ok(selected) => ResponseInputItem::FunctionCallOutput {
+                        call_id: call_id_clone,
+                        output: FunctionCallOutputPayload {
+                            content: format!("Selected in {}: {}", element_ref, selected.join(", ")),
+                            success: Some(true),
+                        },
+                    },
+                    Err(e) => ResponseInputItem::FunctionCallOutput {
+                        call_id: call_id_clone,
+                        output: FunctionCallOutputPayload {
+                            content: format!("Failed to select in {}: {}", element_ref, e),
+                            success: Some(false),
+                        },
+                    },
+                }
+            } else {
+                ResponseInputItem::FunctionCallOutput {
+                    call_id: call_id_clone,
+                    output: FunctionCallOutputPayload {
+                        content: "Browser is not enabled. Use browser_open to enable it first.".to_string(),
+                        success: Some(false),
+                    },
+                }
+            }
+        },
+    )
+    .await
+}
+
+fn format_browser_tab(tab: &code_browser::TabInfo) -> String {
+    let marker = if tab.active { "*" } else { " " };
+    let mut line = format!("{} [{}] {}", marker, tab.short_id(), tab.url);
+    if let Some(title) = tab.title.as_deref().filter(|t| !t.is_empty()) {
+        line.push_str(&format!(" — {}", title));
+    }
+    if let Some(opener) = tab.opener_id.as_deref() {
+        line.push_str(&format!(" (opened by {})", code_browser::manager::short_tab_id(opener)));
+    }
+    line
+}
+
+async fn handle_browser_tabs(sess: &Session, ctx: &ToolCallCtx, arguments: String) -> ResponseInputItem {
+    let params = serde_json::from_str(&arguments).ok();
+    let sess_clone = sess;
+    let arguments_clone = arguments.clone();
+    let call_id_clone = ctx.call_id.clone();
+
+    execute_custom_tool(
+        sess,
+        ctx,
+        "browser_tabs".to_string(),
+        params,
+        || async move {
+            let browser_manager = get_browser_manager_for_session(sess_clone).await;
+            let Some(browser_manager) = browser_manager else {
+                return ResponseInputItem::FunctionCallOutput {
+                    call_id: call_id_clone,
+                    output: FunctionCallOutputPayload {
+                        content: "Browser is not enabled. Use browser_open to enable it first.".to_string(),
+                        success: Some(false),
+                    },
+                };
+            };
+
+            let json = serde_json::from_str::<Value>(&arguments_clone).unwrap_or(Value::Null);
+            let op = json
+                .get("op")
+                .and_then(|v| v.as_str())
+                .unwrap_or("list")
+                .to_lowercase();
+            let tab_id = json.get("tab_id").and_then(|v| v.as_str());
+            let url = json.get("url").and_then(|v| v.as_str());
+
+            let result = match op.as_str() {
+                "list" => browser_manager.list_tabs().await.map(|tabs| {
+                    if tabs.is_empty() {
+                        "No open tabs.".to_string()
+                    } else {
+                        let mut output = format!("{} tab(s) open (* = active):\n", tabs.len());
+                        for tab in &tabs {
+                            output.push_str(&format_browser_tab(tab));
+                            output.push('\n');
+                        }
+                        output
+                    }
+                }),
+                "open" => browser_manager
+                    .open_tab(url)
+                    .await
+                    .map(|tab| format!("Opened new tab:\n{}", format_browser_tab(&tab))),
+                "switch" => match tab_id {
+                    Some(tab_id) => browser_manager
+                        .switch_tab(tab_id)
+                        .await
+                        .map(|tab| format!("Switched to tab:\n{}", format_browser_tab(&tab))),
+                    None => Err(code_browser::BrowserError::ConfigError(
+                        "op=switch requires 'tab_id'".to_string(),
+                    )),
+                },
+                "close" => browser_manager.close_tab(tab_id).await.map(|active| match active {
+                    Some(tab) => format!("Closed tab. Active tab is now:\n{}", format_browser_tab(&tab)),
+                    None => "Closed tab. No tabs remain open.".to_string(),
+                }),
+                other => Err(code_browser::BrowserError::ConfigError(format!(
+                    "Unknown tabs op '{}'; expected list, open, switch or close",
+                    other
+                ))),
+            };
+
+            match result {
+                Ok(content) => ResponseInputItem::FunctionCallOutput {
+                    call_id: call_id_clone,
+                    output: FunctionCallOutputPayload {
+                        content,
+                        success: Some(true),
+                    },
+                },
+                Err(e) => ResponseInputItem::FunctionCallOutput {
+                    call_id: call_id_clone,
+                    output: FunctionCallOutputPayload {
+                        content: format!("Browser tabs {} failed: {}", op, e),
+                        success: Some(false),
+                    },
+                },
+            }
+        },
+    )
+    .await
+}
+
+const NETWORK_BODY_MAX_CHARS: usize = 20_000;
+
+fn format_network_size(bytes: f64) -> String {
+    if bytes >= 1024.0 * 1024.0 {
+        format!("{:.1} MB", bytes / (1024.0 * 1024.0))
+    } else if bytes >= 1024.0 {
+        format!("{:.1} KB", bytes / 1024.0)
+    } else {
+        format!("{} B", bytes as u64)
+    }
+}
+
+fn format_network_entry(entry: &code_browser::NetworkEntry) -> String {
+    let status = match (&entry.error, &entry.response) {
+        (Some(_), _) => "FAILED".to_string(),
+        (None, Some(response)) => response.status.to_string(),
+        (None, None) => "pending".to_string(),
+    };
+    let mut details = Vec::new();
+    if let Some(kind) = entry.resource_type.as_deref() {
+        details.push(kind.to_string());
+    }
+    if let Some(duration) = entry.duration_ms {
+        details.push(format!("{:.0} ms", duration));
+    }
+    if let Some(size) = entry.transfer_size {
+        details.push(format_network_size(size));
+    }
+    if entry.response.as_ref().is_some_and(|r| r.from_cache) {
+        details.push("cached".to_string());
+    }
+    let mut line = format!("[{}] {} {} {}", entry.request_id, entry.method, status, entry.url);
+    if !details.is_empty() {
+        line.push_str(&format!(" ({})", details.join(", ")));
+    }
+    if let Some(error) = entry.error.as_deref() {
+        line.push_str(&format!(" — {}", error));
+    }
+    if let Some(location) = entry.redirect_url.as_deref() {
+        line.push_str(&format!(" → {}", location));
+    }
+    line
+}
+
+async fn handle_browser_network(sess: &Session, ctx: &ToolCallCtx, arguments: String) -> ResponseInputItem {
+    let params = serde_json::from_str(&arguments).ok();
+    let sess_clone = sess;
+    let arguments_clone = arguments.clone();
+    let call_id_clone = ctx.call_id.clone();
+
+    execute_custom_tool(
+        sess,
+        ctx,
+        "browser_network".to_string(),
+        params,
+        || async move {
+            let browser_manager = get_browser_manager_for_session(sess_clone).await;
+            let Some(browser_manager) = browser_manager else {
+                return ResponseInputItem::FunctionCallOutput {
+                    call_id: call_id_clone,
+                    output: FunctionCallOutputPayload {
+                        content: "Browser is not enabled. Use browser_open to enable it first.".to_string(),
+                        success: Some(false),
+                    },
+                };
+            };
+
+            let json = serde_json::from_str::<Value>(&arguments_clone).unwrap_or(Value::Null);
+
+            if let Some(request_id) = json.get("request_id").and_then(|v| v.as_str()) {
+                return match browser_manager.network_response_body(request_id).await {
+                    Ok((body, base64_encoded)) => {
+                        let text = if base64_encoded {
+                            base64::engine::general_purpose::STANDARD
+                                .decode(body.as_bytes())
+                                .ok()
+                                .and_then(|bytes| String::from_utf8(bytes).ok())
+                        } else {
+                            Some(body.clone())
+                        };
+                        let content = match text {
+                            Some(text) if text.chars().count() > NETWORK_BODY_MAX_CHARS => {
+                                let truncated: String = text.chars().take(NETWORK_BODY_MAX_CHARS).collect();
+                                format!(
+                                    "Response body for {} (truncated to {} chars):\n{}",
+                                    request_id, NETWORK_BODY_MAX_CHARS, truncated
+                                )
+                            }
+                            Some(text) => format!("Response body for {}:\n{}", request_id, text),
+                            None => format!(
+                                "Response body for {} is binary ({} base64 chars); not shown.",
+                                request_id,
+                                body.len()
+                            ),
+                        };
+                        ResponseInputItem::FunctionCallOutput {
+                            call_id: call_id_clone,
+                            output: FunctionCallOutputPayload {
+                                content,
+                                success: Some(true),
+                            },
+                        }
+                    }
+                    Err(e) => ResponseInputItem::FunctionCallOutput {
+                        call_id: call_id_clone,
+                        output: FunctionCallOutputPayload {
+                            content: format!("Failed to get response body for {}: {}", request_id, e),
+                            success: Some(false),
+                        },
+                    },
+                };
+            }
+
+            let filter = code_browser::NetworkFilter {
+                url_pattern: json
+                    .get("filter")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string),
+                errors_only: json
+                    .get("errors_only")
+                    .and_then(serde_json::Value::as_bool)
+                    .unwrap_or(false),
+                limit: Some(
+                    json.get("limit")
+                        .and_then(serde_json::Value::as_u64)
+                        .map(|n| n as usize)
+                        .unwrap_or(50),
+                ),
+            };
+
+            let result = browser_manager.network_requests(&filter).await;
+            if result.is_ok() && json.get("clear").and_then(serde_json::Value::as_bool) == Some(true) {
+                let _ = browser_manager.clear_network_requests().await;
+            }
+
+            match result {
+                Ok(entries) => {
+                    let content = if entries.is_empty() {
+                        "No matching network requests captured.".to_string()
+                    } else {
+                        let mut output = format!(
+                            "{} request(s), oldest first. Use request_id to fetch a response body.\n",
+                            entries.len()
+                        );
+                        for entry in &entries {
+                            output.push_str(&format_network_entry(entry));
+                            output.push('\n');
+                        }
+                        output
+                    };
+                    ResponseInputItem::FunctionCallOutput {
+                        call_id: call_id_clone,
+                        output: FunctionCallOutputPayload {
+                            content,
+                            success: Some(true),
+                        },
+                    }
+                }
+                Err(e) => ResponseInputItem::FunctionCallOutput {
+                    call_id: call_id_clone,
+                    output: FunctionCallOutputPayload {
+                        content: format!("Failed to list network requests: {}", e),
+                        success: Some(false),
+                    },
+                },
+            }
+        },
+    )
+    .await
+}
+
+async fn handle_browser_compare(sess: &Session, ctx: &ToolCallCtx, arguments: String) -> ResponseInputItem {
+    let params = serde_json::from_str(&arguments).ok();
+    let sess_clone = sess;
+    let arguments_clone = arguments.clone();
+    let call_id_clone = ctx.call_id.clone();
+    let sub_id = ctx.sub_id.clone();
+
+    execute_custom_tool(
+        sess,
+        ctx,
+        "browser_compare".to_string(),
+        params,
+        || async move {
+            let failure = |content: String| ResponseInputItem::FunctionCallOutput {
+                call_id: call_id_clone.clone(),
+                output: FunctionCallOutputPayload {
+                    content,
+                    success: Some(false),
+                },
+            };
+
+            let Some(browser_manager) = get_browser_manager_for_session(sess_clone).await else {
+                return failure("Browser is not enabled. Use browser_open to enable it first.".to_string());
+            };
+
+            let json = serde_json::from_str::<Value>(&arguments_clone).unwrap_or(Value::Null);
+            let Some(name) = json.get("name").and_then(|v| v.as_str()) else {
+                return failure("action=compare requires 'name'".to_string());
+            };
+            let paths = match crate::visual_regression::BaselinePaths::new(sess_clone.get_cwd(), name) {
+                Ok(paths) => paths,
+                Err(e) => return failure(e),
+            };
+            let accept = json.get("accept").and_then(serde_json::Value::as_bool).unwrap_or(false);
+            let threshold = json
+                .get("threshold")
+                .and_then(serde_json::Value::as_f64)
+                .unwrap_or(crate::visual_regression::DEFAULT_SIMILARITY_THRESHOLD)
+                .clamp(0.0, 1.0);
+
+            // Accepting promotes the last compared capture; only capture anew when there is none.
+            let capture = if accept && paths.current.exists() {
+                None
+            } else {
+                match browser_manager.capture_screenshot().await {
+                    Ok(shots) => match shots.into_iter().next() {
+                        Some(path) => Some(path),
+                        None => return failure("Screenshot capture returned no images".to_string()),
+                    },
+                    Err(e) => return failure(format!("Failed to capture screenshot: {}", e)),
+                }
+            };
+
+            if accept {
+                // Replacing a baseline is the user's call, not the model's.
+                if matches!(sess_clone.approval_policy, AskForApproval::Never) {
+                    return failure(
+                        "Accepting a baseline requires user approval, which the approval policy does not allow."
+                            .to_string(),
+                    );
+                }
+                let approval = async {
+                    sess_clone
+                        .request_command_approval(
+                            sub_id.clone(),
+                            call_id_clone.clone(),
+                            vec![
+                                "browser_compare".to_string(),
+                                "accept".to_string(),
+                                name.to_string(),
+                            ],
+                            sess_clone.get_cwd().to_path_buf(),
+                            Some(format!(
+                                "Replace visual baseline '{}' ({}) with the latest capture?",
+                                name,
+                                paths.baseline.display()
+                            )),
+                            None,
+                        )
+                        .await
+                        .await
+                        .unwrap_or_default()
+                };
+                let content = match crate::visual_regression::accept_baseline_with_approval(
+                    &paths, capture, approval,
+                )
+                .await
+                {
+                    Ok(true) => format!(
+                        "Accepted new baseline '{}' ({}).",
+                        name,
+                        paths.baseline.display()
+                    ),
+                    Ok(false) => {
+                        return failure(format!(
+                            "The user declined to replace baseline '{}'; it is unchanged.",
+                            name
+                        ));
+                    }
+                    Err(e) => return failure(format!("Accepting the baseline failed: {}", e)),
+                };
+                return ResponseInputItem::FunctionCallOutput {
+                    call_id: call_id_clone,
+                    output: FunctionCallOutputPayload {
+                        content,
+                        success: Some(true),
+                    },
+                };
+            }
+
+            let Some(capture) = capture else {
+                return failure("Screenshot capture returned no images".to_string());
+            };
+            let task_paths = paths.clone();
+            let result = tokio::task::spawn_blocking(move || {
+                crate::visual_regression::compare_with_baseline(&task_paths, &capture, threshold)
+            })
+            .await;
+
+            let outcome = match result {
+                Ok(Ok(outcome)) => outcome,
+                Ok(Err(e)) => return failure(format!("Visual comparison failed: {}", e)),
+                Err(e) => return failure(format!("Visual comparison task failed: {}", e)),
+            };
+
+            let content = match outcome {
+                crate::visual_regression::CompareOutcome::Created => format!(
+                    "No baseline named '{}' existed; saved this capture as the baseline ({}).",
+                    name,
+                    paths.baseline.display()
+                ),
+                crate::visual_regression::CompareOutcome::Compared { diff, passed } => {
+                    let mut content = format!(
+                        "{} '{}': similarity {:.4} (threshold {:.4}), {} of {} pixels changed.",
+                        if passed { "PASS" } else { "FAIL" },
+                        name,
+                        diff.similarity,
+                        threshold,
+                        diff.changed_pixels,
+                        diff.total_pixels,
+                    );
+                    if diff.baseline_size != diff.current_size {
+                        content.push_str(&format!(
+                            "\nSize changed: baseline {}x{}, current {}x{}.",
+                            diff.baseline_size.0,
+                            diff.baseline_size.1,
+                            diff.current_size.0,
+                            diff.current_size.1,
+                        ));
+                    }
+                    if diff.perceptually_similar && !passed {
+                        content.push_str("\nPerceptual hashes still match; the change is likely minor.");
+                    }
+                    content.push_str(&format!("\nDiff image: {}", paths.diff.display()));
+                    if !passed {
+                        content.push_str(
+                            "\nChanged pixels are highlighted in the attached diff.",
+                        );
+                        if let Ok(bytes) = std::fs::read(&paths.diff) {
+                            let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
+                            sess_clone.add_pending_input(ResponseInputItem::Message {
+                                role: "user".to_string(),
+                                content: vec![
+                                    ContentItem::InputText {
+                                        text: format!("[image: visual diff for baseline '{}']", name),
+                                    },
+                                    ContentItem::InputImage {
+                                        image_url: format!("data:image/png;base64,{encoded}"),
+                                    },
+                                ],
+                            });
+                        }
+                    }
+                    content
+                }
+            };
+
+            ResponseInputItem::FunctionCallOutput {
+                call_id: call_id_clone,
+                output: FunctionCallOutputPayload {
+                    content,
+                    success: Some(true),
+                },
+            }
+        },
+    )
+    .await
+}
+
 async fn handle_browser_cdp(sess: &Session, ctx: &ToolCallCtx, arguments: String) -> ResponseInputItem {
     let params = serde_json::from_str(&arguments).ok();
     let sess_clone = sess;
@@ -11196,6 +12395,7 @@ mod tests {
             aggregated_output: StreamOutput::new(output),
             duration: Duration::from_secs(1),
             timed_out: false,
+            resource_usage: None,
         }
     }
 
@@ -0,0 +1,183 @@
+//! Session view of the execpolicy overlays.
+//!
+//! When `$CODE_HOME/*.policy` or `<project>/.code/*.policy` files exist, the
+//! default execpolicy merged with those overlays is loaded once per session
+//! and consulted by [`crate::safety::assess_command_safety`]:
+//!
+//! - a command the policy forbids is rejected, whatever the approval policy;
+//! - a command the policy matches without writing files, and whose file
+//!   arguments stay within the session's working directory, no longer needs
+//!   the user's approval. It still runs inside the platform sandbox, because
+//!   project overlays come from the repository being worked on.
+//!
+//! Without overlay files nothing is loaded and approvals are unchanged.
+
+use std::ffi::OsString;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_execpolicy::ExecCall;
+use codex_execpolicy::ExecvChecker;
+use codex_execpolicy::MatchedExec;
+use codex_execpolicy::OverlayConflict;
+use codex_execpolicy::load_policy_with_overlays;
+use codex_execpolicy::overlay_paths;
+
+use crate::bash::try_parse_bash;
+use crate::bash::try_parse_word_only_commands_sequence;
+use crate::is_safe_command::is_bash;
+
+/// How the overlay policy judges a command.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PolicyVerdict {
+    /// Every command matched the policy and only reads files under the
+    /// working directory.
+    Allowed,
+    Forbidden {
+        reason: String,
+    },
+    /// The policy has no opinion; the usual approval rules apply.
+    Unmatched,
+}
+
+pub struct ExecPolicyOverlay {
+    checker: ExecvChecker,
+    cwd: PathBuf,
+    conflicts: Vec<OverlayConflict>,
+}
+
+impl ExecPolicyOverlay {
+    /// Loads the default policy with the overlays found for `code_home` and
+    /// `cwd`. Returns `Ok(None)` when there are no overlay files.
+    pub fn load(code_home: &Path, cwd: &Path) -> anyhow::Result<Option<Self>> {
+        let paths = overlay_paths(Some(code_home), cwd);
+        if paths.user.is_empty() && paths.project.is_empty() {
+            return Ok(None);
+        }
+        let loaded = load_policy_with_overlays(&paths)?;
+        let cwd = cwd.canonicalize().unwrap_or_else(|_| cwd.to_path_buf());
+        Ok(Some(Self {
+            checker: ExecvChecker::new(loaded.policy),
+            cwd,
+            conflicts: loaded.conflicts,
+        }))
+    }
+
+    /// Programs defined by more than one overlay file.
+    pub fn conflicts(&self) -> &[OverlayConflict] {
+        &self.conflicts
+    }
+
+    /// Checks `command`, or each command of a `bash -lc` script made only of
+    /// plain words and `&&`, `||`, `;` or `|`.
+    pub fn check(&self, command: &[String]) -> PolicyVerdict {
+        let commands = match command {
+            [bash, flag, script] if is_bash(bash) && flag == "-lc" => {
+                match try_parse_bash(script)
+                    .and_then(|tree| try_parse_word_only_commands_sequence(&tree, script))
+                {
+                    Some(commands) if !commands.is_empty() => commands,
+                    _ => return PolicyVerdict::Unmatched,
+                }
+            }
+            _ => vec![command.to_vec()],
+        };
+
+        let mut verdict = PolicyVerdict::Allowed;
+        for command in &commands {
+            match self.check_exec(command) {
+                PolicyVerdict::Allowed => {}
+                PolicyVerdict::Forbidden { reason } => return PolicyVerdict::Forbidden { reason },
+                PolicyVerdict::Unmatched => verdict = PolicyVerdict::Unmatched,
+            }
+        }
+        verdict
+    }
+
+    fn check_exec(&self, command: &[String]) -> PolicyVerdict {
+        let Some((program, args)) = command.split_first() else {
+            return PolicyVerdict::Unmatched;
+        };
+        let exec_call = ExecCall {
+            program: program.clone(),
+            args: args.to_vec(),
+        };
+        match self.checker.r#match(&exec_call) {
+            Ok(MatchedExec::Match { exec }) => {
+                let cwd = Some(OsString::from(self.cwd.as_os_str()));
+                // No writeable folders: a command that writes any file is
+                // left to the usual approval rules.
+                match self
+                    .checker
+                    .check(exec, &cwd, std::slice::from_ref(&self.cwd), &[])
+                {
+                    Ok(_) => PolicyVerdict::Allowed,
+                    Err(_) => PolicyVerdict::Unmatched,
+                }
+            }
+            Ok(MatchedExec::Forbidden { reason, .. }) => PolicyVerdict::Forbidden { reason },
+            Err(_) => PolicyVerdict::Unmatched,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEAM_POLICY: &str = r#"
+define_program(
+    program="bazel",
+    args=["query", ARG_OPAQUE_VALUE],
+)
+
+forbid_program_regex(
+    regex="^deploy$",
+    reason="deploys must go through CI",
+)
+"#;
+
+    fn vec_str(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn nothing_is_loaded_without_overlays() {
+        let code_home = tempfile::tempdir().unwrap();
+        let cwd = tempfile::tempdir().unwrap();
+        assert!(
+            ExecPolicyOverlay::load(code_home.path(), cwd.path())
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn overlays_allow_and_forbid_commands() {
+        let code_home = tempfile::tempdir().unwrap();
+        let cwd = tempfile::tempdir().unwrap();
+        std::fs::write(code_home.path().join("team.policy"), TEAM_POLICY).unwrap();
+        let overlay = ExecPolicyOverlay::load(code_home.path(), cwd.path())
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            overlay.check(&vec_str(&["bazel", "query", "//..."])),
+            PolicyVerdict::Allowed
+        );
+        assert_eq!(
+            overlay.check(&vec_str(&["bash", "-lc", "bazel query //... && ls"])),
+            PolicyVerdict::Allowed
+        );
+        assert_eq!(
+            overlay.check(&vec_str(&["bazel", "build", "//..."])),
+            PolicyVerdict::Unmatched
+        );
+        assert_eq!(
+            overlay.check(&vec_str(&["bash", "-lc", "bazel query //... && deploy"])),
+            PolicyVerdict::Forbidden {
+                reason: "deploys must go through CI".to_string()
+            }
+        );
+    }
+}
@@ -2,7 +2,7 @@ use crate::bash::try_parse_bash;
 use crate::bash::try_parse_word_only_commands_sequence;
 use std::path::Path;
 
-fn is_bash(cmd: &str) -> bool {
+pub(crate) fn is_bash(cmd: &str) -> bool {
     let trimmed = cmd.trim_matches('"').trim_matches('\'');
     if trimmed.eq_ignore_ascii_case("bash") || trimmed.eq_ignore_ascii_case("bash.exe") {
         return true;
@@ -12,6 +12,9 @@ use crate::config_types::Notice;
 use crate::config_types::History;
 use crate::config_types::GithubConfig;
 use crate::config_types::ValidationConfig;
+use crate::config_types::McpElicitationPolicy;
+use crate::config_types::McpSamplingConfig;
+use crate::config_types::ModelFailoverConfig;
 use crate::config_types::McpServerConfig;
 use crate::config_types::Notifications;
 use crate::config_types::OtelConfig;
@@ -26,16 +29,21 @@ use crate::config_types::TextVerbosity;
 use crate::config_types::Tui;
 use crate::config_types::UriBasedFileOpener;
 use crate::config_types::ConfirmGuardConfig;
+use crate::config_types::ExecLimits;
 use crate::config_types::DEFAULT_OTEL_ENVIRONMENT;
 use crate::git_info::resolve_root_git_project_for_trust;
 use crate::model_family::ModelFamily;
 use crate::model_family::derive_default_model_family;
 use crate::model_family::find_family_for_model;
+use crate::cassette::CassetteMode;
+use crate::model_failover::ModelFailoverTarget;
+use crate::model_failover::resolve_failover_chain;
 use crate::model_provider_info::ModelProviderInfo;
 use crate::model_provider_info::built_in_model_providers;
 use crate::reasoning::clamp_reasoning_effort_for_model;
 use crate::protocol::AskForApproval;
 use crate::protocol::SandboxPolicy;
+use crate::protocol::default_read_denied_paths;
 use crate::config_types::ReasoningEffort;
 use crate::config_types::ReasoningSummary;
 use crate::project_features::{load_project_commands, ProjectCommand, ProjectHooks};
@@ -189,6 +197,13 @@ pub struct Config {
     /// Info needed to make an API request to the model.
     pub model_provider: ModelProviderInfo,
 
+    /// Fallback providers tried in order when `model_provider` keeps failing.
+    pub model_failover: Vec<ModelFailoverTarget>,
+
+    /// Record model traffic to, or replay it from, a cassette file. Only
+    /// `code exec` sets this (`--record-cassette` / `--replay-cassette`).
+    pub model_cassette: Option<CassetteMode>,
+
     /// Name of the active profile, if any, that populated this configuration.
     pub active_profile: Option<String>,
 
@@ -207,6 +222,9 @@ pub struct Config {
     pub project_commands: Vec<ProjectCommand>,
 
     pub shell_environment_policy: ShellEnvironmentPolicy,
+
+    /// Per-command cgroup and wall-time limits (`[exec_limits]`).
+    pub exec_limits: ExecLimits,
     /// Patterns requiring an explicit confirm prefix before running.
     pub confirm_guard: ConfirmGuardConfig,
 
@@ -287,6 +305,12 @@ pub struct Config {
     /// Definition for MCP servers that Codex can reach out to for tool calls.
     pub mcp_servers: HashMap<String, McpServerConfig>,
 
+    /// How elicitation requests from MCP servers are answered.
+    pub mcp_elicitation: McpElicitationPolicy,
+
+    /// Which MCP servers may run completions on the session's model.
+    pub mcp_sampling: McpSamplingConfig,
+
     /// Optional ACP client tool identifiers supplied by the host IDE.
     pub experimental_client_tools: Option<ClientTools>,
 
@@ -396,6 +420,9 @@ pub struct Config {
     /// Validation harness configuration.
     pub validation: ValidationConfig,
 
+    /// `[browser]` settings applied to the shared browser manager.
+    pub browser: BrowserConfig,
+
     /// Resolved subagent command configurations (including custom ones).
     /// If a command with name `plan|solve|code` exists here, it overrides
     /// the built-in defaults for that slash command.
@@ -498,6 +525,9 @@ pub struct ConfigToml {
     /// Provider to use from the model_providers map.
     pub model_provider: Option<String>,
 
+    /// Ordered `provider:model` fallbacks for when `model_provider` is down.
+    pub model_failover: Option<ModelFailoverConfig>,
+
     /// Size of the context window for the model, in tokens.
     pub model_context_window: Option<u64>,
 
@@ -513,6 +543,10 @@ pub struct ConfigToml {
     #[serde(default)]
     pub shell_environment_policy: ShellEnvironmentPolicyToml,
 
+    /// Per-command cgroup and wall-time limits.
+    #[serde(default)]
+    pub exec_limits: ExecLimits,
+
     /// Sandbox mode to use.
     pub sandbox_mode: Option<SandboxMode>,
 
@@ -548,6 +582,13 @@ pub struct ConfigToml {
     #[serde(default)]
     pub mcp_servers: HashMap<String, McpServerConfig>,
 
+    /// How elicitation requests from MCP servers are answered
+    /// (`ask`, `decline` or `cancel`).
+    pub mcp_elicitation: Option<McpElicitationPolicy>,
+
+    /// Sampling (`sampling/createMessage`) settings for MCP servers.
+    pub mcp_sampling: Option<McpSamplingConfig>,
+
     /// Optional ACP client tool identifiers supplied by the host IDE.
     #[serde(default)]
     pub experimental_client_tools: Option<ClientTools>,
@@ -757,15 +798,21 @@ impl ConfigToml {
                 Some(SandboxWorkspaceWrite {
                     writable_roots,
                     network_access,
+                    network_allowlist,
                     exclude_tmpdir_env_var,
                     exclude_slash_tmp,
                     allow_git_writes,
+                    read_denied_paths,
                 }) => SandboxPolicy::WorkspaceWrite {
                     writable_roots: writable_roots.clone(),
                     network_access: *network_access,
+                    network_allowlist: network_allowlist.clone(),
                     exclude_tmpdir_env_var: *exclude_tmpdir_env_var,
                     exclude_slash_tmp: *exclude_slash_tmp,
                     allow_git_writes: *allow_git_writes,
+                    read_denied_paths: read_denied_paths
+                        .clone()
+                        .unwrap_or_else(default_read_denied_paths),
                 },
                 None => SandboxPolicy::new_workspace_write_policy(),
             },
@@ -844,6 +891,7 @@ pub struct ConfigOverrides {
     pub experimental_client_tools: Option<ClientTools>,
     pub compact_prompt_override: Option<String>,
     pub compact_prompt_override_file: Option<PathBuf>,
+    pub model_cassette: Option<CassetteMode>,
 }
 
 impl Config {
@@ -881,6 +929,7 @@ impl Config {
             experimental_client_tools,
             compact_prompt_override,
             compact_prompt_override_file,
+            model_cassette,
         } = overrides;
 
         if let Some(mcp_servers) = mcp_servers {
@@ -977,15 +1026,19 @@ impl Config {
                 Some(SandboxWorkspaceWrite {
                     writable_roots,
                     network_access,
+                    network_allowlist,
                     exclude_tmpdir_env_var,
                     exclude_slash_tmp,
                     allow_git_writes,
+                    read_denied_paths,
                 }) => SandboxPolicy::WorkspaceWrite {
                     writable_roots,
                     network_access,
+                    network_allowlist,
                     exclude_tmpdir_env_var,
                     exclude_slash_tmp,
                     allow_git_writes,
+                    read_denied_paths: read_denied_paths.unwrap_or_else(default_read_denied_paths),
                 },
                 None => SandboxPolicy::new_workspace_write_policy(),
             },
@@ -1082,6 +1135,20 @@ impl Config {
         let model_family =
             find_family_for_model(&model).unwrap_or_else(|| derive_default_model_family(&model));
 
+        let model_failover = match config_profile
+            .model_failover
+            .clone()
+            .or_else(|| cfg.model_failover.clone())
+        {
+            Some(failover) => resolve_failover_chain(
+                &failover.chain,
+                &model_providers,
+                &model_provider_id,
+                &model,
+            )?,
+            None => Vec::new(),
+        };
+
         // Chat model reasoning effort (used when other flows follow the chat model).
         let preferred_model_reasoning_effort = config_profile
             .preferred_model_reasoning_effort
@@ -1346,6 +1413,8 @@ impl Config {
             model_auto_compact_token_limit,
             model_provider_id,
             model_provider,
+            model_failover,
+            model_cassette,
             cwd: resolved_cwd,
             approval_policy: effective_approval,
             sandbox_policy,
@@ -1353,6 +1422,7 @@ impl Config {
             project_hooks,
             project_commands,
             shell_environment_policy,
+            exec_limits: cfg.exec_limits,
             confirm_guard,
             disable_response_storage: config_profile
                 .disable_response_storage
@@ -1367,6 +1437,8 @@ impl Config {
             base_instructions,
             compact_prompt_override,
             mcp_servers: cfg.mcp_servers,
+            mcp_elicitation: cfg.mcp_elicitation.unwrap_or_default(),
+            mcp_sampling: cfg.mcp_sampling.clone().unwrap_or_default(),
             experimental_client_tools: cfg.experimental_client_tools.clone(),
             agents,
             model_providers,
@@ -1436,6 +1508,7 @@ impl Config {
             api_key_fallback_on_all_accounts_limited,
             github: cfg.github.unwrap_or_default(),
             validation: cfg.validation.unwrap_or_default(),
+            browser: cfg.browser.unwrap_or_default(),
             subagent_commands: cfg
                 .subagents
                 .map(|s| s.commands)
@@ -1677,6 +1750,60 @@ persistence = "none"
         Ok(())
     }
 
+    #[test]
+    fn browser_settings_parse_from_toml() -> std::io::Result<()> {
+        let code_home = TempDir::new()?;
+
+        let cfg = toml::from_str::<ConfigToml>(
+            r#"
+[browser]
+allow_new_tabs = true
+record_network = true
+"#,
+        )
+        .expect("TOML should deserialize");
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides::default(),
+            code_home.path().to_path_buf(),
+        )?;
+        assert!(config.browser.allow_new_tabs);
+        assert!(config.browser.record_network);
+        Ok(())
+    }
+
+    #[test]
+    fn exec_limits_parse_from_toml() -> std::io::Result<()> {
+        let code_home = TempDir::new()?;
+
+        let cfg = toml::from_str::<ConfigToml>(
+            r#"
+[exec_limits]
+cpu_max_cores = 2.5
+pids_max = 512
+io_weight = 50
+wall_time_max_secs = 900
+"#,
+        )
+        .expect("TOML should deserialize");
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides::default(),
+            code_home.path().to_path_buf(),
+        )?;
+        assert_eq!(
+            config.exec_limits,
+            ExecLimits {
+                memory_max_mb: None,
+                cpu_max_cores: Some(2.5),
+                pids_max: Some(512),
+                io_weight: Some(50),
+                wall_time_max_secs: Some(900),
+            }
+        );
+        Ok(())
+    }
+
     #[test]
     fn load_instructions_reads_from_code_home() -> anyhow::Result<()> {
         let code_home = TempDir::new()?;
@@ -1774,9 +1901,11 @@ exclude_slash_tmp = true
             SandboxPolicy::WorkspaceWrite {
                 writable_roots: vec![PathBuf::from("/my/workspace")],
                 network_access: false,
+                network_allowlist: vec![],
                 exclude_tmpdir_env_var: true,
                 exclude_slash_tmp: true,
                 allow_git_writes: true,
+                read_denied_paths: default_read_denied_paths(),
             },
             sandbox_workspace_write_cfg.derive_sandbox_policy(sandbox_mode_override)
         );
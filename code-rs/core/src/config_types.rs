@@ -192,6 +192,63 @@ pub enum McpServerTransportConfig {
     },
 }
 
+/// How to answer `elicitation/create` requests from MCP servers.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum McpElicitationPolicy {
+    /// Show the request to the user and wait for an answer.
+    #[default]
+    Ask,
+    /// Decline every request without asking.
+    Decline,
+    /// Cancel every request without asking.
+    Cancel,
+}
+
+impl McpElicitationPolicy {
+    /// Policy to use when nobody is around to answer: `Ask` becomes `Decline`.
+    pub fn non_interactive(self) -> Self {
+        match self {
+            Self::Ask => Self::Decline,
+            other => other,
+        }
+    }
+}
+
+/// Which MCP servers may run `sampling/createMessage` requests on the
+/// session's model, and how much they may spend.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct McpSamplingConfig {
+    /// Servers allowed to request completions. Requests from any other
+    /// server are rejected.
+    #[serde(default)]
+    pub allowed_servers: Vec<String>,
+
+    /// Total tokens each server may use per session. Unlimited when unset.
+    #[serde(default)]
+    pub token_budget: Option<u64>,
+
+    /// Show the requested messages and ask before running each request.
+    #[serde(default)]
+    pub require_approval: bool,
+}
+
+impl McpSamplingConfig {
+    pub fn allows(&self, server_name: &str) -> bool {
+        self.allowed_servers.iter().any(|name| name == server_name)
+    }
+}
+
+/// Fallback providers and models for when the session's provider keeps
+/// failing after its retries.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct ModelFailoverConfig {
+    /// `provider:model` entries tried in order, e.g.
+    /// `["openai:gpt-5", "azure:gpt-5", "ollama:qwen"]`.
+    #[serde(default)]
+    pub chain: Vec<String>,
+}
+
 mod option_duration_secs {
     use serde::Deserialize;
     use serde::Deserializer;
@@ -1106,6 +1163,16 @@ pub struct BrowserConfig {
 
     #[serde(default)]
     pub format: Option<BrowserImageFormat>,
+
+    /// Let pages open popups and `target=_blank` links in new tabs instead of
+    /// redirecting them into the current tab.
+    #[serde(default)]
+    pub allow_new_tabs: bool,
+
+    /// Journal completed browser requests under `$CODE_HOME/browser/network/`
+    /// so `code browser har` can export them later.
+    #[serde(default)]
+    pub record_network: bool,
 }
 
 #[derive(Deserialize, Debug, Clone, PartialEq)]
@@ -1140,6 +1207,10 @@ pub struct SandboxWorkspaceWrite {
     pub writable_roots: Vec<PathBuf>,
     #[serde(default)]
     pub network_access: bool,
+    /// Hosts reachable through the egress proxy when `network_access` is
+    /// `false`, e.g. `crates.io` or `*.npmjs.org`.
+    #[serde(default)]
+    pub network_allowlist: Vec<String>,
     #[serde(default)]
     pub exclude_tmpdir_env_var: bool,
     #[serde(default)]
@@ -1148,11 +1219,34 @@ pub struct SandboxWorkspaceWrite {
     /// root. Defaults to true (historical behavior allows Git writes).
     #[serde(default = "crate::config_types::default_true_bool")]
     pub allow_git_writes: bool,
+    /// Paths sandboxed commands may not read. When unset, credential
+    /// locations such as `~/.ssh` and the `$CODE_HOME` auth files are hidden.
+    #[serde(default)]
+    pub read_denied_paths: Option<Vec<PathBuf>>,
 }
 
 // Serde helper: default to true for `allow_git_writes` when omitted.
 pub(crate) const fn default_true_bool() -> bool { true }
 
+/// Resource limits applied to every shell command. The cgroup limits take
+/// effect on Linux with cgroup v2 and are skipped when the controller is not
+/// delegated to this process.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ExecLimits {
+    /// `memory.max` for each command. Defaults to 60% of available memory,
+    /// clamped to 512 MiB..4 GiB.
+    pub memory_max_mb: Option<u64>,
+    /// `cpu.max` expressed in cores, e.g. `1.5` for one and a half cores.
+    pub cpu_max_cores: Option<f64>,
+    /// `pids.max` for each command; stops fork bombs at this many tasks.
+    pub pids_max: Option<u64>,
+    /// `io.weight` (1-10000, kernel default 100) relative to other cgroups.
+    pub io_weight: Option<u16>,
+    /// Upper bound on any command's run time, including commands that
+    /// request no timeout or a longer one.
+    pub wall_time_max_secs: Option<u64>,
+}
+
 #[derive(Deserialize, Debug, Clone, PartialEq, Default)]
 #[serde(rename_all = "kebab-case")]
 pub enum ShellEnvironmentPolicyInherit {
@@ -1346,6 +1440,22 @@ pub enum ProjectHookEvent {
     FileBeforeWrite,
     #[serde(rename = "file.after_write")]
     FileAfterWrite,
+    #[serde(rename = "turn.submit")]
+    TurnSubmit,
+    #[serde(rename = "turn.complete")]
+    TurnComplete,
+    #[serde(rename = "context.compact")]
+    ContextCompact,
+    #[serde(rename = "agent.spawn")]
+    AgentSpawn,
+    #[serde(rename = "agent.finish")]
+    AgentFinish,
+    #[serde(rename = "approval.request")]
+    ApprovalRequest,
+    #[serde(rename = "approval.decision")]
+    ApprovalDecision,
+    #[serde(rename = "auto_drive.phase")]
+    AutoDrivePhase,
 }
 
 impl ProjectHookEvent {
@@ -1357,6 +1467,14 @@ impl ProjectHookEvent {
             ProjectHookEvent::ToolAfter => "tool.after",
             ProjectHookEvent::FileBeforeWrite => "file.before_write",
             ProjectHookEvent::FileAfterWrite => "file.after_write",
+            ProjectHookEvent::TurnSubmit => "turn.submit",
+            ProjectHookEvent::TurnComplete => "turn.complete",
+            ProjectHookEvent::ContextCompact => "context.compact",
+            ProjectHookEvent::AgentSpawn => "agent.spawn",
+            ProjectHookEvent::AgentFinish => "agent.finish",
+            ProjectHookEvent::ApprovalRequest => "approval.request",
+            ProjectHookEvent::ApprovalDecision => "approval.decision",
+            ProjectHookEvent::AutoDrivePhase => "auto_drive.phase",
         }
     }
 
@@ -1368,6 +1486,14 @@ impl ProjectHookEvent {
             ProjectHookEvent::ToolAfter => "tool_after",
             ProjectHookEvent::FileBeforeWrite => "file_before_write",
             ProjectHookEvent::FileAfterWrite => "file_after_write",
+            ProjectHookEvent::TurnSubmit => "turn_submit",
+            ProjectHookEvent::TurnComplete => "turn_complete",
+            ProjectHookEvent::ContextCompact => "context_compact",
+            ProjectHookEvent::AgentSpawn => "agent_spawn",
+            ProjectHookEvent::AgentFinish => "agent_finish",
+            ProjectHookEvent::ApprovalRequest => "approval_request",
+            ProjectHookEvent::ApprovalDecision => "approval_decision",
+            ProjectHookEvent::AutoDrivePhase => "auto_drive_phase",
         }
     }
 }
@@ -1387,6 +1513,21 @@ pub struct ProjectHookConfig {
     pub timeout_ms: Option<u64>,
     #[serde(default)]
     pub run_in_background: Option<bool>,
+    /// What a `tool.before`/`file.before_write` hook that exits non-zero (or
+    /// fails to run) means for the pending call. Defaults to `deny`.
+    #[serde(default)]
+    pub on_failure: Option<HookFailurePolicy>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum HookFailurePolicy {
+    /// Block the tool call or write, as if the hook had printed a `deny`
+    /// decision.
+    #[default]
+    Deny,
+    /// Let the tool call or write proceed.
+    Allow,
 }
 
 #[derive(Deserialize, Debug, Clone, PartialEq)]
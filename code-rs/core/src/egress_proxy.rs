@@ -0,0 +1,459 @@
+//! Forwarding proxy that lets sandboxed commands reach an allowlist of hosts.
+//!
+//! When a `workspace-write` policy disables `network_access` but lists hosts
+//! in `network_allowlist`, the Linux sandbox helper runs the command in a
+//! network namespace whose only way out is a Unix socket served by this
+//! proxy. The proxy understands `CONNECT host:port` (used for HTTPS) and
+//! plain `http://` requests, and refuses hosts that are not allowlisted.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use tempfile::TempDir;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio::net::UnixListener;
+use tokio::net::UnixStream;
+use tokio::sync::Mutex;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tracing::debug;
+use tracing::warn;
+
+use crate::protocol::SandboxPolicy;
+
+/// Largest request head the proxy reads before giving up on a connection.
+const MAX_REQUEST_HEAD_BYTES: usize = 64 * 1024;
+
+/// Running proxies keyed by allowlist, shared by every sandboxed command.
+static PROXIES: LazyLock<Mutex<HashMap<Vec<String>, Arc<EgressProxy>>>> =
+    LazyLock::new(Default::default);
+
+/// A connection the proxy refused because its host is not allowlisted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct EgressDenial {
+    pub(crate) host: String,
+    pub(crate) port: u16,
+    /// Process that connected to the proxy, i.e. the sandbox helper.
+    pub(crate) peer_pid: Option<i32>,
+}
+
+impl fmt::Display for EgressDenial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "blocked network access to {}:{} (not in sandbox_workspace_write.network_allowlist)",
+            self.host, self.port
+        )
+    }
+}
+
+pub(crate) struct EgressProxy {
+    socket_path: PathBuf,
+    denials: broadcast::Sender<EgressDenial>,
+    accept_task: JoinHandle<()>,
+    _socket_dir: TempDir,
+}
+
+impl EgressProxy {
+    fn start(allowlist: Vec<String>) -> io::Result<Self> {
+        let socket_dir = tempfile::Builder::new().prefix("code-egress-").tempdir()?;
+        let socket_path = socket_dir.path().join("proxy.sock");
+        let listener = UnixListener::bind(&socket_path)?;
+        let (denials, _) = broadcast::channel(64);
+        let allowlist = Arc::new(allowlist);
+
+        let accept_task = tokio::spawn({
+            let denials = denials.clone();
+            async move {
+                loop {
+                    let stream = match listener.accept().await {
+                        Ok((stream, _)) => stream,
+                        Err(err) => {
+                            warn!("egress proxy failed to accept a connection: {err}");
+                            tokio::time::sleep(Duration::from_millis(100)).await;
+                            continue;
+                        }
+                    };
+                    let allowlist = allowlist.clone();
+                    let denials = denials.clone();
+                    tokio::spawn(async move {
+                        if let Err(err) = handle_connection(stream, &allowlist, &denials).await {
+                            debug!("egress proxy connection ended with error: {err}");
+                        }
+                    });
+                }
+            }
+        });
+
+        Ok(Self {
+            socket_path,
+            denials,
+            accept_task,
+            _socket_dir: socket_dir,
+        })
+    }
+
+    /// Unix socket the sandbox helper forwards the command's connections to.
+    pub(crate) fn socket_path(&self) -> &Path {
+        &self.socket_path
+    }
+
+    /// Receives every connection refused from now on.
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<EgressDenial> {
+        self.denials.subscribe()
+    }
+}
+
+impl Drop for EgressProxy {
+    fn drop(&mut self) {
+        self.accept_task.abort();
+    }
+}
+
+/// Returns the proxy enforcing `allowlist`, starting it on first use.
+pub(crate) async fn shared_proxy(allowlist: &[String]) -> io::Result<Arc<EgressProxy>> {
+    let mut proxies = PROXIES.lock().await;
+    if let Some(proxy) = proxies.get(allowlist) {
+        // A proxy stops with the runtime that started it.
+        if !proxy.accept_task.is_finished() {
+            return Ok(proxy.clone());
+        }
+    }
+    let proxy = Arc::new(EgressProxy::start(allowlist.to_vec())?);
+    proxies.insert(allowlist.to_vec(), proxy.clone());
+    Ok(proxy)
+}
+
+/// Returns the proxy for `policy`'s network allowlist, if it has one.
+pub(crate) async fn proxy_for_policy(
+    policy: &SandboxPolicy,
+) -> io::Result<Option<Arc<EgressProxy>>> {
+    match policy.get_network_allowlist() {
+        [] => Ok(None),
+        allowlist => shared_proxy(allowlist).await.map(Some),
+    }
+}
+
+/// Ports reachable for allowlist entries that do not name one.
+const DEFAULT_ALLOWED_PORTS: &[u16] = &[443, 80];
+
+/// Whether `host` (lowercase, without a trailing dot) may be reached on
+/// `port`. `example.com` allows the host and its subdomains, `*.example.com`
+/// only its subdomains. Entries reach ports 443 and 80 unless they name a
+/// port, as in `example.com:8443`, which then is the only one allowed.
+pub(crate) fn host_allowed(allowlist: &[String], host: &str, port: u16) -> bool {
+    let is_subdomain = |suffix: &str| {
+        host.strip_suffix(suffix)
+            .is_some_and(|prefix| prefix.len() > 1 && prefix.ends_with('.'))
+    };
+    allowlist.iter().any(|entry| {
+        let Some((entry, entry_port)) = parse_authority(entry.trim(), 0) else {
+            return false;
+        };
+        let port_allowed = match entry_port {
+            0 => DEFAULT_ALLOWED_PORTS.contains(&port),
+            entry_port => entry_port == port,
+        };
+        port_allowed
+            && match entry.strip_prefix("*.") {
+                Some(suffix) => is_subdomain(suffix),
+                None => host == entry || is_subdomain(&entry),
+            }
+    })
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ProxyRequest {
+    /// `CONNECT host:port`: tunnel raw bytes once the host is accepted.
+    Connect { host: String, port: u16 },
+    /// A plain HTTP request; `head` is rewritten for the origin server.
+    Forward {
+        host: String,
+        port: u16,
+        head: String,
+    },
+}
+
+impl ProxyRequest {
+    fn parse(head: &str) -> Option<Self> {
+        let (request_line, headers) = head.split_once("\r\n")?;
+        let mut parts = request_line.split_whitespace();
+        let (method, target, version) = (parts.next()?, parts.next()?, parts.next()?);
+        if method.eq_ignore_ascii_case("CONNECT") {
+            let (host, port) = parse_authority(target, 443)?;
+            return Some(Self::Connect { host, port });
+        }
+
+        let scheme_len = "http://".len();
+        if !target
+            .get(..scheme_len)
+            .is_some_and(|scheme| scheme.eq_ignore_ascii_case("http://"))
+        {
+            return None;
+        }
+        let rest = &target[scheme_len..];
+        let (authority, path) = match rest.find('/') {
+            Some(index) => rest.split_at(index),
+            None => (rest, "/"),
+        };
+        let (host, port) = parse_authority(authority, 80)?;
+
+        // One request per connection, so that a kept-alive connection cannot
+        // be reused for a host that was not checked.
+        let mut rewritten = format!("{method} {path} {version}\r\n");
+        for line in headers.split("\r\n").filter(|line| !line.is_empty()) {
+            let name = line.split(':').next().unwrap_or_default().trim();
+            if [
+                "proxy-connection",
+                "proxy-authorization",
+                "connection",
+                "keep-alive",
+            ]
+            .iter()
+            .any(|hop| name.eq_ignore_ascii_case(hop))
+            {
+                continue;
+            }
+            rewritten.push_str(line);
+            rewritten.push_str("\r\n");
+        }
+        rewritten.push_str("Connection: close\r\n\r\n");
+        Some(Self::Forward {
+            host,
+            port,
+            head: rewritten,
+        })
+    }
+
+    fn host_and_port(&self) -> (&str, u16) {
+        match self {
+            Self::Connect { host, port } | Self::Forward { host, port, .. } => (host, *port),
+        }
+    }
+}
+
+/// Splits `[userinfo@]host[:port]` into a normalized host and a port.
+fn parse_authority(authority: &str, default_port: u16) -> Option<(String, u16)> {
+    let authority = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host_port)| host_port);
+    let (host, port) = if let Some(bracketed) = authority.strip_prefix('[') {
+        let (host, after) = bracketed.split_once(']')?;
+        match after.strip_prefix(':') {
+            Some(port) => (host, port.parse().ok()?),
+            None if after.is_empty() => (host, default_port),
+            None => return None,
+        }
+    } else {
+        match authority.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().ok()?),
+            None => (authority, default_port),
+        }
+    };
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    if host.is_empty() {
+        return None;
+    }
+    Some((host, port))
+}
+
+/// Reads up to the end of the request head; returns the head and whatever
+/// was read past it.
+async fn read_request_head(stream: &mut UnixStream) -> io::Result<(String, Vec<u8>)> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        if let Some(end) = buf.windows(4).position(|window| window == b"\r\n\r\n") {
+            let rest = buf.split_off(end + 4);
+            let head = String::from_utf8(buf).map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidData, "request head is not UTF-8")
+            })?;
+            return Ok((head, rest));
+        }
+        if buf.len() > MAX_REQUEST_HEAD_BYTES {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "request head too large",
+            ));
+        }
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        buf.extend_from_slice(&chunk[..read]);
+    }
+}
+
+async fn write_status(stream: &mut UnixStream, status: &str, body: &str) -> io::Result<()> {
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await
+}
+
+async fn handle_connection(
+    mut client: UnixStream,
+    allowlist: &[String],
+    denials: &broadcast::Sender<EgressDenial>,
+) -> io::Result<()> {
+    let peer_pid = client.peer_cred().ok().and_then(|cred| cred.pid());
+    let (head, rest) = read_request_head(&mut client).await?;
+    let Some(request) = ProxyRequest::parse(&head) else {
+        return write_status(
+            &mut client,
+            "400 Bad Request",
+            "Code sandbox proxy only supports CONNECT and http:// requests\n",
+        )
+        .await;
+    };
+
+    let (host, port) = request.host_and_port();
+    if !host_allowed(allowlist, host, port) {
+        let denial = EgressDenial {
+            host: host.to_string(),
+            port,
+            peer_pid,
+        };
+        warn!("egress proxy {denial}");
+        let body = format!("Code sandbox: {denial}\n");
+        let _ = denials.send(denial);
+        return write_status(&mut client, "403 Forbidden", &body).await;
+    }
+
+    let mut upstream = match TcpStream::connect((host, port)).await {
+        Ok(upstream) => upstream,
+        Err(err) => {
+            let body = format!("Code sandbox: could not connect to {host}:{port}: {err}\n");
+            return write_status(&mut client, "502 Bad Gateway", &body).await;
+        }
+    };
+    match &request {
+        ProxyRequest::Connect { .. } => {
+            client
+                .write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")
+                .await?;
+        }
+        ProxyRequest::Forward { head, .. } => upstream.write_all(head.as_bytes()).await?,
+    }
+    upstream.write_all(&rest).await?;
+    tokio::io::copy_bidirectional(&mut client, &mut upstream).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tokio::net::TcpListener;
+
+    fn allowlist(entries: &[&str]) -> Vec<String> {
+        entries.iter().map(|entry| entry.to_string()).collect()
+    }
+
+    #[test]
+    fn allowlist_matches_hosts_and_subdomains() {
+        let list = allowlist(&["crates.io", "*.npmjs.org", "Mirror.Example.COM."]);
+        assert!(host_allowed(&list, "crates.io", 443));
+        assert!(host_allowed(&list, "index.crates.io", 443));
+        assert!(!host_allowed(&list, "evilcrates.io", 443));
+        assert!(!host_allowed(&list, "npmjs.org", 443));
+        assert!(host_allowed(&list, "registry.npmjs.org", 80));
+        assert!(host_allowed(&list, "mirror.example.com", 443));
+        assert!(!host_allowed(&list, "example.com", 443));
+        assert!(!host_allowed(&allowlist(&[""]), "example.com", 443));
+    }
+
+    #[test]
+    fn allowlist_limits_ports() {
+        let list = allowlist(&["crates.io", "git.example.com:2222", "[::1]:8080"]);
+        assert!(host_allowed(&list, "crates.io", 443));
+        assert!(host_allowed(&list, "crates.io", 80));
+        assert!(!host_allowed(&list, "crates.io", 22));
+        assert!(host_allowed(&list, "git.example.com", 2222));
+        assert!(!host_allowed(&list, "git.example.com", 443));
+        assert!(host_allowed(&list, "::1", 8080));
+        assert!(!host_allowed(&list, "::1", 443));
+    }
+
+    #[test]
+    fn parses_connect_and_absolute_form_requests() {
+        assert_eq!(
+            Some(ProxyRequest::Connect {
+                host: "index.crates.io".to_string(),
+                port: 443,
+            }),
+            ProxyRequest::parse(
+                "CONNECT index.crates.io:443 HTTP/1.1\r\nHost: index.crates.io:443\r\n\r\n"
+            )
+        );
+        assert_eq!(
+            Some(ProxyRequest::Connect {
+                host: "::1".to_string(),
+                port: 8443,
+            }),
+            ProxyRequest::parse("CONNECT [::1]:8443 HTTP/1.1\r\n\r\n")
+        );
+        assert_eq!(
+            Some(ProxyRequest::Forward {
+                host: "example.com".to_string(),
+                port: 8080,
+                head: "GET /a?b=1 HTTP/1.1\r\nHost: example.com:8080\r\nConnection: close\r\n\r\n"
+                    .to_string(),
+            }),
+            ProxyRequest::parse(
+                "GET http://user@Example.com:8080/a?b=1 HTTP/1.1\r\nHost: example.com:8080\r\nProxy-Connection: keep-alive\r\n\r\n"
+            )
+        );
+        assert_eq!(None, ProxyRequest::parse("GET /relative HTTP/1.1\r\n\r\n"));
+    }
+
+    #[tokio::test]
+    async fn tunnels_allowed_hosts_and_reports_denied_ones() {
+        let upstream = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream_port = upstream.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = upstream.accept().await.unwrap();
+            let mut buf = [0u8; 4];
+            stream.read_exact(&mut buf).await.unwrap();
+            stream.write_all(&buf).await.unwrap();
+        });
+
+        let proxy =
+            EgressProxy::start(allowlist(&[&format!("127.0.0.1:{upstream_port}")])).unwrap();
+        let mut denials = proxy.subscribe();
+
+        let mut client = UnixStream::connect(proxy.socket_path()).await.unwrap();
+        client
+            .write_all(format!("CONNECT 127.0.0.1:{upstream_port} HTTP/1.1\r\n\r\nping").as_bytes())
+            .await
+            .unwrap();
+        let mut response = vec![0u8; "HTTP/1.1 200 Connection Established\r\n\r\nping".len()];
+        client.read_exact(&mut response).await.unwrap();
+        assert_eq!(
+            "HTTP/1.1 200 Connection Established\r\n\r\nping",
+            String::from_utf8(response).unwrap()
+        );
+
+        let mut client = UnixStream::connect(proxy.socket_path()).await.unwrap();
+        client
+            .write_all(b"CONNECT example.com:443 HTTP/1.1\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 403 Forbidden"), "{response}");
+
+        let denial = denials.recv().await.unwrap();
+        assert_eq!(("example.com", 443), (denial.host.as_str(), denial.port));
+        assert_eq!(Some(std::process::id() as i32), denial.peer_pid);
+    }
+}
@@ -156,6 +156,16 @@ pub enum CodexErr {
     #[error("unsupported operation: {0}")]
     UnsupportedOperation(String),
 
+    /// A blocking `tool.before` or `file.before_write` project hook denied
+    /// the call. The message is meant to be sent back to the model.
+    #[error("{0}")]
+    HookDenied(String),
+
+    /// A `--record-cassette`/`--replay-cassette` file could not be used, or a
+    /// replayed request matched no recorded one. Never retried.
+    #[error("cassette: {0}")]
+    Cassette(String),
+
     // -----------------------------------------------------------------
     // Automatic conversions for common external error types
     // -----------------------------------------------------------------
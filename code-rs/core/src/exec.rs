@@ -20,15 +20,18 @@ use tokio::process::Child;
 use tokio::time::Sleep;
 
 use crate::codex::Session;
+use crate::config_types::ExecLimits;
 use crate::error::CodexErr;
 use crate::error::Result;
 use crate::error::SandboxErr;
 use crate::landlock::spawn_command_under_linux_sandbox;
 use crate::text_encoding::bytes_to_string_smart;
+use crate::protocol::BackgroundEventEvent;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
 use crate::protocol::OrderMeta;
 use crate::protocol::ExecCommandOutputDeltaEvent;
+use crate::protocol::ExecResourceUsage;
 use crate::protocol::ExecOutputStream;
 use crate::protocol::SandboxPolicy;
 use crate::seatbelt::spawn_command_under_seatbelt;
@@ -82,6 +85,59 @@ fn append_with_cap(
     }
 }
 
+/// Appends the connections the egress proxy refused for the sandbox helper
+/// `helper_pid` to the command's stderr, so the model sees them, and reports
+/// each one to the user.
+#[cfg(unix)]
+async fn report_egress_denials(
+    denials: &mut tokio::sync::broadcast::Receiver<crate::egress_proxy::EgressDenial>,
+    helper_pid: Option<u32>,
+    output: &mut RawExecToolCallOutput,
+    stdout_stream: Option<&StdoutStream>,
+) {
+    use tokio::sync::broadcast::error::TryRecvError;
+
+    let mut refused = Vec::new();
+    loop {
+        match denials.try_recv() {
+            Ok(denial) => {
+                let ours = helper_pid.is_some()
+                    && denial.peer_pid.and_then(|pid| u32::try_from(pid).ok()) == helper_pid;
+                if ours && !refused.contains(&denial) {
+                    refused.push(denial);
+                }
+            }
+            Err(TryRecvError::Lagged(_)) => continue,
+            Err(TryRecvError::Empty | TryRecvError::Closed) => break,
+        }
+    }
+
+    for denial in refused {
+        let line = format!("code-linux-sandbox: {denial}\n");
+        output.stderr.text.extend_from_slice(line.as_bytes());
+        output
+            .aggregated_output
+            .text
+            .extend_from_slice(line.as_bytes());
+        if let Some(stream) = stdout_stream {
+            let msg = EventMsg::BackgroundEvent(BackgroundEventEvent {
+                message: format!("Sandbox {denial}"),
+            });
+            let event = if let Some(sess) = &stream.session {
+                sess.make_event(&stream.sub_id, msg)
+            } else {
+                Event {
+                    id: stream.sub_id.clone(),
+                    event_seq: 0,
+                    msg,
+                    order: stream.order.clone(),
+                }
+            };
+            let _ = stream.tx_event.send(event).await;
+        }
+    }
+}
+
 /// Limit the number of ExecCommandOutputDelta events emitted per exec call.
 /// Aggregation still collects full output; only the live event stream is capped.
 pub(crate) const MAX_EXEC_OUTPUT_DELTAS_PER_CALL: usize = 2_048;
@@ -94,13 +150,21 @@ pub struct ExecParams {
     pub env: HashMap<String, String>,
     pub with_escalated_permissions: Option<bool>,
     pub justification: Option<String>,
+    /// Limits from the session's `[exec_limits]` config table.
+    pub exec_limits: ExecLimits,
 }
 
 impl ExecParams {
-    /// Optional timeout for the exec. When `None`, no timeout is enforced and
-    /// the child runs until completion or interruption.
+    /// Optional timeout for the exec, capped by `exec_limits.wall_time_max_secs`.
+    /// When `None`, no timeout is enforced and the child runs until completion
+    /// or interruption.
     pub fn maybe_timeout_duration(&self) -> Option<Duration> {
-        self.timeout_ms.map(Duration::from_millis)
+        let requested = self.timeout_ms.map(Duration::from_millis);
+        let cap = self.exec_limits.wall_time_max_secs.map(Duration::from_secs);
+        match (requested, cap) {
+            (Some(requested), Some(cap)) => Some(requested.min(cap)),
+            (requested, cap) => requested.or(cap),
+        }
     }
 }
 
@@ -155,6 +219,7 @@ pub async fn process_exec_tool_call(
                 command,
                 cwd: command_cwd,
                 env,
+                exec_limits,
                 ..
             } = params;
             let child = spawn_command_under_seatbelt(
@@ -164,6 +229,7 @@ pub async fn process_exec_tool_call(
                 sandbox_cwd,
                 StdioPolicy::RedirectForShellTool,
                 env,
+                Some(&exec_limits),
             )
             .await?;
             consume_truncated_output(child, timeout_duration, stdout_stream.clone()).await
@@ -173,12 +239,18 @@ pub async fn process_exec_tool_call(
                 command,
                 cwd: command_cwd,
                 env,
+                exec_limits,
                 ..
             } = params;
 
             let code_linux_sandbox_exe = code_linux_sandbox_exe
                 .as_ref()
                 .ok_or(CodexErr::LandlockSandboxExecutableNotProvided)?;
+            // Subscribe before spawning so no refused connection is missed.
+            #[cfg(unix)]
+            let mut egress_denials = crate::egress_proxy::proxy_for_policy(sandbox_policy)
+                .await?
+                .map(|proxy| proxy.subscribe());
             let child = spawn_command_under_linux_sandbox(
                 code_linux_sandbox_exe,
                 command,
@@ -187,10 +259,20 @@ pub async fn process_exec_tool_call(
                 sandbox_cwd,
                 StdioPolicy::RedirectForShellTool,
                 env,
+                Some(&exec_limits),
             )
             .await?;
 
-            consume_truncated_output(child, timeout_duration, stdout_stream).await
+            #[cfg(unix)]
+            let helper_pid = child.id();
+            #[allow(unused_mut)]
+            let mut raw_output =
+                consume_truncated_output(child, timeout_duration, stdout_stream.clone()).await;
+            #[cfg(unix)]
+            if let (Some(denials), Ok(output)) = (egress_denials.as_mut(), raw_output.as_mut()) {
+                report_egress_denials(denials, helper_pid, output, stdout_stream.as_ref()).await;
+            }
+            raw_output
         }
     };
     let duration = start.elapsed();
@@ -228,6 +310,7 @@ pub async fn process_exec_tool_call(
                 aggregated_output,
                 duration,
                 timed_out,
+                resource_usage: raw_output.resource_usage,
             };
 
             if timed_out {
@@ -293,6 +376,7 @@ struct RawExecToolCallOutput {
     pub timed_out: bool,
     pub oom_killed: bool,
     pub cgroup_memory_max_bytes: Option<u64>,
+    pub resource_usage: Option<ExecResourceUsage>,
 }
 
 impl StreamOutput<String> {
@@ -323,6 +407,8 @@ pub struct ExecToolCallOutput {
     pub aggregated_output: StreamOutput<String>,
     pub duration: Duration,
     pub timed_out: bool,
+    /// Peak memory and CPU time read from the command's cgroup, when available.
+    pub resource_usage: Option<ExecResourceUsage>,
 }
 
 async fn exec(
@@ -332,7 +418,11 @@ async fn exec(
 ) -> Result<RawExecToolCallOutput> {
     let timeout = params.maybe_timeout_duration();
     let ExecParams {
-        command, cwd, env, ..
+        command,
+        cwd,
+        env,
+        exec_limits,
+        ..
     } = params;
 
     let (program, args) = command.split_first().ok_or_else(|| {
@@ -350,6 +440,7 @@ async fn exec(
         sandbox_policy,
         StdioPolicy::RedirectForShellTool,
         env,
+        Some(&exec_limits),
     )
     .await?;
     consume_truncated_output(child, timeout, stdout_stream).await
@@ -485,6 +576,11 @@ async fn consume_truncated_output(
                                     unsafe { libc::kill(-(pid as i32), libc::SIGKILL); }
                                 }
                             }
+                            // Also catch descendants that left the process group.
+                            #[cfg(target_os = "linux")]
+                            if let Some(pid) = pid {
+                                crate::cgroup::best_effort_kill_exec_cgroup(pid);
+                            }
                             killer.as_mut().start_kill()?;
                             reap_after_kill = true;
                             // Debatable whether `child.wait().await` should be called here.
@@ -564,11 +660,12 @@ async fn consume_truncated_output(
         combined_handle.await.map_err(CodexErr::from)?
     };
 
-    let (oom_killed, cgroup_memory_max_bytes) = {
+    let (oom_killed, cgroup_memory_max_bytes, resource_usage) = {
         #[cfg(target_os = "linux")]
         {
             let mut oom_killed = false;
             let mut cgroup_memory_max_bytes: Option<u64> = None;
+            let resource_usage = pid.and_then(crate::cgroup::exec_cgroup_resource_usage);
             if !timed_out {
                 if let Some(pid) = pid {
                     if matches!(exit_status.signal(), Some(SIGKILL_CODE))
@@ -582,11 +679,11 @@ async fn consume_truncated_output(
             if let Some(pid) = pid {
                 crate::cgroup::best_effort_cleanup_exec_cgroup(pid);
             }
-            (oom_killed, cgroup_memory_max_bytes)
+            (oom_killed, cgroup_memory_max_bytes, resource_usage)
         }
         #[cfg(not(target_os = "linux"))]
         {
-            (false, None)
+            (false, None, None)
         }
     };
 
@@ -598,6 +695,7 @@ async fn consume_truncated_output(
         timed_out,
         oom_killed,
         cgroup_memory_max_bytes,
+        resource_usage,
     })
 }
 
@@ -1,6 +1,6 @@
 use crate::plan_tool::StepStatus;
 use crate::parse_command::ParsedCommand;
-use crate::protocol::{FileChange, RateLimitSnapshotEvent, TokenUsage};
+use crate::protocol::{ExecResourceUsage, FileChange, RateLimitSnapshotEvent, TokenUsage};
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::PathBuf;
@@ -83,6 +83,7 @@ pub enum HistoryDomainEvent {
         status: ExecStatus,
         exit_code: Option<i32>,
         completed_at: Option<SystemTime>,
+        resource_usage: Option<ExecResourceUsage>,
         stdout_tail: Option<String>,
         stderr_tail: Option<String>,
         wait_total: Option<Duration>,
@@ -634,6 +635,8 @@ pub struct ExecRecord {
     pub started_at: SystemTime,
     pub completed_at: Option<SystemTime>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource_usage: Option<ExecResourceUsage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub working_dir: Option<PathBuf>,
     #[serde(default)]
     pub env: Vec<(String, String)>,
@@ -2079,6 +2082,7 @@ impl HistoryState {
                     wait_notes: Vec::new(),
                     started_at,
                     completed_at: None,
+                    resource_usage: None,
                     working_dir,
                     env,
                     tags,
@@ -2094,6 +2098,7 @@ impl HistoryState {
                 status,
                 exit_code,
                 completed_at,
+                resource_usage,
                 wait_total,
                 wait_active,
                 wait_notes,
@@ -2115,6 +2120,7 @@ impl HistoryState {
                         updated.status = status;
                         updated.exit_code = exit_code;
                         updated.completed_at = completed_at;
+                        updated.resource_usage = resource_usage;
                         updated.wait_total = wait_total;
                         updated.wait_active = wait_active;
                         updated.wait_notes = wait_notes;
@@ -2495,6 +2501,7 @@ mod tests {
             status: ExecStatus::Success,
             exit_code: Some(0),
             completed_at: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(5)),
+            resource_usage: None,
             wait_total: Some(Duration::from_secs(2)),
             wait_active: false,
             wait_notes: vec![ExecWaitNote {
@@ -2821,6 +2828,7 @@ mod tests {
             }],
             started_at: SystemTime::UNIX_EPOCH,
             completed_at: Some(SystemTime::UNIX_EPOCH),
+            resource_usage: None,
             working_dir: None,
             env: Vec::new(),
             tags: Vec::new(),
@@ -2876,6 +2884,7 @@ mod tests {
             wait_notes: Vec::new(),
             started_at: SystemTime::UNIX_EPOCH,
             completed_at: Some(SystemTime::UNIX_EPOCH),
+            resource_usage: None,
             working_dir: None,
             env: Vec::new(),
             tags: Vec::new(),
@@ -3053,6 +3062,7 @@ mod tests {
             }],
             started_at: now,
             completed_at: Some(now),
+            resource_usage: None,
             working_dir: None,
             env: Vec::new(),
             tags: Vec::new(),
@@ -1,8 +1,31 @@
-// Use img_hash's re-exported image module for compatibility
-use img_hash::image::{DynamicImage, io::Reader as ImageReader};
+// img_hash bundles an older `image` without codecs; decode with the workspace
+// `image` crate and hand raw pixels over to img_hash's re-exported types.
+use image::{Rgba, RgbaImage};
+use img_hash::image::DynamicImage;
 use img_hash::{HashAlg, HasherConfig, ImageHash};
 use std::path::Path;
 
+/// Per-channel difference below which two pixels count as unchanged
+/// (absorbs antialiasing and compression noise).
+const PIXEL_TOLERANCE: u8 = 16;
+
+/// Colour used to mark changed pixels in diff images.
+const DIFF_COLOR: Rgba<u8> = Rgba([255, 0, 64, 255]);
+
+fn load_rgba<P: AsRef<Path>>(path: P) -> anyhow::Result<RgbaImage> {
+    Ok(image::ImageReader::open(path)?
+        .with_guessed_format()?
+        .decode()?
+        .to_rgba8())
+}
+
+fn to_hash_image(img: &RgbaImage) -> anyhow::Result<DynamicImage> {
+    let (width, height) = img.dimensions();
+    img_hash::image::RgbaImage::from_raw(width, height, img.as_raw().clone())
+        .map(DynamicImage::ImageRgba8)
+        .ok_or_else(|| anyhow::anyhow!("invalid image buffer ({width}x{height})"))
+}
+
 fn phash_256(img: &DynamicImage) -> ImageHash<[u8; 32]> {
     // "Classic" pHash ≈ Mean + DCT, larger hash for sensitivity
     HasherConfig::with_bytes_type::<[u8; 32]>()
@@ -24,7 +47,7 @@ fn dhash_256(img: &DynamicImage) -> ImageHash<[u8; 32]> {
 
 /// Compute a hash for an image that can be stored and compared later
 pub fn compute_image_hash<P: AsRef<Path>>(path: P) -> anyhow::Result<(Vec<u8>, Vec<u8>)> {
-    let img = ImageReader::open(path)?.decode()?;
+    let img = to_hash_image(&load_rgba(path)?)?;
     let phash = phash_256(&img);
     let dhash = dhash_256(&img);
 
@@ -53,3 +76,85 @@ pub fn are_hashes_similar(phash1: &[u8], dhash1: &[u8], phash2: &[u8], dhash2: &
     // 256 bits → ~5% tolerance (≈13 bits)
     phash_dist <= 13 && dhash_dist <= 13
 }
+
+/// Pixel-level comparison of a capture against a baseline image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageDiff {
+    /// Fraction of pixels that match, in `0.0..=1.0`.
+    pub similarity: f64,
+    pub changed_pixels: u64,
+    pub total_pixels: u64,
+    pub baseline_size: (u32, u32),
+    pub current_size: (u32, u32),
+    /// Whether the perceptual hashes consider the images equivalent.
+    pub perceptually_similar: bool,
+}
+
+fn hashes_similar(a: &RgbaImage, b: &RgbaImage) -> anyhow::Result<bool> {
+    let (a, b) = (to_hash_image(a)?, to_hash_image(b)?);
+    let (pa, da) = (phash_256(&a), dhash_256(&a));
+    let (pb, db) = (phash_256(&b), dhash_256(&b));
+    Ok(are_hashes_similar(pa.as_bytes(), da.as_bytes(), pb.as_bytes(), db.as_bytes()))
+}
+
+fn faded(pixel: &Rgba<u8>) -> Rgba<u8> {
+    let [r, g, b, _] = pixel.0;
+    let luma = 0.299 * f64::from(r) + 0.587 * f64::from(g) + 0.114 * f64::from(b);
+    let v = (255.0 - (255.0 - luma) / 4.0).round() as u8;
+    Rgba([v, v, v, 255])
+}
+
+/// Compare `current` against `baseline` and write a PNG diff image to `diff_out`.
+///
+/// Unchanged pixels are drawn as a faded grayscale copy of the baseline and
+/// changed pixels are highlighted. When the sizes differ, the area covered by
+/// only one of the images counts as changed.
+pub fn diff_images<P: AsRef<Path>>(baseline: P, current: P, diff_out: P) -> anyhow::Result<ImageDiff> {
+    let base = load_rgba(baseline)?;
+    let cur = load_rgba(current)?;
+    let (bw, bh) = base.dimensions();
+    let (cw, ch) = cur.dimensions();
+    let (width, height) = (bw.max(cw), bh.max(ch));
+
+    let mut diff = RgbaImage::new(width, height);
+    let mut changed_pixels = 0u64;
+    for y in 0..height {
+        for x in 0..width {
+            let b = (x < bw && y < bh).then(|| base.get_pixel(x, y));
+            let c = (x < cw && y < ch).then(|| cur.get_pixel(x, y));
+            let unchanged = match (b, c) {
+                (Some(b), Some(c)) => b
+                    .0
+                    .iter()
+                    .zip(c.0.iter())
+                    .all(|(l, r)| l.abs_diff(*r) <= PIXEL_TOLERANCE),
+                _ => false,
+            };
+            let pixel = match (unchanged, b) {
+                (true, Some(b)) => faded(b),
+                _ => {
+                    changed_pixels += 1;
+                    DIFF_COLOR
+                }
+            };
+            diff.put_pixel(x, y, pixel);
+        }
+    }
+    diff.save_with_format(diff_out, image::ImageFormat::Png)?;
+
+    let total_pixels = u64::from(width) * u64::from(height);
+    let similarity = if total_pixels == 0 {
+        1.0
+    } else {
+        1.0 - changed_pixels as f64 / total_pixels as f64
+    };
+    let perceptually_similar = (bw, bh) == (cw, ch) && hashes_similar(&base, &cur)?;
+    Ok(ImageDiff {
+        similarity,
+        changed_pixels,
+        total_pixels,
+        baseline_size: (bw, bh),
+        current_size: (cw, ch),
+        perceptually_similar,
+    })
+}
@@ -1,3 +1,4 @@
+use crate::config_types::ExecLimits;
 use crate::protocol::SandboxPolicy;
 use crate::spawn::StdioPolicy;
 use crate::spawn::spawn_child_async;
@@ -21,11 +22,23 @@ pub async fn spawn_command_under_linux_sandbox<P>(
     sandbox_policy_cwd: &Path,
     stdio_policy: StdioPolicy,
     env: HashMap<String, String>,
+    exec_limits: Option<&ExecLimits>,
 ) -> std::io::Result<Child>
 where
     P: AsRef<Path>,
 {
-    let args = create_linux_sandbox_command_args(command, sandbox_policy, sandbox_policy_cwd);
+    #[cfg(unix)]
+    let egress_proxy = crate::egress_proxy::proxy_for_policy(sandbox_policy).await?;
+    #[cfg(unix)]
+    let egress_proxy_socket = egress_proxy.as_ref().map(|proxy| proxy.socket_path());
+    #[cfg(not(unix))]
+    let egress_proxy_socket = None;
+    let args = create_linux_sandbox_command_args(
+        command,
+        sandbox_policy,
+        sandbox_policy_cwd,
+        egress_proxy_socket,
+    );
     let arg0 = Some("codex-linux-sandbox");
     spawn_child_async(
         code_linux_sandbox_exe.as_ref().to_path_buf(),
@@ -35,15 +48,20 @@ where
         sandbox_policy,
         stdio_policy,
         env,
+        exec_limits,
     )
     .await
 }
 
 /// Converts the sandbox policy into the CLI invocation for `codex-linux-sandbox`.
+///
+/// `egress_proxy_socket` is the proxy enforcing the policy's network
+/// allowlist, if it has one.
 fn create_linux_sandbox_command_args(
     command: Vec<String>,
     sandbox_policy: &SandboxPolicy,
     sandbox_policy_cwd: &Path,
+    egress_proxy_socket: Option<&Path>,
 ) -> Vec<String> {
     #[expect(clippy::expect_used)]
     let sandbox_policy_cwd = sandbox_policy_cwd
@@ -55,13 +73,18 @@ fn create_linux_sandbox_command_args(
     let sandbox_policy_json =
         serde_json::to_string(sandbox_policy).expect("Failed to serialize SandboxPolicy to JSON");
 
-    let mut linux_cmd: Vec<String> = vec![
+    let mut linux_cmd: Vec<String> = Vec::new();
+    if let Some(socket) = egress_proxy_socket {
+        linux_cmd.push("--egress-proxy-socket".to_string());
+        linux_cmd.push(socket.to_string_lossy().into_owned());
+    }
+    linux_cmd.extend([
         sandbox_policy_cwd,
         sandbox_policy_json,
         // Separator so that command arguments starting with `-` are not parsed as
         // options of the helper itself.
         "--".to_string(),
-    ];
+    ]);
 
     // Append the original tool command.
     linux_cmd.extend(command);
@@ -15,6 +15,8 @@ mod account_switching;
 pub use account_switching::{RateLimitSwitchState, switch_active_account_on_rate_limit};
 pub mod bash;
 mod auto_drive_pid;
+pub mod cassette;
+mod anthropic_messages;
 mod chat_completions;
 mod client;
 mod client_common;
@@ -50,6 +52,8 @@ pub use environment_context::OperatingSystemInfo;
 pub use environment_context::ViewportDimensions;
 pub use auto_drive_pid::{AutoDriveMode, AutoDrivePidFile};
 pub mod error;
+#[cfg(unix)]
+mod egress_proxy;
 pub mod exec;
 mod exec_command;
 pub mod exec_env;
@@ -59,8 +63,11 @@ pub mod landlock;
 pub mod http_client;
 pub mod housekeeping;
 pub mod mcp_connection_manager;
+mod mcp_resources;
+mod mcp_sampling;
 mod mcp_tool_call;
 mod message_history;
+pub mod model_failover;
 mod model_provider_info;
 pub mod remote_models;
 // Remote model discovery caches its own on-disk state within the module.
@@ -70,6 +77,7 @@ mod agent_tool;
 pub use agent_tool::AGENT_MANAGER;
 mod dry_run_guard;
 mod image_comparison;
+mod visual_regression;
 pub mod git_worktree;
 pub mod slash_commands;
 pub mod parse_command;
@@ -109,6 +117,7 @@ pub mod project_doc;
 pub mod project_features;
 mod rollout;
 pub(crate) mod safety;
+mod sandbox_denial;
 pub mod session_catalog;
 pub mod seatbelt;
 pub mod shell;
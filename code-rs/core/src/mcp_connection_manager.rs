@@ -5,31 +5,68 @@
 //! helpers to query the available tools across *all* servers and returns them
 //! in a single aggregated map using the fully-qualified tool name
 //! `"<server><MCP_TOOL_NAME_DELIMITER><tool>"` as the key.
+//!
+//! Resources, resource templates and prompts are listed once at startup for
+//! servers that advertise the matching capability and are kept per server.
+//!
+//! Elicitation requests from servers are surfaced as
+//! [`EventMsg::ElicitationRequest`] and answered through
+//! [`McpConnectionManager::resolve_elicitation`]. Sampling requests are run
+//! on the session's model for servers allowed by `mcp_sampling`.
 
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::ffi::OsString;
+use std::future::Future;
 use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::Context;
 use anyhow::Result;
 use anyhow::anyhow;
+use async_channel::Sender;
 use code_rmcp_client::RmcpClient;
+use code_rmcp_client::SendElicitation;
+use code_rmcp_client::SendSampling;
+use futures::FutureExt;
 use mcp_types::ClientCapabilities;
+use mcp_types::ElicitRequestParams;
+use mcp_types::ElicitResult;
+use mcp_types::GetPromptRequestParams;
+use mcp_types::GetPromptResult;
 use mcp_types::Implementation;
+use mcp_types::ListPromptsRequestParams;
+use mcp_types::ListResourceTemplatesRequestParams;
+use mcp_types::ListResourceTemplatesResult;
+use mcp_types::ListResourcesRequestParams;
+use mcp_types::ListResourcesResult;
+use mcp_types::Prompt;
+use mcp_types::ReadResourceRequestParams;
+use mcp_types::ReadResourceResult;
+use mcp_types::Resource;
+use mcp_types::ResourceTemplate;
+use mcp_types::ServerCapabilities;
 use mcp_types::Tool;
 
+use serde_json::Value;
 use serde_json::json;
 use sha1::Digest;
 use sha1::Sha1;
+use tokio::sync::Mutex;
 use tokio::sync::RwLock;
+use tokio::sync::oneshot;
 use tokio::task::JoinSet;
 use tracing::info;
 use tracing::warn;
 
+use crate::config_types::McpElicitationPolicy;
 use crate::config_types::McpServerConfig;
 use crate::config_types::McpServerTransportConfig;
+use crate::mcp_sampling::McpSampler;
+use crate::protocol::ElicitationAction;
+use crate::protocol::ElicitationRequestEvent;
+use crate::protocol::Event;
+use crate::protocol::EventMsg;
 
 /// Delimiter used to separate the server name from the tool name in a fully
 /// qualified tool name.
@@ -132,8 +169,104 @@ struct ToolInfo {
     tool: Tool,
 }
 
+/// How requests that MCP servers send to the client are answered. The
+/// default declines elicitation and rejects sampling.
+#[derive(Clone, Default)]
+pub struct McpServerRequestHandlers {
+    /// Policy for `elicitation/create` requests.
+    pub elicitation_policy: McpElicitationPolicy,
+    /// Channel used to ask the user when `elicitation_policy` is `Ask`;
+    /// without it requests are declined.
+    pub tx_event: Option<Sender<Event>>,
+    /// Runs `sampling/createMessage` requests from allowlisted servers.
+    pub(crate) sampler: Option<McpSampler>,
+}
+
+/// Event id used for elicitation requests; they are not tied to a submission.
+const MCP_ELICITATION_EVENT_ID: &str = "mcp_elicitation";
+
+/// Elicitation requests waiting for an answer, keyed by the id sent to the UI.
+#[derive(Clone, Default)]
+struct ElicitationRequests {
+    pending: Arc<Mutex<HashMap<String, oneshot::Sender<ElicitResult>>>>,
+}
+
+impl ElicitationRequests {
+    fn make_sender(
+        &self,
+        server_name: String,
+        policy: McpElicitationPolicy,
+        tx_event: Option<Sender<Event>>,
+    ) -> SendElicitation {
+        let pending = Arc::clone(&self.pending);
+        Arc::new(move |params: ElicitRequestParams| {
+            let pending = Arc::clone(&pending);
+            let server_name = server_name.clone();
+            let tx_event = tx_event.clone();
+            async move {
+                let tx_event = match (policy, tx_event) {
+                    (McpElicitationPolicy::Ask, Some(tx_event)) => tx_event,
+                    (McpElicitationPolicy::Cancel, _) => {
+                        return Ok(elicit_result(ElicitationAction::Cancel, None));
+                    }
+                    _ => return Ok(elicit_result(ElicitationAction::Decline, None)),
+                };
+
+                let id = uuid::Uuid::new_v4().to_string();
+                let (tx, rx) = oneshot::channel();
+                pending.lock().await.insert(id.clone(), tx);
+
+                let event = Event {
+                    id: MCP_ELICITATION_EVENT_ID.to_string(),
+                    event_seq: 0,
+                    msg: EventMsg::ElicitationRequest(ElicitationRequestEvent {
+                        id: id.clone(),
+                        server_name,
+                        message: params.message,
+                        requested_schema: serde_json::to_value(params.requested_schema)?,
+                    }),
+                    order: None,
+                };
+                if tx_event.send(event).await.is_err() {
+                    pending.lock().await.remove(&id);
+                    return Ok(elicit_result(ElicitationAction::Decline, None));
+                }
+
+                rx.await
+                    .context("elicitation request was dropped before it was answered")
+            }
+            .boxed()
+        })
+    }
+
+    async fn resolve(&self, id: &str, result: ElicitResult) -> Result<()> {
+        let responder = self
+            .pending
+            .lock()
+            .await
+            .remove(id)
+            .ok_or_else(|| anyhow!("unknown elicitation request `{id}`"))?;
+        responder
+            .send(result)
+            .map_err(|_| anyhow!("MCP server is no longer waiting for elicitation `{id}`"))
+    }
+}
+
+fn elicit_result(action: ElicitationAction, content: Option<Value>) -> ElicitResult {
+    let (action, content) = match action {
+        ElicitationAction::Accept => ("accept", content),
+        ElicitationAction::Decline => ("decline", None),
+        ElicitationAction::Cancel => ("cancel", None),
+    };
+    ElicitResult {
+        action: action.to_string(),
+        content,
+    }
+}
+
 struct ManagedClient {
     client: McpClientAdapter,
+    capabilities: ServerCapabilities,
     startup_timeout: Duration,
     tool_timeout: Option<Duration>,
 }
@@ -150,13 +283,22 @@ impl McpClientAdapter {
         env: Option<HashMap<String, String>>,
         params: mcp_types::InitializeRequestParams,
         startup_timeout: Duration,
-    ) -> Result<Self> {
+        send_elicitation: SendElicitation,
+        send_sampling: Option<SendSampling>,
+    ) -> Result<(Self, ServerCapabilities)> {
         tracing::debug!(
             "new_stdio_client program: {program:?} args: {args:?} env: {env:?} params: {params:?} startup_timeout: {startup_timeout:?}"
         );
         let client = Arc::new(RmcpClient::new_stdio_client(program, args, env).await?);
-        client.initialize(params, Some(startup_timeout)).await?;
-        Ok(McpClientAdapter::Rmcp(client))
+        let initialized = client
+            .initialize(
+                params,
+                Some(startup_timeout),
+                Some(send_elicitation),
+                send_sampling,
+            )
+            .await?;
+        Ok((McpClientAdapter::Rmcp(client), initialized.capabilities))
     }
 
     async fn new_streamable_http_client(
@@ -164,10 +306,19 @@ impl McpClientAdapter {
         bearer_token: Option<String>,
         params: mcp_types::InitializeRequestParams,
         startup_timeout: Duration,
-    ) -> Result<Self> {
+        send_elicitation: SendElicitation,
+        send_sampling: Option<SendSampling>,
+    ) -> Result<(Self, ServerCapabilities)> {
         let client = Arc::new(RmcpClient::new_streamable_http_client(url, bearer_token)?);
-        client.initialize(params, Some(startup_timeout)).await?;
-        Ok(McpClientAdapter::Rmcp(client))
+        let initialized = client
+            .initialize(
+                params,
+                Some(startup_timeout),
+                Some(send_elicitation),
+                send_sampling,
+            )
+            .await?;
+        Ok((McpClientAdapter::Rmcp(client), initialized.capabilities))
     }
 
     async fn list_tools(
@@ -191,6 +342,58 @@ impl McpClientAdapter {
         }
     }
 
+    async fn list_resources(
+        &self,
+        params: Option<ListResourcesRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListResourcesResult> {
+        match self {
+            McpClientAdapter::Rmcp(client) => client.list_resources(params, timeout).await,
+        }
+    }
+
+    async fn list_resource_templates(
+        &self,
+        params: Option<ListResourceTemplatesRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListResourceTemplatesResult> {
+        match self {
+            McpClientAdapter::Rmcp(client) => {
+                client.list_resource_templates(params, timeout).await
+            }
+        }
+    }
+
+    async fn read_resource(
+        &self,
+        params: ReadResourceRequestParams,
+        timeout: Option<Duration>,
+    ) -> Result<ReadResourceResult> {
+        match self {
+            McpClientAdapter::Rmcp(client) => client.read_resource(params, timeout).await,
+        }
+    }
+
+    async fn list_prompts(
+        &self,
+        params: Option<ListPromptsRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<mcp_types::ListPromptsResult> {
+        match self {
+            McpClientAdapter::Rmcp(client) => client.list_prompts(params, timeout).await,
+        }
+    }
+
+    async fn get_prompt(
+        &self,
+        params: GetPromptRequestParams,
+        timeout: Option<Duration>,
+    ) -> Result<GetPromptResult> {
+        match self {
+            McpClientAdapter::Rmcp(client) => client.get_prompt(params, timeout).await,
+        }
+    }
+
     async fn into_shutdown(self) {
         match self {
             McpClientAdapter::Rmcp(client) => {
@@ -211,6 +414,18 @@ pub struct McpConnectionManager {
 
     /// Fully qualified tool name -> tool instance.
     tools: HashMap<String, ToolInfo>,
+
+    /// Server-name -> resources listed at startup.
+    resources: HashMap<String, Vec<Resource>>,
+
+    /// Server-name -> resource templates listed at startup.
+    resource_templates: HashMap<String, Vec<ResourceTemplate>>,
+
+    /// Server-name -> prompts listed at startup.
+    prompts: HashMap<String, Vec<Prompt>>,
+
+    /// Elicitation requests waiting for the user.
+    elicitation_requests: ElicitationRequests,
 }
 
 impl McpConnectionManager {
@@ -220,11 +435,15 @@ impl McpConnectionManager {
     ///   are human-readable server identifiers and *values* are the spawn
     ///   instructions.
     ///
+    /// * `server_requests` – How elicitation and sampling requests from the
+    ///   servers are answered.
+    ///
     /// Servers that fail to start are reported in `ClientStartErrors`: the
     /// user should be informed about these errors.
     pub async fn new(
         mcp_servers: HashMap<String, McpServerConfig>,
         excluded_tools: HashSet<(String, String)>,
+        server_requests: McpServerRequestHandlers,
     ) -> Result<(Self, ClientStartErrors)> {
         // Early exit if no servers are configured.
         if mcp_servers.is_empty() {
@@ -234,6 +453,7 @@ impl McpConnectionManager {
         // Launch all configured servers concurrently.
         let mut join_set = JoinSet::new();
         let mut errors = ClientStartErrors::new();
+        let elicitation_requests = ElicitationRequests::default();
 
         for (server_name, cfg) in mcp_servers {
             // Validate server name before spawning
@@ -247,6 +467,14 @@ impl McpConnectionManager {
 
             let startup_timeout = cfg.startup_timeout_sec.unwrap_or(DEFAULT_STARTUP_TIMEOUT);
             let tool_timeout = cfg.tool_timeout_sec;
+            let send_elicitation = elicitation_requests.make_sender(
+                server_name.clone(),
+                server_requests.elicitation_policy,
+                server_requests.tx_event.clone(),
+            );
+            let send_sampling = server_requests.sampler.as_ref().and_then(|sampler| {
+                sampler.make_sender(server_name.clone(), Arc::clone(&send_elicitation))
+            });
 
             join_set.spawn(async move {
                 let McpServerConfig { transport, .. } = cfg;
@@ -255,7 +483,8 @@ impl McpConnectionManager {
                     capabilities: ClientCapabilities {
                         experimental: None,
                         roots: None,
-                        sampling: None,
+                        // https://modelcontextprotocol.io/specification/2025-06-18/client/sampling#capabilities
+                        sampling: send_sampling.is_some().then(|| json!({})),
                         // https://modelcontextprotocol.io/specification/2025-06-18/client/elicitation#capabilities
                         // indicates this should be an empty object.
                         elicitation: Some(json!({})),
@@ -284,6 +513,8 @@ impl McpConnectionManager {
                             env,
                             params.clone(),
                             startup_timeout,
+                            send_elicitation,
+                            send_sampling,
                         )
                         .await
                         .with_context(|| {
@@ -306,11 +537,13 @@ impl McpConnectionManager {
                             bearer_token,
                             params,
                             startup_timeout,
+                            send_elicitation,
+                            send_sampling,
                         )
                         .await
                     }
                 }
-                .map(|c| (c, startup_timeout));
+                .map(|(c, capabilities)| (c, capabilities, startup_timeout));
 
                 ((server_name, tool_timeout), client)
             });
@@ -328,11 +561,12 @@ impl McpConnectionManager {
             };
 
             match client_res {
-                Ok((client, startup_timeout)) => {
+                Ok((client, capabilities, startup_timeout)) => {
                     clients.insert(
                         server_name,
                         ManagedClient {
                             client,
+                            capabilities,
                             startup_timeout,
                             tool_timeout,
                         },
@@ -348,9 +582,19 @@ impl McpConnectionManager {
 
         let tools = qualify_tools(all_tools);
 
+        let ServerCatalogs {
+            resources,
+            resource_templates,
+            prompts,
+        } = list_server_catalogs(&clients).await;
+
         Ok((Self {
             clients: RwLock::new(clients),
             tools,
+            resources,
+            resource_templates,
+            prompts,
+            elicitation_requests,
         }, errors))
     }
 
@@ -363,6 +607,41 @@ impl McpConnectionManager {
             .collect()
     }
 
+    /// Answer an elicitation request previously sent as
+    /// [`EventMsg::ElicitationRequest`]. `content` is only forwarded when the
+    /// request is accepted.
+    pub async fn resolve_elicitation(
+        &self,
+        id: &str,
+        action: ElicitationAction,
+        content: Option<Value>,
+    ) -> Result<()> {
+        self.elicitation_requests
+            .resolve(id, elicit_result(action, content))
+            .await
+    }
+
+    /// Resources listed at startup, keyed by server name.
+    pub fn list_all_resources(&self) -> HashMap<String, Vec<Resource>> {
+        self.resources.clone()
+    }
+
+    /// Resource templates listed at startup, keyed by server name.
+    pub fn list_all_resource_templates(&self) -> HashMap<String, Vec<ResourceTemplate>> {
+        self.resource_templates.clone()
+    }
+
+    /// Prompts listed at startup, keyed by server name.
+    pub fn list_all_prompts(&self) -> HashMap<String, Vec<Prompt>> {
+        self.prompts.clone()
+    }
+
+    /// Whether any server published resources or resource templates.
+    pub fn has_resources(&self) -> bool {
+        self.resources.values().any(|list| !list.is_empty())
+            || self.resource_templates.values().any(|list| !list.is_empty())
+    }
+
     /// Invoke the tool indicated by the (server, tool) pair.
     pub async fn call_tool(
         &self,
@@ -371,14 +650,8 @@ impl McpConnectionManager {
         arguments: Option<serde_json::Value>,
         timeout_override: Option<Duration>,
     ) -> Result<mcp_types::CallToolResult> {
-        let (client, timeout) = {
-            let clients = self.clients.read().await;
-            let managed = clients
-                .get(server)
-                .ok_or_else(|| anyhow!("unknown MCP server '{server}'"))?;
-            let timeout = timeout_override.or(managed.tool_timeout);
-            (managed.client.clone(), timeout)
-        };
+        let (client, timeout) = self.client(server).await?;
+        let timeout = timeout_override.or(timeout);
 
         client
             .call_tool(tool.to_string(), arguments, timeout)
@@ -386,6 +659,70 @@ impl McpConnectionManager {
             .with_context(|| format!("tool call failed for `{server}/{tool}`"))
     }
 
+    /// Fetch one page of `resources/list` from `server`.
+    pub async fn list_resources(
+        &self,
+        server: &str,
+        params: Option<ListResourcesRequestParams>,
+    ) -> Result<ListResourcesResult> {
+        let (client, timeout) = self.client(server).await?;
+        client
+            .list_resources(params, timeout)
+            .await
+            .with_context(|| format!("resources/list failed for `{server}`"))
+    }
+
+    /// Fetch one page of `resources/templates/list` from `server`.
+    pub async fn list_resource_templates(
+        &self,
+        server: &str,
+        params: Option<ListResourceTemplatesRequestParams>,
+    ) -> Result<ListResourceTemplatesResult> {
+        let (client, timeout) = self.client(server).await?;
+        client
+            .list_resource_templates(params, timeout)
+            .await
+            .with_context(|| format!("resources/templates/list failed for `{server}`"))
+    }
+
+    /// Read the resource at `uri` from `server`.
+    pub async fn read_resource(&self, server: &str, uri: &str) -> Result<ReadResourceResult> {
+        let (client, timeout) = self.client(server).await?;
+        let params = ReadResourceRequestParams {
+            uri: uri.to_string(),
+        };
+        client
+            .read_resource(params, timeout)
+            .await
+            .with_context(|| format!("resources/read failed for `{server}` ({uri})"))
+    }
+
+    /// Render the prompt `name` published by `server` with `arguments`.
+    pub async fn get_prompt(
+        &self,
+        server: &str,
+        name: &str,
+        arguments: Option<serde_json::Value>,
+    ) -> Result<GetPromptResult> {
+        let (client, timeout) = self.client(server).await?;
+        let params = GetPromptRequestParams {
+            arguments,
+            name: name.to_string(),
+        };
+        client
+            .get_prompt(params, timeout)
+            .await
+            .with_context(|| format!("prompts/get failed for `{server}/{name}`"))
+    }
+
+    async fn client(&self, server: &str) -> Result<(McpClientAdapter, Option<Duration>)> {
+        let clients = self.clients.read().await;
+        let managed = clients
+            .get(server)
+            .ok_or_else(|| anyhow!("unknown MCP server '{server}'"))?;
+        Ok((managed.client.clone(), managed.tool_timeout))
+    }
+
     pub fn parse_tool_name(&self, tool_name: &str) -> Option<(String, String)> {
         self.tools
             .get(tool_name)
@@ -474,6 +811,136 @@ async fn list_all_tools(
     aggregated
 }
 
+#[derive(Default)]
+struct ServerCatalogs {
+    resources: HashMap<String, Vec<Resource>>,
+    resource_templates: HashMap<String, Vec<ResourceTemplate>>,
+    prompts: HashMap<String, Vec<Prompt>>,
+}
+
+/// Query every server that advertises resources or prompts and collect the
+/// full (paginated) listings. Failures are logged and leave that server's
+/// listing empty; they never prevent the server's tools from being used.
+async fn list_server_catalogs(clients: &HashMap<String, ManagedClient>) -> ServerCatalogs {
+    let mut join_set = JoinSet::new();
+
+    for (server_name, managed_client) in clients {
+        let wants_resources = managed_client.capabilities.resources.is_some();
+        let wants_prompts = managed_client.capabilities.prompts.is_some();
+        if !wants_resources && !wants_prompts {
+            continue;
+        }
+
+        let server_name = server_name.clone();
+        let client = managed_client.client.clone();
+        let timeout = Some(managed_client.startup_timeout);
+        join_set.spawn(async move {
+            let mut resources = Ok(Vec::new());
+            let mut templates = Ok(Vec::new());
+            let mut prompts = Ok(Vec::new());
+            if wants_resources {
+                resources = collect_pages(|cursor| {
+                    let client = client.clone();
+                    async move {
+                        let params = cursor.map(|cursor| ListResourcesRequestParams {
+                            cursor: Some(cursor),
+                        });
+                        let page = client.list_resources(params, timeout).await?;
+                        Ok((page.resources, page.next_cursor))
+                    }
+                })
+                .await;
+                templates = collect_pages(|cursor| {
+                    let client = client.clone();
+                    async move {
+                        let params = cursor.map(|cursor| ListResourceTemplatesRequestParams {
+                            cursor: Some(cursor),
+                        });
+                        let page = client.list_resource_templates(params, timeout).await?;
+                        Ok((page.resource_templates, page.next_cursor))
+                    }
+                })
+                .await;
+            }
+            if wants_prompts {
+                prompts = collect_pages(|cursor| {
+                    let client = client.clone();
+                    async move {
+                        let params = cursor.map(|cursor| ListPromptsRequestParams {
+                            cursor: Some(cursor),
+                        });
+                        let page = client.list_prompts(params, timeout).await?;
+                        Ok((page.prompts, page.next_cursor))
+                    }
+                })
+                .await;
+            }
+            (server_name, resources, templates, prompts)
+        });
+    }
+
+    let mut catalogs = ServerCatalogs::default();
+    while let Some(join_res) = join_set.join_next().await {
+        let (server_name, resources, templates, prompts) = match join_res {
+            Ok(result) => result,
+            Err(err) => {
+                warn!("Task panic when listing resources for MCP server: {err:#}");
+                continue;
+            }
+        };
+
+        match resources {
+            Ok(list) if !list.is_empty() => {
+                catalogs.resources.insert(server_name.clone(), list);
+            }
+            Ok(_) => {}
+            Err(err) => warn!("Failed to list resources for MCP server '{server_name}': {err:#}"),
+        }
+        match templates {
+            Ok(list) if !list.is_empty() => {
+                catalogs.resource_templates.insert(server_name.clone(), list);
+            }
+            Ok(_) => {}
+            Err(err) => warn!(
+                "Failed to list resource templates for MCP server '{server_name}': {err:#}"
+            ),
+        }
+        match prompts {
+            Ok(list) if !list.is_empty() => {
+                catalogs.prompts.insert(server_name, list);
+            }
+            Ok(_) => {}
+            Err(err) => warn!("Failed to list prompts for MCP server '{server_name}': {err:#}"),
+        }
+    }
+
+    catalogs
+}
+
+/// Follow `nextCursor` until the server stops returning one.
+async fn collect_pages<T, F, Fut>(mut fetch: F) -> Result<Vec<T>>
+where
+    F: FnMut(Option<String>) -> Fut,
+    Fut: Future<Output = Result<(Vec<T>, Option<String>)>>,
+{
+    let mut collected = Vec::new();
+    let mut seen_cursors = HashSet::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let (items, next_cursor) = fetch(cursor.take()).await?;
+        collected.extend(items);
+        match next_cursor {
+            Some(next) => {
+                if !seen_cursors.insert(next.clone()) {
+                    return Err(anyhow!("server returned a duplicate cursor"));
+                }
+                cursor = Some(next);
+            }
+            None => return Ok(collected),
+        }
+    }
+}
+
 fn is_valid_mcp_server_name(server_name: &str) -> bool {
     !server_name.is_empty()
         && server_name
@@ -590,6 +1057,88 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn collect_pages_follows_cursors_and_rejects_loops() {
+        let pages = collect_pages(|cursor| async move {
+            Ok(match cursor.as_deref() {
+                None => (vec![1, 2], Some("a".to_string())),
+                Some("a") => (vec![3], Some("b".to_string())),
+                _ => (vec![4], None),
+            })
+        })
+        .await
+        .expect("pages");
+        assert_eq!(pages, vec![1, 2, 3, 4]);
+
+        let looped = collect_pages(|_cursor| async move {
+            Ok((vec![0], Some("same".to_string())))
+        })
+        .await;
+        assert!(looped.is_err());
+    }
+
+    #[tokio::test]
+    async fn elicitation_requests_round_trip_through_events() {
+        let requests = ElicitationRequests::default();
+        let (tx_event, rx_event) = async_channel::unbounded();
+        let send = requests.make_sender(
+            "otp".to_string(),
+            McpElicitationPolicy::Ask,
+            Some(tx_event),
+        );
+        let params = ElicitRequestParams {
+            message: "Enter the code".to_string(),
+            requested_schema: mcp_types::ElicitRequestParamsRequestedSchema {
+                properties: json!({ "code": { "type": "string" } }),
+                required: Some(vec!["code".to_string()]),
+                r#type: "object".to_string(),
+            },
+        };
+        let pending = tokio::spawn(send(params));
+
+        let event = rx_event.recv().await.expect("elicitation event");
+        let EventMsg::ElicitationRequest(request) = event.msg else {
+            panic!("unexpected event: {:?}", event.msg);
+        };
+        assert_eq!(request.server_name, "otp");
+        requests
+            .resolve(
+                &request.id,
+                elicit_result(ElicitationAction::Accept, Some(json!({ "code": "123456" }))),
+            )
+            .await
+            .expect("resolve");
+
+        let result = pending.await.expect("join").expect("result");
+        assert_eq!(result.action, "accept");
+        assert_eq!(result.content, Some(json!({ "code": "123456" })));
+        let again = requests
+            .resolve(&request.id, elicit_result(ElicitationAction::Cancel, None))
+            .await;
+        assert!(again.is_err(), "a request can only be answered once");
+    }
+
+    #[tokio::test]
+    async fn non_interactive_elicitation_policy_answers_immediately() {
+        let requests = ElicitationRequests::default();
+        let params = ElicitRequestParams {
+            message: "Confirm?".to_string(),
+            requested_schema: mcp_types::ElicitRequestParamsRequestedSchema {
+                properties: json!({}),
+                required: None,
+                r#type: "object".to_string(),
+            },
+        };
+
+        let cancel = requests.make_sender("s".to_string(), McpElicitationPolicy::Cancel, None);
+        let result = cancel(params.clone()).await.expect("result");
+        assert_eq!(result.action, "cancel");
+
+        let decline = requests.make_sender("s".to_string(), McpElicitationPolicy::Ask, None);
+        let result = decline(params).await.expect("result");
+        assert_eq!(result.action, "decline");
+    }
+
     #[tokio::test]
     async fn stdio_spawn_error_mentions_server_and_command() {
         let mut servers = HashMap::new();
@@ -606,9 +1155,13 @@ mod tests {
             },
         );
 
-        let (_manager, errors) = McpConnectionManager::new(servers, HashSet::new())
-            .await
-            .expect("manager creation should succeed even when servers fail");
+        let (_manager, errors) = McpConnectionManager::new(
+            servers,
+            HashSet::new(),
+            McpServerRequestHandlers::default(),
+        )
+        .await
+        .expect("manager creation should succeed even when servers fail");
 
         let err = errors
             .get("context7-mcp")
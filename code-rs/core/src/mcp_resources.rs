@@ -0,0 +1,747 @@
+//! MCP resources and prompts as seen by the model and the composer.
+//!
+//! Resources are exposed to the model through three read-only function tools
+//! (`list_mcp_resources`, `list_mcp_resource_templates`, `read_mcp_resource`)
+//! and can be attached to a user message with an `@server:resource` mention,
+//! where `resource` is either the resource name or its URI. Server prompts are
+//! invoked as `/server:prompt key=value …` and expanded here before the turn
+//! starts.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::time::Instant;
+
+use mcp_types::CallToolResult;
+use mcp_types::ContentBlock;
+use mcp_types::EmbeddedResourceResource;
+use mcp_types::ListResourceTemplatesRequestParams;
+use mcp_types::ListResourcesRequestParams;
+use mcp_types::Prompt;
+use mcp_types::ReadResourceResultContents;
+use mcp_types::Resource;
+use mcp_types::ResourceTemplate;
+use mcp_types::TextContent;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+use serde_json::json;
+use tracing::warn;
+
+use crate::codex::Session;
+use crate::codex::ToolCallCtx;
+use crate::mcp_connection_manager::McpConnectionManager;
+use crate::protocol::EventMsg;
+use crate::protocol::InputItem;
+use crate::protocol::McpInvocation;
+use crate::protocol::McpResourceInfo;
+use crate::protocol::McpToolCallBeginEvent;
+use crate::protocol::McpToolCallEndEvent;
+use crate::truncate::truncate_middle;
+use code_protocol::custom_prompts::McpPrompt;
+use code_protocol::custom_prompts::McpPromptArgument;
+use code_protocol::models::FunctionCallOutputPayload;
+use code_protocol::models::ResponseInputItem;
+
+pub(crate) const LIST_MCP_RESOURCES_TOOL_NAME: &str = "list_mcp_resources";
+pub(crate) const LIST_MCP_RESOURCE_TEMPLATES_TOOL_NAME: &str = "list_mcp_resource_templates";
+pub(crate) const READ_MCP_RESOURCE_TOOL_NAME: &str = "read_mcp_resource";
+
+/// Server label used in tool-call events when the model did not target a
+/// specific server.
+const ALL_SERVERS_LABEL: &str = "mcp";
+
+/// Text contents returned by `read_mcp_resource` are truncated to this size.
+const READ_RESOURCE_TEXT_MAX_BYTES: usize = 32 * 1024; // 32 KiB
+
+#[derive(Debug, Deserialize, Default)]
+struct ListArgs {
+    #[serde(default)]
+    server: Option<String>,
+    #[serde(default)]
+    cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReadArgs {
+    server: String,
+    uri: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ResourceWithServer {
+    server: String,
+    #[serde(flatten)]
+    resource: Resource,
+}
+
+#[derive(Debug, Serialize)]
+struct ResourceTemplateWithServer {
+    server: String,
+    #[serde(flatten)]
+    template: ResourceTemplate,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ListResourcesPayload {
+    resources: Vec<ResourceWithServer>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ListResourceTemplatesPayload {
+    resource_templates: Vec<ResourceTemplateWithServer>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next_cursor: Option<String>,
+}
+
+/// Handles one of the MCP resource tools and dispatches the matching
+/// `McpToolCallBegin` / `McpToolCallEnd` events.
+pub(crate) async fn handle_mcp_resource_tool(
+    sess: &Session,
+    ctx: &ToolCallCtx,
+    tool_name: &str,
+    arguments: String,
+) -> ResponseInputItem {
+    let arguments_value = if arguments.trim().is_empty() {
+        None
+    } else {
+        match serde_json::from_str::<Value>(&arguments) {
+            Ok(value) => Some(value),
+            Err(err) => {
+                return function_output(
+                    ctx,
+                    format!("failed to parse function arguments: {err}"),
+                    false,
+                );
+            }
+        }
+    };
+
+    let server_label = arguments_value
+        .as_ref()
+        .and_then(|value| value.get("server"))
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|server| !server.is_empty())
+        .unwrap_or(ALL_SERVERS_LABEL)
+        .to_string();
+    let invocation = McpInvocation {
+        server: server_label,
+        tool: tool_name.to_string(),
+        arguments: arguments_value.clone(),
+    };
+    sess.send_ordered_from_ctx(
+        ctx,
+        EventMsg::McpToolCallBegin(McpToolCallBeginEvent {
+            call_id: ctx.call_id.clone(),
+            invocation: invocation.clone(),
+        }),
+    )
+    .await;
+
+    let start = Instant::now();
+    let manager = sess.mcp_connection_manager();
+    let payload = match tool_name {
+        LIST_MCP_RESOURCES_TOOL_NAME => list_resources(manager, arguments_value).await,
+        LIST_MCP_RESOURCE_TEMPLATES_TOOL_NAME => {
+            list_resource_templates(manager, arguments_value).await
+        }
+        READ_MCP_RESOURCE_TOOL_NAME => read_resource(manager, arguments_value).await,
+        other => Err(format!("unsupported MCP resource tool: {other}")),
+    };
+    let payload = payload.and_then(|value| {
+        serde_json::to_string(&value)
+            .map_err(|err| format!("failed to serialize MCP resource response: {err}"))
+    });
+
+    let result = payload.clone().map(|text| CallToolResult {
+        content: vec![ContentBlock::TextContent(TextContent {
+            annotations: None,
+            text,
+            r#type: "text".to_string(),
+        })],
+        is_error: Some(false),
+        structured_content: None,
+    });
+    sess.send_ordered_from_ctx(
+        ctx,
+        EventMsg::McpToolCallEnd(McpToolCallEndEvent {
+            call_id: ctx.call_id.clone(),
+            invocation,
+            duration: start.elapsed(),
+            result,
+        }),
+    )
+    .await;
+
+    match payload {
+        Ok(content) => function_output(ctx, content, true),
+        Err(message) => function_output(ctx, message, false),
+    }
+}
+
+fn function_output(ctx: &ToolCallCtx, content: String, success: bool) -> ResponseInputItem {
+    ResponseInputItem::FunctionCallOutput {
+        call_id: ctx.call_id.clone(),
+        output: FunctionCallOutputPayload {
+            content,
+            success: Some(success),
+        },
+    }
+}
+
+fn parse_list_args(arguments: Option<Value>) -> Result<(Option<String>, Option<String>), String> {
+    let ListArgs { server, cursor } = match arguments {
+        Some(value) => serde_json::from_value(value)
+            .map_err(|err| format!("failed to parse function arguments: {err}"))?,
+        None => ListArgs::default(),
+    };
+    let server = non_empty(server);
+    let cursor = non_empty(cursor);
+    if cursor.is_some() && server.is_none() {
+        return Err("cursor can only be used when a server is specified".to_string());
+    }
+    Ok((server, cursor))
+}
+
+async fn list_resources(
+    manager: &McpConnectionManager,
+    arguments: Option<Value>,
+) -> Result<Value, String> {
+    let (server, cursor) = parse_list_args(arguments)?;
+    let payload = match server {
+        Some(server) => {
+            let params = cursor.map(|cursor| ListResourcesRequestParams {
+                cursor: Some(cursor),
+            });
+            let page = manager
+                .list_resources(&server, params)
+                .await
+                .map_err(|err| format!("{err:#}"))?;
+            ListResourcesPayload {
+                resources: page
+                    .resources
+                    .into_iter()
+                    .map(|resource| ResourceWithServer {
+                        server: server.clone(),
+                        resource,
+                    })
+                    .collect(),
+                next_cursor: page.next_cursor,
+            }
+        }
+        None => ListResourcesPayload {
+            resources: sorted_by_server(manager.list_all_resources())
+                .into_iter()
+                .map(|(server, resource)| ResourceWithServer { server, resource })
+                .collect(),
+            next_cursor: None,
+        },
+    };
+    serde_json::to_value(payload).map_err(|err| err.to_string())
+}
+
+async fn list_resource_templates(
+    manager: &McpConnectionManager,
+    arguments: Option<Value>,
+) -> Result<Value, String> {
+    let (server, cursor) = parse_list_args(arguments)?;
+    let payload = match server {
+        Some(server) => {
+            let params = cursor.map(|cursor| ListResourceTemplatesRequestParams {
+                cursor: Some(cursor),
+            });
+            let page = manager
+                .list_resource_templates(&server, params)
+                .await
+                .map_err(|err| format!("{err:#}"))?;
+            ListResourceTemplatesPayload {
+                resource_templates: page
+                    .resource_templates
+                    .into_iter()
+                    .map(|template| ResourceTemplateWithServer {
+                        server: server.clone(),
+                        template,
+                    })
+                    .collect(),
+                next_cursor: page.next_cursor,
+            }
+        }
+        None => ListResourceTemplatesPayload {
+            resource_templates: sorted_by_server(manager.list_all_resource_templates())
+                .into_iter()
+                .map(|(server, template)| ResourceTemplateWithServer { server, template })
+                .collect(),
+            next_cursor: None,
+        },
+    };
+    serde_json::to_value(payload).map_err(|err| err.to_string())
+}
+
+async fn read_resource(
+    manager: &McpConnectionManager,
+    arguments: Option<Value>,
+) -> Result<Value, String> {
+    let ReadArgs { server, uri } = arguments
+        .ok_or_else(|| "server and uri must be provided".to_string())
+        .and_then(|value| {
+            serde_json::from_value(value)
+                .map_err(|err| format!("failed to parse function arguments: {err}"))
+        })?;
+    let server = non_empty(Some(server)).ok_or_else(|| "server must be provided".to_string())?;
+    let uri = non_empty(Some(uri)).ok_or_else(|| "uri must be provided".to_string())?;
+    let result = manager
+        .read_resource(&server, &uri)
+        .await
+        .map_err(|err| format!("{err:#}"))?;
+    let contents: Vec<Value> = result
+        .contents
+        .into_iter()
+        .map(resource_contents_value)
+        .collect();
+    Ok(json!({
+        "server": server,
+        "uri": uri,
+        "contents": contents,
+    }))
+}
+
+/// Model-facing form of one resource entry: text is truncated and binary
+/// content is replaced by its mime type and size.
+fn resource_contents_value(contents: ReadResourceResultContents) -> Value {
+    match contents {
+        ReadResourceResultContents::TextResourceContents(text) => {
+            let (truncated, original_tokens) =
+                truncate_middle(&text.text, READ_RESOURCE_TEXT_MAX_BYTES);
+            let mut value = json!({
+                "uri": text.uri,
+                "mimeType": text.mime_type,
+                "text": truncated,
+            });
+            if original_tokens.is_some() {
+                value["truncated"] = Value::Bool(true);
+            }
+            value
+        }
+        ReadResourceResultContents::BlobResourceContents(blob) => json!({
+            "uri": blob.uri,
+            "mimeType": blob.mime_type.as_deref().unwrap_or("application/octet-stream"),
+            "size": base64_decoded_len(&blob.blob),
+            "omitted": "binary content",
+        }),
+    }
+}
+
+/// Number of bytes encoded by a base64 string, without decoding it.
+fn base64_decoded_len(encoded: &str) -> usize {
+    let encoded = encoded.trim_end();
+    let padding = encoded.bytes().rev().take_while(|b| *b == b'=').count();
+    (encoded.len() / 4 * 3).saturating_sub(padding)
+}
+
+fn sorted_by_server<T>(by_server: HashMap<String, Vec<T>>) -> Vec<(String, T)> {
+    let mut entries: Vec<(String, Vec<T>)> = by_server.into_iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    entries
+        .into_iter()
+        .flat_map(|(server, items)| items.into_iter().map(move |item| (server.clone(), item)))
+        .collect()
+}
+
+/// Prompts from every connected server, in the shape front-ends use to build
+/// `/server:prompt` commands.
+pub(crate) fn mcp_prompt_infos(manager: &McpConnectionManager) -> Vec<McpPrompt> {
+    let mut prompts: Vec<McpPrompt> = sorted_by_server(manager.list_all_prompts())
+        .into_iter()
+        .map(|(server, prompt)| McpPrompt {
+            server,
+            name: prompt.name,
+            description: prompt.description.or(prompt.title),
+            arguments: prompt
+                .arguments
+                .unwrap_or_default()
+                .into_iter()
+                .map(|argument| McpPromptArgument {
+                    name: argument.name,
+                    description: argument.description,
+                    required: argument.required.unwrap_or(false),
+                })
+                .collect(),
+        })
+        .collect();
+    prompts.sort_by(|a, b| a.server.cmp(&b.server).then_with(|| a.name.cmp(&b.name)));
+    prompts
+}
+
+/// Resources from every connected server, used for `@server:resource`
+/// completion in front-ends.
+pub(crate) fn mcp_resource_infos(manager: &McpConnectionManager) -> Vec<McpResourceInfo> {
+    sorted_by_server(manager.list_all_resources())
+        .into_iter()
+        .map(|(server, resource)| McpResourceInfo {
+            server,
+            uri: resource.uri,
+            name: resource.name,
+            description: resource.description.or(resource.title),
+            mime_type: resource.mime_type,
+        })
+        .collect()
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Expands a leading `/server:prompt` invocation and attaches any
+/// `@server:resource` mentions before the input reaches the model.
+///
+/// Returns an error message suitable for the user when a prompt invocation is
+/// malformed or the server rejects it.
+pub(crate) async fn expand_mcp_input(
+    manager: &McpConnectionManager,
+    mut items: Vec<InputItem>,
+) -> Result<Vec<InputItem>, String> {
+    if let Some(InputItem::Text { text }) = items.first()
+        && let Some(invocation) = parse_prompt_invocation(text, &manager.list_all_prompts())?
+    {
+        let tail = items.split_off(1);
+        items = render_prompt(manager, invocation).await?;
+        items.extend(tail);
+    }
+
+    let resources = manager.list_all_resources();
+    if resources.is_empty() {
+        return Ok(items);
+    }
+    let mentions = items
+        .iter()
+        .filter_map(|item| match item {
+            InputItem::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .flat_map(|text| find_resource_mentions(text, &resources))
+        .collect::<Vec<_>>();
+
+    let mut seen = HashSet::new();
+    for (server, uri) in mentions {
+        if !seen.insert((server.clone(), uri.clone())) {
+            continue;
+        }
+        match manager.read_resource(&server, &uri).await {
+            Ok(result) => {
+                for contents in result.contents {
+                    items.push(resource_contents_item(&server, contents));
+                }
+            }
+            Err(err) => {
+                warn!("failed to attach MCP resource {server}:{uri}: {err:#}");
+                items.push(InputItem::Text {
+                    text: format!(
+                        "<mcp_resource server=\"{server}\" uri=\"{uri}\" error=\"{err}\" />"
+                    ),
+                });
+            }
+        }
+    }
+    Ok(items)
+}
+
+/// Returns `(server, uri)` for every `@server:resource` token in `text` that
+/// names a known resource by name or URI.
+fn find_resource_mentions(
+    text: &str,
+    resources: &HashMap<String, Vec<Resource>>,
+) -> Vec<(String, String)> {
+    text.split_whitespace()
+        .filter_map(|token| token.strip_prefix('@'))
+        .map(|token| token.trim_end_matches([',', ';', '.', ')', '!', '?']))
+        .filter_map(|token| {
+            let (server, key) = token.split_once(':')?;
+            let resource = resources
+                .get(server)?
+                .iter()
+                .find(|resource| resource.uri == key || resource.name == key)?;
+            Some((server.to_string(), resource.uri.clone()))
+        })
+        .collect()
+}
+
+fn resource_contents_item(server: &str, contents: ReadResourceResultContents) -> InputItem {
+    match contents {
+        ReadResourceResultContents::TextResourceContents(text) => InputItem::Text {
+            text: format!(
+                "<mcp_resource server=\"{server}\" uri=\"{}\">\n{}\n</mcp_resource>",
+                text.uri, text.text
+            ),
+        },
+        ReadResourceResultContents::BlobResourceContents(blob) => match blob.mime_type.as_deref() {
+            Some(mime) if mime.starts_with("image/") => InputItem::Image {
+                image_url: format!("data:{mime};base64,{}", blob.blob),
+            },
+            mime => InputItem::Text {
+                text: format!(
+                    "<mcp_resource server=\"{server}\" uri=\"{}\" mime_type=\"{}\" omitted=\"binary content\" />",
+                    blob.uri,
+                    mime.unwrap_or("application/octet-stream")
+                ),
+            },
+        },
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct PromptInvocation {
+    server: String,
+    prompt: String,
+    arguments: serde_json::Map<String, Value>,
+    rest: String,
+}
+
+/// Recognizes `/server:prompt [key=value …]` on the first line of `text`.
+/// Returns `Ok(None)` when the text does not name a published prompt.
+fn parse_prompt_invocation(
+    text: &str,
+    prompts: &HashMap<String, Vec<Prompt>>,
+) -> Result<Option<PromptInvocation>, String> {
+    let Some(stripped) = text.trim_start().strip_prefix('/') else {
+        return Ok(None);
+    };
+    let (first_line, rest) = stripped.split_once('\n').unwrap_or((stripped, ""));
+    let (head, args) = first_line
+        .split_once(char::is_whitespace)
+        .unwrap_or((first_line, ""));
+    let Some((server, prompt_name)) = head.split_once(':') else {
+        return Ok(None);
+    };
+    let Some(prompt) = prompts
+        .get(server)
+        .and_then(|list| list.iter().find(|prompt| prompt.name == prompt_name))
+    else {
+        return Ok(None);
+    };
+
+    let command = format!("/{head}");
+    let declared = prompt.arguments.as_deref().unwrap_or_default();
+    let args = args.trim();
+    let mut arguments = serde_json::Map::new();
+    if !args.is_empty() {
+        let tokens = shlex::split(args)
+            .ok_or_else(|| format!("Could not parse arguments for {command}: unbalanced quotes"))?;
+        if declared.len() == 1 && !tokens.iter().any(|token| token.contains('=')) {
+            // A single declared argument may be passed positionally.
+            arguments.insert(declared[0].name.clone(), Value::String(tokens.join(" ")));
+        } else {
+            for token in tokens {
+                let Some((key, value)) = token.split_once('=') else {
+                    return Err(format!(
+                        "Could not parse arguments for {command}: expected key=value but found '{token}'"
+                    ));
+                };
+                arguments.insert(key.to_string(), Value::String(value.to_string()));
+            }
+        }
+    }
+
+    let missing: Vec<&str> = declared
+        .iter()
+        .filter(|arg| arg.required == Some(true) && !arguments.contains_key(&arg.name))
+        .map(|arg| arg.name.as_str())
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!(
+            "Missing required arguments for {command}: {}",
+            missing.join(", ")
+        ));
+    }
+
+    Ok(Some(PromptInvocation {
+        server: server.to_string(),
+        prompt: prompt_name.to_string(),
+        arguments,
+        rest: rest.trim().to_string(),
+    }))
+}
+
+async fn render_prompt(
+    manager: &McpConnectionManager,
+    invocation: PromptInvocation,
+) -> Result<Vec<InputItem>, String> {
+    let PromptInvocation {
+        server,
+        prompt,
+        arguments,
+        rest,
+    } = invocation;
+    let arguments = (!arguments.is_empty()).then_some(Value::Object(arguments));
+    let result = manager
+        .get_prompt(&server, &prompt, arguments)
+        .await
+        .map_err(|err| format!("/{server}:{prompt} failed: {err:#}"))?;
+
+    let mut texts = Vec::new();
+    let mut images = Vec::new();
+    for message in result.messages {
+        match message.content {
+            ContentBlock::TextContent(content) => texts.push(content.text),
+            ContentBlock::ImageContent(image) => images.push(InputItem::Image {
+                image_url: format!("data:{};base64,{}", image.mime_type, image.data),
+            }),
+            ContentBlock::EmbeddedResource(embedded) => match embedded.resource {
+                EmbeddedResourceResource::TextResourceContents(text) => texts.push(text.text),
+                EmbeddedResourceResource::BlobResourceContents(_) => {}
+            },
+            ContentBlock::ResourceLink(link) => texts.push(link.uri),
+            ContentBlock::AudioContent(_) => {}
+        }
+    }
+    if !rest.is_empty() {
+        texts.push(rest);
+    }
+    if texts.is_empty() && images.is_empty() {
+        return Err(format!("/{server}:{prompt} returned no content"));
+    }
+
+    let mut items = vec![InputItem::Text {
+        text: texts.join("\n\n"),
+    }];
+    items.extend(images);
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_types::BlobResourceContents;
+    use mcp_types::PromptArgument;
+    use mcp_types::TextResourceContents;
+    use pretty_assertions::assert_eq;
+
+    fn resource(uri: &str, name: &str) -> Resource {
+        Resource {
+            annotations: None,
+            description: None,
+            mime_type: None,
+            name: name.to_string(),
+            size: None,
+            title: None,
+            uri: uri.to_string(),
+        }
+    }
+
+    fn prompt(name: &str, args: &[(&str, bool)]) -> Prompt {
+        Prompt {
+            arguments: Some(
+                args.iter()
+                    .map(|(name, required)| PromptArgument {
+                        description: None,
+                        name: (*name).to_string(),
+                        required: Some(*required),
+                        title: None,
+                    })
+                    .collect(),
+            ),
+            description: None,
+            name: name.to_string(),
+            title: None,
+        }
+    }
+
+    #[test]
+    fn read_resource_output_omits_blobs_and_truncates_text() {
+        let blob = resource_contents_value(ReadResourceResultContents::BlobResourceContents(
+            BlobResourceContents {
+                blob: "aGVsbG8gd29ybGQ=".to_string(),
+                mime_type: Some("application/pdf".to_string()),
+                uri: "file:///report.pdf".to_string(),
+            },
+        ));
+        assert_eq!(
+            blob,
+            json!({
+                "uri": "file:///report.pdf",
+                "mimeType": "application/pdf",
+                "size": 11,
+                "omitted": "binary content",
+            })
+        );
+
+        let long_text = "x".repeat(READ_RESOURCE_TEXT_MAX_BYTES * 2);
+        let text = resource_contents_value(ReadResourceResultContents::TextResourceContents(
+            TextResourceContents {
+                mime_type: Some("text/plain".to_string()),
+                text: long_text,
+                uri: "file:///notes.txt".to_string(),
+            },
+        ));
+        let returned = text["text"].as_str().unwrap_or_default();
+        assert!(returned.len() <= READ_RESOURCE_TEXT_MAX_BYTES + 64);
+        assert_eq!(text["truncated"], json!(true));
+    }
+
+    #[test]
+    fn resource_mentions_match_name_or_uri() {
+        let resources = HashMap::from([(
+            "runbooks".to_string(),
+            vec![
+                resource("runbook://deploy", "deploy"),
+                resource("runbook://rollback", "rollback"),
+            ],
+        )]);
+
+        let mentions = find_resource_mentions(
+            "see @runbooks:deploy, then @runbooks:runbook://rollback and @other:deploy",
+            &resources,
+        );
+
+        assert_eq!(
+            mentions,
+            vec![
+                ("runbooks".to_string(), "runbook://deploy".to_string()),
+                ("runbooks".to_string(), "runbook://rollback".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn prompt_invocation_parses_key_value_and_positional_arguments() {
+        let prompts = HashMap::from([(
+            "schema".to_string(),
+            vec![
+                prompt("describe", &[("table", true)]),
+                prompt("diff", &[("from", true), ("to", false)]),
+            ],
+        )]);
+
+        let positional = parse_prompt_invocation("/schema:describe users orders", &prompts)
+            .expect("parse")
+            .expect("invocation");
+        assert_eq!(
+            positional.arguments["table"],
+            Value::String("users orders".into())
+        );
+
+        let keyed = parse_prompt_invocation("/schema:diff from=v1 to=\"v 2\"\nextra", &prompts)
+            .expect("parse")
+            .expect("invocation");
+        assert_eq!(keyed.arguments["from"], Value::String("v1".into()));
+        assert_eq!(keyed.arguments["to"], Value::String("v 2".into()));
+        assert_eq!(keyed.rest, "extra");
+
+        let missing = parse_prompt_invocation("/schema:diff to=v2", &prompts);
+        assert_eq!(
+            missing,
+            Err("Missing required arguments for /schema:diff: from".to_string())
+        );
+
+        assert_eq!(
+            parse_prompt_invocation("/schema:unknown", &prompts),
+            Ok(None)
+        );
+        assert_eq!(parse_prompt_invocation("/status", &prompts), Ok(None));
+    }
+}
@@ -0,0 +1,462 @@
+//! Answers `sampling/createMessage` requests from MCP servers by running the
+//! requested messages through the session's [`ModelClient`].
+//!
+//! Only servers listed in `mcp_sampling.allowed_servers` advertise the
+//! capability. Each server has its own token budget for the session, and
+//! when `require_approval` is set the request is shown to the user (through
+//! the elicitation form) before anything is sent to the model.
+//!
+//! The request's `maxTokens` caps the model's output. Before a request is
+//! sent, that many tokens (or whatever is left of the budget, if less) are
+//! reserved, so concurrent requests cannot overrun the budget together; the
+//! reservation is replaced by the actual usage once the request finishes.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+use chrono::Utc;
+use code_rmcp_client::SendElicitation;
+use code_rmcp_client::SendSampling;
+use futures::FutureExt;
+use futures::StreamExt;
+use mcp_types::CreateMessageRequestParams;
+use mcp_types::CreateMessageResult;
+use mcp_types::CreateMessageResultContent;
+use mcp_types::ElicitRequestParams;
+use mcp_types::ElicitRequestParamsRequestedSchema;
+use mcp_types::Role;
+use mcp_types::SamplingMessage;
+use mcp_types::SamplingMessageContent;
+use mcp_types::TextContent;
+use serde_json::json;
+use tracing::warn;
+
+use crate::account_usage;
+use crate::client::ModelClient;
+use crate::client_common::Prompt;
+use crate::client_common::ResponseEvent;
+use crate::codex::AccountUsageContext;
+use crate::config_types::McpSamplingConfig;
+use crate::protocol::TokenUsage;
+use code_protocol::models::ContentItem;
+use code_protocol::models::ResponseItem;
+
+/// Instructions used when the server does not send a system prompt.
+const DEFAULT_SAMPLING_INSTRUCTIONS: &str =
+    "You are answering a request from a tool. Reply to the conversation below directly.";
+
+/// Runs sampling requests for allowlisted servers and tracks how many tokens
+/// each server has used.
+#[derive(Clone)]
+pub(crate) struct McpSampler {
+    client: ModelClient,
+    config: McpSamplingConfig,
+    budget: Arc<SamplingBudget>,
+}
+
+impl McpSampler {
+    pub(crate) fn new(client: ModelClient, config: McpSamplingConfig) -> Self {
+        let budget = Arc::new(SamplingBudget::new(config.token_budget));
+        Self {
+            client,
+            config,
+            budget,
+        }
+    }
+
+    /// Handler for `server_name`, or `None` when the server is not allowed
+    /// to sample. `ask_approval` is used when approval is required.
+    pub(crate) fn make_sender(
+        &self,
+        server_name: String,
+        ask_approval: SendElicitation,
+    ) -> Option<SendSampling> {
+        if !self.config.allows(&server_name) {
+            return None;
+        }
+        let sampler = self.clone();
+        Some(Arc::new(move |params: CreateMessageRequestParams| {
+            let sampler = sampler.clone();
+            let server_name = server_name.clone();
+            let ask_approval = Arc::clone(&ask_approval);
+            async move {
+                let max_tokens = u64::try_from(params.max_tokens)
+                    .ok()
+                    .filter(|max_tokens| *max_tokens > 0)
+                    .ok_or_else(|| anyhow!("maxTokens must be a positive number"))?;
+                sampler.budget.check(&server_name)?;
+                if sampler.config.require_approval {
+                    let answer = ask_approval(approval_request(&server_name, &params)).await?;
+                    if answer.action != "accept" {
+                        bail!("the user declined the sampling request");
+                    }
+                }
+                let reserved = sampler.budget.reserve(&server_name, max_tokens)?;
+                let result = sampler.sample(&server_name, params, reserved).await;
+                let used = match &result {
+                    Ok((_, Some(usage))) => usage.total_tokens,
+                    // The model ran but did not report usage: keep the
+                    // reservation as the estimate.
+                    Ok((_, None)) => reserved,
+                    Err(_) => 0,
+                };
+                sampler.budget.settle(&server_name, reserved, used);
+                result.map(|(message, _)| message)
+            }
+            .boxed()
+        }))
+    }
+
+    async fn sample(
+        &self,
+        server_name: &str,
+        params: CreateMessageRequestParams,
+        max_output_tokens: u64,
+    ) -> Result<(CreateMessageResult, Option<TokenUsage>)> {
+        let mut prompt = Prompt::default();
+        prompt.input = sampling_input(&params.messages)?;
+        prompt.base_instructions_override = Some(
+            params
+                .system_prompt
+                .filter(|prompt| !prompt.trim().is_empty())
+                .unwrap_or_else(|| DEFAULT_SAMPLING_INSTRUCTIONS.to_string()),
+        );
+        prompt.include_additional_instructions = false;
+        prompt.max_output_tokens = Some(max_output_tokens);
+        prompt.set_log_tag(format!("mcp/sampling/{server_name}"));
+
+        let mut stream = self.client.stream(&prompt).await?;
+        let mut message_text = String::new();
+        let mut delta_text = String::new();
+        let mut token_usage = None;
+        while let Some(event) = stream.next().await {
+            match event? {
+                ResponseEvent::OutputItemDone {
+                    item: ResponseItem::Message { role, content, .. },
+                    ..
+                } if role == "assistant" => {
+                    for item in content {
+                        if let ContentItem::OutputText { text } = item {
+                            message_text.push_str(&text);
+                        }
+                    }
+                }
+                ResponseEvent::OutputTextDelta { delta, .. } => delta_text.push_str(&delta),
+                ResponseEvent::Completed {
+                    token_usage: usage, ..
+                } => {
+                    token_usage = usage;
+                    break;
+                }
+                _ => {}
+            }
+        }
+        if let Some(usage) = token_usage.as_ref() {
+            self.record_account_usage(usage);
+        }
+
+        let text = if message_text.is_empty() {
+            delta_text
+        } else {
+            message_text
+        };
+        let message = CreateMessageResult {
+            content: CreateMessageResultContent::TextContent(TextContent {
+                annotations: None,
+                text,
+                r#type: "text".to_string(),
+            }),
+            model: self.client.get_model(),
+            role: Role::Assistant,
+            stop_reason: Some("endTurn".to_string()),
+        };
+        Ok((message, token_usage))
+    }
+
+    fn record_account_usage(&self, usage: &TokenUsage) {
+        let Some(ctx) = AccountUsageContext::for_code_home(self.client.code_home()) else {
+            return;
+        };
+        let usage = usage.clone();
+        let _ = tokio::task::spawn_blocking(move || {
+            if let Err(err) = account_usage::record_token_usage(
+                &ctx.code_home,
+                &ctx.account_id,
+                ctx.plan.as_deref(),
+                &usage,
+                Utc::now(),
+            ) {
+                warn!("Failed to persist MCP sampling token usage: {err}");
+            }
+        });
+    }
+}
+
+/// Per-server token accounting for one session. Tokens are reserved before a
+/// request is sent and settled once its actual usage is known.
+struct SamplingBudget {
+    /// Tokens each server may use, or `None` for no limit.
+    limit: Option<u64>,
+    /// Tokens used or reserved, by server.
+    used: Mutex<HashMap<String, u64>>,
+}
+
+impl SamplingBudget {
+    fn new(limit: Option<u64>) -> Self {
+        Self {
+            limit,
+            used: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Fails when `server_name` has no budget left.
+    fn check(&self, server_name: &str) -> Result<()> {
+        let Some(limit) = self.limit else {
+            return Ok(());
+        };
+        let used = self
+            .used
+            .lock()
+            .map(|used| used.get(server_name).copied().unwrap_or(0))
+            .unwrap_or(limit);
+        if used >= limit {
+            bail!(
+                "sampling token budget for `{server_name}` is used up ({used} of {limit} tokens)"
+            );
+        }
+        Ok(())
+    }
+
+    /// Reserves up to `max_tokens` for a request from `server_name` and
+    /// returns how many were reserved, which is the request's output cap.
+    fn reserve(&self, server_name: &str, max_tokens: u64) -> Result<u64> {
+        let mut used = self
+            .used
+            .lock()
+            .map_err(|_| anyhow!("sampling token accounting is unavailable"))?;
+        let used = used.entry(server_name.to_string()).or_default();
+        let reserved = match self.limit {
+            Some(limit) if *used >= limit => {
+                bail!(
+                    "sampling token budget for `{server_name}` is used up ({used} of {limit} tokens)"
+                );
+            }
+            Some(limit) => max_tokens.min(limit - *used),
+            None => max_tokens,
+        };
+        *used += reserved;
+        Ok(reserved)
+    }
+
+    /// Replaces a reservation of `reserved` tokens with the `actual` usage.
+    fn settle(&self, server_name: &str, reserved: u64, actual: u64) {
+        if let Ok(mut used) = self.used.lock() {
+            let used = used.entry(server_name.to_string()).or_default();
+            *used = used.saturating_sub(reserved) + actual;
+        }
+    }
+
+    #[cfg(test)]
+    fn used(&self, server_name: &str) -> u64 {
+        self.used
+            .lock()
+            .map(|used| used.get(server_name).copied().unwrap_or(0))
+            .unwrap_or(0)
+    }
+}
+
+/// Convert the server's messages into model input. Audio is not supported.
+fn sampling_input(messages: &[SamplingMessage]) -> Result<Vec<ResponseItem>> {
+    messages
+        .iter()
+        .map(|message| {
+            let (role, content) = match (&message.role, &message.content) {
+                (Role::User, SamplingMessageContent::TextContent(text)) => (
+                    "user",
+                    ContentItem::InputText {
+                        text: text.text.clone(),
+                    },
+                ),
+                (Role::Assistant, SamplingMessageContent::TextContent(text)) => (
+                    "assistant",
+                    ContentItem::OutputText {
+                        text: text.text.clone(),
+                    },
+                ),
+                (Role::User, SamplingMessageContent::ImageContent(image)) => (
+                    "user",
+                    ContentItem::InputImage {
+                        image_url: format!("data:{};base64,{}", image.mime_type, image.data),
+                    },
+                ),
+                (Role::Assistant, SamplingMessageContent::ImageContent(_)) => {
+                    return Err(anyhow!("assistant image messages are not supported"));
+                }
+                (_, SamplingMessageContent::AudioContent(_)) => {
+                    return Err(anyhow!("audio messages are not supported"));
+                }
+            };
+            Ok(ResponseItem::Message {
+                id: None,
+                role: role.to_string(),
+                content: vec![content],
+            })
+        })
+        .collect()
+}
+
+/// Elicitation with no fields that shows the user what the server wants to
+/// send to the model.
+fn approval_request(server_name: &str, params: &CreateMessageRequestParams) -> ElicitRequestParams {
+    let mut message = format!("{server_name} wants to run a completion with your model.");
+    if let Some(system_prompt) = params
+        .system_prompt
+        .as_deref()
+        .filter(|prompt| !prompt.trim().is_empty())
+    {
+        message.push_str(&format!("\n\nsystem: {system_prompt}"));
+    }
+    for sampling_message in &params.messages {
+        let role = match sampling_message.role {
+            Role::User => "user",
+            Role::Assistant => "assistant",
+        };
+        let content = match &sampling_message.content {
+            SamplingMessageContent::TextContent(text) => text.text.as_str(),
+            SamplingMessageContent::ImageContent(_) => "[image]",
+            SamplingMessageContent::AudioContent(_) => "[audio]",
+        };
+        message.push_str(&format!("\n\n{role}: {content}"));
+    }
+    ElicitRequestParams {
+        message,
+        requested_schema: ElicitRequestParamsRequestedSchema {
+            properties: json!({}),
+            required: None,
+            r#type: "object".to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_types::ImageContent;
+    use pretty_assertions::assert_eq;
+
+    fn text_message(role: Role, text: &str) -> SamplingMessage {
+        SamplingMessage {
+            content: SamplingMessageContent::TextContent(TextContent {
+                annotations: None,
+                text: text.to_string(),
+                r#type: "text".to_string(),
+            }),
+            role,
+        }
+    }
+
+    #[test]
+    fn sampling_messages_become_model_input() {
+        let messages = vec![
+            text_message(Role::User, "summarize these results"),
+            text_message(Role::Assistant, "which ones?"),
+            SamplingMessage {
+                content: SamplingMessageContent::ImageContent(ImageContent {
+                    annotations: None,
+                    data: "aGk=".to_string(),
+                    mime_type: "image/png".to_string(),
+                    r#type: "image".to_string(),
+                }),
+                role: Role::User,
+            },
+        ];
+
+        let input = sampling_input(&messages).expect("convert messages");
+
+        assert_eq!(
+            input,
+            vec![
+                ResponseItem::Message {
+                    id: None,
+                    role: "user".to_string(),
+                    content: vec![ContentItem::InputText {
+                        text: "summarize these results".to_string(),
+                    }],
+                },
+                ResponseItem::Message {
+                    id: None,
+                    role: "assistant".to_string(),
+                    content: vec![ContentItem::OutputText {
+                        text: "which ones?".to_string(),
+                    }],
+                },
+                ResponseItem::Message {
+                    id: None,
+                    role: "user".to_string(),
+                    content: vec![ContentItem::InputImage {
+                        image_url: "data:image/png;base64,aGk=".to_string(),
+                    }],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn concurrent_reservations_stay_within_the_budget() {
+        let budget = Arc::new(SamplingBudget::new(Some(1_000)));
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let budget = Arc::clone(&budget);
+                std::thread::spawn(move || budget.reserve("code-search", 400).ok())
+            })
+            .collect();
+        let mut reserved: Vec<u64> = handles
+            .into_iter()
+            .filter_map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|_| panic!("reserve thread panicked"))
+            })
+            .collect();
+        reserved.sort_unstable();
+
+        assert_eq!(reserved, vec![200, 400, 400]);
+        assert_eq!(budget.used("code-search"), 1_000);
+        assert!(budget.check("code-search").is_err());
+
+        // Settling with the actual usage frees what the requests did not use.
+        budget.settle("code-search", 400, 150);
+        assert_eq!(budget.used("code-search"), 750);
+        assert_eq!(budget.reserve("code-search", 400).ok(), Some(250));
+        assert_eq!(budget.reserve("other-server", 400).ok(), Some(400));
+    }
+
+    #[test]
+    fn approval_request_lists_the_requested_messages() {
+        let params = CreateMessageRequestParams {
+            include_context: None,
+            max_tokens: 200,
+            messages: vec![text_message(Role::User, "summarize these results")],
+            metadata: None,
+            model_preferences: None,
+            stop_sequences: None,
+            system_prompt: Some("Be brief.".to_string()),
+            temperature: None,
+        };
+
+        let request = approval_request("code-search", &params);
+
+        assert_eq!(
+            request.message,
+            "code-search wants to run a completion with your model.\n\n\
+             system: Be brief.\n\n\
+             user: summarize these results"
+        );
+        assert_eq!(request.requested_schema.properties, json!({}));
+    }
+}
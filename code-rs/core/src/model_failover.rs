@@ -0,0 +1,307 @@
+//! Ordered fallback providers for model requests (`[model_failover]`).
+//!
+//! When the session's provider keeps failing after its own request and stream
+//! retries, the turn moves to the next `provider:model` entry in the chain and
+//! stays there for the rest of the session.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::error::CodexErr;
+use crate::model_provider_info::ModelProviderInfo;
+use crate::protocol::TokenUsage;
+
+/// A resolved `provider:model` entry from `model_failover.chain`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelFailoverTarget {
+    pub provider_id: String,
+    pub provider: ModelProviderInfo,
+    pub model: String,
+}
+
+impl ModelFailoverTarget {
+    pub fn label(&self) -> String {
+        format!("{}:{}", self.provider_id, self.model)
+    }
+}
+
+/// Resolve chain entries against the provider map. Entries naming the
+/// session's own provider and model are skipped since that is where every
+/// session starts.
+pub(crate) fn resolve_failover_chain(
+    chain: &[String],
+    providers: &HashMap<String, ModelProviderInfo>,
+    primary_provider_id: &str,
+    primary_model: &str,
+) -> std::io::Result<Vec<ModelFailoverTarget>> {
+    let mut targets = Vec::new();
+    for entry in chain {
+        // Split on the first colon only; model slugs such as `qwen:7b`
+        // contain colons of their own.
+        let Some((provider_id, model)) = entry
+            .split_once(':')
+            .map(|(provider, model)| (provider.trim(), model.trim()))
+            .filter(|(provider, model)| !provider.is_empty() && !model.is_empty())
+        else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("model_failover.chain entry `{entry}` must look like `provider:model`"),
+            ));
+        };
+        let Some(provider) = providers.get(provider_id) else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("Model provider `{provider_id}` in model_failover.chain not found"),
+            ));
+        };
+        if provider_id == primary_provider_id && model == primary_model {
+            continue;
+        }
+        targets.push(ModelFailoverTarget {
+            provider_id: provider_id.to_string(),
+            provider: provider.clone(),
+            model: model.to_string(),
+        });
+    }
+    Ok(targets)
+}
+
+/// Whether `err` means the provider itself is unavailable, as opposed to a
+/// problem with the request that another provider would reject too.
+pub(crate) fn is_provider_failure(err: &CodexErr) -> bool {
+    match err {
+        CodexErr::RetryLimit(_)
+        | CodexErr::Stream(..)
+        | CodexErr::ServerError(_)
+        | CodexErr::Reqwest(_) => true,
+        CodexErr::UnexpectedStatus(err) => {
+            err.status.is_server_error() || err.status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        }
+        _ => false,
+    }
+}
+
+/// Failover position and per-provider token usage, shared by every clone of
+/// a session's `ModelClient`.
+#[derive(Debug)]
+pub(crate) struct ModelFailover {
+    primary_label: String,
+    targets: Vec<ModelFailoverTarget>,
+    state: Mutex<FailoverState>,
+}
+
+#[derive(Debug, Default)]
+struct FailoverState {
+    /// Index into `targets`; `None` while the primary provider is in use.
+    active: Option<usize>,
+    usage: HashMap<String, TokenUsage>,
+}
+
+impl ModelFailover {
+    pub(crate) fn new(primary_label: String, targets: Vec<ModelFailoverTarget>) -> Self {
+        Self {
+            primary_label,
+            targets,
+            state: Mutex::new(FailoverState::default()),
+        }
+    }
+
+    /// The fallback serving requests, or `None` while on the primary.
+    pub(crate) fn active_target(&self) -> Option<&ModelFailoverTarget> {
+        let active = self.state.lock().ok()?.active?;
+        self.targets.get(active)
+    }
+
+    /// `provider:model` of whatever currently serves requests.
+    pub(crate) fn active_label(&self) -> String {
+        self.active_target()
+            .map(ModelFailoverTarget::label)
+            .unwrap_or_else(|| self.primary_label.clone())
+    }
+
+    /// Move to the next entry in the chain. Returns `None` once the chain is
+    /// exhausted, in which case the current provider stays active.
+    pub(crate) fn advance(&self) -> Option<&ModelFailoverTarget> {
+        let mut state = self.state.lock().ok()?;
+        let next = state.active.map_or(0, |active| active + 1);
+        let target = self.targets.get(next)?;
+        state.active = Some(next);
+        Some(target)
+    }
+
+    /// Add `usage` to the totals of whatever currently serves requests.
+    pub(crate) fn record_usage(&self, usage: &TokenUsage) {
+        let label = self.active_label();
+        if let Ok(mut state) = self.state.lock() {
+            let total = state.usage.entry(label).or_default();
+            total.input_tokens += usage.input_tokens;
+            total.cached_input_tokens += usage.cached_input_tokens;
+            total.output_tokens += usage.output_tokens;
+            total.reasoning_output_tokens += usage.reasoning_output_tokens;
+            total.total_tokens += usage.total_tokens;
+        }
+    }
+
+    /// Total tokens used through `label` this session.
+    pub(crate) fn tokens_used(&self, label: &str) -> u64 {
+        self.state
+            .lock()
+            .ok()
+            .and_then(|state| state.usage.get(label).map(|usage| usage.total_tokens))
+            .unwrap_or(0)
+    }
+
+    /// Usage per `provider:model` this session. Empty until the session has
+    /// failed over, since all usage belongs to the primary before that.
+    pub(crate) fn usage_by_label(&self) -> BTreeMap<String, TokenUsage> {
+        match self.state.lock() {
+            Ok(state) if state.active.is_some() => state
+                .usage
+                .iter()
+                .map(|(label, usage)| (label.clone(), usage.clone()))
+                .collect(),
+            _ => BTreeMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WireApi;
+    use pretty_assertions::assert_eq;
+
+    fn provider(name: &str) -> ModelProviderInfo {
+        ModelProviderInfo {
+            name: name.to_string(),
+            base_url: None,
+            env_key: None,
+            env_key_instructions: None,
+            wire_api: WireApi::Chat,
+            query_params: None,
+            http_headers: None,
+            env_http_headers: None,
+            request_max_retries: None,
+            stream_max_retries: None,
+            stream_idle_timeout_ms: None,
+            requires_openai_auth: false,
+            openrouter: None,
+        }
+    }
+
+    fn providers() -> HashMap<String, ModelProviderInfo> {
+        ["openai", "azure", "ollama"]
+            .into_iter()
+            .map(|id| (id.to_string(), provider(id)))
+            .collect()
+    }
+
+    #[test]
+    fn chain_skips_the_primary_and_keeps_colons_in_models() {
+        let chain = vec![
+            "openai:gpt-5".to_string(),
+            "azure:gpt-5".to_string(),
+            "ollama:qwen:7b".to_string(),
+        ];
+
+        let targets = resolve_failover_chain(&chain, &providers(), "openai", "gpt-5").unwrap();
+
+        assert_eq!(
+            targets
+                .iter()
+                .map(ModelFailoverTarget::label)
+                .collect::<Vec<_>>(),
+            vec!["azure:gpt-5".to_string(), "ollama:qwen:7b".to_string()]
+        );
+    }
+
+    #[test]
+    fn chain_rejects_unknown_providers_and_malformed_entries() {
+        let unknown = resolve_failover_chain(
+            &["bedrock:claude".to_string()],
+            &providers(),
+            "openai",
+            "gpt-5",
+        )
+        .unwrap_err();
+        assert_eq!(unknown.kind(), std::io::ErrorKind::NotFound);
+
+        let malformed =
+            resolve_failover_chain(&["azure".to_string()], &providers(), "openai", "gpt-5")
+                .unwrap_err();
+        assert_eq!(malformed.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn advance_walks_the_chain_and_tracks_usage_per_provider() {
+        let targets = resolve_failover_chain(
+            &["azure:gpt-5".to_string(), "ollama:qwen".to_string()],
+            &providers(),
+            "openai",
+            "gpt-5",
+        )
+        .unwrap();
+        let failover = ModelFailover::new("openai:gpt-5".to_string(), targets);
+        let usage = TokenUsage {
+            total_tokens: 10,
+            ..Default::default()
+        };
+
+        failover.record_usage(&usage);
+        assert_eq!(
+            failover.advance().map(ModelFailoverTarget::label),
+            Some("azure:gpt-5".to_string())
+        );
+        failover.record_usage(&usage);
+        failover.record_usage(&usage);
+        assert_eq!(
+            failover.advance().map(ModelFailoverTarget::label),
+            Some("ollama:qwen".to_string())
+        );
+        assert_eq!(failover.advance(), None);
+        assert_eq!(failover.active_label(), "ollama:qwen");
+
+        assert_eq!(failover.tokens_used("openai:gpt-5"), 10);
+        assert_eq!(failover.tokens_used("azure:gpt-5"), 20);
+        assert_eq!(failover.tokens_used("ollama:qwen"), 0);
+    }
+
+    #[test]
+    fn usage_by_label_is_reported_once_failed_over() {
+        let targets = resolve_failover_chain(
+            &["azure:gpt-5".to_string()],
+            &providers(),
+            "openai",
+            "gpt-5",
+        )
+        .unwrap();
+        let failover = ModelFailover::new("openai:gpt-5".to_string(), targets);
+        let usage = TokenUsage {
+            input_tokens: 4,
+            output_tokens: 6,
+            total_tokens: 10,
+            ..Default::default()
+        };
+
+        failover.record_usage(&usage);
+        assert_eq!(failover.usage_by_label(), BTreeMap::new());
+
+        failover.advance();
+        failover.record_usage(&usage);
+        failover.record_usage(&usage);
+        let doubled = TokenUsage {
+            input_tokens: 8,
+            output_tokens: 12,
+            total_tokens: 20,
+            ..Default::default()
+        };
+        assert_eq!(
+            failover.usage_by_label(),
+            BTreeMap::from([
+                ("azure:gpt-5".to_string(), doubled),
+                ("openai:gpt-5".to_string(), usage),
+            ])
+        );
+    }
+}
@@ -24,12 +24,16 @@ const MAX_STREAM_MAX_RETRIES: u64 = 100;
 /// Hard cap for user-configured `request_max_retries`.
 const MAX_REQUEST_MAX_RETRIES: u64 = 100;
 
+const ANTHROPIC_VERSION_HEADER: &str = "anthropic-version";
+const DEFAULT_ANTHROPIC_VERSION: &str = "2023-06-01";
+
 /// Wire protocol that the provider speaks. Most third-party services only
 /// implement the classic OpenAI Chat Completions JSON schema, whereas OpenAI
 /// itself (and a handful of others) additionally expose the more modern
-/// *Responses* API. The two protocols use different request/response shapes
-/// and *cannot* be auto-detected at runtime, therefore each provider entry
-/// must declare which one it expects.
+/// *Responses* API, and some gateways only speak the Anthropic *Messages*
+/// format. The protocols use different request/response shapes and *cannot*
+/// be auto-detected at runtime, therefore each provider entry must declare
+/// which one it expects.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum WireApi {
@@ -39,6 +43,9 @@ pub enum WireApi {
     /// Regular Chat Completions compatible with `/v1/chat/completions`.
     #[default]
     Chat,
+
+    /// The Anthropic Messages API at `/v1/messages`.
+    Anthropic,
 }
 
 /// Serializable representation of a provider definition.
@@ -184,7 +191,8 @@ impl ModelProviderInfo {
     /// Construct a `POST` RequestBuilder for the given URL using the provided
     /// reqwest Client applying:
     ///   • provider-specific headers (static + env based)
-    ///   • Bearer auth header when an API key is available.
+    ///   • Bearer auth header when an API key is available (`x-api-key` for
+    ///     Anthropic providers).
     ///   • Auth token for OAuth.
     ///
     /// If the provider declares an `env_key` but the variable is missing/empty, returns an [`Err`] identical to the
@@ -200,9 +208,7 @@ impl ModelProviderInfo {
 
         let mut builder = client.post(&url);
 
-        if let Some(auth) = effective_auth.as_ref() {
-            builder = builder.bearer_auth(auth.get_token().await?);
-        }
+        builder = self.apply_auth(builder, effective_auth.as_ref()).await?;
 
         Ok(self.apply_http_headers(builder))
     }
@@ -220,9 +226,7 @@ impl ModelProviderInfo {
 
         let mut builder = client.request(method, url);
 
-        if let Some(auth) = effective_auth.as_ref() {
-            builder = builder.bearer_auth(auth.get_token().await?);
-        }
+        builder = self.apply_auth(builder, effective_auth.as_ref()).await?;
 
         Ok(self.apply_http_headers(builder))
     }
@@ -252,6 +256,34 @@ impl ModelProviderInfo {
         Ok(self.apply_http_headers(builder))
     }
 
+    async fn apply_auth(
+        &self,
+        builder: reqwest::RequestBuilder,
+        auth: Option<&CodexAuth>,
+    ) -> crate::error::Result<reqwest::RequestBuilder> {
+        if self.wire_api != WireApi::Anthropic {
+            return Ok(match auth {
+                Some(auth) => builder.bearer_auth(auth.get_token().await?),
+                None => builder,
+            });
+        }
+
+        let mut builder = builder;
+        if let Some(auth) = auth {
+            builder = builder.header("x-api-key", auth.get_token().await?);
+        }
+        // Let an explicit `anthropic-version` in `http_headers` win.
+        let has_version = self.http_headers.as_ref().is_some_and(|headers| {
+            headers
+                .keys()
+                .any(|key| key.eq_ignore_ascii_case(ANTHROPIC_VERSION_HEADER))
+        });
+        if !has_version {
+            builder = builder.header(ANTHROPIC_VERSION_HEADER, DEFAULT_ANTHROPIC_VERSION);
+        }
+        Ok(builder)
+    }
+
     fn effective_auth(
         &self,
         auth: &Option<CodexAuth>,
@@ -288,7 +320,9 @@ impl ModelProviderInfo {
     }
 
     pub(crate) fn get_full_url(&self, auth: &Option<CodexAuth>) -> String {
-        let default_base_url = if matches!(
+        let default_base_url = if self.wire_api == WireApi::Anthropic {
+            "https://api.anthropic.com/v1"
+        } else if matches!(
             auth,
             Some(CodexAuth {
                 mode: AuthMode::ChatGPT,
@@ -308,6 +342,7 @@ impl ModelProviderInfo {
         match self.wire_api {
             WireApi::Responses => format!("{base_url}/responses{query_string}"),
             WireApi::Chat => format!("{base_url}/chat/completions{query_string}"),
+            WireApi::Anthropic => format!("{base_url}/messages{query_string}"),
         }
     }
 
@@ -737,4 +772,31 @@ env_http_headers = { "X-Example-Env-Header" = "EXAMPLE_ENV_VAR" }
             );
         }
     }
+
+    #[tokio::test]
+    async fn anthropic_provider_posts_to_messages_with_api_key_header() {
+        let provider_toml = r#"
+name = "Gateway"
+wire_api = "anthropic"
+        "#;
+        let provider: ModelProviderInfo = toml::from_str(provider_toml).unwrap();
+        assert_eq!(provider.wire_api, WireApi::Anthropic);
+        assert_eq!(
+            provider.get_full_url(&None),
+            "https://api.anthropic.com/v1/messages"
+        );
+
+        let client = reqwest::Client::new();
+        let auth = Some(CodexAuth::from_api_key("sk-ant-test"));
+        let request = provider
+            .create_request_builder(&client, &auth)
+            .await
+            .unwrap()
+            .build()
+            .unwrap();
+        let headers = request.headers();
+        assert_eq!(headers.get("x-api-key").unwrap(), "sk-ant-test");
+        assert_eq!(headers.get("anthropic-version").unwrap(), "2023-06-01");
+        assert!(headers.get(reqwest::header::AUTHORIZATION).is_none());
+    }
 }
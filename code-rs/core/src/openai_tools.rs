@@ -840,6 +840,72 @@ pub fn create_kill_tool() -> OpenAiTool {
     })
 }
 
+/// Read-only tools over MCP resources. Only offered when at least one server
+/// publishes resources or resource templates.
+pub(crate) fn create_mcp_resource_tools() -> Vec<OpenAiTool> {
+    let string_property = |description: &str| JsonSchema::String {
+        description: Some(description.to_string()),
+        allowed_values: None,
+    };
+
+    let mut list_properties = BTreeMap::new();
+    list_properties.insert(
+        "server".to_string(),
+        string_property(
+            "MCP server name. When omitted, lists entries from every connected server.",
+        ),
+    );
+    list_properties.insert(
+        "cursor".to_string(),
+        string_property(
+            "Opaque cursor returned by a previous call for the same server (requires server).",
+        ),
+    );
+
+    let mut read_properties = BTreeMap::new();
+    read_properties.insert(
+        "server".to_string(),
+        string_property("MCP server name, as returned in the 'server' field of list_mcp_resources."),
+    );
+    read_properties.insert(
+        "uri".to_string(),
+        string_property("Resource URI to read, as returned by list_mcp_resources or expanded from a template."),
+    );
+
+    vec![
+        OpenAiTool::Function(ResponsesApiTool {
+            name: crate::mcp_resources::LIST_MCP_RESOURCES_TOOL_NAME.to_string(),
+            description: "List resources published by MCP servers (files, schemas, runbooks and other context). Prefer these over web search when they cover the question.".to_string(),
+            strict: false,
+            parameters: JsonSchema::Object {
+                properties: list_properties.clone(),
+                required: None,
+                additional_properties: Some(false.into()),
+            },
+        }),
+        OpenAiTool::Function(ResponsesApiTool {
+            name: crate::mcp_resources::LIST_MCP_RESOURCE_TEMPLATES_TOOL_NAME.to_string(),
+            description: "List parameterized resource templates published by MCP servers. Fill in a template's uriTemplate and pass the result to read_mcp_resource.".to_string(),
+            strict: false,
+            parameters: JsonSchema::Object {
+                properties: list_properties,
+                required: None,
+                additional_properties: Some(false.into()),
+            },
+        }),
+        OpenAiTool::Function(ResponsesApiTool {
+            name: crate::mcp_resources::READ_MCP_RESOURCE_TOOL_NAME.to_string(),
+            description: "Read a resource from an MCP server by server name and URI.".to_string(),
+            strict: false,
+            parameters: JsonSchema::Object {
+                properties: read_properties,
+                required: Some(vec!["server".to_string(), "uri".to_string()]),
+                additional_properties: Some(false.into()),
+            },
+        }),
+    ]
+}
+
 pub fn create_gh_run_wait_tool() -> OpenAiTool {
     let mut properties = BTreeMap::new();
     properties.insert(
@@ -1655,9 +1721,11 @@ mod tests {
         let sandbox_policy = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec!["workspace".into()],
             network_access: false,
+            network_allowlist: vec![],
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
             allow_git_writes: true,
+            read_denied_paths: vec![],
         };
         let tool = super::create_shell_tool_for_sandbox(&sandbox_policy);
         let OpenAiTool::Function(ResponsesApiTool {
@@ -1734,6 +1802,11 @@ fn create_browser_tool(browser_enabled: bool) -> OpenAiTool {
             "history",
             "inspect",
             "console",
+            "snapshot",
+            "select",
+            "tabs",
+            "network",
+            "compare",
             "cleanup",
             "cdp",
         ]);
@@ -1755,7 +1828,7 @@ fn create_browser_tool(browser_enabled: bool) -> OpenAiTool {
         "url".to_string(),
         JsonSchema::String {
             description: Some(
-                "For action=open or fetch: URL to navigate to or retrieve (e.g., https://example.com)."
+                "For action=open or fetch: URL to navigate to or retrieve (e.g., https://example.com). Also used by action=tabs op=open."
                     .to_string(),
             ),
             allowed_values: None,
@@ -1810,10 +1883,66 @@ fn create_browser_tool(browser_enabled: bool) -> OpenAiTool {
     properties.insert(
         "text".to_string(),
         JsonSchema::String {
-            description: Some("For action=type: text to send to the focused element.".to_string()),
+            description: Some("For action=type: text to send to the focused element (or to 'ref' when provided).".to_string()),
+            allowed_values: None,
+        },
+    );
+    properties.insert(
+        "ref".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "For actions=click/type/select: element ref from the latest action=snapshot (e.g., 'e12'); preferred over x/y. Refs expire when the page navigates or reloads."
+                    .to_string(),
+            ),
+            allowed_values: None,
+        },
+    );
+    properties.insert(
+        "values".to_string(),
+        JsonSchema::Array {
+            items: Box::new(JsonSchema::String {
+                description: None,
+                allowed_values: None,
+            }),
+            description: Some(
+                "For action=select: option values or visible labels to choose in the referenced <select>."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "op".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "For action=tabs: 'list' (default), 'open' (optional 'url'), 'switch' or 'close' (use 'tab_id')."
+                    .to_string(),
+            ),
+            allowed_values: Some(vec![
+                "list".to_string(),
+                "open".to_string(),
+                "switch".to_string(),
+                "close".to_string(),
+            ]),
+        },
+    );
+    properties.insert(
+        "tab_id".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "For action=tabs: tab id (or unique prefix) from op=list; close defaults to the active tab."
+                    .to_string(),
+            ),
             allowed_values: None,
         },
     );
+    properties.insert(
+        "max_nodes".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "For action=snapshot: optional cap on outline lines (default 500).".to_string(),
+            ),
+        },
+    );
     properties.insert(
         "key".to_string(),
         JsonSchema::String {
@@ -1856,6 +1985,77 @@ fn create_browser_tool(browser_enabled: bool) -> OpenAiTool {
             ),
         },
     );
+    properties.insert(
+        "filter".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "For action=network: URL substring, or glob when it contains '*' (e.g., '*/api/*')."
+                    .to_string(),
+            ),
+            allowed_values: None,
+        },
+    );
+    properties.insert(
+        "errors_only".to_string(),
+        JsonSchema::Boolean {
+            description: Some(
+                "For action=network: only list failed requests and HTTP status >= 400.".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "limit".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "For action=network: number of most recent requests to list (default 50).".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "request_id".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "For action=network: request id from the listing; returns that response body instead."
+                    .to_string(),
+            ),
+            allowed_values: None,
+        },
+    );
+    properties.insert(
+        "clear".to_string(),
+        JsonSchema::Boolean {
+            description: Some(
+                "For action=network: clear captured requests after listing.".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "name".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "For action=compare: baseline name (letters, digits, '-', '_'); the first capture under a new name becomes its baseline."
+                    .to_string(),
+            ),
+            allowed_values: None,
+        },
+    );
+    properties.insert(
+        "accept".to_string(),
+        JsonSchema::Boolean {
+            description: Some(
+                "For action=compare: ask the user to accept the latest capture as the new baseline instead of comparing."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "threshold".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "For action=compare: minimum similarity (0-1) to pass; default 0.995.".to_string(),
+            ),
+        },
+    );
     properties.insert(
         "method".to_string(),
         JsonSchema::String {
@@ -1901,7 +2101,7 @@ fn create_browser_tool(browser_enabled: bool) -> OpenAiTool {
 
     OpenAiTool::Function(ResponsesApiTool {
         name: "browser".to_string(),
-        description: "Unified browser controller for navigation, interaction, console access, DevTools commands, and one-shot fetches. Choose an action and supply the matching fields.".to_string(),
+        description: "Unified browser controller for navigation, interaction, accessibility snapshots, console and network inspection, visual baseline comparisons, DevTools commands, and one-shot fetches. Prefer action=snapshot and element refs over pixel coordinates. Choose an action and supply the matching fields.".to_string(),
         strict: false,
         parameters: JsonSchema::Object {
             properties,
@@ -1,7 +1,9 @@
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
-use crate::config_types::{ProjectCommandConfig, ProjectHookConfig, ProjectHookEvent};
+use serde::Deserialize;
+
+use crate::config_types::{HookFailurePolicy, ProjectCommandConfig, ProjectHookConfig, ProjectHookEvent};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct ProjectHook {
@@ -12,6 +14,7 @@ pub struct ProjectHook {
     pub env: HashMap<String, String>,
     pub timeout_ms: Option<u64>,
     pub run_in_background: bool,
+    pub on_failure: HookFailurePolicy,
 }
 
 impl ProjectHook {
@@ -44,6 +47,7 @@ impl ProjectHooks {
                 env: cfg.env.clone().unwrap_or_default(),
                 timeout_ms: cfg.timeout_ms,
                 run_in_background: cfg.run_in_background.unwrap_or(false),
+                on_failure: cfg.on_failure.unwrap_or_default(),
             };
             map.entry(cfg.event).or_default().push(hook);
         }
@@ -62,6 +66,76 @@ impl ProjectHooks {
     }
 }
 
+/// Decision printed by a `tool.before` or `file.before_write` hook as a JSON
+/// object on stdout, e.g. `{"decision": "deny", "reason": "..."}`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "decision", rename_all = "lowercase")]
+pub enum HookDecision {
+    Allow,
+    /// Block the call; `reason` is sent back to the model.
+    Deny {
+        #[serde(default = "default_deny_reason")]
+        reason: String,
+    },
+    /// Run a rewritten command (`tool.before`) or apply a rewritten patch
+    /// (`file.before_write`) instead.
+    Modify {
+        #[serde(default, alias = "argv")]
+        command: Option<Vec<String>>,
+        #[serde(default)]
+        patch: Option<String>,
+    },
+}
+
+fn default_deny_reason() -> String {
+    "denied by project hook".to_string()
+}
+
+impl HookDecision {
+    /// Interprets a finished hook. Stdout is either empty, a JSON decision,
+    /// or free-form output whose last line is a JSON decision; anything else
+    /// allows the call. A non-zero exit applies `on_failure` unless the hook
+    /// explicitly denied.
+    pub fn from_hook_output(
+        exit_code: i32,
+        stdout: &str,
+        stderr: &str,
+        on_failure: HookFailurePolicy,
+    ) -> Self {
+        let decision = parse_hook_decision(stdout);
+        if exit_code == 0 {
+            return decision.unwrap_or(HookDecision::Allow);
+        }
+        match (decision, on_failure) {
+            (Some(deny @ HookDecision::Deny { .. }), _) => deny,
+            (_, HookFailurePolicy::Allow) => HookDecision::Allow,
+            (_, HookFailurePolicy::Deny) => {
+                let stderr = stderr.trim();
+                let reason = if stderr.is_empty() {
+                    format!("hook exited with status {exit_code}")
+                } else {
+                    format!("hook exited with status {exit_code}: {stderr}")
+                };
+                HookDecision::Deny { reason }
+            }
+        }
+    }
+}
+
+fn parse_hook_decision(stdout: &str) -> Option<HookDecision> {
+    let stdout = stdout.trim();
+    if stdout.is_empty() {
+        return None;
+    }
+    serde_json::from_str(stdout).ok().or_else(|| {
+        stdout
+            .lines()
+            .map(str::trim)
+            .rfind(|line| !line.is_empty())
+            .and_then(|line| serde_json::from_str(line).ok())
+    })
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ProjectCommand {
     pub name: String,
@@ -123,3 +197,67 @@ fn resolve_optional_path(raw: &Option<String>, project_root: &Path) -> Option<Pa
         Some(project_root.join(path))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hook_decision_defaults_to_allow() {
+        assert_eq!(
+            HookDecision::from_hook_output(0, "", "", HookFailurePolicy::Deny),
+            HookDecision::Allow
+        );
+        assert_eq!(
+            HookDecision::from_hook_output(0, "lint ok\n", "", HookFailurePolicy::Deny),
+            HookDecision::Allow
+        );
+    }
+
+    #[test]
+    fn hook_decision_reads_last_json_line() {
+        let stdout = "checking migrations/\n{\"decision\":\"deny\",\"reason\":\"migrations/ is protected\"}\n";
+        assert_eq!(
+            HookDecision::from_hook_output(0, stdout, "", HookFailurePolicy::Allow),
+            HookDecision::Deny {
+                reason: "migrations/ is protected".to_string()
+            }
+        );
+        assert_eq!(
+            HookDecision::from_hook_output(
+                0,
+                r#"{"decision":"modify","argv":["cargo","test","--offline"]}"#,
+                "",
+                HookFailurePolicy::Deny
+            ),
+            HookDecision::Modify {
+                command: Some(vec![
+                    "cargo".to_string(),
+                    "test".to_string(),
+                    "--offline".to_string()
+                ]),
+                patch: None,
+            }
+        );
+    }
+
+    #[test]
+    fn hook_failure_applies_policy() {
+        assert_eq!(
+            HookDecision::from_hook_output(2, "", "no edits under migrations/\n", HookFailurePolicy::Deny),
+            HookDecision::Deny {
+                reason: "hook exited with status 2: no edits under migrations/".to_string()
+            }
+        );
+        assert_eq!(
+            HookDecision::from_hook_output(2, "", "boom", HookFailurePolicy::Allow),
+            HookDecision::Allow
+        );
+        assert_eq!(
+            HookDecision::from_hook_output(1, r#"{"decision":"deny"}"#, "", HookFailurePolicy::Allow),
+            HookDecision::Deny {
+                reason: "denied by project hook".to_string()
+            }
+        );
+    }
+}
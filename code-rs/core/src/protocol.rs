@@ -3,6 +3,7 @@
 //! Uses a SQ (Submission Queue) / EQ (Event Queue) pattern to asynchronously communicate
 //! between user and agent.
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::fmt;
 use std::path::Path;
@@ -39,7 +40,11 @@ pub use code_protocol::protocol::GitInfo;
 pub use code_protocol::protocol::RolloutItem;
 pub use code_protocol::protocol::RolloutLine;
 pub use code_protocol::protocol::ConversationPathResponseEvent;
+pub use code_protocol::protocol::ElicitationAction;
+pub use code_protocol::protocol::ElicitationRequestEvent;
 pub use code_protocol::protocol::ListCustomPromptsResponseEvent;
+pub use code_protocol::protocol::ListMcpResourcesResponseEvent;
+pub use code_protocol::protocol::McpResourceInfo;
 pub use code_protocol::protocol::ListSkillsResponseEvent;
 pub use code_protocol::protocol::ViewImageToolCallEvent;
 pub use code_protocol::skills::Skill;
@@ -195,6 +200,16 @@ pub enum Op {
         decision: ReviewDecision,
     },
 
+    /// Answer an `EventMsg::ElicitationRequest` from an MCP server.
+    ResolveElicitation {
+        /// The `id` of the elicitation request being answered.
+        id: String,
+        action: ElicitationAction,
+        /// Values for the requested schema; only meaningful with `Accept`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        content: Option<Value>,
+    },
+
     /// Update a specific validation tool toggle for the session.
     UpdateValidationTool {
         name: String,
@@ -239,10 +254,28 @@ pub enum Op {
     /// Reply is delivered via `EventMsg::ListCustomPromptsResponse`.
     ListCustomPrompts,
 
+    /// Request the resources advertised by connected MCP servers.
+    /// Reply is delivered via `EventMsg::ListMcpResourcesResponse`.
+    ListMcpResources,
+
     /// Request the list of available skills.
     /// Reply is delivered via `EventMsg::ListSkillsResponse`.
     ListSkills,
 
+    /// Report an Auto Drive phase change so `auto_drive.phase` project hooks
+    /// can observe it. Auto Drive runs in the client, so this is the only way
+    /// the core learns about its phases.
+    AutoDrivePhase {
+        /// Phase entered, e.g. "awaiting_review".
+        phase: String,
+        /// Phase left.
+        previous_phase: String,
+        /// Goal of the current run, if any.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(default)]
+        goal: Option<String>,
+    },
+
     /// Request the agent to summarize the current conversation context.
     /// The agent will use its existing context (either conversation history or previous response id)
     /// to generate a summary which will be returned as an AgentMessage event.
@@ -315,6 +348,12 @@ pub enum SandboxPolicy {
         #[serde(default)]
         network_access: bool,
 
+        /// Hosts that commands may still reach through the egress proxy when
+        /// `network_access` is `false`. Only enforced on Linux; elsewhere the
+        /// network stays blocked.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        network_allowlist: Vec<String>,
+
         /// When set to `true`, will NOT include the per-user `TMPDIR`
         /// environment variable among the default writable roots. Defaults to
         /// `false`.
@@ -330,12 +369,34 @@ pub enum SandboxPolicy {
         /// writable root. Defaults to true (historical behavior allows Git writes).
         #[serde(default = "crate::protocol::default_true_bool")]
         allow_git_writes: bool,
+
+        /// Paths that must not be readable from within the sandbox. Defaults
+        /// to [`default_read_denied_paths`]; only enforced on Linux.
+        #[serde(default = "crate::protocol::default_read_denied_paths")]
+        read_denied_paths: Vec<PathBuf>,
     },
 }
 
 // Serde helper: default to true for flags where we want historical permissive behavior.
 pub(crate) const fn default_true_bool() -> bool { true }
 
+/// Credential locations hidden from sandboxed commands unless configured
+/// otherwise: `~/.ssh`, `~/.aws`, `~/.config/gh` and the auth files in
+/// `$CODE_HOME`.
+pub fn default_read_denied_paths() -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    if let Some(home) = dirs::home_dir() {
+        paths.push(home.join(".ssh"));
+        paths.push(home.join(".aws"));
+        paths.push(home.join(".config").join("gh"));
+    }
+    if let Ok(code_home) = crate::config::find_code_home() {
+        paths.push(code_home.join("auth.json"));
+        paths.push(code_home.join("auth_accounts.json"));
+    }
+    paths
+}
+
 /// A writable root path accompanied by a list of subpaths that should remain
 /// read‑only even when the root is writable. This is primarily used to ensure
 /// top‑level VCS metadata directories (e.g. `.git`) under a writable root are
@@ -381,17 +442,32 @@ impl SandboxPolicy {
         SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![],
             network_access: false,
+            network_allowlist: vec![],
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
             allow_git_writes: true,
+            read_denied_paths: default_read_denied_paths(),
         }
     }
 
-    /// Always returns `true`; restricting read access is not supported.
+    /// Always returns `true`; reads are only restricted for the paths in
+    /// [`Self::get_read_denied_paths`].
     pub fn has_full_disk_read_access(&self) -> bool {
         true
     }
 
+    /// Paths that sandboxed commands must not read. `ReadOnly` always hides
+    /// the default credential locations.
+    pub fn get_read_denied_paths(&self) -> Vec<PathBuf> {
+        match self {
+            SandboxPolicy::DangerFullAccess => Vec::new(),
+            SandboxPolicy::ReadOnly => default_read_denied_paths(),
+            SandboxPolicy::WorkspaceWrite {
+                read_denied_paths, ..
+            } => read_denied_paths.clone(),
+        }
+    }
+
     pub fn has_full_disk_write_access(&self) -> bool {
         match self {
             SandboxPolicy::DangerFullAccess => true,
@@ -408,6 +484,19 @@ impl SandboxPolicy {
         }
     }
 
+    /// Hosts reachable through the egress proxy. Empty unless the policy is
+    /// `WorkspaceWrite` with `network_access` disabled.
+    pub fn get_network_allowlist(&self) -> &[String] {
+        match self {
+            SandboxPolicy::WorkspaceWrite {
+                network_access: false,
+                network_allowlist,
+                ..
+            } => network_allowlist,
+            _ => &[],
+        }
+    }
+
     /// Returns the list of writable roots (tailored to the current working
     /// directory) together with subpaths that should remain read‑only under
     /// each writable root.
@@ -421,6 +510,8 @@ impl SandboxPolicy {
                 exclude_slash_tmp,
                 allow_git_writes,
                 network_access: _,
+                network_allowlist: _,
+                read_denied_paths: _,
             } => {
                 // Start from explicitly configured writable roots.
                 let mut roots: Vec<PathBuf> = writable_roots.clone();
@@ -535,8 +626,13 @@ pub fn event_msg_to_protocol(msg: &EventMsg) -> Option<code_protocol::protocol::
                 .rate_limits
                 .as_ref()
                 .map(rate_limit_snapshot_to_protocol);
+            let provider_usage = convert_value(&payload.provider_usage).unwrap_or_default();
             Some(code_protocol::protocol::EventMsg::TokenCount(
-                code_protocol::protocol::TokenCountEvent { info, rate_limits },
+                code_protocol::protocol::TokenCountEvent {
+                    info,
+                    rate_limits,
+                    provider_usage,
+                },
             ))
         }
         _ => convert_value(msg),
@@ -552,7 +648,12 @@ pub fn event_msg_from_protocol(msg: &code_protocol::protocol::EventMsg) -> Optio
                 .rate_limits
                 .as_ref()
                 .map(rate_limit_snapshot_from_protocol);
-            Some(EventMsg::TokenCount(TokenCountEvent { info, rate_limits }))
+            let provider_usage = convert_value(&payload.provider_usage).unwrap_or_default();
+            Some(EventMsg::TokenCount(TokenCountEvent {
+                info,
+                rate_limits,
+                provider_usage,
+            }))
         }
         _ => {
             let converted = convert_value(msg)?;
@@ -785,10 +886,16 @@ pub enum EventMsg {
 
     ExecCommandEnd(ExecCommandEndEvent),
 
+    /// A sandboxed command failed because the sandbox denied it something.
+    SandboxDenial(SandboxDenialEvent),
+
     ExecApprovalRequest(ExecApprovalRequestEvent),
 
     ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent),
 
+    /// An MCP server is asking the user for input mid-request.
+    ElicitationRequest(ElicitationRequestEvent),
+
     BackgroundEvent(BackgroundEventEvent),
 
     /// Notification that the agent is about to apply a code patch. Mirrors
@@ -806,6 +913,9 @@ pub enum EventMsg {
     /// List of custom prompts available to the agent.
     ListCustomPromptsResponse(ListCustomPromptsResponseEvent),
 
+    /// List of resources advertised by connected MCP servers.
+    ListMcpResourcesResponse(ListMcpResourcesResponseEvent),
+
     /// List of skills available to the agent.
     ListSkillsResponse(ListSkillsResponseEvent),
 
@@ -986,6 +1096,10 @@ pub struct RateLimitSnapshotEvent {
 pub struct TokenCountEvent {
     pub info: Option<TokenUsageInfo>,
     pub rate_limits: Option<RateLimitSnapshotEvent>,
+    /// Session token usage per `provider:model`, reported once a
+    /// `[model_failover]` entry has served part of the session.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub provider_usage: BTreeMap<String, TokenUsage>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -1162,6 +1276,35 @@ pub struct ExecCommandEndEvent {
     pub exit_code: i32,
     /// The duration of the command execution.
     pub duration: Duration,
+    /// Resources the command consumed, read from its cgroup after exit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource_usage: Option<ExecResourceUsage>,
+}
+
+/// Resource accounting for a finished command (Linux cgroup v2 only).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ExecResourceUsage {
+    /// High-water mark of the cgroup's memory use (`memory.peak`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peak_memory_bytes: Option<u64>,
+    /// User plus system CPU time across the whole process tree (`cpu.stat`).
+    pub cpu_time: Duration,
+}
+
+impl fmt::Display for ExecResourceUsage {
+    /// Renders e.g. `34.2 CPU-s, peak 1.2 GiB`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const MIB: f64 = 1024.0 * 1024.0;
+        const GIB: f64 = MIB * 1024.0;
+        write!(f, "{:.1} CPU-s", self.cpu_time.as_secs_f64())?;
+        match self.peak_memory_bytes {
+            Some(bytes) if bytes as f64 >= GIB => {
+                write!(f, ", peak {:.1} GiB", bytes as f64 / GIB)
+            }
+            Some(bytes) => write!(f, ", peak {:.0} MiB", bytes as f64 / MIB),
+            None => Ok(()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -1193,6 +1336,64 @@ pub struct ExecApprovalRequestEvent {
     /// Optional human-readable reason for the approval (e.g. retry without sandbox).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reason: Option<String>,
+    /// When set, approving reruns the command in the sandbox with this root
+    /// added to the writable roots for the remainder of the session.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grant_root: Option<PathBuf>,
+}
+
+/// What a sandboxed command was prevented from doing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SandboxDenialKind {
+    /// Writing outside the writable roots.
+    Write,
+    /// Reading one of the sandbox's `read_denied_paths`.
+    Read,
+    /// Opening a network connection.
+    Network,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SandboxDenial {
+    pub kind: SandboxDenialKind,
+    /// The denied path, for file system denials.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    /// Writable root that would let the command succeed, when granting one
+    /// is enough.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggested_writable_root: Option<PathBuf>,
+    /// The output line the denial was recognized from.
+    pub evidence: String,
+}
+
+impl fmt::Display for SandboxDenial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.kind, &self.path) {
+            (SandboxDenialKind::Write, Some(path)) => write!(
+                f,
+                "write to {} denied: outside the sandbox's writable roots",
+                path.display()
+            ),
+            (SandboxDenialKind::Read, Some(path)) => write!(
+                f,
+                "read of {} denied: listed in the sandbox's read_denied_paths",
+                path.display()
+            ),
+            (SandboxDenialKind::Write | SandboxDenialKind::Read, None) => {
+                write!(f, "file system access denied by the sandbox")
+            }
+            (SandboxDenialKind::Network, _) => write!(f, "network access denied by the sandbox"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SandboxDenialEvent {
+    /// Identifier for the exec call that was denied.
+    pub call_id: String,
+    pub denial: SandboxDenial,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -1270,6 +1471,9 @@ pub struct BrowserScreenshotUpdateEvent {
     pub screenshot_path: PathBuf,
     /// Current URL of the browser
     pub url: String,
+    /// CDP target id of the tab the screenshot was taken from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tab_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -1445,4 +1649,19 @@ mod tests {
             r#"{"id":"1234","event_seq":0,"msg":{"type":"session_configured","session_id":"67e55044-10b1-426f-9247-bb680e5fe0c8","model":"codex-mini-latest","history_log_id":0,"history_entry_count":0}}"#
         );
     }
+
+    #[test]
+    fn exec_resource_usage_display() {
+        let usage = ExecResourceUsage {
+            peak_memory_bytes: Some(3 * 1024 * 1024 * 1024 / 2),
+            cpu_time: Duration::from_millis(34_240),
+        };
+        assert_eq!(usage.to_string(), "34.2 CPU-s, peak 1.5 GiB");
+
+        let usage = ExecResourceUsage {
+            peak_memory_bytes: Some(200 * 1024 * 1024),
+            cpu_time: Duration::from_millis(500),
+        };
+        assert_eq!(usage.to_string(), "0.5 CPU-s, peak 200 MiB");
+    }
 }
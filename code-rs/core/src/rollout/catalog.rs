@@ -198,7 +198,6 @@ impl SessionCatalog {
     }
 
     /// Get an entry by session ID.
-    #[allow(dead_code)]
     pub fn get(&self, session_id: &Uuid) -> Option<&SessionIndexEntry> {
         self.entries.get(session_id)
     }
@@ -272,7 +271,6 @@ impl SessionCatalog {
     }
 
     /// Remove an entry by session ID.
-    #[allow(dead_code)]
     pub fn remove(&mut self, session_id: &Uuid) -> io::Result<()> {
         if let Some(entry) = self.entries.remove(session_id) {
             self.remove_from_indexes(session_id, &entry);
@@ -313,6 +311,11 @@ impl SessionCatalog {
         for (session_id, entry) in discovered_entries {
             if let Some(existing) = self.entries.get(&session_id).cloned() {
                 if should_replace(&existing, &entry) {
+                    // Archived/deleted are set through the catalog, not the
+                    // rollout, so a rescan must not reset them.
+                    let mut entry = entry;
+                    entry.archived = existing.archived;
+                    entry.deleted = existing.deleted;
                     self.remove_from_indexes(&session_id, &existing);
                     self.index_entry(entry);
                     result.updated += 1;
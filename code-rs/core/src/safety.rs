@@ -7,6 +7,8 @@ use code_apply_patch::ApplyPatchAction;
 use code_apply_patch::ApplyPatchFileChange;
 
 use crate::codex::ApprovedCommandPattern;
+use crate::command_safety::exec_policy::ExecPolicyOverlay;
+use crate::command_safety::exec_policy::PolicyVerdict;
 use crate::exec::SandboxType;
 use crate::is_safe_command::is_known_safe_command;
 use crate::protocol::AskForApproval;
@@ -96,13 +98,24 @@ pub fn assess_patch_safety(
 /// - the user has explicitly approved the command
 /// - the command is on the "known safe" list
 /// - `DangerFullAccess` was specified and `UnlessTrusted` was not
+///
+/// Commands forbidden by the session's execpolicy overlays are rejected, and
+/// commands they allow run in the sandbox without asking the user.
 pub fn assess_command_safety(
     command: &[String],
     approval_policy: AskForApproval,
     sandbox_policy: &SandboxPolicy,
     approved: &HashSet<ApprovedCommandPattern>,
+    exec_policy: Option<&ExecPolicyOverlay>,
     with_escalated_permissions: bool,
 ) -> SafetyCheck {
+    let policy_verdict = exec_policy.map(|policy| policy.check(command));
+    if let Some(PolicyVerdict::Forbidden { reason }) = policy_verdict.as_ref() {
+        return SafetyCheck::Reject {
+            reason: format!("forbidden by execpolicy: {reason}"),
+        };
+    }
+
     // A command is "trusted" because either:
     // - it belongs to a set of commands we consider "safe" by default, or
     // - the user has explicitly approved the command for this session
@@ -128,7 +141,22 @@ pub fn assess_command_safety(
         };
     }
 
-    assess_safety_for_untrusted_command(approval_policy, sandbox_policy, with_escalated_permissions)
+    let untrusted = assess_safety_for_untrusted_command(
+        approval_policy,
+        sandbox_policy,
+        with_escalated_permissions,
+    );
+    if untrusted == SafetyCheck::AskUser
+        && policy_verdict == Some(PolicyVerdict::Allowed)
+        && !with_escalated_permissions
+        && let Some(sandbox_type) = get_platform_sandbox()
+    {
+        return SafetyCheck::AutoApprove {
+            sandbox_type,
+            user_explicitly_approved: false,
+        };
+    }
+    untrusted
 }
 
 pub(crate) fn assess_safety_for_untrusted_command(
@@ -305,9 +333,11 @@ mod tests {
         let policy_workspace_only = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![],
             network_access: false,
+            network_allowlist: vec![],
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
             allow_git_writes: true,
+            read_denied_paths: vec![],
         };
 
         assert!(is_write_patch_constrained_to_writable_paths(
@@ -327,9 +357,11 @@ mod tests {
         let policy_with_parent = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![parent.clone()],
             network_access: false,
+            network_allowlist: vec![],
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
             allow_git_writes: true,
+            read_denied_paths: vec![],
         };
         assert!(is_write_patch_constrained_to_writable_paths(
             &add_outside,
@@ -394,6 +426,7 @@ mod tests {
             approval_policy,
             &sandbox_policy,
             &approved,
+            None,
             request_escalated_privileges,
         );
 
@@ -413,6 +446,7 @@ mod tests {
             approval_policy,
             &sandbox_policy,
             &approved,
+            None,
             request_escalated_privileges,
         );
 
@@ -425,4 +459,59 @@ mod tests {
         };
         assert_eq!(safety_check, expected);
     }
+
+    #[test]
+    fn exec_policy_overlays_allow_and_forbid_commands() {
+        let code_home = TempDir::new().unwrap();
+        let cwd = TempDir::new().unwrap();
+        std::fs::write(
+            code_home.path().join("team.policy"),
+            r#"
+define_program(program="bazel", args=["query", ARG_OPAQUE_VALUE])
+forbid_program_regex(regex="^deploy$", reason="deploys must go through CI")
+"#,
+        )
+        .unwrap();
+        let overlay = ExecPolicyOverlay::load(code_home.path(), cwd.path())
+            .unwrap()
+            .unwrap();
+        let approved: HashSet<ApprovedCommandPattern> = HashSet::new();
+        let assess = |command: &[&str], exec_policy: Option<&ExecPolicyOverlay>| {
+            let command: Vec<String> = command.iter().map(ToString::to_string).collect();
+            assess_command_safety(
+                &command,
+                AskForApproval::UnlessTrusted,
+                &SandboxPolicy::ReadOnly,
+                &approved,
+                exec_policy,
+                false,
+            )
+        };
+
+        assert_eq!(
+            assess(&["bazel", "query", "//..."], None),
+            SafetyCheck::AskUser
+        );
+        let expected = match get_platform_sandbox() {
+            Some(sandbox_type) => SafetyCheck::AutoApprove {
+                sandbox_type,
+                user_explicitly_approved: false,
+            },
+            None => SafetyCheck::AskUser,
+        };
+        assert_eq!(
+            assess(&["bazel", "query", "//..."], Some(&overlay)),
+            expected
+        );
+        assert_eq!(
+            assess(&["bazel", "build", "//..."], Some(&overlay)),
+            SafetyCheck::AskUser
+        );
+        assert_eq!(
+            assess(&["deploy"], Some(&overlay)),
+            SafetyCheck::Reject {
+                reason: "forbidden by execpolicy: deploys must go through CI".to_string(),
+            }
+        );
+    }
 }
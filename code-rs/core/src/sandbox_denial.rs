@@ -0,0 +1,473 @@
+//! Recognizing command failures that the sandbox caused.
+//!
+//! Landlock, seccomp and Seatbelt fail the offending system call with
+//! `EACCES`, `EPERM` or `EROFS` and record nothing else, so the only trace of a
+//! denial is what the command printed about it. We pick the target out of the
+//! failure message and audit it against the policy the command ran under: a
+//! path is only reported when the policy kept the command from it and the
+//! user could otherwise have written it. No tracing of the command is needed.
+
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::protocol::SandboxDenial;
+use crate::protocol::SandboxDenialKind;
+use crate::protocol::SandboxPolicy;
+use crate::protocol::WritableRoot;
+
+/// Lowercased fragments of the messages tools print for a failed file system
+/// call.
+const FS_DENIAL_MARKERS: &[&str] = &[
+    "permission denied",
+    "read-only file system",
+    "operation not permitted",
+    "eacces",
+    "erofs",
+    "eperm",
+];
+
+/// Lowercased fragments of the messages tools print when they cannot reach
+/// the network.
+const NETWORK_DENIAL_MARKERS: &[&str] = &[
+    "could not resolve host",
+    "temporary failure in name resolution",
+    "name or service not known",
+    "network is unreachable",
+    "getaddrinfo enotfound",
+    "getaddrinfo eai_again",
+];
+
+/// Start of the lines appended for connections the egress proxy refused.
+const EGRESS_DENIAL_PREFIX: &str = "code-linux-sandbox: blocked network access";
+
+/// How many earlier lines to search for the path when the failure line does
+/// not name one, as in cargo's `Caused by:` chains.
+const PATH_LOOKBEHIND_LINES: usize = 3;
+
+/// System directories that are never suggested as writable roots, nor is
+/// anything below them.
+const SYSTEM_DIRS: &[&str] = &[
+    "/bin",
+    "/boot",
+    "/dev",
+    "/etc",
+    "/lib",
+    "/lib64",
+    "/proc",
+    "/sbin",
+    "/sys",
+    "/usr",
+    "/Applications",
+    "/Library",
+    "/System",
+];
+
+/// Returns the sandbox denials evident in the output of a failed command that
+/// ran under `policy`, at most one per path.
+pub(crate) fn detect_sandbox_denials(
+    output: &str,
+    policy: &SandboxPolicy,
+    sandbox_cwd: &Path,
+    command_cwd: &Path,
+) -> Vec<SandboxDenial> {
+    if matches!(policy, SandboxPolicy::DangerFullAccess) {
+        return Vec::new();
+    }
+    let writable_roots = policy.get_writable_roots_with_cwd(sandbox_cwd);
+    let read_denied_paths = policy.get_read_denied_paths();
+    let lines: Vec<&str> = output.lines().collect();
+
+    let mut denials: Vec<SandboxDenial> = Vec::new();
+    for (idx, line) in lines.iter().enumerate() {
+        let lower = line.to_ascii_lowercase();
+        let denial = if line.starts_with(EGRESS_DENIAL_PREFIX)
+            || (!policy.has_full_network_access() && is_network_failure(&lower))
+        {
+            Some(SandboxDenial {
+                kind: SandboxDenialKind::Network,
+                path: None,
+                suggested_writable_root: None,
+                evidence: line.trim().to_string(),
+            })
+        } else if FS_DENIAL_MARKERS
+            .iter()
+            .any(|marker| lower.contains(marker))
+        {
+            let mut candidates = path_candidates(line);
+            if candidates.is_empty() {
+                for previous in lines[idx.saturating_sub(PATH_LOOKBEHIND_LINES)..idx]
+                    .iter()
+                    .rev()
+                {
+                    candidates.extend(path_candidates(previous));
+                }
+            }
+            candidates.into_iter().find_map(|candidate| {
+                let path = normalize_path(&command_cwd.join(candidate));
+                classify_path(path, line, policy, &writable_roots, &read_denied_paths)
+            })
+        } else {
+            None
+        };
+
+        let Some(denial) = denial else {
+            continue;
+        };
+        if !denials
+            .iter()
+            .any(|seen| seen.kind == denial.kind && seen.path == denial.path)
+        {
+            denials.push(denial);
+        }
+    }
+    denials
+}
+
+fn is_network_failure(lower: &str) -> bool {
+    NETWORK_DENIAL_MARKERS
+        .iter()
+        .any(|marker| lower.contains(marker))
+        || (lower.contains("socket") && lower.contains("operation not permitted"))
+}
+
+/// Works out whether the policy is why `path` could not be accessed.
+fn classify_path(
+    path: PathBuf,
+    line: &str,
+    policy: &SandboxPolicy,
+    writable_roots: &[WritableRoot],
+    read_denied_paths: &[PathBuf],
+) -> Option<SandboxDenial> {
+    if read_denied_paths
+        .iter()
+        .any(|denied| path.starts_with(denied))
+    {
+        return Some(SandboxDenial {
+            kind: SandboxDenialKind::Read,
+            path: Some(path),
+            suggested_writable_root: None,
+            evidence: line.trim().to_string(),
+        });
+    }
+    if writable_roots
+        .iter()
+        .any(|root| root.is_path_writable(&path))
+    {
+        return None;
+    }
+    // Without the sandbox the write would fail as well, e.g. because of file
+    // permissions, so adding a writable root would not help.
+    let directory = nearest_existing_dir(&path)?;
+    if !writable_outside_sandbox(&path, &directory) {
+        return None;
+    }
+    let suggested_writable_root = match policy {
+        SandboxPolicy::WorkspaceWrite { .. } => {
+            let home = dirs::home_dir();
+            let code_home = crate::config::find_code_home().ok();
+            Some(directory).filter(|directory| {
+                is_suggestable_root(&path, directory, home.as_deref(), code_home.as_deref())
+            })
+        }
+        _ => None,
+    };
+    Some(SandboxDenial {
+        kind: SandboxDenialKind::Write,
+        path: Some(path),
+        suggested_writable_root,
+        evidence: line.trim().to_string(),
+    })
+}
+
+/// Whether `directory` is narrow enough to offer as a writable root for a
+/// denied write to `path`: it must be the path itself or its immediate
+/// parent, and must not be a system directory, the home directory, the Code
+/// home, or anything containing them.
+fn is_suggestable_root(
+    path: &Path,
+    directory: &Path,
+    home: Option<&Path>,
+    code_home: Option<&Path>,
+) -> bool {
+    if directory != path && Some(directory) != path.parent() {
+        return false;
+    }
+    let depth = directory
+        .components()
+        .filter(|component| matches!(component, Component::Normal(_)))
+        .count();
+    if depth < 2
+        || SYSTEM_DIRS
+            .iter()
+            .any(|system_dir| directory.starts_with(system_dir))
+    {
+        return false;
+    }
+    if home.is_some_and(|home| home.starts_with(directory)) {
+        return false;
+    }
+    !code_home.is_some_and(|code_home| {
+        code_home.starts_with(directory) || directory.starts_with(code_home)
+    })
+}
+
+/// Quoted strings and path-like words in a failure message, most likely
+/// first.
+fn path_candidates(line: &str) -> Vec<String> {
+    let mut candidates = Vec::new();
+    for (open, close) in [('\'', '\''), ('"', '"'), ('`', '`'), ('‘', '’')] {
+        let mut rest = line;
+        while let Some(start) = rest.find(open) {
+            let after_open = &rest[start + open.len_utf8()..];
+            let Some(len) = after_open.find(close) else {
+                break;
+            };
+            let quoted = &after_open[..len];
+            if !quoted.trim().is_empty() {
+                candidates.push(quoted.to_string());
+            }
+            rest = &after_open[len + close.len_utf8()..];
+        }
+    }
+    // `bash: /path: Permission denied` and `sh: 1: cannot create /path: ...`
+    for word in line.split(|c: char| c.is_whitespace() || c == ':') {
+        let word = word.trim_end_matches([',', ';']);
+        if word.starts_with('/') || word.starts_with("./") || word.starts_with("../") {
+            candidates.push(word.to_string());
+        }
+    }
+    candidates
+}
+
+/// Resolves `.` and `..` without touching the file system, since the path
+/// may not exist.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    normalized
+}
+
+/// The directory that has to be writable for `path` to be written: the path
+/// itself when it is a directory, otherwise its closest existing ancestor.
+fn nearest_existing_dir(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|ancestor| ancestor.is_dir())
+        .map(Path::to_path_buf)
+}
+
+#[cfg(unix)]
+fn writable_outside_sandbox(path: &Path, directory: &Path) -> bool {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let target = if path.exists() { path } else { directory };
+    let Ok(target) = CString::new(target.as_os_str().as_bytes()) else {
+        return false;
+    };
+    // SAFETY: `target` is a valid NUL-terminated string for the whole call.
+    unsafe { libc::access(target.as_ptr(), libc::W_OK) == 0 }
+}
+
+#[cfg(not(unix))]
+fn writable_outside_sandbox(_path: &Path, _directory: &Path) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn workspace_policy(read_denied_paths: Vec<PathBuf>) -> SandboxPolicy {
+        SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![],
+            network_access: false,
+            network_allowlist: vec![],
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+            allow_git_writes: true,
+            read_denied_paths,
+        }
+    }
+
+    #[test]
+    fn write_outside_writable_roots_suggests_parent_dir() {
+        let tmp = TempDir::new().unwrap();
+        let workspace = tmp.path().join("workspace");
+        let outside = tmp.path().join("outside");
+        std::fs::create_dir_all(&workspace).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        let target = outside.join("out.txt");
+        let output = format!(
+            "touch: cannot touch '{}': Permission denied\n",
+            target.display()
+        );
+
+        let denials =
+            detect_sandbox_denials(&output, &workspace_policy(vec![]), &workspace, &workspace);
+
+        assert_eq!(
+            denials,
+            vec![SandboxDenial {
+                kind: SandboxDenialKind::Write,
+                path: Some(target.clone()),
+                suggested_writable_root: Some(outside),
+                evidence: output.trim().to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn broad_or_sensitive_directories_are_not_suggested() {
+        let home = Path::new("/home/dev");
+        let code_home = Path::new("/home/dev/.code");
+        let suggestable = |path: &str, directory: &str| {
+            is_suggestable_root(
+                Path::new(path),
+                Path::new(directory),
+                Some(home),
+                Some(code_home),
+            )
+        };
+
+        assert!(suggestable(
+            "/home/dev/.cache/pip/x",
+            "/home/dev/.cache/pip"
+        ));
+        assert!(suggestable("/srv/data/out", "/srv/data/out"));
+        // Only the path or its immediate parent, never a distant ancestor.
+        assert!(!suggestable("/srv/data/a/b/c", "/srv/data"));
+        assert!(!suggestable("/home/dev/notes.txt", "/home/dev"));
+        assert!(!suggestable(
+            "/home/dev/.code/config.toml",
+            "/home/dev/.code"
+        ));
+        assert!(!suggestable("/home/dev/.code/log/x", "/home/dev/.code/log"));
+        assert!(!suggestable("/etc/motd", "/etc"));
+        assert!(!suggestable("/usr/local/lib/x", "/usr/local/lib"));
+        assert!(!suggestable("/opt/x", "/opt"));
+        assert!(!suggestable("/x", "/"));
+    }
+
+    #[test]
+    fn relative_paths_resolve_against_command_cwd() {
+        let tmp = TempDir::new().unwrap();
+        let workspace = tmp.path().join("workspace");
+        let sibling = tmp.path().join("sibling");
+        std::fs::create_dir_all(&workspace).unwrap();
+        std::fs::create_dir_all(&sibling).unwrap();
+        let output = "mkdir: cannot create directory ‘../sibling/build’: Permission denied";
+
+        let denials =
+            detect_sandbox_denials(output, &workspace_policy(vec![]), &workspace, &workspace);
+
+        assert_eq!(denials.len(), 1);
+        assert_eq!(denials[0].path, Some(sibling.join("build")));
+        assert_eq!(denials[0].suggested_writable_root, Some(sibling));
+    }
+
+    #[test]
+    fn path_on_an_earlier_line_is_used() {
+        let tmp = TempDir::new().unwrap();
+        let workspace = tmp.path().join("workspace");
+        let cache = tmp.path().join("cache");
+        std::fs::create_dir_all(&workspace).unwrap();
+        std::fs::create_dir_all(&cache).unwrap();
+        let output = format!(
+            "error: failed to create directory `{}`\n\nCaused by:\n  Permission denied (os error 13)\n",
+            cache.join("registry").display()
+        );
+
+        let denials =
+            detect_sandbox_denials(&output, &workspace_policy(vec![]), &workspace, &workspace);
+
+        assert_eq!(denials.len(), 1);
+        assert_eq!(denials[0].path, Some(cache.join("registry")));
+        assert_eq!(denials[0].evidence, "Permission denied (os error 13)");
+    }
+
+    #[test]
+    fn writes_inside_writable_roots_are_not_denials() {
+        let tmp = TempDir::new().unwrap();
+        let output = format!(
+            "bash: {}: Permission denied",
+            tmp.path().join("script.sh").display()
+        );
+
+        let denials =
+            detect_sandbox_denials(&output, &workspace_policy(vec![]), tmp.path(), tmp.path());
+
+        assert_eq!(denials, Vec::new());
+    }
+
+    #[test]
+    fn read_denied_paths_are_reported_without_a_suggestion() {
+        let tmp = TempDir::new().unwrap();
+        let secrets = tmp.path().join("secrets");
+        let output = format!(
+            "PermissionError: [Errno 13] Permission denied: '{}'",
+            secrets.join("token").display()
+        );
+
+        let denials = detect_sandbox_denials(
+            &output,
+            &workspace_policy(vec![secrets.clone()]),
+            tmp.path(),
+            tmp.path(),
+        );
+
+        assert_eq!(denials.len(), 1);
+        assert_eq!(denials[0].kind, SandboxDenialKind::Read);
+        assert_eq!(denials[0].path, Some(secrets.join("token")));
+        assert_eq!(denials[0].suggested_writable_root, None);
+    }
+
+    #[test]
+    fn network_failures_are_reported_only_without_network_access() {
+        let tmp = TempDir::new().unwrap();
+        let output = "curl: (6) Could not resolve host: example.com";
+
+        let denials =
+            detect_sandbox_denials(output, &workspace_policy(vec![]), tmp.path(), tmp.path());
+        assert_eq!(denials.len(), 1);
+        assert_eq!(denials[0].kind, SandboxDenialKind::Network);
+
+        let policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![],
+            network_access: true,
+            network_allowlist: vec![],
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+            allow_git_writes: true,
+            read_denied_paths: vec![],
+        };
+        assert_eq!(
+            detect_sandbox_denials(output, &policy, tmp.path(), tmp.path()),
+            Vec::new()
+        );
+    }
+
+    #[test]
+    fn full_access_never_reports_denials() {
+        let output = "touch: cannot touch '/etc/motd': Permission denied";
+
+        let denials = detect_sandbox_denials(
+            output,
+            &SandboxPolicy::DangerFullAccess,
+            Path::new("/"),
+            Path::new("/"),
+        );
+
+        assert_eq!(denials, Vec::new());
+    }
+}
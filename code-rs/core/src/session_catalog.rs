@@ -4,11 +4,13 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
 use code_protocol::protocol::SessionSource;
 use once_cell::sync::OnceCell;
 use tokio::task;
 use tokio::sync::Mutex as AsyncMutex;
+use uuid::Uuid;
 
 use crate::rollout::catalog::{self as rollout_catalog, SessionIndexEntry};
 
@@ -19,6 +21,14 @@ pub struct SessionQuery {
     pub cwd: Option<PathBuf>,
     /// Filter by git project root.
     pub git_root: Option<PathBuf>,
+    /// Only sessions whose working directory is this path or below it.
+    pub cwd_within: Option<PathBuf>,
+    /// Filter by git branch (exact match).
+    pub git_branch: Option<String>,
+    /// Only sessions whose last event is at or after this time.
+    pub updated_after: Option<DateTime<Utc>>,
+    /// Only sessions whose last event is before this time.
+    pub updated_before: Option<DateTime<Utc>>,
     /// Restrict to these sources; empty = all sources.
     pub sources: Vec<SessionSource>,
     /// Minimum number of user messages required.
@@ -74,6 +84,30 @@ impl SessionCatalog {
                     continue;
                 }
             }
+            if let Some(root) = &query.cwd_within {
+                if !entry.cwd_real.starts_with(root) {
+                    continue;
+                }
+            }
+            if let Some(branch) = &query.git_branch {
+                if entry.git_branch.as_ref() != Some(branch) {
+                    continue;
+                }
+            }
+            if query.updated_after.is_some() || query.updated_before.is_some() {
+                let Some(last_event) = last_event_time(entry) else {
+                    continue;
+                };
+                if query.updated_after.is_some_and(|after| last_event < after) {
+                    continue;
+                }
+                if query
+                    .updated_before
+                    .is_some_and(|before| last_event >= before)
+                {
+                    continue;
+                }
+            }
             if !query.sources.is_empty() && !query.sources.contains(&entry.session_source) {
                 continue;
             }
@@ -101,18 +135,44 @@ impl SessionCatalog {
         let entry = catalog
             .all_ordered()
             .into_iter()
-            .find(|entry| {
-                entry
-                    .session_id
-                    .to_string()
-                    .to_ascii_lowercase()
-                    .starts_with(&needle)
-            })
+            .find(|entry| id_has_prefix(entry, &needle))
             .cloned();
 
         Ok(entry)
     }
 
+    /// Resolve `id_prefix` to exactly one session, archived and deleted ones
+    /// included. Fails when the prefix is empty, matches nothing, or matches
+    /// several sessions, in which case the error lists them.
+    pub async fn find_unique_by_prefix(&self, id_prefix: &str) -> Result<SessionIndexEntry> {
+        let needle = id_prefix.trim().to_ascii_lowercase();
+        if needle.is_empty() {
+            bail!("session id must not be empty");
+        }
+        let catalog = self.load_inner().await?;
+        let matches: Vec<&SessionIndexEntry> = catalog
+            .all_ordered()
+            .into_iter()
+            .filter(|entry| id_has_prefix(entry, &needle))
+            .collect();
+
+        match matches.as_slice() {
+            [] => bail!("no session matches `{id_prefix}`"),
+            [entry] => Ok((*entry).clone()),
+            many => {
+                let candidates: Vec<String> = many
+                    .iter()
+                    .map(|entry| format!("  {}  {}", entry.session_id, entry.last_event_at))
+                    .collect();
+                bail!(
+                    "`{id_prefix}` matches {} sessions; use a longer prefix:\n{}",
+                    many.len(),
+                    candidates.join("\n")
+                )
+            }
+        }
+    }
+
     /// Return the newest session matching the query.
     pub async fn get_latest(&self, query: &SessionQuery) -> Result<Option<SessionIndexEntry>> {
         let mut limited = query.clone();
@@ -121,6 +181,80 @@ impl SessionCatalog {
         Ok(rows.pop())
     }
 
+    /// Mark a session archived (or restore it). Archived sessions are hidden
+    /// from queries unless `include_archived` is set.
+    pub async fn set_archived(
+        &self,
+        session_id: Uuid,
+        archived: bool,
+    ) -> Result<Option<SessionIndexEntry>> {
+        self.update_entry(session_id, |entry| entry.archived = archived)
+            .await
+    }
+
+    /// Mark a session deleted (or restore it) without touching its rollout.
+    pub async fn set_deleted(
+        &self,
+        session_id: Uuid,
+        deleted: bool,
+    ) -> Result<Option<SessionIndexEntry>> {
+        self.update_entry(session_id, |entry| entry.deleted = deleted)
+            .await
+    }
+
+    /// Remove a session's rollout and snapshot files along with its catalog
+    /// entry. Returns the removed entry.
+    pub async fn purge(&self, session_id: Uuid) -> Result<Option<SessionIndexEntry>> {
+        self.load_inner().await?;
+        let mut guard = self.cache.lock().await;
+        let Some(catalog) = guard.as_mut() else {
+            return Ok(None);
+        };
+        let Some(entry) = catalog.get(&session_id).cloned() else {
+            return Ok(None);
+        };
+
+        let files = std::iter::once(&entry.rollout_path).chain(entry.snapshot_path.as_ref());
+        for relative in files {
+            let path = self.code_home.join(relative);
+            match std::fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => {
+                    return Err(err)
+                        .with_context(|| format!("failed to remove {}", path.display()));
+                }
+            }
+        }
+        catalog
+            .remove(&session_id)
+            .context("failed to update session catalog")?;
+        Ok(Some(entry))
+    }
+
+    async fn update_entry<F>(
+        &self,
+        session_id: Uuid,
+        update: F,
+    ) -> Result<Option<SessionIndexEntry>>
+    where
+        F: FnOnce(&mut SessionIndexEntry),
+    {
+        self.load_inner().await?;
+        let mut guard = self.cache.lock().await;
+        let Some(catalog) = guard.as_mut() else {
+            return Ok(None);
+        };
+        let Some(mut entry) = catalog.get(&session_id).cloned() else {
+            return Ok(None);
+        };
+        update(&mut entry);
+        catalog
+            .upsert(entry.clone())
+            .context("failed to update session catalog")?;
+        Ok(Some(entry))
+    }
+
     /// Convert a catalog entry to an absolute rollout path.
     pub fn entry_rollout_path(&self, entry: &SessionIndexEntry) -> PathBuf {
         entry_to_rollout_path(&self.code_home, entry)
@@ -155,6 +289,20 @@ impl SessionCatalog {
     }
 }
 
+fn id_has_prefix(entry: &SessionIndexEntry, needle: &str) -> bool {
+    entry
+        .session_id
+        .to_string()
+        .to_ascii_lowercase()
+        .starts_with(needle)
+}
+
+fn last_event_time(entry: &SessionIndexEntry) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(&entry.last_event_at)
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
+
 /// Helper to convert an entry to an absolute rollout path.
 pub fn entry_to_rollout_path(code_home: &Path, entry: &SessionIndexEntry) -> PathBuf {
     code_home.join(&entry.rollout_path)
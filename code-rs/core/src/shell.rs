@@ -311,6 +311,7 @@ mod tests {
         for (input, expected_cmd, expected_output) in cases {
             use std::collections::HashMap;
 
+            use crate::config_types::ExecLimits;
             use crate::exec::ExecParams;
             use crate::exec::SandboxType;
             use crate::exec::process_exec_tool_call;
@@ -356,6 +357,7 @@ mod tests {
                     )]),
                     with_escalated_permissions: None,
                     justification: None,
+                    exec_limits: ExecLimits::default(),
                 },
                 SandboxType::None,
                 &SandboxPolicy::DangerFullAccess,
@@ -421,6 +423,7 @@ mod macos_tests {
             use std::collections::HashMap;
             use std::path::PathBuf;
 
+            use crate::config_types::ExecLimits;
             use crate::exec::ExecParams;
             use crate::exec::SandboxType;
             use crate::exec::process_exec_tool_call;
@@ -467,6 +470,7 @@ mod macos_tests {
                     )]),
                     with_escalated_permissions: None,
                     justification: None,
+                    exec_limits: ExecLimits::default(),
                 },
                 SandboxType::None,
                 &SandboxPolicy::DangerFullAccess,
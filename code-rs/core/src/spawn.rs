@@ -8,6 +8,7 @@ use tokio::process::Command;
 use tokio::time::sleep;
 use tracing::trace;
 
+use crate::config_types::ExecLimits;
 use crate::protocol::SandboxPolicy;
 
 /// Experimental environment variable that will be set to some non-empty value
@@ -97,6 +98,9 @@ pub enum StdioPolicy {
 /// For now, we take `SandboxPolicy` as a parameter to spawn_child() because
 /// we need to determine whether to set the
 /// `CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR` environment variable.
+///
+/// When `exec_limits` is set, the child joins a per-command cgroup with those
+/// limits (Linux only).
 pub(crate) async fn spawn_child_async(
     program: PathBuf,
     args: Vec<String>,
@@ -105,6 +109,7 @@ pub(crate) async fn spawn_child_async(
     sandbox_policy: &SandboxPolicy,
     stdio_policy: StdioPolicy,
     env: HashMap<String, String>,
+    #[cfg_attr(not(target_os = "linux"), allow(unused_variables))] exec_limits: Option<&ExecLimits>,
 ) -> std::io::Result<Child> {
     trace!(
         "spawn_child_async: {program:?} {args:?} {arg0:?} {cwd:?} {sandbox_policy:?} {stdio_policy:?} {env:?}"
@@ -128,13 +133,14 @@ pub(crate) async fn spawn_child_async(
 
     // Ensure children form their own process group; on timeout we can kill the group.
     // Also, on Linux, set PDEATHSIG so children die if parent dies.
+    // Rendered before fork: the `pre_exec` hook must not allocate.
+    #[cfg(target_os = "linux")]
+    let exec_cgroup = exec_limits
+        .map(crate::cgroup::ExecCgroupLimits::from_config)
+        .as_ref()
+        .and_then(crate::cgroup::PreparedExecCgroup::prepare);
     #[cfg(unix)]
     unsafe {
-        #[cfg(target_os = "linux")]
-        let exec_memory_max_bytes = match stdio_policy {
-            StdioPolicy::RedirectForShellTool => crate::cgroup::default_exec_memory_max_bytes(),
-            StdioPolicy::Inherit => None,
-        };
         cmd.pre_exec(move || {
             // Start a new process group
             let _ = libc::setpgid(0, 0);
@@ -147,11 +153,8 @@ pub(crate) async fn spawn_child_async(
                     libc::raise(libc::SIGTERM);
                 }
 
-                if let Some(memory_max_bytes) = exec_memory_max_bytes {
-                    crate::cgroup::best_effort_attach_self_to_exec_cgroup(
-                        libc::getpid() as u32,
-                        memory_max_bytes,
-                    );
+                if let Some(cgroup) = exec_cgroup.as_ref() {
+                    cgroup.attach_self();
                 }
             }
             Ok(())
@@ -0,0 +1,249 @@
+//! Named screenshot baselines for visual regression checks.
+//!
+//! Baselines live under `<project>/.code/visual-baselines/`, where the project
+//! is the enclosing git repository (or the working directory outside one), so
+//! they do not move with the directory a command runs from. Each name maps to
+//! three files: `<name>.png` (the accepted baseline), `<name>.current.png`
+//! (the most recent capture) and `<name>.diff.png` (the highlighted diff).
+//! Replacing an existing baseline needs the user's approval.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::git_info::get_git_repo_root;
+use crate::image_comparison::ImageDiff;
+use crate::image_comparison::diff_images;
+use crate::protocol::ReviewDecision;
+
+/// Similarity at or above which a comparison passes unless overridden.
+pub const DEFAULT_SIMILARITY_THRESHOLD: f64 = 0.995;
+
+const MAX_BASELINE_NAME_LEN: usize = 100;
+
+/// Directory holding visual baselines for the project containing `cwd`.
+pub fn baseline_dir(cwd: &Path) -> PathBuf {
+    get_git_repo_root(cwd)
+        .unwrap_or_else(|| cwd.to_path_buf())
+        .join(".code")
+        .join("visual-baselines")
+}
+
+/// Validate a baseline name: letters, digits, `-` and `_`. Dots are refused so
+/// a name cannot collide with another baseline's `.current`/`.diff` files.
+pub fn validate_baseline_name(name: &str) -> Result<&str, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("baseline name must not be empty".to_string());
+    }
+    if name.len() > MAX_BASELINE_NAME_LEN {
+        return Err(format!(
+            "baseline name must be at most {MAX_BASELINE_NAME_LEN} characters"
+        ));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+    {
+        return Err(format!(
+            "invalid baseline name '{name}'; use letters, digits, '-' or '_'"
+        ));
+    }
+    Ok(name)
+}
+
+/// Files backing one named baseline.
+#[derive(Debug, Clone)]
+pub struct BaselinePaths {
+    pub baseline: PathBuf,
+    pub current: PathBuf,
+    pub diff: PathBuf,
+}
+
+impl BaselinePaths {
+    pub fn new(cwd: &Path, name: &str) -> Result<Self, String> {
+        let name = validate_baseline_name(name)?;
+        let dir = baseline_dir(cwd);
+        Ok(Self {
+            baseline: dir.join(format!("{name}.png")),
+            current: dir.join(format!("{name}.current.png")),
+            diff: dir.join(format!("{name}.diff.png")),
+        })
+    }
+}
+
+/// Result of comparing a capture against a named baseline.
+#[derive(Debug, Clone)]
+pub enum CompareOutcome {
+    /// No baseline existed; the capture became the baseline.
+    Created,
+    /// The capture was compared against the existing baseline.
+    Compared { diff: ImageDiff, passed: bool },
+}
+
+/// Store `capture` as the latest capture for `paths` and compare it with the
+/// baseline, creating the baseline when none exists yet.
+pub fn compare_with_baseline(
+    paths: &BaselinePaths,
+    capture: &Path,
+    threshold: f64,
+) -> anyhow::Result<CompareOutcome> {
+    if let Some(parent) = paths.baseline.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if !paths.baseline.exists() {
+        save_as_png(capture, &paths.baseline)?;
+        return Ok(CompareOutcome::Created);
+    }
+    save_as_png(capture, &paths.current)?;
+    let diff = diff_images(&paths.baseline, &paths.current, &paths.diff)?;
+    let passed = diff.similarity >= threshold;
+    Ok(CompareOutcome::Compared { diff, passed })
+}
+
+/// Promote a capture to be the new baseline. Uses `capture` when given,
+/// otherwise the most recent compared capture.
+pub fn accept_baseline(paths: &BaselinePaths, capture: Option<&Path>) -> anyhow::Result<()> {
+    if let Some(parent) = paths.baseline.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    match capture {
+        Some(capture) => save_as_png(capture, &paths.baseline)?,
+        None if paths.current.exists() => {
+            std::fs::rename(&paths.current, &paths.baseline)?;
+        }
+        None => anyhow::bail!("no capture to accept; run a comparison first"),
+    }
+    let _ = std::fs::remove_file(&paths.diff);
+    Ok(())
+}
+
+/// Promote a capture to the baseline only once `approval` resolves to an
+/// approving decision; otherwise the stored baseline is left untouched.
+/// Returns whether the baseline was replaced.
+pub async fn accept_baseline_with_approval<F>(
+    paths: &BaselinePaths,
+    capture: Option<PathBuf>,
+    approval: F,
+) -> anyhow::Result<bool>
+where
+    F: Future<Output = ReviewDecision>,
+{
+    match approval.await {
+        ReviewDecision::Approved | ReviewDecision::ApprovedForSession => {}
+        ReviewDecision::Denied | ReviewDecision::Abort => return Ok(false),
+    }
+    let paths = paths.clone();
+    tokio::task::spawn_blocking(move || accept_baseline(&paths, capture.as_deref())).await??;
+    Ok(true)
+}
+
+fn save_as_png(source: &Path, dest: &Path) -> anyhow::Result<()> {
+    let img = image::ImageReader::open(source)?
+        .with_guessed_format()?
+        .decode()?;
+    img.save_with_format(dest, image::ImageFormat::Png)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+    use image::RgbaImage;
+
+    fn write_png(path: &Path, width: u32, height: u32, marked: u32) {
+        let mut img = RgbaImage::from_pixel(width, height, Rgba([255, 255, 255, 255]));
+        for x in 0..marked {
+            img.put_pixel(x, 0, Rgba([0, 0, 0, 255]));
+        }
+        img.save_with_format(path, image::ImageFormat::Png)
+            .unwrap_or_else(|e| panic!("write {}: {e}", path.display()));
+    }
+
+    #[test]
+    fn rejects_unsafe_baseline_names() {
+        assert!(validate_baseline_name("home-page_v2").is_ok());
+        assert!(validate_baseline_name("").is_err());
+        assert!(validate_baseline_name("../escape").is_err());
+        assert!(validate_baseline_name(".hidden").is_err());
+        assert!(validate_baseline_name("a/b").is_err());
+        // Would collide with the capture and diff files of `home`.
+        assert!(validate_baseline_name("home.current").is_err());
+        assert!(validate_baseline_name("home.diff").is_err());
+    }
+
+    #[test]
+    fn baselines_resolve_under_the_repository_root() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        std::fs::create_dir(tmp.path().join(".git"))?;
+        let nested = tmp.path().join("web").join("app");
+        std::fs::create_dir_all(&nested)?;
+
+        let expected = tmp.path().join(".code").join("visual-baselines");
+        assert_eq!(baseline_dir(&nested), expected);
+        assert_eq!(baseline_dir(tmp.path()), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn creates_compares_and_accepts_baselines() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let paths = BaselinePaths::new(tmp.path(), "home").map_err(anyhow::Error::msg)?;
+        let first = tmp.path().join("first.png");
+        let second = tmp.path().join("second.png");
+        write_png(&first, 10, 10, 0);
+        write_png(&second, 10, 10, 10);
+
+        assert!(matches!(
+            compare_with_baseline(&paths, &first, DEFAULT_SIMILARITY_THRESHOLD)?,
+            CompareOutcome::Created
+        ));
+
+        let CompareOutcome::Compared { diff, passed } =
+            compare_with_baseline(&paths, &second, DEFAULT_SIMILARITY_THRESHOLD)?
+        else {
+            panic!("expected a comparison");
+        };
+        assert!(!passed);
+        assert_eq!(diff.changed_pixels, 10);
+        assert!((diff.similarity - 0.9).abs() < 1e-9);
+        assert!(paths.diff.exists());
+
+        accept_baseline(&paths, None)?;
+        let CompareOutcome::Compared { diff, passed } =
+            compare_with_baseline(&paths, &second, DEFAULT_SIMILARITY_THRESHOLD)?
+        else {
+            panic!("expected a comparison");
+        };
+        assert!(passed);
+        assert_eq!(diff.changed_pixels, 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn denied_approval_leaves_the_baseline_untouched() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let paths = BaselinePaths::new(tmp.path(), "home").map_err(anyhow::Error::msg)?;
+        let first = tmp.path().join("first.png");
+        let second = tmp.path().join("second.png");
+        write_png(&first, 10, 10, 0);
+        write_png(&second, 10, 10, 10);
+        compare_with_baseline(&paths, &first, DEFAULT_SIMILARITY_THRESHOLD)?;
+        compare_with_baseline(&paths, &second, DEFAULT_SIMILARITY_THRESHOLD)?;
+        let original = std::fs::read(&paths.baseline)?;
+
+        for decision in [ReviewDecision::Denied, ReviewDecision::Abort] {
+            let accepted =
+                accept_baseline_with_approval(&paths, None, async move { decision }).await?;
+            assert!(!accepted);
+            assert_eq!(std::fs::read(&paths.baseline)?, original);
+            assert!(paths.current.exists());
+        }
+
+        let accepted =
+            accept_baseline_with_approval(&paths, None, async { ReviewDecision::Approved }).await?;
+        assert!(accepted);
+        assert_ne!(std::fs::read(&paths.baseline)?, original);
+        Ok(())
+    }
+}
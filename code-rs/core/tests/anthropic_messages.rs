@@ -0,0 +1,221 @@
+#![allow(clippy::unwrap_used)]
+
+//! Runs a conversation against a mock Anthropic Messages endpoint and checks
+//! both the request shape and how the streamed reply is mapped back.
+
+mod common;
+
+use common::load_default_config_for_test;
+use common::skip_if_no_network;
+
+use code_core::protocol::{AskForApproval, EventMsg, InputItem, Op, SandboxPolicy};
+use code_core::{CodexAuth, ConversationManager, ModelProviderInfo, WireApi};
+use serde_json::{Value, json};
+use tempfile::TempDir;
+use tokio::time::timeout;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn anthropic_sse(events: &[Value]) -> String {
+    events
+        .iter()
+        .map(|event| {
+            format!(
+                "event: {}\ndata: {event}\n\n",
+                event["type"].as_str().unwrap()
+            )
+        })
+        .collect()
+}
+
+fn sse_response(body: String) -> ResponseTemplate {
+    ResponseTemplate::new(200)
+        .insert_header("content-type", "text/event-stream")
+        .set_body_string(body)
+}
+
+fn tool_use_turn(workdir: &str) -> String {
+    let arguments = json!({
+        "command": ["bash", "-lc", "echo hi"],
+        "workdir": workdir,
+    })
+    .to_string();
+    let (first, second) = arguments.split_at(arguments.len() / 2);
+    anthropic_sse(&[
+        json!({"type": "message_start", "message": {"id": "msg_1", "usage": {
+            "input_tokens": 12,
+            "cache_creation_input_tokens": 0,
+            "cache_read_input_tokens": 900,
+            "output_tokens": 1,
+        }}}),
+        json!({"type": "content_block_start", "index": 0, "content_block": {"type": "thinking", "thinking": ""}}),
+        json!({"type": "content_block_delta", "index": 0, "delta": {"type": "thinking_delta", "thinking": "Run the command."}}),
+        json!({"type": "content_block_delta", "index": 0, "delta": {"type": "signature_delta", "signature": "sig-1"}}),
+        json!({"type": "content_block_stop", "index": 0}),
+        json!({"type": "content_block_start", "index": 1, "content_block": {"type": "tool_use", "id": "toolu_1", "name": "shell", "input": {}}}),
+        json!({"type": "content_block_delta", "index": 1, "delta": {"type": "input_json_delta", "partial_json": first}}),
+        json!({"type": "content_block_delta", "index": 1, "delta": {"type": "input_json_delta", "partial_json": second}}),
+        json!({"type": "content_block_stop", "index": 1}),
+        json!({"type": "message_delta", "delta": {"stop_reason": "tool_use"}, "usage": {"output_tokens": 40}}),
+        json!({"type": "message_stop"}),
+    ])
+}
+
+fn text_turn() -> String {
+    anthropic_sse(&[
+        json!({"type": "message_start", "message": {"id": "msg_2", "usage": {"input_tokens": 30, "output_tokens": 1}}}),
+        json!({"type": "ping"}),
+        json!({"type": "content_block_start", "index": 0, "content_block": {"type": "text", "text": ""}}),
+        json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "All "}}),
+        json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "done."}}),
+        json!({"type": "content_block_stop", "index": 0}),
+        json!({"type": "message_delta", "delta": {"stop_reason": "end_turn"}, "usage": {"output_tokens": 5}}),
+        json!({"type": "message_stop"}),
+    ])
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn anthropic_provider_runs_tool_loop() {
+    if skip_if_no_network() {
+        return;
+    }
+
+    let code_home = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let workdir = project_dir.path().to_string_lossy().to_string();
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .and(header("x-api-key", "sk-ant-test"))
+        .and(header("anthropic-version", "2023-06-01"))
+        .respond_with(sse_response(tool_use_turn(&workdir)))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .respond_with(sse_response(text_turn()))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+
+    let mut config = load_default_config_for_test(&code_home);
+    config.cwd = project_dir.path().to_path_buf();
+    config.approval_policy = AskForApproval::Never;
+    config.sandbox_policy = SandboxPolicy::DangerFullAccess;
+    config.model = "claude-sonnet-4-5".to_string();
+    config.model_provider = ModelProviderInfo {
+        name: "Anthropic gateway".to_string(),
+        base_url: Some(format!("{}/v1", server.uri())),
+        env_key: None,
+        env_key_instructions: None,
+        wire_api: WireApi::Anthropic,
+        query_params: None,
+        http_headers: None,
+        env_http_headers: None,
+        request_max_retries: Some(0),
+        stream_max_retries: Some(0),
+        stream_idle_timeout_ms: None,
+        requires_openai_auth: false,
+        openrouter: None,
+    };
+
+    let conversation_manager =
+        ConversationManager::with_auth(CodexAuth::from_api_key("sk-ant-test"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .expect("create conversation")
+        .conversation;
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "say hi from the shell".into(),
+            }],
+            final_output_json_schema: None,
+        })
+        .await
+        .unwrap();
+
+    let mut events = Vec::new();
+    loop {
+        let event = timeout(std::time::Duration::from_secs(10), codex.next_event())
+            .await
+            .expect("timeout waiting for event")
+            .expect("event stream ended unexpectedly");
+        let done = matches!(event.msg, EventMsg::TaskComplete(_));
+        events.push(event.msg);
+        if done {
+            break;
+        }
+    }
+
+    assert!(events.iter().any(|msg| matches!(
+        msg,
+        EventMsg::AgentMessage(ev) if ev.message == "All done."
+    )));
+    assert!(events.iter().any(|msg| matches!(
+        msg,
+        EventMsg::ExecCommandBegin(ev) if ev.call_id == "toolu_1"
+    )));
+    let cached_input = events.iter().find_map(|msg| match msg {
+        EventMsg::TokenCount(ev) => ev
+            .info
+            .as_ref()
+            .map(|info| info.last_token_usage.cached_input_tokens),
+        _ => None,
+    });
+    assert_eq!(cached_input, Some(900));
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2);
+
+    let first: Value = requests[0].body_json().unwrap();
+    assert_eq!(first["model"], "claude-sonnet-4-5");
+    assert_eq!(first["stream"], true);
+    let system = first["system"].as_array().unwrap();
+    assert_eq!(
+        system.last().unwrap()["cache_control"],
+        json!({"type": "ephemeral"})
+    );
+    let tools = first["tools"].as_array().unwrap();
+    assert!(
+        tools
+            .iter()
+            .any(|tool| tool["name"] == "shell" && tool["input_schema"].is_object())
+    );
+    assert_eq!(
+        tools.last().unwrap()["cache_control"],
+        json!({"type": "ephemeral"})
+    );
+
+    // The follow-up replays the signed thinking block and the tool call, then
+    // answers it with a tool_result.
+    let second: Value = requests[1].body_json().unwrap();
+    let messages = second["messages"].as_array().unwrap();
+    let assistant = messages
+        .iter()
+        .rev()
+        .find(|message| message["role"] == "assistant")
+        .unwrap();
+    assert_eq!(
+        assistant["content"][0],
+        json!({"type": "thinking", "thinking": "Run the command.", "signature": "sig-1"})
+    );
+    assert_eq!(assistant["content"][1]["type"], "tool_use");
+    assert_eq!(assistant["content"][1]["id"], "toolu_1");
+    assert_eq!(
+        assistant["content"][1]["input"]["command"],
+        json!(["bash", "-lc", "echo hi"])
+    );
+
+    let tool_result = messages
+        .iter()
+        .filter(|message| message["role"] == "user")
+        .flat_map(|message| message["content"].as_array().unwrap())
+        .find(|block| block["type"] == "tool_result")
+        .unwrap();
+    assert_eq!(tool_result["tool_use_id"], "toolu_1");
+    assert!(tool_result["content"].as_str().unwrap().contains("hi"));
+}
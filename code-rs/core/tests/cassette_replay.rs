@@ -0,0 +1,159 @@
+#![allow(clippy::unwrap_used)]
+
+//! Records a turn to a cassette against a mock provider, then replays it with
+//! the provider gone and checks that a request beyond the recording fails the
+//! turn instead of reaching the network.
+
+mod common;
+
+use common::load_default_config_for_test;
+use common::skip_if_no_network;
+use common::wait_for_event;
+
+use code_core::built_in_model_providers;
+use code_core::cassette::CassetteMode;
+use code_core::config::Config;
+use code_core::protocol::{EventMsg, InputItem, Op};
+use code_core::{CodexAuth, CodexConversation, ConversationManager, ModelProviderInfo};
+use serde_json::{Value, json};
+use std::path::Path;
+use tempfile::TempDir;
+use wiremock::matchers::{method, path_regex};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const REPLY: &str = "Hello from the cassette.";
+
+fn reply_sse() -> String {
+    [
+        json!({"type": "response.output_item.done", "item": {
+            "type": "message",
+            "role": "assistant",
+            "id": "msg-1",
+            "content": [{"type": "output_text", "text": REPLY}],
+        }}),
+        json!({"type": "response.completed", "response": {"id": "resp-1"}}),
+    ]
+    .iter()
+    .map(|event| {
+        format!(
+            "event: {}\ndata: {event}\n\n",
+            event["type"].as_str().unwrap()
+        )
+    })
+    .collect()
+}
+
+fn config_for(code_home: &TempDir, cwd: &Path, base_url: String, mode: CassetteMode) -> Config {
+    let mut config = load_default_config_for_test(code_home);
+    config.cwd = cwd.to_path_buf();
+    config.model_provider = ModelProviderInfo {
+        base_url: Some(base_url),
+        request_max_retries: Some(0),
+        stream_max_retries: Some(0),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    config.model_cassette = Some(mode);
+    config
+}
+
+async fn start(config: Config) -> std::sync::Arc<CodexConversation> {
+    ConversationManager::with_auth(CodexAuth::from_api_key("Test API Key"))
+        .new_conversation(config)
+        .await
+        .expect("create conversation")
+        .conversation
+}
+
+async fn send(codex: &CodexConversation, text: &str) {
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text { text: text.into() }],
+            final_output_json_schema: None,
+        })
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn recorded_turn_replays_without_the_provider() {
+    if skip_if_no_network() {
+        return;
+    }
+
+    let code_home = TempDir::new().unwrap();
+    let cwd = TempDir::new().unwrap();
+    let cassette = code_home.path().join("cassettes").join("turn.jsonl");
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path_regex(".*/responses$"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_string(reply_sse()),
+        )
+        .mount(&server)
+        .await;
+    let base_url = format!("{}/v1", server.uri());
+
+    let recorder = start(config_for(
+        &code_home,
+        cwd.path(),
+        base_url.clone(),
+        CassetteMode::Record(cassette.clone()),
+    ))
+    .await;
+    send(&recorder, "say hello").await;
+    wait_for_event(&recorder, |msg| matches!(msg, EventMsg::TaskComplete(_))).await;
+
+    // The entry is written once the response stream has been drained.
+    let mut recorded = Vec::new();
+    for _ in 0..50 {
+        recorded = std::fs::read_to_string(&cassette)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap())
+            .collect();
+        if !recorded.is_empty() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert!(
+        recorded
+            .iter()
+            .any(|entry| entry["response"].as_str().unwrap().contains(REPLY))
+    );
+    assert!(
+        recorded
+            .iter()
+            .all(|entry| entry["request"].get("prompt_cache_key").is_none())
+    );
+    drop(server);
+
+    let player = start(config_for(
+        &code_home,
+        cwd.path(),
+        base_url,
+        CassetteMode::Replay(cassette),
+    ))
+    .await;
+    send(&player, "say hello").await;
+    let message = wait_for_event(&player, |msg| matches!(msg, EventMsg::AgentMessage(_))).await;
+    let EventMsg::AgentMessage(message) = message else {
+        unreachable!();
+    };
+    assert_eq!(message.message, REPLY);
+    wait_for_event(&player, |msg| matches!(msg, EventMsg::TaskComplete(_))).await;
+
+    send(&player, "say goodbye").await;
+    let error = wait_for_event(&player, |msg| matches!(msg, EventMsg::Error(_))).await;
+    let EventMsg::Error(error) = error else {
+        unreachable!();
+    };
+    assert!(
+        error.message.starts_with("cassette: request #"),
+        "{}",
+        error.message
+    );
+}
@@ -0,0 +1,96 @@
+#![allow(clippy::unwrap_used)]
+
+//! Checks that a turn moves to the next `[model_failover]` entry once the
+//! configured provider has used up its retries.
+
+mod common;
+
+use common::{
+    load_default_config_for_test, load_sse_fixture_with_id, mount_sse_once, skip_if_no_network,
+    wait_for_event,
+};
+
+use code_core::built_in_model_providers;
+use code_core::model_failover::ModelFailoverTarget;
+use code_core::protocol::{EventMsg, InputItem, Op};
+use code_core::{CodexAuth, ConversationManager, ModelProviderInfo};
+use tempfile::TempDir;
+use wiremock::matchers::{method, path_regex};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn provider(server: &MockServer) -> ModelProviderInfo {
+    ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        request_max_retries: Some(0),
+        stream_max_retries: Some(0),
+        ..built_in_model_providers()["openai"].clone()
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn turn_fails_over_to_next_provider_after_server_errors() {
+    if skip_if_no_network() {
+        return;
+    }
+
+    let primary = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path_regex(".*/responses$"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&primary)
+        .await;
+
+    let fallback = MockServer::start().await;
+    let fallback_mock = mount_sse_once(
+        &fallback,
+        load_sse_fixture_with_id("tests/fixtures/completed_template.json", "resp-fallback"),
+    )
+    .await;
+
+    let cwd = TempDir::new().unwrap();
+    let code_home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&code_home);
+    config.cwd = cwd.path().to_path_buf();
+    config.model = "gpt-5".to_string();
+    config.model_provider_id = "primary".to_string();
+    config.model_provider = provider(&primary);
+    config.model_failover = vec![ModelFailoverTarget {
+        provider_id: "backup".to_string(),
+        provider: provider(&fallback),
+        model: "gpt-5-mini".to_string(),
+    }];
+
+    let conversation_manager =
+        ConversationManager::with_auth(CodexAuth::from_api_key("Test API Key"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .expect("create conversation")
+        .conversation;
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "hello".into(),
+            }],
+            final_output_json_schema: None,
+        })
+        .await
+        .unwrap();
+
+    let notice = wait_for_event(
+        &codex,
+        |msg| matches!(msg, EventMsg::BackgroundEvent(ev) if ev.message.starts_with("Failover:")),
+    )
+    .await;
+    let EventMsg::BackgroundEvent(notice) = notice else {
+        unreachable!();
+    };
+    assert!(notice.message.contains("primary:gpt-5 failed"));
+    assert!(notice.message.contains("now using backup:gpt-5-mini"));
+
+    wait_for_event(&codex, |msg| matches!(msg, EventMsg::TaskComplete(_))).await;
+
+    let body = fallback_mock.single_body_json();
+    assert_eq!(body["model"], "gpt-5-mini");
+    assert!(!primary.received_requests().await.unwrap().is_empty());
+}
@@ -308,3 +308,230 @@ async fn reconcile_prefers_last_event_over_mtime() {
 
     assert_eq!(latest.session_id, newer_id);
 }
+
+#[tokio::test]
+async fn archived_and_deleted_sessions_are_hidden_by_default() {
+    let temp = TempDir::new().unwrap();
+    let cwd = PathBuf::from("/workspace/project");
+    let archived_id = Uuid::parse_str("f1f1f1f1-f1f1-4f1f-8f1f-f1f1f1f1f1f1").unwrap();
+    let deleted_id = Uuid::parse_str("f2f2f2f2-f2f2-4f2f-8f2f-f2f2f2f2f2f2").unwrap();
+    for (session_id, created_at, last_event_at) in [
+        (archived_id, "2025-11-15T10:00:00Z", "2025-11-15T10:00:10Z"),
+        (deleted_id, "2025-11-16T10:00:00Z", "2025-11-16T10:00:10Z"),
+    ] {
+        write_rollout_transcript(
+            temp.path(),
+            session_id,
+            created_at,
+            last_event_at,
+            &cwd,
+            SessionSource::Cli,
+            "flagged",
+        );
+    }
+
+    let catalog = SessionCatalog::new(temp.path().to_path_buf());
+    assert!(
+        catalog
+            .set_archived(archived_id, true)
+            .await
+            .unwrap()
+            .unwrap()
+            .archived
+    );
+    assert!(
+        catalog
+            .set_deleted(deleted_id, true)
+            .await
+            .unwrap()
+            .unwrap()
+            .deleted
+    );
+
+    assert!(
+        catalog
+            .query(&SessionQuery::default())
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    // Flags survive a rescan of the sessions directory.
+    let everything = catalog
+        .query(&SessionQuery {
+            include_archived: true,
+            include_deleted: true,
+            ..SessionQuery::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(everything.len(), 2);
+    assert_eq!(everything[0].session_id, deleted_id);
+    assert!(everything[0].deleted);
+    assert!(everything[1].archived);
+
+    catalog.set_archived(archived_id, false).await.unwrap();
+    let visible = catalog.query(&SessionQuery::default()).await.unwrap();
+    assert_eq!(visible.len(), 1);
+    assert_eq!(visible[0].session_id, archived_id);
+}
+
+#[tokio::test]
+async fn query_filters_by_directory_and_last_activity() {
+    let temp = TempDir::new().unwrap();
+    let repo = PathBuf::from("/workspace/project");
+    let inside_id = Uuid::parse_str("a1a1a1a1-a1a1-4a1a-8a1a-a1a1a1a1a1a1").unwrap();
+    let recent_id = Uuid::parse_str("a2a2a2a2-a2a2-4a2a-8a2a-a2a2a2a2a2a2").unwrap();
+    let elsewhere_id = Uuid::parse_str("a3a3a3a3-a3a3-4a3a-8a3a-a3a3a3a3a3a3").unwrap();
+    write_rollout_transcript(
+        temp.path(),
+        inside_id,
+        "2025-11-10T09:00:00Z",
+        "2025-11-10T09:05:00Z",
+        &repo.join("crates").join("core"),
+        SessionSource::Cli,
+        "old, nested",
+    );
+    write_rollout_transcript(
+        temp.path(),
+        recent_id,
+        "2025-11-16T09:00:00Z",
+        "2025-11-16T09:05:00Z",
+        &repo,
+        SessionSource::Exec,
+        "recent",
+    );
+    write_rollout_transcript(
+        temp.path(),
+        elsewhere_id,
+        "2025-11-16T10:00:00Z",
+        "2025-11-16T10:05:00Z",
+        Path::new("/workspace/other"),
+        SessionSource::Cli,
+        "elsewhere",
+    );
+
+    let catalog = SessionCatalog::new(temp.path().to_path_buf());
+    let cutoff = chrono::DateTime::parse_from_rfc3339("2025-11-15T00:00:00Z")
+        .unwrap()
+        .with_timezone(&chrono::Utc);
+
+    let within: Vec<_> = catalog
+        .query(&SessionQuery {
+            cwd_within: Some(repo.clone()),
+            ..SessionQuery::default()
+        })
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|entry| entry.session_id)
+        .collect();
+    assert_eq!(within, vec![recent_id, inside_id]);
+
+    let older = catalog
+        .query(&SessionQuery {
+            cwd_within: Some(repo.clone()),
+            updated_before: Some(cutoff),
+            ..SessionQuery::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(older.len(), 1);
+    assert_eq!(older[0].session_id, inside_id);
+
+    let newer = catalog
+        .query(&SessionQuery {
+            updated_after: Some(cutoff),
+            ..SessionQuery::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(newer.len(), 2);
+    assert!(newer.iter().all(|entry| entry.session_id != inside_id));
+}
+
+#[tokio::test]
+async fn purge_removes_rollout_and_entry() {
+    let temp = TempDir::new().unwrap();
+    let cwd = PathBuf::from("/workspace/project");
+    let session_id = Uuid::parse_str("b1b1b1b1-b1b1-4b1b-8b1b-b1b1b1b1b1b1").unwrap();
+    let rollout_path = write_rollout_transcript(
+        temp.path(),
+        session_id,
+        "2025-11-15T12:00:00Z",
+        "2025-11-15T12:05:00Z",
+        &cwd,
+        SessionSource::Cli,
+        "purge me",
+    );
+
+    let catalog = SessionCatalog::new(temp.path().to_path_buf());
+    let purged = catalog
+        .purge(session_id)
+        .await
+        .unwrap()
+        .expect("purged entry");
+    assert_eq!(purged.session_id, session_id);
+    assert!(!rollout_path.exists());
+
+    let everything = catalog
+        .query(&SessionQuery {
+            include_archived: true,
+            include_deleted: true,
+            ..SessionQuery::default()
+        })
+        .await
+        .unwrap();
+    assert!(everything.is_empty());
+    assert!(catalog.purge(session_id).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn unique_prefix_rejects_empty_and_ambiguous_ids() {
+    let temp = TempDir::new().unwrap();
+    let cwd = PathBuf::from("/workspace/project");
+    let older_id = Uuid::parse_str("c0ffee00-0000-4000-8000-000000000001").unwrap();
+    let newer_id = Uuid::parse_str("c0ffee11-0000-4000-8000-000000000002").unwrap();
+    let older_path = write_rollout_transcript(
+        temp.path(),
+        older_id,
+        "2025-11-15T12:00:00Z",
+        "2025-11-15T12:05:00Z",
+        &cwd,
+        SessionSource::Cli,
+        "older",
+    );
+    write_rollout_transcript(
+        temp.path(),
+        newer_id,
+        "2025-11-16T12:00:00Z",
+        "2025-11-16T12:05:00Z",
+        &cwd,
+        SessionSource::Cli,
+        "newer",
+    );
+
+    let catalog = SessionCatalog::new(temp.path().to_path_buf());
+    assert!(catalog.find_unique_by_prefix("").await.is_err());
+    assert!(catalog.find_unique_by_prefix("  ").await.is_err());
+    assert!(catalog.find_unique_by_prefix("beef").await.is_err());
+
+    let ambiguous = catalog
+        .find_unique_by_prefix("C0FFEE")
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(ambiguous.contains("matches 2 sessions"), "{ambiguous}");
+    assert!(ambiguous.contains(&older_id.to_string()), "{ambiguous}");
+    assert!(ambiguous.contains(&newer_id.to_string()), "{ambiguous}");
+
+    let older = catalog.find_unique_by_prefix("c0ffee00").await.unwrap();
+    assert_eq!(older.session_id, older_id);
+    catalog.purge(older.session_id).await.unwrap();
+    assert!(!older_path.exists());
+
+    // Only the session the prefix named was removed; the shared prefix is
+    // now unique.
+    let remaining = catalog.find_unique_by_prefix("c0ffee").await.unwrap();
+    assert_eq!(remaining.session_id, newer_id);
+}
@@ -8,9 +8,10 @@ use code_core::built_in_model_providers;
 use code_core::config_types::{ProjectHookConfig, ProjectHookEvent};
 use code_core::project_features::ProjectHooks;
 use code_core::protocol::{AskForApproval, EventMsg, InputItem, Op, SandboxPolicy};
-use code_core::{CodexAuth, ConversationManager, ModelProviderInfo};
+use code_core::{CodexAuth, CodexConversation, ConversationManager, ModelProviderInfo};
 use serde_json::json;
 use std::fs::{self, File};
+use std::sync::Arc;
 use tempfile::TempDir;
 use tokio::time::timeout;
 use wiremock::matchers::{method, path_regex};
@@ -51,6 +52,7 @@ async fn tool_hooks_fire_for_shell_exec() {
             env: None,
             timeout_ms: None,
             run_in_background: Some(false),
+            on_failure: None,
         },
         ProjectHookConfig {
             event: ProjectHookEvent::ToolAfter,
@@ -60,14 +62,166 @@ async fn tool_hooks_fire_for_shell_exec() {
             env: None,
             timeout_ms: None,
             run_in_background: Some(false),
+            on_failure: None,
         },
     ];
     config.project_hooks = ProjectHooks::from_configs(&hook_configs, &config.cwd);
 
     let server = MockServer::start().await;
+    let (events, _conversation) = run_shell_turn(
+        config,
+        &server,
+        vec!["bash".to_string(), "-lc".to_string(), "echo exec-body".to_string()],
+    )
+    .await;
 
+    let hook_before_seen = events.iter().any(|msg| match msg {
+        EventMsg::ExecCommandBegin(ev) => ev.call_id.contains("_hook_tool_before"),
+        _ => false,
+    });
+    let hook_after_seen = events.iter().any(|msg| match msg {
+        EventMsg::ExecCommandEnd(ev) => ev.call_id.contains("_hook_tool_after"),
+        _ => false,
+    });
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2, "expected two model requests (tool + follow-up)");
+
+    assert!(hook_before_seen, "tool.before hook did not emit ExecCommandBegin");
+    assert!(hook_after_seen, "tool.after hook did not emit ExecCommandEnd");
+
+    let log_contents = fs::read_to_string(&log_path).unwrap();
+    let lines: Vec<&str> = log_contents.lines().collect();
+    assert!(lines.iter().any(|l| l.contains("before:tool.before")));
+    assert!(lines.iter().any(|l| l.contains("after:tool.after")));
+    assert!(lines.first().unwrap().contains("before"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn tool_before_hook_can_deny_shell_exec() {
+    let code_home = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let marker = project_dir.path().join("ran.txt");
+
+    let mut config = load_default_config_for_test(&code_home);
+    config.cwd = project_dir.path().to_path_buf();
+    config.approval_policy = AskForApproval::Never;
+    config.sandbox_policy = SandboxPolicy::DangerFullAccess;
+
+    let hook_configs = vec![ProjectHookConfig {
+        event: ProjectHookEvent::ToolBefore,
+        name: Some("guard".to_string()),
+        command: vec![
+            "bash".to_string(),
+            "-lc".to_string(),
+            r#"echo '{"decision":"deny","reason":"no writes from the shell"}'"#.to_string(),
+        ],
+        cwd: None,
+        env: None,
+        timeout_ms: None,
+        run_in_background: Some(false),
+        on_failure: None,
+    }];
+    config.project_hooks = ProjectHooks::from_configs(&hook_configs, &config.cwd);
+
+    let server = MockServer::start().await;
+    let (events, _conversation) = run_shell_turn(
+        config,
+        &server,
+        vec![
+            "bash".to_string(),
+            "-lc".to_string(),
+            format!("touch {}", marker.display()),
+        ],
+    )
+    .await;
+
+    assert!(!marker.exists(), "denied command should not run");
+    assert!(
+        !events.iter().any(|msg| matches!(msg, EventMsg::ExecCommandBegin(ev) if ev.call_id == "call-1")),
+        "denied command should not emit ExecCommandBegin"
+    );
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2, "expected two model requests (tool + follow-up)");
+    let follow_up = String::from_utf8_lossy(&requests[1].body);
+    assert!(
+        follow_up.contains("Blocked by hook `guard`: no writes from the shell"),
+        "denial reason should be sent back to the model: {follow_up}"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn lifecycle_hooks_receive_turn_payloads() {
+    let code_home = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let log_path = project_dir.path().join("lifecycle.log");
+
+    let mut config = load_default_config_for_test(&code_home);
+    config.cwd = project_dir.path().to_path_buf();
+    config.approval_policy = AskForApproval::Never;
+    config.sandbox_policy = SandboxPolicy::DangerFullAccess;
+
+    let hook_configs = [ProjectHookEvent::TurnSubmit, ProjectHookEvent::TurnComplete]
+        .into_iter()
+        .map(|event| ProjectHookConfig {
+            event,
+            name: None,
+            command: vec![
+                "bash".to_string(),
+                "-lc".to_string(),
+                format!("printf '%s\\n' \"$CODE_HOOK_PAYLOAD\" >> {}", log_path.display()),
+            ],
+            cwd: None,
+            env: None,
+            timeout_ms: None,
+            run_in_background: Some(false),
+            on_failure: None,
+        })
+        .collect::<Vec<_>>();
+    config.project_hooks = ProjectHooks::from_configs(&hook_configs, &config.cwd);
+
+    let server = MockServer::start().await;
+    let (_events, _conversation) = run_shell_turn(
+        config,
+        &server,
+        vec!["bash".to_string(), "-lc".to_string(), "echo exec-body".to_string()],
+    )
+    .await;
+
+    // Lifecycle hooks run in the background, after the events that trigger them.
+    let mut payloads = Vec::new();
+    for _ in 0..50 {
+        payloads = fs::read_to_string(&log_path)
+            .unwrap_or_default()
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect();
+        if payloads.len() >= 2 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+
+    assert_eq!(payloads.len(), 2, "expected one payload per lifecycle hook: {payloads:?}");
+    assert_eq!(payloads[0]["event"], "turn.submit");
+    assert_eq!(payloads[0]["text"], "run hook");
+    assert_eq!(payloads[0]["queued"], false);
+    assert_eq!(payloads[1]["event"], "turn.complete");
+    assert_eq!(payloads[1]["last_agent_message"], "done");
+    assert!(payloads[1].get("token_usage").is_some());
+}
+
+/// Runs one turn in which the model calls the shell tool with `command` and
+/// then replies "done"; returns the events up to TaskComplete and the
+/// conversation, which stays alive for as long as the caller holds it.
+async fn run_shell_turn(
+    mut config: code_core::config::Config,
+    server: &MockServer,
+    command: Vec<String>,
+) -> (Vec<EventMsg>, Arc<CodexConversation>) {
     let function_call_args = json!({
-        "command": ["bash", "-lc", "echo exec-body"],
+        "command": command,
         "workdir": config.cwd,
         "timeout_ms": null,
         "sandbox_permissions": null,
@@ -137,13 +291,13 @@ event: response.completed\ndata: {}\n\n",
         .and(path_regex(".*/responses$"))
         .respond_with(sse_response(body_one))
         .up_to_n_times(1)
-        .mount(&server)
+        .mount(server)
         .await;
     Mock::given(method("POST"))
         .and(path_regex(".*/responses$"))
         .respond_with(sse_response(body_two))
         .up_to_n_times(1)
-        .mount(&server)
+        .mount(server)
         .await;
 
     config.model_provider = ModelProviderInfo {
@@ -188,25 +342,5 @@ event: response.completed\ndata: {}\n\n",
     }
 
     assert!(saw_task_complete, "did not receive TaskComplete event");
-
-    let hook_before_seen = events.iter().any(|msg| match msg {
-        EventMsg::ExecCommandBegin(ev) => ev.call_id.contains("_hook_tool_before"),
-        _ => false,
-    });
-    let hook_after_seen = events.iter().any(|msg| match msg {
-        EventMsg::ExecCommandEnd(ev) => ev.call_id.contains("_hook_tool_after"),
-        _ => false,
-    });
-
-    let requests = server.received_requests().await.unwrap();
-    assert_eq!(requests.len(), 2, "expected two model requests (tool + follow-up)");
-
-    assert!(hook_before_seen, "tool.before hook did not emit ExecCommandBegin");
-    assert!(hook_after_seen, "tool.after hook did not emit ExecCommandEnd");
-
-    let log_contents = fs::read_to_string(&log_path).unwrap();
-    let lines: Vec<&str> = log_contents.lines().collect();
-    assert!(lines.iter().any(|l| l.contains("before:tool.before")));
-    assert!(lines.iter().any(|l| l.contains("after:tool.after")));
-    assert!(lines.first().unwrap().contains("before"));
+    (events, codex)
 }
@@ -73,6 +73,31 @@ pub struct Cli {
     #[arg(long = "output-schema", value_name = "FILE")]
     pub output_schema: Option<PathBuf>,
 
+    /// Extra turns allowed to repair a final response that does not match
+    /// `--output-schema` before exiting with status 3.
+    #[arg(
+        long = "output-schema-repairs",
+        value_name = "N",
+        default_value_t = 2,
+        requires = "output_schema"
+    )]
+    pub output_schema_repairs: u32,
+
+    /// Record every model request and its streamed response to this cassette
+    /// file so the run can be replayed with `--replay-cassette`.
+    #[arg(
+        long = "record-cassette",
+        value_name = "FILE",
+        conflicts_with = "replay_cassette"
+    )]
+    pub record_cassette: Option<PathBuf>,
+
+    /// Serve model responses from a cassette written by `--record-cassette`
+    /// instead of calling the provider. A request that matches no recorded
+    /// one fails the run with a diff.
+    #[arg(long = "replay-cassette", value_name = "FILE")]
+    pub replay_cassette: Option<PathBuf>,
+
     #[clap(skip)]
     pub config_overrides: CliConfigOverrides,
 
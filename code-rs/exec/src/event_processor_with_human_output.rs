@@ -8,6 +8,7 @@ use code_core::protocol::AgentReasoningDeltaEvent;
 use code_core::protocol::AgentReasoningRawContentDeltaEvent;
 use code_core::protocol::AgentReasoningRawContentEvent;
 use code_core::protocol::BackgroundEventEvent;
+use code_core::protocol::ElicitationRequestEvent;
 use code_core::protocol::ErrorEvent;
 use code_core::protocol::Event;
 use code_core::protocol::EventMsg;
@@ -19,6 +20,7 @@ use code_core::protocol::McpToolCallBeginEvent;
 use code_core::protocol::McpToolCallEndEvent;
 use code_core::protocol::PatchApplyBeginEvent;
 use code_core::protocol::PatchApplyEndEvent;
+use code_core::protocol::SandboxDenialEvent;
 use code_core::protocol::SessionConfiguredEvent;
 use code_core::protocol::TaskCompleteEvent;
 use code_protocol::protocol::TurnAbortReason;
@@ -198,10 +200,15 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             EventMsg::BackgroundEvent(BackgroundEventEvent { message }) => {
                 ts_println!(self, "{}", message.style(self.dimmed));
             }
+            EventMsg::SandboxDenial(SandboxDenialEvent { denial, .. }) => {
+                let prefix = "sandbox:".style(self.red);
+                ts_println!(self, "{prefix} {denial}");
+            }
             EventMsg::EnvironmentContextFull(_)
             | EventMsg::EnvironmentContextDelta(_)
             | EventMsg::BrowserSnapshot(_)
             | EventMsg::ListCustomPromptsResponse(_)
+            | EventMsg::ListMcpResourcesResponse(_)
             | EventMsg::ListSkillsResponse(_)
             | EventMsg::ViewImageToolCall(_) => {
                 // Environment context events are consumed by the TUI; the CLI runner
@@ -324,12 +331,22 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 );
             }
             EventMsg::ExecCommandOutputDelta(_) => {}
-            EventMsg::ExecCommandEnd(ExecCommandEndEvent { call_id, stdout, stderr, duration, exit_code }) => {
+            EventMsg::ExecCommandEnd(ExecCommandEndEvent {
+                call_id,
+                stdout,
+                stderr,
+                duration,
+                exit_code,
+                resource_usage,
+            }) => {
                 let exec_command = self.call_id_to_command.remove(&call_id);
+                let usage = resource_usage
+                    .map(|usage| format!(" ({usage})"))
+                    .unwrap_or_default();
                 let (duration, call) = if let Some(ExecCommandBegin { command, .. }) = exec_command
                 {
                     (
-                        format!(" in {}", format_duration(duration)),
+                        format!(" in {}{usage}", format_duration(duration)),
                         format!("{}", escape_command(&command).style(self.bold)),
                     )
                 } else {
@@ -545,6 +562,19 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             EventMsg::ApplyPatchApprovalRequest(_) => {
                 // Should we exit?
             }
+            EventMsg::ElicitationRequest(ElicitationRequestEvent {
+                server_name,
+                message,
+                ..
+            }) => {
+                // Only reachable when exec is driven with an interactive
+                // policy; nobody can answer here, so just surface it.
+                ts_println!(
+                    self,
+                    "{} {message}",
+                    format!("{server_name} requested input:").style(self.magenta)
+                );
+            }
             EventMsg::AgentReasoning(agent_reasoning_event) => {
                 if self.show_agent_reasoning {
                     if !self.reasoning_started {
@@ -2,6 +2,7 @@ mod cli;
 mod event_processor;
 mod event_processor_with_human_output;
 mod event_processor_with_json_output;
+mod output_schema;
 mod slash;
 
 pub use cli::Cli;
@@ -10,6 +11,8 @@ use code_auto_drive_core::AutoCoordinatorCommand;
 use code_auto_drive_core::AutoCoordinatorEvent;
 use code_auto_drive_core::AutoCoordinatorEventSender;
 use code_auto_drive_core::AutoCoordinatorStatus;
+use code_auto_drive_core::AutoContinueMode;
+use code_auto_drive_core::AutoDriveController;
 use code_auto_drive_core::AutoDriveHistory;
 use code_auto_drive_core::AutoTurnAgentsAction;
 use code_auto_drive_core::AutoTurnAgentsTiming;
@@ -19,7 +22,9 @@ use code_core::AuthManager;
 use code_core::BUILT_IN_OSS_MODEL_PROVIDER_ID;
 use code_core::ConversationManager;
 use code_core::NewConversation;
+use code_core::WireApi;
 use code_core::CodexConversation;
+use code_core::cassette::CassetteMode;
 use code_core::config::set_default_originator;
 use code_core::config::Config;
 use code_core::config::ConfigOverrides;
@@ -72,6 +77,9 @@ use anyhow::Context;
 use crate::cli::Command as ExecCommand;
 use crate::event_processor::CodexStatus;
 use crate::event_processor::EventProcessor;
+use crate::output_schema::OUTPUT_SCHEMA_EXIT_CODE;
+use crate::output_schema::OutputCheck;
+use crate::output_schema::OutputSchemaGuard;
 use crate::slash::{process_exec_slash_command, SlashContext, SlashDispatch};
 use code_auto_drive_core::AUTO_RESOLVE_REVIEW_FOLLOWUP;
 use code_auto_drive_core::AutoResolvePhase;
@@ -109,6 +117,9 @@ pub async fn run_main(cli: Cli, code_linux_sandbox_exe: Option<PathBuf>) -> anyh
         sandbox_mode: sandbox_mode_cli_arg,
         prompt,
         output_schema: output_schema_path,
+        output_schema_repairs,
+        record_cassette,
+        replay_cassette,
         include_plan_tool,
         config_overrides,
         auto_drive,
@@ -207,7 +218,7 @@ pub async fn run_main(cli: Cli, code_linux_sandbox_exe: Option<PathBuf>) -> anyh
     };
     let mut review_request: Option<ReviewRequest> = None;
 
-    let _output_schema = load_output_schema(output_schema_path);
+    let output_schema = load_output_schema(output_schema_path);
 
     let (stdout_with_ansi, stderr_with_ansi) = match color {
         cli::Color::Always => (true, true),
@@ -281,6 +292,9 @@ pub async fn run_main(cli: Cli, code_linux_sandbox_exe: Option<PathBuf>) -> anyh
         experimental_client_tools: None,
         compact_prompt_override: None,
         compact_prompt_override_file: None,
+        model_cassette: record_cassette
+            .map(CassetteMode::Record)
+            .or(replay_cassette.map(CassetteMode::Replay)),
     };
     // Parse `-c` overrides.
     let cli_kv_overrides = match config_overrides.parse_overrides() {
@@ -296,6 +310,8 @@ pub async fn run_main(cli: Cli, code_linux_sandbox_exe: Option<PathBuf>) -> anyh
     config.max_run_deadline = run_deadline_std;
     config.demo_developer_message = cli.demo_developer_message.clone();
     config.timeboxed_exec_mode = timeboxed_auto_exec;
+    // Nobody is around to answer MCP elicitation requests.
+    config.mcp_elicitation = config.mcp_elicitation.non_interactive();
     if timeboxed_auto_exec {
         config.demo_developer_message = merge_developer_message(
             config.demo_developer_message.take(),
@@ -511,6 +527,9 @@ pub async fn run_main(cli: Cli, code_linux_sandbox_exe: Option<PathBuf>) -> anyh
     info!("Codex initialized with event: {session_configured:?}");
 
     if let Some(goal) = auto_drive_goal {
+        if output_schema.is_some() {
+            eprintln!("--output-schema is not enforced for Auto Drive runs.");
+        }
         return run_auto_drive_session(
             goal,
             images,
@@ -656,6 +675,17 @@ pub async fn run_main(cli: Cli, code_linux_sandbox_exe: Option<PathBuf>) -> anyh
         .map(|v| v == "1")
         .unwrap_or(false);
 
+    // Reviews produce their own structured output, so only prompts are checked.
+    // Only the Responses API accepts a schema with the request; other wire
+    // APIs get it in the prompt. The final message is validated either way.
+    let schema_via_provider = config.model_provider.wire_api == WireApi::Responses;
+    let mut output_schema_guard = output_schema
+        .filter(|_| review_request.is_none())
+        .map(|schema| {
+            OutputSchemaGuard::new(schema, schema_via_provider, output_schema_repairs)
+        });
+    let mut output_schema_failed = false;
+
     let _initial_prompt_task_id = if let Some(mut review_request) = review_request.clone() {
         // Cross-process review coordination
         if !skip_review_lock {
@@ -704,6 +734,12 @@ pub async fn run_main(cli: Cli, code_linux_sandbox_exe: Option<PathBuf>) -> anyh
         info!("Sent /review with event ID: {event_id}");
         event_id
     } else {
+        if let Some(instructions) = output_schema_guard
+            .as_ref()
+            .and_then(OutputSchemaGuard::prompt_instructions)
+        {
+            prompt_to_send = format!("{prompt_to_send}\n\n{instructions}");
+        }
         let mut items: Vec<InputItem> = Vec::new();
         items.push(InputItem::Text { text: prompt_to_send });
         items.extend(images.into_iter().map(|path| InputItem::LocalImage { path }));
@@ -711,7 +747,9 @@ pub async fn run_main(cli: Cli, code_linux_sandbox_exe: Option<PathBuf>) -> anyh
         let event_id = conversation
             .submit(Op::UserInput {
                 items,
-                final_output_json_schema: None,
+                final_output_json_schema: output_schema_guard
+                    .as_ref()
+                    .and_then(OutputSchemaGuard::request_schema),
             })
             .await?;
         info!("Sent prompt with event ID: {event_id}");
@@ -756,6 +794,37 @@ pub async fn run_main(cli: Cli, code_linux_sandbox_exe: Option<PathBuf>) -> anyh
             error_seen = true;
         }
 
+        // Check the final message against --output-schema, asking the agent
+        // to repair it before the event processor sees the turn as done.
+        if let EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) = &event.msg
+            && let Some(guard) = output_schema_guard.as_mut()
+            && !shutdown_sent
+        {
+            match guard.check(last_agent_message.as_deref()) {
+                OutputCheck::Valid => {}
+                OutputCheck::Repair(repair_prompt) => {
+                    eprintln!("Final message does not match --output-schema; asking the agent to fix it.");
+                    conversation
+                        .submit(Op::UserInput {
+                            items: vec![InputItem::Text { text: repair_prompt }],
+                            final_output_json_schema: guard.request_schema(),
+                        })
+                        .await?;
+                    continue;
+                }
+                OutputCheck::Failed(failure) => {
+                    let report = serde_json::to_string(&failure)
+                        .unwrap_or_else(|_| r#"{"type":"output_schema_error"}"#.to_string());
+                    if json_mode {
+                        println!("{report}");
+                    } else {
+                        eprintln!("{report}");
+                    }
+                    output_schema_failed = true;
+                }
+            }
+        }
+
         // Handle review auto-resolve: chain follow-up reviews when enabled.
         match &event.msg {
             EventMsg::ExitedReviewMode(event) => {
@@ -1216,6 +1285,9 @@ pub async fn run_main(cli: Cli, code_linux_sandbox_exe: Option<PathBuf>) -> anyh
     if review_runs > 0 {
         eprintln!("Review runs: {} (auto_resolve={} max_attempts={})", review_runs, config.tui.review_auto_resolve, max_auto_resolve_attempts);
     }
+    if output_schema_failed {
+        std::process::exit(OUTPUT_SCHEMA_EXIT_CODE);
+    }
     if error_seen {
         std::process::exit(1);
     }
@@ -1243,6 +1315,10 @@ async fn resolve_resume_path(
         let query = SessionQuery {
             cwd: None,
             git_root: None,
+            cwd_within: None,
+            git_branch: None,
+            updated_after: None,
+            updated_before: None,
             sources: vec![SessionSource::Cli, SessionSource::VSCode, SessionSource::Exec],
             min_user_messages: 1,
             include_archived: false,
@@ -1264,6 +1340,69 @@ struct TurnResult {
     error_seen: bool,
 }
 
+/// Tracks the Auto Drive phase of an exec run so `auto_drive.phase` project
+/// hooks fire the same way they do when the TUI drives the run.
+struct ExecAutoPhases {
+    controller: AutoDriveController,
+    goal: String,
+}
+
+impl ExecAutoPhases {
+    fn new(goal: &str) -> Self {
+        let mut controller = AutoDriveController::default();
+        controller.prepare_launch(
+            goal.to_string(),
+            false,
+            false,
+            false,
+            false,
+            AutoContinueMode::Immediate,
+            true,
+        );
+        Self {
+            controller,
+            goal: goal.to_string(),
+        }
+    }
+
+    fn launched(&mut self) {
+        let now = std::time::Instant::now();
+        let _ = self.controller.launch_succeeded(self.goal.clone(), None, now);
+    }
+
+    fn turn_started(&mut self) {
+        self.controller.set_waiting_for_response(false);
+    }
+
+    fn turn_finished(&mut self) {
+        self.controller.set_coordinator_waiting(true);
+    }
+
+    fn stopped(&mut self) {
+        let _ = self.controller.stop_run(std::time::Instant::now(), None);
+    }
+
+    fn take_ops(&mut self) -> Vec<Op> {
+        self.controller
+            .take_phase_changes()
+            .into_iter()
+            .map(|change| Op::AutoDrivePhase {
+                phase: change.phase.to_string(),
+                previous_phase: change.previous.to_string(),
+                goal: Some(self.goal.clone()),
+            })
+            .collect()
+    }
+
+    async fn report(&mut self, conversation: &CodexConversation) {
+        for op in self.take_ops() {
+            if let Err(err) = conversation.submit(op).await {
+                debug!("failed to report Auto Drive phase change: {err}");
+            }
+        }
+    }
+}
+
 async fn run_auto_drive_session(
     goal: String,
     images: Vec<PathBuf>,
@@ -1339,6 +1478,7 @@ async fn run_auto_drive_session(
         let _ = auto_tx.send(event);
     });
 
+    let mut phases = ExecAutoPhases::new(&goal);
     let handle = start_auto_coordinator(
         sender,
         goal.clone(),
@@ -1347,6 +1487,8 @@ async fn run_auto_drive_session(
         config.debug,
         false,
     )?;
+    phases.launched();
+    phases.report(&conversation).await;
 
     loop {
         let maybe_event = if let Some(deadline) = run_deadline {
@@ -1356,6 +1498,8 @@ async fn run_auto_drive_session(
                 Err(_) => {
                     let _ = handle.send(AutoCoordinatorCommand::Stop);
                     handle.cancel();
+                    phases.stopped();
+                    phases.report(&conversation).await;
                     let _ = conversation.submit(Op::Interrupt).await;
                     let _ = conversation.submit(Op::Shutdown).await;
                     return Err(anyhow::anyhow!("Time budget exceeded"));
@@ -1405,6 +1549,8 @@ async fn run_auto_drive_session(
                     let prompt_text = cmd.trim();
                     if !prompt_text.is_empty() {
                         history.append_raw(&[make_user_message(prompt_text.to_string())]);
+                        phases.turn_started();
+                        phases.report(&conversation).await;
                         let TurnResult {
                             last_agent_message,
                             error_seen: turn_error,
@@ -1421,10 +1567,14 @@ async fn run_auto_drive_session(
                             Err(err) => {
                                 let _ = handle.send(AutoCoordinatorCommand::Stop);
                                 handle.cancel();
+                                phases.stopped();
+                                phases.report(&conversation).await;
                                 return Err(err);
                             }
                         };
                         error_seen |= turn_error;
+                        phases.turn_finished();
+                        phases.report(&conversation).await;
                         if let Some(text) = last_agent_message {
                             history.append_raw(&[make_assistant_message(text.clone())]);
                             final_last_message = Some(text);
@@ -1470,6 +1620,8 @@ async fn run_auto_drive_session(
 
                 let prompt_text = build_auto_prompt(&cli_action, &agents, agents_timing);
                 history.append_raw(&[make_user_message(prompt_text.clone())]);
+                phases.turn_started();
+                phases.report(&conversation).await;
 
                 let TurnResult {
                     last_agent_message,
@@ -1487,10 +1639,14 @@ async fn run_auto_drive_session(
                     Err(err) => {
                         let _ = handle.send(AutoCoordinatorCommand::Stop);
                         handle.cancel();
+                        phases.stopped();
+                        phases.report(&conversation).await;
                         return Err(err);
                     }
                 };
                 error_seen |= turn_error;
+                phases.turn_finished();
+                phases.report(&conversation).await;
                 if let Some(text) = last_agent_message {
                     history.append_raw(&[make_assistant_message(text.clone())]);
                     final_last_message = Some(text);
@@ -1512,6 +1668,8 @@ async fn run_auto_drive_session(
     }
 
     handle.cancel();
+    phases.stopped();
+    phases.report(&conversation).await;
 
     if !auto_review_tracker.is_running() {
         let grace_deadline = Instant::now() + Duration::from_millis(AUTO_REVIEW_SHUTDOWN_GRACE_MS);
@@ -3087,4 +3245,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn auto_phases_report_each_change_with_the_goal() {
+        let mut phases = ExecAutoPhases::new("ship it");
+        phases.launched();
+        phases.turn_started();
+        phases.turn_finished();
+        phases.stopped();
+
+        let changes: Vec<(String, String, Option<String>)> = phases
+            .take_ops()
+            .into_iter()
+            .map(|op| match op {
+                Op::AutoDrivePhase {
+                    phase,
+                    previous_phase,
+                    goal,
+                } => (previous_phase, phase, goal),
+                other => panic!("unexpected op: {other:?}"),
+            })
+            .collect();
+        let expected = [
+            ("idle", "launching"),
+            ("launching", "awaiting_diagnostics"),
+            ("awaiting_diagnostics", "active"),
+            ("active", "awaiting_diagnostics"),
+            ("awaiting_diagnostics", "active"),
+            ("active", "idle"),
+            ("idle", "awaiting_goal_entry"),
+        ]
+        .into_iter()
+        .map(|(previous, phase)| {
+            (
+                previous.to_string(),
+                phase.to_string(),
+                Some("ship it".to_string()),
+            )
+        })
+        .collect::<Vec<_>>();
+        assert_eq!(changes, expected);
+        assert!(phases.take_ops().is_empty());
+    }
 }
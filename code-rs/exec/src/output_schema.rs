@@ -0,0 +1,568 @@
+//! Local enforcement of `--output-schema`.
+//!
+//! For providers on the Responses API the schema is sent with every turn as
+//! the response `text.format`; other wire APIs reject it, so the schema is
+//! added to the prompt instead. Not every provider honours either, so the
+//! final agent message is always checked here. When it does not validate,
+//! exec asks the agent to repair it a few times before giving up with
+//! [`OUTPUT_SCHEMA_EXIT_CODE`].
+//!
+//! The validator covers the JSON Schema keywords used by structured outputs:
+//! `type`, `enum`, `const`, `properties`, `required`, `additionalProperties`,
+//! `items`, `anyOf`/`oneOf`/`allOf`, `$ref` to local definitions, and the
+//! usual length, size and range limits. Unknown keywords are ignored.
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// Exit status used when the final message still fails validation after all
+/// repair turns.
+pub(crate) const OUTPUT_SCHEMA_EXIT_CODE: i32 = 3;
+
+/// One place where a value does not match the schema.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct SchemaViolation {
+    /// JSON pointer to the offending value ("" for the root).
+    pub path: String,
+    pub message: String,
+}
+
+/// Machine-readable report printed when validation ultimately fails.
+#[derive(Debug, Serialize)]
+pub(crate) struct OutputSchemaFailure {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    /// Number of final messages checked, including the first one.
+    attempts: u32,
+    errors: Vec<SchemaViolation>,
+}
+
+pub(crate) enum OutputCheck {
+    Valid,
+    /// Send this prompt as a repair turn.
+    Repair(String),
+    Failed(OutputSchemaFailure),
+}
+
+/// Tracks repair turns for one exec run.
+pub(crate) struct OutputSchemaGuard {
+    schema: Value,
+    /// Whether the provider accepts the schema as a request parameter.
+    send_to_provider: bool,
+    max_repairs: u32,
+    repairs: u32,
+}
+
+impl OutputSchemaGuard {
+    pub(crate) fn new(schema: Value, send_to_provider: bool, max_repairs: u32) -> Self {
+        Self {
+            schema,
+            send_to_provider,
+            max_repairs,
+            repairs: 0,
+        }
+    }
+
+    /// Schema to attach to each turn, if the provider accepts one.
+    pub(crate) fn request_schema(&self) -> Option<Value> {
+        self.send_to_provider.then(|| self.schema.clone())
+    }
+
+    /// Text to append to the prompt when the schema cannot be sent with the
+    /// request.
+    pub(crate) fn prompt_instructions(&self) -> Option<String> {
+        if self.send_to_provider {
+            return None;
+        }
+        let schema = serde_json::to_string_pretty(&self.schema).unwrap_or_default();
+        Some(format!(
+            "Your final message must be only a JSON value matching this JSON Schema, with no other text or code fences:\n\n{schema}"
+        ))
+    }
+
+    /// Check the final message of a turn and decide what to do next.
+    pub(crate) fn check(&mut self, last_agent_message: Option<&str>) -> OutputCheck {
+        let errors = match last_agent_message.map(str::trim) {
+            None | Some("") => vec![SchemaViolation {
+                path: String::new(),
+                message: "the final message is empty".to_string(),
+            }],
+            Some(text) => match serde_json::from_str::<Value>(text) {
+                Ok(value) => validate(&self.schema, &value),
+                Err(err) => vec![SchemaViolation {
+                    path: String::new(),
+                    message: format!("the final message is not valid JSON: {err}"),
+                }],
+            },
+        };
+        if errors.is_empty() {
+            return OutputCheck::Valid;
+        }
+        if self.repairs < self.max_repairs {
+            self.repairs += 1;
+            return OutputCheck::Repair(repair_prompt(&errors));
+        }
+        OutputCheck::Failed(OutputSchemaFailure {
+            kind: "output_schema_error",
+            attempts: self.repairs + 1,
+            errors,
+        })
+    }
+}
+
+fn repair_prompt(errors: &[SchemaViolation]) -> String {
+    let mut prompt = "Your final message does not match the required JSON schema:\n".to_string();
+    for error in errors {
+        let path = if error.path.is_empty() {
+            "(root)"
+        } else {
+            error.path.as_str()
+        };
+        prompt.push_str(&format!("- {path}: {}\n", error.message));
+    }
+    prompt.push_str(
+        "\nReply again with only the corrected JSON value. Do not add any other text or code fences.",
+    );
+    prompt
+}
+
+/// Validate `instance` against `schema`, returning every violation found.
+pub(crate) fn validate(schema: &Value, instance: &Value) -> Vec<SchemaViolation> {
+    let mut validator = Validator {
+        root: schema,
+        active_refs: Vec::new(),
+        errors: Vec::new(),
+    };
+    validator.check(schema, instance, "");
+    validator.errors
+}
+
+struct Validator<'a> {
+    root: &'a Value,
+    /// `$ref` targets being expanded, with the instance path each applies to.
+    /// Expanding the same pair again would recurse without consuming any of
+    /// the instance, so it is skipped.
+    active_refs: Vec<(&'a str, String)>,
+    errors: Vec<SchemaViolation>,
+}
+
+impl<'a> Validator<'a> {
+    fn fail(&mut self, path: &str, message: String) {
+        self.errors.push(SchemaViolation {
+            path: path.to_string(),
+            message,
+        });
+    }
+
+    fn is_valid(&self, schema: &'a Value, instance: &Value, path: &str) -> bool {
+        let mut nested = Validator {
+            root: self.root,
+            active_refs: self.active_refs.clone(),
+            errors: Vec::new(),
+        };
+        nested.check(schema, instance, path);
+        nested.errors.is_empty()
+    }
+
+    fn check(&mut self, schema: &'a Value, instance: &Value, path: &str) {
+        let schema = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => {
+                self.fail(path, "no value is allowed here".to_string());
+                return;
+            }
+            Value::Object(schema) => schema,
+            _ => return,
+        };
+
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            match reference
+                .strip_prefix('#')
+                .and_then(|pointer| self.root.pointer(pointer))
+            {
+                Some(target) => {
+                    let active = (reference, path.to_string());
+                    if !self.active_refs.contains(&active) {
+                        self.active_refs.push(active);
+                        self.check(target, instance, path);
+                        self.active_refs.pop();
+                    }
+                }
+                None => self.fail(path, format!("unresolvable schema reference `{reference}`")),
+            }
+        }
+
+        if let Some(expected) = schema.get("type")
+            && !type_matches(expected, instance)
+        {
+            self.fail(
+                path,
+                format!(
+                    "expected {}, found {}",
+                    describe_type(expected),
+                    kind_of(instance)
+                ),
+            );
+            return;
+        }
+
+        if let Some(options) = schema.get("enum").and_then(Value::as_array)
+            && !options.contains(instance)
+        {
+            let options: Vec<String> = options.iter().map(Value::to_string).collect();
+            self.fail(path, format!("must be one of {}", options.join(", ")));
+        }
+        if let Some(expected) = schema.get("const")
+            && expected != instance
+        {
+            self.fail(path, format!("must equal {expected}"));
+        }
+
+        if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+            for sub in all {
+                self.check(sub, instance, path);
+            }
+        }
+        if let Some(any) = schema.get("anyOf").and_then(Value::as_array)
+            && !any.iter().any(|sub| self.is_valid(sub, instance, path))
+        {
+            self.fail(
+                path,
+                "does not match any of the allowed schemas".to_string(),
+            );
+        }
+        if let Some(one) = schema.get("oneOf").and_then(Value::as_array) {
+            let matches = one
+                .iter()
+                .filter(|sub| self.is_valid(sub, instance, path))
+                .count();
+            if matches != 1 {
+                self.fail(
+                    path,
+                    format!("must match exactly one allowed schema, matched {matches}"),
+                );
+            }
+        }
+
+        match instance {
+            Value::Object(object) => self.check_object(schema, object, path),
+            Value::Array(items) => self.check_array(schema, items, path),
+            Value::String(text) => self.check_string(schema, text, path),
+            Value::Number(number) => {
+                if let Some(number) = number.as_f64() {
+                    self.check_number(schema, number, path);
+                }
+            }
+            Value::Bool(_) | Value::Null => {}
+        }
+    }
+
+    fn check_object(
+        &mut self,
+        schema: &'a serde_json::Map<String, Value>,
+        object: &serde_json::Map<String, Value>,
+        path: &str,
+    ) {
+        let properties = schema.get("properties").and_then(Value::as_object);
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(name) {
+                    self.fail(path, format!("missing required property `{name}`"));
+                }
+            }
+        }
+        for (name, value) in object {
+            let child = format!("{path}/{}", escape_pointer(name));
+            match properties.and_then(|properties| properties.get(name)) {
+                Some(property) => self.check(property, value, &child),
+                None => match schema.get("additionalProperties") {
+                    Some(Value::Bool(false)) => {
+                        self.fail(path, format!("unexpected property `{name}`"));
+                    }
+                    Some(additional @ Value::Object(_)) => self.check(additional, value, &child),
+                    _ => {}
+                },
+            }
+        }
+    }
+
+    fn check_array(
+        &mut self,
+        schema: &'a serde_json::Map<String, Value>,
+        items: &[Value],
+        path: &str,
+    ) {
+        if let Some(min) = schema.get("minItems").and_then(Value::as_u64)
+            && (items.len() as u64) < min
+        {
+            self.fail(path, format!("must have at least {min} items"));
+        }
+        if let Some(max) = schema.get("maxItems").and_then(Value::as_u64)
+            && (items.len() as u64) > max
+        {
+            self.fail(path, format!("must have at most {max} items"));
+        }
+        if let Some(item_schema) = schema.get("items") {
+            for (idx, item) in items.iter().enumerate() {
+                self.check(item_schema, item, &format!("{path}/{idx}"));
+            }
+        }
+    }
+
+    fn check_string(&mut self, schema: &serde_json::Map<String, Value>, text: &str, path: &str) {
+        let len = text.chars().count() as u64;
+        if let Some(min) = schema.get("minLength").and_then(Value::as_u64)
+            && len < min
+        {
+            self.fail(path, format!("must be at least {min} characters"));
+        }
+        if let Some(max) = schema.get("maxLength").and_then(Value::as_u64)
+            && len > max
+        {
+            self.fail(path, format!("must be at most {max} characters"));
+        }
+        if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+            match regex_lite::Regex::new(pattern) {
+                Ok(regex) if !regex.is_match(text) => {
+                    self.fail(path, format!("must match the pattern `{pattern}`"));
+                }
+                Ok(_) => {}
+                Err(err) => self.fail(
+                    path,
+                    format!("invalid pattern `{pattern}` in schema: {err}"),
+                ),
+            }
+        }
+    }
+
+    fn check_number(&mut self, schema: &serde_json::Map<String, Value>, number: f64, path: &str) {
+        let bound = |key: &str| schema.get(key).and_then(Value::as_f64);
+        if let Some(min) = bound("minimum")
+            && number < min
+        {
+            self.fail(path, format!("must be at least {min}"));
+        }
+        if let Some(max) = bound("maximum")
+            && number > max
+        {
+            self.fail(path, format!("must be at most {max}"));
+        }
+        if let Some(min) = bound("exclusiveMinimum")
+            && number <= min
+        {
+            self.fail(path, format!("must be greater than {min}"));
+        }
+        if let Some(max) = bound("exclusiveMaximum")
+            && number >= max
+        {
+            self.fail(path, format!("must be less than {max}"));
+        }
+    }
+}
+
+fn type_matches(expected: &Value, instance: &Value) -> bool {
+    match expected {
+        Value::String(name) => type_name_matches(name, instance),
+        Value::Array(names) => names
+            .iter()
+            .filter_map(Value::as_str)
+            .any(|name| type_name_matches(name, instance)),
+        _ => true,
+    }
+}
+
+fn type_name_matches(name: &str, instance: &Value) -> bool {
+    match name {
+        "object" => instance.is_object(),
+        "array" => instance.is_array(),
+        "string" => instance.is_string(),
+        "boolean" => instance.is_boolean(),
+        "null" => instance.is_null(),
+        "number" => instance.is_number(),
+        "integer" => {
+            instance.is_i64()
+                || instance.is_u64()
+                || instance.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        _ => true,
+    }
+}
+
+fn describe_type(expected: &Value) -> String {
+    match expected {
+        Value::Array(names) => names
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(" or "),
+        Value::String(name) => name.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn kind_of(instance: &Value) -> &'static str {
+    match instance {
+        Value::Object(_) => "object",
+        Value::Array(_) => "array",
+        Value::String(_) => "string",
+        Value::Bool(_) => "boolean",
+        Value::Null => "null",
+        Value::Number(_) => "number",
+    }
+}
+
+fn escape_pointer(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn release_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "version": { "type": "string", "pattern": "^\\d+\\.\\d+\\.\\d+$" },
+                "changes": {
+                    "type": "array",
+                    "minItems": 1,
+                    "items": { "$ref": "#/$defs/change" }
+                }
+            },
+            "required": ["version", "changes"],
+            "additionalProperties": false,
+            "$defs": {
+                "change": {
+                    "type": "object",
+                    "properties": {
+                        "kind": { "enum": ["feat", "fix"] },
+                        "pr": { "type": ["integer", "null"] }
+                    },
+                    "required": ["kind", "pr"],
+                    "additionalProperties": false
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn accepts_a_matching_value() {
+        let value = json!({
+            "version": "1.2.3",
+            "changes": [{ "kind": "fix", "pr": 42 }, { "kind": "feat", "pr": null }]
+        });
+        assert_eq!(validate(&release_schema(), &value), Vec::new());
+    }
+
+    #[test]
+    fn reports_each_violation_with_its_path() {
+        let value = json!({
+            "version": "next",
+            "changes": [{ "kind": "chore", "pr": 1.5 }],
+            "extra": true
+        });
+        // Property order depends on serde_json features, so compare sorted.
+        let mut paths: Vec<(String, String)> = validate(&release_schema(), &value)
+            .into_iter()
+            .map(|violation| (violation.path, violation.message))
+            .collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                ("".to_string(), "unexpected property `extra`".to_string()),
+                (
+                    "/changes/0/kind".to_string(),
+                    "must be one of \"feat\", \"fix\"".to_string()
+                ),
+                (
+                    "/changes/0/pr".to_string(),
+                    "expected integer or null, found number".to_string()
+                ),
+                (
+                    "/version".to_string(),
+                    "must match the pattern `^\\d+\\.\\d+\\.\\d+$`".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn recursive_references_terminate() {
+        let schema = json!({
+            "$defs": {
+                "node": {
+                    "$ref": "#/$defs/node",
+                    "type": "object",
+                    "properties": { "children": { "type": "array", "items": { "$ref": "#/$defs/node" } } },
+                    "required": ["children"]
+                },
+                "loop": { "anyOf": [{ "$ref": "#/$defs/loop" }] }
+            },
+            "type": "object",
+            "properties": {
+                "tree": { "$ref": "#/$defs/node" },
+                "loop": { "$ref": "#/$defs/loop" }
+            }
+        });
+        let value = json!({
+            "tree": { "children": [{ "children": [] }, { "children": [{}] }] },
+            "loop": 1
+        });
+        assert_eq!(
+            validate(&schema, &value),
+            vec![SchemaViolation {
+                path: "/tree/children/1/children/0".to_string(),
+                message: "missing required property `children`".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn guard_requests_repairs_then_fails() {
+        let mut guard = OutputSchemaGuard::new(json!({ "type": "object" }), true, 1);
+
+        let OutputCheck::Repair(prompt) = guard.check(Some("Here you go: {}")) else {
+            panic!("expected a repair turn");
+        };
+        assert!(prompt.contains("(root): the final message is not valid JSON"));
+
+        let OutputCheck::Failed(failure) = guard.check(Some("[]")) else {
+            panic!("expected the guard to give up");
+        };
+        assert_eq!(
+            serde_json::to_value(failure).expect("serialize failure"),
+            json!({
+                "type": "output_schema_error",
+                "attempts": 2,
+                "errors": [{ "path": "", "message": "expected object, found array" }]
+            })
+        );
+    }
+
+    #[test]
+    fn guard_moves_the_schema_into_the_prompt_when_it_cannot_be_sent() {
+        let schema = json!({ "type": "object" });
+        let sent = OutputSchemaGuard::new(schema.clone(), true, 0);
+        assert_eq!(sent.request_schema(), Some(schema.clone()));
+        assert_eq!(sent.prompt_instructions(), None);
+
+        let inline = OutputSchemaGuard::new(schema, false, 0);
+        assert_eq!(inline.request_schema(), None);
+        let instructions = inline
+            .prompt_instructions()
+            .expect("instructions for an inline schema");
+        assert!(
+            instructions.contains("\"type\": \"object\""),
+            "{instructions}"
+        );
+    }
+
+    #[test]
+    fn guard_accepts_valid_json_after_trimming() {
+        let mut guard = OutputSchemaGuard::new(json!({ "type": "object" }), true, 0);
+        assert!(matches!(guard.check(Some("  {}\n")), OutputCheck::Valid));
+    }
+}
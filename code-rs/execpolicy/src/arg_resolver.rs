@@ -1,9 +1,14 @@
+use std::collections::HashMap;
+
 use serde::Serialize;
 
+use crate::ArgType;
 use crate::arg_matcher::ArgMatcher;
 use crate::arg_matcher::ArgMatcherCardinality;
 use crate::error::Error;
 use crate::error::Result;
+use crate::opt::Opt;
+use crate::opt::OptMeta;
 use crate::valid_exec::MatchedArg;
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize)]
@@ -12,10 +17,13 @@ pub struct PositionalArg {
     pub value: String,
 }
 
+/// When `stdin_is_pipe` is set, `ARG_RFILES` may match nothing: a command
+/// in the middle of a pipeline reads its input from the previous stage.
 pub fn resolve_observed_args_with_patterns(
     program: &str,
     args: Vec<PositionalArg>,
     arg_patterns: &Vec<ArgMatcher>,
+    stdin_is_pipe: bool,
 ) -> Result<Vec<MatchedArg>> {
     // Naive matching implementation. Among `arg_patterns`, there is allowed to
     // be at most one vararg pattern. Assuming `arg_patterns` is non-empty, we
@@ -84,7 +92,7 @@ pub fn resolve_observed_args_with_patterns(
                 });
             }
             ArgMatcherCardinality::AtLeastOne => {
-                if vararg.is_empty() {
+                if vararg.is_empty() && !(stdin_is_pipe && pattern == ArgMatcher::ReadableFiles) {
                     return Err(Error::VarargMatcherDidNotMatchAnything {
                         program: program.to_string(),
                         matcher: pattern,
@@ -144,6 +152,116 @@ pub fn resolve_observed_args_with_patterns(
     }
 }
 
+/// An option observed on the command line, after bundles such as `-al` and
+/// `--name=value` forms have been split into their individual options.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ObservedOption {
+    /// An option that does not take a value.
+    Flag { name: String },
+    /// An option whose value was supplied in the same argument, e.g.
+    /// `--color=auto` or `-n5`.
+    Value {
+        name: String,
+        value: String,
+        arg_type: ArgType,
+    },
+    /// An option whose value is the next argument on the command line.
+    ExpectsValue { name: String, arg_type: ArgType },
+}
+
+/// Resolves a single `-`-prefixed argument against `allowed_options`.
+///
+/// An exact match always wins. Otherwise, if `combined_format` is set,
+/// `--name=value` is accepted for options declared with opt(), and if
+/// `option_bundling` is set, `-abc` is treated as `-a -b -c`. Within a bundle,
+/// every option but the last must be a flag; an opt() ends the bundle and
+/// takes the rest of the argument (`-n5`) or, if nothing is left, the next
+/// argument as its value.
+pub fn resolve_option_arg(
+    program: &str,
+    arg: &str,
+    allowed_options: &HashMap<String, Opt>,
+    option_bundling: bool,
+    combined_format: bool,
+) -> Result<Vec<ObservedOption>> {
+    if let Some(opt) = allowed_options.get(arg) {
+        return Ok(vec![observed_option(arg, opt)]);
+    }
+
+    if combined_format
+        && arg.starts_with("--")
+        && let Some((name, value)) = arg.split_once('=')
+    {
+        return match allowed_options.get(name).map(|opt| &opt.meta) {
+            Some(OptMeta::Value(arg_type)) => Ok(vec![ObservedOption::Value {
+                name: name.to_string(),
+                value: value.to_string(),
+                arg_type: arg_type.clone(),
+            }]),
+            Some(OptMeta::Flag) => Err(Error::FlagDoesNotTakeValue {
+                program: program.to_string(),
+                option: name.to_string(),
+                value: value.to_string(),
+            }),
+            None => Err(Error::UnknownOption {
+                program: program.to_string(),
+                option: name.to_string(),
+            }),
+        };
+    }
+
+    if option_bundling && !arg.starts_with("--") && arg.len() > 2 {
+        let bundle = &arg[1..];
+        let mut options = Vec::new();
+        for (offset, c) in bundle.char_indices() {
+            let name = format!("-{c}");
+            let Some(opt) = allowed_options.get(&name) else {
+                return Err(Error::UnknownOption {
+                    program: program.to_string(),
+                    option: name,
+                });
+            };
+            match &opt.meta {
+                OptMeta::Flag => options.push(ObservedOption::Flag { name }),
+                OptMeta::Value(arg_type) => {
+                    let rest = &bundle[offset + c.len_utf8()..];
+                    if rest.is_empty() {
+                        options.push(ObservedOption::ExpectsValue {
+                            name,
+                            arg_type: arg_type.clone(),
+                        });
+                    } else {
+                        options.push(ObservedOption::Value {
+                            name,
+                            value: rest.to_string(),
+                            arg_type: arg_type.clone(),
+                        });
+                    }
+                    break;
+                }
+            }
+        }
+        return Ok(options);
+    }
+
+    Err(Error::UnknownOption {
+        program: program.to_string(),
+        option: arg.to_string(),
+    })
+}
+
+fn observed_option(name: &str, opt: &Opt) -> ObservedOption {
+    match &opt.meta {
+        OptMeta::Flag => ObservedOption::Flag {
+            name: name.to_string(),
+        },
+        OptMeta::Value(arg_type) => ObservedOption::ExpectsValue {
+            name: name.to_string(),
+            arg_type: arg_type.clone(),
+        },
+    }
+}
+
 #[derive(Default)]
 struct ParitionedArgs {
     num_prefix_args: usize,
@@ -10,7 +10,7 @@ use serde_with::serde_as;
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[serde_as]
-#[derive(Debug, Eq, PartialEq, Serialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
 #[serde(tag = "type")]
 pub enum Error {
     NoSpecForProgram {
@@ -29,6 +29,11 @@ pub enum Error {
         program: String,
         option: String,
     },
+    FlagDoesNotTakeValue {
+        program: String,
+        option: String,
+        value: String,
+    },
     UnexpectedArguments {
         program: String,
         args: Vec<PositionalArg>,
@@ -88,6 +93,13 @@ pub enum Error {
     CannotCheckRelativePath {
         file: PathBuf,
     },
+    ShellSyntaxError {
+        script: String,
+    },
+    UnsupportedShellSyntax {
+        kind: String,
+        text: String,
+    },
     CannotCanonicalizePath {
         file: String,
         #[serde_as(as = "DisplayFromStr")]
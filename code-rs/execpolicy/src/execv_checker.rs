@@ -9,11 +9,17 @@ use crate::Error::CannotCheckRelativePath;
 use crate::Error::ReadablePathNotInReadableFolders;
 use crate::Error::WriteablePathNotInWriteableFolders;
 use crate::ExecCall;
+use crate::Explanation;
 use crate::MatchedExec;
 use crate::Policy;
 use crate::Result;
 use crate::ValidExec;
+use crate::shell::Redirect;
+use crate::shell::RedirectKind;
+use crate::shell::ShellVerdict;
+use crate::shell::shell_segments;
 use path_absolutize::*;
+use tree_sitter::Tree;
 
 macro_rules! check_file_in_folders {
     ($file:expr, $folders:expr, $error:ident) => {
@@ -39,6 +45,24 @@ impl ExecvChecker {
         self.execv_policy.check(exec_call)
     }
 
+    /// Explains how `exec_call` fares against the policy and, if it matches,
+    /// whether its file arguments fall within the allowed folders.
+    pub fn explain(
+        &self,
+        exec_call: &ExecCall,
+        cwd: &Option<OsString>,
+        readable_folders: &[PathBuf],
+        writeable_folders: &[PathBuf],
+    ) -> Explanation {
+        let mut explanation = self.execv_policy.explain(exec_call);
+        if let Ok(MatchedExec::Match { exec }) = &explanation.result {
+            explanation.folder_check = self
+                .check(exec.clone(), cwd, readable_folders, writeable_folders)
+                .err();
+        }
+        explanation
+    }
+
     /// The caller is responsible for ensuring readable_folders and
     /// writeable_folders are in canonical form.
     pub fn check(
@@ -96,6 +120,97 @@ impl ExecvChecker {
 
         Ok(program)
     }
+
+    /// Checks every command in a parsed shell script, including pipeline
+    /// stages and `&&`/`||`/`;` list members. Files that output redirections
+    /// write to must be within `writeable_folders`; `/dev/null` is always
+    /// allowed. Scripts with words bash would expand or that `cd` elsewhere
+    /// are rejected, as every path is resolved against `cwd`. The caller is responsible for ensuring readable_folders and
+    /// writeable_folders are in canonical form.
+    pub fn check_shell(
+        &self,
+        tree: &Tree,
+        script: &str,
+        cwd: &Option<OsString>,
+        readable_folders: &[PathBuf],
+        writeable_folders: &[PathBuf],
+    ) -> ShellVerdict {
+        let segments = match shell_segments(tree, script) {
+            Ok(segments) => segments,
+            Err(error) => {
+                return ShellVerdict::Rejected {
+                    segment: script.to_string(),
+                    error,
+                };
+            }
+        };
+
+        let mut commands = Vec::new();
+        let mut first_rejection = None;
+        for segment in segments {
+            let result = if segment.stdin_is_pipe {
+                self.execv_policy.check_with_stdin_pipe(&segment.exec_call)
+            } else {
+                self.execv_policy.check(&segment.exec_call)
+            };
+            let checked = match result {
+                Ok(MatchedExec::Match { exec }) => self
+                    .check(exec.clone(), cwd, readable_folders, writeable_folders)
+                    .and_then(|_| {
+                        segment.redirects.iter().try_for_each(|redirect| {
+                            check_redirect(redirect, cwd, readable_folders, writeable_folders)
+                        })
+                    })
+                    .map(|()| exec),
+                Ok(MatchedExec::Forbidden { reason, .. }) => {
+                    return ShellVerdict::Forbidden {
+                        segment: segment.text,
+                        reason,
+                    };
+                }
+                Err(error) => Err(error),
+            };
+            match checked {
+                Ok(exec) => commands.push(exec),
+                Err(error) => {
+                    first_rejection.get_or_insert((segment.text, error));
+                }
+            }
+        }
+
+        match first_rejection {
+            Some((segment, error)) => ShellVerdict::Rejected { segment, error },
+            None => ShellVerdict::Allowed { commands },
+        }
+    }
+}
+
+fn check_redirect(
+    redirect: &Redirect,
+    cwd: &Option<OsString>,
+    readable_folders: &[PathBuf],
+    writeable_folders: &[PathBuf],
+) -> Result<()> {
+    match redirect.kind {
+        RedirectKind::Write if redirect.target == "/dev/null" => {}
+        RedirectKind::Write => {
+            let writeable_file = ensure_absolute_path(&redirect.target, cwd)?;
+            check_file_in_folders!(
+                writeable_file,
+                writeable_folders,
+                WriteablePathNotInWriteableFolders
+            );
+        }
+        RedirectKind::Read => {
+            let readable_file = ensure_absolute_path(&redirect.target, cwd)?;
+            check_file_in_folders!(
+                readable_file,
+                readable_folders,
+                ReadablePathNotInReadableFolders
+            );
+        }
+    }
+    Ok(())
 }
 
 fn ensure_absolute_path(path: &str, cwd: &Option<OsString>) -> Result<PathBuf> {
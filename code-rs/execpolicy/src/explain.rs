@@ -0,0 +1,238 @@
+//! Rule traces for `Policy::explain()`: which specs were considered, how each
+//! argument was resolved, and what policy addition would have allowed the
+//! command.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::ArgType;
+use crate::ExecCall;
+use crate::MatchedExec;
+use crate::error::Error;
+use crate::error::Result;
+
+/// How a single argument was resolved while checking a spec.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(tag = "step", rename_all = "snake_case")]
+pub enum TraceStep {
+    /// Argument `index` (or part of it, for a bundle) matched a flag().
+    Flag { index: usize, name: String },
+    /// Argument `index` matched an opt() and its value was validated.
+    Opt {
+        index: usize,
+        name: String,
+        value: String,
+        r#type: ArgType,
+    },
+    /// Positional argument `index` was matched against an `ARG_*` pattern.
+    Arg {
+        index: usize,
+        value: String,
+        r#type: ArgType,
+    },
+}
+
+/// The outcome of checking one `define_program()` spec.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct SpecTrace {
+    pub source_location: Option<String>,
+    pub option_bundling: bool,
+    pub combined_format: bool,
+    pub steps: Vec<TraceStep>,
+    /// Why the spec rejected the command, if it did.
+    pub error: Option<Error>,
+}
+
+/// Everything `Policy::explain()` learned about a command.
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct Explanation {
+    pub exec_call: ExecCall,
+    pub specs: Vec<SpecTrace>,
+    pub result: Result<MatchedExec>,
+    /// Values typed as files the command may write (see
+    /// `ArgType::might_write_file`).
+    pub might_write: Vec<String>,
+    /// Set by `ExecvChecker::explain()` when a matched command reads or
+    /// writes outside the allowed folders.
+    pub folder_check: Option<Error>,
+    /// Smallest policy addition that would have allowed the command.
+    pub suggestion: Option<String>,
+}
+
+impl Explanation {
+    pub(crate) fn new(
+        exec_call: ExecCall,
+        specs: Vec<SpecTrace>,
+        result: Result<MatchedExec>,
+    ) -> Self {
+        let might_write = match &result {
+            Ok(MatchedExec::Match { exec }) => exec
+                .opts
+                .iter()
+                .filter(|opt| opt.r#type.might_write_file())
+                .map(|opt| opt.value.clone())
+                .chain(
+                    exec.args
+                        .iter()
+                        .filter(|arg| arg.r#type.might_write_file())
+                        .map(|arg| arg.value.clone()),
+                )
+                .collect(),
+            _ => Vec::new(),
+        };
+        let suggestion = match &result {
+            Err(_) => Some(suggest_policy_addition(&exec_call, &specs)),
+            Ok(_) => None,
+        };
+        Self {
+            exec_call,
+            specs,
+            result,
+            might_write,
+            folder_check: None,
+            suggestion,
+        }
+    }
+}
+
+fn suggest_policy_addition(exec_call: &ExecCall, specs: &[SpecTrace]) -> String {
+    // An unknown option is the one case where extending an existing spec is
+    // smaller than adding a new one.
+    for spec in specs {
+        let Some(Error::UnknownOption { option, .. }) = &spec.error else {
+            continue;
+        };
+        let location = spec
+            .source_location
+            .as_deref()
+            .map(|location| format!(" at {location}"))
+            .unwrap_or_default();
+        // Without combined_format, `--name=value` is reported whole.
+        let (option, takes_value) = match option.split_once('=') {
+            Some((name, _value)) if name.starts_with("--") => (name, true),
+            _ => (
+                option.as_str(),
+                exec_call
+                    .args
+                    .iter()
+                    .any(|arg| arg.starts_with(&format!("{option}="))),
+            ),
+        };
+        return if takes_value {
+            let combined_format = if spec.combined_format {
+                String::new()
+            } else {
+                " and set combined_format=True".to_string()
+            };
+            format!(
+                "add opt(\"{option}\", ARG_OPAQUE_VALUE) to the options of the `{}` spec{location}{combined_format}",
+                exec_call.program
+            )
+        } else {
+            format!(
+                "add flag(\"{option}\") to the options of the `{}` spec{location}",
+                exec_call.program
+            )
+        };
+    }
+
+    new_program_snippet(exec_call)
+}
+
+/// A `define_program()` that matches exactly `exec_call`.
+fn new_program_snippet(exec_call: &ExecCall) -> String {
+    let mut options = Vec::new();
+    let mut args = Vec::new();
+    let mut combined_format = false;
+    for arg in &exec_call.args {
+        if arg.starts_with('-') && arg != "-" {
+            match arg.split_once('=') {
+                Some((name, _value)) if name.starts_with("--") => {
+                    combined_format = true;
+                    options.push(format!("opt({name:?}, ARG_OPAQUE_VALUE)"));
+                }
+                _ => options.push(format!("flag({arg:?})")),
+            }
+        } else if looks_like_path(arg) {
+            args.push("ARG_RFILE".to_string());
+        } else {
+            args.push(format!("{arg:?}"));
+        }
+    }
+    options.dedup();
+
+    let mut snippet = format!("define_program(\n    program={:?},\n", exec_call.program);
+    if combined_format {
+        snippet.push_str("    combined_format=True,\n");
+    }
+    if !options.is_empty() {
+        snippet.push_str(&format!("    options=[{}],\n", options.join(", ")));
+    }
+    snippet.push_str(&format!("    args=[{}],\n", args.join(", ")));
+    snippet.push_str(&format!("    should_match=[{:?}],\n)", exec_call.args));
+    snippet
+}
+
+fn looks_like_path(arg: &str) -> bool {
+    arg.contains('/') || arg.contains('.') || arg == "~"
+}
+
+impl fmt::Display for TraceStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceStep::Flag { index, name } => write!(f, "arg {index}: flag {name}"),
+            TraceStep::Opt {
+                index,
+                name,
+                value,
+                r#type,
+            } => write!(f, "arg {index}: opt {name} = `{value}` ({type:?})"),
+            TraceStep::Arg {
+                index,
+                value,
+                r#type,
+            } => write!(f, "arg {index}: `{value}` matched {type:?}"),
+        }
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "command: {}", self.exec_call)?;
+        if self.specs.is_empty() && self.result.is_err() {
+            writeln!(f, "no define_program() for `{}`", self.exec_call.program)?;
+        }
+        for (i, spec) in self.specs.iter().enumerate() {
+            let location = spec
+                .source_location
+                .as_deref()
+                .unwrap_or("unknown location");
+            writeln!(f, "spec #{} ({location}):", i + 1)?;
+            for step in &spec.steps {
+                writeln!(f, "  {step}")?;
+            }
+            match &spec.error {
+                Some(error) => writeln!(f, "  rejected: {error:?}")?,
+                None => writeln!(f, "  matched")?,
+            }
+        }
+        match &self.result {
+            Ok(MatchedExec::Match { .. }) => writeln!(f, "result: match")?,
+            Ok(MatchedExec::Forbidden { reason, .. }) => {
+                writeln!(f, "result: forbidden: {reason}")?
+            }
+            Err(error) => writeln!(f, "result: rejected: {error:?}")?,
+        }
+        for path in &self.might_write {
+            writeln!(f, "might write: {path}")?;
+        }
+        if let Some(error) = &self.folder_check {
+            writeln!(f, "folder check failed: {error:?}")?;
+        }
+        if let Some(suggestion) = &self.suggestion {
+            writeln!(f, "suggestion:\n{suggestion}")?;
+        }
+        Ok(())
+    }
+}
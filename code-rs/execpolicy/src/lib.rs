@@ -9,11 +9,14 @@ mod arg_type;
 mod error;
 mod exec_call;
 mod execv_checker;
+mod explain;
 mod opt;
+mod overlay;
 mod policy;
 mod policy_parser;
 mod program;
 mod sed_command;
+mod shell;
 mod valid_exec;
 
 pub use arg_matcher::ArgMatcher;
@@ -23,7 +26,17 @@ pub use error::Error;
 pub use error::Result;
 pub use exec_call::ExecCall;
 pub use execv_checker::ExecvChecker;
+pub use explain::Explanation;
+pub use explain::SpecTrace;
+pub use explain::TraceStep;
 pub use opt::Opt;
+pub use overlay::LoadedPolicy;
+pub use overlay::OverlayConflict;
+pub use overlay::OverlayPaths;
+pub use overlay::default_code_home;
+pub use overlay::get_policy_with_overlays;
+pub use overlay::load_policy_with_overlays;
+pub use overlay::overlay_paths;
 pub use policy::Policy;
 pub use policy_parser::PolicyParser;
 pub use program::Forbidden;
@@ -32,6 +45,12 @@ pub use program::NegativeExamplePassedCheck;
 pub use program::PositiveExampleFailedCheck;
 pub use program::ProgramSpec;
 pub use sed_command::parse_sed_command;
+pub use shell::Redirect;
+pub use shell::RedirectKind;
+pub use shell::ShellSegment;
+pub use shell::ShellVerdict;
+pub use shell::parse_shell;
+pub use shell::shell_segments;
 pub use valid_exec::MatchedArg;
 pub use valid_exec::MatchedFlag;
 pub use valid_exec::MatchedOpt;
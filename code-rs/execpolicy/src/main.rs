@@ -2,15 +2,21 @@ use anyhow::Result;
 use clap::Parser;
 use clap::Subcommand;
 use codex_execpolicy::ExecCall;
+use codex_execpolicy::ExecvChecker;
 use codex_execpolicy::MatchedExec;
 use codex_execpolicy::Policy;
 use codex_execpolicy::PolicyParser;
+use codex_execpolicy::ShellVerdict;
 use codex_execpolicy::ValidExec;
+use codex_execpolicy::default_code_home;
 use codex_execpolicy::get_default_policy;
+use codex_execpolicy::get_policy_with_overlays;
+use codex_execpolicy::parse_shell;
 use serde::Deserialize;
 use serde::Serialize;
 use serde::de;
 use starlark::Error as StarlarkError;
+use std::path::Path;
 use std::path::PathBuf;
 use std::str::FromStr;
 
@@ -30,6 +36,11 @@ pub struct Args {
     #[clap(long, short = 'p')]
     pub policy: Option<PathBuf>,
 
+    /// Ignore `.policy` overlays from $CODE_HOME and the project's `.code/`
+    /// directory. Overlays are never applied when --policy is given.
+    #[clap(long)]
+    pub no_overlays: bool,
+
     #[command(subcommand)]
     pub command: Command,
 }
@@ -42,6 +53,32 @@ pub enum Command {
         command: Vec<String>,
     },
 
+    /// Explains how the command was checked: the specs considered, how each
+    /// argument was resolved, and a policy addition that would allow it.
+    Explain {
+        /// Print the explanation as JSON.
+        #[clap(long)]
+        json: bool,
+
+        #[arg(trailing_var_arg = true)]
+        command: Vec<String>,
+    },
+
+    /// Checks every command in a shell script: pipeline stages, `&&`/`||`/`;`
+    /// list members and the files they redirect to.
+    #[clap(name = "check-shell")]
+    CheckShell {
+        /// Folder the script may read from. Defaults to the working directory.
+        #[clap(long)]
+        readable: Vec<PathBuf>,
+
+        /// Folder the script may write to. Defaults to the working directory.
+        #[clap(long)]
+        writeable: Vec<PathBuf>,
+
+        script: String,
+    },
+
     /// Checks the command encoded as a JSON object.
     #[clap(name = "check-json")]
     CheckJson {
@@ -68,11 +105,18 @@ fn main() -> Result<()> {
             let policy_source = policy.to_string_lossy().to_string();
             let unparsed_policy = std::fs::read_to_string(policy)?;
             let parser = PolicyParser::new(&policy_source, &unparsed_policy);
-            parser.parse()
+            parser.parse().map_err(StarlarkError::into_anyhow)?
+        }
+        None if args.no_overlays => get_default_policy().map_err(StarlarkError::into_anyhow)?,
+        None => {
+            let cwd = std::env::current_dir()?;
+            let loaded = get_policy_with_overlays(default_code_home().as_deref(), &cwd)?;
+            for conflict in &loaded.conflicts {
+                eprintln!("warning: {conflict}");
+            }
+            loaded.policy
         }
-        None => get_default_policy(),
     };
-    let policy = policy.map_err(StarlarkError::into_anyhow)?;
 
     let exec = match args.command {
         Command::Check { command } => match command.split_first() {
@@ -86,6 +130,52 @@ fn main() -> Result<()> {
             }
         },
         Command::CheckJson { exec } => exec,
+        Command::Explain { json, command } => {
+            let Some((program, args)) = command.split_first() else {
+                eprintln!("no command provided");
+                std::process::exit(1);
+            };
+            let exec_call = ExecCall {
+                program: program.to_string(),
+                args: args.to_vec(),
+            };
+            let explanation = policy.explain(&exec_call);
+            if json {
+                println!("{}", serde_json::to_string(&explanation)?);
+            } else {
+                print!("{explanation}");
+            }
+            return Ok(());
+        }
+        Command::CheckShell {
+            readable,
+            writeable,
+            script,
+        } => {
+            let cwd = std::env::current_dir()?;
+            let readable = canonical_folders(readable, &cwd)?;
+            let writeable = canonical_folders(writeable, &cwd)?;
+            let Some(tree) = parse_shell(&script) else {
+                eprintln!("failed to parse script");
+                std::process::exit(1);
+            };
+            let checker = ExecvChecker::new(policy);
+            let verdict = checker.check_shell(
+                &tree,
+                &script,
+                &Some(cwd.into_os_string()),
+                &readable,
+                &writeable,
+            );
+            let exit_code = match &verdict {
+                ShellVerdict::Allowed { .. } => 0,
+                ShellVerdict::Forbidden { .. } if args.require_safe => FORBIDDEN_EXIT_CODE,
+                ShellVerdict::Rejected { .. } if args.require_safe => MIGHT_BE_SAFE_EXIT_CODE,
+                ShellVerdict::Forbidden { .. } | ShellVerdict::Rejected { .. } => 0,
+            };
+            println!("{}", serde_json::to_string(&verdict)?);
+            std::process::exit(exit_code);
+        }
     };
 
     let (output, exit_code) = check_command(&policy, exec, args.require_safe);
@@ -94,6 +184,16 @@ fn main() -> Result<()> {
     std::process::exit(exit_code);
 }
 
+fn canonical_folders(folders: Vec<PathBuf>, cwd: &Path) -> Result<Vec<PathBuf>> {
+    if folders.is_empty() {
+        return Ok(vec![cwd.canonicalize()?]);
+    }
+    folders
+        .into_iter()
+        .map(|folder| Ok(cwd.join(folder).canonicalize()?))
+        .collect()
+}
+
 fn check_command(
     policy: &Policy,
     ExecArg { program, args }: ExecArg,
@@ -0,0 +1,203 @@
+//! User- and project-level policy overlays.
+//!
+//! Overlays are ordinary `.policy` files layered on top of the embedded
+//! default policy, in this order:
+//!
+//! 1. `$CODE_HOME/*.policy` (falling back to `$CODEX_HOME`, then `~/.code`)
+//! 2. `<project>/.code/*.policy`, where `<project>` is the nearest ancestor
+//!    of the working directory that contains `.git`
+//!
+//! Files within a directory are applied in lexicographic order. A program
+//! defined by a user overlay replaces every spec for that program from
+//! earlier layers; see [`Policy::apply_overlay`]. Project overlays come from
+//! the repository being worked on, so they may add programs but never widen
+//! one that is already defined: their specs are applied on top of the
+//! existing ones and a command must satisfy both; see
+//! [`Policy::apply_restricting_overlay`].
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::path::PathBuf;
+
+use starlark::Error as StarlarkError;
+
+use crate::Policy;
+use crate::PolicyParser;
+use crate::get_default_policy;
+
+const POLICY_EXTENSION: &str = "policy";
+
+/// The default policy with all overlays applied.
+pub struct LoadedPolicy {
+    pub policy: Policy,
+    /// Overlay files that were applied, in order.
+    pub overlays: Vec<PathBuf>,
+    /// Programs defined by more than one overlay file.
+    pub conflicts: Vec<OverlayConflict>,
+}
+
+/// A program defined by two different overlay files. The later definition
+/// wins (or, for a project overlay, is applied on top), but the conflict is
+/// surfaced so it does not go unnoticed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OverlayConflict {
+    pub program: String,
+    /// Location of the definition that was replaced.
+    pub previous: String,
+    /// Location of the definition that is in effect.
+    pub current: String,
+}
+
+impl fmt::Display for OverlayConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "program `{}` is defined at {} and again at {}",
+            self.program, self.previous, self.current
+        )
+    }
+}
+
+/// Overlay files found for a `code_home` and working directory.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct OverlayPaths {
+    /// `$CODE_HOME/*.policy`, applied first.
+    pub user: Vec<PathBuf>,
+    /// `<project>/.code/*.policy`, applied as restricting overlays.
+    pub project: Vec<PathBuf>,
+}
+
+/// Overlay files to apply for `code_home` and `cwd`, each list in
+/// application order.
+pub fn overlay_paths(code_home: Option<&Path>, cwd: &Path) -> OverlayPaths {
+    let user = code_home.map(policy_files_in).unwrap_or_default();
+    let project_root = find_project_root(cwd);
+    let project = policy_files_in(&project_root.join(".code"));
+    OverlayPaths { user, project }
+}
+
+/// `$CODE_HOME`, then `$CODEX_HOME`, then `~/.code`.
+pub fn default_code_home() -> Option<PathBuf> {
+    std::env::var_os("CODE_HOME")
+        .or_else(|| std::env::var_os("CODEX_HOME"))
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".code")))
+}
+
+/// Loads the default policy and applies the overlays found for `code_home`
+/// and `cwd`.
+pub fn get_policy_with_overlays(
+    code_home: Option<&Path>,
+    cwd: &Path,
+) -> anyhow::Result<LoadedPolicy> {
+    load_policy_with_overlays(&overlay_paths(code_home, cwd))
+}
+
+/// Loads the default policy and applies the user overlays, then the project
+/// overlays, in order.
+///
+/// Each overlay's `should_match` and `should_not_match` examples are verified
+/// before it is applied.
+pub fn load_policy_with_overlays(overlays: &OverlayPaths) -> anyhow::Result<LoadedPolicy> {
+    let mut policy = get_default_policy().map_err(StarlarkError::into_anyhow)?;
+    let mut defined_by = HashMap::<String, (PathBuf, String)>::new();
+    let mut conflicts = Vec::new();
+
+    let user = overlays.user.iter().map(|path| (path, false));
+    let project = overlays.project.iter().map(|path| (path, true));
+    for (path, restricting) in user.chain(project) {
+        let overlay = parse_overlay(path)?;
+
+        let good_violations = overlay.check_each_good_list_individually();
+        if let Some(violation) = good_violations.first() {
+            anyhow::bail!(
+                "{}: should_match example {:?} for `{}` was rejected: {:?}",
+                path.display(),
+                violation.args,
+                violation.program,
+                violation.error
+            );
+        }
+        let bad_violations = overlay.check_each_bad_list_individually();
+        if let Some(violation) = bad_violations.first() {
+            anyhow::bail!(
+                "{}: should_not_match example {:?} for `{}` was accepted",
+                path.display(),
+                violation.args,
+                violation.program
+            );
+        }
+
+        let mut seen_in_file = BTreeMap::<String, String>::new();
+        for spec in overlay.program_specs() {
+            let location = spec
+                .source_location
+                .clone()
+                .unwrap_or_else(|| path.display().to_string());
+            seen_in_file.entry(spec.program.clone()).or_insert(location);
+        }
+        for (program, location) in seen_in_file {
+            if let Some((previous_path, previous)) = defined_by.get(&program)
+                && previous_path != path
+            {
+                conflicts.push(OverlayConflict {
+                    program: program.clone(),
+                    previous: previous.clone(),
+                    current: location.clone(),
+                });
+            }
+            defined_by.insert(program, (path.clone(), location));
+        }
+
+        if restricting {
+            policy.apply_restricting_overlay(overlay)
+        } else {
+            policy.apply_overlay(overlay)
+        }
+        .map_err(|e| anyhow::anyhow!("{}: {e}", path.display()))?;
+    }
+
+    Ok(LoadedPolicy {
+        policy,
+        overlays: overlays
+            .user
+            .iter()
+            .chain(&overlays.project)
+            .cloned()
+            .collect(),
+        conflicts,
+    })
+}
+
+fn parse_overlay(path: &Path) -> anyhow::Result<Policy> {
+    let unparsed_policy = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("failed to read {}: {e}", path.display()))?;
+    let policy_source = path.to_string_lossy().to_string();
+    PolicyParser::new(&policy_source, &unparsed_policy)
+        .parse()
+        .map_err(StarlarkError::into_anyhow)
+}
+
+fn policy_files_in(dir: &Path) -> Vec<PathBuf> {
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = read_dir
+        .filter_map(std::result::Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file() && path.extension().is_some_and(|ext| ext == POLICY_EXTENSION)
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+fn find_project_root(cwd: &Path) -> PathBuf {
+    cwd.ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .unwrap_or(cwd)
+        .to_path_buf()
+}
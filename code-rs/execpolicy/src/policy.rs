@@ -9,12 +9,18 @@ use crate::NegativeExamplePassedCheck;
 use crate::ProgramSpec;
 use crate::error::Error;
 use crate::error::Result;
+use crate::explain::Explanation;
+use crate::explain::SpecTrace;
 use crate::policy_parser::ForbiddenProgramRegex;
 use crate::program::PositiveExampleFailedCheck;
 
 pub struct Policy {
     programs: MultiMap<String, ProgramSpec>,
+    /// Specs from restricting overlays. A command for one of these programs
+    /// must match both a spec in `programs` and one of these.
+    restrictions: MultiMap<String, ProgramSpec>,
     forbidden_program_regexes: Vec<ForbiddenProgramRegex>,
+    forbidden_substrings: Vec<String>,
     forbidden_substrings_pattern: Option<Regex>,
 }
 
@@ -24,28 +30,158 @@ impl Policy {
         forbidden_program_regexes: Vec<ForbiddenProgramRegex>,
         forbidden_substrings: Vec<String>,
     ) -> std::result::Result<Self, RegexError> {
-        let forbidden_substrings_pattern = if forbidden_substrings.is_empty() {
-            None
-        } else {
-            let escaped_substrings = forbidden_substrings
-                .iter()
-                .map(|s| regex_lite::escape(s))
-                .collect::<Vec<_>>()
-                .join("|");
-            Some(Regex::new(&format!("({escaped_substrings})"))?)
-        };
+        let forbidden_substrings_pattern = compile_forbidden_substrings(&forbidden_substrings)?;
         Ok(Self {
             programs,
+            restrictions: MultiMap::new(),
             forbidden_program_regexes,
+            forbidden_substrings,
             forbidden_substrings_pattern,
         })
     }
 
+    /// Specs for every program in the policy, in definition order per program.
+    pub fn program_specs(&self) -> impl Iterator<Item = &ProgramSpec> {
+        self.programs.flat_iter().map(|(_program, spec)| spec)
+    }
+
+    /// Layers `overlay` on top of this policy. Programs defined by the overlay
+    /// replace any existing specs for the same program, so an overlay can both
+    /// add programs and tighten existing ones. Forbidden program regexes and
+    /// forbidden substrings from the overlay are added to the existing ones.
+    pub fn apply_overlay(&mut self, overlay: Policy) -> std::result::Result<(), RegexError> {
+        let programs = self.merge_forbidden(overlay)?;
+        for (program, specs) in programs {
+            self.restrictions.remove(&program);
+            self.programs.remove(&program);
+            self.programs.insert_many(program, specs);
+        }
+        Ok(())
+    }
+
+    /// Layers `overlay` on top of this policy without widening it. Programs
+    /// the policy does not define yet are added as with
+    /// [`Policy::apply_overlay`]; for programs it already defines, the
+    /// overlay's specs are applied in addition to the existing ones, so a
+    /// command must satisfy both. A later restricting overlay for the same
+    /// program replaces the earlier restriction.
+    pub fn apply_restricting_overlay(
+        &mut self,
+        overlay: Policy,
+    ) -> std::result::Result<(), RegexError> {
+        let programs = self.merge_forbidden(overlay)?;
+        for (program, specs) in programs {
+            if self.programs.contains_key(&program) {
+                self.restrictions.remove(&program);
+                self.restrictions.insert_many(program, specs);
+            } else {
+                self.programs.insert_many(program, specs);
+            }
+        }
+        Ok(())
+    }
+
+    fn merge_forbidden(
+        &mut self,
+        overlay: Policy,
+    ) -> std::result::Result<MultiMap<String, ProgramSpec>, RegexError> {
+        let Policy {
+            programs,
+            restrictions: _,
+            forbidden_program_regexes,
+            forbidden_substrings,
+            forbidden_substrings_pattern: _,
+        } = overlay;
+        self.forbidden_program_regexes
+            .extend(forbidden_program_regexes);
+        if !forbidden_substrings.is_empty() {
+            self.forbidden_substrings.extend(forbidden_substrings);
+            self.forbidden_substrings_pattern =
+                compile_forbidden_substrings(&self.forbidden_substrings)?;
+        }
+        Ok(programs)
+    }
+
     pub fn check(&self, exec_call: &ExecCall) -> Result<MatchedExec> {
+        self.check_impl(exec_call, false)
+    }
+
+    /// Like [`Policy::check`], for a command whose stdin is the output of an
+    /// earlier pipeline stage.
+    pub fn check_with_stdin_pipe(&self, exec_call: &ExecCall) -> Result<MatchedExec> {
+        self.check_impl(exec_call, true)
+    }
+
+    fn check_impl(&self, exec_call: &ExecCall, stdin_is_pipe: bool) -> Result<MatchedExec> {
+        if let Some(forbidden) = self.check_forbidden(exec_call) {
+            return Ok(forbidden);
+        }
+
+        let program = &exec_call.program;
+        let mut last_err = Err(Error::NoSpecForProgram {
+            program: program.clone(),
+        });
+        if let Some(spec_list) = self.programs.get_vec(program) {
+            for spec in spec_list {
+                match spec.check_impl(exec_call, stdin_is_pipe, &mut Vec::new()) {
+                    Ok(matched_exec) => {
+                        return self
+                            .check_restrictions(exec_call, stdin_is_pipe)
+                            .map(|()| matched_exec);
+                    }
+                    Err(err) => {
+                        last_err = Err(err);
+                    }
+                }
+            }
+        }
+        last_err
+    }
+
+    /// Succeeds when `exec_call` matches one of the restricting specs for its
+    /// program, or when there are none.
+    fn check_restrictions(&self, exec_call: &ExecCall, stdin_is_pipe: bool) -> Result<()> {
+        let Some(spec_list) = self.restrictions.get_vec(&exec_call.program) else {
+            return Ok(());
+        };
+        let mut last_err = Ok(());
+        for spec in spec_list {
+            match spec.check_impl(exec_call, stdin_is_pipe, &mut Vec::new()) {
+                Ok(_) => return Ok(()),
+                Err(err) => last_err = Err(err),
+            }
+        }
+        last_err
+    }
+
+    /// Checks `exec_call` like [`Policy::check`], recording which specs were
+    /// considered, how each argument was resolved, and the smallest policy
+    /// addition that would have allowed the command.
+    pub fn explain(&self, exec_call: &ExecCall) -> Explanation {
+        if let Some(forbidden) = self.check_forbidden(exec_call) {
+            return Explanation::new(exec_call.clone(), Vec::new(), Ok(forbidden));
+        }
+
+        let mut specs = Vec::new();
+        let mut result = Err(Error::NoSpecForProgram {
+            program: exec_call.program.clone(),
+        });
+        if let Some(spec_list) = self.programs.get_vec(&exec_call.program) {
+            result = trace_specs(exec_call, spec_list, &mut specs);
+        }
+        if result.is_ok()
+            && let Some(spec_list) = self.restrictions.get_vec(&exec_call.program)
+        {
+            result = trace_specs(exec_call, spec_list, &mut specs).and(result);
+        }
+        Explanation::new(exec_call.clone(), specs, result)
+    }
+
+    fn check_forbidden(&self, exec_call: &ExecCall) -> Option<MatchedExec> {
         let ExecCall { program, args } = &exec_call;
         for ForbiddenProgramRegex { regex, reason } in &self.forbidden_program_regexes {
             if regex.is_match(program) {
-                return Ok(MatchedExec::Forbidden {
+                return Some(MatchedExec::Forbidden {
                     cause: Forbidden::Program {
                         program: program.clone(),
                         exec_call: exec_call.clone(),
@@ -59,7 +195,7 @@ impl Policy {
             if let Some(regex) = &self.forbidden_substrings_pattern
                 && regex.is_match(arg)
             {
-                return Ok(MatchedExec::Forbidden {
+                return Some(MatchedExec::Forbidden {
                     cause: Forbidden::Arg {
                         arg: arg.clone(),
                         exec_call: exec_call.clone(),
@@ -68,21 +204,7 @@ impl Policy {
                 });
             }
         }
-
-        let mut last_err = Err(Error::NoSpecForProgram {
-            program: program.clone(),
-        });
-        if let Some(spec_list) = self.programs.get_vec(program) {
-            for spec in spec_list {
-                match spec.check(exec_call) {
-                    Ok(matched_exec) => return Ok(matched_exec),
-                    Err(err) => {
-                        last_err = Err(err);
-                    }
-                }
-            }
-        }
-        last_err
+        None
     }
 
     pub fn check_each_good_list_individually(&self) -> Vec<PositiveExampleFailedCheck> {
@@ -101,3 +223,46 @@ impl Policy {
         violations
     }
 }
+
+/// Checks `exec_call` against each spec in turn until one matches, recording
+/// a trace per spec considered.
+fn trace_specs(
+    exec_call: &ExecCall,
+    spec_list: &[ProgramSpec],
+    specs: &mut Vec<SpecTrace>,
+) -> Result<MatchedExec> {
+    let mut result = Err(Error::NoSpecForProgram {
+        program: exec_call.program.clone(),
+    });
+    for spec in spec_list {
+        let mut steps = Vec::new();
+        let spec_result = spec.check_with_trace(exec_call, &mut steps);
+        specs.push(SpecTrace {
+            source_location: spec.source_location.clone(),
+            option_bundling: spec.option_bundling,
+            combined_format: spec.combined_format,
+            steps,
+            error: spec_result.as_ref().err().cloned(),
+        });
+        let matched = spec_result.is_ok();
+        result = spec_result;
+        if matched {
+            break;
+        }
+    }
+    result
+}
+
+fn compile_forbidden_substrings(
+    forbidden_substrings: &[String],
+) -> std::result::Result<Option<Regex>, RegexError> {
+    if forbidden_substrings.is_empty() {
+        return Ok(None);
+    }
+    let escaped_substrings = forbidden_substrings
+        .iter()
+        .map(|s| regex_lite::escape(s))
+        .collect::<Vec<_>>()
+        .join("|");
+    Ok(Some(Regex::new(&format!("({escaped_substrings})"))?))
+}
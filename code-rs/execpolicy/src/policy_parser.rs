@@ -149,7 +149,7 @@ fn policy_builtins(builder: &mut GlobalsBuilder) {
             }
         }
 
-        let program_spec = ProgramSpec::new(
+        let mut program_spec = ProgramSpec::new(
             program,
             system_path,
             option_bundling,
@@ -168,6 +168,15 @@ fn policy_builtins(builder: &mut GlobalsBuilder) {
                 .map(|v| v.items.to_vec())
                 .collect(),
         );
+        // The innermost frame is define_program() itself, which has no
+        // location; report the nearest Starlark call site instead.
+        program_spec.source_location = eval
+            .call_stack()
+            .frames
+            .iter()
+            .rev()
+            .find_map(|frame| frame.location.as_ref())
+            .map(ToString::to_string);
 
         #[expect(clippy::unwrap_used)]
         let policy_builder = eval
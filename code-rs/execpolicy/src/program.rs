@@ -5,12 +5,14 @@ use std::collections::HashSet;
 use crate::ArgType;
 use crate::ExecCall;
 use crate::arg_matcher::ArgMatcher;
+use crate::arg_resolver::ObservedOption;
 use crate::arg_resolver::PositionalArg;
 use crate::arg_resolver::resolve_observed_args_with_patterns;
+use crate::arg_resolver::resolve_option_arg;
 use crate::error::Error;
 use crate::error::Result;
+use crate::explain::TraceStep;
 use crate::opt::Opt;
-use crate::opt::OptMeta;
 use crate::valid_exec::MatchedFlag;
 use crate::valid_exec::MatchedOpt;
 use crate::valid_exec::ValidExec;
@@ -23,6 +25,8 @@ pub struct ProgramSpec {
     pub combined_format: bool,
     pub allowed_options: HashMap<String, Opt>,
     pub arg_patterns: Vec<ArgMatcher>,
+    /// Where the spec was defined, as `file:line:col`, when known.
+    pub source_location: Option<String>,
     forbidden: Option<String>,
     required_options: HashSet<String>,
     should_match: Vec<Vec<String>>,
@@ -58,6 +62,7 @@ impl ProgramSpec {
             combined_format,
             allowed_options,
             arg_patterns,
+            source_location: None,
             forbidden,
             required_options,
             should_match,
@@ -92,7 +97,26 @@ impl ProgramSpec {
     // a program and the args should be checked against the rules to determine
     // if the program should be allowed to run.
     pub fn check(&self, exec_call: &ExecCall) -> Result<MatchedExec> {
-        let mut expecting_option_value: Option<(String, ArgType)> = None;
+        self.check_with_trace(exec_call, &mut Vec::new())
+    }
+
+    /// Like [`ProgramSpec::check`], but records how each argument was resolved
+    /// in `trace`, up to the point where checking stopped.
+    pub fn check_with_trace(
+        &self,
+        exec_call: &ExecCall,
+        trace: &mut Vec<TraceStep>,
+    ) -> Result<MatchedExec> {
+        self.check_impl(exec_call, false, trace)
+    }
+
+    pub(crate) fn check_impl(
+        &self,
+        exec_call: &ExecCall,
+        stdin_is_pipe: bool,
+        trace: &mut Vec<TraceStep>,
+    ) -> Result<MatchedExec> {
+        let mut expecting_option_value: Option<(usize, String, ArgType)> = None;
         let mut args = Vec::<PositionalArg>::new();
         let mut matched_flags = Vec::<MatchedFlag>::new();
         let mut matched_opts = Vec::<MatchedOpt>::new();
@@ -102,7 +126,7 @@ impl ProgramSpec {
                 // If we are expecting an option value, then the next argument
                 // should be the value for the option.
                 // This had better not be another option!
-                let (name, arg_type) = expected;
+                let (option_index, name, arg_type) = expected;
                 if arg.starts_with("-") {
                     return Err(Error::OptionFollowedByOptionInsteadOfValue {
                         program: self.program.clone(),
@@ -111,36 +135,55 @@ impl ProgramSpec {
                     });
                 }
 
-                matched_opts.push(MatchedOpt::new(&name, arg, arg_type)?);
+                let matched_opt = MatchedOpt::new(&name, arg, arg_type)?;
+                trace.push(TraceStep::Opt {
+                    index: option_index,
+                    name,
+                    value: arg.clone(),
+                    r#type: matched_opt.r#type.clone(),
+                });
+                matched_opts.push(matched_opt);
                 expecting_option_value = None;
             } else if arg == "--" {
                 return Err(Error::DoubleDashNotSupportedYet {
                     program: self.program.clone(),
                 });
             } else if arg.starts_with("-") {
-                match self.allowed_options.get(arg) {
-                    Some(opt) => {
-                        match &opt.meta {
-                            OptMeta::Flag => {
-                                matched_flags.push(MatchedFlag { name: arg.clone() });
-                                // A flag does not expect an argument: continue.
-                                continue;
-                            }
-                            OptMeta::Value(arg_type) => {
-                                expecting_option_value = Some((arg.clone(), arg_type.clone()));
-                                continue;
-                            }
+                let options = resolve_option_arg(
+                    &self.program,
+                    arg,
+                    &self.allowed_options,
+                    self.option_bundling,
+                    self.combined_format,
+                )?;
+                for option in options {
+                    match option {
+                        ObservedOption::Flag { name } => {
+                            trace.push(TraceStep::Flag {
+                                index,
+                                name: name.clone(),
+                            });
+                            matched_flags.push(MatchedFlag { name });
+                        }
+                        ObservedOption::Value {
+                            name,
+                            value,
+                            arg_type,
+                        } => {
+                            let matched_opt = MatchedOpt::new(&name, &value, arg_type)?;
+                            trace.push(TraceStep::Opt {
+                                index,
+                                name,
+                                value,
+                                r#type: matched_opt.r#type.clone(),
+                            });
+                            matched_opts.push(matched_opt);
+                        }
+                        ObservedOption::ExpectsValue { name, arg_type } => {
+                            expecting_option_value = Some((index, name, arg_type));
                         }
-                    }
-                    None => {
-                        // It could be an --option=value style flag...
                     }
                 }
-
-                return Err(Error::UnknownOption {
-                    program: self.program.clone(),
-                    option: arg.clone(),
-                });
             } else {
                 args.push(PositionalArg {
                     index,
@@ -150,15 +193,24 @@ impl ProgramSpec {
         }
 
         if let Some(expected) = expecting_option_value {
-            let (name, _arg_type) = expected;
+            let (_index, name, _arg_type) = expected;
             return Err(Error::OptionMissingValue {
                 program: self.program.clone(),
                 option: name,
             });
         }
 
-        let matched_args =
-            resolve_observed_args_with_patterns(&self.program, args, &self.arg_patterns)?;
+        let matched_args = resolve_observed_args_with_patterns(
+            &self.program,
+            args,
+            &self.arg_patterns,
+            stdin_is_pipe,
+        )?;
+        trace.extend(matched_args.iter().map(|arg| TraceStep::Arg {
+            index: arg.index,
+            value: arg.value.clone(),
+            r#type: arg.r#type.clone(),
+        }));
 
         // Verify all required options are present.
         let matched_opt_names: HashSet<String> = matched_opts
@@ -0,0 +1,294 @@
+//! Splits a shell script parsed with tree-sitter-bash (for example by
+//! `code_core::bash::try_parse_bash`) into the simple commands it runs, so
+//! that pipelines and `&&`/`||`/`;` lists can be checked as a whole.
+//!
+//! Only plain words, quoted strings, pipelines, lists and file redirections
+//! are understood. Anything else (substitutions, expansions, subshells,
+//! control flow, variable assignments, heredocs, ...) is rejected. That
+//! includes unquoted words bash would expand (`~/x`, `*.rs`, `{a,b}`), since
+//! their literal text is not the path the command sees, and `cd`, which would
+//! change the directory later commands resolve paths against.
+
+use serde::Serialize;
+use tree_sitter::Node;
+use tree_sitter::Parser;
+use tree_sitter::Tree;
+use tree_sitter_bash::LANGUAGE as BASH;
+
+use crate::ExecCall;
+use crate::ValidExec;
+use crate::error::Error;
+use crate::error::Result;
+
+/// Operators allowed between the commands of a script.
+const LIST_OPERATORS: &[&str] = &["&&", "||", ";", "|"];
+
+/// Builtins that change the working directory of the rest of the script.
+const DIRECTORY_BUILTINS: &[&str] = &["cd", "pushd", "popd"];
+
+/// Characters that make bash treat an unquoted word as a glob pattern.
+const GLOB_CHARS: &[char] = &['*', '?', '['];
+
+/// Parses `script` with tree-sitter-bash.
+pub fn parse_shell(script: &str) -> Option<Tree> {
+    let lang = BASH.into();
+    let mut parser = Parser::new();
+    parser.set_language(&lang).ok()?;
+    parser.parse(script, None)
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedirectKind {
+    /// `< file`
+    Read,
+    /// `> file`, `>> file`, `&> file`, ...
+    Write,
+}
+
+/// A redirection of one of a command's file descriptors to a file.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct Redirect {
+    pub kind: RedirectKind,
+    pub target: String,
+}
+
+/// One simple command within a script.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct ShellSegment {
+    /// The command as written in the script, including its redirections.
+    pub text: String,
+    pub exec_call: ExecCall,
+    pub redirects: Vec<Redirect>,
+    /// Whether stdin comes from an earlier stage of a pipeline.
+    pub stdin_is_pipe: bool,
+}
+
+/// The verdict for a whole script: either every command is allowed, or the
+/// first command that is not is cited.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(tag = "verdict", rename_all = "snake_case")]
+pub enum ShellVerdict {
+    /// Every command matched the policy and every file it reads, writes or
+    /// redirects to is within the allowed folders.
+    Allowed { commands: Vec<ValidExec> },
+    /// A command is forbidden by the policy. Takes precedence over
+    /// `Rejected`.
+    Forbidden { segment: String, reason: String },
+    /// A command could not be verified; `segment` is the whole script when it
+    /// could not be split into commands.
+    Rejected { segment: String, error: Error },
+}
+
+/// Splits the script `tree` was parsed from into its simple commands, in
+/// source order.
+pub fn shell_segments(tree: &Tree, script: &str) -> Result<Vec<ShellSegment>> {
+    let root = tree.root_node();
+    if root.has_error() {
+        return Err(Error::ShellSyntaxError {
+            script: script.to_string(),
+        });
+    }
+    let mut segments = Vec::new();
+    collect_segments(root, script, false, &mut segments)?;
+    Ok(segments)
+}
+
+fn collect_segments(
+    node: Node,
+    src: &str,
+    stdin_is_pipe: bool,
+    segments: &mut Vec<ShellSegment>,
+) -> Result<()> {
+    match node.kind() {
+        "program" | "list" | "pipeline" => {
+            let is_pipeline = node.kind() == "pipeline";
+            let mut cursor = node.walk();
+            let mut first_stage = true;
+            for child in node.children(&mut cursor) {
+                if child.kind() == "comment" {
+                    continue;
+                }
+                if !child.is_named() {
+                    if !LIST_OPERATORS.contains(&child.kind()) {
+                        return Err(unsupported(child, src));
+                    }
+                    continue;
+                }
+                collect_segments(child, src, is_pipeline && !first_stage, segments)?;
+                first_stage = false;
+            }
+            Ok(())
+        }
+        "redirected_statement" => {
+            let body = node
+                .child_by_field_name("body")
+                .ok_or_else(|| unsupported(node, src))?;
+            let first_new = segments.len();
+            match body.kind() {
+                "command" => segments.push(command_segment(body, src, stdin_is_pipe)?),
+                // tree-sitter-bash attaches a redirection after the last stage
+                // of a pipeline to the pipeline as a whole.
+                "pipeline" => collect_segments(body, src, stdin_is_pipe, segments)?,
+                _ => return Err(unsupported(body, src)),
+            }
+            let Some(segment) = segments.get_mut(first_new..).and_then(<[_]>::last_mut) else {
+                return Err(unsupported(body, src));
+            };
+            let mut cursor = node.walk();
+            for redirect in node.children_by_field_name("redirect", &mut cursor) {
+                segment.redirects.extend(parse_redirect(redirect, src)?);
+            }
+            if body.kind() == "command" {
+                segment.text = node_text(node, src)?;
+            }
+            Ok(())
+        }
+        "command" => {
+            segments.push(command_segment(node, src, stdin_is_pipe)?);
+            Ok(())
+        }
+        _ => Err(unsupported(node, src)),
+    }
+}
+
+fn command_segment(cmd: Node, src: &str, stdin_is_pipe: bool) -> Result<ShellSegment> {
+    let mut words = Vec::new();
+    let mut redirects = Vec::new();
+    let mut cursor = cmd.walk();
+    for child in cmd.named_children(&mut cursor) {
+        match child.kind() {
+            "command_name" => {
+                let word = child
+                    .named_child(0)
+                    .ok_or_else(|| unsupported(child, src))?;
+                words.push(word_text(word, src)?);
+            }
+            "file_redirect" => redirects.extend(parse_redirect(child, src)?),
+            _ => words.push(word_text(child, src)?),
+        }
+    }
+    let Some((program, args)) = words.split_first() else {
+        return Err(unsupported(cmd, src));
+    };
+    if DIRECTORY_BUILTINS.contains(&program.as_str()) {
+        return Err(Error::UnsupportedShellSyntax {
+            kind: "cd".to_string(),
+            text: node_text(cmd, src)?,
+        });
+    }
+    Ok(ShellSegment {
+        text: node_text(cmd, src)?,
+        exec_call: ExecCall {
+            program: program.clone(),
+            args: args.to_vec(),
+        },
+        redirects,
+        stdin_is_pipe,
+    })
+}
+
+/// Returns `None` for redirections that only duplicate file descriptors,
+/// such as `2>&1`.
+fn parse_redirect(node: Node, src: &str) -> Result<Option<Redirect>> {
+    if node.kind() != "file_redirect" {
+        return Err(unsupported(node, src));
+    }
+    let mut operator = None;
+    let mut destination = None;
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        match child.kind() {
+            "file_descriptor" => {}
+            kind if !child.is_named() => operator = Some(kind),
+            _ => destination = Some(child),
+        }
+    }
+    let (Some(operator), Some(destination)) = (operator, destination) else {
+        return Err(unsupported(node, src));
+    };
+    let kind = match operator {
+        "<" => RedirectKind::Read,
+        ">" | ">>" | ">|" | "&>" | "&>>" | "<>" => RedirectKind::Write,
+        ">&" | "<&" | ">&-" | "<&-"
+            if matches!(destination.kind(), "number" | "file_descriptor")
+                || node_text(destination, src)? == "-" =>
+        {
+            return Ok(None);
+        }
+        ">&" => RedirectKind::Write,
+        _ => return Err(unsupported(node, src)),
+    };
+    Ok(Some(Redirect {
+        kind,
+        target: word_text(destination, src)?,
+    }))
+}
+
+fn word_text(node: Node, src: &str) -> Result<String> {
+    match node.kind() {
+        "word" => {
+            let text = node_text(node, src)?;
+            match expansion_kind(&text) {
+                Some(kind) => Err(Error::UnsupportedShellSyntax {
+                    kind: kind.to_string(),
+                    text,
+                }),
+                None => Ok(text),
+            }
+        }
+        "number" => node_text(node, src),
+        "string" => {
+            let content = node
+                .named_child(0)
+                .filter(|content| {
+                    node.named_child_count() == 1 && content.kind() == "string_content"
+                })
+                .ok_or_else(|| unsupported(node, src))?;
+            node_text(content, src)
+        }
+        "raw_string" => {
+            let raw = node_text(node, src)?;
+            raw.strip_prefix('\'')
+                .and_then(|s| s.strip_suffix('\''))
+                .map(str::to_string)
+                .ok_or_else(|| unsupported(node, src))
+        }
+        _ => Err(unsupported(node, src)),
+    }
+}
+
+/// Names the expansion bash would apply to the unquoted `word`, if any.
+/// tree-sitter-bash leaves these as plain words.
+fn expansion_kind(word: &str) -> Option<&'static str> {
+    // Bash also expands a tilde after `=` or `:`, as in `--file=~/x`.
+    if word.starts_with('~') || word.contains("=~") || word.contains(":~") {
+        return Some("tilde_expansion");
+    }
+    if word.contains(GLOB_CHARS) {
+        return Some("glob");
+    }
+    if let Some((_, rest)) = word.split_once('{')
+        && let Some((inner, _)) = rest.split_once('}')
+        && (inner.contains(',') || inner.contains(".."))
+    {
+        return Some("brace_expansion");
+    }
+    None
+}
+
+fn node_text(node: Node, src: &str) -> Result<String> {
+    node.utf8_text(src.as_bytes())
+        .map(str::to_string)
+        .map_err(|_| unsupported(node, src))
+}
+
+fn unsupported(node: Node, src: &str) -> Error {
+    Error::UnsupportedShellSyntax {
+        kind: node.kind().to_string(),
+        text: src
+            .get(node.start_byte()..node.end_byte())
+            .unwrap_or_default()
+            .to_string(),
+    }
+}
@@ -0,0 +1,88 @@
+use codex_execpolicy::ArgType;
+use codex_execpolicy::Error;
+use codex_execpolicy::ExecCall;
+use codex_execpolicy::Policy;
+use codex_execpolicy::TraceStep;
+use codex_execpolicy::get_default_policy;
+
+#[expect(clippy::expect_used)]
+fn setup() -> Policy {
+    get_default_policy().expect("failed to load default policy")
+}
+
+#[test]
+fn test_explain_traces_matched_arguments() {
+    let policy = setup();
+    let explanation = policy.explain(&ExecCall::new("head", &["-n", "5", "foo.txt"]));
+
+    assert!(explanation.result.is_ok());
+    assert_eq!(None, explanation.suggestion);
+    assert_eq!(1, explanation.specs.len());
+    let spec = &explanation.specs[0];
+    assert!(
+        spec.source_location
+            .as_deref()
+            .is_some_and(|location| location.starts_with("#default:"))
+    );
+    assert_eq!(
+        vec![
+            TraceStep::Opt {
+                index: 0,
+                name: "-n".to_string(),
+                value: "5".to_string(),
+                r#type: ArgType::PositiveInteger,
+            },
+            TraceStep::Arg {
+                index: 2,
+                value: "foo.txt".to_string(),
+                r#type: ArgType::ReadableFile,
+            },
+        ],
+        spec.steps
+    );
+}
+
+#[test]
+fn test_explain_reports_writeable_files() {
+    let policy = setup();
+    let explanation = policy.explain(&ExecCall::new("cp", &["foo", "bar"]));
+    assert_eq!(vec!["bar".to_string()], explanation.might_write);
+}
+
+#[test]
+fn test_explain_suggests_flag_for_unknown_option() {
+    let policy = setup();
+    let explanation = policy.explain(&ExecCall::new("ls", &["-alR"]));
+
+    assert_eq!(
+        Some(Error::UnknownOption {
+            program: "ls".to_string(),
+            option: "-R".to_string(),
+        }),
+        explanation.specs[0].error
+    );
+    let suggestion = explanation.suggestion.expect("suggestion");
+    assert!(
+        suggestion.starts_with("add flag(\"-R\") to the options of the `ls` spec at #default:"),
+        "{suggestion}"
+    );
+}
+
+#[test]
+fn test_explain_suggests_new_program() {
+    let policy = setup();
+    let explanation = policy.explain(&ExecCall::new("buf", &["lint", "proto/"]));
+
+    assert!(explanation.specs.is_empty());
+    assert_eq!(
+        Some(
+            r#"define_program(
+    program="buf",
+    args=["lint", ARG_RFILE],
+    should_match=[["lint", "proto/"]],
+)"#
+            .to_string()
+        ),
+        explanation.suggestion
+    );
+}
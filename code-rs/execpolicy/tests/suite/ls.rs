@@ -6,6 +6,7 @@ use codex_execpolicy::ExecCall;
 use codex_execpolicy::MatchedArg;
 use codex_execpolicy::MatchedExec;
 use codex_execpolicy::MatchedFlag;
+use codex_execpolicy::MatchedOpt;
 use codex_execpolicy::Policy;
 use codex_execpolicy::Result;
 use codex_execpolicy::ValidExec;
@@ -66,14 +67,68 @@ fn test_ls_dash_z() {
 fn test_ls_dash_al() {
     let policy = setup();
 
-    // This currently fails, but it should pass once option_bundling=True is implemented.
+    // option_bundling=True expands -al into -a -l.
     let ls_al = ExecCall::new("ls", &["-al"]);
+    assert_eq!(
+        Ok(MatchedExec::Match {
+            exec: ValidExec {
+                program: "ls".into(),
+                flags: vec![MatchedFlag::new("-a"), MatchedFlag::new("-l")],
+                system_path: ["/bin/ls".into(), "/usr/bin/ls".into()].into(),
+                ..Default::default()
+            }
+        }),
+        policy.check(&ls_al)
+    );
+}
+
+#[test]
+fn test_ls_bundle_with_unknown_option() {
+    let policy = setup();
+
+    // The offending option within the bundle is reported.
+    let ls_alz = ExecCall::new("ls", &["-alz"]);
     assert_eq!(
         Err(Error::UnknownOption {
             program: "ls".into(),
-            option: "-al".into()
+            option: "-z".into()
         }),
-        policy.check(&ls_al)
+        policy.check(&ls_alz)
+    );
+}
+
+#[test]
+fn test_ls_color_equals_value() -> Result<()> {
+    let policy = setup();
+
+    // combined_format=True accepts --option=value for opt() options.
+    let ls_color = ExecCall::new("ls", &["--color=auto", "foo"]);
+    assert_eq!(
+        Ok(MatchedExec::Match {
+            exec: ValidExec {
+                program: "ls".into(),
+                opts: vec![MatchedOpt::new("--color", "auto", ArgType::OpaqueNonFile)?],
+                args: vec![MatchedArg::new(1, ArgType::ReadableFile, "foo")?],
+                system_path: ["/bin/ls".into(), "/usr/bin/ls".into()].into(),
+                ..Default::default()
+            }
+        }),
+        policy.check(&ls_color)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_ls_unknown_option_equals_value() {
+    let policy = setup();
+
+    let ls_all = ExecCall::new("ls", &["--all=yes"]);
+    assert_eq!(
+        Err(Error::UnknownOption {
+            program: "ls".into(),
+            option: "--all".into()
+        }),
+        policy.check(&ls_all)
     );
 }
 
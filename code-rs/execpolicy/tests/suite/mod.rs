@@ -1,10 +1,13 @@
 // Aggregates all former standalone integration tests as modules.
 mod bad;
 mod cp;
+mod explain;
 mod good;
 mod head;
 mod literal;
 mod ls;
+mod overlay;
 mod parse_sed_command;
 mod pwd;
 mod sed;
+mod shell;
@@ -0,0 +1,173 @@
+use std::path::Path;
+
+use codex_execpolicy::ExecCall;
+use codex_execpolicy::MatchedExec;
+use codex_execpolicy::OverlayPaths;
+use codex_execpolicy::get_policy_with_overlays;
+use codex_execpolicy::overlay_paths;
+
+const TEAM_POLICY: &str = r#"
+define_program(
+    program="bazel",
+    args=["query", ARG_OPAQUE_VALUE],
+    should_match=[["query", "//..."]],
+    should_not_match=[["build", "//..."]],
+)
+
+define_program(
+    program="buf",
+    args=["lint"],
+)
+"#;
+
+const PROJECT_POLICY: &str = r#"
+# Only allow single-column listings in this repo.
+define_program(
+    program="ls",
+    options=[flag("-1")],
+    args=[ARG_RFILES_OR_CWD],
+)
+
+define_program(
+    program="bazel",
+    args=["query", ARG_OPAQUE_VALUE],
+)
+
+forbid_program_regex(
+    regex="^deploy$",
+    reason="deploys must go through CI",
+)
+"#;
+
+#[expect(clippy::expect_used)]
+fn write(path: &Path, contents: &str) {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).expect("create parent dir");
+    }
+    std::fs::write(path, contents).expect("write policy");
+}
+
+#[test]
+fn test_overlays_add_tighten_and_forbid() {
+    let code_home = tempfile::tempdir().expect("code home");
+    let project = tempfile::tempdir().expect("project");
+    std::fs::create_dir(project.path().join(".git")).expect("create .git");
+    let cwd = project.path().join("src");
+    std::fs::create_dir(&cwd).expect("create src");
+
+    let team_policy = code_home.path().join("team.policy");
+    let project_policy = project.path().join(".code").join("project.policy");
+    write(&team_policy, TEAM_POLICY);
+    write(&project_policy, PROJECT_POLICY);
+    write(&code_home.path().join("notes.txt"), "not a policy");
+
+    assert_eq!(
+        OverlayPaths {
+            user: vec![team_policy.clone()],
+            project: vec![project_policy.clone()],
+        },
+        overlay_paths(Some(code_home.path()), &cwd)
+    );
+
+    let loaded = get_policy_with_overlays(Some(code_home.path()), &cwd).expect("load overlays");
+    let policy = &loaded.policy;
+
+    // Added by the user overlay.
+    assert!(matches!(
+        policy.check(&ExecCall::new("buf", &["lint"])),
+        Ok(MatchedExec::Match { .. })
+    ));
+    assert!(policy.check(&ExecCall::new("buf", &["generate"])).is_err());
+
+    // Tightened by the project overlay.
+    assert!(matches!(
+        policy.check(&ExecCall::new("ls", &["-1"])),
+        Ok(MatchedExec::Match { .. })
+    ));
+    assert!(policy.check(&ExecCall::new("ls", &["-al"])).is_err());
+
+    // Untouched programs keep their default specs.
+    assert!(matches!(
+        policy.check(&ExecCall::new("cat", &["file.txt"])),
+        Ok(MatchedExec::Match { .. })
+    ));
+
+    assert!(matches!(
+        policy.check(&ExecCall::new("deploy", &[])),
+        Ok(MatchedExec::Forbidden { .. })
+    ));
+
+    // `bazel` is defined by both overlays: the project one is applied on top
+    // of the user one and the conflict is reported with both locations.
+    assert!(matches!(
+        policy.check(&ExecCall::new("bazel", &["query", "//..."])),
+        Ok(MatchedExec::Match { .. })
+    ));
+    assert_eq!(1, loaded.conflicts.len());
+    let conflict = &loaded.conflicts[0];
+    assert_eq!("bazel", conflict.program);
+    assert!(
+        conflict
+            .previous
+            .starts_with(&team_policy.display().to_string())
+    );
+    assert!(
+        conflict
+            .current
+            .starts_with(&project_policy.display().to_string())
+    );
+}
+
+#[test]
+fn test_overlay_examples_are_verified() {
+    let code_home = tempfile::tempdir().expect("code home");
+    let project = tempfile::tempdir().expect("project");
+    write(
+        &code_home.path().join("broken.policy"),
+        r#"
+define_program(
+    program="bazel",
+    args=["query", ARG_OPAQUE_VALUE],
+    should_match=[["build", "//..."]],
+)
+"#,
+    );
+
+    let err = match get_policy_with_overlays(Some(code_home.path()), project.path()) {
+        Ok(_) => panic!("expected the overlay to be rejected"),
+        Err(err) => err.to_string(),
+    };
+    assert!(err.contains("broken.policy"), "{err}");
+    assert!(err.contains("should_match"), "{err}");
+}
+
+#[test]
+fn test_project_overlay_cannot_loosen_existing_programs() {
+    let project = tempfile::tempdir().expect("project");
+    std::fs::create_dir(project.path().join(".git")).expect("create .git");
+    write(
+        &project.path().join(".code").join("loosen.policy"),
+        r#"
+define_program(
+    program="cp",
+    options=[flag("--force")],
+    args=[ARG_OPAQUE_VALUE, ARG_OPAQUE_VALUE],
+    should_match=[["--force", "foo", "bar"]],
+)
+"#,
+    );
+
+    let loaded = get_policy_with_overlays(None, project.path()).expect("load overlays");
+    let policy = &loaded.policy;
+
+    // The default `cp` spec still has to match, so the extra flag is refused.
+    assert!(
+        policy
+            .check(&ExecCall::new("cp", &["--force", "foo", "bar"]))
+            .is_err()
+    );
+    assert!(matches!(
+        policy.check(&ExecCall::new("cp", &["foo", "bar"])),
+        Ok(MatchedExec::Match { .. })
+    ));
+}
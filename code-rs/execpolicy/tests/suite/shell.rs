@@ -0,0 +1,226 @@
+use std::path::PathBuf;
+
+use codex_execpolicy::Error;
+use codex_execpolicy::ExecvChecker;
+use codex_execpolicy::RedirectKind;
+use codex_execpolicy::ShellVerdict;
+use codex_execpolicy::get_default_policy;
+use codex_execpolicy::parse_shell;
+use codex_execpolicy::shell_segments;
+
+#[expect(clippy::expect_used)]
+fn check_shell(script: &str, writeable_folders: &[PathBuf]) -> ShellVerdict {
+    let policy = get_default_policy().expect("failed to load default policy");
+    let checker = ExecvChecker::new(policy);
+    let tree = parse_shell(script).expect("failed to parse script");
+    let cwd = Some("/work".into());
+    checker.check_shell(
+        &tree,
+        script,
+        &cwd,
+        &[PathBuf::from("/work")],
+        writeable_folders,
+    )
+}
+
+fn command_count(verdict: &ShellVerdict) -> Option<usize> {
+    match verdict {
+        ShellVerdict::Allowed { commands } => Some(commands.len()),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_pipeline_stages_read_from_the_pipe() {
+    let script = "rg -n foo | head -n 20";
+    let tree = parse_shell(script).expect("failed to parse script");
+    let segments = shell_segments(&tree, script).expect("failed to split script");
+    assert_eq!(
+        vec![("rg -n foo", false), ("head -n 20", true)],
+        segments
+            .iter()
+            .map(|segment| (segment.text.as_str(), segment.stdin_is_pipe))
+            .collect::<Vec<_>>()
+    );
+
+    assert_eq!(Some(2), command_count(&check_shell(script, &[])));
+    assert_eq!(
+        Some(2),
+        command_count(&check_shell("cat foo.txt | head -n 5", &[]))
+    );
+}
+
+#[test]
+fn test_list_members_are_checked_individually() {
+    assert_eq!(
+        Some(3),
+        command_count(&check_shell("pwd && ls src; cat 'a b.txt'", &[]))
+    );
+    assert_eq!(
+        ShellVerdict::Rejected {
+            segment: "cp a.txt /etc/passwd".to_string(),
+            error: Error::WriteablePathNotInWriteableFolders {
+                file: PathBuf::from("/etc/passwd"),
+                folders: vec![],
+            },
+        },
+        check_shell("ls || cp a.txt /etc/passwd", &[])
+    );
+}
+
+#[test]
+fn test_failing_segment_is_cited() {
+    let verdict = check_shell("ls && git push", &[]);
+    let ShellVerdict::Rejected { segment, error } = verdict else {
+        panic!("expected rejection, got {verdict:?}");
+    };
+    assert_eq!("git push", segment);
+    assert_eq!(
+        Error::NoSpecForProgram {
+            program: "git".to_string()
+        },
+        error
+    );
+}
+
+#[test]
+fn test_redirections_are_writes() {
+    let script = "ls > out.txt 2>&1";
+    let tree = parse_shell(script).expect("failed to parse script");
+    let segments = shell_segments(&tree, script).expect("failed to split script");
+    assert_eq!(1, segments.len());
+    assert_eq!(
+        vec![(RedirectKind::Write, "out.txt")],
+        segments[0]
+            .redirects
+            .iter()
+            .map(|redirect| (redirect.kind, redirect.target.as_str()))
+            .collect::<Vec<_>>()
+    );
+
+    assert_eq!(
+        ShellVerdict::Rejected {
+            segment: script.to_string(),
+            error: Error::WriteablePathNotInWriteableFolders {
+                file: PathBuf::from("/work/out.txt"),
+                folders: vec![],
+            },
+        },
+        check_shell(script, &[])
+    );
+    assert_eq!(
+        Some(1),
+        command_count(&check_shell(script, &[PathBuf::from("/work")]))
+    );
+    assert_eq!(Some(1), command_count(&check_shell("ls 2>/dev/null", &[])));
+}
+
+#[test]
+fn test_unsupported_syntax_is_rejected() {
+    let verdict = check_shell("ls $(pwd)", &[]);
+    let ShellVerdict::Rejected { segment, error } = verdict else {
+        panic!("expected rejection, got {verdict:?}");
+    };
+    assert_eq!("ls $(pwd)", segment);
+    assert!(matches!(error, Error::UnsupportedShellSyntax { .. }));
+
+    assert!(matches!(
+        check_shell("ls &&", &[]),
+        ShellVerdict::Rejected {
+            error: Error::ShellSyntaxError { .. },
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_redirection_after_pipeline_applies_to_last_stage() {
+    let script = "rg foo | head -n 3 > out.txt";
+    let tree = parse_shell(script).expect("failed to parse script");
+    let segments = shell_segments(&tree, script).expect("failed to split script");
+    assert_eq!(2, segments.len());
+    assert!(segments[0].redirects.is_empty());
+    assert_eq!(1, segments[1].redirects.len());
+
+    assert!(matches!(
+        check_shell(script, &[]),
+        ShellVerdict::Rejected {
+            error: Error::WriteablePathNotInWriteableFolders { .. },
+            ..
+        }
+    ));
+    assert_eq!(
+        Some(2),
+        command_count(&check_shell(script, &[PathBuf::from("/work")]))
+    );
+}
+
+fn rejected_syntax_kind(verdict: &ShellVerdict) -> Option<&str> {
+    match verdict {
+        ShellVerdict::Rejected {
+            error: Error::UnsupportedShellSyntax { kind, .. },
+            ..
+        } => Some(kind.as_str()),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_tilde_words_are_rejected() {
+    let verdict = check_shell("cat ~/.ssh/id_rsa", &[]);
+    assert_eq!(
+        ShellVerdict::Rejected {
+            segment: "cat ~/.ssh/id_rsa".to_string(),
+            error: Error::UnsupportedShellSyntax {
+                kind: "tilde_expansion".to_string(),
+                text: "~/.ssh/id_rsa".to_string(),
+            },
+        },
+        verdict
+    );
+    assert_eq!(
+        Some("tilde_expansion"),
+        rejected_syntax_kind(&check_shell("ls > ~/out.txt", &[PathBuf::from("/work")]))
+    );
+    // Quoted tildes are not expanded by bash.
+    assert_eq!(Some(1), command_count(&check_shell("cat '~'", &[])));
+}
+
+#[test]
+fn test_glob_words_are_rejected() {
+    assert_eq!(
+        Some("glob"),
+        rejected_syntax_kind(&check_shell("cat *.txt", &[]))
+    );
+    assert_eq!(
+        Some("glob"),
+        rejected_syntax_kind(&check_shell("ls src/?.rs", &[]))
+    );
+    assert!(matches!(
+        check_shell("cat {a,b}.txt", &[]),
+        ShellVerdict::Rejected {
+            error: Error::UnsupportedShellSyntax { .. },
+            ..
+        }
+    ));
+    assert_eq!(Some(1), command_count(&check_shell("cat '*.txt'", &[])));
+}
+
+#[test]
+fn test_scripts_that_change_directory_are_rejected() {
+    let verdict = check_shell("cd /etc && cat passwd", &[]);
+    assert_eq!(
+        ShellVerdict::Rejected {
+            segment: "cd /etc && cat passwd".to_string(),
+            error: Error::UnsupportedShellSyntax {
+                kind: "cd".to_string(),
+                text: "cd /etc".to_string(),
+            },
+        },
+        verdict
+    );
+    assert_eq!(
+        Some("cd"),
+        rejected_syntax_kind(&check_shell("ls; pushd /etc", &[]))
+    );
+}
@@ -0,0 +1,191 @@
+//! Network egress through the CLI's allowlisting proxy.
+//!
+//! The helper moves itself into a new user and network namespace whose only
+//! interface is loopback, listens on a loopback port there and forwards every
+//! connection to the proxy's Unix socket, which is reachable because it lives
+//! on the file system. The command runs in a forked child with `HTTP_PROXY`
+//! and friends pointing at that port, so it can only reach the hosts the proxy
+//! lets through.
+
+use std::io;
+use std::net::Shutdown;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::os::fd::AsRawFd;
+use std::os::fd::FromRawFd;
+use std::os::fd::OwnedFd;
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::path::PathBuf;
+use std::thread;
+
+use code_core::protocol::SandboxPolicy;
+
+use crate::landlock::apply_sandbox_policy_to_current_thread;
+use crate::linux_run_main::exec_command;
+
+/// Proxy variables set for the command; most tools read one of these.
+const PROXY_ENV_VARS: &[&str] = &[
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "ALL_PROXY",
+    "http_proxy",
+    "https_proxy",
+    "all_proxy",
+];
+
+/// Loopback listener inside the egress network namespace; connections to it
+/// are forwarded to the proxy.
+pub(crate) struct EgressListener {
+    listener: TcpListener,
+    port: u16,
+}
+
+/// Moves this process into a network namespace that only has loopback and
+/// listens there for the command's proxy connections.
+///
+/// Fails when the namespace cannot be created, e.g. because unprivileged user
+/// namespaces are disabled. Must be called while the process is still
+/// single-threaded.
+pub(crate) fn enter_egress_namespace() -> io::Result<EgressListener> {
+    enter_network_namespace()?;
+    let listener = TcpListener::bind(("127.0.0.1", 0))?;
+    let port = listener.local_addr()?.port();
+    Ok(EgressListener { listener, port })
+}
+
+/// Runs `command` under `sandbox_policy` with network access limited to the
+/// proxy listening on `proxy_socket`, and exits with the command's status.
+pub(crate) fn run_command_with_egress_proxy(
+    EgressListener { listener, port }: EgressListener,
+    sandbox_policy: &SandboxPolicy,
+    sandbox_policy_cwd: &Path,
+    proxy_socket: PathBuf,
+    command: &[String],
+) -> ! {
+    let parent_pid = unsafe { libc::getpid() };
+
+    match unsafe { libc::fork() } {
+        -1 => panic!("failed to fork: {}", io::Error::last_os_error()),
+        0 => {
+            drop(listener);
+            // Do not outlive the forwarder, e.g. when the command times out and
+            // the helper is killed.
+            unsafe {
+                libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL);
+                if libc::getppid() != parent_pid {
+                    libc::_exit(1);
+                }
+            }
+
+            let proxy_url = format!("http://127.0.0.1:{port}");
+            for var in PROXY_ENV_VARS {
+                // SAFETY: the forked child is single-threaded.
+                unsafe { std::env::set_var(var, &proxy_url) };
+            }
+            for var in ["NO_PROXY", "no_proxy"] {
+                // SAFETY: as above.
+                unsafe { std::env::set_var(var, "localhost,127.0.0.1,::1") };
+            }
+
+            if let Err(e) =
+                apply_sandbox_policy_to_current_thread(sandbox_policy, sandbox_policy_cwd, true)
+            {
+                panic!("error running landlock: {e:?}");
+            }
+            exec_command(command)
+        }
+        child_pid => {
+            thread::spawn(move || forward_connections(listener, proxy_socket));
+            exit_like(wait_for(child_pid))
+        }
+    }
+}
+
+/// Enters a new user and network namespace, keeping the current user and
+/// group ids, and brings up its loopback interface.
+fn enter_network_namespace() -> io::Result<()> {
+    let uid = unsafe { libc::geteuid() };
+    let gid = unsafe { libc::getegid() };
+    if unsafe { libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    std::fs::write("/proc/self/setgroups", "deny")?;
+    std::fs::write("/proc/self/uid_map", format!("{uid} {uid} 1"))?;
+    std::fs::write("/proc/self/gid_map", format!("{gid} {gid} 1"))?;
+    bring_up_loopback()
+}
+
+fn bring_up_loopback() -> io::Result<()> {
+    let raw_fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, 0) };
+    if raw_fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: `raw_fd` is a freshly created socket that nothing else owns.
+    let fd = unsafe { OwnedFd::from_raw_fd(raw_fd) };
+
+    // SAFETY: `ifreq` is plain old data, for which all zeroes is valid.
+    let mut ifr: libc::ifreq = unsafe { std::mem::zeroed() };
+    for (dst, src) in ifr.ifr_name.iter_mut().zip(b"lo") {
+        *dst = *src as libc::c_char;
+    }
+    ifr.ifr_ifru.ifru_flags = (libc::IFF_UP | libc::IFF_RUNNING) as libc::c_short;
+    // SAFETY: `ifr` outlives the call and matches what SIOCSIFFLAGS expects.
+    let result = unsafe { libc::ioctl(fd.as_raw_fd(), libc::SIOCSIFFLAGS as _, &ifr) };
+    if result < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+fn forward_connections(listener: TcpListener, proxy_socket: PathBuf) {
+    for client in listener.incoming().flatten() {
+        let proxy_socket = proxy_socket.clone();
+        thread::spawn(move || {
+            if let Ok(upstream) = UnixStream::connect(&proxy_socket) {
+                pipe(client, upstream);
+            }
+        });
+    }
+}
+
+/// Copies bytes both ways until each side has closed its half.
+fn pipe(mut client: TcpStream, mut upstream: UnixStream) {
+    let (Ok(mut client_read), Ok(mut upstream_write)) = (client.try_clone(), upstream.try_clone())
+    else {
+        return;
+    };
+    let to_upstream = thread::spawn(move || {
+        let _ = io::copy(&mut client_read, &mut upstream_write);
+        let _ = upstream_write.shutdown(Shutdown::Write);
+    });
+    let _ = io::copy(&mut upstream, &mut client);
+    let _ = client.shutdown(Shutdown::Write);
+    let _ = to_upstream.join();
+}
+
+fn wait_for(pid: libc::pid_t) -> libc::c_int {
+    let mut status = 0;
+    loop {
+        if unsafe { libc::waitpid(pid, &mut status, 0) } != -1 {
+            return status;
+        }
+        let err = io::Error::last_os_error();
+        if err.kind() != io::ErrorKind::Interrupted {
+            panic!("failed to wait for sandboxed command: {err}");
+        }
+    }
+}
+
+/// Exits the way the command did, re-raising its signal if it was killed.
+fn exit_like(status: libc::c_int) -> ! {
+    if libc::WIFSIGNALED(status) {
+        let signal = libc::WTERMSIG(status);
+        unsafe {
+            libc::signal(signal, libc::SIG_DFL);
+            libc::kill(libc::getpid(), signal);
+        }
+        std::process::exit(128 + signal);
+    }
+    std::process::exit(libc::WEXITSTATUS(status));
+}
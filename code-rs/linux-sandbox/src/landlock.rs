@@ -27,12 +27,17 @@ use seccompiler::apply_filter;
 
 /// Apply sandbox policies inside this thread so only the child inherits
 /// them, not the entire CLI process.
+///
+/// `in_egress_namespace` is set when the thread already runs in a network
+/// namespace that only reaches the egress proxy, in which case IP sockets are
+/// allowed.
 pub(crate) fn apply_sandbox_policy_to_current_thread(
     sandbox_policy: &SandboxPolicy,
     cwd: &Path,
+    in_egress_namespace: bool,
 ) -> Result<()> {
     if !sandbox_policy.has_full_network_access() {
-        install_network_seccomp_filter_on_current_thread()?;
+        install_network_seccomp_filter_on_current_thread(in_egress_namespace)?;
     }
 
     if !sandbox_policy.has_full_disk_write_access() {
@@ -41,36 +46,66 @@ pub(crate) fn apply_sandbox_policy_to_current_thread(
             .into_iter()
             .map(|writable_root| writable_root.root)
             .collect();
-        install_filesystem_landlock_rules_on_current_thread(writable_roots)?;
+        install_filesystem_landlock_rules_on_current_thread(
+            writable_roots,
+            &sandbox_policy.get_read_denied_paths(),
+        )?;
     }
 
-    // TODO(ragona): Add appropriate restrictions if
-    // `sandbox_policy.has_full_disk_read_access()` is `false`.
-
     Ok(())
 }
 
 /// Installs Landlock file-system rules on the current thread allowing read
-/// access to the entire file-system while restricting write access to
-/// `/dev/null` and the provided list of `writable_roots`.
+/// access to the entire file-system except `read_denied_paths`, while
+/// restricting write access to `/dev/null` and the provided list of
+/// `writable_roots`.
+///
+/// Landlock can only grant access, so a root that contains a denied path is
+/// replaced by its entries that do not, recursively. The directories on the
+/// way to a denied path can still be listed, which reveals the names (but not
+/// the contents) of the denied paths; entries created in those directories
+/// after the sandbox starts are not accessible.
 ///
 /// # Errors
 /// Returns [`CodexErr::Sandbox`] variants when the ruleset fails to apply.
-fn install_filesystem_landlock_rules_on_current_thread(writable_roots: Vec<PathBuf>) -> Result<()> {
+fn install_filesystem_landlock_rules_on_current_thread(
+    writable_roots: Vec<PathBuf>,
+    read_denied_paths: &[PathBuf],
+) -> Result<()> {
     let abi = ABI::V5;
     let access_rw = AccessFs::from_all(abi);
     let access_ro = AccessFs::from_read(abi);
+    let access_list = AccessFs::ReadDir;
+
+    let denied: Vec<PathBuf> = read_denied_paths
+        .iter()
+        .map(|path| path.canonicalize().unwrap_or_else(|_| path.clone()))
+        .collect();
 
+    let (readable, mut listable) = split_around_denied_paths(Path::new("/"), &denied);
+    let mut writable = Vec::new();
+    for root in writable_roots {
+        let root = root.canonicalize().unwrap_or(root);
+        let (granted, listed) = split_around_denied_paths(&root, &denied);
+        writable.extend(granted);
+        listable.extend(listed);
+    }
+
+    // A readable path that cannot be opened (e.g. removed since it was
+    // listed) should not prevent the rest of the rules from being installed.
     let mut ruleset = Ruleset::default()
         .set_compatibility(CompatLevel::BestEffort)
         .handle_access(access_rw)?
         .create()?
-        .add_rules(landlock::path_beneath_rules(&["/"], access_ro))?
+        .add_rules(landlock::path_beneath_rules(&readable, access_ro).filter(|rule| rule.is_ok()))?
+        .add_rules(
+            landlock::path_beneath_rules(&listable, access_list).filter(|rule| rule.is_ok()),
+        )?
         .add_rules(landlock::path_beneath_rules(&["/dev/null"], access_rw))?
         .set_no_new_privs(true);
 
-    if !writable_roots.is_empty() {
-        ruleset = ruleset.add_rules(landlock::path_beneath_rules(&writable_roots, access_rw))?;
+    if !writable.is_empty() {
+        ruleset = ruleset.add_rules(landlock::path_beneath_rules(&writable, access_rw))?;
     }
 
     let status = ruleset.restrict_self()?;
@@ -82,9 +117,56 @@ fn install_filesystem_landlock_rules_on_current_thread(writable_roots: Vec<PathB
     Ok(())
 }
 
+/// Splits `root` into paths that can be granted access beneath without
+/// exposing any of `denied` (first), and the directories that lead to a
+/// denied path, which may only be listed (second).
+///
+/// A denied path at or above `root` does not restrict it: an explicitly writable
+/// root stays accessible. Symlinks are skipped since Landlock checks accesses
+/// against the resolved path, which is covered by its own entry.
+fn split_around_denied_paths(root: &Path, denied: &[PathBuf]) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let mut granted = Vec::new();
+    let mut listed = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        if denied.iter().any(|path| path == &dir) && dir != root {
+            continue;
+        }
+        if !denied
+            .iter()
+            .any(|path| path.starts_with(&dir) && path != &dir)
+        {
+            granted.push(dir);
+            continue;
+        }
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            listed.push(dir);
+            continue;
+        };
+        for entry in entries.flatten() {
+            if entry
+                .file_type()
+                .is_ok_and(|file_type| file_type.is_symlink())
+            {
+                continue;
+            }
+            pending.push(entry.path());
+        }
+        listed.push(dir);
+    }
+    (granted, listed)
+}
+
 /// Installs a seccomp filter that blocks outbound network access except for
-/// AF_UNIX domain sockets.
-fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(), SandboxErr> {
+/// AF_UNIX domain sockets. With `allow_ip_sockets`, IPv4 and IPv6 sockets are
+/// allowed as well, for use inside the egress proxy's network namespace.
+fn install_network_seccomp_filter_on_current_thread(
+    allow_ip_sockets: bool,
+) -> std::result::Result<(), SandboxErr> {
+    if allow_ip_sockets {
+        return install_egress_seccomp_filter_on_current_thread();
+    }
+
     // Build rule map.
     let mut rules: BTreeMap<i64, Vec<SeccompRule>> = BTreeMap::new();
 
@@ -124,6 +206,14 @@ fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(),
     rules.insert(libc::SYS_socket, vec![unix_only_rule.clone()]);
     rules.insert(libc::SYS_socketpair, vec![unix_only_rule]); // always deny (Unix can use socketpair but fine, keep open?)
 
+    apply_seccomp_deny_rules(rules)
+}
+
+/// Installs a seccomp filter on the current thread that fails the syscalls
+/// matched by `rules` with `EPERM` and allows everything else.
+fn apply_seccomp_deny_rules(
+    rules: BTreeMap<i64, Vec<SeccompRule>>,
+) -> std::result::Result<(), SandboxErr> {
     let filter = SeccompFilter::new(
         rules,
         SeccompAction::Allow,                     // default – allow
@@ -143,3 +233,24 @@ fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(),
 
     Ok(())
 }
+
+/// Variant of the network filter for the egress proxy's network namespace,
+/// where loopback is the only interface: sockets are limited to AF_UNIX,
+/// AF_INET and AF_INET6, and ptrace stays denied.
+fn install_egress_seccomp_filter_on_current_thread() -> std::result::Result<(), SandboxErr> {
+    let mut rules: BTreeMap<i64, Vec<SeccompRule>> = BTreeMap::new();
+    rules.insert(libc::SYS_ptrace, vec![]);
+
+    let allowed_domain = |domain: libc::c_int| {
+        SeccompCondition::new(0, SeccompCmpArgLen::Dword, SeccompCmpOp::Ne, domain as u64)
+    };
+    let other_domain_rule = SeccompRule::new(vec![
+        allowed_domain(libc::AF_UNIX)?,
+        allowed_domain(libc::AF_INET)?,
+        allowed_domain(libc::AF_INET6)?,
+    ])?;
+    rules.insert(libc::SYS_socket, vec![other_domain_rule.clone()]);
+    rules.insert(libc::SYS_socketpair, vec![other_domain_rule]);
+
+    apply_seccomp_deny_rules(rules)
+}
@@ -2,10 +2,17 @@ use clap::Parser;
 use std::ffi::CString;
 use std::path::PathBuf;
 
+use crate::egress::enter_egress_namespace;
+use crate::egress::run_command_with_egress_proxy;
 use crate::landlock::apply_sandbox_policy_to_current_thread;
 
 #[derive(Debug, Parser)]
 pub struct LandlockCommand {
+    /// Unix socket of the proxy enforcing the policy's network allowlist.
+    /// When set, the command can reach the network only through it.
+    #[arg(long)]
+    pub egress_proxy_socket: Option<PathBuf>,
+
     /// It is possible that the cwd used in the context of the sandbox policy
     /// is different from the cwd of the process to spawn.
     pub sandbox_policy_cwd: PathBuf,
@@ -19,19 +26,44 @@ pub struct LandlockCommand {
 
 pub fn run_main() -> ! {
     let LandlockCommand {
+        egress_proxy_socket,
         sandbox_policy_cwd,
         sandbox_policy,
         command,
     } = LandlockCommand::parse();
 
-    if let Err(e) = apply_sandbox_policy_to_current_thread(&sandbox_policy, &sandbox_policy_cwd) {
-        panic!("error running landlock: {e:?}");
-    }
-
     if command.is_empty() {
         panic!("No command specified to execute.");
     }
 
+    if let Some(proxy_socket) = egress_proxy_socket {
+        // Without a network namespace the allowlist cannot be enforced, so
+        // fall through to blocking the network entirely.
+        match enter_egress_namespace() {
+            Ok(listener) => run_command_with_egress_proxy(
+                listener,
+                &sandbox_policy,
+                &sandbox_policy_cwd,
+                proxy_socket,
+                &command,
+            ),
+            Err(e) => eprintln!(
+                "code-linux-sandbox: network allowlist unavailable ({e}); all network access is blocked"
+            ),
+        }
+    }
+
+    if let Err(e) =
+        apply_sandbox_policy_to_current_thread(&sandbox_policy, &sandbox_policy_cwd, false)
+    {
+        panic!("error running landlock: {e:?}");
+    }
+
+    exec_command(&command)
+}
+
+/// Replaces the current process with `command`.
+pub(crate) fn exec_command(command: &[String]) -> ! {
     #[expect(clippy::expect_used)]
     let c_command =
         CString::new(command[0].as_str()).expect("Failed to convert command to CString");
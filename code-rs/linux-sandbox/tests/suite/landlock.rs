@@ -1,4 +1,5 @@
 #![cfg(target_os = "linux")]
+use code_core::config_types::ExecLimits;
 use code_core::config_types::ShellEnvironmentPolicy;
 use code_core::error::CodexErr;
 use code_core::error::SandboxErr;
@@ -33,8 +34,17 @@ fn create_env_from_core_vars() -> HashMap<String, String> {
     create_env(&policy)
 }
 
-#[expect(clippy::print_stdout, clippy::expect_used, clippy::unwrap_used)]
 async fn run_cmd(cmd: &[&str], writable_roots: &[PathBuf], timeout_ms: u64) {
+    run_cmd_with_read_denied_paths(cmd, writable_roots, &[], timeout_ms).await;
+}
+
+#[expect(clippy::print_stdout, clippy::expect_used, clippy::unwrap_used)]
+async fn run_cmd_with_read_denied_paths(
+    cmd: &[&str],
+    writable_roots: &[PathBuf],
+    read_denied_paths: &[PathBuf],
+    timeout_ms: u64,
+) {
     let cwd = std::env::current_dir().expect("cwd should exist");
     let sandbox_cwd = cwd.clone();
     let params = ExecParams {
@@ -44,17 +54,20 @@ async fn run_cmd(cmd: &[&str], writable_roots: &[PathBuf], timeout_ms: u64) {
         env: create_env_from_core_vars(),
         with_escalated_permissions: None,
         justification: None,
+        exec_limits: ExecLimits::default(),
     };
 
     let sandbox_policy = SandboxPolicy::WorkspaceWrite {
         writable_roots: writable_roots.to_vec(),
         network_access: false,
+        network_allowlist: vec![],
         // Exclude tmp-related folders from writable roots because we need a
         // folder that is writable by tests but that we intentionally disallow
         // writing to in the sandbox.
         exclude_tmpdir_env_var: true,
         exclude_slash_tmp: true,
         allow_git_writes: true,
+        read_denied_paths: read_denied_paths.to_vec(),
     };
     let sandbox_program = env!("CARGO_BIN_EXE_code-linux-sandbox");
     let codex_linux_sandbox_exe = Some(PathBuf::from(sandbox_program));
@@ -71,8 +84,9 @@ async fn run_cmd(cmd: &[&str], writable_roots: &[PathBuf], timeout_ms: u64) {
 
     if res.exit_code != 0 {
         println!("stdout:\n{}", res.stdout.text);
-        println!("stderr:\n{}", res.stderr.text);
-        panic!("exit code: {}", res.exit_code);
+        // Carry stderr in the panic so `should_panic` tests can match the
+        // reason a command failed, not just that it did.
+        panic!("exit code: {}\nstderr:\n{}", res.exit_code, res.stderr.text);
     }
 }
 
@@ -124,6 +138,63 @@ async fn test_writable_root() {
     .await;
 }
 
+/// Creates a directory holding `secret.txt`, which the returned policy paths
+/// deny, next to a readable `public.txt`.
+#[expect(clippy::unwrap_used)]
+fn read_denied_fixture() -> (tempfile::TempDir, Vec<PathBuf>) {
+    let tmpdir = tempfile::tempdir().unwrap();
+    let secret = tmpdir.path().join("secret.txt");
+    std::fs::write(&secret, "secret").unwrap();
+    std::fs::write(tmpdir.path().join("public.txt"), "public").unwrap();
+    (tmpdir, vec![secret])
+}
+
+#[tokio::test]
+async fn test_read_denied_path_siblings_stay_readable() {
+    let (tmpdir, read_denied_paths) = read_denied_fixture();
+    let public = tmpdir.path().join("public.txt");
+    run_cmd_with_read_denied_paths(
+        &["cat", &public.to_string_lossy()],
+        &[],
+        &read_denied_paths,
+        LONG_TIMEOUT_MS,
+    )
+    .await;
+    run_cmd_with_read_denied_paths(
+        &["ls", "-l", "/bin"],
+        &[],
+        &read_denied_paths,
+        LONG_TIMEOUT_MS,
+    )
+    .await;
+}
+
+#[tokio::test]
+#[should_panic(expected = "Permission denied")]
+async fn test_read_denied_path() {
+    let (_tmpdir, read_denied_paths) = read_denied_fixture();
+    run_cmd_with_read_denied_paths(
+        &["cat", &read_denied_paths[0].to_string_lossy()],
+        &[],
+        &read_denied_paths,
+        LONG_TIMEOUT_MS,
+    )
+    .await;
+}
+
+#[tokio::test]
+#[should_panic(expected = "Permission denied")]
+async fn test_read_denied_path_inside_writable_root() {
+    let (tmpdir, read_denied_paths) = read_denied_fixture();
+    run_cmd_with_read_denied_paths(
+        &["cat", &read_denied_paths[0].to_string_lossy()],
+        &[tmpdir.path().to_path_buf()],
+        &read_denied_paths,
+        LONG_TIMEOUT_MS,
+    )
+    .await;
+}
+
 #[tokio::test]
 #[should_panic(expected = "Sandbox(Timeout")]
 async fn test_timeout() {
@@ -147,6 +218,7 @@ async fn assert_network_blocked(cmd: &[&str]) {
         env: create_env_from_core_vars(),
         with_escalated_permissions: None,
         justification: None,
+        exec_limits: ExecLimits::default(),
     };
 
     let sandbox_policy = SandboxPolicy::new_read_only_policy();
@@ -272,6 +272,7 @@ impl CodexToolCallParam {
             experimental_client_tools: None,
             compact_prompt_override: None,
             compact_prompt_override_file: None,
+            model_cassette: None,
         };
 
         let cli_overrides = cli_overrides
@@ -280,7 +281,10 @@ impl CodexToolCallParam {
             .map(|(k, v)| (k, json_to_toml(v)))
             .collect();
 
-        let cfg = code_core::config::Config::load_with_cli_overrides(cli_overrides, overrides)?;
+        let mut cfg =
+            code_core::config::Config::load_with_cli_overrides(cli_overrides, overrides)?;
+        // Tool calls run unattended, so MCP elicitation requests cannot be answered.
+        cfg.mcp_elicitation = cfg.mcp_elicitation.non_interactive();
 
         Ok((prompt, cfg))
     }
@@ -190,6 +190,7 @@ async fn run_code_tool_session_inner(
                         cwd,
                         call_id,
                         reason: _,
+                        grant_root: _,
                     }) => {
                         handle_exec_approval_request(
                             command,
@@ -278,6 +279,7 @@ async fn run_code_tool_session_inner(
                     | EventMsg::ExecCommandBegin(_)
                     | EventMsg::ExecCommandOutputDelta(_)
                     | EventMsg::ExecCommandEnd(_)
+                    | EventMsg::SandboxDenial(_)
                     | EventMsg::BackgroundEvent(_)
                     | EventMsg::PatchApplyBegin(_)
                     | EventMsg::PatchApplyEnd(_)
@@ -292,6 +294,8 @@ async fn run_code_tool_session_inner(
                     | EventMsg::EnvironmentContextFull(_)
                     | EventMsg::EnvironmentContextDelta(_)
                     | EventMsg::ListCustomPromptsResponse(_)
+                    | EventMsg::ListMcpResourcesResponse(_)
+                    | EventMsg::ElicitationRequest(_)
                     | EventMsg::ListSkillsResponse(_)
                     | EventMsg::AgentStatusUpdate(_)
                     | EventMsg::CompactionCheckpointWarning(_)
@@ -41,6 +41,7 @@ pub async fn run_conversation_loop(
                         cwd,
                         call_id,
                         reason: _,
+                        grant_root: _,
                     }) => {
                         handle_exec_approval_request(
                             command,
@@ -101,6 +102,7 @@ pub async fn run_conversation_loop(
                     | EventMsg::McpToolCallEnd(_)
                     | EventMsg::ExecCommandBegin(_)
                     | EventMsg::ExecCommandEnd(_)
+                    | EventMsg::SandboxDenial(_)
                     | EventMsg::TurnDiff(_)
                     | EventMsg::BackgroundEvent(_)
                     | EventMsg::ExecCommandOutputDelta(_)
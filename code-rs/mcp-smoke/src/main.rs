@@ -62,7 +62,8 @@ async fn main() -> Result<()> {
     servers.insert("slow_fail".to_string(), slow_fail);
 
     let (mgr, errs) =
-        McpConnectionManager::new(servers, std::collections::HashSet::new()).await?;
+        McpConnectionManager::new(servers, std::collections::HashSet::new(), Default::default())
+            .await?;
     println!("Errors: {}", errs.len());
     for (name, e) in &errs {
         println!("  {}: {}", name, e);
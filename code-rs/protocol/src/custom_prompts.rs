@@ -17,3 +17,22 @@ pub struct CustomPrompt {
     pub description: Option<String>,
     pub argument_hint: Option<String>,
 }
+
+/// A prompt exposed by an MCP server. Front-ends surface these as
+/// `/server:prompt` slash commands.
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+pub struct McpPrompt {
+    pub server: String,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub arguments: Vec<McpPromptArgument>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+pub struct McpPromptArgument {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+}
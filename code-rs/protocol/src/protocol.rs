@@ -3,6 +3,7 @@
 //! Uses a SQ (Submission Queue) / EQ (Event Queue) pattern to asynchronously communicate
 //! between user and agent.
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::fmt;
 use std::path::Path;
@@ -14,6 +15,7 @@ use crate::ConversationId;
 use crate::config_types::ReasoningEffort as ReasoningEffortConfig;
 use crate::config_types::ReasoningSummary as ReasoningSummaryConfig;
 use crate::custom_prompts::CustomPrompt;
+use crate::custom_prompts::McpPrompt;
 use crate::skills::Skill;
 use crate::message_history::HistoryEntry;
 use crate::models::ContentItem;
@@ -167,6 +169,16 @@ pub enum Op {
         decision: ReviewDecision,
     },
 
+    /// Answer an `EventMsg::ElicitationRequest` from an MCP server.
+    ResolveElicitation {
+        /// The `id` of the elicitation request being answered.
+        id: String,
+        action: ElicitationAction,
+        /// Values for the requested schema; only meaningful with `Accept`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        content: Option<Value>,
+    },
+
     /// Append an entry to the persistent cross-session message history.
     ///
     /// Note the entry is not guaranteed to be logged if the user has
@@ -195,6 +207,10 @@ pub enum Op {
     /// Request the list of available custom prompts.
     ListCustomPrompts,
 
+    /// Request the resources advertised by connected MCP servers.
+    /// Reply is delivered via `EventMsg::ListMcpResourcesResponse`.
+    ListMcpResources,
+
     /// Request the list of available skills.
     /// Reply is delivered via `EventMsg::ListSkillsResponse`.
     ListSkills,
@@ -288,6 +304,11 @@ pub enum SandboxPolicy {
         /// Defaults to true to match historical behavior that permits Git writes.
         #[serde(default = "default_true_bool")]
         allow_git_writes: bool,
+
+        /// Paths that must not be readable from within the sandbox. `None`
+        /// leaves the choice to the receiver, which applies its defaults.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        read_denied_paths: Option<Vec<PathBuf>>,
     },
 }
 
@@ -348,6 +369,7 @@ impl SandboxPolicy {
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
             allow_git_writes: true,
+            read_denied_paths: None,
         }
     }
 
@@ -385,6 +407,7 @@ impl SandboxPolicy {
                 exclude_slash_tmp,
                 allow_git_writes,
                 network_access: _,
+                read_denied_paths: _,
             } => {
                 // Start from explicitly configured writable roots.
                 let mut roots: Vec<PathBuf> = writable_roots.clone();
@@ -575,6 +598,9 @@ pub enum EventMsg {
 
     ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent),
 
+    /// An MCP server is asking the user for input mid-request.
+    ElicitationRequest(ElicitationRequestEvent),
+
     BackgroundEvent(BackgroundEventEvent),
 
     /// Notification that a model stream experienced an error or disconnect
@@ -599,6 +625,9 @@ pub enum EventMsg {
     /// List of custom prompts available to the agent.
     ListCustomPromptsResponse(ListCustomPromptsResponseEvent),
 
+    /// List of resources advertised by connected MCP servers.
+    ListMcpResourcesResponse(ListMcpResourcesResponseEvent),
+
     /// List of skills available to the agent.
     ListSkillsResponse(ListSkillsResponseEvent),
 
@@ -744,6 +773,10 @@ impl TokenUsageInfo {
 pub struct TokenCountEvent {
     pub info: Option<TokenUsageInfo>,
     pub rate_limits: Option<RateLimitSnapshot>,
+    /// Session token usage per `provider:model`, reported once a
+    /// `[model_failover]` entry has served part of the session.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub provider_usage: BTreeMap<String, TokenUsage>,
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize, TS)]
@@ -1340,6 +1373,31 @@ pub struct ExecApprovalRequestEvent {
     /// Optional human-readable reason for the approval (e.g. retry without sandbox).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reason: Option<String>,
+    /// When set, approving reruns the command in the sandbox with this root
+    /// added to the writable roots for the remainder of the session.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grant_root: Option<PathBuf>,
+}
+
+/// An MCP server asked for user input while handling a request (for example
+/// a confirmation or a one-time code).
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct ElicitationRequestEvent {
+    /// Identifier to echo back in `Op::ResolveElicitation`.
+    pub id: String,
+    pub server_name: String,
+    pub message: String,
+    /// Flat JSON schema (`type: "object"` with primitive `properties` and an
+    /// optional `required` list) describing the requested values.
+    pub requested_schema: Value,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "lowercase")]
+pub enum ElicitationAction {
+    Accept,
+    Decline,
+    Cancel,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
@@ -1419,6 +1477,26 @@ pub struct McpListToolsResponseEvent {
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
 pub struct ListCustomPromptsResponseEvent {
     pub custom_prompts: Vec<CustomPrompt>,
+    /// Prompts exposed by connected MCP servers.
+    #[serde(default)]
+    pub mcp_prompts: Vec<McpPrompt>,
+}
+
+/// Response payload for `Op::ListMcpResources`.
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct ListMcpResourcesResponseEvent {
+    pub resources: Vec<McpResourceInfo>,
+}
+
+/// A resource advertised by an MCP server, addressable in user input as
+/// `@server:name`.
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct McpResourceInfo {
+    pub server: String,
+    pub uri: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub mime_type: Option<String>,
 }
 
 /// Response payload for `Op::ListSkills`.
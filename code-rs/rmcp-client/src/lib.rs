@@ -3,3 +3,5 @@ mod rmcp_client;
 mod utils;
 
 pub use rmcp_client::RmcpClient;
+pub use rmcp_client::SendElicitation;
+pub use rmcp_client::SendSampling;
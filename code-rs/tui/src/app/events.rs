@@ -99,6 +99,9 @@ impl App<'_> {
         app_event_tx.send(AppEvent::ScheduleFrameIn(Duration::from_millis(120)));
 
         'main: loop {
+            if let AppState::Chat { widget } = &mut self.app_state {
+                widget.report_auto_drive_phase_changes();
+            }
             let event = match self.next_event_priority() { Some(e) => e, None => break 'main };
             match event {
                 AppEvent::InsertHistory(mut lines) => match &mut self.app_state {
@@ -17,6 +17,7 @@ use super::mention_popup::MentionPopup;
 use super::paste_burst::PasteBurst;
 use crate::slash_command::{built_in_slash_commands, SlashCommand};
 use code_protocol::custom_prompts::CustomPrompt;
+use code_protocol::custom_prompts::McpPrompt;
 use code_protocol::custom_prompts::PROMPTS_CMD_PREFIX;
 
 use crate::app_event_sender::AppEventSender;
@@ -157,6 +158,7 @@ pub(crate) struct ChatComposer {
     animation_running: Option<Arc<AtomicBool>>,
     using_chatgpt_auth: bool,
     custom_prompts: Vec<CustomPrompt>,
+    mcp_prompts: Vec<McpPrompt>,
     // Ephemeral footer notice and its expiry
     footer_notice: Option<(String, std::time::Instant)>,
     // Persistent hint for specific modes (e.g., standard terminal mode)
@@ -165,6 +167,7 @@ pub(crate) struct ChatComposer {
     weave_status: Option<String>,
     // Cached Weave agent names available for #mention completion.
     weave_mention_candidates: Vec<String>,
+    mcp_resource_candidates: Vec<String>,
     // Auto Review status displayed in the footer
     auto_review_status: Option<AutoReviewFooterStatus>,
     // Agent hint label to display alongside Auto Review footer state
@@ -236,10 +239,12 @@ impl ChatComposer {
             animation_running: None,
             using_chatgpt_auth,
             custom_prompts: Vec::new(),
+            mcp_prompts: Vec::new(),
             footer_notice: None,
             standard_terminal_hint: None,
             weave_status: None,
             weave_mention_candidates: Vec::new(),
+            mcp_resource_candidates: Vec::new(),
             auto_review_status: None,
             agent_hint_label: AgentHintLabel::Agents,
             access_mode_label: None,
@@ -958,11 +963,27 @@ impl ChatComposer {
         self.weave_mention_candidates.sort();
         self.weave_mention_candidates.dedup();
 
-        if let ActivePopup::Mention(popup) = &mut self.active_popup {
+        if let ActivePopup::Mention(popup) = &mut self.active_popup
+            && popup.sigil() == '#'
+        {
             popup.set_candidates(self.weave_mention_candidates.clone());
         }
     }
 
+    /// Resources advertised by MCP servers, as `server:name` (or `server:uri`
+    /// when the name is not a single token), offered after `@server:`.
+    pub(crate) fn set_mcp_resource_candidates(&mut self, candidates: Vec<String>) {
+        self.mcp_resource_candidates = candidates;
+        self.mcp_resource_candidates.sort();
+        self.mcp_resource_candidates.dedup();
+
+        if let ActivePopup::Mention(popup) = &mut self.active_popup
+            && popup.sigil() == '@'
+        {
+            popup.set_candidates(self.mcp_resource_candidates.clone());
+        }
+    }
+
     pub fn set_text_content(&mut self, text: String) {
         self.textarea.set_text(&text);
         *self.textarea_state.borrow_mut() = TextAreaState::default();
@@ -1176,6 +1197,14 @@ impl ChatComposer {
                                 }
                             }
                         }
+                        CommandItem::McpPrompt(i) => {
+                            if let Some(prompt) = popup.mcp_prompt(i) {
+                                let command = format!("/{}:{}", prompt.server, prompt.name);
+                                if !first_line.trim_start().starts_with(&command) {
+                                    self.textarea.set_text(&format!("{command} "));
+                                }
+                            }
+                        }
                     }
                     // After completing, place the cursor at the end of the
                     // slash command so the user can immediately type args.
@@ -1233,6 +1262,38 @@ impl ChatComposer {
                             }
                             return (InputResult::None, true);
                         }
+                        CommandItem::McpPrompt(i) => {
+                            if let Some(prompt) = popup.mcp_prompt(i) {
+                                // Core expands `/server:prompt key=value` before the
+                                // turn starts, so submit it as a regular message once
+                                // the user has had a chance to fill required args.
+                                let command = format!("/{}:{}", prompt.server, prompt.name);
+                                let needs_args = prompt.arguments.iter().any(|arg| arg.required);
+                                let first_line = command_text.lines().next().unwrap_or("");
+                                let typed = first_line.trim_start().starts_with(&command);
+                                if typed && (!needs_args || first_line.trim() != command) {
+                                    self.active_popup = ActivePopup::None;
+                                    return self
+                                        .handle_key_event_without_popup(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+                                }
+                                if !needs_args {
+                                    self.textarea.set_text("");
+                                    self.active_popup = ActivePopup::None;
+                                    return (InputResult::Submitted(command), true);
+                                }
+                                let hint = prompt
+                                    .arguments
+                                    .iter()
+                                    .filter(|arg| arg.required)
+                                    .map(|arg| format!("{}=", arg.name))
+                                    .collect::<Vec<_>>()
+                                    .join(" ");
+                                self.textarea.set_text(&format!("{command} {hint}"));
+                                let new_cursor = self.textarea.text().len();
+                                self.textarea.set_cursor(new_cursor);
+                            }
+                            return (InputResult::None, true);
+                        }
                     }
                 }
                 self.handle_key_event_without_popup(key_event)
@@ -1369,7 +1430,8 @@ impl ChatComposer {
                 ..
             } => {
                 if let Some(sel) = popup.selected_mention() {
-                    self.insert_selected_mention(&sel);
+                    let sigil = popup.sigil();
+                    self.insert_selected_mention(sigil, &sel);
                     self.active_popup = ActivePopup::None;
                     return (InputResult::None, true);
                 }
@@ -1509,6 +1571,21 @@ impl ChatComposer {
         token.strip_prefix('#').map(ToString::to_string)
     }
 
+    /// Extract an `@server:…` token under the cursor when `server` advertises
+    /// MCP resources. Plain `@path` tokens are left to file search.
+    fn current_mcp_resource_token(textarea: &TextArea, candidates: &[String]) -> Option<String> {
+        let token = Self::current_at_token(textarea)?;
+        let (server, _) = token.split_once(':')?;
+        if server.is_empty() {
+            return None;
+        }
+        let prefix = format!("{server}:");
+        candidates
+            .iter()
+            .any(|candidate| candidate.starts_with(&prefix))
+            .then_some(token)
+    }
+
     /// Extract the completion token under the cursor for auto file search.
     ///
     /// Auto-trigger only for:
@@ -1657,7 +1734,7 @@ impl ChatComposer {
         self.textarea.set_cursor(new_cursor);
     }
 
-    fn insert_selected_mention(&mut self, mention: &str) {
+    fn insert_selected_mention(&mut self, sigil: char, mention: &str) {
         let cursor_offset = self.textarea.cursor();
         let text = self.textarea.text();
         let safe_cursor = Self::clamp_to_char_boundary(text, cursor_offset);
@@ -1678,7 +1755,7 @@ impl ChatComposer {
             .unwrap_or(after_cursor.len());
         let end_idx = safe_cursor + end_rel_idx;
 
-        let inserted = format!("#{mention}");
+        let inserted = format!("{sigil}{mention}");
         let mut new_text =
             String::with_capacity(text.len() - (end_idx - start_idx) + inserted.len() + 1);
         new_text.push_str(&text[..start_idx]);
@@ -2044,6 +2121,9 @@ impl ChatComposer {
                     if !self.custom_prompts.is_empty() {
                         command_popup.set_prompts(self.custom_prompts.clone());
                     }
+                    if !self.mcp_prompts.is_empty() {
+                        command_popup.set_mcp_prompts(self.mcp_prompts.clone());
+                    }
                     // Load saved subagent commands to include in autocomplete (exclude built-ins)
                     if let Ok(cfg) = code_core::config::Config::load_with_cli_overrides(vec![], code_core::config::ConfigOverrides::default()) {
                         let mut names: Vec<String> = cfg
@@ -2076,6 +2156,13 @@ impl ChatComposer {
         }
     }
 
+    pub(crate) fn set_mcp_prompts(&mut self, prompts: Vec<McpPrompt>) {
+        self.mcp_prompts = prompts.clone();
+        if let ActivePopup::Command(popup) = &mut self.active_popup {
+            popup.set_mcp_prompts(prompts);
+        }
+    }
+
     fn sync_mention_popup(&mut self) {
         // Avoid showing weave mention suggestions while the user is typing a slash command.
         let first_line = self.textarea.text().lines().next().unwrap_or("");
@@ -2086,14 +2173,21 @@ impl ChatComposer {
             return;
         }
 
-        let Some(query) = Self::current_hash_token(&self.textarea) else {
-            if matches!(self.active_popup, ActivePopup::Mention(_)) {
-                self.active_popup = ActivePopup::None;
-            }
-            return;
-        };
+        let (sigil, query, candidates) =
+            if let Some(query) = Self::current_hash_token(&self.textarea) {
+                ('#', query, &self.weave_mention_candidates)
+            } else if let Some(query) =
+                Self::current_mcp_resource_token(&self.textarea, &self.mcp_resource_candidates)
+            {
+                ('@', query, &self.mcp_resource_candidates)
+            } else {
+                if matches!(self.active_popup, ActivePopup::Mention(_)) {
+                    self.active_popup = ActivePopup::None;
+                }
+                return;
+            };
 
-        if self.weave_mention_candidates.is_empty() {
+        if candidates.is_empty() {
             if matches!(self.active_popup, ActivePopup::Mention(_)) {
                 self.active_popup = ActivePopup::None;
             }
@@ -2101,12 +2195,12 @@ impl ChatComposer {
         }
 
         match &mut self.active_popup {
-            ActivePopup::Mention(popup) => {
-                popup.set_candidates(self.weave_mention_candidates.clone());
+            ActivePopup::Mention(popup) if popup.sigil() == sigil => {
+                popup.set_candidates(candidates.clone());
                 popup.on_query_change(query);
             }
             _ => {
-                let mut popup = MentionPopup::new(self.weave_mention_candidates.clone());
+                let mut popup = MentionPopup::with_sigil(candidates.clone(), sigil);
                 popup.on_query_change(query);
                 self.active_popup = ActivePopup::Mention(popup);
             }
@@ -11,6 +11,7 @@ use crate::slash_command::SlashCommand;
 use crate::slash_command::built_in_slash_commands;
 use code_common::fuzzy_match::fuzzy_match;
 use code_protocol::custom_prompts::CustomPrompt;
+use code_protocol::custom_prompts::McpPrompt;
 use code_protocol::custom_prompts::PROMPTS_CMD_PREFIX;
 use std::collections::HashSet;
 
@@ -22,12 +23,15 @@ pub(crate) enum CommandItem {
     UserPrompt(usize),
     // Index into `subagents`
     Subagent(usize),
+    // Index into `mcp_prompts`
+    McpPrompt(usize),
 }
 
 pub(crate) struct CommandPopup {
     command_filter: String,
     builtins: Vec<(&'static str, SlashCommand)>,
     prompts: Vec<CustomPrompt>,
+    mcp_prompts: Vec<McpPrompt>,
     state: ScrollState,
     subagents: Vec<String>,
 }
@@ -43,6 +47,7 @@ impl CommandPopup {
             command_filter: String::new(),
             builtins: commands,
             prompts: Vec::new(),
+            mcp_prompts: Vec::new(),
             state: ScrollState::new(),
             subagents: Vec::new(),
         }
@@ -64,6 +69,17 @@ impl CommandPopup {
         self.prompts.get(idx)
     }
 
+    /// Supply prompts exposed by MCP servers; they appear as `/server:prompt`.
+    pub(crate) fn set_mcp_prompts(&mut self, mut prompts: Vec<McpPrompt>) {
+        prompts.sort_by(|a, b| a.server.cmp(&b.server).then_with(|| a.name.cmp(&b.name)));
+        self.mcp_prompts = prompts;
+        self.state.clamp_selection(self.filtered_items().len());
+    }
+
+    pub(crate) fn mcp_prompt(&self, idx: usize) -> Option<&McpPrompt> {
+        self.mcp_prompts.get(idx)
+    }
+
     pub(crate) fn subagent_name(&self, idx: usize) -> Option<&str> {
         self.subagents.get(idx).map(|s| s.as_str())
     }
@@ -135,6 +151,10 @@ impl CommandPopup {
             for idx in 0..self.prompts.len() {
                 out.push((CommandItem::UserPrompt(idx), None, 0));
             }
+            // MCP server prompts last, grouped by server.
+            for idx in 0..self.mcp_prompts.len() {
+                out.push((CommandItem::McpPrompt(idx), None, 0));
+            }
             return out;
         }
 
@@ -166,6 +186,12 @@ impl CommandPopup {
                 out.push((CommandItem::UserPrompt(idx), Some(indices), score));
             }
         }
+        for (idx, p) in self.mcp_prompts.iter().enumerate() {
+            let qualified = format!("{}:{}", p.server, p.name);
+            if let Some((indices, score)) = fuzzy_match(&qualified, filter) {
+                out.push((CommandItem::McpPrompt(idx), Some(indices), score));
+            }
+        }
         // When filtering, sort by ascending score, then prefer built-ins over
         // subagents over prompts to keep core commands easy to reach on exact
         // matches (e.g., `/prompts` should not auto-select a custom prompt),
@@ -182,6 +208,7 @@ impl CommandPopup {
                 CommandItem::Builtin(_) => 0,
                 CommandItem::Subagent(_) => 1,
                 CommandItem::UserPrompt(_) => 2,
+                CommandItem::McpPrompt(_) => 3,
             };
             let rank_cmp = rank(&a.0).cmp(&rank(&b.0));
             if rank_cmp != Ordering::Equal {
@@ -192,11 +219,13 @@ impl CommandPopup {
                 CommandItem::Builtin(c) => c.command(),
                 CommandItem::UserPrompt(i) => &self.prompts[i].name,
                 CommandItem::Subagent(i) => &self.subagents[i],
+                CommandItem::McpPrompt(i) => &self.mcp_prompts[i].name,
             };
             let bn = match b.0 {
                 CommandItem::Builtin(c) => c.command(),
                 CommandItem::UserPrompt(i) => &self.prompts[i].name,
                 CommandItem::Subagent(i) => &self.subagents[i],
+                CommandItem::McpPrompt(i) => &self.mcp_prompts[i].name,
             };
             an.cmp(bn)
         });
@@ -274,6 +303,14 @@ impl WidgetRef for CommandPopup {
                             format!("/{}", self.subagents[i]),
                             Some("custom subagent".to_string()),
                         ),
+                        CommandItem::McpPrompt(i) => {
+                            let prompt = &self.mcp_prompts[i];
+                            let desc = match prompt.description.as_deref().map(str::trim) {
+                                Some(text) if !text.is_empty() => format!("[mcp] {text}"),
+                                _ => "[mcp] server prompt".to_string(),
+                            };
+                            (format!("/{}:{}", prompt.server, prompt.name), Some(desc))
+                        }
                     };
                     GenericDisplayRow {
                         name,
@@ -325,4 +362,21 @@ mod tests {
         let first = popup.filtered_items().first().copied();
         assert!(matches!(first, Some(CommandItem::Builtin(SlashCommand::Prompts))));
     }
+
+    #[test]
+    fn mcp_prompts_match_on_server_qualified_name() {
+        let mut popup = CommandPopup::new_with_filter(false);
+        popup.set_mcp_prompts(vec![McpPrompt {
+            server: "docs".to_string(),
+            name: "summarize".to_string(),
+            description: Some("Summarize a page".to_string()),
+            arguments: Vec::new(),
+        }]);
+
+        popup.on_composer_text_change("/docs:sum".to_string());
+
+        let first = popup.filtered_items().first().copied();
+        assert_eq!(first, Some(CommandItem::McpPrompt(0)));
+        assert_eq!(popup.mcp_prompt(0).map(|p| p.name.as_str()), Some("summarize"));
+    }
 }
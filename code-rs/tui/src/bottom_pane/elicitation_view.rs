@@ -0,0 +1,644 @@
+//! Bottom-pane form for MCP elicitation requests.
+//!
+//! Servers describe the values they want with a flat JSON schema: an object
+//! whose properties are strings (optionally restricted by `enum`), numbers,
+//! integers or booleans. Each property becomes one form row. The user can
+//! submit the form, decline the request or cancel it; the answer is sent back
+//! to core as `Op::ResolveElicitation`.
+
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Alignment;
+use ratatui::layout::Rect;
+use ratatui::style::Modifier;
+use ratatui::style::Style;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use ratatui::widgets::Block;
+use ratatui::widgets::Borders;
+use ratatui::widgets::Clear;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::Widget;
+use serde_json::Map;
+use serde_json::Value;
+
+use code_core::protocol::ElicitationAction;
+use code_core::protocol::ElicitationRequestEvent;
+use code_core::protocol::Op;
+
+use crate::app_event::AppEvent;
+use crate::app_event_sender::AppEventSender;
+
+use super::BottomPane;
+use super::CancellationEvent;
+use super::bottom_pane_view::BottomPaneView;
+use super::bottom_pane_view::ConditionalUpdate;
+use super::form_text_field::FormTextField;
+
+const ACTIONS: [(&str, ElicitationAction); 3] = [
+    ("Submit", ElicitationAction::Accept),
+    ("Decline", ElicitationAction::Decline),
+    ("Cancel", ElicitationAction::Cancel),
+];
+
+#[derive(Debug)]
+enum FieldKind {
+    Text {
+        min_length: Option<usize>,
+        max_length: Option<usize>,
+        format: Option<String>,
+    },
+    Number {
+        integer: bool,
+        minimum: Option<f64>,
+        maximum: Option<f64>,
+    },
+    Boolean(bool),
+    Choice {
+        values: Vec<String>,
+        labels: Vec<String>,
+        selected: Option<usize>,
+    },
+}
+
+#[derive(Debug)]
+struct Field {
+    name: String,
+    label: String,
+    description: Option<String>,
+    required: bool,
+    kind: FieldKind,
+    input: FormTextField,
+}
+
+impl Field {
+    fn from_schema(name: &str, schema: &Value, required: bool) -> Self {
+        let str_prop = |key: &str| schema.get(key).and_then(Value::as_str).map(str::to_string);
+        let usize_prop = |key: &str| {
+            schema
+                .get(key)
+                .and_then(Value::as_u64)
+                .and_then(|n| usize::try_from(n).ok())
+        };
+        let default = schema.get("default");
+
+        let kind = if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+            let values: Vec<String> = values
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect();
+            let labels = schema
+                .get("enumNames")
+                .and_then(Value::as_array)
+                .map(|names| {
+                    names
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .map(str::to_string)
+                        .collect::<Vec<_>>()
+                })
+                .filter(|names| names.len() == values.len())
+                .unwrap_or_else(|| values.clone());
+            let selected = default
+                .and_then(Value::as_str)
+                .and_then(|default| values.iter().position(|value| value == default))
+                .or_else(|| (required && !values.is_empty()).then_some(0));
+            FieldKind::Choice {
+                values,
+                labels,
+                selected,
+            }
+        } else {
+            match schema.get("type").and_then(Value::as_str) {
+                Some("boolean") => {
+                    FieldKind::Boolean(default.and_then(Value::as_bool).unwrap_or(false))
+                }
+                Some(ty @ ("number" | "integer")) => FieldKind::Number {
+                    integer: ty == "integer",
+                    minimum: schema.get("minimum").and_then(Value::as_f64),
+                    maximum: schema.get("maximum").and_then(Value::as_f64),
+                },
+                _ => FieldKind::Text {
+                    min_length: usize_prop("minLength"),
+                    max_length: usize_prop("maxLength"),
+                    format: str_prop("format"),
+                },
+            }
+        };
+
+        let mut input = FormTextField::new_single_line();
+        match (&kind, default) {
+            (FieldKind::Text { .. }, Some(Value::String(text))) => input.set_text(text),
+            (FieldKind::Number { .. }, Some(Value::Number(number))) => {
+                input.set_text(&number.to_string())
+            }
+            _ => {}
+        }
+
+        Self {
+            name: name.to_string(),
+            label: str_prop("title").unwrap_or_else(|| name.to_string()),
+            description: str_prop("description"),
+            required,
+            kind,
+            input,
+        }
+    }
+
+    fn is_text_input(&self) -> bool {
+        matches!(self.kind, FieldKind::Text { .. } | FieldKind::Number { .. })
+    }
+
+    /// Step a boolean or choice field. Optional choices include "unset".
+    fn cycle(&mut self, forward: bool) {
+        match &mut self.kind {
+            FieldKind::Boolean(value) => *value = !*value,
+            FieldKind::Choice {
+                values, selected, ..
+            } => {
+                let len = values.len();
+                if len == 0 {
+                    return;
+                }
+                *selected = match (*selected, forward, self.required) {
+                    (Some(idx), true, _) if idx + 1 < len => Some(idx + 1),
+                    (Some(_), true, true) => Some(0),
+                    (Some(_), true, false) => None,
+                    (None, true, _) => Some(0),
+                    (Some(idx), false, _) if idx > 0 => Some(idx - 1),
+                    (Some(_), false, true) => Some(len - 1),
+                    (Some(_), false, false) => None,
+                    (None, false, _) => Some(len - 1),
+                };
+            }
+            FieldKind::Text { .. } | FieldKind::Number { .. } => {}
+        }
+    }
+
+    /// Validated value for this field, or `None` when an optional field is
+    /// left empty.
+    fn value(&self) -> Result<Option<Value>, String> {
+        let label = &self.label;
+        match &self.kind {
+            FieldKind::Boolean(value) => Ok(Some(Value::Bool(*value))),
+            FieldKind::Choice {
+                values, selected, ..
+            } => match selected.and_then(|idx| values.get(idx)) {
+                Some(value) => Ok(Some(Value::String(value.clone()))),
+                None if self.required => Err(format!("{label} is required")),
+                None => Ok(None),
+            },
+            FieldKind::Text {
+                min_length,
+                max_length,
+                format,
+            } => {
+                let text = self.input.text().trim();
+                if text.is_empty() {
+                    return if self.required {
+                        Err(format!("{label} is required"))
+                    } else {
+                        Ok(None)
+                    };
+                }
+                let len = text.chars().count();
+                if let Some(min) = min_length
+                    && len < *min
+                {
+                    return Err(format!("{label} must be at least {min} characters"));
+                }
+                if let Some(max) = max_length
+                    && len > *max
+                {
+                    return Err(format!("{label} must be at most {max} characters"));
+                }
+                if format.as_deref() == Some("email") && !text.contains('@') {
+                    return Err(format!("{label} must be an email address"));
+                }
+                Ok(Some(Value::String(text.to_string())))
+            }
+            FieldKind::Number {
+                integer,
+                minimum,
+                maximum,
+            } => {
+                let text = self.input.text().trim();
+                if text.is_empty() {
+                    return if self.required {
+                        Err(format!("{label} is required"))
+                    } else {
+                        Ok(None)
+                    };
+                }
+                let (number, value) = if *integer {
+                    let parsed = text
+                        .parse::<i64>()
+                        .map_err(|_| format!("{label} must be a whole number"))?;
+                    (parsed as f64, Value::from(parsed))
+                } else {
+                    let parsed = text
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|n| n.is_finite())
+                        .ok_or_else(|| format!("{label} must be a number"))?;
+                    (parsed, Value::from(parsed))
+                };
+                if let Some(min) = minimum
+                    && number < *min
+                {
+                    return Err(format!("{label} must be at least {min}"));
+                }
+                if let Some(max) = maximum
+                    && number > *max
+                {
+                    return Err(format!("{label} must be at most {max}"));
+                }
+                Ok(Some(value))
+            }
+        }
+    }
+
+    fn value_line(&self, selected: bool) -> Line<'static> {
+        let marker = if selected { "› " } else { "  " };
+        let mut spans = vec![Span::styled(
+            marker,
+            Style::default().fg(crate::colors::primary()),
+        )];
+        match &self.kind {
+            FieldKind::Boolean(value) => {
+                let text = if *value { "[x] yes" } else { "[ ] no" };
+                spans.push(Span::raw(text));
+            }
+            FieldKind::Choice {
+                labels,
+                selected: choice,
+                ..
+            } => {
+                let label = choice
+                    .and_then(|idx| labels.get(idx))
+                    .map(String::as_str)
+                    .unwrap_or("(none)");
+                spans.push(Span::raw(format!("‹ {label} ›")));
+            }
+            // Text inputs are drawn by `FormTextField` over this row.
+            FieldKind::Text { .. } | FieldKind::Number { .. } => {}
+        }
+        Line::from(spans)
+    }
+}
+
+fn parse_fields(schema: &Value) -> Vec<Field> {
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|names| names.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+    properties
+        .iter()
+        .map(|(name, prop)| Field::from_schema(name, prop, required.contains(&name.as_str())))
+        .collect()
+}
+
+struct ElicitationLayout {
+    lines: Vec<Line<'static>>,
+    /// Row offset of each text input, by field index.
+    input_rows: Vec<(usize, u16)>,
+}
+
+pub(crate) struct ElicitationView {
+    id: String,
+    server_name: String,
+    message: String,
+    fields: Vec<Field>,
+    /// Focused row: one per field, then one per entry in `ACTIONS`.
+    selected: usize,
+    error: Option<String>,
+    complete: bool,
+    app_event_tx: AppEventSender,
+}
+
+impl ElicitationView {
+    pub(crate) fn new(request: ElicitationRequestEvent, app_event_tx: AppEventSender) -> Self {
+        let fields = parse_fields(&request.requested_schema);
+        Self {
+            id: request.id,
+            server_name: request.server_name,
+            message: request.message,
+            fields,
+            selected: 0,
+            error: None,
+            complete: false,
+            app_event_tx,
+        }
+    }
+
+    fn row_count(&self) -> usize {
+        self.fields.len() + ACTIONS.len()
+    }
+
+    fn selected_action(&self) -> Option<ElicitationAction> {
+        self.selected
+            .checked_sub(self.fields.len())
+            .and_then(|idx| ACTIONS.get(idx))
+            .map(|(_, action)| *action)
+    }
+
+    fn content(&self) -> Result<Map<String, Value>, (usize, String)> {
+        let mut content = Map::new();
+        for (idx, field) in self.fields.iter().enumerate() {
+            if let Some(value) = field.value().map_err(|err| (idx, err))? {
+                content.insert(field.name.clone(), value);
+            }
+        }
+        Ok(content)
+    }
+
+    fn respond(&mut self, action: ElicitationAction) {
+        let content = if action == ElicitationAction::Accept {
+            match self.content() {
+                Ok(content) => Some(Value::Object(content)),
+                Err((idx, err)) => {
+                    self.selected = idx;
+                    self.error = Some(err);
+                    return;
+                }
+            }
+        } else {
+            None
+        };
+        self.app_event_tx
+            .send(AppEvent::CodexOp(Op::ResolveElicitation {
+                id: self.id.clone(),
+                action,
+                content,
+            }));
+        self.complete = true;
+    }
+
+    fn handle_key(&mut self, key_event: KeyEvent) {
+        let last_row = self.row_count().saturating_sub(1);
+        let on_action = self.selected_action().is_some();
+        match key_event.code {
+            KeyCode::Esc => self.respond(ElicitationAction::Cancel),
+            KeyCode::Tab | KeyCode::Down => self.selected = (self.selected + 1).min(last_row),
+            KeyCode::BackTab | KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Left if on_action && self.selected > self.fields.len() => {
+                self.selected -= 1;
+            }
+            KeyCode::Right if on_action => self.selected = (self.selected + 1).min(last_row),
+            KeyCode::Enter => match self.selected_action() {
+                Some(action) => self.respond(action),
+                None => self.selected = (self.selected + 1).min(last_row),
+            },
+            _ => {
+                let Some(field) = self.fields.get_mut(self.selected) else {
+                    return;
+                };
+                if field.is_text_input() {
+                    field.input.handle_key(key_event);
+                    self.error = None;
+                    return;
+                }
+                match key_event.code {
+                    KeyCode::Left => field.cycle(false),
+                    KeyCode::Right | KeyCode::Char(' ') => field.cycle(true),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn layout(&self, width: u16) -> ElicitationLayout {
+        let wrap_width = usize::from(width.max(1));
+        let dim = Style::default().fg(crate::colors::text_dim());
+        let mut lines: Vec<Line<'static>> = textwrap::wrap(&self.message, wrap_width)
+            .into_iter()
+            .map(|line| Line::from(line.into_owned()))
+            .collect();
+        let mut input_rows = Vec::new();
+
+        for (idx, field) in self.fields.iter().enumerate() {
+            lines.push(Line::from(""));
+            let label_style = if idx == self.selected {
+                Style::default()
+                    .fg(crate::colors::primary())
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().add_modifier(Modifier::BOLD)
+            };
+            let mut label = vec![Span::styled(field.label.clone(), label_style)];
+            if field.required {
+                label.push(Span::styled(
+                    " *",
+                    Style::default().fg(crate::colors::error()),
+                ));
+            }
+            if let Some(description) = &field.description {
+                label.push(Span::styled(format!("  {description}"), dim));
+            }
+            lines.push(Line::from(label));
+            if field.is_text_input() {
+                input_rows.push((idx, lines.len() as u16));
+            }
+            lines.push(field.value_line(idx == self.selected));
+        }
+
+        lines.push(Line::from(""));
+        let mut actions: Vec<Span<'static>> = Vec::new();
+        for (offset, (label, action)) in ACTIONS.iter().enumerate() {
+            // A request without fields is a yes/no question (e.g. approving
+            // an MCP sampling request), so "Submit" reads better as "Allow".
+            let label = if self.fields.is_empty() && *action == ElicitationAction::Accept {
+                "Allow"
+            } else {
+                label
+            };
+            let style = if self.selected == self.fields.len() + offset {
+                Style::default()
+                    .fg(crate::colors::primary())
+                    .add_modifier(Modifier::BOLD | Modifier::REVERSED)
+            } else {
+                Style::default()
+            };
+            actions.push(Span::styled(format!(" {label} "), style));
+            actions.push(Span::raw("  "));
+        }
+        lines.push(Line::from(actions));
+
+        if let Some(error) = &self.error {
+            lines.push(Line::from(Span::styled(
+                error.clone(),
+                Style::default().fg(crate::colors::error()),
+            )));
+        }
+        lines.push(Line::from(Span::styled(
+            "Tab/↑↓ move · ←→/Space change · Enter select · Esc cancel",
+            dim,
+        )));
+
+        ElicitationLayout { lines, input_rows }
+    }
+}
+
+impl<'a> BottomPaneView<'a> for ElicitationView {
+    fn handle_key_event(&mut self, _pane: &mut BottomPane<'a>, key_event: KeyEvent) {
+        self.handle_key(key_event);
+    }
+
+    fn handle_paste(&mut self, text: String) -> ConditionalUpdate {
+        match self.fields.get_mut(self.selected) {
+            Some(field) if field.is_text_input() => {
+                field.input.handle_paste(text);
+                ConditionalUpdate::NeedsRedraw
+            }
+            _ => ConditionalUpdate::NoRedraw,
+        }
+    }
+
+    fn on_ctrl_c(&mut self, _pane: &mut BottomPane<'a>) -> CancellationEvent {
+        self.respond(ElicitationAction::Cancel);
+        CancellationEvent::Handled
+    }
+
+    fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// The server stops waiting once its request ends, so drop the form with
+    /// the turn.
+    fn should_hide_when_task_is_done(&mut self) -> bool {
+        true
+    }
+
+    fn desired_height(&self, width: u16) -> u16 {
+        let content_width = width.saturating_sub(4).max(1);
+        (self.layout(content_width).lines.len() as u16).saturating_add(2)
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(crate::colors::border()))
+            .style(
+                Style::default()
+                    .bg(crate::colors::background())
+                    .fg(crate::colors::text()),
+            )
+            .title(format!(" {} needs input ", self.server_name))
+            .title_alignment(Alignment::Center);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let content = Rect {
+            x: inner.x.saturating_add(1),
+            y: inner.y,
+            width: inner.width.saturating_sub(2),
+            height: inner.height,
+        };
+        let ElicitationLayout { lines, input_rows } = self.layout(content.width);
+        Paragraph::new(lines)
+            .style(
+                Style::default()
+                    .bg(crate::colors::background())
+                    .fg(crate::colors::text()),
+            )
+            .render(content, buf);
+
+        for (idx, row) in input_rows {
+            if row >= content.height {
+                continue;
+            }
+            let field_area = Rect {
+                x: content.x.saturating_add(2),
+                y: content.y.saturating_add(row),
+                width: content.width.saturating_sub(2),
+                height: 1,
+            };
+            if let Some(field) = self.fields.get(idx) {
+                field.input.render(field_area, buf, idx == self.selected);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn field(schema: Value, required: bool) -> Field {
+        Field::from_schema("value", &schema, required)
+    }
+
+    #[test]
+    fn parses_schema_properties_and_required_flags() {
+        let fields = parse_fields(&json!({
+            "type": "object",
+            "properties": {
+                "code": { "type": "string", "title": "One-time code", "minLength": 6 },
+                "remember": { "type": "boolean", "default": true },
+                "region": { "type": "string", "enum": ["eu", "us"], "enumNames": ["Europe", "US"] }
+            },
+            "required": ["code"]
+        }));
+
+        let code = fields
+            .iter()
+            .find(|f| f.name == "code")
+            .expect("code field");
+        assert_eq!(code.label, "One-time code");
+        assert!(code.required);
+        assert_eq!(code.value(), Err("One-time code is required".to_string()));
+
+        let remember = fields
+            .iter()
+            .find(|f| f.name == "remember")
+            .expect("remember");
+        assert_eq!(remember.value(), Ok(Some(Value::Bool(true))));
+
+        let region = fields.iter().find(|f| f.name == "region").expect("region");
+        assert_eq!(region.value(), Ok(None));
+    }
+
+    #[test]
+    fn validates_numbers_and_lengths() {
+        let mut count = field(
+            json!({ "type": "integer", "minimum": 1, "maximum": 5 }),
+            true,
+        );
+        count.input.set_text("7");
+        assert_eq!(count.value(), Err("value must be at most 5".to_string()));
+        count.input.set_text("2.5");
+        assert_eq!(
+            count.value(),
+            Err("value must be a whole number".to_string())
+        );
+        count.input.set_text("3");
+        assert_eq!(count.value(), Ok(Some(json!(3))));
+
+        let mut code = field(json!({ "type": "string", "maxLength": 4 }), false);
+        code.input.set_text("12345");
+        assert_eq!(
+            code.value(),
+            Err("value must be at most 4 characters".to_string())
+        );
+    }
+
+    #[test]
+    fn optional_choice_cycles_through_unset() {
+        let mut choice = field(json!({ "type": "string", "enum": ["a", "b"] }), false);
+        choice.cycle(true);
+        assert_eq!(choice.value(), Ok(Some(json!("a"))));
+        choice.cycle(true);
+        choice.cycle(true);
+        assert_eq!(choice.value(), Ok(None));
+        choice.cycle(false);
+        assert_eq!(choice.value(), Ok(Some(json!("b"))));
+    }
+}
@@ -15,19 +15,31 @@ pub(crate) struct MentionPopup {
     query: String,
     candidates: Vec<String>,
     state: ScrollState,
+    /// Leading character of the mention token: `#` for weave agents, `@` for
+    /// MCP resources (`@server:resource`).
+    sigil: char,
 }
 
 impl MentionPopup {
     pub(crate) fn new(candidates: Vec<String>) -> Self {
+        Self::with_sigil(candidates, '#')
+    }
+
+    pub(crate) fn with_sigil(candidates: Vec<String>, sigil: char) -> Self {
         let mut s = Self {
             query: String::new(),
             candidates,
             state: ScrollState::new(),
+            sigil,
         };
         s.state.clamp_selection(s.filtered_items().len());
         s
     }
 
+    pub(crate) fn sigil(&self) -> char {
+        self.sigil
+    }
+
     pub(crate) fn set_candidates(&mut self, mut candidates: Vec<String>) {
         candidates.sort();
         candidates.dedup();
@@ -108,7 +120,7 @@ impl WidgetRef for &MentionPopup {
         let matches = self.filtered();
         if matches.is_empty() {
             let msg = if self.candidates.is_empty() {
-                "no weave agents"
+                if self.sigil == '@' { "no MCP resources" } else { "no weave agents" }
             } else {
                 "no matches"
             };
@@ -126,7 +138,7 @@ impl WidgetRef for &MentionPopup {
         let rows_all: Vec<GenericDisplayRow> = matches
             .into_iter()
             .map(|(name, indices, _)| {
-                let rendered = format!("#{name}");
+                let rendered = format!("{}{name}", self.sigil);
                 GenericDisplayRow {
                     name: rendered,
                     match_indices: indices.map(|v| v.into_iter().map(|i| i + 1).collect()),
@@ -11,7 +11,9 @@ pub(crate) use bottom_pane_view::BottomPaneView;
 pub(crate) use bottom_pane_view::ConditionalUpdate;
 use crate::util::buffer::fill_rect;
 use code_protocol::custom_prompts::CustomPrompt;
+use code_protocol::custom_prompts::McpPrompt;
 use code_protocol::skills::Skill;
+use code_core::protocol::ElicitationRequestEvent;
 use code_core::protocol::TokenUsage;
 use code_file_search::FileMatch;
 use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
@@ -30,6 +32,7 @@ mod bottom_pane_view;
 mod chat_composer;
 mod chat_composer_history;
 mod diff_popup;
+mod elicitation_view;
 mod custom_prompt_view;
 pub(crate) mod prompt_args;
 mod command_popup;
@@ -557,6 +560,14 @@ impl BottomPane<'_> {
         self.composer.set_custom_prompts(prompts);
     }
 
+    pub(crate) fn set_mcp_prompts(&mut self, prompts: Vec<McpPrompt>) {
+        self.composer.set_mcp_prompts(prompts);
+    }
+
+    pub(crate) fn set_mcp_resource_candidates(&mut self, candidates: Vec<String>) {
+        self.composer.set_mcp_resource_candidates(candidates);
+    }
+
     pub(crate) fn custom_prompts(&self) -> &[CustomPrompt] {
         &self.custom_prompts
     }
@@ -736,6 +747,15 @@ impl BottomPane<'_> {
         self.request_redraw()
     }
 
+    /// Show the form for an MCP server's elicitation request.
+    pub(crate) fn show_elicitation_request(&mut self, request: ElicitationRequestEvent) {
+        let view = elicitation_view::ElicitationView::new(request, self.app_event_tx.clone());
+        self.active_view = Some(Box::new(view));
+        self.active_view_kind = ActiveViewKind::Other;
+        self.status_view_active = false;
+        self.request_redraw()
+    }
+
     /// Show the model selection UI
     pub fn show_model_selection(
         &mut self,
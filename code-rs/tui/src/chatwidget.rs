@@ -148,6 +148,7 @@ use code_core::TextFormat;
 use code_core::protocol::AgentMessageDeltaEvent;
 use code_core::protocol::ApprovedCommandMatchKind;
 use code_core::protocol::AskForApproval;
+use code_core::protocol::SandboxDenialEvent;
 use code_core::protocol::SandboxPolicy;
 use code_core::protocol::AgentSourceKind;
 use code_core::protocol::AgentMessageEvent;
@@ -5555,7 +5556,9 @@ impl ChatWidget<'_> {
             .push_approval_request(ApprovalRequest::Exec {
                 id: approval_id,
                 command: ev.command,
+                cwd: ev.cwd,
                 reason: ev.reason,
+                grant_root: ev.grant_root,
             }, ticket);
     }
 
@@ -13700,6 +13703,7 @@ impl ChatWidget<'_> {
 
                 // Ask core for custom prompts so the slash menu can show them.
                 self.submit_op(Op::ListCustomPrompts);
+                self.submit_op(Op::ListMcpResources);
                 self.submit_op(Op::ListSkills);
 
                 if self.resume_placeholder_visible && event.history_entry_count == 0 {
@@ -14395,6 +14399,12 @@ impl ChatWidget<'_> {
                     },
                 );
             }
+            EventMsg::ElicitationRequest(ev) => {
+                // Elicitations arrive while a tool call is blocked on the
+                // answer, so show the form right away instead of deferring.
+                self.bottom_pane.show_elicitation_request(ev);
+                self.request_redraw();
+            }
             EventMsg::ExecCommandBegin(ev) => {
                 let seq = event.event_seq;
                 let om_begin = event
@@ -15201,6 +15211,24 @@ impl ChatWidget<'_> {
                 let len = ev.custom_prompts.len();
                 debug!("received {len} custom prompts");
                 self.bottom_pane.set_custom_prompts(ev.custom_prompts);
+                self.bottom_pane.set_mcp_prompts(ev.mcp_prompts);
+            }
+            EventMsg::ListMcpResourcesResponse(ev) => {
+                let len = ev.resources.len();
+                debug!("received {len} MCP resources");
+                let candidates = ev
+                    .resources
+                    .into_iter()
+                    .map(|resource| {
+                        let name = resource.name.trim();
+                        if name.is_empty() || name.contains(char::is_whitespace) {
+                            format!("{}:{}", resource.server, resource.uri)
+                        } else {
+                            format!("{}:{name}", resource.server)
+                        }
+                    })
+                    .collect();
+                self.bottom_pane.set_mcp_resource_candidates(candidates);
             }
             EventMsg::ListSkillsResponse(ev) => {
                 let len = ev.skills.len();
@@ -15216,6 +15244,19 @@ impl ChatWidget<'_> {
                 info!("TurnDiffEvent: {unified_diff}");
                 self.turn_had_code_edits = true;
             }
+            EventMsg::SandboxDenial(SandboxDenialEvent { denial, .. }) => {
+                info!("SandboxDenial: {denial}");
+                let cell = history_cell::new_background_event(format!("Sandbox: {denial}"));
+                let record = HistoryDomainRecord::BackgroundEvent(cell.state().clone());
+                self.push_system_cell(
+                    Box::new(cell),
+                    SystemPlacement::EarlyInCurrent,
+                    None,
+                    event.order.as_ref(),
+                    "background",
+                    Some(record),
+                );
+            }
             EventMsg::BackgroundEvent(BackgroundEventEvent { message }) => {
                 info!("BackgroundEvent: {message}");
                 if browser_sessions::handle_background_event(
@@ -15427,12 +15468,17 @@ impl ChatWidget<'_> {
                 #[cfg(feature = "code-fork")]
                 handle_browser_screenshot(&payload, &self.app_event_tx);
 
-                let BrowserScreenshotUpdateEvent { screenshot_path, url } = payload;
+                let BrowserScreenshotUpdateEvent {
+                    screenshot_path,
+                    url,
+                    tab_id,
+                } = payload;
                 let update = browser_sessions::handle_screenshot_update(
                     self,
                     event.order.as_ref(),
                     &screenshot_path,
                     &url,
+                    tab_id,
                 );
                 tracing::info!(
                     "Received browser screenshot update: {} at URL: {}",
@@ -25475,6 +25521,18 @@ Have we met every part of this goal and is there no further work to do?"#
         self.request_redraw();
     }
 
+    /// Reports Auto Drive phase changes recorded since the last call to the
+    /// core, which runs any `auto_drive.phase` project hooks.
+    pub(crate) fn report_auto_drive_phase_changes(&mut self) {
+        for change in self.auto_state.take_phase_changes() {
+            self.submit_op(Op::AutoDrivePhase {
+                phase: change.phase.to_string(),
+                previous_phase: change.previous.to_string(),
+                goal: self.auto_state.goal.clone(),
+            });
+        }
+    }
+
     /// Forward an `Op` directly to codex.
     pub(crate) fn submit_op(&self, op: Op) {
         if let Err(e) = self.code_op_tx.send(op) {
@@ -26964,6 +27022,7 @@ Have we met every part of this goal and is there no further work to do?"#
                                                             BrowserScreenshotUpdateEvent {
                                                                 screenshot_path: first_path.clone(),
                                                                 url: url_inner,
+                                                                tab_id: browser_manager_inner.active_tab_id().await,
                                                             },
                                                         ),
                                                         order: None,
@@ -27037,6 +27096,7 @@ Have we met every part of this goal and is there no further work to do?"#
                                                             url: url.unwrap_or_else(|| {
                                                                 "Chrome".to_string()
                                                             }),
+                                                            tab_id: browser_manager.active_tab_id().await,
                                                         },
                                                     ),
                                                     order: None,
@@ -27168,6 +27228,7 @@ Have we met every part of this goal and is there no further work to do?"#
                                                                 msg: EventMsg::BrowserScreenshotUpdate(BrowserScreenshotUpdateEvent {
                                                                     screenshot_path: first_path.clone(),
                                                                     url: url_inner,
+                                                                    tab_id: browser_manager_inner.active_tab_id().await,
                                                                 }),
                                                                 order: None,
                                                             }));
@@ -27232,6 +27293,7 @@ Have we met every part of this goal and is there no further work to do?"#
                                                             msg: EventMsg::BrowserScreenshotUpdate(BrowserScreenshotUpdateEvent {
                                                                 screenshot_path: first_path.clone(),
                                                                 url: url.unwrap_or_else(|| "Chrome".to_string()),
+                                                                tab_id: browser_manager.active_tab_id().await,
                                                             }),
                                                             order: None,
                                                         }));
@@ -27669,6 +27731,7 @@ Have we met every part of this goal and is there no further work to do?"#
                                                             BrowserScreenshotUpdateEvent {
                                                                 screenshot_path: first_path.clone(),
                                                                 url: url_inner,
+                                                                tab_id: browser_manager_inner.active_tab_id().await,
                                                             },
                                                         ),
                                                         order: None,
@@ -27726,6 +27789,7 @@ Have we met every part of this goal and is there no further work to do?"#
                                                 let _ = app_event_tx_inner.send(AppEvent::CodexEvent(Event { id: uuid::Uuid::new_v4().to_string(), event_seq: 0, msg: EventMsg::BrowserScreenshotUpdate(BrowserScreenshotUpdateEvent {
                                                         screenshot_path: first_path.clone(),
                                                         url: url_inner,
+                                                        tab_id: browser_manager.active_tab_id().await,
                                                     }), order: None }));
                                             }
                                         }
@@ -27781,6 +27845,7 @@ Have we met every part of this goal and is there no further work to do?"#
                                                 BrowserScreenshotUpdateEvent {
                                                     screenshot_path: first_path.clone(),
                                                     url: url.unwrap_or_else(|| result.url.clone()),
+                                                    tab_id: browser_manager.active_tab_id().await,
                                                 },
                                             ),
                                             order: None,
@@ -28540,6 +28605,7 @@ Have we met every part of this goal and is there no further work to do?"#
                             msg: EventMsg::BrowserScreenshotUpdate(BrowserScreenshotUpdateEvent {
                                 screenshot_path: first_path.clone(),
                                 url: url.unwrap_or_else(|| "Browser".to_string()),
+                                tab_id: browser_manager.active_tab_id().await,
                             }),
                             order: None,
                         }));
@@ -28730,6 +28796,7 @@ Have we met every part of this goal and is there no further work to do?"#
                                     BrowserScreenshotUpdateEvent {
                                         screenshot_path: first_path.clone(),
                                         url: url.unwrap_or_else(|| "Browser".to_string()),
+                                        tab_id: bm.active_tab_id().await,
                                     },
                                 ),
                                 order: None,
@@ -40291,8 +40358,15 @@ impl ChatWidget<'_> {
             .filter(|value| !value.is_empty())
             .unwrap_or_else(|| summary_label.clone());
 
+        let screenshot_tab = cell_opt.zip(
+            screenshot_history.and_then(|history| history.get(selected_index)),
+        )
+        .and_then(|(cell, record)| cell.tab_label_for(record));
         let screenshot_info = if screenshot_count > 0 {
-            format!("Shot {}/{}", selected_index + 1, screenshot_count)
+            match screenshot_tab {
+                Some(tab) => format!("Shot {}/{} · {}", selected_index + 1, screenshot_count, tab),
+                None => format!("Shot {}/{}", selected_index + 1, screenshot_count),
+            }
         } else {
             "No screenshots yet".to_string()
         };
@@ -307,6 +307,7 @@ pub(super) fn handle_screenshot_update(
     order: Option<&OrderMeta>,
     screenshot_path: &PathBuf,
     url: &str,
+    tab_id: Option<String>,
 ) -> BrowserScreenshotUpdateResult {
     let mut result = BrowserScreenshotUpdateResult {
         grouped: false,
@@ -345,7 +346,7 @@ pub(super) fn handle_screenshot_update(
 
     tracker
         .cell
-        .record_screenshot(relative, screenshot_path.clone(), Some(url.to_string()));
+        .record_screenshot(relative, screenshot_path.clone(), Some(url.to_string()), tab_id);
 
     ensure_cell_picker(chat, &tracker.cell);
     if tracker.slot.has_order_change() && !tracker.anchor_inserted {
@@ -471,7 +472,14 @@ fn summarize_action(
                 .and_then(|value| value.get("selector"))
                 .and_then(Value::as_str)
                 .map(|s| s.to_string());
-            summary.target = description.clone().or_else(|| selector.clone());
+            let element_ref = params
+                .and_then(|value| value.get("ref"))
+                .and_then(Value::as_str)
+                .map(|s| s.to_string());
+            summary.target = description
+                .clone()
+                .or_else(|| selector.clone())
+                .or(element_ref);
             if let (Some(_), Some(sel)) = (description.as_ref(), selector.as_ref()) {
                 summary.value = Some(sel.clone());
             }
@@ -510,11 +518,60 @@ fn summarize_action(
             }
             if let Some(selector) = params
                 .and_then(|value| value.get("selector"))
+                .or_else(|| params.and_then(|value| value.get("ref")))
                 .and_then(Value::as_str)
             {
                 summary.target = Some(selector.to_string());
             }
         }
+        "browser_select" => {
+            summary.target = params
+                .and_then(|value| value.get("ref"))
+                .and_then(Value::as_str)
+                .map(|s| s.to_string());
+            summary.value = params
+                .and_then(|value| value.get("values"))
+                .and_then(Value::as_array)
+                .map(|values| {
+                    values
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .filter(|joined| !joined.is_empty());
+        }
+        "browser_tabs" => {
+            summary.value = params
+                .and_then(|value| value.get("op"))
+                .and_then(Value::as_str)
+                .map(|s| s.to_string());
+            summary.target = params
+                .and_then(|value| value.get("tab_id"))
+                .or_else(|| params.and_then(|value| value.get("url")))
+                .and_then(Value::as_str)
+                .map(|s| s.to_string());
+        }
+        "browser_network" => {
+            summary.target = params
+                .and_then(|value| value.get("request_id"))
+                .or_else(|| params.and_then(|value| value.get("filter")))
+                .and_then(Value::as_str)
+                .map(|s| s.to_string());
+        }
+        "browser_compare" => {
+            summary.target = params
+                .and_then(|value| value.get("name"))
+                .and_then(Value::as_str)
+                .map(|s| s.to_string());
+            if params
+                .and_then(|value| value.get("accept"))
+                .and_then(Value::as_bool)
+                .unwrap_or(false)
+            {
+                summary.value = Some("accept".to_string());
+            }
+        }
         "browser_key" => {
             if let Some(key) = params
                 .and_then(|value| value.get("key"))
@@ -606,6 +663,11 @@ fn summarize_action_label(tool_name: &str) -> String {
         "browser_move" => "Move".to_string(),
         "browser_history" => "History".to_string(),
         "browser_console" => "Console".to_string(),
+        "browser_snapshot" => "Snapshot".to_string(),
+        "browser_select" => "Select".to_string(),
+        "browser_tabs" => "Tabs".to_string(),
+        "browser_network" => "Network".to_string(),
+        "browser_compare" => "Compare".to_string(),
         "browser_javascript" => "Script".to_string(),
         "browser_cdp" => "CDP".to_string(),
         "browser_status" => "Status".to_string(),
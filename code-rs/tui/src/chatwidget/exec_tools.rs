@@ -324,6 +324,7 @@ fn exec_record_from_begin(ev: &ExecCommandBeginEvent) -> ExecRecord {
         wait_notes: Vec::new(),
         started_at: std::time::SystemTime::now(),
         completed_at: None,
+        resource_usage: None,
         working_dir: Some(ev.cwd.clone()),
         env: Vec::new(),
         tags: Vec::new(),
@@ -589,6 +590,7 @@ pub(super) fn finalize_all_running_due_to_answer(chat: &mut ChatWidget<'_>) {
                     status: ExecStatus::Success,
                     exit_code: Some(exit_code),
                     completed_at: Some(now),
+                    resource_usage: None,
                     wait_total: running.wait_total,
                     wait_active: false,
                     wait_notes: wait_notes_record,
@@ -779,6 +781,7 @@ pub(super) fn finalize_wait_missing_exec(
             status: ExecStatus::Error,
             exit_code: Some(-1),
             completed_at: Some(SystemTime::now()),
+            resource_usage: None,
             wait_total,
             wait_active: false,
             wait_notes: wait_notes_record,
@@ -1432,6 +1435,7 @@ pub(super) fn handle_exec_end_now(
         duration,
         stdout,
         stderr,
+        resource_usage,
     } = ev;
     let cmd = chat
         .exec
@@ -1626,6 +1630,7 @@ pub(super) fn handle_exec_end_now(
             status,
             exit_code: Some(exit_code),
             completed_at: Some(now),
+            resource_usage,
             wait_total,
             wait_active: false,
             wait_notes: wait_notes_record,
@@ -1662,6 +1667,7 @@ pub(super) fn handle_exec_end_now(
             cell.set_wait_notes(&wait_notes_pairs);
             cell.set_waiting(false);
             cell.set_run_duration(Some(duration));
+            cell.record.resource_usage = resource_usage;
             if cell.record.call_id.as_deref().is_none() {
                 cell.record.call_id = Some(call_id.clone());
             }
@@ -221,6 +221,7 @@ fn run_refresh(
                 msg: EventMsg::TokenCount(TokenCountEvent {
                     info: None,
                     rate_limits: Some(snapshot),
+                    provider_usage: Default::default(),
                 }),
                 order: None,
             };
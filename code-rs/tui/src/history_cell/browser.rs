@@ -60,6 +60,8 @@ pub(crate) struct BrowserScreenshotRecord {
     pub path: PathBuf,
     pub url: Option<String>,
     pub timestamp: Duration,
+    /// CDP target id of the tab the screenshot came from
+    pub tab_id: Option<String>,
 }
 pub(crate) struct BrowserSessionCell {
     url: Option<String>,
@@ -238,6 +240,7 @@ impl BrowserSessionCell {
         timestamp: Duration,
         path: PathBuf,
         url: Option<String>,
+        tab_id: Option<String>,
     ) {
         let display_path = path.display().to_string();
         self.screenshot_path = Some(display_path);
@@ -245,6 +248,7 @@ impl BrowserSessionCell {
             path,
             url,
             timestamp,
+            tab_id,
         });
         if self.screenshot_history.len() > MAX_SCREENSHOT_HISTORY {
             let overflow = self.screenshot_history.len() - MAX_SCREENSHOT_HISTORY;
@@ -273,6 +277,23 @@ impl BrowserSessionCell {
         &self.screenshot_history
     }
 
+    /// Short tab label for a screenshot, only when this session spans several tabs.
+    pub(crate) fn tab_label_for(&self, record: &BrowserScreenshotRecord) -> Option<String> {
+        let tab_id = record.tab_id.as_deref()?;
+        let spans_tabs = self
+            .screenshot_history
+            .iter()
+            .filter_map(|shot| shot.tab_id.as_deref())
+            .any(|other| other != tab_id);
+        spans_tabs.then(|| format!("tab {}", code_browser::manager::short_tab_id(tab_id)))
+    }
+
+    fn latest_tab_label(&self) -> Option<String> {
+        self.screenshot_history
+            .last()
+            .and_then(|record| self.tab_label_for(record))
+    }
+
     pub(crate) fn total_duration(&self) -> Duration {
         self.total_duration
     }
@@ -327,6 +348,9 @@ impl BrowserSessionCell {
         };
 
         let mut title = format!("{}: {}", label, self.display_label());
+        if let Some(tab) = self.latest_tab_label() {
+            title.push_str(&format!(" · {}", tab));
+        }
         if let Some(code) = &self.status_code {
             title.push_str(&format!(" [{}]", code));
         }
@@ -999,7 +1023,10 @@ fn compute_screenshot_rows(&self, screenshot_cols: usize) -> Option<usize> {
             lines.push(format!("Action: {}", format_action_line(action)));
         }
         if let Some(path) = &self.screenshot_path {
-            lines.push(format!("Screenshot: {}", path));
+            match self.latest_tab_label() {
+                Some(tab) => lines.push(format!("Screenshot: {} ({})", path, tab)),
+                None => lines.push(format!("Screenshot: {}", path)),
+            }
         }
         lines
     }
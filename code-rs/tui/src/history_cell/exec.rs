@@ -523,7 +523,10 @@ impl ExecCell {
         }
         if let Some(run_duration) = state.run_duration {
             if run_duration >= Duration::from_secs(10) {
-                let text = format!("Ran for {}", format_duration(run_duration));
+                let mut text = format!("Ran for {}", format_duration(run_duration));
+                if let Some(usage) = self.record.resource_usage {
+                    text.push_str(&format!(" · {usage}"));
+                }
                 return Some(Line::styled(
                     text,
                     Style::default().fg(crate::colors::text_dim()),
@@ -858,6 +861,7 @@ fn build_exec_record(
                 wait_notes: Vec::new(),
                 started_at,
                 completed_at: Some(SystemTime::now()),
+                resource_usage: None,
                 working_dir: None,
                 env: Vec::new(),
                 tags: Vec::new(),
@@ -878,6 +882,7 @@ fn build_exec_record(
             wait_notes: Vec::new(),
             started_at,
             completed_at: None,
+            resource_usage: None,
             working_dir: None,
             env: Vec::new(),
             tags: Vec::new(),
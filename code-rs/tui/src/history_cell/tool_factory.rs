@@ -97,6 +97,11 @@ fn browser_running_title(tool_name: &str) -> &'static str {
         "browser_history" => "Navigating...",
         "browser_inspect" => "Inspecting...",
         "browser_console" => "Reading console...",
+        "browser_snapshot" => "Capturing snapshot...",
+        "browser_select" => "Selecting...",
+        "browser_tabs" => "Managing tabs...",
+        "browser_network" => "Reading network...",
+        "browser_compare" => "Comparing to baseline...",
         "browser_move" => "Moving...",
         _ => "Working...",
     }
@@ -754,6 +759,11 @@ fn browser_tool_title(tool_name: &str) -> &'static str {
         "browser_history" => "Browser History",
         "browser_inspect" => "Browser Inspect",
         "browser_console" => "Browser Console",
+        "browser_snapshot" => "Browser Snapshot",
+        "browser_select" => "Browser Select",
+        "browser_tabs" => "Browser Tabs",
+        "browser_network" => "Browser Network",
+        "browser_compare" => "Browser Compare",
         "browser_cdp" => "Browser CDP",
         "browser_move" => "Browser Move",
         _ => "Browser Tool",
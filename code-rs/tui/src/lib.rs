@@ -457,6 +457,7 @@ pub async fn run_main(
         experimental_client_tools: None,
         compact_prompt_override: cli.compact_prompt_override.clone(),
         compact_prompt_override_file: cli.compact_prompt_file.clone(),
+        model_cassette: None,
     };
 
     // Parse `-c` overrides from the CLI.
@@ -1322,9 +1323,14 @@ fn determine_repo_trust_state(
                 config.sandbox_policy = SandboxPolicy::WorkspaceWrite {
                     writable_roots: workspace_write.writable_roots.clone(),
                     network_access,
+                    network_allowlist: workspace_write.network_allowlist.clone(),
                     exclude_tmpdir_env_var: workspace_write.exclude_tmpdir_env_var,
                     exclude_slash_tmp: workspace_write.exclude_slash_tmp,
                     allow_git_writes: workspace_write.allow_git_writes,
+                    read_denied_paths: workspace_write
+                        .read_denied_paths
+                        .clone()
+                        .unwrap_or_else(code_core::protocol::default_read_denied_paths),
                 };
                 return Ok(false);
             }
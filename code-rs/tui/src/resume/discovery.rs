@@ -34,6 +34,10 @@ pub fn list_sessions_for_cwd(
         let query = SessionQuery {
             cwd: Some(cwd),
             git_root: None,
+            cwd_within: None,
+            git_branch: None,
+            updated_after: None,
+            updated_before: None,
             sources: vec![SessionSource::Cli, SessionSource::VSCode, SessionSource::Exec],
             min_user_messages: 1,
             include_archived: false,
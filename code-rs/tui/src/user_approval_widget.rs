@@ -10,6 +10,14 @@ use std::path::Path;
 use std::path::PathBuf;
 use code_core::protocol::Op;
 use code_core::protocol::ReviewDecision;
+use codex_execpolicy::ExecCall;
+use codex_execpolicy::ExecvChecker;
+use codex_execpolicy::Policy;
+use codex_execpolicy::ShellVerdict;
+use codex_execpolicy::default_code_home;
+use codex_execpolicy::get_policy_with_overlays;
+use codex_execpolicy::parse_shell;
+use codex_execpolicy::shell_segments;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyEventKind;
@@ -36,7 +44,13 @@ pub(crate) enum ApprovalRequest {
     Exec {
         id: String,
         command: Vec<String>,
+        /// Working directory of the command; project policy overlays are
+        /// looked up from here.
+        cwd: PathBuf,
         reason: Option<String>,
+        /// Set when approving reruns the command with this writable root
+        /// added for the session, after the sandbox denied it.
+        grant_root: Option<PathBuf>,
     },
     ApplyPatch {
         id: String,
@@ -66,6 +80,8 @@ enum SelectAction {
         persist: bool,
         semantic_prefix: Option<Vec<String>>,
     },
+    ToggleExplanation,
+    Deny,
     Abort,
 }
 
@@ -80,6 +96,10 @@ pub(crate) struct UserApprovalWidget<'a> {
     /// Currently selected index in *select* mode.
     selected_option: usize,
 
+    /// Execpolicy trace for exec requests, shown while set. Toggled by the
+    /// "Explain policy decision" option.
+    explanation: Option<Vec<String>>,
+
     /// Set to `true` once a decision has been sent – the parent view can then
     /// remove this widget from its queue.
     done: bool,
@@ -93,27 +113,11 @@ impl UserApprovalWidget<'_> {
     ) -> Self {
         let confirmation_prompt = match &approval_request {
             ApprovalRequest::Exec {
-                command, reason, ..
-            } => {
-                let cmd = strip_bash_lc_and_escape(command);
-                // Present a single-line summary without cwd: "codex wants to run: <cmd>"
-                let mut cmd_span: Span = cmd.clone().into();
-                cmd_span.style = cmd_span.style.add_modifier(Modifier::DIM);
-                let mut contents: Vec<Line> = vec![
-                    Line::from(""), // extra spacing above the prompt
-                    Line::from(vec![
-                        "? ".fg(crate::colors::info()),
-                        "Code wants to run ".bold(),
-                        cmd_span,
-                    ]),
-                    Line::from(""),
-                ];
-                if let Some(reason) = reason {
-                    contents.push(Line::from(reason.clone().italic()));
-                    contents.push(Line::from(""));
-                }
-                Paragraph::new(contents).wrap(Wrap { trim: false })
-            }
+                command,
+                reason,
+                grant_root,
+                ..
+            } => exec_confirmation_prompt(command, reason.as_deref(), grant_root.as_deref(), None),
             ApprovalRequest::ApplyPatch {
                 reason, grant_root, ..
             } => {
@@ -152,6 +156,10 @@ impl UserApprovalWidget<'_> {
         };
 
         let select_options = match &approval_request {
+            ApprovalRequest::Exec {
+                grant_root: Some(root),
+                ..
+            } => build_grant_root_select_options(root),
             ApprovalRequest::Exec { command, .. } => build_exec_select_options(command),
             ApprovalRequest::ApplyPatch { .. } => build_patch_select_options(),
             ApprovalRequest::TerminalCommand { .. } => build_terminal_select_options(),
@@ -164,6 +172,7 @@ impl UserApprovalWidget<'_> {
             confirmation_prompt,
             select_options,
             selected_option: 0,
+            explanation: None,
             done: false,
         }
     }
@@ -262,6 +271,21 @@ impl UserApprovalWidget<'_> {
 
         // Emit a background event instead of an assistant message.
         let message = match &self.approval_request {
+            ApprovalRequest::Exec {
+                grant_root: Some(root),
+                ..
+            } => match decision {
+                ReviewDecision::Approved | ReviewDecision::ApprovedForSession => {
+                    format!(
+                        "approved: write access to {} (this session)",
+                        root.display()
+                    )
+                }
+                ReviewDecision::Denied => {
+                    format!("not approved: write access to {}", root.display())
+                }
+                ReviewDecision::Abort => format!("canceled: write access to {}", root.display()),
+            },
             ApprovalRequest::Exec { command, .. } => {
                 let cmd = strip_bash_lc_and_escape(command);
                 match decision {
@@ -340,12 +364,40 @@ impl UserApprovalWidget<'_> {
                 });
                 self.send_decision(ReviewDecision::ApprovedForSession);
             }
+            SelectAction::ToggleExplanation => {
+                self.toggle_explanation();
+            }
+            SelectAction::Deny => {
+                self.send_decision(ReviewDecision::Denied);
+            }
             SelectAction::Abort => {
                 self.send_decision(ReviewDecision::Abort);
             }
         }
     }
 
+    fn toggle_explanation(&mut self) {
+        let ApprovalRequest::Exec {
+            command,
+            cwd,
+            reason,
+            grant_root,
+            ..
+        } = &self.approval_request
+        else {
+            return;
+        };
+        if self.explanation.take().is_none() {
+            self.explanation = Some(explain_exec_policy(command, cwd));
+        }
+        self.confirmation_prompt = exec_confirmation_prompt(
+            command,
+            reason.as_deref(),
+            grant_root.as_deref(),
+            self.explanation.as_deref(),
+        );
+    }
+
     /// Returns `true` once the user has made a decision and the widget no
     /// longer needs to be displayed.
     pub(crate) fn is_complete(&self) -> bool {
@@ -452,6 +504,13 @@ fn build_exec_select_options(command: &[String]) -> Vec<SelectOption> {
     }
     }
 
+    options.push(SelectOption {
+        label: "Explain policy decision".to_string(),
+        description: "Show how the command was checked against the execpolicy rules".to_string(),
+        hotkey: KeyCode::Char('e'),
+        action: SelectAction::ToggleExplanation,
+    });
+
     options.push(SelectOption {
         label: "No, provide feedback".to_string(),
         description: "Do not run the command; provide feedback".to_string(),
@@ -462,6 +521,133 @@ fn build_exec_select_options(command: &[String]) -> Vec<SelectOption> {
     options
 }
 
+fn exec_confirmation_prompt<'a>(
+    command: &[String],
+    reason: Option<&str>,
+    grant_root: Option<&Path>,
+    explanation: Option<&[String]>,
+) -> Paragraph<'a> {
+    let cmd = strip_bash_lc_and_escape(command);
+    // Present a single-line summary without cwd: "codex wants to run: <cmd>"
+    let mut cmd_span: Span = cmd.into();
+    cmd_span.style = cmd_span.style.add_modifier(Modifier::DIM);
+    let mut contents: Vec<Line> = vec![
+        Line::from(""), // extra spacing above the prompt
+        Line::from(vec![
+            "? ".fg(crate::colors::info()),
+            "Code wants to run ".bold(),
+            cmd_span,
+        ]),
+        Line::from(""),
+    ];
+    if let Some(reason) = reason {
+        contents.push(Line::from(reason.to_string().italic()));
+        contents.push(Line::from(""));
+    }
+    if let Some(root) = grant_root {
+        contents.push(Line::from(format!(
+            "This will grant write access to {} for the remainder of this session.",
+            root.display()
+        )));
+        contents.push(Line::from(""));
+    }
+    if let Some(explanation) = explanation {
+        let style = Style::default().fg(crate::colors::text_dim());
+        for line in explanation {
+            contents.push(Line::from(Span::styled(format!("  {line}"), style)));
+        }
+        contents.push(Line::from(""));
+    }
+    Paragraph::new(contents).wrap(Wrap { trim: false })
+}
+
+/// Trace the command through the default execpolicy plus any user and
+/// project overlays for `cwd`, for display in the approval modal. The policy
+/// is loaded on each call so edited overlays are picked up.
+fn explain_exec_policy(command: &[String], cwd: &Path) -> Vec<String> {
+    match get_policy_with_overlays(default_code_home().as_deref(), cwd) {
+        Ok(loaded) => explain_with_policy(loaded.policy, command, cwd),
+        Err(err) => vec![format!("execpolicy unavailable: {err}")],
+    }
+}
+
+fn explain_with_policy(policy: Policy, command: &[String], cwd: &Path) -> Vec<String> {
+    if command.len() == 3 && is_shell_wrapper(&command[0], &command[1]) {
+        return explain_shell_script(policy, &command[2], cwd);
+    }
+    let Some((program, args)) = command.split_first() else {
+        return vec!["no command to explain".to_string()];
+    };
+    let exec_call = ExecCall {
+        program: program.clone(),
+        args: args.to_vec(),
+    };
+    explanation_lines(&policy, &exec_call)
+}
+
+/// Trace every command of a `bash -lc` script, then give the verdict for the
+/// whole script with `cwd` as the readable and writeable folder.
+fn explain_shell_script(policy: Policy, script: &str, cwd: &Path) -> Vec<String> {
+    let Some(tree) = parse_shell(script) else {
+        return vec!["failed to parse the shell script".to_string()];
+    };
+    let mut lines = Vec::new();
+    if let Ok(segments) = shell_segments(&tree, script) {
+        for segment in &segments {
+            lines.extend(explanation_lines(&policy, &segment.exec_call));
+        }
+    }
+
+    let cwd = cwd.canonicalize().unwrap_or_else(|_| cwd.to_path_buf());
+    let folders = vec![cwd.clone()];
+    let verdict = ExecvChecker::new(policy).check_shell(
+        &tree,
+        script,
+        &Some(cwd.into_os_string()),
+        &folders,
+        &folders,
+    );
+    lines.push(match verdict {
+        ShellVerdict::Allowed { commands } => {
+            format!("script: allowed ({} commands)", commands.len())
+        }
+        ShellVerdict::Forbidden { segment, reason } => {
+            format!("script: forbidden at `{segment}`: {reason}")
+        }
+        ShellVerdict::Rejected { segment, error } => {
+            format!("script: rejected at `{segment}`: {error:?}")
+        }
+    });
+    lines
+}
+
+fn explanation_lines(policy: &Policy, exec_call: &ExecCall) -> Vec<String> {
+    policy
+        .explain(exec_call)
+        .to_string()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+fn build_grant_root_select_options(root: &Path) -> Vec<SelectOption> {
+    vec![
+        SelectOption {
+            label: format!("Yes, allow writes to {}", root.display()),
+            description: "Rerun the command in the sandbox with this directory writable"
+                .to_string(),
+            hotkey: KeyCode::Char('y'),
+            action: SelectAction::ApproveOnce,
+        },
+        SelectOption {
+            label: "No".to_string(),
+            description: "Keep the sandbox as is and report the failure".to_string(),
+            hotkey: KeyCode::Char('n'),
+            action: SelectAction::Deny,
+        },
+    ]
+}
+
 fn build_patch_select_options() -> Vec<SelectOption> {
     vec![
         SelectOption {
@@ -560,3 +746,43 @@ fn hotkey_suffix(key: KeyCode) -> String {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_execpolicy::get_default_policy;
+
+    fn explain(command: &[&str]) -> Vec<String> {
+        let policy = get_default_policy().unwrap_or_else(|e| panic!("{e}"));
+        let command: Vec<String> = command.iter().map(|s| (*s).to_string()).collect();
+        explain_with_policy(policy, &command, &std::env::temp_dir())
+    }
+
+    #[test]
+    fn shell_scripts_are_checked_command_by_command() {
+        let lines = explain(&["bash", "-lc", "pwd && ls"]);
+        assert_eq!(
+            lines
+                .iter()
+                .filter(|line| line.starts_with("command: "))
+                .count(),
+            2
+        );
+        assert_eq!(
+            lines.last().map(String::as_str),
+            Some("script: allowed (2 commands)")
+        );
+
+        let lines = explain(&["bash", "-lc", "ls && git push"]);
+        let verdict = lines.last().cloned().unwrap_or_default();
+        assert!(
+            verdict.starts_with("script: rejected at `git push`"),
+            "unexpected verdict: {verdict}"
+        );
+    }
+
+    #[test]
+    fn plain_commands_are_explained_directly() {
+        let lines = explain(&["ls", "-l"]);
+        assert_eq!(lines.last().map(String::as_str), Some("result: match"));
+    }
+}
@@ -54,6 +54,7 @@ fn exec_command_completes_properly() {
             stderr: String::new(),
             exit_code: 0,
             duration: Duration::from_millis(50),
+            resource_usage: None,
         }),
         order: Some(next_order_meta(1, &mut seq)),
     });
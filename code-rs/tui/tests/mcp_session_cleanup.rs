@@ -122,7 +122,7 @@ async fn mcp_stdio_server_exits_before_next_session() {
 
     let servers_map = config.mcp_servers.clone();
 
-    let (manager1, _) = McpConnectionManager::new(servers_map.clone(), HashSet::new())
+    let (manager1, _) = McpConnectionManager::new(servers_map.clone(), HashSet::new(), Default::default())
     .await
     .expect("start first MCP manager");
 
@@ -139,7 +139,7 @@ async fn mcp_stdio_server_exits_before_next_session() {
         std::fs::read_to_string(&log_path).unwrap_or_default()
     );
 
-    let (_manager2, _) = McpConnectionManager::new(servers_map, HashSet::new())
+    let (_manager2, _) = McpConnectionManager::new(servers_map, HashSet::new(), Default::default())
         .await
         .expect("start second MCP manager");
 }
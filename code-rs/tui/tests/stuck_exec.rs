@@ -72,6 +72,7 @@ fn exec_cell_clears_after_patch_flow() {
             stderr: String::new(),
             exit_code: 0,
             duration: Duration::from_millis(50),
+            resource_usage: None,
         }),
         order: Some(next_order_meta(1, &mut seq)),
     });
@@ -221,6 +222,7 @@ fn synthetic_end_clears_cancelled_exec_spinner() {
             stderr: "Command cancelled by user.".to_string(),
             exit_code: 130,
             duration: Duration::ZERO,
+            resource_usage: None,
         }),
         order: Some(next_order_meta(1, &mut seq)),
     });
@@ -337,6 +339,7 @@ fn wait_interrupt_after_exec_end_does_not_mutate_exec() {
             stderr: String::new(),
             exit_code: 0,
             duration: Duration::from_millis(5),
+            resource_usage: None,
         }),
         order: Some(next_order_meta(1, &mut seq)),
     });
@@ -415,6 +418,7 @@ fn wait_missing_job_skips_merged_exec() {
             stderr: String::new(),
             exit_code: 0,
             duration: Duration::from_millis(5),
+            resource_usage: None,
         }),
         order: Some(next_order_meta(1, &mut seq)),
     });
@@ -440,6 +444,7 @@ fn wait_missing_job_skips_merged_exec() {
             stderr: String::new(),
             exit_code: 0,
             duration: Duration::from_millis(5),
+            resource_usage: None,
         }),
         order: Some(next_order_meta(1, &mut seq)),
     });
@@ -515,6 +520,7 @@ fn exec_begin_upgrades_running_tool_cell() {
             stderr: String::new(),
             exit_code: 0,
             duration: Duration::from_millis(5),
+            resource_usage: None,
         }),
         order: Some(next_order_meta(1, &mut seq)),
     });
@@ -660,6 +666,7 @@ fn queued_exec_end_flushes_after_stream_clears() {
             stderr: String::new(),
             exit_code: 0,
             duration: Duration::from_millis(5),
+            resource_usage: None,
         }),
         order: Some(next_order_meta(1, &mut seq)),
     });
@@ -732,6 +739,7 @@ fn background_style_exec_end_with_zero_seq_does_not_get_stuck() {
             stderr: String::new(),
             exit_code: 0,
             duration: Duration::from_millis(5),
+            resource_usage: None,
         }),
         order: Some(next_order_meta(1, &mut seq)),
     });
@@ -462,6 +462,7 @@ fn smoke_exec_approval_event_structure() {
         command: vec!["echo".into(), "hi".into()],
         cwd: PathBuf::from("/tmp"),
         reason: Some("verify".into()),
+        grant_root: None,
     };
 
     let encoded = serde_json::to_string(&approval).expect("serialize exec approval");
@@ -487,6 +488,7 @@ fn smoke_exec_begin_end_consistency() {
         stderr: String::new(),
         exit_code: 0,
         duration: Duration::from_secs(2),
+        resource_usage: None,
     };
 
     assert_eq!(end.call_id, begin.call_id);
@@ -672,6 +674,7 @@ fn smoke_exec_command_stream() {
             stderr: String::new(),
             exit_code: 0,
             duration: Duration::from_millis(20),
+            resource_usage: None,
         }),
         order: Some(OrderMeta {
             request_ordinal: 1,
@@ -1536,6 +1536,7 @@ fn browser_session_grouped_desired_layout() {
         msg: EventMsg::BrowserScreenshotUpdate(BrowserScreenshotUpdateEvent {
             screenshot_path: PathBuf::from("/tmp/browser_session.png"),
             url: "https://example.com/docs".into(),
+            tab_id: None,
         }),
         order: Some(OrderMeta {
             request_ordinal: 1,